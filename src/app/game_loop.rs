@@ -0,0 +1,233 @@
+use std::time::Duration;
+
+/// What one call of [`GameLoop::advance`] asks the caller to do.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frame {
+    /// Fixed updates to run, each of [`GameLoop::timestep`].
+    pub steps: u32,
+    /// How far the render lies between the last two simulated states, in
+    /// `[0, 1)`.
+    pub alpha: f32,
+    /// Simulation time given up by the spiral-of-death guard.
+    pub dropped: Duration,
+}
+
+/// Fixed-timestep clock: wall time accumulates, and is spent in whole
+/// steps of simulation time, leaving a remainder for interpolation.
+///
+/// Long frames are clamped to [`max_frame_time`](Self::max_frame_time)
+/// and at most [`max_steps`](Self::max_steps) updates run per frame, so a
+/// simulation slower than real time falls behind instead of taking ever
+/// longer to catch up.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameLoop {
+    pub timestep: Duration,
+    pub max_steps: u32,
+    pub max_frame_time: Duration,
+    time_scale: f64,
+    paused: bool,
+    /// Steps requested by [`step`](Self::step) while paused.
+    queued: u32,
+    accumulator: Duration,
+    ticks: u64,
+}
+
+impl Default for GameLoop {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(1) / 60)
+    }
+}
+
+impl GameLoop {
+    pub fn new(timestep: Duration) -> Self {
+        assert!(!timestep.is_zero(), "timestep must be positive");
+
+        Self {
+            timestep,
+            max_steps: 8,
+            max_frame_time: Duration::from_millis(250),
+            time_scale: 1.0,
+            paused: false,
+            queued: 0,
+            accumulator: Duration::ZERO,
+            ticks: 0,
+        }
+    }
+
+    /// Fixed updates run so far.
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// Counts fixed updates run outside [`advance`](Self::advance).
+    pub fn add_ticks(&mut self, ticks: u64) {
+        self.ticks += ticks;
+    }
+
+    /// Simulated time: ticks times the timestep.
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_nanos((self.timestep.as_nanos() * self.ticks as u128) as u64)
+    }
+
+    pub fn time_scale(&self) -> f64 {
+        self.time_scale
+    }
+
+    /// Multiplies wall time before it is simulated: 0.5 is half speed.
+    /// Scaled time saturates at [`Duration::MAX`].
+    pub fn set_time_scale(&mut self, scale: f64) {
+        assert!(
+            scale >= 0.0 && scale.is_finite(),
+            "invalid time scale {scale}"
+        );
+        self.time_scale = scale;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Stops simulation time; rendering continues with a frozen alpha.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+        self.queued = 0;
+    }
+
+    /// Runs `steps` more fixed updates on the next frames while paused.
+    pub fn step(&mut self, steps: u32) {
+        self.queued = self.queued.saturating_add(steps);
+    }
+
+    /// Accounts for `elapsed` wall time since the previous frame.
+    pub fn advance(&mut self, elapsed: Duration) -> Frame {
+        if self.paused {
+            let steps = self.queued.min(self.max_steps);
+            self.queued -= steps;
+            self.ticks += steps as u64;
+
+            return Frame {
+                steps,
+                alpha: self.alpha(),
+                dropped: Duration::ZERO,
+            };
+        }
+
+        self.accumulator = self
+            .accumulator
+            .saturating_add(self.scaled(elapsed.min(self.max_frame_time)));
+        let mut steps = 0;
+        while self.accumulator >= self.timestep && steps < self.max_steps {
+            self.accumulator -= self.timestep;
+            steps += 1;
+        }
+
+        let mut dropped = self.scaled(elapsed.saturating_sub(self.max_frame_time));
+        if self.accumulator >= self.timestep {
+            // Keep the fraction for interpolation, give up whole steps.
+            let fraction = Duration::from_nanos(
+                (self.accumulator.as_nanos() % self.timestep.as_nanos()) as u64,
+            );
+            dropped = dropped.saturating_add(self.accumulator - fraction);
+            self.accumulator = fraction;
+        }
+        self.ticks += steps as u64;
+
+        return Frame {
+            steps,
+            alpha: self.alpha(),
+            dropped,
+        };
+    }
+
+    /// `duration` times the time scale, saturating instead of overflowing.
+    fn scaled(&self, duration: Duration) -> Duration {
+        Duration::try_from_secs_f64(duration.as_secs_f64() * self.time_scale)
+            .unwrap_or(Duration::MAX)
+    }
+
+    fn alpha(&self) -> f32 {
+        (self.accumulator.as_secs_f64() / self.timestep.as_secs_f64()) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn accumulate_test() {
+        let mut game_loop = GameLoop::new(ms(10));
+
+        let frame = game_loop.advance(ms(25));
+        assert_eq!(frame.steps, 2);
+        assert!((frame.alpha - 0.5).abs() < 1e-6);
+
+        let frame = game_loop.advance(ms(5));
+        assert_eq!(frame.steps, 1);
+        assert_eq!(frame.alpha, 0.0);
+        assert_eq!(game_loop.ticks(), 3);
+        assert_eq!(game_loop.elapsed(), ms(30));
+    }
+
+    #[test]
+    fn spiral_guard_test() {
+        let mut game_loop = GameLoop::new(ms(10));
+        game_loop.max_steps = 4;
+
+        // Clamped to 250 ms, of which 40 ms are simulated.
+        let frame = game_loop.advance(Duration::from_secs(2));
+        assert_eq!(frame.steps, 4);
+        assert_eq!(frame.dropped, Duration::from_secs(2) - ms(40));
+        assert_eq!(frame.alpha, 0.0);
+
+        let frame = game_loop.advance(ms(47));
+        assert_eq!((frame.steps, frame.dropped), (4, ms(0)));
+        assert!((frame.alpha - 0.7).abs() < 1e-4);
+    }
+
+    #[test]
+    fn controls_test() {
+        let mut game_loop = GameLoop::new(ms(10));
+        game_loop.advance(ms(15));
+
+        game_loop.pause();
+        assert_eq!(game_loop.advance(ms(100)).steps, 0);
+        game_loop.step(2);
+        let frame = game_loop.advance(ms(1));
+        assert_eq!(frame.steps, 2);
+        assert!((frame.alpha - 0.5).abs() < 1e-6);
+        assert_eq!(game_loop.advance(ms(1)).steps, 0);
+
+        game_loop.resume();
+        game_loop.set_time_scale(0.5);
+        assert_eq!(game_loop.advance(ms(10)).steps, 1);
+        assert_eq!(game_loop.ticks(), 4);
+    }
+
+    #[test]
+    fn overflow_test() {
+        let mut game_loop = GameLoop::new(ms(10));
+        game_loop.set_time_scale(f64::MAX);
+        let frame = game_loop.advance(Duration::MAX);
+        assert_eq!(frame.steps, 8);
+        assert_eq!(frame.dropped, Duration::MAX);
+        assert!(frame.alpha < 1.0);
+
+        let frame = game_loop.advance(ms(1));
+        assert_eq!(frame.steps, 8);
+        assert!(frame.alpha < 1.0);
+
+        game_loop.pause();
+        game_loop.step(u32::MAX);
+        game_loop.step(u32::MAX);
+        assert_eq!(game_loop.advance(ms(1)).steps, 8);
+    }
+}
@@ -0,0 +1,239 @@
+//! The engine loop.
+//!
+//! [`App`] owns an ECS [`World`] and two schedules: fixed-update systems
+//! advance the simulation in steps of constant length, and frame systems
+//! run once per rendered frame with the [`Time::alpha`] to interpolate
+//! between the last two simulated states. The stepping itself is done by
+//! [`GameLoop`].
+
+#![allow(clippy::needless_return)]
+
+pub mod game_loop;
+
+pub use game_loop::{Frame, GameLoop};
+
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::ecs::{IntoSystem, Schedule, SystemBuilder, World};
+
+/// Timing for the running systems, available as a resource.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Time {
+    /// In fixed updates the timestep, in frame systems the scaled wall
+    /// time since the last frame; in seconds.
+    pub delta: f32,
+    /// Simulated seconds.
+    pub elapsed: f64,
+    /// Fixed updates so far, including the one running.
+    pub tick: u64,
+    /// Interpolation factor for frame systems.
+    pub alpha: f32,
+}
+
+/// Insert as a resource to stop [`App::run`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AppExit;
+
+pub struct App {
+    world: World,
+    fixed: Schedule,
+    frame: Schedule,
+    game_loop: GameLoop,
+}
+
+impl Default for App {
+    fn default() -> Self {
+        Self::new(GameLoop::default())
+    }
+}
+
+impl App {
+    pub fn new(game_loop: GameLoop) -> Self {
+        let mut world = World::new();
+        world.insert_resource(Time::default());
+
+        Self {
+            world,
+            fixed: Schedule::new(),
+            frame: Schedule::new(),
+            game_loop,
+        }
+    }
+
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.world
+    }
+
+    pub fn game_loop(&self) -> &GameLoop {
+        &self.game_loop
+    }
+
+    /// Pause, single-step and time scale controls.
+    pub fn game_loop_mut(&mut self) -> &mut GameLoop {
+        &mut self.game_loop
+    }
+
+    /// Adds a system run every fixed update.
+    pub fn add_fixed_system<M>(&mut self, system: impl IntoSystem<M>) -> SystemBuilder<'_> {
+        self.fixed.add_system(system)
+    }
+
+    /// Adds a system run once per frame, after the fixed updates.
+    pub fn add_frame_system<M>(&mut self, system: impl IntoSystem<M>) -> SystemBuilder<'_> {
+        self.frame.add_system(system)
+    }
+
+    /// Runs the fixed updates `elapsed` wall time calls for, then the frame
+    /// systems.
+    pub fn update(&mut self, elapsed: Duration) -> Frame {
+        let first_tick = self.game_loop.ticks();
+        let frame = self.game_loop.advance(elapsed);
+        for step in 1..=frame.steps as u64 {
+            self.run_fixed(first_tick + step);
+        }
+
+        let mut time = self.time();
+        time.delta = (elapsed.as_secs_f64() * self.game_loop.time_scale()) as f32;
+        time.alpha = frame.alpha;
+        self.world.insert_resource(time);
+        self.frame.run(&mut self.world);
+
+        return frame;
+    }
+
+    /// Runs `ticks` fixed updates and no frame systems, regardless of the
+    /// pause state and time scale: a deterministic mode for tests and
+    /// servers.
+    pub fn run_headless(&mut self, ticks: u64) {
+        let first_tick = self.game_loop.ticks();
+        self.game_loop.add_ticks(ticks);
+
+        for step in 1..=ticks {
+            self.run_fixed(first_tick + step);
+        }
+    }
+
+    /// Runs frames in real time until a system inserts [`AppExit`],
+    /// sleeping when a frame finishes before `min_frame_time`.
+    pub fn run(&mut self, min_frame_time: Duration) {
+        let mut last = Instant::now();
+        while !self.world.contains_resource::<AppExit>() {
+            let now = Instant::now();
+            self.update(now - last);
+            last = now;
+
+            let spent = last.elapsed();
+            if spent < min_frame_time {
+                thread::sleep(min_frame_time - spent);
+            }
+        }
+    }
+
+    fn run_fixed(&mut self, tick: u64) {
+        let timestep = self.game_loop.timestep;
+        self.world.insert_resource(Time {
+            delta: timestep.as_secs_f32(),
+            elapsed: timestep.as_secs_f64() * tick as f64,
+            tick,
+            alpha: 0.0,
+        });
+        self.fixed.run(&mut self.world);
+    }
+
+    fn time(&self) -> Time {
+        *self.world.resource::<Time>().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ecs::{Commands, Query, Res, ResMut, Transform},
+        math::Vector3D,
+    };
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Previous(Vector3D);
+
+    #[derive(Default)]
+    struct Rendered(Vec<f32>);
+
+    fn movement(mut query: Query<(&mut Transform, &mut Previous)>, time: Res<Time>) {
+        query.for_each(|(mut transform, mut previous)| {
+            previous.0 = transform.position;
+            transform.position += Vector3D::create(1.0, 0.0, 0.0) * time.delta;
+        });
+    }
+
+    fn render(
+        mut query: Query<(&Transform, &Previous)>,
+        time: Res<Time>,
+        mut rendered: ResMut<Rendered>,
+    ) {
+        query.for_each(|(transform, previous)| {
+            let x = previous.0.x() + (transform.position.x() - previous.0.x()) * time.alpha;
+            rendered.0.push(x);
+        });
+    }
+
+    fn app() -> App {
+        let mut app = App::new(GameLoop::new(Duration::from_millis(100)));
+        app.world_mut().insert_resource(Rendered::default());
+        app.world_mut()
+            .spawn((Transform::default(), Previous(Vector3D::default())));
+        app.add_fixed_system(movement);
+        app.add_frame_system(render);
+
+        return app;
+    }
+
+    #[test]
+    fn interpolate_test() {
+        let mut app = app();
+        let frame = app.update(Duration::from_millis(250));
+        assert_eq!(frame.steps, 2);
+
+        // Halfway between x = 0.1 and x = 0.2.
+        let rendered = &app.world().resource::<Rendered>().unwrap().0;
+        assert!((rendered[0] - 0.15).abs() < 1e-5);
+        assert_eq!(app.world().resource::<Time>().unwrap().tick, 2);
+    }
+
+    #[test]
+    fn headless_test() {
+        let mut app = app();
+        app.game_loop_mut().pause();
+        app.run_headless(30);
+
+        let mut x = 0.0;
+        app.world()
+            .query::<&Transform>()
+            .for_each(|t| x = t.position.x());
+        assert!((x - 3.0).abs() < 1e-4);
+        assert_eq!(app.game_loop().ticks(), 30);
+        assert!(app.world().resource::<Rendered>().unwrap().0.is_empty());
+        assert!((app.world().resource::<Time>().unwrap().elapsed - 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn exit_test() {
+        fn quit(time: Res<Time>, mut commands: Commands) {
+            if time.tick >= 2 {
+                commands.insert_resource(AppExit);
+            }
+        }
+
+        let mut app = App::new(GameLoop::new(Duration::from_millis(1)));
+        app.add_fixed_system(quit);
+        app.run(Duration::from_millis(1));
+        assert!(app.game_loop().ticks() >= 2);
+    }
+}
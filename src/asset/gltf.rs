@@ -0,0 +1,2230 @@
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    math::{Matrix4x4F32, Quaternion, Vector2D, Vector3D},
+    scene::{SceneGraph, Transform},
+};
+
+use super::json::{self, ParseJsonError, Value};
+
+/// `glTF` in little endian.
+const GLB_MAGIC: u32 = 0x4654_6c67;
+const CHUNK_JSON: u32 = 0x4e4f_534a;
+const CHUNK_BIN: u32 = 0x004e_4942;
+
+const BYTE: u32 = 5120;
+const UNSIGNED_BYTE: u32 = 5121;
+const SHORT: u32 = 5122;
+const UNSIGNED_SHORT: u32 = 5123;
+const UNSIGNED_INT: u32 = 5125;
+const FLOAT: u32 = 5126;
+
+/// Most elements an accessor without a `bufferView` may declare, since
+/// nothing in the file backs its zero-filled storage.
+const MAX_ZERO_FILLED: usize = 1 << 24;
+
+#[derive(Debug)]
+pub enum GltfError {
+    Io {
+        path: PathBuf,
+        error: io::Error,
+    },
+    Json(ParseJsonError),
+    /// The binary container is malformed.
+    Glb(String),
+    /// A property is missing or has a bad value. `path` locates it, as in
+    /// `meshes[0].primitives[1].indices`.
+    Invalid {
+        path: String,
+        message: String,
+    },
+    /// An accessor's data does not fit its buffer, or does not have the
+    /// type its use requires.
+    Accessor {
+        index: usize,
+        message: String,
+    },
+    /// A feature or required extension the importer does not implement.
+    Unsupported(String),
+}
+
+impl Display for GltfError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            GltfError::Io { path, error } => write!(f, "{}: {error}", path.display()),
+            GltfError::Json(e) => write!(f, "invalid JSON: {e}"),
+            GltfError::Glb(message) => write!(f, "invalid GLB: {message}"),
+            GltfError::Invalid { path, message } => write!(f, "{path}: {message}"),
+            GltfError::Accessor { index, message } => write!(f, "accessors[{index}]: {message}"),
+            GltfError::Unsupported(what) => write!(f, "unsupported: {what}"),
+        }
+    }
+}
+
+impl Error for GltfError {}
+
+impl From<ParseJsonError> for GltfError {
+    fn from(e: ParseJsonError) -> Self {
+        GltfError::Json(e)
+    }
+}
+
+/// A triangle list with one material. Attributes other than `positions`
+/// are empty when the primitive does not have them. UVs keep glTF's
+/// top-left origin.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Primitive {
+    pub positions: Vec<Vector3D>,
+    pub normals: Vec<Vector3D>,
+    pub uvs: Vec<Vector2D>,
+    /// `xyz` is the tangent and `w` the handedness of the bitangent.
+    pub tangents: Vec<[f32; 4]>,
+    /// Indices into the skin's joints.
+    pub joints: Vec<[u16; 4]>,
+    pub weights: Vec<[f32; 4]>,
+    /// Counterclockwise triangles; strips and fans are converted.
+    pub indices: Vec<u32>,
+    pub material: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct GltfMesh {
+    pub name: String,
+    pub primitives: Vec<Primitive>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct GltfNode {
+    pub name: String,
+    /// Relative to the parent; a node matrix is decomposed.
+    pub transform: Transform,
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+    pub mesh: Option<usize>,
+    pub skin: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct GltfScene {
+    pub name: String,
+    /// Root nodes.
+    pub nodes: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AlphaMode {
+    Opaque,
+    /// Fully transparent below `cutoff`, opaque otherwise.
+    Mask {
+        cutoff: f32,
+    },
+    Blend,
+}
+
+/// An image and the texture coordinate set used to sample it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextureRef {
+    pub image: usize,
+    pub uv_set: usize,
+}
+
+/// A metallic-roughness material.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfMaterial {
+    pub name: String,
+    /// Linear RGBA.
+    pub base_color: [f32; 4],
+    pub metallic: f32,
+    pub roughness: f32,
+    pub emissive: Vector3D,
+    pub alpha_mode: AlphaMode,
+    pub double_sided: bool,
+    pub base_color_texture: Option<TextureRef>,
+    /// Roughness in green, metalness in blue.
+    pub metallic_roughness_texture: Option<TextureRef>,
+    pub normal_texture: Option<TextureRef>,
+    pub occlusion_texture: Option<TextureRef>,
+    pub emissive_texture: Option<TextureRef>,
+}
+
+impl Default for GltfMaterial {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            base_color: [1.0; 4],
+            metallic: 1.0,
+            roughness: 1.0,
+            emissive: Vector3D::default(),
+            alpha_mode: AlphaMode::Opaque,
+            double_sided: false,
+            base_color_texture: None,
+            metallic_roughness_texture: None,
+            normal_texture: None,
+            occlusion_texture: None,
+            emissive_texture: None,
+        }
+    }
+}
+
+/// Image data is left encoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GltfImage {
+    /// A file relative to the glTF file.
+    Uri(String),
+    /// A data URI or buffer view.
+    Embedded { mime_type: String, data: Vec<u8> },
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct GltfSkin {
+    pub name: String,
+    /// Nodes acting as joints, indexed by [`Primitive::joints`].
+    pub joints: Vec<usize>,
+    /// One per joint; identity when the file has none.
+    pub inverse_bind_matrices: Vec<Matrix4x4F32>,
+    pub skeleton: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interpolation {
+    Linear,
+    Step,
+    /// Each keyframe stores an in-tangent, a value and an out-tangent.
+    CubicSpline,
+}
+
+/// Keyframe values of a channel, in the layout its interpolation implies.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Keyframes {
+    Translation(Vec<Vector3D>),
+    Rotation(Vec<Quaternion>),
+    Scale(Vec<Vector3D>),
+    /// Morph target weights, all targets of a keyframe together.
+    Weights(Vec<f32>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfChannel {
+    pub node: usize,
+    pub interpolation: Interpolation,
+    /// Seconds, increasing.
+    pub times: Vec<f32>,
+    pub values: Keyframes,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct GltfAnimation {
+    pub name: String,
+    pub channels: Vec<GltfChannel>,
+}
+
+/// An imported glTF 2.0 asset. Cross references are indices into the
+/// vectors here and have been checked to be in range.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Gltf {
+    pub meshes: Vec<GltfMesh>,
+    pub nodes: Vec<GltfNode>,
+    pub scenes: Vec<GltfScene>,
+    /// The scene to show by default.
+    pub scene: Option<usize>,
+    pub materials: Vec<GltfMaterial>,
+    pub images: Vec<GltfImage>,
+    pub skins: Vec<GltfSkin>,
+    pub animations: Vec<GltfAnimation>,
+}
+
+impl Gltf {
+    /// The nodes of a scene as a [`SceneGraph`] whose data is the glTF
+    /// node index, or `None` if there is no such scene.
+    pub fn scene_graph(&self, scene: usize) -> Option<SceneGraph<usize>> {
+        let mut graph = SceneGraph::new();
+        let mut stack: Vec<_> = self
+            .scenes
+            .get(scene)?
+            .nodes
+            .iter()
+            .rev()
+            .map(|&node| (node, None))
+            .collect();
+
+        while let Some((node, parent)) = stack.pop() {
+            let transform = self.nodes[node].transform;
+            let id = match parent {
+                None => graph.add(transform, node),
+                Some(parent) => graph
+                    .add_child(parent, transform, node)
+                    .expect("parent was just added"),
+            };
+            stack.extend(
+                self.nodes[node]
+                    .children
+                    .iter()
+                    .rev()
+                    .map(|&c| (c, Some(id))),
+            );
+        }
+
+        return Some(graph);
+    }
+}
+
+/// Reads a `.gltf` or `.glb` file. External buffers are looked up beside
+/// it; external images are left as URIs.
+pub fn load(path: impl AsRef<Path>) -> Result<Gltf, GltfError> {
+    let path = path.as_ref();
+    let bytes = fs::read(path).map_err(|error| GltfError::Io {
+        path: path.to_path_buf(),
+        error,
+    })?;
+
+    return from_slice(&bytes, path.parent());
+}
+
+/// Parses glTF JSON or a GLB container. `base` is where external buffers
+/// are read from; without it only embedded data can be used.
+pub fn from_slice(bytes: &[u8], base: Option<&Path>) -> Result<Gltf, GltfError> {
+    let (text, bin) = if bytes.starts_with(&GLB_MAGIC.to_le_bytes()) {
+        split_glb(bytes)?
+    } else {
+        (bytes, None)
+    };
+    let text = std::str::from_utf8(text)
+        .map_err(|_| GltfError::Glb("the JSON chunk is not UTF-8".to_string()))?;
+    let root = json::parse(text.strip_prefix('\u{feff}').unwrap_or(text))?;
+
+    return Reader::new(&root, bin, base)?.read();
+}
+
+/// The JSON chunk and the binary chunk, if any.
+fn split_glb(bytes: &[u8]) -> Result<(&[u8], Option<&[u8]>), GltfError> {
+    let error = |message: &str| GltfError::Glb(message.to_string());
+    let word = |at: usize| -> Option<u32> {
+        let b = bytes.get(at..at + 4)?;
+        return Some(u32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+    };
+
+    let version = word(4).ok_or_else(|| error("truncated header"))?;
+    if version != 2 {
+        return Err(GltfError::Unsupported(format!("GLB version {version}")));
+    }
+    let length = word(8).ok_or_else(|| error("truncated header"))? as usize;
+    if length > bytes.len() {
+        return Err(error("file is shorter than its header says"));
+    }
+
+    let mut chunks = Vec::new();
+    let mut at = 12;
+    while at < length {
+        let (Some(size), Some(kind)) = (word(at), word(at + 4)) else {
+            return Err(error("truncated chunk header"));
+        };
+        let start = at + 8;
+        let end = start + size as usize;
+        if end > length {
+            return Err(error("chunk extends past the end of the file"));
+        }
+        chunks.push((kind, &bytes[start..end]));
+        at = end;
+    }
+
+    match chunks.as_slice() {
+        [(CHUNK_JSON, text)] => Ok((text, None)),
+        [(CHUNK_JSON, text), (CHUNK_BIN, bin), ..] => Ok((text, Some(bin))),
+        [(CHUNK_JSON, _), ..] => Err(error("the second chunk is not BIN")),
+        _ => Err(error("the first chunk is not JSON")),
+    }
+}
+
+fn invalid(path: impl Into<String>, message: impl Into<String>) -> GltfError {
+    GltfError::Invalid {
+        path: path.into(),
+        message: message.into(),
+    }
+}
+
+/// The location of member `key` of the object at `path`.
+fn field(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        return key.to_string();
+    }
+
+    return format!("{path}.{key}");
+}
+
+/// The array `key` of `object`, empty if absent.
+fn array<'a>(object: &'a Value, key: &str, path: &str) -> Result<&'a [Value], GltfError> {
+    match object.get(key) {
+        None => Ok(&[]),
+        Some(value) => value
+            .as_array()
+            .ok_or_else(|| invalid(field(path, key), "expected an array")),
+    }
+}
+
+/// An optional index into a top-level array with `count` elements.
+fn index(object: &Value, key: &str, path: &str, count: usize) -> Result<Option<usize>, GltfError> {
+    let Some(value) = object.get(key) else {
+        return Ok(None);
+    };
+
+    return match value.as_usize() {
+        Some(i) if i < count => Ok(Some(i)),
+        Some(i) => Err(invalid(
+            field(path, key),
+            format!("index {i} out of range; {count} defined"),
+        )),
+        None => Err(invalid(field(path, key), "expected an index")),
+    };
+}
+
+fn required_index(object: &Value, key: &str, path: &str, count: usize) -> Result<usize, GltfError> {
+    index(object, key, path, count)?.ok_or_else(|| invalid(field(path, key), "missing"))
+}
+
+fn number(object: &Value, key: &str, path: &str, default: f32) -> Result<f32, GltfError> {
+    match object.get(key) {
+        None => Ok(default),
+        Some(value) => value
+            .as_f64()
+            .map(|n| n as f32)
+            .ok_or_else(|| invalid(field(path, key), "expected a number")),
+    }
+}
+
+fn numbers<const N: usize>(
+    object: &Value,
+    key: &str,
+    path: &str,
+    default: [f32; N],
+) -> Result<[f32; N], GltfError> {
+    let Some(value) = object.get(key) else {
+        return Ok(default);
+    };
+
+    let error = || invalid(field(path, key), format!("expected {N} numbers"));
+    let items = value
+        .as_array()
+        .filter(|a| a.len() == N)
+        .ok_or_else(error)?;
+    let mut result = [0.0; N];
+    for (r, item) in result.iter_mut().zip(items) {
+        *r = item.as_f64().ok_or_else(error)? as f32;
+    }
+
+    return Ok(result);
+}
+
+fn string(object: &Value, key: &str, path: &str) -> Result<String, GltfError> {
+    match object.get(key) {
+        None => Ok(String::new()),
+        Some(value) => value
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| invalid(field(path, key), "expected a string")),
+    }
+}
+
+/// A column-major glTF matrix.
+fn matrix(m: [f32; 16]) -> Matrix4x4F32 {
+    return Matrix4x4F32::from(std::array::from_fn::<f32, 16, _>(|i| {
+        m[(i % 4) * 4 + i / 4]
+    }));
+}
+
+/// Accessor data as `count` elements of `width` components each.
+struct Elements {
+    width: usize,
+    values: Vec<f64>,
+}
+
+impl Elements {
+    fn count(&self) -> usize {
+        self.values.len() / self.width
+    }
+
+    fn chunks<const N: usize>(&self) -> impl Iterator<Item = [f32; N]> + '_ {
+        self.values
+            .chunks_exact(N)
+            .map(|c| std::array::from_fn(|i| c[i] as f32))
+    }
+}
+
+/// What a use of an accessor allows.
+struct Expect<'a> {
+    types: &'a [&'a str],
+    components: &'a [u32],
+    /// Whether integer components must be normalized, as for colors and
+    /// weights, or must not be, as for indices.
+    normalized: bool,
+}
+
+const VEC3_FLOAT: Expect = Expect {
+    types: &["VEC3"],
+    components: &[FLOAT],
+    normalized: false,
+};
+
+struct Reader<'a> {
+    root: &'a Value,
+    buffers: Vec<Vec<u8>>,
+    counts: Counts,
+}
+
+/// Sizes of the top-level arrays, for checking references.
+struct Counts {
+    accessors: usize,
+    buffer_views: usize,
+    images: usize,
+    materials: usize,
+    meshes: usize,
+    nodes: usize,
+    skins: usize,
+    textures: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(root: &'a Value, bin: Option<&[u8]>, base: Option<&Path>) -> Result<Self, GltfError> {
+        if root.as_object().is_none() {
+            return Err(invalid("", "the document is not an object"));
+        }
+        let version = root
+            .get("asset")
+            .and_then(|asset| asset.get("version"))
+            .and_then(Value::as_str)
+            .ok_or_else(|| invalid("asset.version", "missing"))?;
+        if !version.starts_with("2.") {
+            return Err(GltfError::Unsupported(format!("glTF version {version}")));
+        }
+        let required = array(root, "extensionsRequired", "")?;
+        if !required.is_empty() {
+            let names: Vec<&str> = required.iter().filter_map(Value::as_str).collect();
+            return Err(GltfError::Unsupported(format!(
+                "required extensions {}",
+                names.join(", ")
+            )));
+        }
+
+        let count = |key: &str| array(root, key, "").map(<[Value]>::len);
+        let counts = Counts {
+            accessors: count("accessors")?,
+            buffer_views: count("bufferViews")?,
+            images: count("images")?,
+            materials: count("materials")?,
+            meshes: count("meshes")?,
+            nodes: count("nodes")?,
+            skins: count("skins")?,
+            textures: count("textures")?,
+        };
+
+        let mut buffers = Vec::new();
+        for (i, buffer) in array(root, "buffers", "")?.iter().enumerate() {
+            let path = format!("buffers[{i}]");
+            let length = buffer
+                .get("byteLength")
+                .and_then(Value::as_usize)
+                .ok_or_else(|| invalid(format!("{path}.byteLength"), "missing"))?;
+
+            // The first buffer of a GLB file may be its binary chunk.
+            let mut data = match (buffer.get("uri").map(Value::as_str), bin) {
+                (None, Some(bin)) if i == 0 => bin.to_vec(),
+                (None, _) => return Err(invalid(format!("{path}.uri"), "missing")),
+                (Some(None), _) => return Err(invalid(format!("{path}.uri"), "expected a string")),
+                (Some(Some(uri)), _) => read_uri(uri, base, &format!("{path}.uri"))?.1,
+            };
+            if data.len() < length {
+                return Err(invalid(
+                    format!("{path}.byteLength"),
+                    format!("is {length} but the data has {} bytes", data.len()),
+                ));
+            }
+            data.truncate(length);
+            buffers.push(data);
+        }
+
+        return Ok(Self {
+            root,
+            buffers,
+            counts,
+        });
+    }
+
+    fn list(&self, key: &str) -> Result<&'a [Value], GltfError> {
+        array(self.root, key, "")
+    }
+
+    fn read(&self) -> Result<Gltf, GltfError> {
+        let mut gltf = Gltf {
+            images: self.images()?,
+            materials: self.materials()?,
+            meshes: self.meshes()?,
+            nodes: self.nodes()?,
+            skins: self.skins()?,
+            animations: self.animations()?,
+            ..Gltf::default()
+        };
+
+        for (i, scene) in self.list("scenes")?.iter().enumerate() {
+            let path = format!("scenes[{i}]");
+            let mut nodes = Vec::new();
+            for (j, _) in array(scene, "nodes", &path)?.iter().enumerate() {
+                let node = self.element_index(scene, "nodes", j, &path, self.counts.nodes)?;
+                if gltf.nodes[node].parent.is_some() {
+                    return Err(invalid(
+                        format!("{path}.nodes[{j}]"),
+                        format!("node {node} is not a root"),
+                    ));
+                }
+                nodes.push(node);
+            }
+            gltf.scenes.push(GltfScene {
+                name: string(scene, "name", &path)?,
+                nodes,
+            });
+        }
+        gltf.scene = index(self.root, "scene", "", gltf.scenes.len())?;
+
+        return Ok(gltf);
+    }
+
+    /// Element `j` of the index array `key`.
+    fn element_index(
+        &self,
+        object: &Value,
+        key: &str,
+        j: usize,
+        path: &str,
+        count: usize,
+    ) -> Result<usize, GltfError> {
+        let value = &array(object, key, path)?[j];
+        return match value.as_usize() {
+            Some(i) if i < count => Ok(i),
+            _ => Err(invalid(
+                format!("{path}.{key}[{j}]"),
+                format!("expected an index below {count}"),
+            )),
+        };
+    }
+
+    fn images(&self) -> Result<Vec<GltfImage>, GltfError> {
+        let mut images = Vec::new();
+
+        for (i, image) in self.list("images")?.iter().enumerate() {
+            let path = format!("images[{i}]");
+            let mime_type = string(image, "mimeType", &path)?;
+
+            let image = match (
+                image.get("uri"),
+                index(image, "bufferView", &path, self.counts.buffer_views)?,
+            ) {
+                (Some(uri), None) => {
+                    let uri = uri
+                        .as_str()
+                        .ok_or_else(|| invalid(format!("{path}.uri"), "expected a string"))?;
+                    if uri.starts_with("data:") {
+                        let (mime, data) = read_uri(uri, None, &format!("{path}.uri"))?;
+                        GltfImage::Embedded {
+                            mime_type: mime.unwrap_or(mime_type),
+                            data,
+                        }
+                    } else {
+                        GltfImage::Uri(uri.to_string())
+                    }
+                }
+                (None, Some(view)) => {
+                    if mime_type.is_empty() {
+                        return Err(invalid(format!("{path}.mimeType"), "missing"));
+                    }
+                    GltfImage::Embedded {
+                        mime_type,
+                        data: self.view_bytes(view)?.to_vec(),
+                    }
+                }
+                _ => return Err(invalid(path, "needs exactly one of uri and bufferView")),
+            };
+            images.push(image);
+        }
+
+        return Ok(images);
+    }
+
+    fn texture(&self, info: Option<&Value>, path: &str) -> Result<Option<TextureRef>, GltfError> {
+        let Some(info) = info else {
+            return Ok(None);
+        };
+
+        let texture = required_index(info, "index", path, self.counts.textures)?;
+        let uv_set = match info.get("texCoord") {
+            None => 0,
+            Some(n) => n
+                .as_usize()
+                .ok_or_else(|| invalid(format!("{path}.texCoord"), "expected an index"))?,
+        };
+
+        // A texture without a source relies on an extension for its image.
+        let path = format!("textures[{texture}]");
+        let texture = &self.list("textures")?[texture];
+        let image = index(texture, "source", &path, self.counts.images)?;
+
+        return Ok(image.map(|image| TextureRef { image, uv_set }));
+    }
+
+    fn materials(&self) -> Result<Vec<GltfMaterial>, GltfError> {
+        let mut materials = Vec::new();
+
+        for (i, material) in self.list("materials")?.iter().enumerate() {
+            let path = format!("materials[{i}]");
+            let pbr_path = format!("{path}.pbrMetallicRoughness");
+            let empty = Value::Null;
+            let pbr = material.get("pbrMetallicRoughness").unwrap_or(&empty);
+            let texture = |object: &Value, key: &str, path: &str| {
+                self.texture(object.get(key), &field(path, key))
+            };
+
+            let alpha_mode = match material.get("alphaMode").map(Value::as_str) {
+                None | Some(Some("OPAQUE")) => AlphaMode::Opaque,
+                Some(Some("MASK")) => AlphaMode::Mask {
+                    cutoff: number(material, "alphaCutoff", &path, 0.5)?,
+                },
+                Some(Some("BLEND")) => AlphaMode::Blend,
+                _ => {
+                    return Err(invalid(
+                        format!("{path}.alphaMode"),
+                        "expected OPAQUE, MASK or BLEND",
+                    ))
+                }
+            };
+
+            materials.push(GltfMaterial {
+                name: string(material, "name", &path)?,
+                base_color: numbers(pbr, "baseColorFactor", &pbr_path, [1.0; 4])?,
+                metallic: number(pbr, "metallicFactor", &pbr_path, 1.0)?,
+                roughness: number(pbr, "roughnessFactor", &pbr_path, 1.0)?,
+                emissive: numbers(material, "emissiveFactor", &path, [0.0; 3])?.into(),
+                alpha_mode,
+                double_sided: material
+                    .get("doubleSided")
+                    .map_or(Some(false), Value::as_bool)
+                    .ok_or_else(|| invalid(format!("{path}.doubleSided"), "expected a boolean"))?,
+                base_color_texture: texture(pbr, "baseColorTexture", &pbr_path)?,
+                metallic_roughness_texture: texture(pbr, "metallicRoughnessTexture", &pbr_path)?,
+                normal_texture: texture(material, "normalTexture", &path)?,
+                occlusion_texture: texture(material, "occlusionTexture", &path)?,
+                emissive_texture: texture(material, "emissiveTexture", &path)?,
+            });
+        }
+
+        return Ok(materials);
+    }
+
+    fn meshes(&self) -> Result<Vec<GltfMesh>, GltfError> {
+        let mut meshes = Vec::new();
+
+        for (i, mesh) in self.list("meshes")?.iter().enumerate() {
+            let path = format!("meshes[{i}]");
+            let primitives = array(mesh, "primitives", &path)?;
+            if primitives.is_empty() {
+                return Err(invalid(format!("{path}.primitives"), "missing"));
+            }
+
+            meshes.push(GltfMesh {
+                name: string(mesh, "name", &path)?,
+                primitives: primitives
+                    .iter()
+                    .enumerate()
+                    .map(|(j, p)| self.primitive(p, &format!("{path}.primitives[{j}]")))
+                    .collect::<Result<_, _>>()?,
+            });
+        }
+
+        return Ok(meshes);
+    }
+
+    fn primitive(&self, primitive: &Value, path: &str) -> Result<Primitive, GltfError> {
+        let attributes_path = format!("{path}.attributes");
+        let attributes = primitive
+            .get("attributes")
+            .filter(|a| a.as_object().is_some())
+            .ok_or_else(|| invalid(&attributes_path, "missing"))?;
+        let attribute = |name: &str, expect: &Expect| -> Result<Option<Elements>, GltfError> {
+            match index(attributes, name, &attributes_path, self.counts.accessors)? {
+                None => Ok(None),
+                Some(accessor) => self.accessor(accessor, expect).map(Some),
+            }
+        };
+        let unit = |width: &'static [&'static str]| Expect {
+            types: width,
+            components: &[FLOAT, UNSIGNED_BYTE, UNSIGNED_SHORT],
+            normalized: true,
+        };
+
+        let positions = attribute("POSITION", &VEC3_FLOAT)?
+            .ok_or_else(|| invalid(format!("{attributes_path}.POSITION"), "missing"))?;
+        let normals = attribute("NORMAL", &VEC3_FLOAT)?;
+        let uvs = attribute("TEXCOORD_0", &unit(&["VEC2"]))?;
+        let tangents = attribute(
+            "TANGENT",
+            &Expect {
+                types: &["VEC4"],
+                components: &[FLOAT],
+                normalized: false,
+            },
+        )?;
+        let joints = attribute(
+            "JOINTS_0",
+            &Expect {
+                types: &["VEC4"],
+                components: &[UNSIGNED_BYTE, UNSIGNED_SHORT],
+                normalized: false,
+            },
+        )?;
+        let weights = attribute("WEIGHTS_0", &unit(&["VEC4"]))?;
+
+        let count = positions.count();
+        for (name, elements) in [
+            ("NORMAL", &normals),
+            ("TEXCOORD_0", &uvs),
+            ("TANGENT", &tangents),
+            ("JOINTS_0", &joints),
+            ("WEIGHTS_0", &weights),
+        ] {
+            if elements.as_ref().is_some_and(|e| e.count() != count) {
+                return Err(invalid(
+                    format!("{attributes_path}.{name}"),
+                    format!("count differs from the {count} positions"),
+                ));
+            }
+        }
+
+        let vertices: Vec<u32> = match index(primitive, "indices", path, self.counts.accessors)? {
+            None => (0..count as u32).collect(),
+            Some(accessor) => {
+                let indices = self.accessor(
+                    accessor,
+                    &Expect {
+                        types: &["SCALAR"],
+                        components: &[UNSIGNED_BYTE, UNSIGNED_SHORT, UNSIGNED_INT],
+                        normalized: false,
+                    },
+                )?;
+                if let Some(&i) = indices.values.iter().find(|&&i| i as usize >= count) {
+                    return Err(invalid(
+                        format!("{path}.indices"),
+                        format!("index {i} out of range; {count} vertices"),
+                    ));
+                }
+                indices.values.iter().map(|&i| i as u32).collect()
+            }
+        };
+
+        let mode = match primitive.get("mode") {
+            None => 4,
+            Some(mode) => mode
+                .as_usize()
+                .ok_or_else(|| invalid(format!("{path}.mode"), "expected a number"))?,
+        };
+        let indices = match mode {
+            4 if vertices.len().is_multiple_of(3) => vertices,
+            4 => {
+                return Err(invalid(
+                    path,
+                    format!("{} indices do not form triangles", vertices.len()),
+                ))
+            }
+            // Every other triangle of a strip is flipped to keep the winding.
+            5 => (2..vertices.len())
+                .flat_map(|i| match i % 2 {
+                    0 => [vertices[i - 2], vertices[i - 1], vertices[i]],
+                    _ => [vertices[i - 1], vertices[i - 2], vertices[i]],
+                })
+                .collect(),
+            6 => (2..vertices.len())
+                .flat_map(|i| [vertices[0], vertices[i - 1], vertices[i]])
+                .collect(),
+            0..=3 => return Err(GltfError::Unsupported(format!("{path}: points and lines"))),
+            _ => {
+                return Err(invalid(
+                    format!("{path}.mode"),
+                    format!("unknown mode {mode}"),
+                ))
+            }
+        };
+
+        return Ok(Primitive {
+            positions: positions.chunks::<3>().map(Vector3D::from).collect(),
+            normals: normals.map_or(vec![], |n| n.chunks::<3>().map(Vector3D::from).collect()),
+            uvs: uvs.map_or(vec![], |uv| uv.chunks::<2>().map(Vector2D::from).collect()),
+            tangents: tangents.map_or(vec![], |t| t.chunks().collect()),
+            joints: joints.map_or(vec![], |j| {
+                j.values
+                    .chunks_exact(4)
+                    .map(|c| std::array::from_fn(|i| c[i] as u16))
+                    .collect()
+            }),
+            weights: weights.map_or(vec![], |w| w.chunks().collect()),
+            indices,
+            material: index(primitive, "material", path, self.counts.materials)?,
+        });
+    }
+
+    fn nodes(&self) -> Result<Vec<GltfNode>, GltfError> {
+        let list = self.list("nodes")?;
+        let mut nodes = Vec::new();
+
+        for (i, node) in list.iter().enumerate() {
+            let path = format!("nodes[{i}]");
+            let transform = if node.get("matrix").is_some() {
+                Transform::from_matrix(&matrix(numbers(node, "matrix", &path, [0.0; 16])?))
+            } else {
+                let [x, y, z, w] = numbers(node, "rotation", &path, [0.0, 0.0, 0.0, 1.0])?;
+                Transform {
+                    translation: numbers(node, "translation", &path, [0.0; 3])?.into(),
+                    rotation: Quaternion::create(x, y, z, w).normalize(),
+                    scale: numbers(node, "scale", &path, [1.0; 3])?.into(),
+                }
+            };
+
+            let children = (0..array(node, "children", &path)?.len())
+                .map(|j| self.element_index(node, "children", j, &path, list.len()))
+                .collect::<Result<_, _>>()?;
+
+            nodes.push(GltfNode {
+                name: string(node, "name", &path)?,
+                transform,
+                parent: None,
+                children,
+                mesh: index(node, "mesh", &path, self.counts.meshes)?,
+                skin: index(node, "skin", &path, self.counts.skins)?,
+            });
+        }
+
+        for i in 0..nodes.len() {
+            for j in 0..nodes[i].children.len() {
+                let child = nodes[i].children[j];
+                if nodes[child].parent.is_some() {
+                    return Err(invalid(
+                        format!("nodes[{i}].children[{j}]"),
+                        format!("node {child} has more than one parent"),
+                    ));
+                }
+                nodes[child].parent = Some(i);
+            }
+        }
+
+        // With single parents, a node is in a cycle if walking up from it
+        // takes more steps than there are nodes.
+        for i in 0..nodes.len() {
+            let mut node = i;
+            for _ in 0..=nodes.len() {
+                match nodes[node].parent {
+                    Some(parent) => node = parent,
+                    None => break,
+                }
+            }
+            if nodes[node].parent.is_some() {
+                return Err(invalid(format!("nodes[{i}]"), "is its own ancestor"));
+            }
+        }
+
+        return Ok(nodes);
+    }
+
+    fn skins(&self) -> Result<Vec<GltfSkin>, GltfError> {
+        let mut skins = Vec::new();
+
+        for (i, skin) in self.list("skins")?.iter().enumerate() {
+            let path = format!("skins[{i}]");
+            let joints: Vec<usize> = (0..array(skin, "joints", &path)?.len())
+                .map(|j| self.element_index(skin, "joints", j, &path, self.counts.nodes))
+                .collect::<Result<_, _>>()?;
+            if joints.is_empty() {
+                return Err(invalid(format!("{path}.joints"), "missing"));
+            }
+
+            let inverse_bind_matrices =
+                match index(skin, "inverseBindMatrices", &path, self.counts.accessors)? {
+                    None => vec![Matrix4x4F32::identity(); joints.len()],
+                    Some(accessor) => {
+                        let elements = self.accessor(
+                            accessor,
+                            &Expect {
+                                types: &["MAT4"],
+                                components: &[FLOAT],
+                                normalized: false,
+                            },
+                        )?;
+                        if elements.count() < joints.len() {
+                            return Err(invalid(
+                                format!("{path}.inverseBindMatrices"),
+                                format!(
+                                    "{} matrices for {} joints",
+                                    elements.count(),
+                                    joints.len()
+                                ),
+                            ));
+                        }
+                        elements.chunks().take(joints.len()).map(matrix).collect()
+                    }
+                };
+
+            skins.push(GltfSkin {
+                name: string(skin, "name", &path)?,
+                joints,
+                inverse_bind_matrices,
+                skeleton: index(skin, "skeleton", &path, self.counts.nodes)?,
+            });
+        }
+
+        return Ok(skins);
+    }
+
+    fn animations(&self) -> Result<Vec<GltfAnimation>, GltfError> {
+        let mut animations = Vec::new();
+
+        for (i, animation) in self.list("animations")?.iter().enumerate() {
+            let path = format!("animations[{i}]");
+            let samplers = array(animation, "samplers", &path)?;
+            let mut channels = Vec::new();
+
+            for (j, channel) in array(animation, "channels", &path)?.iter().enumerate() {
+                let channel_path = format!("{path}.channels[{j}]");
+                let sampler = required_index(channel, "sampler", &channel_path, samplers.len())?;
+                let target = channel
+                    .get("target")
+                    .ok_or_else(|| invalid(format!("{channel_path}.target"), "missing"))?;
+                let target_path = format!("{channel_path}.target");
+                // Targets without a node are for extensions.
+                let Some(node) = index(target, "node", &target_path, self.counts.nodes)? else {
+                    continue;
+                };
+                let property = target.get("path").and_then(Value::as_str).unwrap_or("");
+
+                let sampler_path = format!("{path}.samplers[{sampler}]");
+                channels.push(self.channel(
+                    &samplers[sampler],
+                    &sampler_path,
+                    node,
+                    property,
+                    &target_path,
+                )?);
+            }
+
+            animations.push(GltfAnimation {
+                name: string(animation, "name", &path)?,
+                channels,
+            });
+        }
+
+        return Ok(animations);
+    }
+
+    fn channel(
+        &self,
+        sampler: &Value,
+        path: &str,
+        node: usize,
+        property: &str,
+        target_path: &str,
+    ) -> Result<GltfChannel, GltfError> {
+        let interpolation = match sampler.get("interpolation").map(Value::as_str) {
+            None | Some(Some("LINEAR")) => Interpolation::Linear,
+            Some(Some("STEP")) => Interpolation::Step,
+            Some(Some("CUBICSPLINE")) => Interpolation::CubicSpline,
+            _ => {
+                return Err(invalid(
+                    format!("{path}.interpolation"),
+                    "expected LINEAR, STEP or CUBICSPLINE",
+                ))
+            }
+        };
+
+        let input = required_index(sampler, "input", path, self.counts.accessors)?;
+        let times: Vec<f32> = self
+            .accessor(
+                input,
+                &Expect {
+                    types: &["SCALAR"],
+                    components: &[FLOAT],
+                    normalized: false,
+                },
+            )?
+            .chunks::<1>()
+            .map(|[t]| t)
+            .collect();
+        if times.windows(2).any(|w| w[0] >= w[1]) {
+            return Err(GltfError::Accessor {
+                index: input,
+                message: "keyframe times do not increase".to_string(),
+            });
+        }
+
+        let output = required_index(sampler, "output", path, self.counts.accessors)?;
+        let expect = match property {
+            "translation" | "scale" => VEC3_FLOAT,
+            "rotation" => Expect {
+                types: &["VEC4"],
+                components: &[FLOAT, BYTE, UNSIGNED_BYTE, SHORT, UNSIGNED_SHORT],
+                normalized: true,
+            },
+            "weights" => Expect {
+                types: &["SCALAR"],
+                components: &[FLOAT, BYTE, UNSIGNED_BYTE, SHORT, UNSIGNED_SHORT],
+                normalized: true,
+            },
+            _ => {
+                return Err(invalid(
+                    format!("{target_path}.path"),
+                    "expected translation, rotation, scale or weights",
+                ))
+            }
+        };
+        let elements = self.accessor(output, &expect)?;
+
+        let per_key = if interpolation == Interpolation::CubicSpline {
+            3
+        } else {
+            1
+        };
+        let count = elements.count();
+        let matches = match property {
+            "weights" => !times.is_empty() && count.is_multiple_of(times.len() * per_key),
+            _ => count == times.len() * per_key,
+        };
+        if !matches {
+            return Err(GltfError::Accessor {
+                index: output,
+                message: format!("{count} values for {} keyframes", times.len()),
+            });
+        }
+
+        let values = match property {
+            "translation" => {
+                Keyframes::Translation(elements.chunks::<3>().map(Vector3D::from).collect())
+            }
+            "scale" => Keyframes::Scale(elements.chunks::<3>().map(Vector3D::from).collect()),
+            "rotation" => Keyframes::Rotation(
+                elements
+                    .chunks()
+                    .map(|[x, y, z, w]| Quaternion::create(x, y, z, w))
+                    .collect(),
+            ),
+            _ => Keyframes::Weights(elements.chunks::<1>().map(|[w]| w).collect()),
+        };
+
+        return Ok(GltfChannel {
+            node,
+            interpolation,
+            times,
+            values,
+        });
+    }
+
+    /// The bytes of a buffer view, checked against its buffer.
+    fn view_bytes(&self, view: usize) -> Result<&[u8], GltfError> {
+        let path = format!("bufferViews[{view}]");
+        let object = &self.list("bufferViews")?[view];
+        let buffer = required_index(object, "buffer", &path, self.buffers.len())?;
+        let offset = object.get("byteOffset").map_or(Some(0), Value::as_usize);
+        let length = object.get("byteLength").and_then(Value::as_usize);
+        let (Some(offset), Some(length)) = (offset, length) else {
+            return Err(invalid(path, "needs a byteLength and a valid byteOffset"));
+        };
+
+        let data = &self.buffers[buffer];
+        let Some(end) = offset.checked_add(length).filter(|&end| end <= data.len()) else {
+            return Err(invalid(
+                path,
+                format!(
+                    "bytes {offset}..{} are outside buffer {buffer} of {} bytes",
+                    offset as u128 + length as u128,
+                    data.len()
+                ),
+            ));
+        };
+
+        return Ok(&data[offset..end]);
+    }
+
+    /// Reads and validates accessor `index` for a use allowing `expect`.
+    fn accessor(&self, index: usize, expect: &Expect) -> Result<Elements, GltfError> {
+        let error = |message: String| GltfError::Accessor { index, message };
+        let object = &self.list("accessors")?[index];
+
+        let kind = object.get("type").and_then(Value::as_str).unwrap_or("");
+        let width = match kind {
+            "SCALAR" => 1,
+            "VEC2" => 2,
+            "VEC3" => 3,
+            "VEC4" | "MAT2" => 4,
+            "MAT3" => 9,
+            "MAT4" => 16,
+            _ => return Err(error(format!("unknown type {kind:?}"))),
+        };
+        if !expect.types.contains(&kind) {
+            return Err(error(format!(
+                "is {kind} where {} is expected",
+                expect.types.join(" or ")
+            )));
+        }
+
+        let component = object
+            .get("componentType")
+            .and_then(Value::as_usize)
+            .unwrap_or(0) as u32;
+        if !(BYTE..=FLOAT).contains(&component) || component == 5124 {
+            return Err(error(format!("unknown component type {component}")));
+        }
+        if !expect.components.contains(&component) {
+            return Err(error(format!(
+                "component type {component} is not allowed here"
+            )));
+        }
+        let normalized = object
+            .get("normalized")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        if component != FLOAT && normalized != expect.normalized {
+            let not = if expect.normalized { "" } else { "not " };
+            return Err(error(format!("integer components must {not}be normalized")));
+        }
+
+        let count = object
+            .get("count")
+            .and_then(Value::as_usize)
+            .filter(|&c| c > 0)
+            .ok_or_else(|| error("count must be positive".to_string()))?;
+        let offset = object
+            .get("byteOffset")
+            .map_or(Some(0), Value::as_usize)
+            .ok_or_else(|| error("invalid byteOffset".to_string()))?;
+
+        let layout = Layout {
+            width,
+            component,
+            normalized,
+        };
+        let mut values = match self::index(
+            object,
+            "bufferView",
+            &format!("accessors[{index}]"),
+            self.counts.buffer_views,
+        )? {
+            None if count > MAX_ZERO_FILLED => {
+                return Err(error(format!(
+                    "count {count} is too large without a bufferView"
+                )));
+            }
+            None => vec![0.0; count * width],
+            Some(view) => {
+                let stride = self.list("bufferViews")?[view]
+                    .get("byteStride")
+                    .and_then(Value::as_usize);
+                elements(self.view_bytes(view)?, view, offset, count, stride, layout)
+                    .map_err(error)?
+            }
+        };
+
+        if let Some(sparse) = object.get("sparse") {
+            self.sparse(sparse, index, count, layout, &mut values)?;
+        }
+
+        return Ok(Elements { width, values });
+    }
+
+    /// Applies an accessor's sparse substitutions.
+    fn sparse(
+        &self,
+        sparse: &Value,
+        index: usize,
+        count: usize,
+        layout: Layout,
+        values: &mut [f64],
+    ) -> Result<(), GltfError> {
+        let error = |message: String| GltfError::Accessor { index, message };
+        let path = format!("accessors[{index}].sparse");
+        let (Some(n), Some(indices), Some(replacements)) = (
+            sparse
+                .get("count")
+                .and_then(Value::as_usize)
+                .filter(|&n| n > 0),
+            sparse.get("indices"),
+            sparse.get("values"),
+        ) else {
+            return Err(invalid(path, "needs count, indices and values"));
+        };
+        if n > count {
+            return Err(error(format!("sparse count {n} exceeds count {count}")));
+        }
+
+        let part = |object: &Value, layout: Layout, what: &str| -> Result<Vec<f64>, GltfError> {
+            let path = format!("{path}.{what}");
+            let view = required_index(object, "bufferView", &path, self.counts.buffer_views)?;
+            let offset = object
+                .get("byteOffset")
+                .map_or(Some(0), Value::as_usize)
+                .ok_or_else(|| invalid(format!("{path}.byteOffset"), "expected a number"))?;
+            return elements(self.view_bytes(view)?, view, offset, n, None, layout)
+                .map_err(|m| error(format!("sparse {what}: {m}")));
+        };
+
+        let component = indices
+            .get("componentType")
+            .and_then(Value::as_usize)
+            .unwrap_or(0) as u32;
+        if ![UNSIGNED_BYTE, UNSIGNED_SHORT, UNSIGNED_INT].contains(&component) {
+            return Err(error(format!("sparse index component type {component}")));
+        }
+        let targets = part(
+            indices,
+            Layout {
+                width: 1,
+                component,
+                normalized: false,
+            },
+            "indices",
+        )?;
+        let replacements = part(replacements, layout, "values")?;
+
+        let width = layout.width;
+        for (k, &target) in targets.iter().enumerate() {
+            let target = target as usize;
+            if target >= count {
+                return Err(error(format!("sparse index {target} out of range")));
+            }
+            values[target * width..(target + 1) * width]
+                .copy_from_slice(&replacements[k * width..(k + 1) * width]);
+        }
+
+        return Ok(());
+    }
+}
+
+/// Decodes `count` elements from the bytes of buffer view `view`.
+fn elements(
+    bytes: &[u8],
+    view: usize,
+    offset: usize,
+    count: usize,
+    stride: Option<usize>,
+    layout: Layout,
+) -> Result<Vec<f64>, String> {
+    let size = component_size(layout.component);
+    let element = size * layout.width;
+    let stride = stride.unwrap_or(element);
+    let too_short = |needed: String| {
+        format!(
+            "needs {needed} bytes of buffer view {view}, which has {}",
+            bytes.len()
+        )
+    };
+    let overflow = || too_short(format!("more than {}", usize::MAX));
+
+    // Counts the view cannot possibly hold are rejected before any
+    // arithmetic on them.
+    match count.checked_mul(element) {
+        None => return Err(overflow()),
+        Some(needed) if needed > bytes.len() => return Err(too_short(needed.to_string())),
+        Some(_) => {}
+    }
+
+    if !offset.is_multiple_of(size) {
+        return Err(format!("byteOffset {offset} is not a multiple of {size}"));
+    }
+    if stride < element || !stride.is_multiple_of(size) {
+        return Err(format!(
+            "byteStride {stride} does not fit {element}-byte elements"
+        ));
+    }
+    let end = stride
+        .checked_mul(count - 1)
+        .and_then(|n| n.checked_add(offset))
+        .and_then(|n| n.checked_add(element))
+        .ok_or_else(overflow)?;
+    if end > bytes.len() {
+        return Err(too_short(end.to_string()));
+    }
+
+    let mut values = Vec::with_capacity(count * layout.width);
+    for i in 0..count {
+        let start = offset + i * stride;
+        for c in 0..layout.width {
+            let at = start + c * size;
+            values.push(decode(&bytes[at..at + size], layout));
+        }
+    }
+
+    return Ok(values);
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Layout {
+    width: usize,
+    component: u32,
+    normalized: bool,
+}
+
+fn component_size(component: u32) -> usize {
+    match component {
+        BYTE | UNSIGNED_BYTE => 1,
+        SHORT | UNSIGNED_SHORT => 2,
+        _ => 4,
+    }
+}
+
+/// One component, mapping normalized integers to `[0, 1]` or `[-1, 1]`.
+fn decode(bytes: &[u8], layout: Layout) -> f64 {
+    let (value, max) = match layout.component {
+        BYTE => (bytes[0] as i8 as f64, 127.0),
+        UNSIGNED_BYTE => (bytes[0] as f64, 255.0),
+        SHORT => (i16::from_le_bytes([bytes[0], bytes[1]]) as f64, 32767.0),
+        UNSIGNED_SHORT => (u16::from_le_bytes([bytes[0], bytes[1]]) as f64, 65535.0),
+        UNSIGNED_INT => (
+            u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+            1.0,
+        ),
+        _ => {
+            let f = f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            return f as f64;
+        }
+    };
+
+    if layout.normalized {
+        return (value / max).max(-1.0);
+    }
+
+    return value;
+}
+
+/// The media type, if given, and the bytes of a data URI, or of a file
+/// relative to `base`.
+fn read_uri(
+    uri: &str,
+    base: Option<&Path>,
+    path: &str,
+) -> Result<(Option<String>, Vec<u8>), GltfError> {
+    if let Some(data) = uri.strip_prefix("data:") {
+        let (header, payload) = data
+            .split_once(',')
+            .ok_or_else(|| invalid(path, "data URI without a comma"))?;
+        let Some(mime) = header.strip_suffix(";base64") else {
+            return Err(GltfError::Unsupported(format!(
+                "{path}: data URI that is not base64"
+            )));
+        };
+        let bytes = base64(payload).ok_or_else(|| invalid(path, "invalid base64 data"))?;
+
+        return Ok(((!mime.is_empty()).then(|| mime.to_string()), bytes));
+    }
+
+    let Some(base) = base else {
+        return Err(invalid(path, "external file without a base directory"));
+    };
+    let file = base.join(percent_decode(uri));
+    let bytes = fs::read(&file).map_err(|error| GltfError::Io { path: file, error })?;
+
+    return Ok((None, bytes));
+}
+
+/// Decodes `%XX` escapes in a relative URI.
+fn percent_decode(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| uri.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+
+    return String::from_utf8_lossy(&out).into_owned();
+}
+
+/// Standard base64 with optional padding.
+fn base64(text: &str) -> Option<Vec<u8>> {
+    let digit = |c: u8| -> Option<u32> {
+        match c {
+            b'A'..=b'Z' => Some((c - b'A') as u32),
+            b'a'..=b'z' => Some((c - b'a') as u32 + 26),
+            b'0'..=b'9' => Some((c - b'0') as u32 + 52),
+            b'+' => Some(62),
+            b'/' => Some(63),
+            _ => None,
+        }
+    };
+
+    let text = text.trim_end_matches('=').as_bytes();
+    if text.len() % 4 == 1 {
+        return None;
+    }
+
+    let mut out = Vec::with_capacity(text.len() * 3 / 4);
+    for chunk in text.chunks(4) {
+        let mut bits = 0;
+        for &c in chunk {
+            bits = bits << 6 | digit(c)?;
+        }
+        bits <<= 6 * (4 - chunk.len());
+        out.extend(&bits.to_be_bytes()[1..chunk.len()]);
+    }
+
+    return Some(out);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    fn encode_base64(bytes: &[u8]) -> String {
+        const DIGITS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+        let mut out = String::new();
+
+        for chunk in bytes.chunks(3) {
+            let mut bits = 0u32;
+            for (i, &b) in chunk.iter().enumerate() {
+                bits |= (b as u32) << (16 - 8 * i);
+            }
+            for i in 0..4 {
+                out.push(if i <= chunk.len() {
+                    DIGITS[(bits >> (18 - 6 * i) & 63) as usize] as char
+                } else {
+                    '='
+                });
+            }
+        }
+
+        return out;
+    }
+
+    /// A skinned, animated quad drawn as a strip, and the JSON that
+    /// describes it with `buffer` as its first buffer.
+    fn quad(buffer: &str) -> (String, Vec<u8>) {
+        let mut bin = Vec::new();
+        let mut floats = |values: &[f32]| {
+            for v in values {
+                bin.extend(v.to_le_bytes());
+            }
+        };
+        // Positions at 0, times at 48, translations at 56, inverse bind
+        // matrix at 80.
+        floats(&[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0]);
+        floats(&[0.0, 2.0]);
+        floats(&[0.0, 0.0, 0.0, 0.0, 3.0, 0.0]);
+        floats(&[
+            1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, -1.0, -2.0, -3.0, 1.0,
+        ]);
+        // Indices at 144, joints at 152 and weights at 168.
+        bin.extend([0u16, 1, 2, 3].iter().flat_map(|i| i.to_le_bytes()));
+        bin.extend([0u8, 1, 0, 0].repeat(4));
+        bin.extend([255u8, 0, 0, 0, 128, 127, 0, 0].repeat(2));
+
+        let json = format!(
+            r#"{{
+  "asset": {{"version": "2.0"}},
+  "scene": 0,
+  "scenes": [{{"nodes": [0]}}],
+  "nodes": [
+    {{"name": "root", "children": [1, 2], "translation": [1, 0, 0],
+      "rotation": [0, 0.7071068, 0, 0.7071068]}},
+    {{"name": "quad", "mesh": 0, "skin": 0,
+      "matrix": [2, 0, 0, 0, 0, 2, 0, 0, 0, 0, 2, 0, 0, 5, 0, 1]}},
+    {{"name": "bone"}}
+  ],
+  "meshes": [{{"name": "quad", "primitives": [{{
+    "attributes": {{"POSITION": 0, "JOINTS_0": 4, "WEIGHTS_0": 5}},
+    "indices": 1, "mode": 5, "material": 0}}]}}],
+  "materials": [{{"name": "paint", "alphaMode": "MASK",
+    "pbrMetallicRoughness": {{"baseColorFactor": [1, 0.5, 0, 1], "metallicFactor": 0,
+      "baseColorTexture": {{"index": 0, "texCoord": 1}}}}}}],
+  "textures": [{{"source": 0}}],
+  "images": [{{"uri": "paint%20can.png"}}],
+  "skins": [{{"joints": [2], "inverseBindMatrices": 6}}],
+  "animations": [{{"channels": [{{"sampler": 0, "target": {{"node": 2, "path": "translation"}}}}],
+    "samplers": [{{"input": 2, "output": 3}}]}}],
+  "accessors": [
+    {{"bufferView": 0, "componentType": 5126, "count": 4, "type": "VEC3"}},
+    {{"bufferView": 1, "componentType": 5123, "count": 4, "type": "SCALAR"}},
+    {{"bufferView": 0, "byteOffset": 48, "componentType": 5126, "count": 2, "type": "SCALAR"}},
+    {{"bufferView": 0, "byteOffset": 56, "componentType": 5126, "count": 2, "type": "VEC3"}},
+    {{"bufferView": 1, "byteOffset": 8, "componentType": 5121, "count": 4, "type": "VEC4"}},
+    {{"bufferView": 1, "byteOffset": 24, "componentType": 5121, "normalized": true,
+      "count": 4, "type": "VEC4"}},
+    {{"bufferView": 0, "byteOffset": 80, "componentType": 5126, "count": 1, "type": "MAT4"}}
+  ],
+  "bufferViews": [
+    {{"buffer": 0, "byteLength": 144}},
+    {{"buffer": 0, "byteOffset": 144, "byteLength": 40}}
+  ],
+  "buffers": [{{{buffer}"byteLength": {}}}]
+}}"#,
+            bin.len()
+        );
+
+        return (json, bin);
+    }
+
+    fn glb(json: &str, bin: &[u8]) -> Vec<u8> {
+        let pad = |data: &mut Vec<u8>, byte: u8| {
+            while !data.len().is_multiple_of(4) {
+                data.push(byte);
+            }
+        };
+        let (mut json, mut bin) = (json.as_bytes().to_vec(), bin.to_vec());
+        pad(&mut json, b' ');
+        pad(&mut bin, 0);
+
+        let mut out = Vec::new();
+        out.extend(GLB_MAGIC.to_le_bytes());
+        out.extend(2u32.to_le_bytes());
+        out.extend((12 + 8 + json.len() as u32 + 8 + bin.len() as u32).to_le_bytes());
+        for (kind, chunk) in [(CHUNK_JSON, json), (CHUNK_BIN, bin)] {
+            out.extend((chunk.len() as u32).to_le_bytes());
+            out.extend(kind.to_le_bytes());
+            out.extend(chunk);
+        }
+
+        return out;
+    }
+
+    fn quad_glb() -> Gltf {
+        let (json, bin) = quad("");
+        return from_slice(&glb(&json, &bin), None).unwrap();
+    }
+
+    /// The quad with the first `from` in its JSON replaced by `to`.
+    fn edited(from: &str, to: &str) -> Result<Gltf, GltfError> {
+        let (json, bin) = quad("");
+        let edited = json.replacen(from, to, 1);
+        assert_ne!(edited, json, "{from:?} not found");
+
+        return from_slice(&glb(&edited, &bin), None);
+    }
+
+    fn error(from: &str, to: &str) -> String {
+        edited(from, to).unwrap_err().to_string()
+    }
+
+    fn assert_matrix_close(a: &Matrix4x4F32, b: &Matrix4x4F32) {
+        for (x, y) in a.iter().zip(b.iter()) {
+            assert!((x - y).abs() < 1e-5, "{a:?} != {b:?}");
+        }
+    }
+
+    /// A directory of its own for each test, as tests run in parallel.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("wmb-gltf-{name}-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        return dir;
+    }
+
+    #[test]
+    fn primitive_test() {
+        let gltf = quad_glb();
+        let primitive = &gltf.meshes[0].primitives[0];
+        assert_eq!(gltf.meshes[0].name, "quad");
+        assert_eq!(primitive.positions[3], Vector3D::create(1.0, 1.0, 0.0));
+        // The strip becomes two counterclockwise triangles.
+        assert_eq!(primitive.indices, [0, 1, 2, 2, 1, 3]);
+        assert_eq!(primitive.joints[0], [0, 1, 0, 0]);
+        assert_eq!(primitive.weights[0], [1.0, 0.0, 0.0, 0.0]);
+        assert!((primitive.weights[1][0] - 128.0 / 255.0).abs() < 1e-6);
+        assert_eq!(primitive.material, Some(0));
+        assert!(primitive.normals.is_empty() && primitive.uvs.is_empty());
+    }
+
+    #[test]
+    fn material_test() {
+        let material = &quad_glb().materials[0];
+        assert_eq!(material.name, "paint");
+        assert_eq!(material.base_color, [1.0, 0.5, 0.0, 1.0]);
+        assert_eq!((material.metallic, material.roughness), (0.0, 1.0));
+        assert_eq!(material.alpha_mode, AlphaMode::Mask { cutoff: 0.5 });
+        assert_eq!(
+            material.base_color_texture,
+            Some(TextureRef {
+                image: 0,
+                uv_set: 1
+            })
+        );
+        assert_eq!(material.normal_texture, None);
+    }
+
+    #[test]
+    fn image_test() {
+        assert_eq!(
+            quad_glb().images[0],
+            GltfImage::Uri("paint%20can.png".to_string())
+        );
+
+        let gltf = edited("paint%20can.png", "data:image/png;base64,aGVsbG8=").unwrap();
+        assert_eq!(
+            gltf.images[0],
+            GltfImage::Embedded {
+                mime_type: "image/png".to_string(),
+                data: b"hello".to_vec()
+            }
+        );
+    }
+
+    #[test]
+    fn node_trs_test() {
+        let gltf = quad_glb();
+        let root = &gltf.nodes[0];
+        assert_eq!(root.name, "root");
+        assert_eq!(root.children, [1, 2]);
+        assert_eq!(root.parent, None);
+        assert_eq!(root.transform.translation, Vector3D::create(1.0, 0.0, 0.0));
+        assert_eq!(root.transform.scale, Vector3D::create(1.0, 1.0, 1.0));
+        assert!((root.transform.rotation.magnitude() - 1.0).abs() < 1e-6);
+
+        let bone = &gltf.nodes[2];
+        assert_eq!(bone.transform, Transform::default());
+        assert_eq!((bone.parent, bone.mesh, bone.skin), (Some(0), None, None));
+    }
+
+    #[test]
+    fn node_matrix_test() {
+        let quad = &quad_glb().nodes[1];
+        assert_eq!(quad.parent, Some(0));
+        assert_eq!(quad.transform.translation, Vector3D::create(0.0, 5.0, 0.0));
+        assert_eq!(quad.transform.scale, Vector3D::create(2.0, 2.0, 2.0));
+        assert_eq!((quad.mesh, quad.skin), (Some(0), Some(0)));
+
+        // A quarter turn about z with y doubled, as a matrix and as TRS.
+        let matrix = edited(
+            r#"{"name": "bone"}"#,
+            r#"{"name": "bone", "matrix": [0, 1, 0, 0, -2, 0, 0, 0, 0, 0, 1, 0, 1, 2, 3, 1]}"#,
+        )
+        .unwrap()
+        .nodes[2]
+            .transform;
+        let trs = edited(
+            r#"{"name": "bone"}"#,
+            r#"{"name": "bone", "translation": [1, 2, 3],
+                "rotation": [0, 0, 0.7071068, 0.7071068], "scale": [1, 2, 1]}"#,
+        )
+        .unwrap()
+        .nodes[2]
+            .transform;
+
+        assert_eq!(matrix.translation, trs.translation);
+        assert!((matrix.scale - trs.scale).magnitude() < 1e-5);
+        assert!(matrix.rotation.dot(&trs.rotation).abs() > 1.0 - 1e-5);
+        assert_matrix_close(&matrix.matrix(), &trs.matrix());
+    }
+
+    #[test]
+    fn node_hierarchy_error_test() {
+        assert_eq!(
+            error(r#""children": [1, 2]"#, r#""children": [1, 0]"#),
+            "nodes[0]: is its own ancestor"
+        );
+        assert_eq!(
+            error(r#""name": "bone""#, r#""name": "bone", "children": [1]"#),
+            "nodes[2].children[0]: node 1 has more than one parent"
+        );
+        assert_eq!(
+            error(r#""children": [1, 2]"#, r#""children": [1, 3]"#),
+            "nodes[0].children[1]: expected an index below 3"
+        );
+    }
+
+    #[test]
+    fn scene_graph_test() {
+        let gltf = quad_glb();
+        assert_eq!(gltf.scene, Some(0));
+
+        // The root turns +x to -z, so the quad's offset up stays up.
+        let mut graph = gltf.scene_graph(0).unwrap();
+        graph.update();
+        let (ids, nodes): (Vec<_>, Vec<_>) = graph.iter().map(|(id, n)| (id, *n.data())).unzip();
+        assert_eq!(nodes, [0, 1, 2]);
+        let world = graph.world_matrix(ids[1]).unwrap();
+        let corner = world.transform_point(&Vector3D::create(1.0, 0.0, 0.0));
+        assert!((corner - Vector3D::create(1.0, 5.0, -2.0)).magnitude() < 1e-5);
+        assert!(gltf.scene_graph(1).is_none());
+    }
+
+    #[test]
+    fn skin_test() {
+        let gltf = quad_glb();
+        let skin = &gltf.skins[0];
+        assert_eq!(skin.joints, [2]);
+        assert_eq!(skin.skeleton, None);
+        assert_eq!(skin.inverse_bind_matrices.len(), 1);
+        let bind = skin.inverse_bind_matrices[0];
+        assert_eq!(
+            bind.transform_point(&Vector3D::create(1.0, 2.0, 3.0)),
+            Vector3D::default()
+        );
+    }
+
+    #[test]
+    fn skin_default_inverse_bind_test() {
+        let gltf = edited(r#", "inverseBindMatrices": 6"#, "").unwrap();
+        assert_eq!(
+            gltf.skins[0].inverse_bind_matrices,
+            [Matrix4x4F32::identity()]
+        );
+    }
+
+    #[test]
+    fn skin_error_test() {
+        assert_eq!(
+            error(r#""joints": [2]"#, r#""joints": [2, 1]"#),
+            "skins[0].inverseBindMatrices: 1 matrices for 2 joints"
+        );
+        assert_eq!(
+            error(r#""joints": [2]"#, r#""joints": []"#),
+            "skins[0].joints: missing"
+        );
+        assert_eq!(
+            error(r#""joints": [2]"#, r#""joints": [5]"#),
+            "skins[0].joints[0]: expected an index below 3"
+        );
+        assert_eq!(
+            error(r#""inverseBindMatrices": 6"#, r#""inverseBindMatrices": 0"#),
+            "accessors[0]: is VEC3 where MAT4 is expected"
+        );
+        assert_eq!(
+            error(r#""mesh": 0, "skin": 0"#, r#""mesh": 0, "skin": 1"#),
+            "nodes[1].skin: index 1 out of range; 1 defined"
+        );
+    }
+
+    #[test]
+    fn animation_test() {
+        let channel = &quad_glb().animations[0].channels[0];
+        assert_eq!(
+            (channel.node, channel.interpolation),
+            (2, Interpolation::Linear)
+        );
+        assert_eq!(channel.times, [0.0, 2.0]);
+        assert_eq!(
+            channel.values,
+            Keyframes::Translation(vec![Vector3D::default(), Vector3D::create(0.0, 3.0, 0.0)])
+        );
+    }
+
+    #[test]
+    fn interpolation_test() {
+        let step = edited(
+            r#""output": 3}"#,
+            r#""output": 3, "interpolation": "STEP"}"#,
+        )
+        .unwrap();
+        let channel = &step.animations[0].channels[0];
+        assert_eq!(channel.interpolation, Interpolation::Step);
+        assert_eq!(channel.values, quad_glb().animations[0].channels[0].values);
+
+        // Cubic splines store three values per keyframe; these six run on
+        // into the inverse bind matrix.
+        let (json, bin) = quad("");
+        let json = json
+            .replacen(
+                r#""output": 3}"#,
+                r#""output": 3, "interpolation": "CUBICSPLINE"}"#,
+                1,
+            )
+            .replacen(
+                r#""byteOffset": 56, "componentType": 5126, "count": 2"#,
+                r#""byteOffset": 56, "componentType": 5126, "count": 6"#,
+                1,
+            );
+        let gltf = from_slice(&glb(&json, &bin), None).unwrap();
+        let channel = &gltf.animations[0].channels[0];
+        assert_eq!(channel.interpolation, Interpolation::CubicSpline);
+        let Keyframes::Translation(values) = &channel.values else {
+            panic!("{:?}", channel.values);
+        };
+        assert_eq!(values.len(), 6);
+        assert_eq!(values[1], Vector3D::create(0.0, 3.0, 0.0));
+        assert_eq!(values[2], Vector3D::create(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn animation_error_test() {
+        assert_eq!(
+            error(
+                r#""output": 3}"#,
+                r#""output": 3, "interpolation": "SMOOTH"}"#
+            ),
+            "animations[0].samplers[0].interpolation: expected LINEAR, STEP or CUBICSPLINE"
+        );
+        assert_eq!(
+            error(
+                r#""output": 3}"#,
+                r#""output": 3, "interpolation": "CUBICSPLINE"}"#
+            ),
+            "accessors[3]: 2 values for 2 keyframes"
+        );
+        // Starting one float early repeats the time 0.
+        assert_eq!(
+            error(r#""byteOffset": 48"#, r#""byteOffset": 44"#),
+            "accessors[2]: keyframe times do not increase"
+        );
+        assert_eq!(
+            error(r#""path": "translation""#, r#""path": "rotation""#),
+            "accessors[3]: is VEC3 where VEC4 is expected"
+        );
+        assert_eq!(
+            error(r#""path": "translation""#, r#""path": "color""#),
+            "animations[0].channels[0].target.path: expected translation, rotation, scale or weights"
+        );
+        assert_eq!(
+            error(r#""output": 3"#, r#""output": 6"#),
+            "accessors[6]: is MAT4 where VEC3 is expected"
+        );
+        assert_eq!(
+            error(r#""sampler": 0"#, r#""sampler": 1"#),
+            "animations[0].channels[0].sampler: index 1 out of range; 1 defined"
+        );
+    }
+
+    #[test]
+    fn accessor_bounds_test() {
+        assert_eq!(
+            error(
+                r#""count": 4, "type": "VEC3""#,
+                r#""count": 13, "type": "VEC3""#
+            ),
+            "accessors[0]: needs 156 bytes of buffer view 0, which has 144"
+        );
+        assert_eq!(
+            error(
+                r#""count": 4, "type": "VEC3""#,
+                r#""count": 2000000000000000000, "type": "VEC3""#
+            ),
+            format!(
+                "accessors[0]: needs more than {} bytes of buffer view 0, which has 144",
+                usize::MAX
+            )
+        );
+        assert_eq!(
+            error(
+                r#""count": 4, "type": "VEC3""#,
+                r#""count": 0, "type": "VEC3""#
+            ),
+            "accessors[0]: count must be positive"
+        );
+        // The translations fit, but not past the end of the view.
+        assert_eq!(
+            error(r#""byteOffset": 56"#, r#""byteOffset": 124"#),
+            "accessors[3]: needs 148 bytes of buffer view 0, which has 144"
+        );
+        assert_eq!(
+            error(r#""byteOffset": 48"#, r#""byteOffset": 50"#),
+            "accessors[2]: byteOffset 50 is not a multiple of 4"
+        );
+    }
+
+    #[test]
+    fn accessor_stride_test() {
+        assert_eq!(
+            error(
+                r#"{"buffer": 0, "byteLength": 144}"#,
+                r#"{"buffer": 0, "byteLength": 144, "byteStride": 8}"#
+            ),
+            "accessors[0]: byteStride 8 does not fit 12-byte elements"
+        );
+        assert_eq!(
+            error(
+                r#"{"buffer": 0, "byteLength": 144}"#,
+                r#"{"buffer": 0, "byteLength": 144, "byteStride": 14}"#
+            ),
+            "accessors[0]: byteStride 14 does not fit 12-byte elements"
+        );
+        // Four elements 48 bytes apart end past the view.
+        assert_eq!(
+            error(
+                r#"{"buffer": 0, "byteLength": 144}"#,
+                r#"{"buffer": 0, "byteLength": 144, "byteStride": 48}"#
+            ),
+            "accessors[0]: needs 156 bytes of buffer view 0, which has 144"
+        );
+
+        // Positions interleaved with a float of padding each.
+        let mut bin = Vec::new();
+        for v in [1.0f32, 2.0, 3.0, -1.0, 4.0, 5.0, 6.0, -1.0, 7.0, 8.0, 9.0] {
+            bin.extend(v.to_le_bytes());
+        }
+        let json = format!(
+            r#"{{"asset": {{"version": "2.0"}},
+  "meshes": [{{"primitives": [{{"attributes": {{"POSITION": 0}}}}]}}],
+  "accessors": [{{"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3"}}],
+  "bufferViews": [{{"buffer": 0, "byteLength": 44, "byteStride": 16}}],
+  "buffers": [{{"byteLength": {}}}]
+}}"#,
+            bin.len()
+        );
+        let gltf = from_slice(&glb(&json, &bin), None).unwrap();
+        assert_eq!(
+            gltf.meshes[0].primitives[0].positions,
+            [
+                Vector3D::create(1.0, 2.0, 3.0),
+                Vector3D::create(4.0, 5.0, 6.0),
+                Vector3D::create(7.0, 8.0, 9.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn accessor_type_test() {
+        assert_eq!(
+            error(r#""POSITION": 0"#, r#""POSITION": 2"#),
+            "accessors[2]: is SCALAR where VEC3 is expected"
+        );
+        assert_eq!(
+            error(r#""indices": 1"#, r#""indices": 2"#),
+            "accessors[2]: component type 5126 is not allowed here"
+        );
+        assert_eq!(
+            error(r#""normalized": true,"#, ""),
+            "accessors[5]: integer components must be normalized"
+        );
+        assert_eq!(
+            error(r#""componentType": 5123"#, r#""componentType": 5124"#),
+            "accessors[1]: unknown component type 5124"
+        );
+        assert_eq!(
+            error(r#""indices": 1"#, r#""indices": 9"#),
+            "meshes[0].primitives[0].indices: index 9 out of range; 7 defined"
+        );
+    }
+
+    #[test]
+    fn buffer_view_test() {
+        assert_eq!(
+            error(r#""byteLength": 144}"#, r#""byteLength": 400}"#),
+            "bufferViews[0]: bytes 0..400 are outside buffer 0 of 184 bytes"
+        );
+        // The end of the view does not fit in a `usize`.
+        assert_eq!(
+            error(
+                r#""byteOffset": 144, "byteLength": 40"#,
+                r#""byteOffset": 144, "byteLength": 18446744073709549568"#
+            ),
+            "bufferViews[1]: bytes 144..18446744073709549712 are outside buffer 0 of 184 bytes"
+        );
+        assert_eq!(
+            error(r#""byteOffset": 144,"#, r#""byteOffset": -4,"#),
+            "bufferViews[1]: needs a byteLength and a valid byteOffset"
+        );
+        assert_eq!(
+            error(
+                r#""buffers": [{"byteLength""#,
+                r#""buffers": [{"byteLength": 1000, "x""#
+            ),
+            "buffers[0].byteLength: is 1000 but the data has 184 bytes"
+        );
+    }
+
+    #[test]
+    fn data_uri_buffer_test() {
+        let (_, bin) = quad("");
+        let uri = format!(
+            r#""uri": "data:application/octet-stream;base64,{}", "#,
+            encode_base64(&bin)
+        );
+        let (json, _) = quad(&uri);
+        assert_eq!(from_slice(json.as_bytes(), None).unwrap(), quad_glb());
+    }
+
+    #[test]
+    fn data_uri_error_test() {
+        let error = |uri: &str| {
+            let (json, _) = quad(&format!(r#""uri": "{uri}", "#));
+            return from_slice(json.as_bytes(), None).unwrap_err().to_string();
+        };
+        assert_eq!(
+            error("data:application/octet-stream;base64"),
+            "buffers[0].uri: data URI without a comma"
+        );
+        assert_eq!(
+            error("data:text/plain,hello"),
+            "unsupported: buffers[0].uri: data URI that is not base64"
+        );
+        assert_eq!(
+            error("data:;base64,a"),
+            "buffers[0].uri: invalid base64 data"
+        );
+        assert_eq!(
+            error("quad.bin"),
+            "buffers[0].uri: external file without a base directory"
+        );
+    }
+
+    #[test]
+    fn external_buffer_test() {
+        let dir = temp_dir("external");
+        let (_, bin) = quad("");
+        fs::write(dir.join("quad data.bin"), &bin).unwrap();
+        let (json, _) = quad(r#""uri": "quad%20data.bin", "#);
+        fs::write(dir.join("quad.gltf"), json).unwrap();
+
+        let gltf = load(dir.join("quad.gltf"));
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(gltf.unwrap(), quad_glb());
+    }
+
+    #[test]
+    fn missing_external_buffer_test() {
+        let dir = temp_dir("missing");
+        let (json, _) = quad(r#""uri": "quad%20data.bin", "#);
+        fs::write(dir.join("quad.gltf"), json).unwrap();
+
+        let gltf = load(dir.join("quad.gltf"));
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(
+            gltf,
+            Err(GltfError::Io { path, .. }) if path.ends_with("quad data.bin")
+        ));
+    }
+
+    #[test]
+    fn glb_test() {
+        let (json, bin) = quad("");
+        let gltf = from_slice(&glb(&json, &bin), None).unwrap();
+        // The first buffer has no URI and is the binary chunk.
+        assert_eq!(
+            gltf.meshes[0].primitives[0].positions[1],
+            Vector3D::create(1.0, 0.0, 0.0)
+        );
+
+        let (json, _) = quad(r#""uri": "quad.bin", "#);
+        assert_eq!(
+            from_slice(&glb(&json, &[]), None).unwrap_err().to_string(),
+            "buffers[0].uri: external file without a base directory"
+        );
+    }
+
+    #[test]
+    fn glb_error_test() {
+        let (json, bin) = quad("");
+        let file = glb(&json, &bin);
+        let error = |bytes: &[u8]| match from_slice(bytes, None) {
+            Err(GltfError::Glb(message)) => message,
+            other => panic!("{other:?}"),
+        };
+
+        assert_eq!(
+            error(&file[..file.len() - 4]),
+            "file is shorter than its header says"
+        );
+        assert_eq!(error(&file[..10]), "truncated header");
+        let mut swapped = file.clone();
+        swapped[16..20].copy_from_slice(&CHUNK_BIN.to_le_bytes());
+        assert_eq!(error(&swapped), "the first chunk is not JSON");
+    }
+
+    #[test]
+    fn unsupported_test() {
+        assert_eq!(
+            error(
+                r#""asset": {"version": "2.0"}"#,
+                r#""asset": {"version": "2.0"}, "extensionsRequired": ["KHR_draco_mesh_compression"]"#
+            ),
+            "unsupported: required extensions KHR_draco_mesh_compression"
+        );
+        assert_eq!(
+            error(r#""version": "2.0""#, r#""version": "1.0""#),
+            "unsupported: glTF version 1.0"
+        );
+    }
+
+    #[test]
+    fn json_error_test() {
+        assert!(
+            error(r#""scene": 0,"#, r#""scene": 0"#).starts_with("invalid JSON: line 4 column 3")
+        );
+    }
+
+    /// Five positions, all zero but the third, with a fan of triangles.
+    fn sparse() -> (String, Vec<u8>) {
+        let mut bin = Vec::new();
+        for v in [1.0f32, 2.0, 3.0] {
+            bin.extend(v.to_le_bytes());
+        }
+        bin.push(2);
+
+        let json = format!(
+            r#"{{"asset": {{"version": "2.0"}},
+  "meshes": [{{"primitives": [{{"attributes": {{"POSITION": 0}}, "mode": 6}}]}}],
+  "accessors": [
+    {{"componentType": 5126, "count": 5, "type": "VEC3",
+      "sparse": {{"count": 1, "indices": {{"bufferView": 0, "componentType": 5121}},
+        "values": {{"bufferView": 1}}}}}}
+  ],
+  "bufferViews": [{{"buffer": 0, "byteOffset": 12, "byteLength": 1}},
+    {{"buffer": 0, "byteLength": 12}}],
+  "buffers": [{{"byteLength": {}}}]
+}}"#,
+            bin.len()
+        );
+
+        return (json, bin);
+    }
+
+    #[test]
+    fn sparse_test() {
+        let (json, bin) = sparse();
+        let gltf = from_slice(&glb(&json, &bin), None).unwrap();
+        let primitive = &gltf.meshes[0].primitives[0];
+        assert_eq!(primitive.positions[2], Vector3D::create(1.0, 2.0, 3.0));
+        assert_eq!(primitive.positions[1], Vector3D::default());
+        assert_eq!(primitive.indices, [0, 1, 2, 0, 2, 3, 0, 3, 4]);
+    }
+
+    #[test]
+    fn sparse_error_test() {
+        let (json, bin) = sparse();
+        let error = |from: &str, to: &str| {
+            let edited = json.replacen(from, to, 1);
+            return from_slice(&glb(&edited, &bin), None)
+                .unwrap_err()
+                .to_string();
+        };
+
+        assert_eq!(
+            error(r#""count": 5"#, r#""count": 100000000000"#),
+            "accessors[0]: count 100000000000 is too large without a bufferView"
+        );
+        assert_eq!(
+            error(r#""count": 1"#, r#""count": 6"#),
+            "accessors[0]: sparse count 6 exceeds count 5"
+        );
+        assert_eq!(
+            error(r#""count": 5"#, r#""count": 2"#),
+            "accessors[0]: sparse index 2 out of range"
+        );
+    }
+
+    #[test]
+    fn base64_test() {
+        assert_eq!(base64("aGVsbG8").unwrap(), b"hello");
+        assert_eq!(base64("aGVsbG8=").unwrap(), b"hello");
+        let (_, bin) = quad("");
+        assert_eq!(base64(&encode_base64(&bin)).unwrap(), bin);
+        assert!(base64("a").is_none());
+        assert!(base64("aG!s").is_none());
+        assert_eq!(percent_decode("paint%20can%2"), "paint can%2");
+    }
+}
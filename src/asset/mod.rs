@@ -0,0 +1,14 @@
+//! Importers for 3D asset formats.
+//!
+//! [`obj`] reads Wavefront OBJ meshes and their MTL material libraries, and
+//! [`gltf`] reads glTF 2.0 as JSON with separate buffers or as binary GLB.
+//! Both are self-contained; [`json`] is the parser glTF uses.
+
+#![allow(clippy::needless_return)]
+
+pub mod gltf;
+pub mod json;
+pub mod obj;
+
+pub use gltf::{Gltf, GltfError};
+pub use obj::{Obj, ObjError, ObjMaterial, ObjMesh, ParseObjError};
@@ -0,0 +1,709 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::{self, Display, Formatter},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    geometry::{triangulate, Polygon},
+    math::{Vector2D, Vector3D},
+};
+
+/// A line of an OBJ or MTL file that could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseObjError {
+    /// 1-based; the first line of a statement continued with `\`.
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for ParseObjError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for ParseObjError {}
+
+/// Why [`load`] failed, with the file at fault.
+#[derive(Debug)]
+pub enum ObjError {
+    Io { path: PathBuf, error: io::Error },
+    Parse { path: PathBuf, error: ParseObjError },
+}
+
+impl Display for ObjError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjError::Io { path, error } => write!(f, "{}: {error}", path.display()),
+            ObjError::Parse { path, error } => write!(f, "{}: {error}", path.display()),
+        }
+    }
+}
+
+impl Error for ObjError {}
+
+/// A material from an MTL library. Colors are RGB in `[0, 1]`; texture
+/// maps are paths relative to the library, without their options.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjMaterial {
+    pub name: String,
+    /// `Ka`.
+    pub ambient: Vector3D,
+    /// `Kd`.
+    pub diffuse: Vector3D,
+    /// `Ks`.
+    pub specular: Vector3D,
+    /// `Ke`.
+    pub emissive: Vector3D,
+    /// `Ns`, the specular exponent.
+    pub shininess: f32,
+    /// `d`, or one minus `Tr`.
+    pub opacity: f32,
+    /// `Ni`.
+    pub ior: f32,
+    /// `illum`.
+    pub illumination: u32,
+    pub diffuse_map: Option<String>,
+    pub ambient_map: Option<String>,
+    pub specular_map: Option<String>,
+    /// `map_Bump`, `bump` or `norm`.
+    pub bump_map: Option<String>,
+    /// `map_d`.
+    pub opacity_map: Option<String>,
+}
+
+impl ObjMaterial {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ambient: Vector3D::default(),
+            diffuse: Vector3D::create(0.8, 0.8, 0.8),
+            specular: Vector3D::default(),
+            emissive: Vector3D::default(),
+            shininess: 0.0,
+            opacity: 1.0,
+            ior: 1.0,
+            illumination: 2,
+            diffuse_map: None,
+            ambient_map: None,
+            specular_map: None,
+            bump_map: None,
+            opacity_map: None,
+        }
+    }
+}
+
+/// An indexed triangle mesh for one group and material of an OBJ file.
+///
+/// There is a normal for every position. Faces without normals get smooth
+/// normals shared within their smoothing group, or flat normals when
+/// smoothing is off. `uvs` is empty if no face had texture coordinates.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ObjMesh {
+    /// The `o` or `g` name in effect, or empty.
+    pub name: String,
+    /// The `usemtl` name in effect.
+    pub material: Option<String>,
+    pub positions: Vec<Vector3D>,
+    pub normals: Vec<Vector3D>,
+    pub uvs: Vec<Vector2D>,
+    /// Counterclockwise triangles.
+    pub indices: Vec<u32>,
+}
+
+/// The contents of an OBJ file.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Obj {
+    /// Meshes with at least one face, in file order.
+    pub meshes: Vec<ObjMesh>,
+    /// The `mtllib` names, relative to the OBJ file.
+    pub material_libraries: Vec<String>,
+    /// Filled in by [`load`]; empty after [`parse`].
+    pub materials: Vec<ObjMaterial>,
+}
+
+impl Obj {
+    /// The material a mesh uses, if it was loaded.
+    pub fn material(&self, mesh: &ObjMesh) -> Option<&ObjMaterial> {
+        let name = mesh.material.as_ref()?;
+
+        return self.materials.iter().find(|m| &m.name == name);
+    }
+}
+
+/// Reads an OBJ file and the material libraries it names, which are looked
+/// up beside it.
+pub fn load(path: impl AsRef<Path>) -> Result<Obj, ObjError> {
+    let path = path.as_ref();
+    let mut obj = parse(&read(path)?).map_err(|error| ObjError::Parse {
+        path: path.to_path_buf(),
+        error,
+    })?;
+
+    let dir = path.parent().unwrap_or(Path::new(""));
+    for library in &obj.material_libraries {
+        let path = dir.join(library);
+        let materials = parse_mtl(&read(&path)?).map_err(|error| ObjError::Parse {
+            path: path.clone(),
+            error,
+        })?;
+        obj.materials.extend(materials);
+    }
+
+    return Ok(obj);
+}
+
+fn read(path: &Path) -> Result<String, ObjError> {
+    fs::read_to_string(path).map_err(|error| ObjError::Io {
+        path: path.to_path_buf(),
+        error,
+    })
+}
+
+/// Parses the text of an OBJ file.
+///
+/// Polygons are triangulated by ear clipping in their own plane, so
+/// concave faces are handled. Indices may be negative, counting back from
+/// the latest vertex. Statements other than geometry, grouping, smoothing
+/// and materials are ignored.
+pub fn parse(source: &str) -> Result<Obj, ParseObjError> {
+    let mut positions = Vec::new();
+    let mut uvs = Vec::new();
+    let mut normals = Vec::new();
+    let mut obj = Obj::default();
+    let mut builder = MeshBuilder::default();
+    let mut smoothing = 0;
+
+    for (line, statement) in statements(source) {
+        let error = |message: String| ParseObjError { line, message };
+        let mut words = statement.split_whitespace();
+        let keyword = words.next().unwrap();
+        let args: Vec<&str> = words.collect();
+
+        match keyword {
+            "v" => {
+                let v = floats(&args, 3, 7).map_err(error)?;
+                positions.push(Vector3D::create(v[0], v[1], v[2]));
+            }
+            "vt" => {
+                let v = floats(&args, 1, 3).map_err(error)?;
+                uvs.push(Vector2D::create(v[0], v.get(1).copied().unwrap_or(0.0)));
+            }
+            "vn" => {
+                let v = floats(&args, 3, 3).map_err(error)?;
+                normals.push(Vector3D::create(v[0], v[1], v[2]));
+            }
+            "f" => {
+                if args.len() < 3 {
+                    return Err(error(format!(
+                        "a face needs at least three vertices, found {}",
+                        args.len()
+                    )));
+                }
+                let counts = [positions.len(), uvs.len(), normals.len()];
+                let corners = args
+                    .iter()
+                    .map(|corner| parse_corner(corner, counts))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(error)?;
+                builder.face(&corners, smoothing, &positions, &uvs, &normals);
+            }
+            "o" | "g" => {
+                builder.finish(&mut obj.meshes);
+                builder.name = args.join(" ");
+            }
+            "usemtl" => {
+                if args.is_empty() {
+                    return Err(error("usemtl needs a material name".to_string()));
+                }
+                builder.finish(&mut obj.meshes);
+                builder.material = Some(args.join(" "));
+            }
+            "mtllib" => {
+                if args.is_empty() {
+                    return Err(error("mtllib needs a file name".to_string()));
+                }
+                obj.material_libraries
+                    .extend(args.iter().map(|s| s.to_string()));
+            }
+            "s" => {
+                smoothing = match args.as_slice() {
+                    ["off"] => 0,
+                    [group] => group
+                        .parse()
+                        .map_err(|_| error(format!("invalid smoothing group {group:?}")))?,
+                    _ => return Err(error("s needs a group number or \"off\"".to_string())),
+                };
+            }
+            _ => {}
+        }
+    }
+
+    builder.finish(&mut obj.meshes);
+
+    return Ok(obj);
+}
+
+/// Parses the text of an MTL file.
+pub fn parse_mtl(source: &str) -> Result<Vec<ObjMaterial>, ParseObjError> {
+    let mut materials: Vec<ObjMaterial> = Vec::new();
+
+    for (line, statement) in statements(source) {
+        let error = |message: String| ParseObjError { line, message };
+        let mut words = statement.split_whitespace();
+        let keyword = words.next().unwrap();
+        let args: Vec<&str> = words.collect();
+
+        if keyword == "newmtl" {
+            if args.is_empty() {
+                return Err(error("newmtl needs a material name".to_string()));
+            }
+            materials.push(ObjMaterial::new(&args.join(" ")));
+            continue;
+        }
+
+        let Some(material) = materials.last_mut() else {
+            return Err(error(format!("{keyword} before any newmtl")));
+        };
+        let color = || {
+            // A single value is a grey.
+            let v = floats(&args, 1, 3).map_err(error)?;
+            return match *v.as_slice() {
+                [grey] => Ok(Vector3D::create(grey, grey, grey)),
+                [r, g, b] => Ok(Vector3D::create(r, g, b)),
+                _ => Err(error(format!("expected 1 or 3 numbers, found {}", v.len()))),
+            };
+        };
+        let float = || floats(&args, 1, 1).map(|v| v[0]).map_err(error);
+        let map = || match args.last() {
+            Some(file) => Ok(Some(file.to_string())),
+            None => Err(error(format!("{keyword} needs a file name"))),
+        };
+
+        match keyword {
+            "Ka" => material.ambient = color()?,
+            "Kd" => material.diffuse = color()?,
+            "Ks" => material.specular = color()?,
+            "Ke" => material.emissive = color()?,
+            "Ns" => material.shininess = float()?,
+            "Ni" => material.ior = float()?,
+            "d" => material.opacity = float()?,
+            "Tr" => material.opacity = 1.0 - float()?,
+            "illum" => {
+                material.illumination = match args.as_slice() {
+                    [n] => n
+                        .parse()
+                        .map_err(|_| error(format!("invalid illumination model {n:?}")))?,
+                    _ => return Err(error("illum needs one number".to_string())),
+                };
+            }
+            "map_Kd" => material.diffuse_map = map()?,
+            "map_Ka" => material.ambient_map = map()?,
+            "map_Ks" => material.specular_map = map()?,
+            "map_Bump" | "map_bump" | "bump" | "norm" => material.bump_map = map()?,
+            "map_d" => material.opacity_map = map()?,
+            _ => {}
+        }
+    }
+
+    return Ok(materials);
+}
+
+/// Non-empty statements with comments removed and `\` continuations
+/// joined, each with the line it starts on.
+fn statements(source: &str) -> Vec<(usize, String)> {
+    let mut result = Vec::new();
+    let mut pending: Option<(usize, String)> = None;
+
+    for (i, line) in source.lines().enumerate() {
+        let line = line.split('#').next().unwrap();
+        let (text, continued) = match line.trim_end().strip_suffix('\\') {
+            Some(text) => (text, true),
+            None => (line, false),
+        };
+
+        let (start, mut statement) = pending.take().unwrap_or((i + 1, String::new()));
+        statement.push(' ');
+        statement.push_str(text);
+
+        if continued {
+            pending = Some((start, statement));
+        } else if !statement.trim().is_empty() {
+            result.push((start, statement));
+        }
+    }
+
+    if let Some((start, statement)) = pending {
+        if !statement.trim().is_empty() {
+            result.push((start, statement));
+        }
+    }
+
+    return result;
+}
+
+/// Between `min` and `max` numbers.
+fn floats(args: &[&str], min: usize, max: usize) -> Result<Vec<f32>, String> {
+    if args.len() < min || args.len() > max {
+        let expected = if min == max {
+            format!("{min}")
+        } else {
+            format!("{min} to {max}")
+        };
+        return Err(format!("expected {expected} numbers, found {}", args.len()));
+    }
+
+    return args
+        .iter()
+        .map(|s| s.parse().map_err(|_| format!("invalid number {s:?}")))
+        .collect();
+}
+
+/// A face corner: zero-based position, texture coordinate and normal.
+type Corner = (usize, Option<usize>, Option<usize>);
+
+/// Parses `v`, `v/vt`, `v//vn` or `v/vt/vn` against the number of
+/// positions, texture coordinates and normals defined so far.
+fn parse_corner(corner: &str, counts: [usize; 3]) -> Result<Corner, String> {
+    let mut parts = corner.split('/');
+    let mut index = |kind: usize, required: bool| -> Result<Option<usize>, String> {
+        let text = parts.next().unwrap_or("");
+        if text.is_empty() && !required {
+            return Ok(None);
+        }
+
+        let names = ["vertex", "texture coordinate", "normal"];
+        let i: i64 = text
+            .parse()
+            .map_err(|_| format!("invalid {} index {text:?} in {corner:?}", names[kind]))?;
+        let count = counts[kind] as i64;
+        let resolved = if i < 0 { count + i } else { i - 1 };
+        if i == 0 || resolved < 0 || resolved >= count {
+            return Err(format!(
+                "{} index {i} out of range; {count} defined",
+                names[kind]
+            ));
+        }
+
+        return Ok(Some(resolved as usize));
+    };
+
+    let position = index(0, true)?.unwrap();
+    let uv = index(1, false)?;
+    let normal = index(2, false)?;
+    if parts.next().is_some() {
+        return Err(format!("too many indices in {corner:?}"));
+    }
+
+    return Ok((position, uv, normal));
+}
+
+/// Where a vertex's normal comes from; part of its identity, so the same
+/// position gets a separate vertex per smoothing group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum NormalSource {
+    Given(usize),
+    /// Averaged over the faces of a smoothing group.
+    Smooth(u32),
+    /// The normal of a single face.
+    Flat(usize),
+}
+
+#[derive(Default)]
+struct MeshBuilder {
+    name: String,
+    material: Option<String>,
+    mesh: ObjMesh,
+    has_uvs: bool,
+    faces: usize,
+    vertices: HashMap<(usize, Option<usize>, NormalSource), u32>,
+    /// Vertices whose normals are summed face normals to normalize.
+    generated: Vec<u32>,
+}
+
+impl MeshBuilder {
+    fn face(
+        &mut self,
+        corners: &[Corner],
+        smoothing: u32,
+        positions: &[Vector3D],
+        uvs: &[Vector2D],
+        normals: &[Vector3D],
+    ) {
+        let points: Vec<Vector3D> = corners.iter().map(|c| positions[c.0]).collect();
+        let normal = newell_normal(&points);
+        self.faces += 1;
+
+        let vertices: Vec<u32> = corners
+            .iter()
+            .map(|&(position, uv, given)| {
+                let source = match (given, smoothing) {
+                    (Some(n), _) => NormalSource::Given(n),
+                    (None, 0) => NormalSource::Flat(self.faces),
+                    (None, group) => NormalSource::Smooth(group),
+                };
+                let mesh = &mut self.mesh;
+                let generated = &mut self.generated;
+                let index = *self
+                    .vertices
+                    .entry((position, uv, source))
+                    .or_insert_with(|| {
+                        let index = mesh.positions.len() as u32;
+                        mesh.positions.push(positions[position]);
+                        mesh.uvs.push(uv.map_or(Vector2D::default(), |i| uvs[i]));
+                        mesh.normals.push(match source {
+                            NormalSource::Given(n) => normals[n],
+                            _ => {
+                                generated.push(index);
+                                Vector3D::default()
+                            }
+                        });
+                        return index;
+                    });
+                if given.is_none() {
+                    // Weighted by area, since the Newell normal is not unit.
+                    self.mesh.normals[index as usize] += normal;
+                }
+                self.has_uvs |= uv.is_some();
+
+                return index;
+            })
+            .collect();
+
+        for [a, b, c] in triangulate_face(&points, normal) {
+            self.mesh
+                .indices
+                .extend([vertices[a], vertices[b], vertices[c]]);
+        }
+    }
+
+    /// Moves the mesh built so far, if it has faces, to `meshes` and starts
+    /// a new one with the same name and material.
+    fn finish(&mut self, meshes: &mut Vec<ObjMesh>) {
+        let mut mesh = std::mem::take(&mut self.mesh);
+        for &i in &self.generated {
+            let n = mesh.normals[i as usize];
+            if n.magnitude() > 0.0 {
+                mesh.normals[i as usize] = n.normalize();
+            }
+        }
+        if !self.has_uvs {
+            mesh.uvs.clear();
+        }
+        self.vertices.clear();
+        self.generated.clear();
+        self.has_uvs = false;
+
+        if !mesh.indices.is_empty() {
+            mesh.name = self.name.clone();
+            mesh.material = self.material.clone();
+            meshes.push(mesh);
+        }
+    }
+}
+
+/// The polygon's normal scaled by twice its area, robust for non-planar
+/// and concave polygons.
+fn newell_normal(points: &[Vector3D]) -> Vector3D {
+    let mut normal = Vector3D::default();
+    for i in 0..points.len() {
+        normal += points[i].cross(&points[(i + 1) % points.len()]);
+    }
+
+    return normal;
+}
+
+/// Triangles of a polygon given its Newell normal, with the polygon's
+/// winding. Degenerate polygons fall back to a fan.
+fn triangulate_face(points: &[Vector3D], normal: Vector3D) -> Vec<[usize; 3]> {
+    let fan = || (1..points.len() - 1).map(|i| [0, i, i + 1]).collect();
+    if points.len() == 3 || normal.magnitude() == 0.0 {
+        return fan();
+    }
+
+    // Project onto a basis with `u × v = normal`, where the polygon winds
+    // counterclockwise as ear clipping expects.
+    let n = normal.normalize();
+    let axis = if n.x().abs() < 0.9 {
+        Vector3D::create(1.0, 0.0, 0.0)
+    } else {
+        Vector3D::create(0.0, 1.0, 0.0)
+    };
+    let u = axis.cross(&n).normalize();
+    let v = n.cross(&u);
+    let polygon = Polygon {
+        outer: points
+            .iter()
+            .map(|p| Vector2D::create(p.dot(&u), p.dot(&v)))
+            .collect(),
+        holes: vec![],
+    };
+
+    return triangulate(&polygon).unwrap_or_else(|_| fan());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    const CUBE: &str = "
+# A unit cube with quads and relative indices.
+mtllib cube.mtl
+o cube
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+v 0 0 1
+v 1 0 1
+v 1 1 1
+v 0 1 1
+usemtl red
+s off
+f -8 -5 -6 -7
+f 5 6 7 8
+f 1 2 6 5
+usemtl blue
+f 2 3 7 6
+f 3 4 8 7
+f 4 1 5 8
+";
+
+    fn triangle_normal(mesh: &ObjMesh, t: usize) -> Vector3D {
+        let [a, b, c] = [0, 1, 2].map(|k| mesh.positions[mesh.indices[3 * t + k] as usize]);
+        return (b - a).cross(&(c - a)).normalize();
+    }
+
+    #[test]
+    fn cube_test() {
+        let obj = parse(CUBE).unwrap();
+        assert_eq!(obj.material_libraries, ["cube.mtl"]);
+        assert_eq!(obj.meshes.len(), 2);
+
+        let red = &obj.meshes[0];
+        assert_eq!(red.name, "cube");
+        assert_eq!(red.material.as_deref(), Some("red"));
+        assert_eq!(red.indices.len(), 3 * 6);
+        // Flat shading gives every face its own four vertices.
+        assert_eq!(red.positions.len(), 12);
+        assert!(red.uvs.is_empty());
+
+        // Faces wind outward and their vertices carry the face normal.
+        let center = Vector3D::create(0.5, 0.5, 0.5);
+        for mesh in &obj.meshes {
+            for t in 0..mesh.indices.len() / 3 {
+                let normal = triangle_normal(mesh, t);
+                let a = mesh.indices[3 * t] as usize;
+                assert!(normal.dot(&(mesh.positions[a] - center)) > 0.0);
+                assert_eq!(mesh.normals[a], normal);
+            }
+        }
+    }
+
+    #[test]
+    fn smoothing_test() {
+        let source = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 0 -1\nvt 0 0\nvt 1 0\nvt 1 1\n\
+                      s 1\nf 1/1 2/2 3/3\nf 1/1 4/2 2/2\n";
+        let mesh = &parse(source).unwrap().meshes[0];
+
+        // Shared vertices average the normals of both faces.
+        assert_eq!(mesh.positions.len(), 4);
+        assert_eq!(mesh.uvs.len(), 4);
+        let shared = Vector3D::create(0.0, -1.0, 1.0).normalize();
+        assert_eq!(mesh.normals[0], shared);
+        assert_eq!(mesh.normals[2], Vector3D::create(0.0, 0.0, 1.0));
+
+        // Given normals are kept and split vertices.
+        let source =
+            "v 0 0 0\nv 1 0 0\nv 0 1 0\nvn 0 0 1\nvn 1 0 0\nf 1//1 2//1 3//1\nf 1//2 3//2 2//2\n";
+        let mesh = &parse(source).unwrap().meshes[0];
+        assert_eq!(mesh.positions.len(), 6);
+        assert_eq!(mesh.normals[3], Vector3D::create(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn ngon_test() {
+        // An L-shaped hexagon in the xz plane, which a fan would get wrong.
+        let source = "v 0 0 0\nv 0 0 -2\nv 1 0 -2\nv 1 0 -1\nv 2 0 -1\nv 2 0 0\n\
+                      f 5 4 3 2 1 6\n";
+        let mesh = &parse(source).unwrap().meshes[0];
+        assert_eq!(mesh.indices.len(), 3 * 4);
+
+        let mut area = 0.0;
+        for t in 0..4 {
+            let [a, b, c] = [0, 1, 2].map(|k| mesh.positions[mesh.indices[3 * t + k] as usize]);
+            let cross = (b - a).cross(&(c - a));
+            assert!(cross.y() > 0.0);
+            area += cross.magnitude() / 2.0;
+        }
+        assert!((area - 3.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn error_test() {
+        let error = |source: &str| parse(source).unwrap_err();
+
+        assert_eq!(
+            error("v 0 0 0\nv 1 0 0\n\nf 1 2 3\n"),
+            ParseObjError {
+                line: 4,
+                message: "vertex index 3 out of range; 2 defined".to_string()
+            }
+        );
+        assert_eq!(
+            error("v 0 0\n").to_string(),
+            "line 1: expected 3 to 7 numbers, found 2"
+        );
+        assert_eq!(error("# x\nv 0 0 zero\n").line, 2);
+        assert_eq!(error("v 0 0 0\nf 1 1\n").line, 2);
+        assert_eq!(error("v 0 0 0\nf 1/x 1 1\n").line, 2);
+        assert_eq!(error("v 0 0 0\nf 0 1 1\n").line, 2);
+        assert_eq!(error("v 1 \\\n 2 3\nv 0 0 0 \\\n  1 2 3 4 5\n").line, 3);
+        assert_eq!(error("s maybe\n").line, 1);
+
+        assert_eq!(parse_mtl("Kd 1 0 0\n").unwrap_err().line, 1);
+        assert_eq!(parse_mtl("newmtl a\nNs high\n").unwrap_err().line, 2);
+    }
+
+    #[test]
+    fn load_test() {
+        let dir = env::temp_dir().join(format!("wmb-obj-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("cube.obj"), CUBE).unwrap();
+        fs::write(
+            dir.join("cube.mtl"),
+            "newmtl red\nKd 1 0 0\nNs 10\nd 0.5\nmap_Kd -bm 1 red.png\n\n\
+             newmtl blue\nKd 0 0 1\nTr 0.25\nillum 1\nbump blue_normal.png\n",
+        )
+        .unwrap();
+
+        let obj = load(dir.join("cube.obj")).unwrap();
+        let red = obj.material(&obj.meshes[0]).unwrap();
+        assert_eq!(red.diffuse, Vector3D::create(1.0, 0.0, 0.0));
+        assert_eq!((red.shininess, red.opacity), (10.0, 0.5));
+        assert_eq!(red.diffuse_map.as_deref(), Some("red.png"));
+
+        let blue = obj.material(&obj.meshes[1]).unwrap();
+        assert_eq!((blue.opacity, blue.illumination), (0.75, 1));
+        assert_eq!(blue.bump_map.as_deref(), Some("blue_normal.png"));
+
+        fs::write(dir.join("cube.mtl"), "newmtl red\nKd 1 0\n").unwrap();
+        let error = load(dir.join("cube.obj")).unwrap_err();
+        assert!(error
+            .to_string()
+            .ends_with("cube.mtl: line 2: expected 1 or 3 numbers, found 2"));
+
+        fs::remove_file(dir.join("cube.mtl")).unwrap();
+        assert!(matches!(
+            load(dir.join("cube.obj")),
+            Err(ObjError::Io { path, .. }) if path.ends_with("cube.mtl")
+        ));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,305 @@
+use std::f32::consts::FRAC_PI_2;
+
+use crate::math::{Quaternion, Vector2D, Vector3D};
+
+use super::Camera;
+
+/// Pitch stops short of straight up or down, where yaw is undefined.
+const MAX_PITCH: f32 = FRAC_PI_2 - 0.01;
+
+fn world_up() -> Vector3D {
+    Vector3D::create(0.0, 1.0, 0.0)
+}
+
+/// Yaw about world `+y`, then pitch about the camera's `x` axis.
+fn orientation(yaw: f32, pitch: f32) -> Quaternion {
+    Quaternion::from_axis_angle(&world_up(), yaw)
+        * Quaternion::from_axis_angle(&Vector3D::create(1.0, 0.0, 0.0), pitch)
+}
+
+/// Yaw and pitch that make a camera look along `forward`.
+fn angles(forward: &Vector3D) -> (f32, f32) {
+    let forward = forward.normalize();
+
+    return (
+        (-forward.x()).atan2(-forward.z()),
+        forward.y().clamp(-1.0, 1.0).asin(),
+    );
+}
+
+/// Mouse look shared by the free-moving controllers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Look {
+    yaw: f32,
+    pitch: f32,
+}
+
+impl Look {
+    /// `delta` in pixels, `+x` right and `+y` down like the cursor.
+    fn turn(&mut self, delta: Vector2D, sensitivity: f32) {
+        self.yaw -= delta.x() * sensitivity;
+        self.pitch = (self.pitch - delta.y() * sensitivity).clamp(-MAX_PITCH, MAX_PITCH);
+    }
+}
+
+/// Free flight: moves along the view direction, with `y` movement along
+/// world up.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FlyController {
+    /// World units per second.
+    pub speed: f32,
+    /// Radians per pixel of mouse movement.
+    pub sensitivity: f32,
+    look: Look,
+}
+
+impl FlyController {
+    /// Starts from the camera's current orientation.
+    pub fn new(camera: &Camera, speed: f32, sensitivity: f32) -> Self {
+        let (yaw, pitch) = angles(&camera.forward());
+
+        Self {
+            speed,
+            sensitivity,
+            look: Look { yaw, pitch },
+        }
+    }
+
+    /// `movement` is `x` right, `y` up and `z` forward, each in `[-1, 1]`.
+    pub fn update(&mut self, camera: &mut Camera, movement: Vector3D, look: Vector2D, dt: f32) {
+        self.look.turn(look, self.sensitivity);
+        camera.rotation = orientation(self.look.yaw, self.look.pitch);
+
+        let direction = camera.right() * movement.x()
+            + world_up() * movement.y()
+            + camera.forward() * movement.z();
+        camera.position += direction * (self.speed * dt);
+    }
+}
+
+/// Walking: looks around freely but moves only in the horizontal plane.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FirstPersonController {
+    pub speed: f32,
+    pub sensitivity: f32,
+    look: Look,
+}
+
+impl FirstPersonController {
+    pub fn new(camera: &Camera, speed: f32, sensitivity: f32) -> Self {
+        let (yaw, pitch) = angles(&camera.forward());
+
+        Self {
+            speed,
+            sensitivity,
+            look: Look { yaw, pitch },
+        }
+    }
+
+    /// `movement` is `x` strafe right and `y` forward.
+    pub fn update(&mut self, camera: &mut Camera, movement: Vector2D, look: Vector2D, dt: f32) {
+        self.look.turn(look, self.sensitivity);
+        camera.rotation = orientation(self.look.yaw, self.look.pitch);
+
+        let heading = Quaternion::from_axis_angle(&world_up(), self.look.yaw);
+        let forward = heading.rotate(&Vector3D::create(0.0, 0.0, -1.0));
+        let right = heading.rotate(&Vector3D::create(1.0, 0.0, 0.0));
+        let mut direction = right * movement.x() + forward * movement.y();
+        // Diagonals are no faster than straight lines.
+        if direction.magnitude() > 1.0 {
+            direction = direction.normalize();
+        }
+        camera.position += direction * (self.speed * dt);
+    }
+}
+
+/// Circles a target point at a distance, as in model viewers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrbitController {
+    pub target: Vector3D,
+    pub distance: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    pub sensitivity: f32,
+    yaw: f32,
+    pitch: f32,
+}
+
+impl OrbitController {
+    pub fn new(target: Vector3D, distance: f32, sensitivity: f32) -> Self {
+        Self {
+            target,
+            distance,
+            min_distance: 0.01,
+            max_distance: f32::INFINITY,
+            sensitivity,
+            yaw: 0.0,
+            pitch: 0.0,
+        }
+    }
+
+    /// `rotate` and `pan` in pixels; `zoom` in wheel lines, positive
+    /// moving closer by 10% per line.
+    pub fn update(&mut self, camera: &mut Camera, rotate: Vector2D, zoom: f32, pan: Vector2D) {
+        self.yaw -= rotate.x() * self.sensitivity;
+        self.pitch = (self.pitch - rotate.y() * self.sensitivity).clamp(-MAX_PITCH, MAX_PITCH);
+        self.distance =
+            (self.distance * 0.9f32.powf(zoom)).clamp(self.min_distance, self.max_distance);
+
+        let rotation = orientation(self.yaw, self.pitch);
+        // Dragging moves the target with the cursor, faster when further
+        // away.
+        let scale = self.sensitivity * self.distance;
+        let right = rotation.rotate(&Vector3D::create(1.0, 0.0, 0.0));
+        let up = rotation.rotate(&Vector3D::create(0.0, 1.0, 0.0));
+        self.target += (up * pan.y() - right * pan.x()) * scale;
+
+        camera.rotation = rotation;
+        camera.position = self.target + rotation.rotate(&Vector3D::create(0.0, 0.0, self.distance));
+    }
+}
+
+/// Keeps a 2D target in view: the camera stays put while the target moves
+/// inside the dead zone, then eases after it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Follow2D {
+    /// Half-size of the dead zone around the camera centre, in world units.
+    pub dead_zone: Vector2D,
+    /// Seconds to close about 63% of the remaining gap; 0 snaps.
+    pub smoothing: f32,
+}
+
+impl Follow2D {
+    pub fn new(dead_zone: Vector2D, smoothing: f32) -> Self {
+        Self {
+            dead_zone,
+            smoothing,
+        }
+    }
+
+    pub fn update(&self, camera: &mut Camera, target: Vector2D, dt: f32) {
+        let center = Vector2D::create(camera.position.x(), camera.position.y());
+        let offset = target - center;
+        let excess = |d: f32, zone: f32| d.signum() * (d.abs() - zone).max(0.0);
+        let desired = center
+            + Vector2D::create(
+                excess(offset.x(), self.dead_zone.x()),
+                excess(offset.y(), self.dead_zone.y()),
+            );
+
+        let factor = if self.smoothing > 0.0 {
+            1.0 - (-dt / self.smoothing).exp()
+        } else {
+            1.0
+        };
+        let moved = center + (desired - center) * factor;
+        camera.position = Vector3D::create(moved.x(), moved.y(), camera.position.z());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn camera() -> Camera {
+        Camera::perspective(1.0, 0.1, 100.0, Vector2D::create(100.0, 100.0))
+    }
+
+    fn assert_close(a: Vector3D, b: Vector3D) {
+        assert!((a - b).magnitude() < 1e-4, "{a} != {b}");
+    }
+
+    #[test]
+    fn fly_test() {
+        let mut camera = camera();
+        let mut fly = FlyController::new(&camera, 2.0, 0.01);
+
+        // Turn right by 90° and fly forward for a second.
+        fly.update(
+            &mut camera,
+            Vector3D::create(0.0, 0.0, 1.0),
+            Vector2D::create(FRAC_PI_2 * 100.0, 0.0),
+            1.0,
+        );
+        assert_close(camera.forward(), Vector3D::create(1.0, 0.0, 0.0));
+        assert_close(camera.position, Vector3D::create(2.0, 0.0, 0.0));
+
+        // Looking down, forward flies downward too.
+        fly.update(
+            &mut camera,
+            Vector3D::create(0.0, 0.0, 1.0),
+            Vector2D::create(0.0, 1000.0),
+            0.5,
+        );
+        assert!(camera.position.y() < -0.9);
+    }
+
+    #[test]
+    fn first_person_test() {
+        let mut camera = camera();
+        camera.look_at(&Vector3D::create(0.0, -1.0, -1.0), &world_up());
+        let mut fps = FirstPersonController::new(&camera, 1.0, 0.01);
+
+        fps.update(
+            &mut camera,
+            Vector2D::create(1.0, 1.0),
+            Vector2D::default(),
+            1.0,
+        );
+        assert_eq!(camera.position.y(), 0.0);
+        assert!((camera.position.magnitude() - 1.0).abs() < 1e-5);
+        assert!(camera.forward().y() < -0.7);
+    }
+
+    #[test]
+    fn orbit_test() {
+        let mut camera = camera();
+        let target = Vector3D::create(1.0, 2.0, 3.0);
+        let mut orbit = OrbitController::new(target, 10.0, 0.01);
+
+        orbit.update(
+            &mut camera,
+            Vector2D::create(FRAC_PI_2 * 100.0, 0.0),
+            0.0,
+            Vector2D::default(),
+        );
+        assert_close(camera.position, target + Vector3D::create(-10.0, 0.0, 0.0));
+        assert_close(camera.forward(), Vector3D::create(1.0, 0.0, 0.0));
+
+        orbit.update(&mut camera, Vector2D::default(), 1.0, Vector2D::default());
+        assert!((camera.position.distance(&target) - 9.0).abs() < 1e-4);
+
+        orbit.max_distance = 5.0;
+        orbit.update(&mut camera, Vector2D::default(), -10.0, Vector2D::default());
+        assert_eq!(orbit.distance, 5.0);
+
+        orbit.update(
+            &mut camera,
+            Vector2D::default(),
+            0.0,
+            Vector2D::create(0.0, 10.0),
+        );
+        assert!(orbit.target.y() > target.y());
+    }
+
+    #[test]
+    fn follow_test() {
+        let mut camera = camera();
+        camera.position = Vector3D::create(0.0, 0.0, 10.0);
+        let snap = Follow2D::new(Vector2D::create(2.0, 1.0), 0.0);
+
+        snap.update(&mut camera, Vector2D::create(1.5, -0.5), 0.1);
+        assert_eq!(camera.position, Vector3D::create(0.0, 0.0, 10.0));
+        snap.update(&mut camera, Vector2D::create(5.0, -3.0), 0.1);
+        assert_eq!(camera.position, Vector3D::create(3.0, -2.0, 10.0));
+
+        let smooth = Follow2D::new(Vector2D::default(), 0.5);
+        let mut gaps = Vec::new();
+        for _ in 0..3 {
+            smooth.update(&mut camera, Vector2D::create(13.0, -2.0), 0.1);
+            gaps.push(13.0 - camera.position.x());
+        }
+        assert!(gaps[0] < 10.0 && gaps[1] < gaps[0] && gaps[2] > 0.0);
+        assert!((gaps[1] / gaps[0] - (-0.2f32).exp()).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,228 @@
+//! Cameras and camera controllers.
+//!
+//! A [`Camera`] has a position, an orientation and a [`Projection`], and
+//! converts between world space and screen pixels. The controllers in
+//! [`controller`] move a camera from per-frame input deltas.
+
+#![allow(clippy::needless_return)]
+
+pub mod controller;
+
+pub use controller::{FirstPersonController, FlyController, Follow2D, OrbitController};
+
+use crate::{
+    math::{Matrix4x4F32, Quaternion, Vector2D, Vector3D},
+    spatial::{Frustum, Ray},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    /// `fov_y` is the vertical field of view in radians.
+    Perspective { fov_y: f32, near: f32, far: f32 },
+    /// `height` is the visible height in world units.
+    Orthographic { height: f32, near: f32, far: f32 },
+}
+
+/// A viewpoint looking down its local `-z` axis with `+y` up, following
+/// the [`Matrix4x4F32`] conventions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    pub position: Vector3D,
+    pub rotation: Quaternion,
+    pub projection: Projection,
+    /// Width and height of the target in pixels.
+    pub viewport: Vector2D,
+}
+
+impl Camera {
+    pub fn perspective(fov_y: f32, near: f32, far: f32, viewport: Vector2D) -> Self {
+        Self {
+            position: Vector3D::default(),
+            rotation: Quaternion::identity(),
+            projection: Projection::Perspective { fov_y, near, far },
+            viewport,
+        }
+    }
+
+    pub fn orthographic(height: f32, near: f32, far: f32, viewport: Vector2D) -> Self {
+        Self {
+            position: Vector3D::default(),
+            rotation: Quaternion::identity(),
+            projection: Projection::Orthographic { height, near, far },
+            viewport,
+        }
+    }
+
+    pub fn aspect(&self) -> f32 {
+        self.viewport.x() / self.viewport.y()
+    }
+
+    pub fn forward(&self) -> Vector3D {
+        self.rotation.rotate(&Vector3D::create(0.0, 0.0, -1.0))
+    }
+
+    pub fn right(&self) -> Vector3D {
+        self.rotation.rotate(&Vector3D::create(1.0, 0.0, 0.0))
+    }
+
+    pub fn up(&self) -> Vector3D {
+        self.rotation.rotate(&Vector3D::create(0.0, 1.0, 0.0))
+    }
+
+    /// Turns the camera toward `target`, keeping `up` above.
+    pub fn look_at(&mut self, target: &Vector3D, up: &Vector3D) {
+        let forward = (*target - self.position).normalize();
+        let right = forward.cross(up).normalize();
+        let up = right.cross(&forward);
+
+        let mut basis = Matrix4x4F32::identity();
+        for (j, axis) in [right, up, -forward].iter().enumerate() {
+            basis[(0, j)] = axis.x();
+            basis[(1, j)] = axis.y();
+            basis[(2, j)] = axis.z();
+        }
+        self.rotation = Quaternion::from_rotation_matrix(&basis);
+    }
+
+    /// World to camera space.
+    pub fn view_matrix(&self) -> Matrix4x4F32 {
+        self.rotation.conjugate().to_matrix() * Matrix4x4F32::translation(&-self.position)
+    }
+
+    /// Camera to clip space.
+    pub fn projection_matrix(&self) -> Matrix4x4F32 {
+        match self.projection {
+            Projection::Perspective { fov_y, near, far } => {
+                Matrix4x4F32::perspective(fov_y, self.aspect(), near, far)
+            }
+            Projection::Orthographic { height, near, far } => {
+                let (half_w, half_h) = (height * self.aspect() / 2.0, height / 2.0);
+                Matrix4x4F32::orthographic(-half_w, half_w, -half_h, half_h, near, far)
+            }
+        }
+    }
+
+    pub fn view_projection(&self) -> Matrix4x4F32 {
+        self.projection_matrix() * self.view_matrix()
+    }
+
+    /// The visible volume in world space, for culling.
+    pub fn frustum(&self) -> Frustum {
+        Frustum::from_matrix(&self.view_projection())
+    }
+
+    /// The ray from the near plane through a pixel, with `(0, 0)` the
+    /// top-left corner of the viewport and a unit direction.
+    pub fn screen_to_ray(&self, cursor: Vector2D) -> Ray {
+        let x = 2.0 * cursor.x() / self.viewport.x() - 1.0;
+        let y = 1.0 - 2.0 * cursor.y() / self.viewport.y();
+
+        let inverse = self.view_projection().inverse().expect("invertible camera");
+        let near = inverse.transform_point(&Vector3D::create(x, y, -1.0));
+        let far = inverse.transform_point(&Vector3D::create(x, y, 1.0));
+
+        return Ray::new(near, (far - near).normalize());
+    }
+
+    /// The pixel a point projects to, or `None` if it is behind the
+    /// camera. Points outside the viewport map outside it.
+    pub fn world_to_screen(&self, point: &Vector3D) -> Option<Vector2D> {
+        let clip = self.view_projection() * [point.x(), point.y(), point.z(), 1.0];
+        if clip[3] <= 0.0 {
+            return None;
+        }
+
+        let (x, y) = (clip[0] / clip[3], clip[1] / clip[3]);
+        return Some(Vector2D::create(
+            (x + 1.0) / 2.0 * self.viewport.x(),
+            (1.0 - y) / 2.0 * self.viewport.y(),
+        ));
+    }
+
+    /// The height in pixels of a length `size` facing the camera at
+    /// `distance` along its view direction; infinite at or behind the
+    /// camera in perspective.
+    pub fn projected_size(&self, size: f32, distance: f32) -> f32 {
+        match self.projection {
+            Projection::Perspective { fov_y, .. } => {
+                if distance <= 0.0 {
+                    return f32::INFINITY;
+                }
+                size * self.viewport.y() / (2.0 * distance * (fov_y / 2.0).tan())
+            }
+            Projection::Orthographic { height, .. } => size * self.viewport.y() / height,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+
+    fn assert_close(a: Vector3D, b: Vector3D) {
+        assert!((a - b).magnitude() < 1e-4, "{a} != {b}");
+    }
+
+    #[test]
+    fn view_test() {
+        let mut camera = Camera::perspective(FRAC_PI_2, 0.1, 100.0, Vector2D::create(800.0, 600.0));
+        camera.position = Vector3D::create(1.0, 2.0, 3.0);
+        camera.look_at(
+            &Vector3D::create(4.0, 2.0, -1.0),
+            &Vector3D::create(0.0, 1.0, 0.0),
+        );
+
+        let expected = Matrix4x4F32::look_at(
+            &camera.position,
+            &Vector3D::create(4.0, 2.0, -1.0),
+            &Vector3D::create(0.0, 1.0, 0.0),
+        );
+        for (a, b) in camera.view_matrix().iter().zip(expected.iter()) {
+            assert!((a - b).abs() < 1e-5);
+        }
+        assert_close(camera.forward(), Vector3D::create(0.6, 0.0, -0.8));
+
+        let frustum = camera.frustum();
+        assert!(frustum.contains_point(camera.position + camera.forward() * 50.0));
+        assert!(!frustum.contains_point(camera.position - camera.forward()));
+        assert!(!frustum.contains_point(camera.position + camera.forward() * 101.0));
+    }
+
+    #[test]
+    fn unproject_test() {
+        let viewport = Vector2D::create(800.0, 600.0);
+        let mut camera = Camera::perspective(FRAC_PI_2, 0.1, 100.0, viewport);
+        camera.position = Vector3D::create(0.0, 0.0, 5.0);
+
+        let center = camera.screen_to_ray(Vector2D::create(400.0, 300.0));
+        assert_close(center.direction, Vector3D::create(0.0, 0.0, -1.0));
+        assert_close(center.origin, Vector3D::create(0.0, 0.0, 4.9));
+
+        // With a 90° vertical field of view the top edge is at 45°.
+        let top = camera.screen_to_ray(Vector2D::create(400.0, 0.0));
+        assert_close(top.direction, Vector3D::create(0.0, 1.0, -1.0).normalize());
+
+        let point = Vector3D::create(1.0, -0.5, -2.0);
+        let pixel = camera.world_to_screen(&point).unwrap();
+        let ray = camera.screen_to_ray(pixel);
+        let to_point = (point - ray.origin).normalize();
+        assert_close(ray.direction, to_point);
+        assert!(camera
+            .world_to_screen(&Vector3D::create(0.0, 0.0, 10.0))
+            .is_none());
+
+        let ortho = Camera::orthographic(10.0, 0.1, 100.0, viewport);
+        let ray = ortho.screen_to_ray(Vector2D::create(800.0, 0.0));
+        assert_close(ray.direction, Vector3D::create(0.0, 0.0, -1.0));
+        assert_close(
+            ray.origin,
+            Vector3D::create(10.0 * 4.0 / 3.0 / 2.0, 5.0, -0.1),
+        );
+
+        assert!((camera.projected_size(1.0, 3.0) - 100.0).abs() < 1e-3);
+        assert_eq!(camera.projected_size(1.0, 0.0), f32::INFINITY);
+        assert_eq!(ortho.projected_size(1.0, 3.0), 60.0);
+    }
+}
@@ -0,0 +1,210 @@
+use std::sync::Mutex;
+
+use super::{storage::Component, world::Bundle, Entity, World};
+
+type Command = Box<dyn FnOnce(&mut World) + Send>;
+
+/// Structural changes recorded for later, in order.
+#[derive(Default)]
+pub struct CommandBuffer {
+    commands: Vec<Command>,
+}
+
+impl CommandBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    pub fn push(&mut self, command: impl FnOnce(&mut World) + Send + 'static) {
+        self.commands.push(Box::new(command));
+    }
+
+    /// Moves the commands of `other` to the end of this buffer.
+    pub fn append(&mut self, other: &mut CommandBuffer) {
+        self.commands.append(&mut other.commands);
+    }
+
+    pub(crate) fn apply(self, world: &mut World) {
+        for command in self.commands {
+            command(world);
+        }
+    }
+}
+
+/// Records changes to a [`World`] that is only borrowed shared, e.g. by a
+/// running query. Entities spawned here get their IDs immediately.
+///
+/// Commands handed to a system are applied after the schedule run;
+/// otherwise take them with [`into_buffer`](Self::into_buffer).
+pub struct Commands<'w> {
+    world: &'w World,
+    buffer: CommandBuffer,
+    /// Receives the commands on drop.
+    sink: Option<&'w Mutex<CommandBuffer>>,
+}
+
+impl<'w> Commands<'w> {
+    pub(crate) fn new(world: &'w World) -> Self {
+        Self {
+            world,
+            buffer: CommandBuffer::new(),
+            sink: None,
+        }
+    }
+
+    pub(crate) fn with_sink(world: &'w World, sink: &'w Mutex<CommandBuffer>) -> Self {
+        Self {
+            world,
+            buffer: CommandBuffer::new(),
+            sink: Some(sink),
+        }
+    }
+
+    pub fn spawn<B: Bundle>(&mut self, bundle: B) -> Entity {
+        let entity = self.world.reserve();
+        self.buffer
+            .push(move |world| world.spawn_reserved(entity, bundle));
+
+        return entity;
+    }
+
+    pub fn despawn(&mut self, entity: Entity) {
+        self.buffer.push(move |world| {
+            world.despawn(entity);
+        });
+    }
+
+    pub fn insert<T: Component>(&mut self, entity: Entity, component: T) {
+        self.buffer.push(move |world| {
+            world.insert(entity, component);
+        });
+    }
+
+    pub fn remove<T: Component>(&mut self, entity: Entity) {
+        self.buffer.push(move |world| {
+            world.remove::<T>(entity);
+        });
+    }
+
+    pub fn insert_resource<R: Component>(&mut self, resource: R) {
+        self.buffer
+            .push(move |world| world.insert_resource(resource));
+    }
+
+    pub fn remove_resource<R: Component>(&mut self) {
+        self.buffer.push(|world| {
+            world.remove_resource::<R>();
+        });
+    }
+
+    pub fn push(&mut self, command: impl FnOnce(&mut World) + Send + 'static) {
+        self.buffer.push(command);
+    }
+
+    /// The recorded commands, to pass to [`World::apply`].
+    pub fn into_buffer(mut self) -> CommandBuffer {
+        std::mem::take(&mut self.buffer)
+    }
+}
+
+impl Drop for Commands<'_> {
+    fn drop(&mut self) {
+        if let Some(sink) = self.sink {
+            sink.lock().unwrap().append(&mut self.buffer);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct A(u32);
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Log(Vec<u32>);
+
+    #[test]
+    fn order_test() {
+        let mut world = World::new();
+        world.insert_resource(Log::default());
+
+        let mut first = CommandBuffer::new();
+        let mut second = CommandBuffer::new();
+        for i in 0..3 {
+            first.push(move |world| world.resource_mut::<Log>().unwrap().0.push(i));
+            second.push(move |world| world.resource_mut::<Log>().unwrap().0.push(i + 10));
+        }
+        first.append(&mut second);
+        assert!(second.is_empty());
+
+        world.apply(first);
+        assert_eq!(world.resource::<Log>().unwrap().0, [0, 1, 2, 10, 11, 12]);
+    }
+
+    #[test]
+    fn later_commands_see_earlier_test() {
+        let mut world = World::new();
+        let mut commands = world.commands();
+        let entity = commands.spawn((A(0),));
+        commands.insert(entity, A(1));
+        commands.remove::<A>(entity);
+        commands.insert(entity, A(2));
+        commands.insert_resource(Log(vec![1]));
+        commands.remove_resource::<Log>();
+        commands.insert_resource(Log(vec![2]));
+        let buffer = commands.into_buffer();
+
+        world.apply(buffer);
+        assert_eq!(*world.get::<A>(entity).unwrap(), A(2));
+        assert_eq!(*world.resource::<Log>().unwrap(), Log(vec![2]));
+    }
+
+    #[test]
+    fn despawn_gone_test() {
+        let mut world = World::new();
+        let gone = world.spawn((A(0),));
+        world.despawn(gone);
+
+        let mut commands = world.commands();
+        let spawned = commands.spawn((A(1),));
+        commands.despawn(spawned);
+        commands.despawn(spawned);
+        commands.insert(spawned, A(2));
+        commands.despawn(gone);
+        commands.remove::<A>(gone);
+        let buffer = commands.into_buffer();
+
+        world.apply(buffer);
+        assert!(!world.contains(spawned));
+        assert!(!world.contains(gone));
+        assert!(world.is_empty());
+    }
+
+    #[test]
+    fn sink_test() {
+        let world = World::new();
+        let sink = Mutex::new(CommandBuffer::new());
+        {
+            let mut commands = Commands::with_sink(&world, &sink);
+            commands.spawn(());
+            commands.despawn(world.reserve());
+        }
+        assert_eq!(sink.lock().unwrap().len(), 2);
+
+        // Taking the buffer leaves nothing for the sink.
+        let mut commands = Commands::with_sink(&world, &sink);
+        commands.spawn(());
+        assert_eq!(commands.into_buffer().len(), 1);
+        assert_eq!(sink.into_inner().unwrap().len(), 2);
+    }
+}
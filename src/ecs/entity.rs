@@ -0,0 +1,181 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+/// A generational entity ID.
+///
+/// The index of a despawned entity is reused with a higher generation, so
+/// an old `Entity` never refers to whatever replaced it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Entity {
+    index: u32,
+    generation: u32,
+}
+
+impl Entity {
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+impl Display for Entity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}v{}", self.index, self.generation)
+    }
+}
+
+/// Where an entity's components live: a row of an archetype.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Location {
+    pub(crate) archetype: usize,
+    pub(crate) row: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Meta {
+    generation: u32,
+    location: Option<Location>,
+}
+
+/// Allocates entity IDs and tracks where each live entity is stored.
+#[derive(Debug, Default)]
+pub(crate) struct Entities {
+    meta: Vec<Meta>,
+    free: Vec<u32>,
+    /// Indices handed out by [`reserve`](Self::reserve) beyond `meta`.
+    reserved: AtomicU32,
+}
+
+impl Entities {
+    pub(crate) fn alloc(&mut self) -> Entity {
+        self.flush();
+
+        if let Some(index) = self.free.pop() {
+            return Entity {
+                index,
+                generation: self.meta[index as usize].generation,
+            };
+        }
+
+        self.meta.push(Meta {
+            generation: 0,
+            location: None,
+        });
+        return Entity {
+            index: self.meta.len() as u32 - 1,
+            generation: 0,
+        };
+    }
+
+    /// An ID usable before the entity exists, e.g. from a command buffer.
+    /// It becomes live, without components, at the next [`flush`](Self::flush).
+    pub(crate) fn reserve(&self) -> Entity {
+        let offset = self.reserved.fetch_add(1, Ordering::Relaxed);
+
+        return Entity {
+            index: self.meta.len() as u32 + offset,
+            generation: 0,
+        };
+    }
+
+    /// Makes reserved entities live; returns them so they can be placed.
+    pub(crate) fn flush(&mut self) -> Vec<Entity> {
+        let count = std::mem::take(self.reserved.get_mut());
+        let start = self.meta.len() as u32;
+        self.meta.extend((0..count).map(|_| Meta {
+            generation: 0,
+            location: None,
+        }));
+
+        return (start..start + count)
+            .map(|index| Entity {
+                index,
+                generation: 0,
+            })
+            .collect();
+    }
+
+    /// Frees the ID; returns `false` if it was not live.
+    pub(crate) fn free(&mut self, entity: Entity) -> bool {
+        if !self.contains(entity) {
+            return false;
+        }
+
+        let meta = &mut self.meta[entity.index as usize];
+        meta.generation += 1;
+        meta.location = None;
+        self.free.push(entity.index);
+
+        return true;
+    }
+
+    /// Whether the entity is live, i.e. allocated and placed.
+    pub(crate) fn contains(&self, entity: Entity) -> bool {
+        self.meta
+            .get(entity.index as usize)
+            .is_some_and(|m| m.generation == entity.generation && m.location.is_some())
+    }
+
+    pub(crate) fn location(&self, entity: Entity) -> Option<Location> {
+        let meta = self.meta.get(entity.index as usize)?;
+        if meta.generation != entity.generation {
+            return None;
+        }
+
+        return meta.location;
+    }
+
+    pub(crate) fn set_location(&mut self, entity: Entity, location: Location) {
+        self.meta[entity.index as usize].location = Some(location);
+    }
+
+    /// Live entities.
+    pub(crate) fn len(&self) -> usize {
+        self.meta.iter().filter(|m| m.location.is_some()).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generation_test() {
+        let mut entities = Entities::default();
+        let a = entities.alloc();
+        entities.set_location(
+            a,
+            Location {
+                archetype: 0,
+                row: 0,
+            },
+        );
+        assert!(entities.contains(a));
+
+        assert!(entities.free(a));
+        assert!(!entities.free(a));
+        assert!(!entities.contains(a));
+
+        let b = entities.alloc();
+        assert_eq!(b.index(), a.index());
+        assert_eq!(b.generation(), 1);
+        assert_eq!(entities.location(a), None);
+        assert_eq!(b.to_string(), "0v1");
+    }
+
+    #[test]
+    fn reserve_test() {
+        let mut entities = Entities::default();
+        entities.alloc();
+
+        let (r1, r2) = (entities.reserve(), entities.reserve());
+        assert_eq!((r1.index(), r2.index()), (1, 2));
+        assert_eq!(entities.flush(), [r1, r2]);
+        assert_eq!(entities.alloc().index(), 3);
+    }
+}
@@ -0,0 +1,292 @@
+//! An archetype-based entity-component-system.
+//!
+//! A [`World`] stores entities, identified by generational [`Entity`] IDs,
+//! with any set of components. Entities with the same component types
+//! share an [`Archetype`] whose components are stored column by column.
+//! [`Query`] visits the entities with given components, optionally
+//! filtered by [`With`], [`Without`], [`Added`] and [`Changed`].
+//! Resources are singletons keyed by type, and [`Commands`] defer
+//! structural changes until the world can be borrowed mutably.
+//!
+//! Functions taking [`Query`], [`Res`], [`ResMut`] and [`Commands`]
+//! arguments are systems. A [`Schedule`] derives what each one reads and
+//! writes from its signature and runs systems that do not conflict on
+//! several threads, in the order given by labels.
+
+#![allow(clippy::needless_return)]
+
+pub mod command;
+pub mod entity;
+pub mod query;
+pub mod schedule;
+pub mod storage;
+pub mod system;
+pub mod world;
+
+pub use command::{CommandBuffer, Commands};
+pub use entity::Entity;
+pub use query::{Access, Added, Changed, Query, QueryData, QueryFilter, With, Without};
+pub use schedule::{Ambiguity, Schedule, ScheduleError, SystemBuilder};
+pub use storage::{Archetype, Component};
+pub use system::{IntoSystem, System, SystemParam};
+pub use world::{Bundle, Mut, Ref, Res, ResMut, World};
+
+use crate::math::{Matrix4x4F32, Vector3D};
+
+/// Position, orientation and size of an entity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub position: Vector3D,
+    /// Euler angles in radians, applied about X, then Y, then Z.
+    pub rotation: Vector3D,
+    pub scale: Vector3D,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            position: Vector3D::default(),
+            rotation: Vector3D::default(),
+            scale: Vector3D::create(1.0, 1.0, 1.0),
+        }
+    }
+}
+
+impl Transform {
+    pub fn from_position(position: Vector3D) -> Self {
+        Self {
+            position,
+            ..Self::default()
+        }
+    }
+
+    /// Scale, then rotate, then translate.
+    pub fn matrix(&self) -> Matrix4x4F32 {
+        let rotation = Matrix4x4F32::rotation_z(self.rotation.z())
+            * Matrix4x4F32::rotation_y(self.rotation.y())
+            * Matrix4x4F32::rotation_x(self.rotation.x());
+
+        return Matrix4x4F32::translation(&self.position)
+            * rotation
+            * Matrix4x4F32::scale(&self.scale);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Velocity(Vector3D);
+
+    #[derive(Debug, PartialEq)]
+    struct Name(&'static str);
+
+    struct Frozen;
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Time(f32);
+
+    fn v(x: f32, y: f32, z: f32) -> Vector3D {
+        Vector3D::create(x, y, z)
+    }
+
+    #[test]
+    fn spawn_despawn_test() {
+        let mut world = World::new();
+        let a = world.spawn((Transform::default(), Name("a")));
+        let b = world.spawn((Transform::default(), Name("b")));
+        let c = world.spawn((Name("c"),));
+        assert_eq!(world.len(), 3);
+
+        assert!(world.despawn(a));
+        assert!(!world.despawn(a));
+        assert!(!world.contains(a));
+        // `b` moved into `a`'s row and must still be found.
+        assert_eq!(*world.get::<Name>(b).unwrap(), Name("b"));
+        assert!(world.get::<Transform>(c).is_none());
+
+        let d = world.spawn((Name("d"),));
+        assert_eq!(d.index(), a.index());
+        assert!(world.get::<Name>(a).is_none());
+        assert_eq!(*world.get::<Name>(d).unwrap(), Name("d"));
+    }
+
+    #[test]
+    #[should_panic(expected = "duplicate")]
+    fn duplicate_bundle_test() {
+        World::new().spawn((Name("a"), Name("b")));
+    }
+
+    #[test]
+    fn insert_remove_test() {
+        let mut world = World::new();
+        let a = world.spawn((Name("a"),));
+        let b = world.spawn((Name("b"),));
+
+        assert!(world.insert(a, Velocity(v(1.0, 0.0, 0.0))));
+        assert!(world.has::<Velocity>(a));
+        assert_eq!(*world.get::<Name>(b).unwrap(), Name("b"));
+        assert!(world.insert(a, Velocity(v(2.0, 0.0, 0.0))));
+        assert_eq!(world.get::<Velocity>(a).unwrap().0, v(2.0, 0.0, 0.0));
+
+        assert_eq!(
+            world.remove::<Velocity>(a),
+            Some(Velocity(v(2.0, 0.0, 0.0)))
+        );
+        assert_eq!(world.remove::<Velocity>(a), None);
+        assert_eq!(*world.get::<Name>(a).unwrap(), Name("a"));
+        assert_eq!(world.remove::<Name>(a), Some(Name("a")));
+        assert!(world.contains(a));
+        assert!(world.has::<Name>(b));
+    }
+
+    #[test]
+    fn query_test() {
+        let mut world = World::new();
+        for i in 0..10 {
+            let e = world.spawn((Transform::from_position(v(i as f32, 0.0, 0.0)),));
+            if i % 2 == 0 {
+                world.insert(e, Velocity(v(0.0, 1.0, 0.0)));
+            }
+            if i % 4 == 0 {
+                world.insert(e, Frozen);
+            }
+        }
+
+        world
+            .query_filtered::<(&mut Transform, &Velocity), Without<Frozen>>()
+            .for_each(|(mut transform, velocity)| transform.position += velocity.0);
+
+        let mut moved = 0;
+        world.query::<(Entity, &Transform)>().for_each(|(_, t)| {
+            if t.position.y() == 1.0 {
+                moved += 1;
+            }
+        });
+        assert_eq!(moved, 2);
+        assert_eq!(
+            world.query_filtered::<&Transform, With<Frozen>>().count(),
+            3
+        );
+        assert_eq!(
+            world
+                .query_filtered::<Entity, (With<Velocity>, Without<Frozen>)>()
+                .count(),
+            2
+        );
+
+        let first = world.query::<Entity>().count();
+        assert_eq!(first, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "both read and written")]
+    fn query_conflict_test() {
+        World::new().query::<(&mut Transform, &Transform)>();
+    }
+
+    #[test]
+    fn changed_test() {
+        let mut world = World::new();
+        let entities: Vec<Entity> = (0..4)
+            .map(|_| world.spawn((Transform::default(),)))
+            .collect();
+        assert_eq!(
+            world
+                .query_filtered::<&Transform, Changed<Transform>>()
+                .count(),
+            4
+        );
+
+        world.clear_trackers();
+        assert_eq!(
+            world
+                .query_filtered::<&Transform, Changed<Transform>>()
+                .count(),
+            0
+        );
+
+        // Reading through `Mut` does not count as a change.
+        world
+            .query::<&mut Transform>()
+            .for_each(|t| assert_eq!(t.scale.x(), 1.0));
+        world.get_mut::<Transform>(entities[1]).unwrap().position = v(1.0, 2.0, 3.0);
+        world
+            .query::<(Entity, &mut Transform)>()
+            .get(entities[3], |(_, mut t)| t.scale = v(2.0, 2.0, 2.0));
+
+        let mut changed = Vec::new();
+        world
+            .query_filtered::<Entity, Changed<Transform>>()
+            .for_each(|e| changed.push(e));
+        changed.sort();
+        assert_eq!(changed, [entities[1], entities[3]]);
+
+        // The filter may name a component the query writes.
+        world
+            .query_filtered::<&mut Transform, Changed<Transform>>()
+            .for_each(|mut t| t.position = Vector3D::default());
+        assert_eq!(
+            world.get::<Transform>(entities[1]).unwrap().position,
+            Vector3D::default()
+        );
+    }
+
+    #[test]
+    fn resource_test() {
+        let mut world = World::new();
+        assert!(world.resource::<Time>().is_none());
+
+        world.insert_resource(Time(0.5));
+        world.resource_mut::<Time>().unwrap().0 += 1.0;
+        assert_eq!(*world.resource::<Time>().unwrap(), Time(1.5));
+        assert_eq!(world.remove_resource::<Time>(), Some(Time(1.5)));
+        assert!(!world.contains_resource::<Time>());
+    }
+
+    #[test]
+    fn commands_test() {
+        let mut world = World::new();
+        let a = world.spawn((Name("a"), Velocity(v(0.0, 0.0, 0.0))));
+        let b = world.spawn((Name("b"),));
+
+        let mut commands = world.commands();
+        let mut spawned = Vec::new();
+        world.query::<(Entity, &Name)>().for_each(|(e, name)| {
+            if name.0 == "a" {
+                commands.despawn(e);
+            } else {
+                spawned.push(commands.spawn((Name("child"), Transform::default())));
+                commands.insert(e, Frozen);
+            }
+        });
+        let empty = commands.spawn(());
+        commands.insert_resource(Time(2.0));
+        let buffer = commands.into_buffer();
+        assert_eq!(buffer.len(), 5);
+        assert!(!world.contains(spawned[0]));
+
+        world.apply(buffer);
+        assert!(!world.contains(a));
+        assert!(world.has::<Frozen>(b));
+        assert_eq!(*world.get::<Name>(spawned[0]).unwrap(), Name("child"));
+        assert!(world.contains(empty));
+        assert_eq!(world.len(), 3);
+        assert_eq!(*world.resource::<Time>().unwrap(), Time(2.0));
+    }
+
+    #[test]
+    fn transform_test() {
+        let transform = Transform {
+            position: v(1.0, 2.0, 3.0),
+            rotation: v(0.0, 0.0, FRAC_PI_2),
+            scale: v(2.0, 2.0, 2.0),
+        };
+
+        let p = transform.matrix().transform_point(&v(1.0, 0.0, 0.0));
+        assert_eq!(p, v(1.0, 4.0, 3.0));
+    }
+}
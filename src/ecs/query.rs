@@ -0,0 +1,539 @@
+use std::{
+    any::{type_name, TypeId},
+    marker::PhantomData,
+    sync::{RwLockReadGuard, RwLockWriteGuard},
+};
+
+use super::{
+    storage::{typed, typed_mut, Archetype, Column, Component},
+    world::{Mut, World},
+    Entity,
+};
+
+/// The component and resource types something reads and writes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Access {
+    components: AccessSet,
+    resources: AccessSet,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct AccessSet {
+    reads: Vec<(TypeId, &'static str)>,
+    writes: Vec<(TypeId, &'static str)>,
+}
+
+impl AccessSet {
+    fn add_read<T: 'static>(&mut self) {
+        let id = TypeId::of::<T>();
+        assert!(
+            !self.writes.iter().any(|(w, _)| *w == id),
+            "{} is both read and written",
+            type_name::<T>()
+        );
+
+        if !self.reads.iter().any(|(r, _)| *r == id) {
+            self.reads.push((id, type_name::<T>()));
+        }
+    }
+
+    fn add_write<T: 'static>(&mut self) {
+        let id = TypeId::of::<T>();
+        assert!(
+            !self.reads.iter().chain(&self.writes).any(|(a, _)| *a == id),
+            "{} is accessed more than once while written",
+            type_name::<T>()
+        );
+
+        self.writes.push((id, type_name::<T>()));
+    }
+
+    fn conflicts(&self, other: &AccessSet, names: &mut Vec<&'static str>) {
+        for (id, name) in &self.writes {
+            if other
+                .reads
+                .iter()
+                .chain(&other.writes)
+                .any(|(o, _)| o == id)
+            {
+                names.push(*name);
+            }
+        }
+        for (id, name) in &other.writes {
+            if self.reads.iter().any(|(s, _)| s == id) {
+                names.push(*name);
+            }
+        }
+    }
+}
+
+impl Access {
+    /// Panics if component `T` is already written.
+    pub fn add_read<T: 'static>(&mut self) {
+        self.components.add_read::<T>();
+    }
+
+    /// Panics if component `T` is already read or written.
+    pub fn add_write<T: 'static>(&mut self) {
+        self.components.add_write::<T>();
+    }
+
+    /// A read needed only to evaluate a filter, which is covered by a
+    /// write of the same type.
+    pub fn add_filter_read<T: 'static>(&mut self) {
+        let id = TypeId::of::<T>();
+        let set = &mut self.components;
+        if !set.reads.iter().chain(&set.writes).any(|(a, _)| *a == id) {
+            set.reads.push((id, type_name::<T>()));
+        }
+    }
+
+    /// Panics if resource `R` is already written.
+    pub fn add_resource_read<R: 'static>(&mut self) {
+        self.resources.add_read::<R>();
+    }
+
+    /// Panics if resource `R` is already read or written.
+    pub fn add_resource_write<R: 'static>(&mut self) {
+        self.resources.add_write::<R>();
+    }
+
+    /// Component types read but not written.
+    pub fn reads(&self) -> impl Iterator<Item = TypeId> + '_ {
+        self.components.reads.iter().map(|(id, _)| *id)
+    }
+
+    pub fn writes(&self) -> impl Iterator<Item = TypeId> + '_ {
+        self.components.writes.iter().map(|(id, _)| *id)
+    }
+
+    /// Names of the types one side writes and the other accesses.
+    pub fn conflicts(&self, other: &Access) -> Vec<&'static str> {
+        let mut names = Vec::new();
+        self.components.conflicts(&other.components, &mut names);
+        self.resources.conflicts(&other.resources, &mut names);
+
+        return names;
+    }
+
+    pub fn is_compatible(&self, other: &Access) -> bool {
+        self.conflicts(other).is_empty()
+    }
+}
+
+/// What a query fetches for each entity: `Entity`, `&T`, `&mut T` (as
+/// [`Mut<T>`]), or a tuple of up to eight of these.
+pub trait QueryData {
+    /// Locked columns of one archetype.
+    type State<'w>;
+    type Item<'s>;
+
+    fn access(access: &mut Access);
+    fn matches(archetype: &Archetype) -> bool;
+    fn lock<'w>(archetype: &'w Archetype, tick: u32) -> Self::State<'w>;
+    fn fetch<'s>(state: &'s mut Self::State<'_>, row: usize) -> Self::Item<'s>;
+}
+
+impl QueryData for Entity {
+    type State<'w> = &'w [Entity];
+    type Item<'s> = Entity;
+
+    fn access(_: &mut Access) {}
+
+    fn matches(_: &Archetype) -> bool {
+        true
+    }
+
+    fn lock<'w>(archetype: &'w Archetype, _: u32) -> Self::State<'w> {
+        archetype.entities()
+    }
+
+    fn fetch(state: &mut Self::State<'_>, row: usize) -> Entity {
+        state[row]
+    }
+}
+
+impl<T: Component> QueryData for &T {
+    type State<'w> = RwLockReadGuard<'w, Box<dyn Column>>;
+    type Item<'s> = &'s T;
+
+    fn access(access: &mut Access) {
+        access.add_read::<T>();
+    }
+
+    fn matches(archetype: &Archetype) -> bool {
+        archetype.contains(TypeId::of::<T>())
+    }
+
+    fn lock<'w>(archetype: &'w Archetype, _: u32) -> Self::State<'w> {
+        let i = archetype.column_index(TypeId::of::<T>()).unwrap();
+
+        return archetype.columns[i].read().unwrap();
+    }
+
+    fn fetch<'s>(state: &'s mut Self::State<'_>, row: usize) -> &'s T {
+        &typed::<T>(&***state).data[row]
+    }
+}
+
+impl<T: Component> QueryData for &mut T {
+    type State<'w> = (RwLockWriteGuard<'w, Box<dyn Column>>, u32);
+    type Item<'s> = Mut<'s, T>;
+
+    fn access(access: &mut Access) {
+        access.add_write::<T>();
+    }
+
+    fn matches(archetype: &Archetype) -> bool {
+        archetype.contains(TypeId::of::<T>())
+    }
+
+    fn lock<'w>(archetype: &'w Archetype, tick: u32) -> Self::State<'w> {
+        let i = archetype.column_index(TypeId::of::<T>()).unwrap();
+
+        return (archetype.columns[i].write().unwrap(), tick);
+    }
+
+    fn fetch<'s>((guard, tick): &'s mut Self::State<'_>, row: usize) -> Mut<'s, T> {
+        let column = typed_mut::<T>(&mut ***guard);
+
+        return Mut::new(&mut column.data[row], &mut column.changed[row], *tick);
+    }
+}
+
+macro_rules! query_data {
+    ($($name:ident $index:tt),*) => {
+        impl<$($name: QueryData),*> QueryData for ($($name,)*) {
+            type State<'w> = ($($name::State<'w>,)*);
+            type Item<'s> = ($($name::Item<'s>,)*);
+
+            fn access(access: &mut Access) {
+                $($name::access(access);)*
+            }
+
+            fn matches(archetype: &Archetype) -> bool {
+                true $(&& $name::matches(archetype))*
+            }
+
+            fn lock<'w>(archetype: &'w Archetype, tick: u32) -> Self::State<'w> {
+                ($($name::lock(archetype, tick),)*)
+            }
+
+            fn fetch<'s>(state: &'s mut Self::State<'_>, row: usize) -> Self::Item<'s> {
+                ($($name::fetch(&mut state.$index, row),)*)
+            }
+        }
+    };
+}
+
+query_data!(A 0);
+query_data!(A 0, B 1);
+query_data!(A 0, B 1, C 2);
+query_data!(A 0, B 1, C 2, D 3);
+query_data!(A 0, B 1, C 2, D 3, E 4);
+query_data!(A 0, B 1, C 2, D 3, E 4, F 5);
+query_data!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+query_data!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+
+/// Restricts a query without fetching anything: `With<T>`, `Without<T>`,
+/// `Changed<T>`, or a tuple of these, all of which must hold.
+pub trait QueryFilter {
+    fn access(access: &mut Access);
+    fn matches(archetype: &Archetype) -> bool;
+
+    /// Which rows of a matching archetype pass, or `None` if all do.
+    fn rows(archetype: &Archetype, since: u32) -> Option<Vec<bool>>;
+}
+
+/// Entities that have `T`.
+pub struct With<T>(PhantomData<T>);
+
+/// Entities that do not have `T`.
+pub struct Without<T>(PhantomData<T>);
+
+/// Entities whose `T` was added or written since the query's last run,
+/// or for [`World::query`], since [`World::clear_trackers`].
+pub struct Changed<T>(PhantomData<T>);
+
+/// Entities that got `T` since the query's last run, or for
+/// [`World::query`], since [`World::clear_trackers`].
+pub struct Added<T>(PhantomData<T>);
+
+impl QueryFilter for () {
+    fn access(_: &mut Access) {}
+
+    fn matches(_: &Archetype) -> bool {
+        true
+    }
+
+    fn rows(_: &Archetype, _: u32) -> Option<Vec<bool>> {
+        None
+    }
+}
+
+impl<T: Component> QueryFilter for With<T> {
+    fn access(_: &mut Access) {}
+
+    fn matches(archetype: &Archetype) -> bool {
+        archetype.contains(TypeId::of::<T>())
+    }
+
+    fn rows(_: &Archetype, _: u32) -> Option<Vec<bool>> {
+        None
+    }
+}
+
+impl<T: Component> QueryFilter for Without<T> {
+    fn access(_: &mut Access) {}
+
+    fn matches(archetype: &Archetype) -> bool {
+        !archetype.contains(TypeId::of::<T>())
+    }
+
+    fn rows(_: &Archetype, _: u32) -> Option<Vec<bool>> {
+        None
+    }
+}
+
+impl<T: Component> QueryFilter for Changed<T> {
+    fn access(access: &mut Access) {
+        access.add_filter_read::<T>();
+    }
+
+    fn matches(archetype: &Archetype) -> bool {
+        archetype.contains(TypeId::of::<T>())
+    }
+
+    fn rows(archetype: &Archetype, since: u32) -> Option<Vec<bool>> {
+        return Some(ticks_since::<T>(archetype, since, |c| c.changed_ticks()));
+    }
+}
+
+impl<T: Component> QueryFilter for Added<T> {
+    fn access(access: &mut Access) {
+        access.add_filter_read::<T>();
+    }
+
+    fn matches(archetype: &Archetype) -> bool {
+        archetype.contains(TypeId::of::<T>())
+    }
+
+    fn rows(archetype: &Archetype, since: u32) -> Option<Vec<bool>> {
+        return Some(ticks_since::<T>(archetype, since, |c| c.added_ticks()));
+    }
+}
+
+/// Whether each row's tick, as picked from `T`'s column, is at least
+/// `since`.
+fn ticks_since<T: Component>(
+    archetype: &Archetype,
+    since: u32,
+    ticks: impl Fn(&dyn Column) -> &[u32],
+) -> Vec<bool> {
+    let i = archetype.column_index(TypeId::of::<T>()).unwrap();
+    let column = archetype.columns[i].read().unwrap();
+
+    return ticks(&**column).iter().map(|&tick| tick >= since).collect();
+}
+
+macro_rules! query_filter {
+    ($($name:ident),*) => {
+        impl<$($name: QueryFilter),*> QueryFilter for ($($name,)*) {
+            fn access(access: &mut Access) {
+                $($name::access(access);)*
+            }
+
+            fn matches(archetype: &Archetype) -> bool {
+                true $(&& $name::matches(archetype))*
+            }
+
+            fn rows(archetype: &Archetype, since: u32) -> Option<Vec<bool>> {
+                let mut rows: Option<Vec<bool>> = None;
+                $(
+                    if let Some(other) = $name::rows(archetype, since) {
+                        rows = Some(match rows {
+                            Some(rows) => rows.iter().zip(other).map(|(a, b)| *a && b).collect(),
+                            None => other,
+                        });
+                    }
+                )*
+
+                return rows;
+            }
+        }
+    };
+}
+
+query_filter!(A);
+query_filter!(A, B);
+query_filter!(A, B, C);
+query_filter!(A, B, C, D);
+
+/// Entities with the components in `D` that pass the filter `F`.
+///
+/// Columns are locked per archetype while it is visited: a query that
+/// writes a component blocks other threads accessing it, and querying it
+/// again from inside the callback deadlocks.
+pub struct Query<'w, D: QueryData, F: QueryFilter = ()> {
+    world: &'w World,
+    since: u32,
+    tick: u32,
+    marker: PhantomData<fn() -> (D, F)>,
+}
+
+impl<'w, D: QueryData, F: QueryFilter> Query<'w, D, F> {
+    /// Panics if `D` reads and writes, or writes twice, the same component.
+    pub(crate) fn new(world: &'w World, since: u32, tick: u32) -> Self {
+        Self::access();
+
+        Self {
+            world,
+            since,
+            tick,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn access() -> Access {
+        let mut access = Access::default();
+        D::access(&mut access);
+        F::access(&mut access);
+
+        return access;
+    }
+
+    pub fn for_each(&mut self, mut f: impl FnMut(D::Item<'_>)) {
+        for archetype in self.archetypes() {
+            let rows = F::rows(archetype, self.since);
+            let mut state = D::lock(archetype, self.tick);
+            for row in 0..archetype.len() {
+                if rows.as_ref().is_none_or(|rows| rows[row]) {
+                    f(D::fetch(&mut state, row));
+                }
+            }
+        }
+    }
+
+    /// Calls `f` with the entity's item if it matches the query.
+    pub fn get<R>(&mut self, entity: Entity, f: impl FnOnce(D::Item<'_>) -> R) -> Option<R> {
+        let location = self.world.entities().location(entity)?;
+        let archetype = &self.world.archetypes()[location.archetype];
+        if !(D::matches(archetype) && F::matches(archetype)) {
+            return None;
+        }
+        if F::rows(archetype, self.since).is_some_and(|rows| !rows[location.row]) {
+            return None;
+        }
+
+        let mut state = D::lock(archetype, self.tick);
+        return Some(f(D::fetch(&mut state, location.row)));
+    }
+
+    pub fn count(&self) -> usize {
+        self.archetypes()
+            .map(|archetype| match F::rows(archetype, self.since) {
+                Some(rows) => rows.iter().filter(|&&r| r).count(),
+                None => archetype.len(),
+            })
+            .sum()
+    }
+
+    fn archetypes(&self) -> impl Iterator<Item = &'w Archetype> {
+        self.world
+            .archetypes()
+            .iter()
+            .filter(|a| !a.is_empty() && D::matches(a) && F::matches(a))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct A(u32);
+
+    struct B;
+
+    fn matching<F: QueryFilter>(world: &World) -> Vec<Entity> {
+        let mut entities = Vec::new();
+        world
+            .query_filtered::<Entity, F>()
+            .for_each(|e| entities.push(e));
+        entities.sort();
+
+        return entities;
+    }
+
+    #[test]
+    fn added_test() {
+        let mut world = World::new();
+        let old = world.spawn((A(0),));
+        let moved = world.spawn((A(1),));
+        assert_eq!(matching::<Added<A>>(&world), [old, moved]);
+
+        world.clear_trackers();
+        assert!(matching::<Added<A>>(&world).is_empty());
+
+        // Writing or replacing a component changes it without adding it.
+        world.get_mut::<A>(old).unwrap().0 = 5;
+        world.insert(moved, A(6));
+        assert!(matching::<Added<A>>(&world).is_empty());
+        assert_eq!(matching::<Changed<A>>(&world), [old, moved]);
+
+        let new = world.spawn((A(2),));
+        world.insert(old, B);
+        assert_eq!(matching::<Added<A>>(&world), [new]);
+        assert_eq!(matching::<Added<B>>(&world), [old]);
+        assert_eq!(matching::<(Added<A>, Without<B>)>(&world), [new]);
+    }
+
+    #[test]
+    fn changed_ticks_follow_moves_test() {
+        let mut world = World::new();
+        let quiet = world.spawn((A(0),));
+        let loud = world.spawn((A(1),));
+        world.clear_trackers();
+
+        world.get_mut::<A>(loud).unwrap().0 = 2;
+        // Moving to another archetype keeps the ticks of the other
+        // components.
+        world.insert(quiet, B);
+        world.insert(loud, B);
+        assert_eq!(matching::<Changed<A>>(&world), [loud]);
+        assert_eq!(matching::<Changed<B>>(&world), [quiet, loud]);
+
+        world.remove::<B>(loud);
+        assert_eq!(matching::<Changed<A>>(&world), [loud]);
+        assert_eq!(world.query_filtered::<&A, Changed<A>>().count(), 1);
+    }
+
+    #[test]
+    fn get_filtered_test() {
+        let mut world = World::new();
+        let a = world.spawn((A(0),));
+        let b = world.spawn((A(1), B));
+        world.clear_trackers();
+        world.get_mut::<A>(b).unwrap().0 = 2;
+
+        let mut query = world.query_filtered::<&A, Changed<A>>();
+        assert_eq!(query.get(a, |a| a.0), None);
+        assert_eq!(query.get(b, |a| a.0), Some(2));
+        assert_eq!(
+            world.query_filtered::<&A, Without<B>>().get(a, |a| a.0),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn access_test() {
+        let reads = Query::<(&A, Entity), Changed<B>>::access();
+        let writes = Query::<&mut A>::access();
+        let other = Query::<&B>::access();
+
+        assert_eq!(reads.conflicts(&writes), [type_name::<A>()]);
+        assert!(reads.is_compatible(&other));
+        assert!(!writes.is_compatible(&writes));
+        assert!(reads.is_compatible(&reads));
+    }
+}
@@ -0,0 +1,598 @@
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    panic::{self, AssertUnwindSafe},
+    sync::{Condvar, Mutex},
+    thread,
+};
+
+use super::{
+    query::Access,
+    system::{IntoSystem, System},
+    World,
+};
+
+/// An ordering constraint that cannot be satisfied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScheduleError {
+    /// `before` or `after` names a label no system has.
+    UnknownLabel { system: String, label: &'static str },
+    /// The named systems depend on each other in a cycle.
+    Cycle(Vec<String>),
+}
+
+impl Display for ScheduleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ScheduleError::UnknownLabel { system, label } => {
+                write!(f, "{system} is ordered against unknown label {label:?}")
+            }
+            ScheduleError::Cycle(systems) => {
+                write!(f, "ordering cycle between {}", systems.join(", "))
+            }
+        }
+    }
+}
+
+impl Error for ScheduleError {}
+
+/// Two systems that access the same data, at least one of them writing,
+/// with no order between them: they never run at the same time, but
+/// which runs first can change from run to run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ambiguity {
+    pub first: String,
+    pub second: String,
+    /// The contested component and resource types.
+    pub conflicts: Vec<&'static str>,
+}
+
+impl Display for Ambiguity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} and {} have no defined order but both access {}",
+            self.first,
+            self.second,
+            self.conflicts.join(", ")
+        )
+    }
+}
+
+struct Entry {
+    system: Mutex<Box<dyn System>>,
+    name: String,
+    access: Access,
+    labels: Vec<&'static str>,
+    before: Vec<&'static str>,
+    after: Vec<&'static str>,
+    ambiguous_with: Vec<&'static str>,
+}
+
+/// Ordering configuration for a system just added to a [`Schedule`].
+pub struct SystemBuilder<'s> {
+    entry: &'s mut Entry,
+}
+
+impl SystemBuilder<'_> {
+    /// Names the system for ordering; several systems may share a label.
+    pub fn label(self, label: &'static str) -> Self {
+        self.entry.labels.push(label);
+        return self;
+    }
+
+    /// Runs the system before every system with `label`.
+    pub fn before(self, label: &'static str) -> Self {
+        self.entry.before.push(label);
+        return self;
+    }
+
+    /// Runs the system after every system with `label`.
+    pub fn after(self, label: &'static str) -> Self {
+        self.entry.after.push(label);
+        return self;
+    }
+
+    /// Accepts either order against systems with `label` and stops them
+    /// being reported as ambiguous.
+    pub fn ambiguous_with(self, label: &'static str) -> Self {
+        self.entry.ambiguous_with.push(label);
+        return self;
+    }
+}
+
+/// Order derived from labels, in a form ready to execute.
+struct Graph {
+    /// Topological order.
+    order: Vec<usize>,
+    dependencies: Vec<usize>,
+    dependents: Vec<Vec<usize>>,
+    /// Whether two systems may not run at the same time.
+    conflicting: Vec<Vec<bool>>,
+}
+
+/// Runs systems on a thread pool, as many at a time as their access sets
+/// and ordering constraints allow.
+///
+/// Systems take their data from a shared [`World`]; [`Commands`](super::Commands)
+/// they issue are applied in the order the systems were added, after all
+/// of them have run.
+pub struct Schedule {
+    systems: Vec<Entry>,
+    graph: Option<Graph>,
+    ambiguities: Vec<Ambiguity>,
+    threads: usize,
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Schedule {
+    pub fn new() -> Self {
+        Self {
+            systems: Vec::new(),
+            graph: None,
+            ambiguities: Vec::new(),
+            threads: 0,
+        }
+    }
+
+    /// Worker threads; 0 uses the available parallelism and 1 runs the
+    /// systems on the calling thread.
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads;
+    }
+
+    pub fn len(&self) -> usize {
+        self.systems.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.systems.is_empty()
+    }
+
+    /// Panics if the system's parameters conflict with each other, such
+    /// as two queries writing the same component.
+    pub fn add_system<M>(&mut self, system: impl IntoSystem<M>) -> SystemBuilder<'_> {
+        let system = system.into_system();
+        self.graph = None;
+        self.systems.push(Entry {
+            name: system.name().to_string(),
+            access: system.access().clone(),
+            system: Mutex::new(system),
+            labels: Vec::new(),
+            before: Vec::new(),
+            after: Vec::new(),
+            ambiguous_with: Vec::new(),
+        });
+
+        return SystemBuilder {
+            entry: self.systems.last_mut().unwrap(),
+        };
+    }
+
+    /// Resolves the ordering constraints and returns the
+    /// [`ambiguities`](Self::ambiguities) found. Called by
+    /// [`run`](Self::run) when systems were added.
+    pub fn build(&mut self) -> Result<&[Ambiguity], ScheduleError> {
+        let n = self.systems.len();
+        let mut dependents = vec![Vec::new(); n];
+        for (i, entry) in self.systems.iter().enumerate() {
+            for (labels, forward) in [(&entry.before, true), (&entry.after, false)] {
+                for &label in labels {
+                    let others: Vec<usize> = (0..n)
+                        .filter(|&j| self.systems[j].labels.contains(&label))
+                        .collect();
+                    if others.is_empty() {
+                        return Err(ScheduleError::UnknownLabel {
+                            system: entry.name.clone(),
+                            label,
+                        });
+                    }
+
+                    for j in others.into_iter().filter(|&j| j != i) {
+                        let (from, to) = if forward { (i, j) } else { (j, i) };
+                        if !dependents[from].contains(&to) {
+                            dependents[from].push(to);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut dependencies = vec![0; n];
+        for &to in dependents.iter().flatten() {
+            dependencies[to] += 1;
+        }
+
+        // Kahn's algorithm, preferring the order systems were added in.
+        let mut remaining = dependencies.clone();
+        let mut order = Vec::with_capacity(n);
+        let mut done = vec![false; n];
+        while let Some(i) = (0..n).find(|&i| !done[i] && remaining[i] == 0) {
+            done[i] = true;
+            order.push(i);
+            for &j in &dependents[i] {
+                remaining[j] -= 1;
+            }
+        }
+        if order.len() < n {
+            let cycle = (0..n)
+                .filter(|&i| !done[i])
+                .map(|i| self.systems[i].name.clone())
+                .collect();
+            return Err(ScheduleError::Cycle(cycle));
+        }
+
+        // reachable[i][j]: `i` always runs before `j`.
+        let mut reachable = vec![vec![false; n]; n];
+        for &i in order.iter().rev() {
+            for &j in &dependents[i] {
+                let later = reachable[j].clone();
+                reachable[i][j] = true;
+                for (k, &after) in later.iter().enumerate() {
+                    reachable[i][k] |= after;
+                }
+            }
+        }
+
+        let mut conflicting = vec![vec![false; n]; n];
+        self.ambiguities.clear();
+        for i in 0..n {
+            for j in i + 1..n {
+                let (a, b) = (&self.systems[i], &self.systems[j]);
+                let conflicts = a.access.conflicts(&b.access);
+                if conflicts.is_empty() {
+                    continue;
+                }
+
+                conflicting[i][j] = true;
+                conflicting[j][i] = true;
+                let ordered = reachable[i][j] || reachable[j][i];
+                let accepted = a.ambiguous_with.iter().any(|l| b.labels.contains(l))
+                    || b.ambiguous_with.iter().any(|l| a.labels.contains(l));
+                if !ordered && !accepted {
+                    self.ambiguities.push(Ambiguity {
+                        first: a.name.clone(),
+                        second: b.name.clone(),
+                        conflicts,
+                    });
+                }
+            }
+        }
+
+        self.graph = Some(Graph {
+            order,
+            dependencies,
+            dependents,
+            conflicting,
+        });
+
+        return Ok(&self.ambiguities);
+    }
+
+    /// Unordered pairs of conflicting systems found by the last
+    /// [`build`](Self::build).
+    pub fn ambiguities(&self) -> &[Ambiguity] {
+        &self.ambiguities
+    }
+
+    /// Runs every system once, then applies their commands.
+    ///
+    /// Panics if the ordering constraints cannot be satisfied, and
+    /// re-raises the panic of a system after the others have finished.
+    pub fn run(&mut self, world: &mut World) {
+        if self.graph.is_none() {
+            if let Err(e) = self.build() {
+                panic!("{e}");
+            }
+        }
+
+        world.flush();
+        let threads = match self.threads {
+            0 => thread::available_parallelism().map_or(1, |n| n.get()),
+            n => n,
+        };
+        if threads == 1 {
+            for &i in &self.graph.as_ref().unwrap().order {
+                self.systems[i].system.get_mut().unwrap().run(world);
+            }
+        } else {
+            self.run_parallel(world, threads);
+        }
+
+        for entry in &mut self.systems {
+            let commands = entry.system.get_mut().unwrap().take_commands();
+            world.apply(commands);
+        }
+    }
+
+    fn run_parallel(&self, world: &World, threads: usize) {
+        struct State {
+            dependencies: Vec<usize>,
+            started: Vec<bool>,
+            running: Vec<usize>,
+            finished: usize,
+            panic: Option<Box<dyn std::any::Any + Send>>,
+        }
+
+        let graph = self.graph.as_ref().unwrap();
+        let n = self.systems.len();
+        let state = Mutex::new(State {
+            dependencies: graph.dependencies.clone(),
+            started: vec![false; n],
+            running: Vec::new(),
+            finished: 0,
+            panic: None,
+        });
+        let wake = Condvar::new();
+
+        let worker = || loop {
+            let next = {
+                let mut state = state.lock().unwrap();
+                loop {
+                    if state.finished == n {
+                        return;
+                    }
+
+                    let ready = (0..n).find(|&i| {
+                        !state.started[i]
+                            && state.dependencies[i] == 0
+                            && state.running.iter().all(|&r| !graph.conflicting[i][r])
+                    });
+                    if let Some(i) = ready {
+                        state.started[i] = true;
+                        state.running.push(i);
+                        break i;
+                    }
+                    state = wake.wait(state).unwrap();
+                }
+            };
+
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                self.systems[next].system.lock().unwrap().run(world);
+            }));
+
+            let mut state = state.lock().unwrap();
+            state.running.retain(|&r| r != next);
+            state.finished += 1;
+            for &j in &graph.dependents[next] {
+                state.dependencies[j] -= 1;
+            }
+            if let Err(payload) = result {
+                state.panic.get_or_insert(payload);
+            }
+            drop(state);
+            wake.notify_all();
+        };
+
+        thread::scope(|s| {
+            for _ in 0..threads.min(n) {
+                s.spawn(worker);
+            }
+        });
+
+        if let Some(payload) = state.into_inner().unwrap().panic {
+            panic::resume_unwind(payload);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::{Duration, Instant},
+    };
+
+    use super::*;
+    use crate::{
+        ecs::{Changed, Commands, Entity, Query, Res, ResMut, Transform},
+        math::Vector3D,
+    };
+
+    struct Velocity(Vector3D);
+
+    #[derive(Default)]
+    struct Log(Vec<&'static str>);
+
+    struct Time(f32);
+
+    fn movement(mut query: Query<(&mut Transform, &Velocity)>, time: Res<Time>) {
+        query.for_each(|(mut transform, velocity)| transform.position += velocity.0 * time.0);
+    }
+
+    fn spawner(mut commands: Commands, mut query: Query<(Entity, &Transform)>) {
+        query.for_each(|(entity, transform)| {
+            if transform.position.x() > 1.5 {
+                commands.despawn(entity);
+            }
+        });
+    }
+
+    fn logger(name: &'static str) -> impl IntoSystem<fn(ResMut<'static, Log>)> {
+        move |mut log: ResMut<Log>| log.0.push(name)
+    }
+
+    #[test]
+    fn systems_test() {
+        let mut world = World::new();
+        world.insert_resource(Time(1.0));
+        let a = world.spawn((
+            Transform::default(),
+            Velocity(Vector3D::create(1.0, 0.0, 0.0)),
+        ));
+        let b = world.spawn((Transform::default(),));
+
+        let mut schedule = Schedule::new();
+        schedule.add_system(movement).label("movement");
+        schedule.add_system(spawner).after("movement");
+
+        schedule.run(&mut world);
+        assert!(world.contains(a));
+        schedule.run(&mut world);
+        assert!(!world.contains(a));
+        assert!(world.contains(b));
+    }
+
+    #[test]
+    fn order_test() {
+        for threads in [1, 4] {
+            let mut world = World::new();
+            world.insert_resource(Log::default());
+
+            let mut schedule = Schedule::new();
+            schedule.set_threads(threads);
+            schedule
+                .add_system(logger("render"))
+                .label("render")
+                .after("physics");
+            schedule
+                .add_system(logger("physics"))
+                .label("physics")
+                .after("input");
+            schedule.add_system(logger("input")).label("input");
+            schedule
+                .add_system(logger("audio"))
+                .ambiguous_with("input")
+                .ambiguous_with("render");
+            schedule.build().unwrap();
+            // Audio and physics both write the log without an order between them.
+            assert_eq!(schedule.ambiguities().len(), 1);
+            assert_eq!(schedule.ambiguities()[0].conflicts.len(), 1);
+
+            schedule.run(&mut world);
+            let log = &world.resource::<Log>().unwrap().0;
+            assert_eq!(log.len(), 4);
+            let ordered: Vec<_> = log.iter().filter(|&&name| name != "audio").collect();
+            assert_eq!(ordered, [&"input", &"physics", &"render"]);
+        }
+    }
+
+    #[test]
+    fn ambiguities_test() {
+        fn first(_: ResMut<Log>) {}
+        fn second(_: Res<Log>) {}
+        fn third(_: Res<Log>) {}
+
+        let mut schedule = Schedule::new();
+        schedule.add_system(first).label("first");
+        schedule.add_system(second);
+        schedule.add_system(third).after("first");
+        let ambiguities = schedule.build().unwrap().to_vec();
+        assert_eq!(ambiguities.len(), 1);
+        assert!(ambiguities[0].first.ends_with("first"));
+        assert!(ambiguities[0].second.ends_with("second"));
+        assert_eq!(ambiguities[0].conflicts, [std::any::type_name::<Log>()]);
+        assert_eq!(schedule.ambiguities(), ambiguities);
+        assert!(ambiguities[0].to_string().contains("have no defined order"));
+
+        schedule
+            .add_system(logger("fourth"))
+            .ambiguous_with("first");
+        // The new writer is unordered against both readers, but accepted
+        // against `first`.
+        assert_eq!(schedule.build().unwrap().len(), 3);
+        assert!(schedule
+            .ambiguities()
+            .iter()
+            .all(|a| !a.first.ends_with("first") || a.second.ends_with("second")));
+    }
+
+    #[test]
+    fn errors_test() {
+        let mut schedule = Schedule::new();
+        schedule.add_system(logger("a")).label("a").after("b");
+        assert!(matches!(
+            schedule.build(),
+            Err(ScheduleError::UnknownLabel { label: "b", .. })
+        ));
+
+        schedule.add_system(logger("b")).label("b").after("a");
+        let Err(ScheduleError::Cycle(systems)) = schedule.build() else {
+            panic!("expected a cycle");
+        };
+        assert_eq!(systems.len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "more than once")]
+    fn conflicting_params_test() {
+        fn twice(_: Query<&mut Transform>, _: Query<&mut Transform>) {}
+
+        Schedule::new().add_system(twice);
+    }
+
+    #[test]
+    fn parallel_test() {
+        // Each system waits until both are running, which only happens if
+        // they run at the same time.
+        let running = Arc::new(AtomicUsize::new(0));
+        let overlapped = Arc::new(AtomicUsize::new(0));
+        let wait = |running: Arc<AtomicUsize>, overlapped: Arc<AtomicUsize>| {
+            move |_: Query<&Transform>| {
+                running.fetch_add(1, Ordering::SeqCst);
+                let start = Instant::now();
+                while running.load(Ordering::SeqCst) < 2 && start.elapsed() < Duration::from_secs(5)
+                {
+                    thread::yield_now();
+                }
+                if running.load(Ordering::SeqCst) >= 2 {
+                    overlapped.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+        };
+
+        let mut world = World::new();
+        world.spawn((Transform::default(),));
+        let mut schedule = Schedule::new();
+        schedule.set_threads(2);
+        schedule.add_system(wait(running.clone(), overlapped.clone()));
+        schedule.add_system(wait(running.clone(), overlapped.clone()));
+        schedule.run(&mut world);
+        assert_eq!(overlapped.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn changed_since_last_run_test() {
+        fn count(mut query: Query<Entity, Changed<Transform>>, mut log: ResMut<Log>) {
+            if query.count() > 0 {
+                log.0.push("changed");
+            }
+        }
+
+        let mut world = World::new();
+        world.insert_resource(Log::default());
+        let e = world.spawn((Transform::default(),));
+        let mut schedule = Schedule::new();
+        schedule.add_system(count);
+
+        schedule.run(&mut world);
+        schedule.run(&mut world);
+        world.get_mut::<Transform>(e).unwrap().scale = Vector3D::default();
+        schedule.run(&mut world);
+        assert_eq!(world.resource::<Log>().unwrap().0, ["changed", "changed"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "boom")]
+    fn system_panic_test() {
+        fn boom(_: Query<&Transform>) {
+            panic!("boom");
+        }
+
+        let mut schedule = Schedule::new();
+        schedule.set_threads(2);
+        schedule.add_system(boom);
+        schedule.add_system(logger("other"));
+        let mut world = World::new();
+        world.insert_resource(Log::default());
+        schedule.run(&mut world);
+    }
+}
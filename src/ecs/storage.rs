@@ -0,0 +1,234 @@
+use std::{
+    any::{Any, TypeId},
+    sync::RwLock,
+};
+
+use super::Entity;
+
+/// Anything `'static` that can be shared between threads can be a
+/// component or a resource.
+pub trait Component: Any + Send + Sync {}
+
+impl<T: Any + Send + Sync> Component for T {}
+
+/// One component type's values for every entity of an archetype, with the
+/// ticks at which each was added and last changed.
+pub(crate) struct TypedColumn<T> {
+    pub(crate) data: Vec<T>,
+    pub(crate) added: Vec<u32>,
+    pub(crate) changed: Vec<u32>,
+}
+
+/// Type-erased operations on a [`TypedColumn`].
+#[doc(hidden)]
+pub trait Column: Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    /// An empty column of the same type.
+    fn empty(&self) -> Box<dyn Column>;
+    /// Drops the value at `row`, moving the last row into its place.
+    fn swap_remove(&mut self, row: usize);
+    /// Moves the value at `row` to the end of `to`, which must hold the
+    /// same type, filling the gap with the last row.
+    fn move_row(&mut self, row: usize, to: &mut dyn Column);
+    fn added_ticks(&self) -> &[u32];
+    fn changed_ticks(&self) -> &[u32];
+}
+
+impl<T: Component> Column for TypedColumn<T> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn empty(&self) -> Box<dyn Column> {
+        Box::new(TypedColumn::<T> {
+            data: Vec::new(),
+            added: Vec::new(),
+            changed: Vec::new(),
+        })
+    }
+
+    fn swap_remove(&mut self, row: usize) {
+        self.data.swap_remove(row);
+        self.added.swap_remove(row);
+        self.changed.swap_remove(row);
+    }
+
+    fn move_row(&mut self, row: usize, to: &mut dyn Column) {
+        let to = to
+            .as_any_mut()
+            .downcast_mut::<TypedColumn<T>>()
+            .expect("same column type");
+        to.data.push(self.data.swap_remove(row));
+        to.added.push(self.added.swap_remove(row));
+        to.changed.push(self.changed.swap_remove(row));
+    }
+
+    fn added_ticks(&self) -> &[u32] {
+        &self.added
+    }
+
+    fn changed_ticks(&self) -> &[u32] {
+        &self.changed
+    }
+}
+
+pub(crate) fn new_column<T: Component>() -> Box<dyn Column> {
+    Box::new(TypedColumn::<T> {
+        data: Vec::new(),
+        added: Vec::new(),
+        changed: Vec::new(),
+    })
+}
+
+/// Downcasts a column known to hold `T`.
+pub(crate) fn typed<T: Component>(column: &dyn Column) -> &TypedColumn<T> {
+    column
+        .as_any()
+        .downcast_ref()
+        .expect("column holds the component type")
+}
+
+pub(crate) fn typed_mut<T: Component>(column: &mut dyn Column) -> &mut TypedColumn<T> {
+    column
+        .as_any_mut()
+        .downcast_mut()
+        .expect("column holds the component type")
+}
+
+/// All entities with exactly one set of component types, stored column by
+/// column. Each column has its own lock so queries on different
+/// components can run at the same time.
+pub struct Archetype {
+    /// Sorted.
+    types: Vec<TypeId>,
+    names: Vec<&'static str>,
+    pub(crate) columns: Vec<RwLock<Box<dyn Column>>>,
+    pub(crate) entities: Vec<Entity>,
+}
+
+impl Archetype {
+    /// `columns` pairs each type with an empty column and its name, in
+    /// any order.
+    pub(crate) fn new(mut columns: Vec<(TypeId, &'static str, Box<dyn Column>)>) -> Self {
+        columns.sort_by_key(|(id, _, _)| *id);
+
+        Self {
+            types: columns.iter().map(|(id, _, _)| *id).collect(),
+            names: columns.iter().map(|(_, name, _)| *name).collect(),
+            columns: columns
+                .into_iter()
+                .map(|(_, _, c)| RwLock::new(c))
+                .collect(),
+            entities: Vec::new(),
+        }
+    }
+
+    pub fn types(&self) -> &[TypeId] {
+        &self.types
+    }
+
+    /// Component type names, in the order of [`types`](Self::types).
+    pub fn type_names(&self) -> &[&'static str] {
+        &self.names
+    }
+
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    pub fn contains(&self, id: TypeId) -> bool {
+        self.column_index(id).is_some()
+    }
+
+    pub(crate) fn column_index(&self, id: TypeId) -> Option<usize> {
+        self.types.binary_search(&id).ok()
+    }
+
+    /// The empty columns of this archetype with `extra` added and `removed`
+    /// left out, ready to build a neighbouring archetype.
+    pub(crate) fn neighbour_columns(
+        &mut self,
+        extra: Option<(TypeId, &'static str, Box<dyn Column>)>,
+        removed: Option<TypeId>,
+    ) -> Vec<(TypeId, &'static str, Box<dyn Column>)> {
+        let mut columns: Vec<_> = (0..self.types.len())
+            .filter(|&i| Some(self.types[i]) != removed)
+            .map(|i| {
+                (
+                    self.types[i],
+                    self.names[i],
+                    self.columns[i].get_mut().unwrap().empty(),
+                )
+            })
+            .collect();
+        columns.extend(extra);
+
+        return columns;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(values: &[u32]) -> TypedColumn<u32> {
+        TypedColumn {
+            data: values.to_vec(),
+            added: values.iter().map(|v| v + 100).collect(),
+            changed: values.iter().map(|v| v + 200).collect(),
+        }
+    }
+
+    #[test]
+    fn move_row_test() {
+        let mut from = column(&[1, 2, 3]);
+        let mut to = column(&[4]);
+
+        from.move_row(0, &mut to);
+        assert_eq!(from.data, [3, 2]);
+        assert_eq!(from.added, [103, 102]);
+        assert_eq!(from.changed_ticks(), [203, 202]);
+        assert_eq!(to.data, [4, 1]);
+        assert_eq!(to.added_ticks(), [104, 101]);
+        assert_eq!(to.changed, [204, 201]);
+
+        from.swap_remove(1);
+        assert_eq!(
+            (from.data, from.added, from.changed),
+            (vec![3], vec![103], vec![203])
+        );
+    }
+
+    #[test]
+    fn neighbour_columns_test() {
+        let mut archetype = Archetype::new(vec![
+            (TypeId::of::<u32>(), "u32", new_column::<u32>()),
+            (TypeId::of::<u8>(), "u8", new_column::<u8>()),
+        ]);
+        assert!(archetype.types().is_sorted());
+        assert!(archetype.contains(TypeId::of::<u8>()));
+        assert!(!archetype.contains(TypeId::of::<i8>()));
+
+        let columns = archetype.neighbour_columns(
+            Some((TypeId::of::<i8>(), "i8", new_column::<i8>())),
+            Some(TypeId::of::<u32>()),
+        );
+        let mut names: Vec<&str> = columns.iter().map(|(_, name, _)| *name).collect();
+        names.sort();
+        assert_eq!(names, ["i8", "u8"]);
+        assert!(columns[0].2.as_any().is::<TypedColumn<u8>>());
+    }
+}
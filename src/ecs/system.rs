@@ -0,0 +1,254 @@
+use std::{any::type_name, marker::PhantomData, sync::Mutex};
+
+use super::{
+    command::{CommandBuffer, Commands},
+    query::{Access, Query, QueryData, QueryFilter},
+    storage::Component,
+    world::{Res, ResMut, World},
+};
+
+/// Something a [`Schedule`](super::Schedule) can run against a shared
+/// [`World`].
+pub trait System: Send {
+    fn name(&self) -> &str;
+    /// What [`run`](Self::run) reads and writes, used to decide which
+    /// systems may run at the same time.
+    fn access(&self) -> &Access;
+    fn run(&mut self, world: &World);
+    /// Structural changes requested by the runs so far.
+    fn take_commands(&mut self) -> CommandBuffer;
+}
+
+/// Bookkeeping for one system, available to its parameters.
+pub struct SystemMeta {
+    name: &'static str,
+    access: Access,
+    /// Change tick of the previous run; 0 before the first.
+    last_run: u32,
+    tick: u32,
+    commands: Mutex<CommandBuffer>,
+}
+
+impl SystemMeta {
+    fn new(name: &'static str, access: Access) -> Self {
+        Self {
+            name,
+            access,
+            last_run: 0,
+            tick: 0,
+            commands: Mutex::new(CommandBuffer::new()),
+        }
+    }
+}
+
+/// A function argument a system can ask for: [`Query`], [`Res`],
+/// [`ResMut`] or [`Commands`].
+pub trait SystemParam {
+    type Item<'w>;
+
+    /// Panics if the parameter conflicts with what is already in `access`.
+    fn access(access: &mut Access);
+    fn fetch<'w>(world: &'w World, meta: &'w SystemMeta) -> Self::Item<'w>;
+}
+
+impl<D: QueryData, F: QueryFilter> SystemParam for Query<'_, D, F> {
+    type Item<'w> = Query<'w, D, F>;
+
+    fn access(access: &mut Access) {
+        D::access(access);
+        F::access(access);
+    }
+
+    /// `Changed` filters see changes made since the system last ran.
+    fn fetch<'w>(world: &'w World, meta: &'w SystemMeta) -> Query<'w, D, F> {
+        Query::new(world, meta.last_run + 1, meta.tick)
+    }
+}
+
+impl<R: Component> SystemParam for Res<'_, R> {
+    type Item<'w> = Res<'w, R>;
+
+    fn access(access: &mut Access) {
+        access.add_resource_read::<R>();
+    }
+
+    fn fetch<'w>(world: &'w World, meta: &'w SystemMeta) -> Res<'w, R> {
+        world
+            .resource()
+            .unwrap_or_else(|| panic!("{} needs missing resource {}", meta.name, type_name::<R>()))
+    }
+}
+
+impl<R: Component> SystemParam for ResMut<'_, R> {
+    type Item<'w> = ResMut<'w, R>;
+
+    fn access(access: &mut Access) {
+        access.add_resource_write::<R>();
+    }
+
+    fn fetch<'w>(world: &'w World, meta: &'w SystemMeta) -> ResMut<'w, R> {
+        world
+            .resource_mut()
+            .unwrap_or_else(|| panic!("{} needs missing resource {}", meta.name, type_name::<R>()))
+    }
+}
+
+impl SystemParam for Commands<'_> {
+    type Item<'w> = Commands<'w>;
+
+    fn access(_: &mut Access) {}
+
+    fn fetch<'w>(world: &'w World, meta: &'w SystemMeta) -> Commands<'w> {
+        Commands::with_sink(world, &meta.commands)
+    }
+}
+
+/// Conversion into a boxed [`System`], implemented for functions whose
+/// arguments are all [`SystemParam`]s. `Marker` only keeps the
+/// implementations apart.
+pub trait IntoSystem<Marker> {
+    fn into_system(self) -> Box<dyn System>;
+}
+
+impl<S: System + 'static> IntoSystem<()> for S {
+    fn into_system(self) -> Box<dyn System> {
+        Box::new(self)
+    }
+}
+
+/// A function run as a system.
+pub struct FunctionSystem<F, P> {
+    function: F,
+    meta: SystemMeta,
+    marker: PhantomData<fn() -> P>,
+}
+
+macro_rules! function_system {
+    ($($param:ident $arg:ident),*) => {
+        impl<Func, $($param: SystemParam + 'static),*> IntoSystem<fn($($param),*)> for Func
+        where
+            Func: FnMut($($param),*) + for<'w> FnMut($($param::Item<'w>),*) + Send + 'static,
+        {
+            fn into_system(self) -> Box<dyn System> {
+                let mut access = Access::default();
+                $($param::access(&mut access);)*
+
+                Box::new(FunctionSystem::<Func, ($($param,)*)> {
+                    function: self,
+                    meta: SystemMeta::new(type_name::<Func>(), access),
+                    marker: PhantomData,
+                })
+            }
+        }
+
+        impl<Func, $($param: SystemParam + 'static),*> System for FunctionSystem<Func, ($($param,)*)>
+        where
+            Func: for<'w> FnMut($($param::Item<'w>),*) + Send + 'static,
+        {
+            fn name(&self) -> &str {
+                self.meta.name
+            }
+
+            fn access(&self) -> &Access {
+                &self.meta.access
+            }
+
+            fn run(&mut self, world: &World) {
+                // Calling through a generic function picks the `FnMut`
+                // implementation taking the fetched items.
+                #[allow(clippy::too_many_arguments)]
+                fn call<$($param),*>(mut function: impl FnMut($($param),*), $($arg: $param),*) {
+                    function($($arg),*);
+                }
+
+                self.meta.tick = world.increment_change_tick();
+                $(let $arg = $param::fetch(world, &self.meta);)*
+                call(&mut self.function, $($arg),*);
+                self.meta.last_run = self.meta.tick;
+            }
+
+            fn take_commands(&mut self) -> CommandBuffer {
+                std::mem::take(self.meta.commands.get_mut().unwrap())
+            }
+        }
+    };
+}
+
+function_system!();
+function_system!(A a);
+function_system!(A a, B b);
+function_system!(A a, B b, C c);
+function_system!(A a, B b, C c, D d);
+function_system!(A a, B b, C c, D d, E e);
+function_system!(A a, B b, C c, D d, E e, F f);
+function_system!(A a, B b, C c, D d, E e, F f, G g);
+function_system!(A a, B b, C c, D d, E e, F f, G g, H h);
+
+#[cfg(test)]
+mod tests {
+    use std::any::TypeId;
+
+    use super::*;
+    use crate::ecs::{Added, Entity};
+
+    #[derive(Debug, PartialEq)]
+    struct A(u32);
+
+    #[derive(Default)]
+    struct Seen(Vec<Entity>);
+
+    fn run(system: &mut Box<dyn System>, world: &mut World) {
+        system.run(world);
+        world.apply(system.take_commands());
+    }
+
+    #[test]
+    fn access_test() {
+        fn system(_: Query<&mut A>, _: Res<Seen>, _: Commands) {}
+
+        let system = system.into_system();
+        assert_eq!(
+            system.access().writes().collect::<Vec<_>>(),
+            [TypeId::of::<A>()]
+        );
+        assert_eq!(system.access().reads().count(), 0);
+        assert!(system.name().ends_with("system"));
+    }
+
+    #[test]
+    fn added_since_last_run_test() {
+        let mut world = World::new();
+        world.insert_resource(Seen::default());
+        let first = world.spawn((A(0),));
+
+        let mut system = (|mut query: Query<Entity, Added<A>>, mut seen: ResMut<Seen>| {
+            query.for_each(|e| seen.0.push(e));
+        })
+        .into_system();
+
+        run(&mut system, &mut world);
+        run(&mut system, &mut world);
+        let second = world.spawn((A(1),));
+        run(&mut system, &mut world);
+        assert_eq!(world.resource::<Seen>().unwrap().0, [first, second]);
+    }
+
+    #[test]
+    fn commands_test() {
+        let mut world = World::new();
+        let mut system = (|mut commands: Commands| {
+            commands.spawn((A(0),));
+        })
+        .into_system();
+
+        system.run(&world);
+        system.run(&world);
+        assert!(world.is_empty());
+        let commands = system.take_commands();
+        assert_eq!(commands.len(), 2);
+        assert!(system.take_commands().is_empty());
+
+        world.apply(commands);
+        assert_eq!(world.query::<&A>().count(), 2);
+    }
+}
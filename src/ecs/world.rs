@@ -0,0 +1,623 @@
+use std::{
+    any::{type_name, Any, TypeId},
+    collections::HashMap,
+    ops::{Deref, DerefMut},
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        RwLock, RwLockReadGuard, RwLockWriteGuard,
+    },
+};
+
+use super::{
+    command::{CommandBuffer, Commands},
+    entity::{Entities, Location},
+    query::{Query, QueryData, QueryFilter},
+    storage::{new_column, typed, typed_mut, Archetype, Column, Component},
+    Entity,
+};
+
+/// A set of components spawned together, implemented for tuples of up to
+/// eight distinct component types. Spawn a single component as `(c,)` and
+/// an entity without components as `()`.
+pub trait Bundle: Send + Sync + 'static {
+    #[doc(hidden)]
+    fn columns() -> Vec<(TypeId, &'static str, Box<dyn Column>)>;
+
+    /// Appends one row to `archetype`, which has exactly these columns.
+    #[doc(hidden)]
+    fn push(self, archetype: &mut Archetype, tick: u32);
+}
+
+macro_rules! bundle {
+    ($($name:ident $index:tt),*) => {
+        impl<$($name: Component),*> Bundle for ($($name,)*) {
+            fn columns() -> Vec<(TypeId, &'static str, Box<dyn Column>)> {
+                vec![$((TypeId::of::<$name>(), type_name::<$name>(), new_column::<$name>())),*]
+            }
+
+            fn push(self, archetype: &mut Archetype, tick: u32) {
+                $(
+                    let i = archetype.column_index(TypeId::of::<$name>()).unwrap();
+                    let column = typed_mut::<$name>(&mut **archetype.columns[i].get_mut().unwrap());
+                    column.data.push(self.$index);
+                    column.added.push(tick);
+                    column.changed.push(tick);
+                )*
+            }
+        }
+    };
+}
+
+bundle!();
+bundle!(A 0);
+bundle!(A 0, B 1);
+bundle!(A 0, B 1, C 2);
+bundle!(A 0, B 1, C 2, D 3);
+bundle!(A 0, B 1, C 2, D 3, E 4);
+bundle!(A 0, B 1, C 2, D 3, E 4, F 5);
+bundle!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+bundle!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+
+/// Entities, their components grouped into archetypes, and global
+/// resources.
+///
+/// Structural changes (spawning, despawning, adding and removing
+/// components) need `&mut World`. Queries and resource access work through
+/// `&World` and lock what they touch, so systems that use different data
+/// can run in parallel; changes they want to make to the structure go
+/// through a [`Commands`] buffer.
+pub struct World {
+    entities: Entities,
+    /// Archetype 0 has no components.
+    archetypes: Vec<Archetype>,
+    archetype_ids: HashMap<Vec<TypeId>, usize>,
+    resources: HashMap<TypeId, RwLock<Box<dyn Any + Send + Sync>>>,
+    change_tick: AtomicU32,
+    last_clear: u32,
+}
+
+impl Default for World {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl World {
+    pub fn new() -> Self {
+        let mut archetype_ids = HashMap::new();
+        archetype_ids.insert(Vec::new(), 0);
+
+        Self {
+            entities: Entities::default(),
+            archetypes: vec![Archetype::new(Vec::new())],
+            archetype_ids,
+            resources: HashMap::new(),
+            change_tick: AtomicU32::new(1),
+            last_clear: 1,
+        }
+    }
+
+    /// Panics if the bundle names a component type twice.
+    pub fn spawn<B: Bundle>(&mut self, bundle: B) -> Entity {
+        self.flush();
+        let entity = self.entities.alloc();
+        self.place(entity, bundle);
+
+        return entity;
+    }
+
+    /// An ID for an entity that is created, without components, the next
+    /// time the world is changed structurally.
+    pub fn reserve(&self) -> Entity {
+        self.entities.reserve()
+    }
+
+    /// Removes the entity and its components; returns `false` if it was
+    /// not alive.
+    pub fn despawn(&mut self, entity: Entity) -> bool {
+        self.flush();
+        let Some(location) = self.entities.location(entity) else {
+            return false;
+        };
+
+        let archetype = &mut self.archetypes[location.archetype];
+        for column in &mut archetype.columns {
+            column.get_mut().unwrap().swap_remove(location.row);
+        }
+        self.remove_row(location);
+        self.entities.free(entity);
+
+        return true;
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.entities.contains(entity)
+    }
+
+    /// Live entities.
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn has<T: Component>(&self, entity: Entity) -> bool {
+        self.entities
+            .location(entity)
+            .is_some_and(|l| self.archetypes[l.archetype].contains(TypeId::of::<T>()))
+    }
+
+    /// Adds the component or replaces the existing one, which counts as a
+    /// change but not as adding it; returns `false` if the entity is not
+    /// alive.
+    pub fn insert<T: Component>(&mut self, entity: Entity, component: T) -> bool {
+        self.flush();
+        let Some(location) = self.entities.location(entity) else {
+            return false;
+        };
+        let tick = self.change_tick();
+
+        let id = TypeId::of::<T>();
+        if let Some(i) = self.archetypes[location.archetype].column_index(id) {
+            let column = typed_mut::<T>(
+                &mut **self.archetypes[location.archetype].columns[i]
+                    .get_mut()
+                    .unwrap(),
+            );
+            column.data[location.row] = component;
+            column.changed[location.row] = tick;
+            return true;
+        }
+
+        let target = self.neighbour(
+            location.archetype,
+            Some((id, type_name::<T>(), new_column::<T>())),
+            None,
+        );
+        let row = self.move_entity(entity, location, target, None);
+        let i = self.archetypes[target].column_index(id).unwrap();
+        let column = typed_mut::<T>(&mut **self.archetypes[target].columns[i].get_mut().unwrap());
+        column.data.push(component);
+        column.added.push(tick);
+        column.changed.push(tick);
+        debug_assert_eq!(column.data.len(), row + 1);
+
+        return true;
+    }
+
+    /// Removes and returns the component, if the entity has it.
+    pub fn remove<T: Component>(&mut self, entity: Entity) -> Option<T> {
+        self.flush();
+        let location = self.entities.location(entity)?;
+        let id = TypeId::of::<T>();
+        self.archetypes[location.archetype].column_index(id)?;
+
+        let mut taken = new_column::<T>();
+        let target = self.neighbour(location.archetype, None, Some(id));
+        self.move_entity(entity, location, target, Some(&mut *taken));
+        let value = typed_mut::<T>(&mut *taken).data.pop().unwrap();
+
+        return Some(value);
+    }
+
+    /// Read access to a component. Blocks while a query writes the
+    /// component's column on another thread, and deadlocks on this one.
+    pub fn get<T: Component>(&self, entity: Entity) -> Option<Ref<'_, T>> {
+        let location = self.entities.location(entity)?;
+        let archetype = &self.archetypes[location.archetype];
+        let i = archetype.column_index(TypeId::of::<T>())?;
+
+        return Some(Ref {
+            guard: archetype.columns[i].read().unwrap(),
+            row: location.row,
+            marker: std::marker::PhantomData,
+        });
+    }
+
+    /// Write access to a component; writing through it marks it changed.
+    pub fn get_mut<T: Component>(&mut self, entity: Entity) -> Option<Mut<'_, T>> {
+        let location = self.entities.location(entity)?;
+        let tick = self.change_tick();
+        let archetype = &mut self.archetypes[location.archetype];
+        let i = archetype.column_index(TypeId::of::<T>())?;
+
+        let column = typed_mut::<T>(&mut **archetype.columns[i].get_mut().unwrap());
+        return Some(Mut::new(
+            &mut column.data[location.row],
+            &mut column.changed[location.row],
+            tick,
+        ));
+    }
+
+    pub(crate) fn entities(&self) -> &Entities {
+        &self.entities
+    }
+
+    pub fn archetypes(&self) -> &[Archetype] {
+        &self.archetypes
+    }
+
+    /// Iterates over the entities with the components in `D`.
+    pub fn query<D: QueryData>(&self) -> Query<'_, D> {
+        Query::new(self, self.last_clear, self.change_tick())
+    }
+
+    /// [`query`](Self::query) restricted by a filter such as
+    /// `(With<A>, Changed<B>)`.
+    pub fn query_filtered<D: QueryData, F: QueryFilter>(&self) -> Query<'_, D, F> {
+        Query::new(self, self.last_clear, self.change_tick())
+    }
+
+    /// Replaces any resource of the same type.
+    pub fn insert_resource<R: Component>(&mut self, resource: R) {
+        self.resources
+            .insert(TypeId::of::<R>(), RwLock::new(Box::new(resource)));
+    }
+
+    pub fn remove_resource<R: Component>(&mut self) -> Option<R> {
+        let resource = self.resources.remove(&TypeId::of::<R>())?;
+
+        return Some(*resource.into_inner().unwrap().downcast::<R>().unwrap());
+    }
+
+    pub fn contains_resource<R: Component>(&self) -> bool {
+        self.resources.contains_key(&TypeId::of::<R>())
+    }
+
+    pub fn resource<R: Component>(&self) -> Option<Res<'_, R>> {
+        let lock = self.resources.get(&TypeId::of::<R>())?;
+
+        return Some(Res {
+            guard: lock.read().unwrap(),
+            marker: std::marker::PhantomData,
+        });
+    }
+
+    pub fn resource_mut<R: Component>(&self) -> Option<ResMut<'_, R>> {
+        let lock = self.resources.get(&TypeId::of::<R>())?;
+
+        return Some(ResMut {
+            guard: lock.write().unwrap(),
+            marker: std::marker::PhantomData,
+        });
+    }
+
+    /// A buffer for structural changes made through `&World`.
+    pub fn commands(&self) -> Commands<'_> {
+        Commands::new(self)
+    }
+
+    /// Runs buffered commands in the order they were recorded.
+    pub fn apply(&mut self, buffer: CommandBuffer) {
+        self.flush();
+        buffer.apply(self);
+    }
+
+    /// The tick stamped on components changed now.
+    pub fn change_tick(&self) -> u32 {
+        self.change_tick.load(Ordering::Relaxed)
+    }
+
+    /// Ends a frame: changes made so far no longer match
+    /// [`Changed`](super::Changed) in queries from [`query`](Self::query).
+    pub fn clear_trackers(&mut self) {
+        let tick = self.change_tick.get_mut();
+        *tick += 1;
+        self.last_clear = *tick;
+    }
+
+    /// Advances the change tick and returns the previous value, so that
+    /// each system run stamps its own tick and later changes are newer.
+    pub(crate) fn increment_change_tick(&self) -> u32 {
+        self.change_tick.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Creates reserved entities without components.
+    pub(crate) fn flush(&mut self) {
+        for entity in self.entities.flush() {
+            self.entities.set_location(
+                entity,
+                Location {
+                    archetype: 0,
+                    row: self.archetypes[0].entities.len(),
+                },
+            );
+            self.archetypes[0].entities.push(entity);
+        }
+    }
+
+    /// Stores `bundle` for an allocated entity with no components yet.
+    pub(crate) fn place<B: Bundle>(&mut self, entity: Entity, bundle: B) {
+        let columns = B::columns();
+        let mut ids: Vec<TypeId> = columns.iter().map(|(id, _, _)| *id).collect();
+        ids.sort();
+        assert!(
+            ids.windows(2).all(|w| w[0] != w[1]),
+            "bundle has duplicate component types"
+        );
+
+        let archetype = self.archetype_for(ids, columns);
+        let tick = self.change_tick();
+        let row = self.archetypes[archetype].entities.len();
+        bundle.push(&mut self.archetypes[archetype], tick);
+        self.archetypes[archetype].entities.push(entity);
+        self.entities
+            .set_location(entity, Location { archetype, row });
+    }
+
+    /// Gives a reserved entity its components.
+    pub(crate) fn spawn_reserved<B: Bundle>(&mut self, entity: Entity, bundle: B) {
+        self.flush();
+        let Some(location) = self.entities.location(entity) else {
+            return;
+        };
+        assert_eq!(location.archetype, 0, "entity already has components");
+
+        self.remove_row(location);
+        self.place(entity, bundle);
+    }
+
+    fn archetype_for(
+        &mut self,
+        ids: Vec<TypeId>,
+        columns: Vec<(TypeId, &'static str, Box<dyn Column>)>,
+    ) -> usize {
+        if let Some(&index) = self.archetype_ids.get(&ids) {
+            return index;
+        }
+
+        self.archetypes.push(Archetype::new(columns));
+        self.archetype_ids.insert(ids, self.archetypes.len() - 1);
+
+        return self.archetypes.len() - 1;
+    }
+
+    /// The archetype with `archetype`'s components plus `extra` and minus
+    /// `removed`.
+    fn neighbour(
+        &mut self,
+        archetype: usize,
+        extra: Option<(TypeId, &'static str, Box<dyn Column>)>,
+        removed: Option<TypeId>,
+    ) -> usize {
+        let columns = self.archetypes[archetype].neighbour_columns(extra, removed);
+        let mut ids: Vec<TypeId> = columns.iter().map(|(id, _, _)| *id).collect();
+        ids.sort();
+
+        return self.archetype_for(ids, columns);
+    }
+
+    /// Moves the entity's row to `target`, which must have the same
+    /// components as its archetype plus or minus one. The value of a column
+    /// `target` lacks goes to `removed`, or is dropped. Returns the new row.
+    fn move_entity(
+        &mut self,
+        entity: Entity,
+        location: Location,
+        target: usize,
+        mut removed: Option<&mut dyn Column>,
+    ) -> usize {
+        let (source, destination) = pair_mut(&mut self.archetypes, location.archetype, target);
+
+        for i in 0..source.types().len() {
+            let j = destination.column_index(source.types()[i]);
+            let column = source.columns[i].get_mut().unwrap();
+            match j {
+                Some(j) => column.move_row(
+                    location.row,
+                    &mut **destination.columns[j].get_mut().unwrap(),
+                ),
+                None => match removed.take() {
+                    Some(to) => column.move_row(location.row, to),
+                    None => column.swap_remove(location.row),
+                },
+            }
+        }
+
+        let row = destination.entities.len();
+        destination.entities.push(entity);
+        self.remove_row(location);
+        self.entities.set_location(
+            entity,
+            Location {
+                archetype: target,
+                row,
+            },
+        );
+
+        return row;
+    }
+
+    /// Removes the entity at `location` from the archetype's entity list
+    /// (its columns are already handled) and fixes the location of the
+    /// entity moved into its row.
+    fn remove_row(&mut self, location: Location) {
+        let archetype = &mut self.archetypes[location.archetype];
+        archetype.entities.swap_remove(location.row);
+
+        if let Some(&moved) = archetype.entities.get(location.row) {
+            self.entities.set_location(moved, location);
+        }
+    }
+}
+
+/// Two distinct elements of a slice, mutably.
+fn pair_mut<T>(items: &mut [T], a: usize, b: usize) -> (&mut T, &mut T) {
+    assert_ne!(a, b);
+
+    if a < b {
+        let (left, right) = items.split_at_mut(b);
+        return (&mut left[a], &mut right[0]);
+    }
+
+    let (left, right) = items.split_at_mut(a);
+    return (&mut right[0], &mut left[b]);
+}
+
+/// A component borrowed from the [`World`].
+pub struct Ref<'w, T> {
+    guard: RwLockReadGuard<'w, Box<dyn Column>>,
+    row: usize,
+    marker: std::marker::PhantomData<T>,
+}
+
+impl<T: Component> Deref for Ref<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &typed::<T>(&**self.guard).data[self.row]
+    }
+}
+
+/// Mutable access to a component that records a change when written.
+pub struct Mut<'a, T> {
+    value: &'a mut T,
+    changed: &'a mut u32,
+    tick: u32,
+}
+
+impl<'a, T> Mut<'a, T> {
+    pub(crate) fn new(value: &'a mut T, changed: &'a mut u32, tick: u32) -> Self {
+        Self {
+            value,
+            changed,
+            tick,
+        }
+    }
+}
+
+impl<T> Deref for Mut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+impl<T> DerefMut for Mut<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        *self.changed = self.tick;
+        self.value
+    }
+}
+
+/// A resource borrowed from the [`World`].
+pub struct Res<'w, R> {
+    guard: RwLockReadGuard<'w, Box<dyn Any + Send + Sync>>,
+    marker: std::marker::PhantomData<R>,
+}
+
+impl<R: Component> Deref for Res<'_, R> {
+    type Target = R;
+
+    fn deref(&self) -> &R {
+        self.guard.downcast_ref().unwrap()
+    }
+}
+
+/// A resource borrowed mutably from the [`World`].
+pub struct ResMut<'w, R> {
+    guard: RwLockWriteGuard<'w, Box<dyn Any + Send + Sync>>,
+    marker: std::marker::PhantomData<R>,
+}
+
+impl<R: Component> Deref for ResMut<'_, R> {
+    type Target = R;
+
+    fn deref(&self) -> &R {
+        self.guard.downcast_ref().unwrap()
+    }
+}
+
+impl<R: Component> DerefMut for ResMut<'_, R> {
+    fn deref_mut(&mut self) -> &mut R {
+        self.guard.downcast_mut().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct A(u32);
+
+    #[derive(Debug, PartialEq)]
+    struct B(u32);
+
+    fn archetype_of(world: &World, entity: Entity) -> usize {
+        world.entities().location(entity).unwrap().archetype
+    }
+
+    #[test]
+    fn insert_moves_archetype_test() {
+        let mut world = World::new();
+        let first = world.spawn((A(1),));
+        let second = world.spawn((A(2),));
+        let start = archetype_of(&world, first);
+
+        assert!(world.insert(first, B(10)));
+        let moved = archetype_of(&world, first);
+        assert_ne!(moved, start);
+        assert_eq!(archetype_of(&world, second), start);
+        assert_eq!(world.archetypes()[start].entities(), [second]);
+        assert_eq!(world.archetypes()[moved].entities(), [first]);
+        assert_eq!(world.archetypes()[moved].types().len(), 2);
+        assert_eq!(*world.get::<A>(first).unwrap(), A(1));
+        assert_eq!(*world.get::<A>(second).unwrap(), A(2));
+
+        // The archetype for the same set of components is reused.
+        assert!(world.insert(second, B(20)));
+        assert_eq!(archetype_of(&world, second), moved);
+        assert_eq!(world.archetypes().len(), 3);
+        assert!(world.archetypes()[start].is_empty());
+    }
+
+    #[test]
+    fn remove_moves_archetype_test() {
+        let mut world = World::new();
+        let single = world.spawn((A(0),));
+        let both = world.spawn((A(1), B(1)));
+        let other = world.spawn((A(2), B(2)));
+
+        assert_eq!(world.remove::<B>(both), Some(B(1)));
+        assert_eq!(archetype_of(&world, both), archetype_of(&world, single));
+        assert!(!world.has::<B>(both));
+        assert_eq!(*world.get::<A>(both).unwrap(), A(1));
+        // `other` filled the row `both` left behind.
+        assert_eq!(*world.get::<B>(other).unwrap(), B(2));
+
+        assert_eq!(world.remove::<A>(both), Some(A(1)));
+        assert_eq!(archetype_of(&world, both), 0);
+        assert_eq!(world.archetypes().len(), 3);
+    }
+
+    #[test]
+    fn despawn_dead_test() {
+        let mut world = World::new();
+        let dead = world.spawn((A(0),));
+        assert!(world.despawn(dead));
+        let alive = world.spawn((A(1),));
+        assert_eq!(alive.index(), dead.index());
+
+        assert!(!world.despawn(dead));
+        assert!(!world.insert(dead, B(0)));
+        assert_eq!(world.remove::<A>(dead), None);
+        assert!(world.get_mut::<A>(dead).is_none());
+        assert_eq!(*world.get::<A>(alive).unwrap(), A(1));
+        assert_eq!(world.len(), 1);
+    }
+
+    #[test]
+    fn despawn_reserved_test() {
+        let mut world = World::new();
+        let reserved = world.reserve();
+        assert!(!world.contains(reserved));
+
+        assert!(world.despawn(reserved));
+        assert!(!world.contains(reserved));
+        assert!(world.is_empty());
+    }
+}
@@ -0,0 +1,740 @@
+//! Polygon booleans by the Greiner–Hormann clipping algorithm.
+//!
+//! Every ring of both operands is intersected with every ring of the other,
+//! the crossings are labelled as entries or exits, and the result is traced
+//! by walking between the two sets of rings. The algorithm cannot handle a
+//! vertex lying exactly on the other polygon's boundary, so when that
+//! happens the edges of the second operand are offset by a tiny distance
+//! and the clip is retried: outwards for unions and differences, so that
+//! touching inputs overlap, and inwards for intersections, so that they
+//! part. Every output vertex is then snapped back onto the unmoved inputs,
+//! so shared vertices and edges come out exactly where they were, and
+//! duplicate and collinear vertices are dropped.
+
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+use crate::math::{
+    predicates::{orient2d, orient2d_raw},
+    Vector2D,
+};
+
+use super::{signed_area, Polygon};
+
+type Point = [f64; 2];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BooleanError {
+    /// An input coordinate is NaN or infinite.
+    NonFinite,
+    /// The inputs stayed degenerate however they were nudged.
+    Degenerate,
+}
+
+impl Display for BooleanError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            BooleanError::NonFinite => write!(f, "polygon has a non-finite coordinate"),
+            BooleanError::Degenerate => write!(f, "polygon clipping did not converge"),
+        }
+    }
+}
+
+impl Error for BooleanError {}
+
+/// Regions covered by both `a` and `b`.
+pub fn intersection(a: &Polygon, b: &Polygon) -> Result<Vec<Polygon>, BooleanError> {
+    clip(a, b, Operation::Intersection)
+}
+
+/// Regions covered by `a`, `b` or both.
+pub fn union(a: &Polygon, b: &Polygon) -> Result<Vec<Polygon>, BooleanError> {
+    clip(a, b, Operation::Union)
+}
+
+/// Regions covered by `a` but not by `b`.
+pub fn difference(a: &Polygon, b: &Polygon) -> Result<Vec<Polygon>, BooleanError> {
+    clip(a, b, Operation::Difference)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operation {
+    Intersection,
+    Union,
+    Difference,
+}
+
+impl Operation {
+    /// Whether the entry flags of the subject and the clip are inverted.
+    fn flips(self) -> (bool, bool) {
+        match self {
+            Operation::Intersection => (false, false),
+            Operation::Union => (true, true),
+            Operation::Difference => (true, false),
+        }
+    }
+}
+
+const MAX_ATTEMPTS: usize = 8;
+
+fn clip(a: &Polygon, b: &Polygon, operation: Operation) -> Result<Vec<Polygon>, BooleanError> {
+    let (subject, _) = rings(a);
+    let (clip, outward) = rings(b);
+    if subject
+        .iter()
+        .chain(&clip)
+        .flatten()
+        .flatten()
+        .any(|c| !c.is_finite())
+    {
+        return Err(BooleanError::NonFinite);
+    }
+
+    let scale = subject
+        .iter()
+        .chain(&clip)
+        .flatten()
+        .fold(1.0f64, |m, p| m.max(p[0].abs()).max(p[1].abs()));
+
+    for attempt in 0..MAX_ATTEMPTS {
+        let delta = attempt as f64 * scale * 1e-7;
+        let distance = match operation {
+            Operation::Intersection => -delta,
+            Operation::Union | Operation::Difference => delta,
+        };
+        let moved = offset(&clip, &outward, distance);
+        // Crossings further than this from a shared vertex are real.
+        let tolerance = MAX_MITER * 2.0 * delta;
+
+        if let Some(result) = try_clip(&subject, &clip, &moved, tolerance, operation) {
+            return Ok(assemble(result));
+        }
+    }
+
+    return Err(BooleanError::Degenerate);
+}
+
+/// The rings with at least three vertices, each with the sign that turns
+/// its left normals away from the region the polygon covers.
+fn rings(polygon: &Polygon) -> (Vec<Vec<Point>>, Vec<f64>) {
+    let mut rings = Vec::with_capacity(polygon.holes.len() + 1);
+    let mut outward = Vec::with_capacity(polygon.holes.len() + 1);
+
+    for (i, ring) in std::iter::once(&polygon.outer)
+        .chain(&polygon.holes)
+        .enumerate()
+    {
+        if ring.len() >= 3 {
+            rings.push(ring.iter().map(|p| [p.x() as f64, p.y() as f64]).collect());
+            // The region is inside the outer ring and outside the holes.
+            let region_on_left = (signed_area(ring) >= 0.0) == (i == 0);
+            outward.push(if region_on_left { -1.0 } else { 1.0 });
+        }
+    }
+
+    (rings, outward)
+}
+
+/// The largest factor by which [`offset`] moves a vertex further than its
+/// edges, which limits how far sharp corners shoot out.
+const MAX_MITER: f64 = 20.0;
+
+/// Moves every edge `distance` away from the region the rings bound, or
+/// into it if `distance` is negative, with vertices where the moved edges
+/// meet.
+fn offset(rings: &[Vec<Point>], outward: &[f64], distance: f64) -> Vec<Vec<Point>> {
+    rings
+        .iter()
+        .zip(outward)
+        .map(|(ring, &side)| {
+            let n = ring.len();
+            let normal = |i: usize| -> Point {
+                let (a, b) = (ring[i], ring[(i + 1) % n]);
+                let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+                let length = dx.hypot(dy);
+                if length == 0.0 {
+                    return [0.0, 0.0];
+                }
+                [-dy / length * side, dx / length * side]
+            };
+
+            (0..n)
+                .map(|i| {
+                    let (n1, n2) = (normal((i + n - 1) % n), normal(i));
+                    let dot = n1[0] * n2[0] + n1[1] * n2[1];
+                    let scale = distance / (1.0 + dot).max(2.0 / MAX_MITER);
+                    [
+                        ring[i][0] + (n1[0] + n2[0]) * scale,
+                        ring[i][1] + (n1[1] + n2[1]) * scale,
+                    ]
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    point: Point,
+    /// Where the node lies on the unshifted inputs.
+    original: Point,
+    /// For crossings, the position of the same crossing in the other
+    /// polygon.
+    neighbor: Option<(usize, usize)>,
+    entry: bool,
+    visited: bool,
+}
+
+struct Crossing {
+    point: Point,
+    original: Point,
+    subject: (usize, usize, f64),
+    clip: (usize, usize, f64),
+}
+
+/// Runs one clip against `clip`, the `original` clip rings moved slightly,
+/// or returns `None` if the input is degenerate.
+fn try_clip(
+    subject: &[Vec<Point>],
+    original: &[Vec<Point>],
+    clip: &[Vec<Point>],
+    tolerance: f64,
+    operation: Operation,
+) -> Option<Vec<Vec<Point>>> {
+    let mut crossings = Vec::new();
+
+    for (i, ra) in subject.iter().enumerate() {
+        for ea in 0..ra.len() {
+            let (a1, a2) = (ra[ea], ra[(ea + 1) % ra.len()]);
+            for (j, rb) in clip.iter().enumerate() {
+                for eb in 0..rb.len() {
+                    let (b1, b2) = (rb[eb], rb[(eb + 1) % rb.len()]);
+                    if let Some((t, u)) = crossing(a1, a2, b1, b2)? {
+                        let point = [a1[0] + t * (a2[0] - a1[0]), a1[1] + t * (a2[1] - a1[1])];
+                        let ob = &original[j];
+                        let unshifted = (ob[eb], ob[(eb + 1) % ob.len()]);
+                        crossings.push(Crossing {
+                            point,
+                            original: snap(point, (a1, a2), unshifted, tolerance),
+                            subject: (i, ea, t),
+                            clip: (j, eb, u),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    let (flip_subject, flip_clip) = operation.flips();
+    let mut a = build(
+        subject,
+        subject,
+        &crossings,
+        |c| c.subject,
+        clip,
+        flip_subject,
+    );
+    let mut b = build(clip, original, &crossings, |c| c.clip, subject, flip_clip);
+    link(&mut a, &mut b, &crossings);
+
+    let mut result = trace(&mut a, &mut b);
+
+    // Rings without crossings are either wholly kept or wholly dropped.
+    for (r, ring) in subject.iter().enumerate() {
+        let keep = match operation {
+            Operation::Intersection => contains(clip, &ring[0]),
+            Operation::Union | Operation::Difference => !contains(clip, &ring[0]),
+        };
+        if keep && !crossings.iter().any(|c| c.subject.0 == r) {
+            result.push(ring.clone());
+        }
+    }
+    for (r, ring) in clip.iter().enumerate() {
+        let keep = match operation {
+            Operation::Intersection | Operation::Difference => contains(subject, &ring[0]),
+            Operation::Union => !contains(subject, &ring[0]),
+        };
+        if keep && !crossings.iter().any(|c| c.clip.0 == r) {
+            result.push(original[r].clone());
+        }
+    }
+
+    Some(result)
+}
+
+/// Moves a crossing of edge `a` with the moved copy of edge `b` onto the
+/// unmoved inputs: to a nearby endpoint if there is one, since that is
+/// where touching inputs meet, or else to where the original edges cross.
+fn snap(point: Point, a: (Point, Point), b: (Point, Point), tolerance: f64) -> Point {
+    let distance = |q: &Point| (q[0] - point[0]).hypot(q[1] - point[1]);
+    let nearest = [a.0, a.1, b.0, b.1]
+        .into_iter()
+        .min_by(|p, q| distance(p).total_cmp(&distance(q)))
+        .unwrap();
+    if distance(&nearest) <= tolerance {
+        return nearest;
+    }
+
+    let (da, db) = (
+        [a.1[0] - a.0[0], a.1[1] - a.0[1]],
+        [b.1[0] - b.0[0], b.1[1] - b.0[1]],
+    );
+    let denominator = da[0] * db[1] - da[1] * db[0];
+    if denominator == 0.0 {
+        return point;
+    }
+    let t = ((b.0[0] - a.0[0]) * db[1] - (b.0[1] - a.0[1]) * db[0]) / denominator;
+
+    return [a.0[0] + t * da[0], a.0[1] + t * da[1]];
+}
+
+/// Parameters along both segments of a proper crossing, `Some(None)` if they
+/// do not meet, or `None` if they touch in a degenerate way.
+fn crossing(a1: Point, a2: Point, b1: Point, b2: Point) -> Option<Option<(f64, f64)>> {
+    let o1 = orient2d_raw(a1, a2, b1);
+    let o2 = orient2d_raw(a1, a2, b2);
+    let o3 = orient2d_raw(b1, b2, a1);
+    let o4 = orient2d_raw(b1, b2, a2);
+
+    if (o1 > 0.0 && o2 > 0.0) || (o1 < 0.0 && o2 < 0.0) {
+        return Some(None);
+    }
+    if (o3 > 0.0 && o4 > 0.0) || (o3 < 0.0 && o4 < 0.0) {
+        return Some(None);
+    }
+    if o1 == 0.0 && o2 == 0.0 {
+        let apart = a1[0].max(a2[0]) < b1[0].min(b2[0])
+            || b1[0].max(b2[0]) < a1[0].min(a2[0])
+            || a1[1].max(a2[1]) < b1[1].min(b2[1])
+            || b1[1].max(b2[1]) < a1[1].min(a2[1]);
+        return if apart { Some(None) } else { None };
+    }
+    if o1 == 0.0 || o2 == 0.0 || o3 == 0.0 || o4 == 0.0 {
+        return None;
+    }
+
+    Some(Some((o3 / (o3 - o4), o1 / (o1 - o2))))
+}
+
+/// Even-odd containment over all the rings of a polygon.
+fn contains(rings: &[Vec<Point>], p: &Point) -> bool {
+    let mut inside = false;
+
+    for ring in rings {
+        for i in 0..ring.len() {
+            let (a, b) = (ring[i], ring[(i + 1) % ring.len()]);
+            if (a[1] > p[1]) != (b[1] > p[1]) {
+                let side = orient2d_raw(a, b, *p);
+                if (side > 0.0) == (b[1] > a[1]) {
+                    inside = !inside;
+                }
+            }
+        }
+    }
+
+    inside
+}
+
+/// Node lists of one polygon with its crossings inserted in edge order and
+/// labelled as entries into or exits from `other`. `originals` are the
+/// rings before any shift.
+fn build(
+    rings: &[Vec<Point>],
+    originals: &[Vec<Point>],
+    crossings: &[Crossing],
+    side: impl Fn(&Crossing) -> (usize, usize, f64),
+    other: &[Vec<Point>],
+    flip: bool,
+) -> Vec<Vec<Node>> {
+    let mut result = Vec::with_capacity(rings.len());
+
+    for (r, ring) in rings.iter().enumerate() {
+        let mut nodes = Vec::with_capacity(ring.len());
+        let mut inside = contains(other, &ring[0]);
+
+        for (e, (&point, &original)) in ring.iter().zip(&originals[r]).enumerate() {
+            nodes.push(Node {
+                point,
+                original,
+                neighbor: None,
+                entry: false,
+                visited: false,
+            });
+
+            let mut on_edge: Vec<(f64, usize)> = crossings
+                .iter()
+                .enumerate()
+                .filter_map(|(id, c)| {
+                    let (cr, ce, t) = side(c);
+                    (cr == r && ce == e).then_some((t, id))
+                })
+                .collect();
+            on_edge.sort_by(|x, y| x.0.total_cmp(&y.0));
+
+            for (_, id) in on_edge {
+                nodes.push(Node {
+                    point: crossings[id].point,
+                    original: crossings[id].original,
+                    // Temporarily the crossing id; `link` resolves it.
+                    neighbor: Some((usize::MAX, id)),
+                    entry: inside == flip,
+                    visited: false,
+                });
+                inside = !inside;
+            }
+        }
+
+        result.push(nodes);
+    }
+
+    result
+}
+
+fn link(a: &mut [Vec<Node>], b: &mut [Vec<Node>], crossings: &[Crossing]) {
+    let mut in_a = vec![(0, 0); crossings.len()];
+    let mut in_b = vec![(0, 0); crossings.len()];
+
+    for (positions, rings) in [(&mut in_a, &*a), (&mut in_b, &*b)] {
+        for (r, ring) in rings.iter().enumerate() {
+            for (k, node) in ring.iter().enumerate() {
+                if let Some((_, id)) = node.neighbor {
+                    positions[id] = (r, k);
+                }
+            }
+        }
+    }
+
+    for (rings, positions) in [(a, &in_b), (b, &in_a)] {
+        for ring in rings.iter_mut() {
+            for node in ring.iter_mut() {
+                if let Some((_, id)) = node.neighbor {
+                    node.neighbor = Some(positions[id]);
+                }
+            }
+        }
+    }
+}
+
+/// Walks the crossings, switching polygons at each one, until every
+/// crossing has been used.
+fn trace(a: &mut [Vec<Node>], b: &mut [Vec<Node>]) -> Vec<Vec<Point>> {
+    let mut result = Vec::new();
+
+    for r in 0..a.len() {
+        for k in 0..a[r].len() {
+            if a[r][k].neighbor.is_none() || a[r][k].visited {
+                continue;
+            }
+
+            let mut ring = vec![a[r][k].original];
+            let (mut on_a, mut cur) = (true, (r, k));
+
+            loop {
+                let polygon: &mut [Vec<Node>] = if on_a { &mut *a } else { &mut *b };
+                polygon[cur.0][cur.1].visited = true;
+                let forward = polygon[cur.0][cur.1].entry;
+                let len = polygon[cur.0].len();
+
+                loop {
+                    cur.1 = if forward {
+                        (cur.1 + 1) % len
+                    } else {
+                        (cur.1 + len - 1) % len
+                    };
+                    let node = &mut polygon[cur.0][cur.1];
+                    ring.push(node.original);
+                    if node.neighbor.is_some() {
+                        node.visited = true;
+                        break;
+                    }
+                }
+
+                cur = polygon[cur.0][cur.1].neighbor.unwrap();
+                on_a = !on_a;
+
+                let other: &mut [Vec<Node>] = if on_a { &mut *a } else { &mut *b };
+                if other[cur.0][cur.1].visited {
+                    break;
+                }
+            }
+
+            // The walk ends back on the starting crossing.
+            ring.pop();
+            result.push(ring);
+        }
+    }
+
+    result
+}
+
+/// Sorts traced rings into outer boundaries and holes by how deeply each
+/// one is nested in the others.
+fn assemble(rings: Vec<Vec<Point>>) -> Vec<Polygon> {
+    let rings: Vec<Vec<Vector2D>> = rings
+        .into_iter()
+        .map(|r| {
+            simplify(
+                r.iter()
+                    .map(|p| Vector2D::create(p[0] as f32, p[1] as f32))
+                    .collect(),
+            )
+        })
+        .filter(|r: &Vec<Vector2D>| r.len() >= 3 && signed_area(r) != 0.0)
+        .collect();
+    let as_points: Vec<Vec<Point>> = rings
+        .iter()
+        .map(|r| r.iter().map(|p| [p.x() as f64, p.y() as f64]).collect())
+        .collect();
+
+    let parents: Vec<Vec<usize>> = (0..rings.len())
+        .map(|i| {
+            (0..rings.len())
+                .filter(|&j| j != i && contains(&as_points[j..=j], &probe(&as_points[i])))
+                .collect()
+        })
+        .collect();
+
+    let mut polygons = Vec::new();
+    let mut outer_of = vec![usize::MAX; rings.len()];
+
+    for i in 0..rings.len() {
+        if parents[i].len().is_multiple_of(2) {
+            outer_of[i] = polygons.len();
+            polygons.push(Polygon::new(rings[i].clone(), Vec::new()));
+        }
+    }
+
+    for i in 0..rings.len() {
+        if parents[i].len() % 2 == 1 {
+            // The innermost outer ring around the hole.
+            let owner = parents[i]
+                .iter()
+                .copied()
+                .find(|&j| parents[j].len() == parents[i].len() - 1)
+                .unwrap();
+            let mut hole = rings[i].clone();
+            if signed_area(&hole) > 0.0 {
+                hole.reverse();
+            }
+            polygons[outer_of[owner]].holes.push(hole);
+        }
+    }
+
+    polygons
+}
+
+/// Drops repeated vertices and vertices in line with their neighbours,
+/// including the tips of zero-width spikes left by snapping.
+fn simplify(mut ring: Vec<Vector2D>) -> Vec<Vector2D> {
+    loop {
+        let count = ring.len();
+        let mut i = 0;
+        while ring.len() >= 3 && i < ring.len() {
+            let n = ring.len();
+            let (previous, next) = (ring[(i + n - 1) % n], ring[(i + 1) % n]);
+            if orient2d(&previous, &ring[i], &next) == 0.0 {
+                ring.remove(i);
+            } else {
+                i += 1;
+            }
+        }
+
+        if ring.len() == count || ring.len() < 3 {
+            return ring;
+        }
+    }
+}
+
+/// A point just inside the ring next to its first edge, so nesting tests
+/// are not fooled by rings that share vertices.
+fn probe(ring: &[Point]) -> Point {
+    let area: f64 = (0..ring.len())
+        .map(|i| {
+            let (a, b) = (ring[i], ring[(i + 1) % ring.len()]);
+            a[0] * b[1] - b[0] * a[1]
+        })
+        .sum();
+    let (a, b) = (ring[0], ring[1]);
+    let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+    let length = (dx * dx + dy * dy).sqrt();
+    let side = if area > 0.0 { 1.0 } else { -1.0 };
+    let step = length * 1e-3;
+
+    [
+        (a[0] + b[0]) / 2.0 - side * dy / length * step,
+        (a[1] + b[1]) / 2.0 + side * dx / length * step,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(x: f32, y: f32, size: f32) -> Vec<Vector2D> {
+        vec![
+            Vector2D::create(x, y),
+            Vector2D::create(x + size, y),
+            Vector2D::create(x + size, y + size),
+            Vector2D::create(x, y + size),
+        ]
+    }
+
+    fn area(polygons: &[Polygon]) -> f32 {
+        polygons.iter().map(Polygon::area).sum()
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    #[test]
+    fn overlapping_squares_test() {
+        let a = Polygon::new(square(0.0, 0.0, 2.0), Vec::new());
+        let b = Polygon::new(square(1.0, 1.0, 2.0), Vec::new());
+
+        let i = intersection(&a, &b).unwrap();
+        assert_eq!(i.len(), 1);
+        assert_eq!(i[0].outer.len(), 4);
+        assert!(close(area(&i), 1.0));
+
+        let u = union(&a, &b).unwrap();
+        assert_eq!(u.len(), 1);
+        assert_eq!(u[0].outer.len(), 8);
+        assert!(close(area(&u), 7.0));
+
+        let d = difference(&a, &b).unwrap();
+        assert_eq!(d.len(), 1);
+        assert_eq!(d[0].outer.len(), 6);
+        assert!(close(area(&d), 3.0));
+        assert!(d[0].contains(&Vector2D::create(0.5, 0.5)));
+        assert!(!d[0].contains(&Vector2D::create(1.5, 1.5)));
+    }
+
+    #[test]
+    fn disjoint_and_nested_test() {
+        let a = Polygon::new(square(0.0, 0.0, 4.0), Vec::new());
+        let far = Polygon::new(square(10.0, 0.0, 1.0), Vec::new());
+        let inner = Polygon::new(square(1.0, 1.0, 1.0), Vec::new());
+
+        assert!(intersection(&a, &far).unwrap().is_empty());
+        assert_eq!(union(&a, &far).unwrap().len(), 2);
+        assert!(close(area(&difference(&a, &far).unwrap()), 16.0));
+
+        assert!(close(area(&intersection(&a, &inner).unwrap()), 1.0));
+        assert!(close(area(&union(&a, &inner).unwrap()), 16.0));
+
+        // Cutting a square out of the middle leaves a hole.
+        let d = difference(&a, &inner).unwrap();
+        assert_eq!(d.len(), 1);
+        assert_eq!(d[0].holes.len(), 1);
+        assert!(close(d[0].area(), 15.0));
+        assert!(difference(&inner, &a).unwrap().is_empty());
+    }
+
+    #[test]
+    fn holes_test() {
+        let ring = Polygon::new(square(0.0, 0.0, 4.0), vec![square(1.0, 1.0, 2.0)]);
+        let bar = Polygon::new(
+            vec![
+                Vector2D::create(-1.0, 1.5),
+                Vector2D::create(5.0, 1.5),
+                Vector2D::create(5.0, 2.5),
+                Vector2D::create(-1.0, 2.5),
+            ],
+            Vec::new(),
+        );
+
+        // The bar crosses the ring twice, once on either side of the hole.
+        let i = intersection(&ring, &bar).unwrap();
+        assert_eq!(i.len(), 2);
+        assert!(close(area(&i), 2.0));
+
+        // Splitting the ring with the bar leaves two U shapes.
+        let d = difference(&ring, &bar).unwrap();
+        assert_eq!(d.len(), 2);
+        assert!(close(area(&d), 10.0));
+
+        let u = union(&ring, &bar).unwrap();
+        assert!(close(area(&u), 12.0 + 4.0));
+        assert_eq!(u.iter().map(|p| p.holes.len()).sum::<usize>(), 2);
+    }
+
+    /// Whether two rings have the same vertices in the same cyclic order.
+    fn same_ring(a: &[Vector2D], b: &[Vector2D]) -> bool {
+        a.len() == b.len()
+            && (0..a.len()).any(|start| (0..a.len()).all(|i| a[(start + i) % a.len()] == b[i]))
+    }
+
+    #[test]
+    fn shared_edge_test() {
+        let a = Polygon::new(square(0.0, 0.0, 1.0), Vec::new());
+        let b = Polygon::new(square(1.0, 0.0, 1.0), Vec::new());
+        let left = Polygon::new(square(-1.0, 0.0, 1.0), Vec::new());
+
+        // Touching inputs are nudged apart, but the output lands exactly on
+        // the original vertices.
+        let u = union(&a, &b).unwrap();
+        assert_eq!(u.len(), 1, "{u:?}");
+        let rectangle = [
+            Vector2D::create(0.0, 0.0),
+            Vector2D::create(2.0, 0.0),
+            Vector2D::create(2.0, 1.0),
+            Vector2D::create(0.0, 1.0),
+        ];
+        assert!(same_ring(&u[0].outer, &rectangle), "{:?}", u[0].outer);
+
+        assert!(intersection(&a, &b).unwrap().is_empty());
+        assert!(intersection(&a, &left).unwrap().is_empty());
+        let d = difference(&a, &left).unwrap();
+        assert_eq!(d.len(), 1);
+        assert!(same_ring(&d[0].outer, &a.outer), "{:?}", d[0].outer);
+
+        for same in [intersection(&a, &a).unwrap(), union(&a, &a).unwrap()] {
+            assert_eq!(same.len(), 1);
+            assert!(same_ring(&same[0].outer, &a.outer), "{:?}", same[0].outer);
+        }
+        assert!(difference(&a, &a).unwrap().is_empty());
+
+        // Sharing part of an edge leaves an exact L shape.
+        let wide = Polygon::new(
+            vec![
+                Vector2D::create(0.0, 0.0),
+                Vector2D::create(2.0, 0.0),
+                Vector2D::create(2.0, 1.0),
+                Vector2D::create(0.0, 1.0),
+            ],
+            Vec::new(),
+        );
+        let top = Polygon::new(square(1.0, 1.0, 1.0), Vec::new());
+        let l = union(&wide, &top).unwrap();
+        assert_eq!(l.len(), 1);
+        let expected = [
+            Vector2D::create(0.0, 0.0),
+            Vector2D::create(2.0, 0.0),
+            Vector2D::create(2.0, 2.0),
+            Vector2D::create(1.0, 2.0),
+            Vector2D::create(1.0, 1.0),
+            Vector2D::create(0.0, 1.0),
+        ];
+        assert!(same_ring(&l[0].outer, &expected), "{:?}", l[0].outer);
+
+        // Sharing only a corner.
+        let corner = Polygon::new(square(1.0, 1.0, 1.0), Vec::new());
+        assert!(intersection(&a, &corner).unwrap().is_empty());
+        assert!(close(area(&union(&a, &corner).unwrap()), 2.0));
+    }
+
+    #[test]
+    fn error_test() {
+        let a = Polygon::new(square(0.0, 0.0, 1.0), Vec::new());
+        let nan = Polygon::new(square(f32::NAN, 0.0, 1.0), Vec::new());
+
+        assert_eq!(union(&a, &nan), Err(BooleanError::NonFinite));
+        assert_eq!(
+            BooleanError::Degenerate.to_string(),
+            "polygon clipping did not converge"
+        );
+    }
+}
@@ -0,0 +1,264 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+use crate::math::{
+    predicates::{incircle, orient2d},
+    Vector2D,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DelaunayError {
+    /// The point at this index has a NaN or infinite coordinate.
+    NonFinite(usize),
+}
+
+impl Display for DelaunayError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DelaunayError::NonFinite(i) => write!(f, "point {i} has a non-finite coordinate"),
+        }
+    }
+}
+
+impl Error for DelaunayError {}
+
+/// A triangulation of a point set, as indices into the input slice.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Triangulation {
+    /// Counterclockwise triangles.
+    pub triangles: Vec<[usize; 3]>,
+    /// Boundary vertices in counterclockwise order, including points that
+    /// lie on a hull edge.
+    pub hull: Vec<usize>,
+}
+
+impl Triangulation {
+    /// Maps every directed edge `(a, b)` to the triangle that contains it.
+    pub fn edges(&self) -> HashMap<(usize, usize), usize> {
+        let mut edges = HashMap::with_capacity(self.triangles.len() * 3);
+
+        for (id, t) in self.triangles.iter().enumerate() {
+            for k in 0..3 {
+                edges.insert((t[k], t[(k + 1) % 3]), id);
+            }
+        }
+
+        edges
+    }
+}
+
+/// Delaunay triangulation by a sweep over the sorted points followed by
+/// Lawson edge flips.
+///
+/// Duplicate points only take part once; the later copies are not referenced
+/// by any triangle. When all the points are collinear there are no triangles
+/// and `hull` lists the distinct points along the line.
+pub fn delaunay(points: &[Vector2D]) -> Result<Triangulation, DelaunayError> {
+    if let Some(i) = points
+        .iter()
+        .position(|p| !p.x().is_finite() || !p.y().is_finite())
+    {
+        return Err(DelaunayError::NonFinite(i));
+    }
+
+    let mut order: Vec<usize> = (0..points.len()).collect();
+    order.sort_by(|&a, &b| {
+        let (pa, pb) = (&points[a], &points[b]);
+        pa.x().total_cmp(&pb.x()).then(pa.y().total_cmp(&pb.y()))
+    });
+    order.dedup_by(|a, b| points[*a].x() == points[*b].x() && points[*a].y() == points[*b].y());
+
+    // Sorted points are collinear up to the first one that turns.
+    let first = (2..order.len())
+        .find(|&j| orient2d(&points[order[0]], &points[order[1]], &points[order[j]]) != 0.0);
+    let Some(first) = first else {
+        return Ok(Triangulation {
+            triangles: Vec::new(),
+            hull: order,
+        });
+    };
+
+    let chain = &order[..first];
+    let apex = order[first];
+    let mut triangles = Vec::with_capacity(order.len() * 2);
+    let mut hull: Vec<usize>;
+
+    if orient2d(&points[chain[0]], &points[chain[1]], &points[apex]) > 0.0 {
+        for m in 0..chain.len() - 1 {
+            triangles.push([chain[m], chain[m + 1], apex]);
+        }
+        hull = chain.to_vec();
+        hull.push(apex);
+    } else {
+        for m in 0..chain.len() - 1 {
+            triangles.push([chain[m + 1], chain[m], apex]);
+        }
+        hull = vec![apex];
+        hull.extend(chain.iter().rev());
+    }
+
+    for &p in &order[first + 1..] {
+        let n = hull.len();
+        let visible =
+            |k: usize| orient2d(&points[hull[k]], &points[hull[(k + 1) % n]], &points[p]) < 0.0;
+
+        // Each new point is lexicographically largest so far, hence strictly
+        // outside the hull and sees a contiguous run of edges.
+        let start = (0..n)
+            .find(|&k| visible(k) && !visible((k + n - 1) % n))
+            .expect("a new point sees the hull");
+        let mut count = 0;
+        while visible((start + count) % n) {
+            let u = hull[(start + count) % n];
+            let v = hull[(start + count + 1) % n];
+            triangles.push([v, u, p]);
+            count += 1;
+        }
+
+        hull.rotate_left(start);
+        hull.splice(1..count, [p]);
+    }
+
+    legalize(points, &mut triangles);
+
+    Ok(Triangulation { triangles, hull })
+}
+
+/// Flips edges until every one of them is locally Delaunay.
+fn legalize(points: &[Vector2D], triangles: &mut [[usize; 3]]) {
+    let mut edges: HashMap<(usize, usize), usize> = HashMap::new();
+    let mut stack = Vec::new();
+
+    for (id, t) in triangles.iter().enumerate() {
+        for k in 0..3 {
+            edges.insert((t[k], t[(k + 1) % 3]), id);
+            stack.push((t[k], t[(k + 1) % 3]));
+        }
+    }
+
+    while let Some((a, b)) = stack.pop() {
+        let (Some(&t1), Some(&t2)) = (edges.get(&(a, b)), edges.get(&(b, a))) else {
+            continue;
+        };
+        let c = opposite(&triangles[t1], a, b);
+        let d = opposite(&triangles[t2], b, a);
+
+        if incircle(&points[a], &points[b], &points[c], &points[d]) <= 0.0 {
+            continue;
+        }
+
+        // Replace ab with cd; the quad a, d, b, c is counterclockwise.
+        edges.remove(&(a, b));
+        edges.remove(&(b, a));
+        triangles[t1] = [a, d, c];
+        triangles[t2] = [d, b, c];
+        for (id, t) in [(t1, triangles[t1]), (t2, triangles[t2])] {
+            for k in 0..3 {
+                edges.insert((t[k], t[(k + 1) % 3]), id);
+            }
+        }
+
+        stack.extend([(a, d), (d, b), (b, c), (c, a)]);
+    }
+}
+
+fn opposite(t: &[usize; 3], a: usize, b: usize) -> usize {
+    for k in 0..3 {
+        if t[k] == a && t[(k + 1) % 3] == b {
+            return t[(k + 2) % 3];
+        }
+    }
+    unreachable!("edge ({a}, {b}) is not in triangle {t:?}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn p(x: f32, y: f32) -> Vector2D {
+        Vector2D::create(x, y)
+    }
+
+    fn assert_delaunay(points: &[Vector2D], triangulation: &Triangulation) {
+        for t in &triangulation.triangles {
+            assert!(orient2d(&points[t[0]], &points[t[1]], &points[t[2]]) > 0.0);
+            for q in points {
+                assert!(incircle(&points[t[0]], &points[t[1]], &points[t[2]], q) <= 0.0);
+            }
+        }
+    }
+
+    #[test]
+    fn delaunay_square_test() {
+        let points = [
+            p(0.0, 0.0),
+            p(1.0, 0.0),
+            p(1.0, 1.0),
+            p(0.0, 1.0),
+            p(0.5, 0.5),
+        ];
+        let triangulation = delaunay(&points).unwrap();
+
+        assert_eq!(triangulation.triangles.len(), 4);
+        assert!(triangulation.triangles.iter().all(|t| t.contains(&4)));
+        assert_eq!(triangulation.hull.len(), 4);
+        assert_delaunay(&points, &triangulation);
+    }
+
+    #[test]
+    fn delaunay_random_test() {
+        let mut rng = StdRng::seed_from_u64(33);
+        let points: Vec<Vector2D> = (0..300)
+            .map(|_| p(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)))
+            .collect();
+        let triangulation = delaunay(&points).unwrap();
+
+        // Euler: a triangulation of n points with h on the hull has
+        // 2n - 2 - h triangles.
+        let n = points.len();
+        let h = triangulation.hull.len();
+        assert_eq!(triangulation.triangles.len(), 2 * n - 2 - h);
+        assert_delaunay(&points, &triangulation);
+    }
+
+    #[test]
+    fn delaunay_grid_test() {
+        // Cocircular everywhere: any diagonal is valid, but the result must
+        // still be a proper triangulation.
+        let mut points = Vec::new();
+        for i in 0..6 {
+            for j in 0..6 {
+                points.push(p(i as f32, j as f32));
+            }
+        }
+        points.push(p(2.0, 3.0));
+        let triangulation = delaunay(&points).unwrap();
+
+        assert_eq!(triangulation.triangles.len(), 50);
+        assert_eq!(triangulation.hull.len(), 20);
+        assert_delaunay(&points, &triangulation);
+    }
+
+    #[test]
+    fn delaunay_collinear_test() {
+        let points = [p(2.0, 2.0), p(0.0, 0.0), p(1.0, 1.0)];
+        let triangulation = delaunay(&points).unwrap();
+
+        assert!(triangulation.triangles.is_empty());
+        assert_eq!(triangulation.hull, vec![1, 2, 0]);
+        assert_eq!(delaunay(&[]), Ok(Triangulation::default()));
+    }
+
+    #[test]
+    fn delaunay_non_finite_test() {
+        let points = [p(0.0, 0.0), p(1.0, 0.0), p(f32::NAN, 1.0), p(0.0, 1.0)];
+        assert_eq!(delaunay(&points), Err(DelaunayError::NonFinite(2)));
+
+        let points = [p(0.0, 0.0), p(1.0, 0.0), p(0.0, f32::INFINITY)];
+        assert_eq!(delaunay(&points), Err(DelaunayError::NonFinite(2)));
+    }
+}
@@ -0,0 +1,319 @@
+use std::collections::HashMap;
+
+use crate::math::{
+    predicates::{orient2d, orient3d},
+    Vector2D, Vector3D,
+};
+
+/// Convex hull of a 2D point set by Andrew's monotone chain.
+///
+/// Returns indices into `points` in counterclockwise order, starting at the
+/// lowest-leftmost point. Collinear points on the hull edges and duplicates
+/// are left out. Fewer than three indices are returned when every point is
+/// collinear.
+pub fn convex_hull_2d(points: &[Vector2D]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..points.len()).collect();
+    order.sort_by(|&a, &b| {
+        let (pa, pb) = (&points[a], &points[b]);
+        pa.x().total_cmp(&pb.x()).then(pa.y().total_cmp(&pb.y()))
+    });
+    order.dedup_by(|a, b| same(&points[*a], &points[*b]));
+
+    if order.len() < 3 {
+        return order;
+    }
+
+    let mut hull: Vec<usize> = Vec::with_capacity(order.len() * 2);
+
+    // Lower hull left to right, then upper hull right to left.
+    for pass in [order.clone(), order.iter().rev().copied().collect()] {
+        let start = hull.len();
+
+        for i in pass {
+            while hull.len() >= start + 2 {
+                let a = &points[hull[hull.len() - 2]];
+                let b = &points[hull[hull.len() - 1]];
+                if orient2d(a, b, &points[i]) > 0.0 {
+                    break;
+                }
+                hull.pop();
+            }
+            hull.push(i);
+        }
+
+        // The last point of each chain starts the other one.
+        hull.pop();
+    }
+
+    if hull.len() < 3 {
+        hull.truncate(2);
+    }
+
+    hull
+}
+
+fn same(a: &Vector2D, b: &Vector2D) -> bool {
+    a.x() == b.x() && a.y() == b.y()
+}
+
+/// Convex hull of a 3D point set by incremental construction.
+///
+/// Returns triangles as index triples into `points`, wound counterclockwise
+/// when seen from outside the hull. An empty list is returned when all the
+/// points are coplanar.
+pub fn convex_hull_3d(points: &[Vector3D]) -> Vec<[usize; 3]> {
+    let Some(seed) = initial_tetrahedron(points) else {
+        return Vec::new();
+    };
+
+    let mut faces: Vec<Option<[usize; 3]>> = Vec::new();
+    // Directed edge -> face that contains it.
+    let mut edges: HashMap<(usize, usize), usize> = HashMap::new();
+
+    let add_face = |faces: &mut Vec<Option<[usize; 3]>>,
+                    edges: &mut HashMap<(usize, usize), usize>,
+                    f: [usize; 3]| {
+        let id = faces.len();
+        faces.push(Some(f));
+        for k in 0..3 {
+            edges.insert((f[k], f[(k + 1) % 3]), id);
+        }
+    };
+
+    let [a, b, c, d] = seed;
+    for (face, opposite) in [
+        ([a, b, c], d),
+        ([a, b, d], c),
+        ([a, c, d], b),
+        ([b, c, d], a),
+    ] {
+        let [p, q, r] = face;
+        // Orient every face so the remaining vertex lies below it.
+        if orient3d(&points[p], &points[q], &points[r], &points[opposite]) > 0.0 {
+            add_face(&mut faces, &mut edges, [p, q, r]);
+        } else {
+            add_face(&mut faces, &mut edges, [p, r, q]);
+        }
+    }
+
+    for i in 0..points.len() {
+        if seed.contains(&i) {
+            continue;
+        }
+
+        let p = &points[i];
+        let visible: Vec<usize> = faces
+            .iter()
+            .enumerate()
+            .filter_map(|(id, f)| {
+                let [a, b, c] = (*f)?;
+                (orient3d(&points[a], &points[b], &points[c], p) < 0.0).then_some(id)
+            })
+            .collect();
+
+        if visible.is_empty() {
+            continue;
+        }
+
+        // Horizon edges belong to a visible face whose twin is not visible.
+        let mut horizon = Vec::new();
+        for &id in &visible {
+            let f = faces[id].unwrap();
+            for k in 0..3 {
+                let (u, v) = (f[k], f[(k + 1) % 3]);
+                let twin = edges[&(v, u)];
+                if !visible.contains(&twin) {
+                    horizon.push((u, v));
+                }
+            }
+        }
+
+        for &id in &visible {
+            let f = faces[id].take().unwrap();
+            for k in 0..3 {
+                edges.remove(&(f[k], f[(k + 1) % 3]));
+            }
+        }
+
+        for (u, v) in horizon {
+            add_face(&mut faces, &mut edges, [u, v, i]);
+        }
+    }
+
+    faces.into_iter().flatten().collect()
+}
+
+/// Four points spanning a tetrahedron, chosen from the extreme points.
+fn initial_tetrahedron(points: &[Vector3D]) -> Option<[usize; 4]> {
+    if points.len() < 4 {
+        return None;
+    }
+
+    let a = (0..points.len()).min_by(|&i, &j| points[i].x().total_cmp(&points[j].x()))?;
+    let b = (0..points.len()).max_by(|&i, &j| {
+        let (di, dj) = (
+            points[i].distance(&points[a]),
+            points[j].distance(&points[a]),
+        );
+        di.total_cmp(&dj)
+    })?;
+    if points[a].distance(&points[b]) == 0.0 {
+        return None;
+    }
+
+    let ab = points[b] - points[a];
+    let c = (0..points.len()).max_by(|&i, &j| {
+        let ci = ab.cross(&(points[i] - points[a])).magnitude();
+        let cj = ab.cross(&(points[j] - points[a])).magnitude();
+        ci.total_cmp(&cj)
+    })?;
+
+    let normal = ab.cross(&(points[c] - points[a]));
+    let d = (0..points.len()).max_by(|&i, &j| {
+        let di = normal.dot(&(points[i] - points[a])).abs();
+        let dj = normal.dot(&(points[j] - points[a])).abs();
+        di.total_cmp(&dj)
+    })?;
+
+    // The float search above only picks candidates; the exact predicate
+    // decides whether they are really non-degenerate.
+    if orient3d(&points[a], &points[b], &points[c], &points[d]) != 0.0 {
+        return Some([a, b, c, d]);
+    }
+
+    // Fall back to any point off the plane through a, b, c.
+    (0..points.len())
+        .find(|&i| orient3d(&points[a], &points[b], &points[c], &points[i]) != 0.0)
+        .map(|d| [a, b, c, d])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn p2(x: f32, y: f32) -> Vector2D {
+        Vector2D::create(x, y)
+    }
+
+    fn p3(x: f32, y: f32, z: f32) -> Vector3D {
+        Vector3D::create(x, y, z)
+    }
+
+    #[test]
+    fn hull_2d_square_test() {
+        let points = [
+            p2(0.0, 0.0),
+            p2(1.0, 1.0),
+            p2(2.0, 0.0),
+            p2(2.0, 2.0),
+            p2(0.0, 2.0),
+            p2(1.0, 0.0),
+            p2(0.5, 1.5),
+        ];
+
+        assert_eq!(convex_hull_2d(&points), vec![0, 2, 3, 4]);
+    }
+
+    #[test]
+    fn hull_2d_degenerate_test() {
+        assert!(convex_hull_2d(&[]).is_empty());
+        assert_eq!(convex_hull_2d(&[p2(1.0, 1.0), p2(1.0, 1.0)]), vec![0]);
+
+        let line = [p2(0.0, 0.0), p2(2.0, 2.0), p2(1.0, 1.0), p2(3.0, 3.0)];
+        assert_eq!(convex_hull_2d(&line), vec![0, 3]);
+    }
+
+    #[test]
+    fn hull_2d_nearly_collinear_test() {
+        // A tiny bump above the segment must stay on the hull, a point
+        // exactly on it must not.
+        let points = [
+            p2(0.0, 0.0),
+            p2(1.0, 0.0),
+            p2(0.5, 1e-7),
+            p2(0.25, 0.0),
+            p2(0.5, -1.0),
+        ];
+
+        assert_eq!(convex_hull_2d(&points), vec![0, 4, 1, 2]);
+    }
+
+    #[test]
+    fn hull_2d_random_test() {
+        let mut rng = StdRng::seed_from_u64(31);
+        let points: Vec<Vector2D> = (0..500)
+            .map(|_| p2(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)))
+            .collect();
+        let hull = convex_hull_2d(&points);
+
+        for k in 0..hull.len() {
+            let a = &points[hull[k]];
+            let b = &points[hull[(k + 1) % hull.len()]];
+            for p in &points {
+                assert!(orient2d(a, b, p) >= 0.0);
+            }
+        }
+    }
+
+    #[test]
+    fn hull_3d_cube_test() {
+        let mut points = Vec::new();
+        for &x in &[0.0, 1.0] {
+            for &y in &[0.0, 1.0] {
+                for &z in &[0.0, 1.0] {
+                    points.push(p3(x, y, z));
+                }
+            }
+        }
+        points.push(p3(0.5, 0.5, 0.5));
+
+        let faces = convex_hull_3d(&points);
+
+        // Six square faces, each split in two; coplanar corners may be
+        // triangulated either way.
+        assert_eq!(faces.len(), 12);
+        assert!(faces.iter().all(|f| !f.contains(&8)));
+    }
+
+    #[test]
+    fn hull_3d_random_test() {
+        let mut rng = StdRng::seed_from_u64(32);
+        let points: Vec<Vector3D> = (0..300)
+            .map(|_| {
+                p3(
+                    rng.gen_range(-1.0..1.0),
+                    rng.gen_range(-1.0..1.0),
+                    rng.gen_range(-1.0..1.0),
+                )
+            })
+            .collect();
+        let faces = convex_hull_3d(&points);
+
+        // Every point is on or behind every face.
+        for f in &faces {
+            for p in &points {
+                assert!(orient3d(&points[f[0]], &points[f[1]], &points[f[2]], p) >= 0.0);
+            }
+        }
+
+        // Closed 2-manifold: V - E + F = 2.
+        let mut vertices: Vec<usize> = faces.iter().flatten().copied().collect();
+        vertices.sort();
+        vertices.dedup();
+        let edges = faces.len() * 3 / 2;
+        assert_eq!(vertices.len() + faces.len() - edges, 2);
+    }
+
+    #[test]
+    fn hull_3d_coplanar_test() {
+        let points = [
+            p3(0.0, 0.0, 0.0),
+            p3(1.0, 0.0, 0.0),
+            p3(0.0, 1.0, 0.0),
+            p3(1.0, 1.0, 0.0),
+        ];
+
+        assert!(convex_hull_3d(&points).is_empty());
+    }
+}
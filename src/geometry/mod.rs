@@ -0,0 +1,186 @@
+//! Computational geometry on `Vector2D`/`Vector3D` point sets.
+//!
+//! All orientation and in-circle decisions go through the robust predicates
+//! in [`crate::math::predicates`], so the algorithms stay consistent on
+//! collinear, cocircular and nearly degenerate input.
+
+#![allow(clippy::needless_return)]
+
+pub mod boolean;
+pub mod delaunay;
+pub mod hull;
+pub mod triangulate;
+pub mod voronoi;
+
+pub use boolean::{difference, intersection, union, BooleanError};
+pub use delaunay::{delaunay, DelaunayError, Triangulation};
+pub use hull::{convex_hull_2d, convex_hull_3d};
+pub use triangulate::{triangulate, TriangulateError};
+pub use voronoi::{Voronoi, VoronoiEdge};
+
+use crate::math::{predicates::orient2d, Vector2D};
+
+/// A simple polygon with optional holes. The outer ring is counterclockwise
+/// and every hole is clockwise; use [`Polygon::new`] to normalise rings given
+/// in either order.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Polygon {
+    pub outer: Vec<Vector2D>,
+    pub holes: Vec<Vec<Vector2D>>,
+}
+
+impl Polygon {
+    pub fn new(mut outer: Vec<Vector2D>, mut holes: Vec<Vec<Vector2D>>) -> Self {
+        if signed_area(&outer) < 0.0 {
+            outer.reverse();
+        }
+
+        for hole in holes.iter_mut() {
+            if signed_area(hole) > 0.0 {
+                hole.reverse();
+            }
+        }
+
+        Self { outer, holes }
+    }
+
+    /// Area of the outer ring minus the area of the holes.
+    pub fn area(&self) -> f32 {
+        let holes: f32 = self.holes.iter().map(|h| signed_area(h)).sum();
+
+        signed_area(&self.outer) + holes
+    }
+
+    /// True if `point` is inside the outer ring and outside every hole.
+    /// Points on the boundary count as inside.
+    pub fn contains(&self, point: &Vector2D) -> bool {
+        ring_contains(&self.outer, point) != Containment::Outside
+            && self
+                .holes
+                .iter()
+                .all(|h| ring_contains(h, point) != Containment::Inside)
+    }
+}
+
+/// Signed area of the ring: positive when it is counterclockwise.
+pub fn signed_area(ring: &[Vector2D]) -> f32 {
+    let mut sum = 0.0f64;
+
+    for i in 0..ring.len() {
+        let a = &ring[i];
+        let b = &ring[(i + 1) % ring.len()];
+        sum += a.x() as f64 * b.y() as f64 - b.x() as f64 * a.y() as f64;
+    }
+
+    (sum / 2.0) as f32
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Containment {
+    Inside,
+    Boundary,
+    Outside,
+}
+
+/// Point in ring test by winding number, exact thanks to `orient2d`.
+pub fn ring_contains(ring: &[Vector2D], point: &Vector2D) -> Containment {
+    let mut winding = 0;
+
+    for i in 0..ring.len() {
+        let a = &ring[i];
+        let b = &ring[(i + 1) % ring.len()];
+        let side = orient2d(a, b, point);
+
+        if side == 0.0 && on_segment_bounds(a, b, point) {
+            return Containment::Boundary;
+        }
+
+        if a.y() <= point.y() {
+            if b.y() > point.y() && side > 0.0 {
+                winding += 1;
+            }
+        } else if b.y() <= point.y() && side < 0.0 {
+            winding -= 1;
+        }
+    }
+
+    if winding == 0 {
+        Containment::Outside
+    } else {
+        Containment::Inside
+    }
+}
+
+/// For a point known to be collinear with `a`-`b`, whether it lies between.
+fn on_segment_bounds(a: &Vector2D, b: &Vector2D, p: &Vector2D) -> bool {
+    p.x() >= a.x().min(b.x())
+        && p.x() <= a.x().max(b.x())
+        && p.y() >= a.y().min(b.y())
+        && p.y() <= a.y().max(b.y())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(min: f32, max: f32) -> Vec<Vector2D> {
+        vec![
+            Vector2D::create(min, min),
+            Vector2D::create(max, min),
+            Vector2D::create(max, max),
+            Vector2D::create(min, max),
+        ]
+    }
+
+    #[test]
+    fn signed_area_test() {
+        let mut ring = square(0.0, 2.0);
+
+        assert_eq!(signed_area(&ring), 4.0);
+        ring.reverse();
+        assert_eq!(signed_area(&ring), -4.0);
+    }
+
+    #[test]
+    fn polygon_normalises_orientation_test() {
+        let mut outer = square(0.0, 4.0);
+        outer.reverse();
+        let polygon = Polygon::new(outer, vec![square(1.0, 2.0)]);
+
+        assert!(signed_area(&polygon.outer) > 0.0);
+        assert!(signed_area(&polygon.holes[0]) < 0.0);
+        assert_eq!(polygon.area(), 15.0);
+    }
+
+    #[test]
+    fn ring_contains_test() {
+        let ring = square(0.0, 2.0);
+
+        assert_eq!(
+            ring_contains(&ring, &Vector2D::create(1.0, 1.0)),
+            Containment::Inside
+        );
+        assert_eq!(
+            ring_contains(&ring, &Vector2D::create(3.0, 1.0)),
+            Containment::Outside
+        );
+        assert_eq!(
+            ring_contains(&ring, &Vector2D::create(2.0, 1.0)),
+            Containment::Boundary
+        );
+        assert_eq!(
+            ring_contains(&ring, &Vector2D::create(0.0, 0.0)),
+            Containment::Boundary
+        );
+    }
+
+    #[test]
+    fn polygon_contains_test() {
+        let polygon = Polygon::new(square(0.0, 4.0), vec![square(1.0, 2.0)]);
+
+        assert!(polygon.contains(&Vector2D::create(3.0, 3.0)));
+        assert!(!polygon.contains(&Vector2D::create(1.5, 1.5)));
+        assert!(polygon.contains(&Vector2D::create(1.0, 1.5)));
+        assert!(!polygon.contains(&Vector2D::create(5.0, 1.5)));
+    }
+}
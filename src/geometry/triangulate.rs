@@ -0,0 +1,422 @@
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+use crate::math::{predicates::orient2d, Vector2D};
+
+use super::{signed_area, Polygon};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriangulateError {
+    /// A ring has fewer than three vertices; `0` is the outer ring and
+    /// `1..` are the holes.
+    TooFewVertices(usize),
+    /// The hole cannot be connected to the outer ring, usually because it
+    /// is not inside it.
+    UnreachableHole(usize),
+    /// A ring intersects itself, or no ear was left to clip.
+    SelfIntersecting,
+}
+
+impl Display for TriangulateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TriangulateError::TooFewVertices(ring) => {
+                write!(f, "ring {ring} has fewer than three vertices")
+            }
+            TriangulateError::UnreachableHole(hole) => {
+                write!(f, "hole {hole} cannot be connected to the outer ring")
+            }
+            TriangulateError::SelfIntersecting => write!(f, "polygon intersects itself"),
+        }
+    }
+}
+
+impl Error for TriangulateError {}
+
+/// Ear-clipping triangulation of a polygon with holes.
+///
+/// Triangles are counterclockwise and index into the vertices of `outer`
+/// followed by the vertices of every hole in order. Holes are first joined
+/// to the outer ring by bridge edges, or directly where they share a vertex
+/// with it or with a hole already joined, then ears are clipped from the
+/// single resulting ring.
+pub fn triangulate(polygon: &Polygon) -> Result<Vec<[usize; 3]>, TriangulateError> {
+    let mut points: Vec<Vector2D> = polygon.outer.clone();
+    let mut ring = oriented(0, polygon.outer.len(), &points, true);
+    if ring.len() < 3 {
+        return Err(TriangulateError::TooFewVertices(0));
+    }
+
+    let mut holes = Vec::with_capacity(polygon.holes.len());
+    for (h, hole) in polygon.holes.iter().enumerate() {
+        if hole.len() < 3 {
+            return Err(TriangulateError::TooFewVertices(h + 1));
+        }
+        let start = points.len();
+        points.extend(hole);
+        holes.push((h, oriented(start, hole.len(), &points, false)));
+    }
+
+    if !simple(&points, &ring) || holes.iter().any(|(_, hole)| !simple(&points, hole)) {
+        return Err(TriangulateError::SelfIntersecting);
+    }
+
+    // The hole reaching furthest right always sees the outer ring, so
+    // bridging from right to left never gets blocked by another hole.
+    let rightmost = |hole: &[usize]| {
+        let mut best = hole[0];
+        for &i in hole {
+            if points[i].x() > points[best].x() {
+                best = i;
+            }
+        }
+        best
+    };
+    holes.sort_by(|(_, a), (_, b)| {
+        points[rightmost(b)]
+            .x()
+            .total_cmp(&points[rightmost(a)].x())
+    });
+
+    for k in 0..holes.len() {
+        let (h, ref hole) = holes[k];
+        let len = hole.len();
+        let mut spliced = Vec::with_capacity(ring.len() + len + 2);
+
+        if let Some((offset, at)) = pinch(&points, &ring, hole) {
+            // The hole shares a vertex with the ring: ring[..=at], the rest
+            // of the hole round to the shared vertex, then on along the ring.
+            spliced.extend_from_slice(&ring[..=at]);
+            for j in 1..=len {
+                spliced.push(hole[(offset + j) % len]);
+            }
+            spliced.extend_from_slice(&ring[at + 1..]);
+        } else {
+            let m = rightmost(hole);
+            let blockers: Vec<&[usize]> =
+                holes[k + 1..].iter().map(|(_, r)| r.as_slice()).collect();
+            let Some(at) = bridge(&points, &ring, &blockers, m) else {
+                return Err(TriangulateError::UnreachableHole(h + 1));
+            };
+
+            // ring[..=at], the hole from m round to m again, then back to
+            // ring[at].
+            let offset = hole.iter().position(|&i| i == m).unwrap();
+            spliced.extend_from_slice(&ring[..=at]);
+            for j in 0..=len {
+                spliced.push(hole[(offset + j) % len]);
+            }
+            spliced.extend_from_slice(&ring[at..]);
+        }
+        ring = spliced;
+    }
+
+    clip_ears(&points, ring)
+}
+
+/// Indices `start..start + len`, reversed if needed so the ring is
+/// counterclockwise (`ccw`) or clockwise.
+fn oriented(start: usize, len: usize, points: &[Vector2D], ccw: bool) -> Vec<usize> {
+    let mut ring: Vec<usize> = (start..start + len).collect();
+    if (signed_area(&points[start..start + len]) > 0.0) != ccw {
+        ring.reverse();
+    }
+    ring
+}
+
+/// Whether no two non-adjacent edges of the ring touch.
+fn simple(points: &[Vector2D], ring: &[usize]) -> bool {
+    let n = ring.len();
+
+    for i in 0..n {
+        for j in i + 2..n {
+            if i == 0 && j == n - 1 {
+                continue;
+            }
+            let e1 = (ring[i], ring[(i + 1) % n]);
+            let e2 = (ring[j], ring[(j + 1) % n]);
+            if touches(points, e1, e2) {
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
+/// A hole vertex lying on a ring vertex whose interior wedge holds the hole,
+/// as positions in `hole` and `ring`. Such holes need no bridge.
+fn pinch(points: &[Vector2D], ring: &[usize], hole: &[usize]) -> Option<(usize, usize)> {
+    let n = ring.len();
+
+    return hole.iter().enumerate().find_map(|(j, &v)| {
+        let next = &points[hole[(j + 1) % hole.len()]];
+        let at = (0..n).find(|&at| {
+            let (prev, p, after) = (ring[(at + n - 1) % n], ring[at], ring[(at + 1) % n]);
+            same(points, p, v) && in_cone(&points[prev], &points[p], &points[after], next)
+        })?;
+        return Some((j, at));
+    });
+}
+
+fn same(points: &[Vector2D], a: usize, b: usize) -> bool {
+    points[a].x() == points[b].x() && points[a].y() == points[b].y()
+}
+
+/// Position in `ring` of the closest vertex that `m` can be joined to
+/// without crossing any edge of the ring or of the holes still unbridged.
+fn bridge(points: &[Vector2D], ring: &[usize], holes: &[&[usize]], m: usize) -> Option<usize> {
+    let pm = &points[m];
+    let mut candidates: Vec<usize> = (0..ring.len()).collect();
+    candidates.sort_by(|&a, &b| {
+        let (da, db) = (points[ring[a]].distance(pm), points[ring[b]].distance(pm));
+        da.total_cmp(&db)
+    });
+
+    let edges = |r: &'_ [usize]| {
+        let r = r.to_vec();
+        (0..r.len()).map(move |i| (r[i], r[(i + 1) % r.len()]))
+    };
+
+    candidates.into_iter().find(|&at| {
+        let p = ring[at];
+        let n = ring.len();
+        let (prev, next) = (ring[(at + n - 1) % n], ring[(at + 1) % n]);
+
+        // Bridged vertices appear more than once; only the copy whose
+        // interior wedge faces `m` can take the bridge.
+        in_cone(&points[prev], &points[p], &points[next], pm)
+            && edges(ring)
+                .chain(holes.iter().flat_map(|h| edges(h)))
+                .all(|(a, b)| {
+                    // Edges of a hole touching this one at `m` only meet
+                    // the bridge there.
+                    [a, b].iter().any(|&e| e == p || same(points, e, m))
+                        || !touches(points, (a, b), (m, p))
+                })
+    })
+}
+
+/// Whether `q` lies strictly inside the interior angle at `p` of a
+/// counterclockwise ring.
+fn in_cone(prev: &Vector2D, p: &Vector2D, next: &Vector2D, q: &Vector2D) -> bool {
+    let left_of_in = orient2d(prev, p, q) > 0.0;
+    let left_of_out = orient2d(p, next, q) > 0.0;
+
+    if orient2d(prev, p, next) >= 0.0 {
+        left_of_in && left_of_out
+    } else {
+        left_of_in || left_of_out
+    }
+}
+
+/// Closed segment intersection, exact thanks to `orient2d`.
+fn touches(points: &[Vector2D], (a, b): (usize, usize), (c, d): (usize, usize)) -> bool {
+    let (a, b, c, d) = (&points[a], &points[b], &points[c], &points[d]);
+    let o1 = orient2d(a, b, c);
+    let o2 = orient2d(a, b, d);
+    let o3 = orient2d(c, d, a);
+    let o4 = orient2d(c, d, b);
+
+    if o1 * o2 > 0.0 || o3 * o4 > 0.0 {
+        return false;
+    }
+    if o1 == 0.0 && o2 == 0.0 {
+        // Collinear: overlap of the bounding boxes.
+        return a.x().min(b.x()) <= c.x().max(d.x())
+            && c.x().min(d.x()) <= a.x().max(b.x())
+            && a.y().min(b.y()) <= c.y().max(d.y())
+            && c.y().min(d.y()) <= a.y().max(b.y());
+    }
+    true
+}
+
+fn clip_ears(
+    points: &[Vector2D],
+    mut ring: Vec<usize>,
+) -> Result<Vec<[usize; 3]>, TriangulateError> {
+    let mut triangles = Vec::with_capacity(ring.len().saturating_sub(2));
+    let same = |a: usize, b: usize| same(points, a, b);
+
+    while ring.len() > 3 {
+        let n = ring.len();
+        let ear = (0..n).find(|&i| {
+            let (a, b, c) = (ring[(i + n - 1) % n], ring[i], ring[(i + 1) % n]);
+            let (pa, pb, pc) = (&points[a], &points[b], &points[c]);
+
+            orient2d(pa, pb, pc) > 0.0
+                && ring.iter().all(|&q| {
+                    same(q, a)
+                        || same(q, b)
+                        || same(q, c)
+                        || orient2d(pa, pb, &points[q]) < 0.0
+                        || orient2d(pb, pc, &points[q]) < 0.0
+                        || orient2d(pc, pa, &points[q]) < 0.0
+                })
+        });
+
+        match ear {
+            Some(i) => {
+                triangles.push([ring[(i + n - 1) % n], ring[i], ring[(i + 1) % n]]);
+                ring.remove(i);
+            }
+            None => {
+                // Collinear vertices would only produce slivers; drop one.
+                let flat = (0..n).find(|&i| {
+                    let (a, b, c) = (ring[(i + n - 1) % n], ring[i], ring[(i + 1) % n]);
+                    orient2d(&points[a], &points[b], &points[c]) == 0.0
+                });
+                match flat {
+                    Some(i) => {
+                        ring.remove(i);
+                    }
+                    None => return Err(TriangulateError::SelfIntersecting),
+                }
+            }
+        }
+    }
+
+    if orient2d(&points[ring[0]], &points[ring[1]], &points[ring[2]]) > 0.0 {
+        triangles.push([ring[0], ring[1], ring[2]]);
+    }
+
+    Ok(triangles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn p(x: f32, y: f32) -> Vector2D {
+        Vector2D::create(x, y)
+    }
+
+    fn square(min: f32, max: f32) -> Vec<Vector2D> {
+        vec![p(min, min), p(max, min), p(max, max), p(min, max)]
+    }
+
+    fn area(points: &[Vector2D], triangles: &[[usize; 3]]) -> f32 {
+        triangles
+            .iter()
+            .map(|t| signed_area(&[points[t[0]], points[t[1]], points[t[2]]]))
+            .sum()
+    }
+
+    fn all_points(polygon: &Polygon) -> Vec<Vector2D> {
+        let mut points = polygon.outer.clone();
+        for hole in &polygon.holes {
+            points.extend(hole);
+        }
+        points
+    }
+
+    #[test]
+    fn triangulate_convex_test() {
+        let polygon = Polygon::new(square(0.0, 2.0), Vec::new());
+        let triangles = triangulate(&polygon).unwrap();
+
+        assert_eq!(triangles.len(), 2);
+        assert_eq!(area(&polygon.outer, &triangles), 4.0);
+    }
+
+    #[test]
+    fn triangulate_concave_test() {
+        // An L shape given clockwise.
+        let outer = vec![
+            p(0.0, 0.0),
+            p(0.0, 2.0),
+            p(1.0, 2.0),
+            p(1.0, 1.0),
+            p(2.0, 1.0),
+            p(2.0, 0.0),
+        ];
+        let polygon = Polygon {
+            outer,
+            holes: Vec::new(),
+        };
+        let triangles = triangulate(&polygon).unwrap();
+
+        assert_eq!(triangles.len(), 4);
+        assert_eq!(area(&polygon.outer, &triangles), 3.0);
+        for t in &triangles {
+            let o = orient2d(
+                &polygon.outer[t[0]],
+                &polygon.outer[t[1]],
+                &polygon.outer[t[2]],
+            );
+            assert!(o > 0.0);
+        }
+    }
+
+    #[test]
+    fn triangulate_holes_test() {
+        let polygon = Polygon::new(
+            square(0.0, 10.0),
+            vec![
+                square(1.0, 3.0),
+                square(6.0, 8.0),
+                vec![p(4.0, 6.0), p(5.0, 6.0), p(4.5, 9.0)],
+            ],
+        );
+        let points = all_points(&polygon);
+        let triangles = triangulate(&polygon).unwrap();
+
+        // n vertices and h holes give n + 2h - 2 triangles.
+        assert_eq!(triangles.len(), points.len() + 2 * 3 - 2);
+        assert!((area(&points, &triangles) - polygon.area()).abs() < 1e-4);
+
+        for t in &triangles {
+            let centroid = (points[t[0]] + points[t[1]] + points[t[2]]) / 3.0;
+            assert!(polygon.contains(&centroid));
+        }
+    }
+
+    #[test]
+    fn triangulate_touching_holes_test() {
+        // One hole touches a corner of the outer ring, and two more touch
+        // each other at (6, 6).
+        let polygon = Polygon::new(
+            square(0.0, 10.0),
+            vec![
+                vec![p(0.0, 0.0), p(2.0, 1.0), p(1.0, 2.0)],
+                square(4.0, 6.0),
+                square(6.0, 8.0),
+            ],
+        );
+        let points = all_points(&polygon);
+        let triangles = triangulate(&polygon).unwrap();
+
+        assert!((area(&points, &triangles) - polygon.area()).abs() < 1e-4);
+        for t in &triangles {
+            let centroid = (points[t[0]] + points[t[1]] + points[t[2]]) / 3.0;
+            assert!(polygon.contains(&centroid));
+        }
+    }
+
+    #[test]
+    fn triangulate_errors_test() {
+        let polygon = Polygon::new(vec![p(0.0, 0.0), p(1.0, 0.0)], Vec::new());
+        assert_eq!(
+            triangulate(&polygon),
+            Err(TriangulateError::TooFewVertices(0))
+        );
+
+        let polygon = Polygon::new(square(0.0, 1.0), vec![square(2.0, 3.0)]);
+        assert_eq!(
+            triangulate(&polygon),
+            Err(TriangulateError::UnreachableHole(1))
+        );
+
+        // A bow tie.
+        let polygon = Polygon {
+            outer: vec![p(0.0, 0.0), p(2.0, 2.0), p(2.0, 0.0), p(0.0, 2.0)],
+            holes: Vec::new(),
+        };
+        assert_eq!(
+            triangulate(&polygon),
+            Err(TriangulateError::SelfIntersecting)
+        );
+    }
+}
@@ -0,0 +1,253 @@
+use crate::math::Vector2D;
+
+use super::delaunay::{delaunay, DelaunayError, Triangulation};
+
+/// An edge of a Voronoi diagram, separating the cells of two sites.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VoronoiEdge {
+    /// A finite edge between two Voronoi vertices.
+    Segment {
+        sites: [usize; 2],
+        start: Vector2D,
+        end: Vector2D,
+    },
+    /// An unbounded edge leaving the diagram from `origin`.
+    Ray {
+        sites: [usize; 2],
+        origin: Vector2D,
+        direction: Vector2D,
+    },
+}
+
+/// Voronoi diagram of a point set, built as the dual of its Delaunay
+/// triangulation.
+#[derive(Debug, Clone, Default)]
+pub struct Voronoi {
+    sites: Vec<Vector2D>,
+    /// One vertex per Delaunay triangle: its circumcenter.
+    vertices: Vec<Vector2D>,
+    edges: Vec<VoronoiEdge>,
+    neighbors: Vec<Vec<usize>>,
+}
+
+impl Voronoi {
+    /// Builds the diagram. When every site is collinear there are no
+    /// vertices or edges, but [`Voronoi::cell`] still works.
+    pub fn new(sites: &[Vector2D]) -> Result<Self, DelaunayError> {
+        let triangulation = delaunay(sites)?;
+        let vertices: Vec<Vector2D> = triangulation
+            .triangles
+            .iter()
+            .map(|t| circumcenter(&sites[t[0]], &sites[t[1]], &sites[t[2]]))
+            .collect();
+
+        let mut neighbors = vec![Vec::new(); sites.len()];
+        let mut edges = Vec::new();
+        let map = triangulation.edges();
+
+        for (&(a, b), &t1) in &map {
+            match map.get(&(b, a)) {
+                Some(&t2) => {
+                    // Each interior edge is seen from both sides; keep one.
+                    if a < b {
+                        edges.push(VoronoiEdge::Segment {
+                            sites: [a, b],
+                            start: vertices[t1],
+                            end: vertices[t2],
+                        });
+                        neighbors[a].push(b);
+                        neighbors[b].push(a);
+                    }
+                }
+                None => {
+                    // Hull edges are counterclockwise, so the outward normal
+                    // is the edge direction turned clockwise.
+                    let d = sites[b] - sites[a];
+                    edges.push(VoronoiEdge::Ray {
+                        sites: [a, b],
+                        origin: vertices[t1],
+                        direction: Vector2D::create(d.y(), -d.x()).normalize(),
+                    });
+                    neighbors[a].push(b);
+                    neighbors[b].push(a);
+                }
+            }
+        }
+
+        if triangulation.triangles.is_empty() {
+            link_chain(&triangulation, &mut neighbors);
+        }
+
+        Ok(Self {
+            sites: sites.to_vec(),
+            vertices,
+            edges,
+            neighbors,
+        })
+    }
+
+    pub fn sites(&self) -> &[Vector2D] {
+        &self.sites
+    }
+
+    pub fn vertices(&self) -> &[Vector2D] {
+        &self.vertices
+    }
+
+    pub fn edges(&self) -> &[VoronoiEdge] {
+        &self.edges
+    }
+
+    /// Sites whose cells share an edge with the cell of `site`.
+    pub fn neighbors(&self, site: usize) -> &[usize] {
+        &self.neighbors[site]
+    }
+
+    /// The cell of `site` clipped to the box from `min` to `max`, as a
+    /// counterclockwise polygon. Empty if the cell misses the box.
+    pub fn cell(&self, site: usize, min: Vector2D, max: Vector2D) -> Vec<Vector2D> {
+        let mut polygon = vec![
+            min,
+            Vector2D::create(max.x(), min.y()),
+            max,
+            Vector2D::create(min.x(), max.y()),
+        ];
+        let s = self.sites[site];
+
+        for &n in &self.neighbors[site] {
+            // Keep the half-plane closer to `s`: x . (n - s) <= (|n|² - |s|²) / 2.
+            let n = self.sites[n];
+            let normal = n - s;
+            let limit = (n.dot(&n) - s.dot(&s)) / 2.0;
+            polygon = clip(&polygon, &normal, limit);
+
+            if polygon.is_empty() {
+                break;
+            }
+        }
+
+        polygon
+    }
+}
+
+/// Collinear sites have no triangles; each one borders the next along the
+/// line.
+fn link_chain(triangulation: &Triangulation, neighbors: &mut [Vec<usize>]) {
+    for pair in triangulation.hull.windows(2) {
+        neighbors[pair[0]].push(pair[1]);
+        neighbors[pair[1]].push(pair[0]);
+    }
+}
+
+/// Sutherland–Hodgman clip of a convex polygon against `x . normal <= limit`.
+fn clip(polygon: &[Vector2D], normal: &Vector2D, limit: f32) -> Vec<Vector2D> {
+    let mut result = Vec::with_capacity(polygon.len() + 1);
+
+    for i in 0..polygon.len() {
+        let a = polygon[i];
+        let b = polygon[(i + 1) % polygon.len()];
+        let da = a.dot(normal) - limit;
+        let db = b.dot(normal) - limit;
+
+        if da <= 0.0 {
+            result.push(a);
+        }
+        if (da < 0.0 && db > 0.0) || (da > 0.0 && db < 0.0) {
+            let t = da / (da - db);
+            result.push(a + (b - a) * t);
+        }
+    }
+
+    result
+}
+
+/// Circumcenter of a triangle, computed in `f64` relative to `a`.
+fn circumcenter(a: &Vector2D, b: &Vector2D, c: &Vector2D) -> Vector2D {
+    let (bx, by) = ((b.x() - a.x()) as f64, (b.y() - a.y()) as f64);
+    let (cx, cy) = ((c.x() - a.x()) as f64, (c.y() - a.y()) as f64);
+    let d = 2.0 * (bx * cy - by * cx);
+    let b2 = bx * bx + by * by;
+    let c2 = cx * cx + cy * cy;
+    let ux = (cy * b2 - by * c2) / d;
+    let uy = (bx * c2 - cx * b2) / d;
+
+    Vector2D::create(a.x() + ux as f32, a.y() + uy as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::signed_area;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn p(x: f32, y: f32) -> Vector2D {
+        Vector2D::create(x, y)
+    }
+
+    #[test]
+    fn circumcenter_test() {
+        let center = circumcenter(&p(0.0, 0.0), &p(2.0, 0.0), &p(0.0, 2.0));
+
+        assert_eq!(center, p(1.0, 1.0));
+    }
+
+    #[test]
+    fn voronoi_square_test() {
+        let sites = [
+            p(0.0, 0.0),
+            p(1.0, 0.0),
+            p(1.0, 1.0),
+            p(0.0, 1.0),
+            p(0.5, 0.5),
+        ];
+        let voronoi = Voronoi::new(&sites).unwrap();
+
+        assert_eq!(voronoi.vertices().len(), 4);
+        assert_eq!(voronoi.neighbors(4).len(), 4);
+
+        let rays = voronoi
+            .edges()
+            .iter()
+            .filter(|e| matches!(e, VoronoiEdge::Ray { .. }))
+            .count();
+        assert_eq!(rays, 4);
+        assert_eq!(voronoi.edges().len(), 8);
+
+        // The centre site owns the diamond between the four circumcenters.
+        let cell = voronoi.cell(4, p(-1.0, -1.0), p(2.0, 2.0));
+        assert_eq!(cell.len(), 4);
+        assert!((signed_area(&cell) - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn voronoi_cells_tile_box_test() {
+        let mut rng = StdRng::seed_from_u64(34);
+        let sites: Vec<Vector2D> = (0..100)
+            .map(|_| p(rng.gen_range(0.0..10.0), rng.gen_range(0.0..10.0)))
+            .collect();
+        let voronoi = Voronoi::new(&sites).unwrap();
+
+        let total: f32 = (0..sites.len())
+            .map(|i| signed_area(&voronoi.cell(i, p(0.0, 0.0), p(10.0, 10.0))))
+            .sum();
+        assert!((total - 100.0).abs() < 1e-2);
+
+        // Every vertex is equidistant from the sites of its triangle and no
+        // site is closer.
+        for v in voronoi.vertices() {
+            let mut d: Vec<f32> = sites.iter().map(|s| s.distance(v)).collect();
+            d.sort_by(f32::total_cmp);
+            assert!((d[2] - d[0]).abs() < 1e-3 * d[0].max(1.0));
+        }
+    }
+
+    #[test]
+    fn voronoi_collinear_test() {
+        let sites = [p(0.0, 0.0), p(2.0, 0.0), p(1.0, 0.0)];
+        let voronoi = Voronoi::new(&sites).unwrap();
+
+        assert!(voronoi.edges().is_empty());
+        let cell = voronoi.cell(2, p(-1.0, -1.0), p(3.0, 1.0));
+        assert!((signed_area(&cell) - 2.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,277 @@
+//! Windows bitmaps.
+//!
+//! Pixels are written as `BI_BITFIELDS` with a `BITMAPV4HEADER`, which is
+//! the smallest header that lets readers find the alpha channel. The
+//! decoder reads uncompressed 1, 4, 8, 16, 24 and 32-bit files with any of
+//! the info header versions in common use.
+
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
+
+use super::Image;
+
+const FILE_HEADER: usize = 14;
+const INFO_HEADER: usize = 108;
+
+pub fn encode(image: &Image) -> Vec<u8> {
+    let data_size = image.width() * image.height() * 4;
+    let mut out = Vec::with_capacity(FILE_HEADER + INFO_HEADER + data_size);
+
+    // BITMAPFILEHEADER
+    out.extend_from_slice(b"BM");
+    push_u32(&mut out, (FILE_HEADER + INFO_HEADER + data_size) as u32);
+    push_u32(&mut out, 0);
+    push_u32(&mut out, (FILE_HEADER + INFO_HEADER) as u32);
+
+    // BITMAPV4HEADER; a positive height means rows are stored bottom up.
+    push_u32(&mut out, INFO_HEADER as u32);
+    push_u32(&mut out, image.width() as u32);
+    push_u32(&mut out, image.height() as u32);
+    out.extend_from_slice(&1u16.to_le_bytes());
+    out.extend_from_slice(&32u16.to_le_bytes());
+    push_u32(&mut out, 3); // BI_BITFIELDS
+    push_u32(&mut out, data_size as u32);
+    push_u32(&mut out, 2835); // 72 DPI in pixels per metre
+    push_u32(&mut out, 2835);
+    push_u32(&mut out, 0);
+    push_u32(&mut out, 0);
+    for mask in [0x00ff_0000, 0x0000_ff00, 0x0000_00ff, 0xff00_0000] {
+        push_u32(&mut out, mask);
+    }
+    out.extend_from_slice(b"BGRs"); // LCS_sRGB, stored little endian
+    out.resize(FILE_HEADER + INFO_HEADER, 0); // endpoints and gamma unused
+
+    for y in (0..image.height()).rev() {
+        for x in 0..image.width() {
+            let [r, g, b, a] = image.pixel(x, y);
+            out.extend_from_slice(&[b, g, r, a]);
+        }
+    }
+
+    return out;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    InvalidSignature,
+    UnexpectedEnd,
+    /// A header version, bit count or compression this decoder does not
+    /// read.
+    Unsupported(String),
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::InvalidSignature => write!(f, "not a BMP file"),
+            DecodeError::UnexpectedEnd => write!(f, "BMP ends unexpectedly"),
+            DecodeError::Unsupported(what) => write!(f, "unsupported BMP: {what}"),
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
+pub fn decode(bytes: &[u8]) -> Result<Image, DecodeError> {
+    if !bytes.starts_with(b"BM") {
+        return Err(DecodeError::InvalidSignature);
+    }
+    let u16_at = |at: usize| -> Result<u32, DecodeError> {
+        let b = bytes.get(at..at + 2).ok_or(DecodeError::UnexpectedEnd)?;
+        return Ok(u16::from_le_bytes([b[0], b[1]]) as u32);
+    };
+    let u32_at = |at: usize| -> Result<u32, DecodeError> {
+        let b = bytes.get(at..at + 4).ok_or(DecodeError::UnexpectedEnd)?;
+        return Ok(u32::from_le_bytes(b.try_into().unwrap()));
+    };
+
+    let offset = u32_at(10)? as usize;
+    let header_size = u32_at(FILE_HEADER)? as usize;
+    if header_size < 40 {
+        return Err(DecodeError::Unsupported(format!(
+            "{header_size}-byte header"
+        )));
+    }
+    let width = u32_at(FILE_HEADER + 4)? as i32;
+    let height = u32_at(FILE_HEADER + 8)? as i32;
+    let bits = u16_at(FILE_HEADER + 14)?;
+    let compression = u32_at(FILE_HEADER + 16)?;
+    let colors_used = u32_at(FILE_HEADER + 32)? as usize;
+    if width <= 0 || height == 0 || height == i32::MIN {
+        return Err(DecodeError::Unsupported(format!("size {width}x{height}")));
+    }
+    // A negative height means rows are stored top down.
+    let (width, top_down, height) = (width as usize, height < 0, height.unsigned_abs() as usize);
+
+    // Channel masks for red, green, blue and alpha.
+    let masks = match (compression, bits) {
+        (0, 16) => [0x7c00, 0x03e0, 0x001f, 0],
+        (0, 24 | 32) => [0x00ff_0000, 0x0000_ff00, 0x0000_00ff, 0],
+        (0, 1 | 4 | 8) => [0; 4],
+        // BI_BITFIELDS and BI_ALPHABITFIELDS; the masks follow a 40-byte
+        // header and are part of any longer one.
+        (3 | 6, 16 | 32) => {
+            let at = FILE_HEADER + 40;
+            let alpha = if compression == 6 || header_size >= 56 {
+                u32_at(at + 12)?
+            } else {
+                0
+            };
+            [u32_at(at)?, u32_at(at + 4)?, u32_at(at + 8)?, alpha]
+        }
+        _ => {
+            return Err(DecodeError::Unsupported(format!(
+                "{bits}-bit, compression {compression}"
+            )))
+        }
+    };
+
+    let mut palette = Vec::new();
+    if bits <= 8 {
+        let count = if colors_used == 0 {
+            1 << bits
+        } else {
+            colors_used
+        };
+        let at = FILE_HEADER + header_size;
+        let table = bytes
+            .get(at..at + 4 * count)
+            .ok_or(DecodeError::UnexpectedEnd)?;
+        palette = table
+            .chunks_exact(4)
+            .map(|c| [c[2], c[1], c[0], 255])
+            .collect();
+    }
+
+    let stride = (width * bits as usize).div_ceil(32) * 4;
+    let data = bytes
+        .get(offset..offset + stride * height)
+        .ok_or(DecodeError::UnexpectedEnd)?;
+    let mut pixels = Vec::with_capacity(width * height);
+
+    for y in 0..height {
+        let row = if top_down { y } else { height - 1 - y };
+        let line = &data[row * stride..(row + 1) * stride];
+        for x in 0..width {
+            let pixel = match bits {
+                1 | 4 | 8 => {
+                    let bit = x * bits as usize;
+                    let shift = 8 - bits as usize - bit % 8;
+                    let index = (line[bit / 8] >> shift) & ((1u16 << bits) - 1) as u8;
+                    // Out-of-range indices are black, as in most readers.
+                    palette
+                        .get(index as usize)
+                        .copied()
+                        .unwrap_or([0, 0, 0, 255])
+                }
+                _ => {
+                    let size = bits as usize / 8;
+                    let mut value = 0;
+                    for (i, &b) in line[x * size..(x + 1) * size].iter().enumerate() {
+                        value |= (b as u32) << (8 * i);
+                    }
+                    let channel = |mask: u32, missing: u8| {
+                        if mask == 0 {
+                            return missing;
+                        }
+                        let max = mask >> mask.trailing_zeros();
+                        let v = (value & mask) >> mask.trailing_zeros();
+                        return ((v as u64 * 255 + max as u64 / 2) / max as u64) as u8;
+                    };
+                    [
+                        channel(masks[0], 0),
+                        channel(masks[1], 0),
+                        channel(masks[2], 0),
+                        channel(masks[3], 255),
+                    ]
+                }
+            };
+            pixels.push(pixel);
+        }
+    }
+
+    return Ok(Image::from_pixels(width, height, pixels));
+}
+
+fn push_u32(out: &mut Vec<u8>, v: u32) {
+    out.extend_from_slice(&v.to_le_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn encode_test() {
+        let image = Image::from_pixels(
+            2,
+            2,
+            vec![
+                [1, 2, 3, 4],
+                [5, 6, 7, 8],
+                [9, 10, 11, 12],
+                [13, 14, 15, 16],
+            ],
+        );
+        let bytes = encode(&image);
+
+        assert_eq!(&bytes[..2], b"BM");
+        assert_eq!(u32_at(&bytes, 2) as usize, bytes.len());
+        assert_eq!(u32_at(&bytes, 10), 122);
+        assert_eq!(u32_at(&bytes, 14), 108);
+        assert_eq!(u32_at(&bytes, 18), 2);
+        assert_eq!(u32_at(&bytes, 22), 2);
+
+        // Bottom row first, BGRA.
+        assert_eq!(&bytes[122..130], &[11, 10, 9, 12, 15, 14, 13, 16]);
+        assert_eq!(&bytes[130..138], &[3, 2, 1, 4, 7, 6, 5, 8]);
+    }
+
+    #[test]
+    fn decode_test() {
+        let image = Image::from_fn(5, 3, |x, y| [x as u8 * 50, y as u8 * 100, 7, 200 - x as u8]);
+        assert_eq!(decode(&encode(&image)).unwrap(), image);
+
+        // A top-down 4-bit palette image, 3x2, with padded rows.
+        let mut bytes = b"BM".to_vec();
+        for field in [0, 0, 14 + 40 + 8, 40, 3, (-2i32) as u32] {
+            push_u32(&mut bytes, field);
+        }
+        bytes.extend_from_slice(&[1, 0, 4, 0]);
+        for field in [0, 0, 0, 0, 2, 0] {
+            push_u32(&mut bytes, field);
+        }
+        bytes.extend_from_slice(&[30, 20, 10, 0, 3, 2, 1, 0]);
+        bytes.extend_from_slice(&[0x01, 0x00, 0, 0, 0x10, 0x10, 0, 0]);
+
+        let image = decode(&bytes).unwrap();
+        assert_eq!(
+            image.pixels(),
+            [
+                [10, 20, 30, 255],
+                [1, 2, 3, 255],
+                [10, 20, 30, 255],
+                [1, 2, 3, 255],
+                [10, 20, 30, 255],
+                [1, 2, 3, 255],
+            ]
+        );
+
+        assert_eq!(decode(b"PNG"), Err(DecodeError::InvalidSignature));
+        assert_eq!(decode(&bytes[..40]), Err(DecodeError::UnexpectedEnd));
+
+        // Images without pixels have nothing to sample.
+        for (width, height) in [(0, 3), (5, 0)] {
+            let mut bytes = encode(&Image::new(5, 3));
+            bytes[18..22].copy_from_slice(&(width as u32).to_le_bytes());
+            bytes[22..26].copy_from_slice(&(height as u32).to_le_bytes());
+            assert!(matches!(decode(&bytes), Err(DecodeError::Unsupported(_))));
+        }
+    }
+}
@@ -0,0 +1,493 @@
+//! Comparing images against checked-in references ("golden images").
+//!
+//! [`compare`] measures how far an image is from a reference: how many
+//! pixels differ by more than a per-channel tolerance, plus PSNR and SSIM,
+//! and renders a diff image highlighting the differences. [`check_golden`]
+//! wraps that for tests, reading the reference from a PNG file and writing
+//! the actual output and the diff next to it when the comparison fails.
+//!
+//! Set `WMB_BLESS=1` to (re)write references from the current output.
+
+use std::{
+    env,
+    error::Error,
+    fmt::{self, Display, Formatter},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use super::{png, Image};
+
+/// Environment variable that makes [`check_golden`] overwrite references.
+pub const BLESS_VAR: &str = "WMB_BLESS";
+
+/// What counts as a match.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiffOptions {
+    /// Largest per-channel difference that still counts as equal.
+    pub tolerance: u8,
+    /// Fraction of pixels, in `[0, 1]`, allowed to exceed `tolerance`.
+    pub max_mismatched: f64,
+    /// Lowest acceptable PSNR in decibels, if checked.
+    pub min_psnr: Option<f64>,
+    /// Lowest acceptable SSIM, if checked.
+    pub min_ssim: Option<f64>,
+}
+
+impl Default for DiffOptions {
+    /// Exact equality.
+    fn default() -> Self {
+        Self {
+            tolerance: 0,
+            max_mismatched: 0.0,
+            min_psnr: None,
+            min_ssim: None,
+        }
+    }
+}
+
+/// The outcome of [`compare`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffReport {
+    /// Pixels with a channel differing by more than the tolerance.
+    pub mismatched: usize,
+    pub total: usize,
+    /// Largest per-channel difference over the whole image.
+    pub max_difference: u8,
+    pub psnr: f64,
+    pub ssim: f64,
+    /// Mismatched pixels in red, scaled by their difference, over a dimmed
+    /// grayscale copy of the reference.
+    pub diff: Image,
+}
+
+impl DiffReport {
+    pub fn passes(&self, options: &DiffOptions) -> bool {
+        let fraction = if self.total == 0 {
+            0.0
+        } else {
+            self.mismatched as f64 / self.total as f64
+        };
+
+        return fraction <= options.max_mismatched
+            && options.min_psnr.is_none_or(|min| self.psnr >= min)
+            && options.min_ssim.is_none_or(|min| self.ssim >= min);
+    }
+}
+
+impl Display for DiffReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{} pixels differ (max channel difference {}), PSNR {:.2} dB, SSIM {:.4}",
+            self.mismatched, self.total, self.max_difference, self.psnr, self.ssim
+        )
+    }
+}
+
+#[derive(Debug)]
+pub enum DiffError {
+    SizeMismatch {
+        expected: (usize, usize),
+        actual: (usize, usize),
+    },
+    /// No reference exists yet; rerun with [`BLESS_VAR`] set to create it.
+    MissingReference(PathBuf),
+    Io(io::Error),
+    Decode(png::DecodeError),
+    /// The comparison ran but did not pass. The actual image and the diff
+    /// have been written to the paths given.
+    Mismatch {
+        report: Box<DiffReport>,
+        actual: PathBuf,
+        diff: PathBuf,
+    },
+}
+
+impl Display for DiffError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DiffError::SizeMismatch { expected, actual } => write!(
+                f,
+                "image is {}x{} but the reference is {}x{}",
+                actual.0, actual.1, expected.0, expected.1
+            ),
+            DiffError::MissingReference(path) => write!(
+                f,
+                "reference image {} does not exist; set {BLESS_VAR}=1 to create it",
+                path.display()
+            ),
+            DiffError::Io(e) => write!(f, "{e}"),
+            DiffError::Decode(e) => write!(f, "cannot read reference image: {e}"),
+            DiffError::Mismatch {
+                report,
+                actual,
+                diff,
+            } => write!(
+                f,
+                "image does not match its reference: {report}; see {} and {}",
+                actual.display(),
+                diff.display()
+            ),
+        }
+    }
+}
+
+impl Error for DiffError {}
+
+impl From<io::Error> for DiffError {
+    fn from(e: io::Error) -> Self {
+        DiffError::Io(e)
+    }
+}
+
+impl From<png::DecodeError> for DiffError {
+    fn from(e: png::DecodeError) -> Self {
+        DiffError::Decode(e)
+    }
+}
+
+/// Compares `actual` to `expected` pixel by pixel.
+pub fn compare(actual: &Image, expected: &Image, tolerance: u8) -> Result<DiffReport, DiffError> {
+    if (actual.width(), actual.height()) != (expected.width(), expected.height()) {
+        return Err(DiffError::SizeMismatch {
+            expected: (expected.width(), expected.height()),
+            actual: (actual.width(), actual.height()),
+        });
+    }
+
+    let mut mismatched = 0;
+    let mut max_difference = 0;
+    let mut diff = Vec::with_capacity(actual.pixels().len());
+
+    for (a, e) in actual.pixels().iter().zip(expected.pixels()) {
+        let difference = (0..4).map(|i| a[i].abs_diff(e[i])).max().unwrap();
+        max_difference = max_difference.max(difference);
+
+        if difference > tolerance {
+            mismatched += 1;
+            diff.push([128 + difference / 2, 0, 0, 255]);
+        } else {
+            let gray = (luma(e) / 4.0) as u8;
+            diff.push([gray, gray, gray, 255]);
+        }
+    }
+
+    return Ok(DiffReport {
+        mismatched,
+        total: actual.pixels().len(),
+        max_difference,
+        psnr: psnr(actual, expected),
+        ssim: ssim(actual, expected),
+        diff: Image::from_pixels(actual.width(), actual.height(), diff),
+    });
+}
+
+/// Peak signal-to-noise ratio over all four channels, in decibels.
+/// Identical images give infinity.
+///
+/// Panics if the images differ in size.
+pub fn psnr(a: &Image, b: &Image) -> f64 {
+    assert_eq!(
+        a.as_bytes().len(),
+        b.as_bytes().len(),
+        "images differ in size"
+    );
+
+    let bytes = a.as_bytes().len().max(1) as f64;
+    let mse = a
+        .as_bytes()
+        .iter()
+        .zip(b.as_bytes())
+        .map(|(&x, &y)| (x as f64 - y as f64).powi(2))
+        .sum::<f64>()
+        / bytes;
+
+    if mse == 0.0 {
+        return f64::INFINITY;
+    }
+
+    return 10.0 * (255.0 * 255.0 / mse).log10();
+}
+
+/// Window size and stride of [`ssim`].
+const WINDOW: usize = 8;
+const STRIDE: usize = 4;
+
+/// Mean structural similarity of the luma of two images, in `[-1, 1]`.
+///
+/// Uses uniformly weighted 8x8 windows every 4 pixels; images smaller than
+/// a window are compared as a single window.
+///
+/// Panics if the images differ in size.
+pub fn ssim(a: &Image, b: &Image) -> f64 {
+    assert_eq!(
+        (a.width(), a.height()),
+        (b.width(), b.height()),
+        "images differ in size"
+    );
+    if a.pixels().is_empty() {
+        return 1.0;
+    }
+
+    let la: Vec<f64> = a.pixels().iter().map(luma).collect();
+    let lb: Vec<f64> = b.pixels().iter().map(luma).collect();
+    let (w, h) = (a.width(), a.height());
+    let (ww, wh) = (WINDOW.min(w), WINDOW.min(h));
+
+    let mut total = 0.0;
+    let mut windows = 0;
+    let mut y = 0;
+    loop {
+        let mut x = 0;
+        loop {
+            total += window_ssim(&la, &lb, w, x, y, ww, wh);
+            windows += 1;
+
+            if x + ww >= w {
+                break;
+            }
+            x = (x + STRIDE).min(w - ww);
+        }
+
+        if y + wh >= h {
+            break;
+        }
+        y = (y + STRIDE).min(h - wh);
+    }
+
+    return total / windows as f64;
+}
+
+fn window_ssim(
+    a: &[f64],
+    b: &[f64],
+    stride: usize,
+    x0: usize,
+    y0: usize,
+    w: usize,
+    h: usize,
+) -> f64 {
+    const C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+    const C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+
+    let n = (w * h) as f64;
+    let (mut sa, mut sb, mut saa, mut sbb, mut sab) = (0.0, 0.0, 0.0, 0.0, 0.0);
+    for y in y0..y0 + h {
+        for x in x0..x0 + w {
+            let (p, q) = (a[y * stride + x], b[y * stride + x]);
+            sa += p;
+            sb += q;
+            saa += p * p;
+            sbb += q * q;
+            sab += p * q;
+        }
+    }
+
+    let (ma, mb) = (sa / n, sb / n);
+    let va = saa / n - ma * ma;
+    let vb = sbb / n - mb * mb;
+    let cov = sab / n - ma * mb;
+
+    return ((2.0 * ma * mb + C1) * (2.0 * cov + C2)) / ((ma * ma + mb * mb + C1) * (va + vb + C2));
+}
+
+/// Rec. 601 luma of the color channels, in `[0, 255]`.
+fn luma(p: &[u8; 4]) -> f64 {
+    0.299 * p[0] as f64 + 0.587 * p[1] as f64 + 0.114 * p[2] as f64
+}
+
+/// Compares `actual` against the PNG reference at `path`.
+///
+/// On failure `<name>.actual.png` and `<name>.diff.png` are written beside
+/// the reference. With [`BLESS_VAR`] set the reference is overwritten with
+/// `actual` instead and the check passes.
+pub fn check_golden(
+    actual: &Image,
+    path: impl AsRef<Path>,
+    options: &DiffOptions,
+) -> Result<DiffReport, DiffError> {
+    let path = path.as_ref();
+
+    if env::var_os(BLESS_VAR).is_some_and(|v| !v.is_empty() && v != "0") {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, png::encode(actual))?;
+        return compare(actual, actual, options.tolerance);
+    }
+
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            return Err(DiffError::MissingReference(path.to_path_buf()))
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let expected = png::decode(&bytes)?;
+    let report = compare(actual, &expected, options.tolerance)?;
+
+    if report.passes(options) {
+        return Ok(report);
+    }
+
+    let actual_path = path.with_extension("actual.png");
+    let diff_path = path.with_extension("diff.png");
+    fs::write(&actual_path, png::encode(actual))?;
+    fs::write(&diff_path, png::encode(&report.diff))?;
+
+    return Err(DiffError::Mismatch {
+        report: Box::new(report),
+        actual: actual_path,
+        diff: diff_path,
+    });
+}
+
+/// [`check_golden`] for use in tests: panics with the report on failure.
+#[track_caller]
+pub fn assert_golden(actual: &Image, path: impl AsRef<Path>, options: &DiffOptions) {
+    if let Err(e) = check_golden(actual, path, options) {
+        panic!("{e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gradient(width: usize, height: usize) -> Image {
+        Image::from_fn(width, height, |x, y| {
+            [(x * 8) as u8, (y * 8) as u8, 96, 255]
+        })
+    }
+
+    #[test]
+    fn compare_identical_test() {
+        let image = gradient(16, 16);
+        let report = compare(&image, &image, 0).unwrap();
+
+        assert_eq!(report.mismatched, 0);
+        assert_eq!(report.psnr, f64::INFINITY);
+        assert!((report.ssim - 1.0).abs() < 1e-12);
+        assert!(report.passes(&DiffOptions::default()));
+    }
+
+    #[test]
+    fn compare_tolerance_test() {
+        let expected = gradient(16, 16);
+        let mut actual = expected.clone();
+        actual.set_pixel(3, 4, [0, 0, 0, 255]);
+        actual.set_pixel(5, 5, {
+            let mut p = expected.pixel(5, 5);
+            p[2] += 2;
+            p
+        });
+
+        let report = compare(&actual, &expected, 2).unwrap();
+        assert_eq!(report.mismatched, 1);
+        assert_eq!(report.max_difference, 96);
+        assert_eq!(report.diff.pixel(3, 4), [176, 0, 0, 255]);
+        assert_eq!(report.diff.pixel(5, 5)[0], report.diff.pixel(5, 5)[1]);
+
+        assert!(!report.passes(&DiffOptions::default()));
+        assert!(report.passes(&DiffOptions {
+            max_mismatched: 0.01,
+            ..Default::default()
+        }));
+        assert!(!report.passes(&DiffOptions {
+            max_mismatched: 1.0,
+            min_psnr: Some(60.0),
+            ..Default::default()
+        }));
+    }
+
+    #[test]
+    fn compare_size_mismatch_test() {
+        let error = compare(&gradient(4, 4), &gradient(4, 5), 0).unwrap_err();
+
+        assert!(matches!(
+            error,
+            DiffError::SizeMismatch {
+                expected: (4, 5),
+                actual: (4, 4)
+            }
+        ));
+    }
+
+    #[test]
+    fn psnr_test() {
+        let a = Image::from_pixels(1, 1, vec![[0, 0, 0, 0]]);
+        let b = Image::from_pixels(1, 1, vec![[255, 255, 255, 255]]);
+        let c = Image::from_pixels(1, 1, vec![[0, 0, 0, 51]]);
+
+        assert_eq!(psnr(&a, &b), 0.0);
+        // MSE is 51^2 / 4, so the PSNR is 20 log10(10).
+        assert!((psnr(&a, &c) - 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn ssim_test() {
+        let image = gradient(20, 12);
+        let brighter = Image::from_fn(20, 12, |x, y| {
+            let p = image.pixel(x, y);
+            [
+                p[0].saturating_add(10),
+                p[1].saturating_add(10),
+                p[2] + 10,
+                255,
+            ]
+        });
+        let noisy = Image::from_fn(20, 12, |x, y| {
+            let p = image.pixel(x, y);
+            let n = if (x + y) % 2 == 0 { 40 } else { 0 };
+            [
+                p[0].saturating_add(n),
+                p[1].saturating_add(n),
+                p[2] + n,
+                255,
+            ]
+        });
+
+        // A brightness shift keeps the structure; noise of a similar size
+        // does not.
+        let shifted = ssim(&image, &brighter);
+        assert!(shifted > 0.95 && shifted < 1.0);
+        assert!(ssim(&image, &noisy) < shifted);
+
+        let tiny = gradient(3, 2);
+        assert!((ssim(&tiny, &tiny) - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn check_golden_test() {
+        let dir = env::temp_dir().join(format!("wmb-golden-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("gradient.png");
+        let image = gradient(8, 8);
+
+        assert!(matches!(
+            check_golden(&image, &path, &DiffOptions::default()),
+            Err(DiffError::MissingReference(_))
+        ));
+
+        fs::write(&path, png::encode(&image)).unwrap();
+        assert_golden(&image, &path, &DiffOptions::default());
+
+        let mut changed = image.clone();
+        changed.set_pixel(0, 0, [255, 0, 255, 255]);
+        match check_golden(&changed, &path, &DiffOptions::default()) {
+            Err(DiffError::Mismatch {
+                report,
+                actual,
+                diff,
+            }) => {
+                assert_eq!(report.mismatched, 1);
+                assert_eq!(png::decode(&fs::read(actual).unwrap()).unwrap(), changed);
+                assert_eq!(png::decode(&fs::read(diff).unwrap()).unwrap(), report.diff);
+            }
+            other => panic!("expected a mismatch, got {other:?}"),
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,95 @@
+//! IEEE 754 half-precision floats, stored as `u16` bits.
+
+/// Widens half-precision bits to an `f32`; exact for every value.
+pub fn to_f32(bits: u16) -> f32 {
+    let sign = ((bits & 0x8000) as u32) << 16;
+    let exponent = ((bits >> 10) & 0x1f) as u32;
+    let mantissa = (bits & 0x03ff) as u32;
+
+    let magnitude = match exponent {
+        // Subnormals are mantissa * 2^-24.
+        0 => {
+            let value = mantissa as f32 * (-24f32).exp2();
+            return if sign != 0 { -value } else { value };
+        }
+        0x1f => 0x7f80_0000 | (mantissa << 13),
+        _ => ((exponent + 127 - 15) << 23) | (mantissa << 13),
+    };
+
+    return f32::from_bits(sign | magnitude);
+}
+
+/// Narrows an `f32` to half precision, rounding to nearest even. Values
+/// past the half range become infinity and NaN stays NaN.
+pub fn from_f32(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x007f_ffff;
+
+    if exponent == 0xff {
+        let nan = if mantissa != 0 { 0x0200 } else { 0 };
+        return sign | 0x7c00 | nan;
+    }
+
+    let exponent = exponent - 127 + 15;
+    if exponent >= 0x1f {
+        return sign | 0x7c00;
+    }
+
+    // The mantissa with its implicit bit, and how far it shifts right to
+    // fit ten bits (more for subnormals).
+    let (mantissa, shift) = if exponent <= 0 {
+        if exponent < -10 {
+            return sign;
+        }
+        (mantissa | 0x0080_0000, (14 - exponent) as u32)
+    } else {
+        (mantissa, 13)
+    };
+
+    let half = 1 << (shift - 1);
+    let remainder = mantissa & ((1 << shift) - 1);
+    let mut result = mantissa >> shift;
+    if remainder > half || (remainder == half && result & 1 == 1) {
+        result += 1;
+    }
+
+    // A carry out of the mantissa correctly bumps the exponent.
+    let exponent = exponent.max(0) as u32;
+    return sign | ((exponent << 10) + result) as u16;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn half_test() {
+        for (value, bits) in [
+            (0.0, 0x0000),
+            (-0.0, 0x8000),
+            (1.0, 0x3c00),
+            (-2.0, 0xc000),
+            (0.5, 0x3800),
+            (65504.0, 0x7bff),
+            (6.103_515_6e-5, 0x0400),
+            (5.960_464_5e-8, 0x0001),
+            (f32::INFINITY, 0x7c00),
+        ] {
+            assert_eq!(from_f32(value), bits, "{value}");
+            assert_eq!(to_f32(bits), value, "{bits:#06x}");
+        }
+
+        assert_eq!(from_f32(1e6), 0x7c00);
+        assert_eq!(from_f32(1e-9), 0);
+        assert!(to_f32(from_f32(f32::NAN)).is_nan());
+        // Halfway between 1 and the next half rounds to even.
+        assert_eq!(from_f32(1.0 + 2f32.powi(-11)), 0x3c00);
+        assert_eq!(from_f32(1.0 + 3.0 * 2f32.powi(-11)), 0x3c02);
+
+        for bits in 0..0x7c00u16 {
+            assert_eq!(from_f32(to_f32(bits)), bits);
+        }
+    }
+}
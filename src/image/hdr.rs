@@ -0,0 +1,224 @@
+//! Radiance RGBE (`.hdr`) decoding, the usual format for environment maps.
+//!
+//! Each pixel is an 8-bit mantissa per channel sharing an exponent. Rows
+//! are either stored flat or run-length encoded per channel, the "new"
+//! encoding every current writer uses.
+
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
+
+use super::texture::{Texture, TextureFormat};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    InvalidSignature,
+    UnexpectedEnd,
+    /// A pixel format or orientation this decoder does not read.
+    Unsupported(String),
+    /// A run-length encoded scanline overflows its row.
+    InvalidScanline(usize),
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::InvalidSignature => write!(f, "not a Radiance HDR file"),
+            DecodeError::UnexpectedEnd => write!(f, "HDR ends unexpectedly"),
+            DecodeError::Unsupported(what) => write!(f, "unsupported HDR: {what}"),
+            DecodeError::InvalidScanline(y) => write!(f, "corrupt HDR scanline {y}"),
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
+/// Decodes to a linear [`TextureFormat::Rgb32F`] texture. Only the
+/// standard `-Y height +X width` orientation is read.
+pub fn decode(bytes: &[u8]) -> Result<Texture, DecodeError> {
+    if !bytes.starts_with(b"#?RADIANCE") && !bytes.starts_with(b"#?RGBE") {
+        return Err(DecodeError::InvalidSignature);
+    }
+
+    // Header lines up to an empty one, then the resolution line.
+    let mut at = 0;
+    let mut line = || -> Result<&str, DecodeError> {
+        let length = bytes[at..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .ok_or(DecodeError::UnexpectedEnd)?;
+        let text = std::str::from_utf8(&bytes[at..at + length])
+            .map_err(|_| DecodeError::Unsupported("non-UTF-8 header".to_string()))?;
+        at += length + 1;
+        return Ok(text.trim_end_matches('\r'));
+    };
+    loop {
+        let text = line()?;
+        if text.is_empty() {
+            break;
+        }
+        if let Some(format) = text.strip_prefix("FORMAT=") {
+            if format != "32-bit_rle_rgbe" {
+                return Err(DecodeError::Unsupported(format.to_string()));
+            }
+        }
+    }
+
+    let resolution = line()?;
+    let size = match resolution.split_whitespace().collect::<Vec<_>>()[..] {
+        ["-Y", height, "+X", width] => width.parse::<usize>().ok().zip(height.parse().ok()),
+        _ => None,
+    };
+    let unsupported = || DecodeError::Unsupported(format!("resolution {resolution}"));
+    let (width, height) = size
+        .filter(|&(width, height)| width > 0 && height > 0)
+        .ok_or_else(unsupported)?;
+    let count = width.checked_mul(height).ok_or_else(unsupported)?;
+
+    // Every scanline takes some bytes, so a resolution the rest of the
+    // file cannot hold is rejected before allocating for it.
+    let smallest = if (8..0x8000).contains(&width) {
+        4 + 4 * 2 * width.div_ceil(127)
+    } else {
+        width.checked_mul(4).ok_or_else(unsupported)?
+    };
+    if smallest
+        .checked_mul(height)
+        .is_none_or(|n| n > bytes.len() - at)
+    {
+        return Err(DecodeError::UnexpectedEnd);
+    }
+
+    let mut pixels = Vec::with_capacity(count);
+    let mut row = vec![[0u8; 4]; width];
+    for y in 0..height {
+        at = read_scanline(bytes, at, &mut row).map_err(|e| match e {
+            DecodeError::InvalidScanline(_) => DecodeError::InvalidScanline(y),
+            e => e,
+        })?;
+        pixels.extend(row.iter().map(|&rgbe| to_linear(rgbe)));
+    }
+
+    return Ok(Texture::from_fn(
+        width,
+        height,
+        TextureFormat::Rgb32F,
+        false,
+        |x, y| pixels[y * width + x],
+    ));
+}
+
+/// Reads one row of RGBE pixels starting at `at`, returning where the next
+/// one starts.
+fn read_scanline(bytes: &[u8], mut at: usize, row: &mut [[u8; 4]]) -> Result<usize, DecodeError> {
+    let width = row.len();
+    let start = bytes.get(at..at + 4).ok_or(DecodeError::UnexpectedEnd)?;
+    let encoded = (8..0x8000).contains(&width)
+        && start[0] == 2
+        && start[1] == 2
+        && u16::from_be_bytes([start[2], start[3]]) as usize == width;
+
+    if !encoded {
+        let data = bytes
+            .get(at..at + 4 * width)
+            .ok_or(DecodeError::UnexpectedEnd)?;
+        for (pixel, rgbe) in row.iter_mut().zip(data.chunks_exact(4)) {
+            pixel.copy_from_slice(rgbe);
+        }
+        return Ok(at + 4 * width);
+    }
+
+    // Each channel in turn, as runs (count above 128) and literals.
+    at += 4;
+    for channel in 0..4 {
+        let mut x = 0;
+        while x < width {
+            let count = *bytes.get(at).ok_or(DecodeError::UnexpectedEnd)? as usize;
+            let (run, literal) = if count > 128 {
+                (count - 128, false)
+            } else {
+                (count, true)
+            };
+            if run == 0 || x + run > width {
+                return Err(DecodeError::InvalidScanline(0));
+            }
+
+            let length = if literal { run } else { 1 };
+            let data = bytes
+                .get(at + 1..at + 1 + length)
+                .ok_or(DecodeError::UnexpectedEnd)?;
+            for (i, pixel) in row[x..x + run].iter_mut().enumerate() {
+                pixel[channel] = if literal { data[i] } else { data[0] };
+            }
+            x += run;
+            at += 1 + length;
+        }
+    }
+
+    return Ok(at);
+}
+
+fn to_linear([r, g, b, e]: [u8; 4]) -> [f32; 4] {
+    if e == 0 {
+        return [0.0, 0.0, 0.0, 1.0];
+    }
+
+    let scale = (e as f32 - 136.0).exp2();
+    return [r as f32 * scale, g as f32 * scale, b as f32 * scale, 1.0];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(width: usize, height: usize) -> Vec<u8> {
+        format!("#?RADIANCE\nFORMAT=32-bit_rle_rgbe\nEXPOSURE=1.0\n\n-Y {height} +X {width}\n")
+            .into_bytes()
+    }
+
+    #[test]
+    fn decode_test() {
+        // Flat: 1.0 is a mantissa of 128 with exponent 129.
+        let mut bytes = header(2, 1);
+        bytes.extend_from_slice(&[128, 64, 0, 129, 0, 0, 0, 0]);
+        let texture = decode(&bytes).unwrap();
+        assert_eq!(texture.format(), TextureFormat::Rgb32F);
+        assert_eq!(texture.texel(0, 0), [1.0, 0.5, 0.0, 1.0]);
+        assert_eq!(texture.texel(1, 0), [0.0, 0.0, 0.0, 1.0]);
+
+        // Run-length encoded, 8 wide, two rows.
+        let mut bytes = header(8, 2);
+        for y in 0..2u8 {
+            bytes.extend_from_slice(&[2, 2, 0, 8]);
+            bytes.extend_from_slice(&[8, 1, 2, 3, 4, 5, 6, 7, 8]); // red literals
+            bytes.extend_from_slice(&[128 + 8, 128]); // green run
+            bytes.extend_from_slice(&[128 + 4, y, 128 + 4, 255]); // blue runs
+            bytes.extend_from_slice(&[128 + 8, 136]); // exponent run
+        }
+        let texture = decode(&bytes).unwrap();
+        assert_eq!(texture.texel(2, 0), [3.0, 128.0, 0.0, 1.0]);
+        assert_eq!(texture.texel(7, 1), [8.0, 128.0, 255.0, 1.0]);
+
+        let overflow = bytes.len() - 2;
+        bytes[overflow] = 128 + 9;
+        assert_eq!(decode(&bytes), Err(DecodeError::InvalidScanline(1)));
+        assert_eq!(decode(b"P6"), Err(DecodeError::InvalidSignature));
+        assert_eq!(decode(&header(2, 1)), Err(DecodeError::UnexpectedEnd));
+        assert!(matches!(
+            decode(b"#?RADIANCE\n\n+Y 1 +X 1\n"),
+            Err(DecodeError::Unsupported(_))
+        ));
+
+        // Resolutions the file cannot hold fail before allocating.
+        assert!(matches!(
+            decode(&header(100000000000, 100000000000)),
+            Err(DecodeError::Unsupported(_))
+        ));
+        assert_eq!(
+            decode(&header(100000000000, 1)),
+            Err(DecodeError::UnexpectedEnd)
+        );
+        assert_eq!(decode(&header(8, 1 << 40)), Err(DecodeError::UnexpectedEnd));
+    }
+}
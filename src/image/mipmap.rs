@@ -0,0 +1,270 @@
+//! Mipmap chains and trilinear filtering.
+//!
+//! Each level halves the one above it, rounding down to at least one
+//! texel, and is filtered from it in linear space so sRGB textures do not
+//! darken as they shrink.
+
+use std::f32::consts::PI;
+
+use super::texture::{Texture, TextureFormat, Wrap};
+use crate::math::Vector2D;
+
+/// Half-width of the Kaiser filter in destination texels.
+const KAISER_WIDTH: f32 = 3.0;
+/// Kaiser window shape; higher trades sharpness for less ringing.
+const KAISER_ALPHA: f32 = 4.0;
+
+/// How [`MipChain::generate`] downsamples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MipFilter {
+    /// Averages the texels each new one covers. Cheap, a little blurry.
+    #[default]
+    Box,
+    /// A Kaiser-windowed sinc, which keeps more detail without aliasing.
+    Kaiser,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MipChain {
+    /// Largest first, down to 1x1. All levels share the top's format.
+    pub levels: Vec<Texture>,
+}
+
+impl MipChain {
+    /// Builds every level down to 1x1 from `texture`. Edge texels are
+    /// filtered as `wrap` would sample them.
+    pub fn generate(texture: &Texture, filter: MipFilter, wrap: Wrap) -> Self {
+        let format = texture.format();
+        let srgb = texture.is_srgb();
+        let mut levels = vec![texture.clone()];
+
+        let (mut width, mut height) = (texture.width(), texture.height());
+        let mut source: Vec<[f32; 4]> = (0..width * height)
+            .map(|i| texture.texel(i % width, i / width))
+            .collect();
+
+        while width > 1 || height > 1 {
+            let (next_width, next_height) = ((width / 2).max(1), (height / 2).max(1));
+            let columns = weights(width, next_width, filter, wrap);
+            let rows = weights(height, next_height, filter, wrap);
+
+            // Separable: rows first, then columns.
+            let mut horizontal = vec![[0.0; 4]; next_width * height];
+            for y in 0..height {
+                for (x, taps) in columns.iter().enumerate() {
+                    horizontal[y * next_width + x] =
+                        blend(taps.iter().map(|&(i, w)| (source[y * width + i], w)));
+                }
+            }
+            let mut next = vec![[0.0; 4]; next_width * next_height];
+            for (y, taps) in rows.iter().enumerate() {
+                for x in 0..next_width {
+                    next[y * next_width + x] = blend(
+                        taps.iter()
+                            .map(|&(i, w)| (horizontal[i * next_width + x], w)),
+                    );
+                }
+            }
+
+            levels.push(Texture::from_fn(
+                next_width,
+                next_height,
+                format,
+                srgb,
+                |x, y| next[y * next_width + x],
+            ));
+            (width, height, source) = (next_width, next_height, next);
+        }
+
+        return Self { levels };
+    }
+
+    /// The level of detail for a pixel whose UV changes by `duv_dx` and
+    /// `duv_dy` across one pixel horizontally and vertically, i.e. `log2`
+    /// of how many top-level texels it spans.
+    pub fn lod(&self, duv_dx: Vector2D, duv_dy: Vector2D) -> f32 {
+        let size = Vector2D::create(
+            self.levels[0].width() as f32,
+            self.levels[0].height() as f32,
+        );
+        let texels = |d: Vector2D| Vector2D::create(d.x() * size.x(), d.y() * size.y());
+        let span = texels(duv_dx).magnitude().max(texels(duv_dy).magnitude());
+
+        return span.log2().max(0.0);
+    }
+
+    /// Bilinear lookups in the two levels around `lod`, blended linearly.
+    pub fn sample_trilinear(&self, uv: Vector2D, lod: f32, wrap: Wrap) -> [f32; 4] {
+        let lod = lod.clamp(0.0, (self.levels.len() - 1) as f32);
+        let level = lod.floor() as usize;
+        let t = lod - level as f32;
+
+        let fine = self.levels[level].sample_bilinear(uv, wrap);
+        if t == 0.0 {
+            return fine;
+        }
+        let coarse = self.levels[level + 1].sample_bilinear(uv, wrap);
+
+        return std::array::from_fn(|i| fine[i] + (coarse[i] - fine[i]) * t);
+    }
+
+    pub fn format(&self) -> TextureFormat {
+        self.levels[0].format()
+    }
+}
+
+/// For each of `to` texels, the source texels and normalised weights that
+/// filter `from` texels down to it.
+fn weights(from: usize, to: usize, filter: MipFilter, wrap: Wrap) -> Vec<Vec<(usize, f32)>> {
+    let scale = from as f32 / to as f32;
+
+    return (0..to)
+        .map(|x| {
+            let center = (x as f32 + 0.5) * scale;
+            let radius = match filter {
+                MipFilter::Box => scale / 2.0,
+                MipFilter::Kaiser => KAISER_WIDTH * scale,
+            };
+
+            let first = (center - radius - 0.5).floor() as i64;
+            let last = (center + radius + 0.5).ceil() as i64;
+            let mut taps: Vec<(usize, f32)> = Vec::new();
+            for i in first..=last {
+                // Distance between texel centers, in destination texels.
+                let t = (i as f32 + 0.5 - center) / scale;
+                let weight = match filter {
+                    // Overlap of the source texel with the destination's.
+                    MipFilter::Box => {
+                        let lo = (i as f32).max(center - radius);
+                        let hi = (i as f32 + 1.0).min(center + radius);
+                        (hi - lo).max(0.0)
+                    }
+                    MipFilter::Kaiser => sinc(t) * kaiser(t / KAISER_WIDTH),
+                };
+                if weight != 0.0 {
+                    taps.push((wrap.apply(i, from), weight));
+                }
+            }
+
+            let total: f32 = taps.iter().map(|&(_, w)| w).sum();
+            for tap in &mut taps {
+                tap.1 /= total;
+            }
+            taps
+        })
+        .collect();
+}
+
+fn blend(taps: impl Iterator<Item = ([f32; 4], f32)>) -> [f32; 4] {
+    let mut out = [0.0; 4];
+    for (texel, weight) in taps {
+        for i in 0..4 {
+            out[i] += texel[i] * weight;
+        }
+    }
+
+    return out;
+}
+
+fn sinc(x: f32) -> f32 {
+    if x.abs() < 1e-6 {
+        return 1.0;
+    }
+
+    return (PI * x).sin() / (PI * x);
+}
+
+/// The Kaiser window over `[-1, 1]`.
+fn kaiser(x: f32) -> f32 {
+    if x.abs() >= 1.0 {
+        return 0.0;
+    }
+
+    return bessel_i0(KAISER_ALPHA * (1.0 - x * x).sqrt()) / bessel_i0(KAISER_ALPHA);
+}
+
+/// The modified Bessel function of the first kind, order zero, by its
+/// power series.
+fn bessel_i0(x: f32) -> f32 {
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    let quarter = x * x / 4.0;
+
+    for k in 1..32 {
+        term *= quarter / (k * k) as f32;
+        sum += term;
+        if term < sum * 1e-8 {
+            break;
+        }
+    }
+
+    return sum;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_test() {
+        let texture = Texture::from_fn(8, 5, TextureFormat::Rgba16F, false, |x, y| {
+            [x as f32, y as f32, 1.0, 1.0]
+        });
+
+        for filter in [MipFilter::Box, MipFilter::Kaiser] {
+            let chain = MipChain::generate(&texture, filter, Wrap::ClampToEdge);
+            let sizes: Vec<(usize, usize)> = chain
+                .levels
+                .iter()
+                .map(|l| (l.width(), l.height()))
+                .collect();
+            assert_eq!(sizes, [(8, 5), (4, 2), (2, 1), (1, 1)]);
+
+            // Constant channels stay constant.
+            for level in &chain.levels {
+                let texel = level.texel(level.width() - 1, 0);
+                assert!((texel[2] - 1.0).abs() < 1e-3, "{filter:?} {texel:?}");
+            }
+            // The mean survives; the box filter keeps a ramp exactly.
+            let last = chain.levels[3].texel(0, 0);
+            assert!((last[0] - 3.5).abs() < 0.05, "{filter:?} {last:?}");
+            assert!((last[1] - 2.0).abs() < 0.05, "{filter:?} {last:?}");
+        }
+
+        let chain = MipChain::generate(&texture, MipFilter::Box, Wrap::ClampToEdge);
+        assert_eq!(chain.levels[1].texel(1, 0)[0], 2.5);
+    }
+
+    #[test]
+    fn srgb_test() {
+        // A black and white checkerboard averages to linear mid gray, which
+        // is much lighter than 128 in sRGB.
+        let texture = Texture::from_fn(2, 2, TextureFormat::Rgba8, true, |x, y| {
+            let v = ((x + y) % 2) as f32;
+            [v, v, v, 1.0]
+        });
+        let chain = MipChain::generate(&texture, MipFilter::Box, Wrap::Repeat);
+
+        assert_eq!(chain.levels[1].to_image().pixel(0, 0), [188, 188, 188, 255]);
+    }
+
+    #[test]
+    fn trilinear_test() {
+        let texture = Texture::from_fn(4, 4, TextureFormat::Rgb32F, false, |x, _| {
+            let v = if x < 2 { 0.0 } else { 1.0 };
+            [v, v, v, 1.0]
+        });
+        let chain = MipChain::generate(&texture, MipFilter::Box, Wrap::ClampToEdge);
+        let uv = Vector2D::create(0.125, 0.5);
+
+        assert_eq!(chain.sample_trilinear(uv, 0.0, Wrap::ClampToEdge)[0], 0.0);
+        assert_eq!(chain.sample_trilinear(uv, 2.0, Wrap::ClampToEdge)[0], 0.5);
+        assert_eq!(chain.sample_trilinear(uv, 9.0, Wrap::ClampToEdge)[0], 0.5);
+        assert_eq!(chain.sample_trilinear(uv, 1.5, Wrap::ClampToEdge)[0], 0.25);
+
+        let pixel = Vector2D::create(1.0 / 4.0, 0.0);
+        assert_eq!(chain.lod(pixel, Vector2D::create(0.0, 0.0)), 0.0);
+        assert_eq!(chain.lod(pixel * 4.0, pixel), 2.0);
+        assert_eq!(chain.lod(pixel * 0.5, pixel * 0.5), 0.0);
+    }
+}
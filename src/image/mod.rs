@@ -0,0 +1,371 @@
+//! In-memory images and encoders to common file formats.
+//!
+//! [`Image`] stores 8-bit RGBA pixels and [`HdrImage`] stores linear float
+//! RGBA; both keep rows top to bottom. Images can be written as binary PPM,
+//! 32-bit BMP or PNG, all without external dependencies, and compared
+//! against reference images with [`diff`].
+//!
+//! For rendering, [`texture`] decodes PNG, BMP, TGA and Radiance HDR files
+//! into textures that sample by UV, and [`mipmap`] builds filtered mipmap
+//! chains for trilinear lookups.
+
+#![allow(clippy::needless_return)]
+
+pub mod bmp;
+pub mod diff;
+pub mod half;
+pub mod hdr;
+pub mod mipmap;
+pub mod png;
+pub mod ppm;
+pub mod texture;
+pub mod tga;
+pub mod zlib;
+
+use std::{fs, io, path::Path};
+
+use crate::render::Framebuffer;
+
+/// An 8-bit RGBA image.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<[u8; 4]>,
+}
+
+impl Image {
+    /// A transparent black image.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![[0; 4]; width * height],
+        }
+    }
+
+    /// Wraps row-major pixels.
+    ///
+    /// Panics if there are not exactly `width * height` of them.
+    pub fn from_pixels(width: usize, height: usize, pixels: Vec<[u8; 4]>) -> Self {
+        assert_eq!(
+            pixels.len(),
+            width * height,
+            "{} pixels do not fill a {width}x{height} image",
+            pixels.len()
+        );
+
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Evaluates `f(x, y)` for every pixel, e.g. for procedural textures.
+    pub fn from_fn(
+        width: usize,
+        height: usize,
+        mut f: impl FnMut(usize, usize) -> [u8; 4],
+    ) -> Self {
+        let mut pixels = Vec::with_capacity(width * height);
+
+        for y in 0..height {
+            for x in 0..width {
+                pixels.push(f(x, y));
+            }
+        }
+
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Panics if the pixel is outside the image.
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        return self.pixels[self.index(x, y)];
+    }
+
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: [u8; 4]) {
+        let i = self.index(x, y);
+        self.pixels[i] = color;
+    }
+
+    pub fn pixels(&self) -> &[[u8; 4]] {
+        &self.pixels
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        self.pixels.as_flattened()
+    }
+
+    pub fn encode_ppm(&self) -> Vec<u8> {
+        ppm::encode(self)
+    }
+
+    pub fn encode_bmp(&self) -> Vec<u8> {
+        bmp::encode(self)
+    }
+
+    pub fn encode_png(&self) -> Vec<u8> {
+        png::encode(self)
+    }
+
+    /// Writes the image in the format named by the extension of `path`:
+    /// `ppm`, `bmp` or `png`.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+
+        let bytes = match extension.as_deref() {
+            Some("ppm") => self.encode_ppm(),
+            Some("bmp") => self.encode_bmp(),
+            Some("png") => self.encode_png(),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("unsupported image format: {}", path.display()),
+                ))
+            }
+        };
+
+        return fs::write(path, bytes);
+    }
+
+    fn index(&self, x: usize, y: usize) -> usize {
+        assert!(
+            x < self.width && y < self.height,
+            "pixel ({x}, {y}) out of range for a {}x{} image",
+            self.width,
+            self.height
+        );
+
+        return y * self.width + x;
+    }
+}
+
+impl From<&Framebuffer> for Image {
+    fn from(fb: &Framebuffer) -> Self {
+        Image::from_pixels(fb.width(), fb.height(), fb.pixels().to_vec())
+    }
+}
+
+/// How [`HdrImage::to_image`] compresses unbounded radiance into `[0, 1]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ToneMap {
+    /// Clip every channel at 1.
+    Clamp,
+    /// `c / (1 + c)`, which keeps detail in highlights.
+    #[default]
+    Reinhard,
+}
+
+/// A linear floating-point RGBA image.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct HdrImage {
+    width: usize,
+    height: usize,
+    pixels: Vec<[f32; 4]>,
+}
+
+impl HdrImage {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![[0.0; 4]; width * height],
+        }
+    }
+
+    pub fn from_fn(
+        width: usize,
+        height: usize,
+        mut f: impl FnMut(usize, usize) -> [f32; 4],
+    ) -> Self {
+        let mut pixels = Vec::with_capacity(width * height);
+
+        for y in 0..height {
+            for x in 0..width {
+                pixels.push(f(x, y));
+            }
+        }
+
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> [f32; 4] {
+        return self.pixels[self.index(x, y)];
+    }
+
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: [f32; 4]) {
+        let i = self.index(x, y);
+        self.pixels[i] = color;
+    }
+
+    pub fn pixels(&self) -> &[[f32; 4]] {
+        &self.pixels
+    }
+
+    pub fn pixels_mut(&mut self) -> &mut [[f32; 4]] {
+        &mut self.pixels
+    }
+
+    /// Scales the color by `2^exposure`, tone maps it and encodes it as
+    /// sRGB. Alpha is clamped but otherwise kept linear.
+    pub fn to_image(&self, exposure: f32, tone_map: ToneMap) -> Image {
+        let scale = exposure.exp2();
+
+        let pixels = self
+            .pixels
+            .iter()
+            .map(|p| {
+                let mut out = [0u8; 4];
+                for i in 0..3 {
+                    let c = (p[i] * scale).max(0.0);
+                    let mapped = match tone_map {
+                        ToneMap::Clamp => c.min(1.0),
+                        ToneMap::Reinhard => c / (1.0 + c),
+                    };
+                    out[i] = to_u8(linear_to_srgb(mapped));
+                }
+                out[3] = to_u8(p[3].clamp(0.0, 1.0));
+                out
+            })
+            .collect();
+
+        return Image::from_pixels(self.width, self.height, pixels);
+    }
+
+    fn index(&self, x: usize, y: usize) -> usize {
+        assert!(
+            x < self.width && y < self.height,
+            "pixel ({x}, {y}) out of range for a {}x{} image",
+            self.width,
+            self.height
+        );
+
+        return y * self.width + x;
+    }
+}
+
+/// The sRGB transfer function for a linear value in `[0, 1]`.
+pub fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.003_130_8 {
+        return c * 12.92;
+    }
+
+    return 1.055 * c.powf(1.0 / 2.4) - 0.055;
+}
+
+/// Inverse of [`linear_to_srgb`].
+pub fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.040_45 {
+        return c / 12.92;
+    }
+
+    return ((c + 0.055) / 1.055).powf(2.4);
+}
+
+fn to_u8(c: f32) -> u8 {
+    (c * 255.0 + 0.5) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_fn_test() {
+        let image = Image::from_fn(3, 2, |x, y| [x as u8, y as u8, 0, 255]);
+
+        assert_eq!(image.pixel(2, 1), [2, 1, 0, 255]);
+        assert_eq!(image.pixels()[5], [2, 1, 0, 255]);
+        assert_eq!(image.as_bytes().len(), 24);
+    }
+
+    #[test]
+    #[should_panic(expected = "do not fill")]
+    fn from_pixels_wrong_length_test() {
+        Image::from_pixels(2, 2, vec![[0; 4]; 3]);
+    }
+
+    #[test]
+    fn from_framebuffer_test() {
+        let mut fb = Framebuffer::new(2, 2);
+        fb.set_pixel(1, 0, [1, 2, 3, 4]);
+
+        assert_eq!(Image::from(&fb).pixel(1, 0), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn srgb_round_trip_test() {
+        for i in 0..=255 {
+            let c = i as f32 / 255.0;
+            assert!((linear_to_srgb(srgb_to_linear(c)) - c).abs() < 1e-5);
+        }
+        assert_eq!(to_u8(linear_to_srgb(0.5)), 188);
+    }
+
+    #[test]
+    fn hdr_to_image_test() {
+        let mut hdr = HdrImage::new(2, 1);
+        hdr.set_pixel(0, 0, [1.0, 4.0, 0.0, 1.0]);
+        hdr.set_pixel(1, 0, [-1.0, 0.5, 100.0, 2.0]);
+
+        let clamped = hdr.to_image(0.0, ToneMap::Clamp);
+        assert_eq!(clamped.pixel(0, 0), [255, 255, 0, 255]);
+        assert_eq!(clamped.pixel(1, 0), [0, 188, 255, 255]);
+
+        // Reinhard maps 1.0 to 0.5; one stop of exposure maps 0.5 there too.
+        let mapped = hdr.to_image(0.0, ToneMap::Reinhard);
+        assert_eq!(mapped.pixel(0, 0)[0], 188);
+        assert_eq!(hdr.to_image(1.0, ToneMap::Reinhard).pixel(1, 0)[1], 188);
+    }
+
+    #[test]
+    fn save_test() {
+        let image = Image::from_fn(4, 4, |x, y| [(x * 60) as u8, (y * 60) as u8, 128, 255]);
+        let dir = std::env::temp_dir().join(format!("wmb-image-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        for (name, header) in [
+            ("a.ppm", &b"P6"[..]),
+            ("a.bmp", b"BM"),
+            ("a.PNG", b"\x89PNG"),
+        ] {
+            let path = dir.join(name);
+            image.save(&path).unwrap();
+            assert!(fs::read(&path).unwrap().starts_with(header));
+        }
+
+        let error = image.save(dir.join("a.gif")).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
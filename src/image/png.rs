@@ -0,0 +1,783 @@
+//! PNG encoding of 8-bit RGBA images, and decoding of any standard PNG.
+//!
+//! Every scanline is filtered with whichever of the five PNG filters gives
+//! the smallest sum of absolute differences, the usual heuristic, before the
+//! whole image is compressed with [`zlib`](super::zlib). The decoder also
+//! reads the other color types and bit depths and Adam7 interlacing, as
+//! found in textures from other tools.
+
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+use super::{
+    zlib::{self, InflateError},
+    Image,
+};
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+const BYTES_PER_PIXEL: usize = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    InvalidSignature,
+    UnexpectedEnd,
+    ChecksumMismatch([u8; 4]),
+    MissingHeader,
+    /// The header gives a zero width or height, one above 2^31 - 1, or a
+    /// size whose pixels cannot be counted.
+    InvalidSize(usize, usize),
+    /// A palette image without a valid `PLTE` chunk, or a pixel with an
+    /// index past its end.
+    InvalidPalette,
+    /// The image uses a bit depth, color type or interlacing this decoder
+    /// does not read.
+    Unsupported(String),
+    InvalidFilter(u8),
+    Inflate(InflateError),
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::InvalidSignature => write!(f, "not a PNG file"),
+            DecodeError::UnexpectedEnd => write!(f, "unexpected end of PNG data"),
+            DecodeError::ChecksumMismatch(kind) => {
+                write!(f, "CRC mismatch in {} chunk", String::from_utf8_lossy(kind))
+            }
+            DecodeError::MissingHeader => write!(f, "PNG has no IHDR chunk"),
+            DecodeError::InvalidSize(width, height) => {
+                write!(f, "invalid PNG size {width}x{height}")
+            }
+            DecodeError::InvalidPalette => write!(f, "missing or invalid PNG palette"),
+            DecodeError::Unsupported(what) => write!(f, "unsupported PNG: {what}"),
+            DecodeError::InvalidFilter(kind) => write!(f, "invalid scanline filter {kind}"),
+            DecodeError::Inflate(e) => write!(f, "corrupt image data: {e}"),
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
+impl From<InflateError> for DecodeError {
+    fn from(e: InflateError) -> Self {
+        DecodeError::Inflate(e)
+    }
+}
+
+pub fn encode(image: &Image) -> Vec<u8> {
+    let mut out = SIGNATURE.to_vec();
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(image.width() as u32).to_be_bytes());
+    header.extend_from_slice(&(image.height() as u32).to_be_bytes());
+    // Bit depth 8, color type 6 (RGBA), deflate, adaptive filtering, no
+    // interlacing.
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+    write_chunk(&mut out, b"IHDR", &header);
+
+    write_chunk(&mut out, b"IDAT", &zlib::compress(&filter(image)));
+    write_chunk(&mut out, b"IEND", &[]);
+
+    return out;
+}
+
+/// RGBA pixels with 16-bit samples, as decoded by [`decode16`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image16 {
+    pub width: usize,
+    pub height: usize,
+    /// The file's bit depth; samples of lower depths, and palette entries,
+    /// are scaled to the full 16-bit range.
+    pub bit_depth: usize,
+    pub pixels: Vec<[u16; 4]>,
+}
+
+impl Image16 {
+    /// Rounds the samples to 8 bits.
+    pub fn to_image(&self) -> Image {
+        let to_u8 = |s: u16| ((s as u32 * 255 + 32767) / 65535) as u8;
+
+        return Image::from_pixels(
+            self.width,
+            self.height,
+            self.pixels.iter().map(|p| p.map(to_u8)).collect(),
+        );
+    }
+}
+
+/// Decodes any standard PNG: grayscale, RGB, palette, with or without
+/// alpha or a `tRNS` transparency chunk, at every bit depth, interlaced or
+/// not. 16-bit samples are rounded to 8 bits.
+pub fn decode(bytes: &[u8]) -> Result<Image, DecodeError> {
+    return Ok(decode16(bytes)?.to_image());
+}
+
+/// Decodes like [`decode`], keeping 16-bit samples.
+pub fn decode16(bytes: &[u8]) -> Result<Image16, DecodeError> {
+    if !bytes.starts_with(&SIGNATURE) {
+        return Err(DecodeError::InvalidSignature);
+    }
+
+    let mut header = None;
+    let mut palette = Vec::new();
+    let mut transparency = Vec::new();
+    let mut data = Vec::new();
+    let mut at = SIGNATURE.len();
+    while at < bytes.len() {
+        let (kind, chunk) = read_chunk(bytes, &mut at)?;
+        match &kind {
+            b"IHDR" => header = Some(Header::parse(chunk)?),
+            b"PLTE" => {
+                if chunk.len() % 3 != 0 || chunk.len() > 3 * 256 {
+                    return Err(DecodeError::InvalidPalette);
+                }
+                palette = chunk
+                    .chunks_exact(3)
+                    .map(|c| [c[0], c[1], c[2], 255])
+                    .collect();
+            }
+            b"tRNS" => transparency = chunk.to_vec(),
+            b"IDAT" => data.extend_from_slice(chunk),
+            b"IEND" => break,
+            _ => {}
+        }
+    }
+
+    let header = header.ok_or(DecodeError::MissingHeader)?;
+    if header.color_type == 3 {
+        if palette.is_empty() {
+            return Err(DecodeError::InvalidPalette);
+        }
+        for (entry, &alpha) in palette.iter_mut().zip(&transparency) {
+            entry[3] = alpha;
+        }
+    }
+
+    let (width, height) = (header.width, header.height);
+    let passes: &[(usize, usize, usize, usize)] = if header.interlaced {
+        &ADAM7
+    } else {
+        &[(0, 0, 1, 1)]
+    };
+    let bits = header.bits_per_pixel();
+
+    // The scanlines' total size caps decompression, and the data must hold
+    // all of them before the header's size is trusted with an allocation.
+    let mut layout = Vec::with_capacity(passes.len());
+    let mut needed = 0usize;
+    for &(x0, y0, dx, dy) in passes {
+        let columns = width.saturating_sub(x0).div_ceil(dx);
+        let rows = height.saturating_sub(y0).div_ceil(dy);
+        if columns == 0 || rows == 0 {
+            continue;
+        }
+
+        let stride = columns
+            .checked_mul(bits)
+            .ok_or(DecodeError::InvalidSize(width, height))?
+            .div_ceil(8);
+        needed = (stride + 1)
+            .checked_mul(rows)
+            .and_then(|length| needed.checked_add(length))
+            .ok_or(DecodeError::InvalidSize(width, height))?;
+        layout.push((x0, y0, dx, dy, columns, rows, stride));
+    }
+    let raw = zlib::decompress(&data, needed)?;
+    if needed > raw.len() {
+        return Err(DecodeError::UnexpectedEnd);
+    }
+
+    let count = width
+        .checked_mul(height)
+        .ok_or(DecodeError::InvalidSize(width, height))?;
+    let mut pixels = vec![[0, 0, 0, u16::MAX]; count];
+    let key = header.transparent_key(&transparency);
+    let mut at = 0;
+
+    for (x0, y0, dx, dy, columns, rows, stride) in layout {
+        let end = at + (stride + 1) * rows;
+        let lines = unfilter(&raw[at..end], stride, rows, bits.div_ceil(8))?;
+        at = end;
+
+        for y in 0..rows {
+            let line = &lines[y * stride..(y + 1) * stride];
+            for x in 0..columns {
+                let pixel = header.pixel(line, x, &palette, key)?;
+                pixels[(y0 + y * dy) * width + x0 + x * dx] = pixel;
+            }
+        }
+    }
+
+    return Ok(Image16 {
+        width,
+        height,
+        bit_depth: header.bit_depth,
+        pixels,
+    });
+}
+
+/// The first column and row and the spacing of each Adam7 pass.
+const ADAM7: [(usize, usize, usize, usize); 7] = [
+    (0, 0, 8, 8),
+    (4, 0, 8, 8),
+    (0, 4, 4, 8),
+    (2, 0, 4, 4),
+    (0, 2, 2, 4),
+    (1, 0, 2, 2),
+    (0, 1, 1, 2),
+];
+
+/// The fields of an `IHDR` chunk this decoder uses.
+struct Header {
+    width: usize,
+    height: usize,
+    bit_depth: usize,
+    color_type: u8,
+    interlaced: bool,
+}
+
+impl Header {
+    fn parse(chunk: &[u8]) -> Result<Self, DecodeError> {
+        if chunk.len() != 13 {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+
+        let (bit_depth, color_type) = (chunk[8], chunk[9]);
+        let depths: &[u8] = match color_type {
+            0 => &[1, 2, 4, 8, 16],
+            3 => &[1, 2, 4, 8],
+            2 | 4 | 6 => &[8, 16],
+            _ => &[],
+        };
+        if !depths.contains(&bit_depth) || chunk[10] != 0 || chunk[11] != 0 || chunk[12] > 1 {
+            return Err(DecodeError::Unsupported(format!(
+                "bit depth {bit_depth}, color type {color_type}, interlace {}",
+                chunk[12]
+            )));
+        }
+
+        // The PNG specification limits both dimensions to 1..=2^31 - 1.
+        let width = u32::from_be_bytes(chunk[0..4].try_into().unwrap()) as usize;
+        let height = u32::from_be_bytes(chunk[4..8].try_into().unwrap()) as usize;
+        let valid = 1..=i32::MAX as usize;
+        if !valid.contains(&width) || !valid.contains(&height) {
+            return Err(DecodeError::InvalidSize(width, height));
+        }
+
+        return Ok(Self {
+            width,
+            height,
+            bit_depth: bit_depth as usize,
+            color_type,
+            interlaced: chunk[12] == 1,
+        });
+    }
+
+    fn channels(&self) -> usize {
+        match self.color_type {
+            2 => 3,
+            4 => 2,
+            6 => 4,
+            _ => 1,
+        }
+    }
+
+    fn bits_per_pixel(&self) -> usize {
+        self.channels() * self.bit_depth
+    }
+
+    /// Sample `k` of pixel `x` in a scanline, at the image's bit depth.
+    fn sample(&self, line: &[u8], x: usize, k: usize) -> u16 {
+        let index = x * self.channels() + k;
+        match self.bit_depth {
+            16 => u16::from_be_bytes([line[2 * index], line[2 * index + 1]]),
+            8 => line[index] as u16,
+            depth => {
+                let bit = index * depth;
+                let shift = 8 - depth - bit % 8;
+                ((line[bit / 8] >> shift) & ((1 << depth) - 1) as u8) as u16
+            }
+        }
+    }
+
+    /// The gray level or RGB color a `tRNS` chunk makes fully transparent,
+    /// padded with zeros like the samples of [`Header::pixel`].
+    fn transparent_key(&self, transparency: &[u8]) -> Option<[u16; 4]> {
+        if !matches!(self.color_type, 0 | 2) || transparency.len() != 2 * self.channels() {
+            return None;
+        }
+
+        let mut key = [0; 4];
+        for (k, c) in key.iter_mut().zip(transparency.chunks_exact(2)) {
+            *k = u16::from_be_bytes([c[0], c[1]]);
+        }
+
+        return Some(key);
+    }
+
+    /// The pixel's RGBA samples, scaled to 16 bits.
+    fn pixel(
+        &self,
+        line: &[u8],
+        x: usize,
+        palette: &[[u8; 4]],
+        key: Option<[u16; 4]>,
+    ) -> Result<[u16; 4], DecodeError> {
+        let mut samples = [0; 4];
+        for (k, sample) in samples.iter_mut().enumerate().take(self.channels()) {
+            *sample = self.sample(line, x, k);
+        }
+        if self.color_type == 3 {
+            return palette
+                .get(samples[0] as usize)
+                .map(|entry| entry.map(|c| c as u16 * 257))
+                .ok_or(DecodeError::InvalidPalette);
+        }
+
+        // The maximum of every depth below 16 divides 65535.
+        let scale = (u16::MAX as u32 / ((1u32 << self.bit_depth) - 1)) as u16;
+        let opaque = if key == Some(samples) { 0 } else { u16::MAX };
+        let samples = samples.map(|s| s * scale);
+
+        return Ok(match self.color_type {
+            0 => {
+                let v = samples[0];
+                [v, v, v, opaque]
+            }
+            2 => [samples[0], samples[1], samples[2], opaque],
+            4 => {
+                let v = samples[0];
+                [v, v, v, samples[1]]
+            }
+            _ => samples,
+        });
+    }
+}
+
+fn read_chunk<'a>(bytes: &'a [u8], at: &mut usize) -> Result<([u8; 4], &'a [u8]), DecodeError> {
+    let start = *at;
+    let header = bytes
+        .get(start..start + 8)
+        .ok_or(DecodeError::UnexpectedEnd)?;
+    let length = u32::from_be_bytes(header[..4].try_into().unwrap()) as usize;
+    let kind: [u8; 4] = header[4..].try_into().unwrap();
+
+    let end = start + 8 + length;
+    let crc = bytes.get(end..end + 4).ok_or(DecodeError::UnexpectedEnd)?;
+    let mut expected = Crc32::new();
+    expected.update(&bytes[start + 4..end]);
+    if u32::from_be_bytes(crc.try_into().unwrap()) != expected.finish() {
+        return Err(DecodeError::ChecksumMismatch(kind));
+    }
+
+    *at = end + 4;
+    return Ok((kind, &bytes[start + 8..end]));
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+
+    let mut crc = Crc32::new();
+    crc.update(kind);
+    crc.update(data);
+    out.extend_from_slice(&crc.finish().to_be_bytes());
+}
+
+/// Filtered scanlines, each prefixed with its filter type.
+fn filter(image: &Image) -> Vec<u8> {
+    let stride = image.width() * BYTES_PER_PIXEL;
+    let bytes = image.as_bytes();
+    let zero = vec![0u8; stride];
+    let mut out = Vec::with_capacity((stride + 1) * image.height());
+    let mut candidate = vec![0u8; stride];
+    let mut best = vec![0u8; stride];
+
+    for y in 0..image.height() {
+        let row = &bytes[y * stride..(y + 1) * stride];
+        let above = if y == 0 {
+            &zero[..]
+        } else {
+            &bytes[(y - 1) * stride..y * stride]
+        };
+
+        let mut best_type = 0;
+        let mut best_cost = u64::MAX;
+        for kind in 0..5 {
+            for i in 0..stride {
+                let left = if i >= BYTES_PER_PIXEL {
+                    row[i - BYTES_PER_PIXEL]
+                } else {
+                    0
+                };
+                let upper_left = if i >= BYTES_PER_PIXEL {
+                    above[i - BYTES_PER_PIXEL]
+                } else {
+                    0
+                };
+                candidate[i] = row[i].wrapping_sub(predict(kind, left, above[i], upper_left));
+            }
+
+            // Residuals are signed; small magnitudes compress best.
+            let cost: u64 = candidate
+                .iter()
+                .map(|&b| (b as i8).unsigned_abs() as u64)
+                .sum();
+            if cost < best_cost {
+                best_cost = cost;
+                best_type = kind;
+                best.copy_from_slice(&candidate);
+            }
+        }
+
+        out.push(best_type);
+        out.extend_from_slice(&best);
+    }
+
+    return out;
+}
+
+/// Reverses [`filter`] for `height` scanlines of `stride` bytes with
+/// `bpp` bytes per pixel (at least one), dropping the filter type bytes.
+fn unfilter(data: &[u8], stride: usize, height: usize, bpp: usize) -> Result<Vec<u8>, DecodeError> {
+    if data.len() < (stride + 1) * height {
+        return Err(DecodeError::UnexpectedEnd);
+    }
+    let bpp = bpp.max(1);
+    let mut out: Vec<u8> = Vec::with_capacity(stride * height);
+
+    for y in 0..height {
+        let line = &data[y * (stride + 1)..(y + 1) * (stride + 1)];
+        if line[0] > 4 {
+            return Err(DecodeError::InvalidFilter(line[0]));
+        }
+
+        let start = out.len();
+        for i in 0..stride {
+            let left = if i >= bpp { out[start + i - bpp] } else { 0 };
+            let above = if y > 0 { out[start + i - stride] } else { 0 };
+            let upper_left = if y > 0 && i >= bpp {
+                out[start + i - stride - bpp]
+            } else {
+                0
+            };
+            out.push(line[1 + i].wrapping_add(predict(line[0], left, above, upper_left)));
+        }
+    }
+
+    return Ok(out);
+}
+
+/// The value filter `kind` predicts from the neighbouring bytes.
+pub(crate) fn predict(kind: u8, left: u8, above: u8, upper_left: u8) -> u8 {
+    match kind {
+        0 => 0,
+        1 => left,
+        2 => above,
+        3 => ((left as u16 + above as u16) / 2) as u8,
+        _ => paeth(left, above, upper_left),
+    }
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = (
+        (p - a as i16).abs(),
+        (p - b as i16).abs(),
+        (p - c as i16).abs(),
+    );
+
+    if pa <= pb && pa <= pc {
+        return a;
+    }
+    if pb <= pc {
+        return b;
+    }
+
+    return c;
+}
+
+/// CRC-32 as used by PNG (and zip): reflected polynomial `0xedb88320`.
+pub(crate) struct Crc32 {
+    table: [u32; 256],
+    value: u32,
+}
+
+impl Crc32 {
+    pub(crate) fn new() -> Self {
+        let mut table = [0u32; 256];
+
+        for (n, entry) in table.iter_mut().enumerate() {
+            let mut c = n as u32;
+            for _ in 0..8 {
+                c = if c & 1 == 1 {
+                    0xedb8_8320 ^ (c >> 1)
+                } else {
+                    c >> 1
+                };
+            }
+            *entry = c;
+        }
+
+        Self {
+            table,
+            value: 0xffff_ffff,
+        }
+    }
+
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.value =
+                self.table[((self.value ^ byte as u32) & 0xff) as usize] ^ (self.value >> 8);
+        }
+    }
+
+    pub(crate) fn finish(&self) -> u32 {
+        self.value ^ 0xffff_ffff
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Splits a PNG into `(type, data)` chunks, checking every CRC.
+    fn chunks(bytes: &[u8]) -> Vec<([u8; 4], Vec<u8>)> {
+        assert_eq!(&bytes[..8], &SIGNATURE);
+
+        let mut chunks = Vec::new();
+        let mut at = 8;
+        while at < bytes.len() {
+            let length = u32::from_be_bytes(bytes[at..at + 4].try_into().unwrap()) as usize;
+            let kind: [u8; 4] = bytes[at + 4..at + 8].try_into().unwrap();
+            let data = bytes[at + 8..at + 8 + length].to_vec();
+            let crc =
+                u32::from_be_bytes(bytes[at + 8 + length..at + 12 + length].try_into().unwrap());
+
+            let mut expected = Crc32::new();
+            expected.update(&bytes[at + 4..at + 8 + length]);
+            assert_eq!(crc, expected.finish());
+
+            chunks.push((kind, data));
+            at += 12 + length;
+        }
+
+        return chunks;
+    }
+
+    #[test]
+    fn crc32_test() {
+        let mut crc = Crc32::new();
+        crc.update(b"123456789");
+
+        assert_eq!(crc.finish(), 0xcbf4_3926);
+    }
+
+    #[test]
+    fn paeth_test() {
+        assert_eq!(paeth(10, 20, 10), 20);
+        assert_eq!(paeth(20, 10, 10), 20);
+        assert_eq!(paeth(10, 10, 30), 10);
+    }
+
+    #[test]
+    fn encode_round_trip_test() {
+        let image = Image::from_fn(37, 23, |x, y| {
+            [
+                (x * 7) as u8,
+                (y * 11) as u8,
+                ((x * y) % 256) as u8,
+                255 - x as u8,
+            ]
+        });
+        let bytes = encode(&image);
+        let chunks = chunks(&bytes);
+
+        let kinds: Vec<&[u8; 4]> = chunks.iter().map(|(k, _)| k).collect();
+        assert_eq!(kinds, [b"IHDR", b"IDAT", b"IEND"]);
+        assert_eq!(chunks[0].1, [0, 0, 0, 37, 0, 0, 0, 23, 8, 6, 0, 0, 0]);
+
+        assert_eq!(decode(&bytes).unwrap(), image);
+
+        // Gradients filter away almost entirely.
+        assert!(chunks[1].1.len() < image.as_bytes().len() / 4);
+    }
+
+    #[test]
+    fn empty_chunk_crc_test() {
+        let bytes = encode(&Image::new(1, 1));
+
+        assert_eq!(&bytes[bytes.len() - 12..], b"\0\0\0\0IEND\xae\x42\x60\x82");
+    }
+
+    #[test]
+    fn decode_error_test() {
+        let mut bytes = encode(&Image::new(2, 2));
+
+        assert_eq!(decode(&bytes[1..]), Err(DecodeError::InvalidSignature));
+        assert_eq!(decode(&bytes[..30]), Err(DecodeError::UnexpectedEnd));
+
+        // Sizes beyond the specification's limit, or beyond the data.
+        let huge = build((u32::MAX, u32::MAX), [8, 6, 0, 0, 0], &[], &[0]);
+        assert_eq!(
+            decode(&huge),
+            Err(DecodeError::InvalidSize(
+                u32::MAX as usize,
+                u32::MAX as usize
+            ))
+        );
+        for size in [(0, 1), (1, 0)] {
+            let empty = build(size, [8, 6, 0, 0, 0], &[], &[]);
+            let (width, height) = (size.0 as usize, size.1 as usize);
+            assert_eq!(decode(&empty), Err(DecodeError::InvalidSize(width, height)));
+        }
+        let large = build((1 << 30, 1 << 30), [1, 0, 0, 0, 1], &[], &[0; 64]);
+        assert_eq!(decode(&large), Err(DecodeError::UnexpectedEnd));
+
+        // Data inflating past the scanlines stops at their size.
+        let bomb = build((1, 1), [8, 0, 0, 0, 0], &[], &[0; 1 << 16]);
+        assert!(bomb.len() < 1024);
+        assert_eq!(
+            decode(&bomb),
+            Err(DecodeError::Inflate(InflateError::TooLarge))
+        );
+
+        // Flip a bit in the IHDR payload.
+        bytes[20] ^= 1;
+        assert_eq!(decode(&bytes), Err(DecodeError::ChecksumMismatch(*b"IHDR")));
+    }
+
+    /// A PNG with the given `IHDR` fields after the size, extra chunks
+    /// before the data, and unfiltered scanlines.
+    pub(crate) fn build(
+        size: (u32, u32),
+        fields: [u8; 5],
+        extra: &[(&[u8; 4], &[u8])],
+        lines: &[u8],
+    ) -> Vec<u8> {
+        let mut out = SIGNATURE.to_vec();
+        let mut header = size.0.to_be_bytes().to_vec();
+        header.extend_from_slice(&size.1.to_be_bytes());
+        header.extend_from_slice(&fields);
+        write_chunk(&mut out, b"IHDR", &header);
+        for (kind, data) in extra {
+            write_chunk(&mut out, kind, data);
+        }
+        write_chunk(&mut out, b"IDAT", &zlib::compress(lines));
+        write_chunk(&mut out, b"IEND", &[]);
+
+        return out;
+    }
+
+    #[test]
+    fn decode_formats_test() {
+        // 2-bit gray with level 1 keyed out by tRNS.
+        let bytes = build(
+            (3, 1),
+            [2, 0, 0, 0, 0],
+            &[(b"tRNS", &[0, 1])],
+            &[0, 0b00_01_11_00],
+        );
+        let image = decode(&bytes).unwrap();
+        assert_eq!(
+            image.pixels(),
+            [[0, 0, 0, 255], [85, 85, 85, 0], [255, 255, 255, 255]]
+        );
+
+        // 8-bit RGB with one color keyed out.
+        let bytes = build(
+            (2, 1),
+            [8, 2, 0, 0, 0],
+            &[(b"tRNS", &[0, 1, 0, 2, 0, 3])],
+            &[0, 1, 2, 3, 1, 2, 4],
+        );
+        let image = decode(&bytes).unwrap();
+        assert_eq!(image.pixels(), [[1, 2, 3, 0], [1, 2, 4, 255]]);
+
+        // 1-bit palette with a translucent first entry.
+        let bytes = build(
+            (2, 2),
+            [1, 3, 0, 0, 0],
+            &[(b"PLTE", &[10, 20, 30, 40, 50, 60]), (b"tRNS", &[128])],
+            &[0, 0b1000_0000, 0, 0b0100_0000],
+        );
+        let image = decode(&bytes).unwrap();
+        assert_eq!(image.pixel(0, 0), [40, 50, 60, 255]);
+        assert_eq!(image.pixel(1, 0), [10, 20, 30, 128]);
+        assert_eq!(image.pixel(1, 1), [40, 50, 60, 255]);
+        assert_eq!(
+            decode(&build((1, 1), [8, 3, 0, 0, 0], &[], &[0, 0])),
+            Err(DecodeError::InvalidPalette)
+        );
+
+        // 16-bit gray and alpha, with a Sub filter on the second pixel.
+        let bytes = build(
+            (2, 1),
+            [16, 4, 0, 0, 0],
+            &[],
+            &[1, 0xff, 0xff, 0x80, 0x00, 0, 0, 0x80, 0x00],
+        );
+        let image = decode(&bytes).unwrap();
+        assert_eq!(image.pixels(), [[255, 255, 255, 128], [255, 255, 255, 0]]);
+    }
+
+    #[test]
+    fn decode16_test() {
+        let decode = |fields: [u8; 5], lines: &[u8]| decode16(&build((1, 1), fields, &[], lines));
+
+        let gray = decode([16, 0, 0, 0, 0], &[0, 0x03, 0xe8]).unwrap();
+        assert_eq!(
+            (gray.bit_depth, gray.pixels[0]),
+            (16, [1000, 1000, 1000, 65535])
+        );
+        let gray_alpha = decode([16, 4, 0, 0, 0], &[0, 0x03, 0xe8, 0x80, 0x01]).unwrap();
+        assert_eq!(gray_alpha.pixels, [[1000, 1000, 1000, 32769]]);
+        let rgb = decode([16, 2, 0, 0, 0], &[0, 0, 1, 0x75, 0x30, 0xff, 0xff]).unwrap();
+        assert_eq!(rgb.pixels, [[1, 30000, 65535, 65535]]);
+        let rgba = decode(
+            [16, 6, 0, 0, 0],
+            &[0, 0, 1, 0x75, 0x30, 0xff, 0xfe, 0x9c, 0x40],
+        )
+        .unwrap();
+        assert_eq!(rgba.pixels, [[1, 30000, 65534, 40000]]);
+        assert_eq!(rgba.to_image().pixels(), [[0, 117, 255, 156]]);
+
+        // Lower depths and palettes fill the 16-bit range.
+        let gray = decode([4, 0, 0, 0, 0], &[0, 0x50]).unwrap();
+        assert_eq!((gray.bit_depth, gray.pixels[0][0]), (4, 5 * 4369));
+        let palette = decode16(&build(
+            (1, 1),
+            [8, 3, 0, 0, 0],
+            &[(b"PLTE", &[1, 2, 255])],
+            &[0, 0],
+        ))
+        .unwrap();
+        assert_eq!(palette.pixels, [[257, 514, 65535, 65535]]);
+    }
+
+    #[test]
+    fn decode_interlaced_test() {
+        let image = Image::from_fn(5, 6, |x, y| [x as u8, y as u8, (x * y) as u8, 255]);
+
+        let mut lines = Vec::new();
+        for (x0, y0, dx, dy) in ADAM7 {
+            for y in (y0..image.height()).step_by(dy) {
+                let row: Vec<usize> = (x0..image.width()).step_by(dx).collect();
+                if row.is_empty() {
+                    break;
+                }
+                lines.push(0);
+                for x in row {
+                    let [r, g, b, _] = image.pixel(x, y);
+                    lines.extend_from_slice(&[r, g, b]);
+                }
+            }
+        }
+
+        let bytes = build((5, 6), [8, 2, 0, 0, 1], &[], &lines);
+        assert_eq!(decode(&bytes).unwrap(), image);
+    }
+}
@@ -0,0 +1,28 @@
+//! Binary portable pixmaps (`P6`). The format has no alpha channel, so it
+//! is dropped.
+
+use super::Image;
+
+pub fn encode(image: &Image) -> Vec<u8> {
+    let header = format!("P6\n{} {}\n255\n", image.width(), image.height());
+    let mut out = Vec::with_capacity(header.len() + image.pixels().len() * 3);
+
+    out.extend_from_slice(header.as_bytes());
+    for p in image.pixels() {
+        out.extend_from_slice(&p[..3]);
+    }
+
+    return out;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_test() {
+        let image = Image::from_pixels(2, 1, vec![[1, 2, 3, 4], [5, 6, 7, 8]]);
+
+        assert_eq!(encode(&image), b"P6\n2 1\n255\n\x01\x02\x03\x05\x06\x07");
+    }
+}
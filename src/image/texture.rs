@@ -0,0 +1,496 @@
+//! Textures for the CPU renderer: images decoded from common file formats
+//! into one of a few texel formats, with filtered lookups by UV.
+//!
+//! Lookups always return linear RGBA, so 8-bit sRGB textures are decoded
+//! on the fly. UVs follow [`Mesh`](crate::mesh::Mesh): `(0, 0)` is the
+//! bottom left corner, on the last row, and `(1, 1)` the top right of the
+//! first; texel centers sit half a texel in from the edges.
+
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    fs, io,
+    path::Path,
+    sync::OnceLock,
+};
+
+use super::{bmp, half, hdr, linear_to_srgb, png, srgb_to_linear, tga, to_u8, Image};
+use crate::math::Vector2D;
+
+/// How a [`Texture`] stores its texels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureFormat {
+    /// 8 bits per channel, linear or sRGB-encoded.
+    Rgba8,
+    /// Linear half floats.
+    Rgba16F,
+    /// Linear floats with no alpha; lookups return an alpha of 1.
+    Rgb32F,
+}
+
+/// What lookups do with coordinates outside `[0, 1]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Wrap {
+    #[default]
+    Repeat,
+    ClampToEdge,
+    MirroredRepeat,
+}
+
+impl Wrap {
+    /// Maps a texel index along an axis of `size` texels into range.
+    pub fn apply(self, i: i64, size: usize) -> usize {
+        let n = size as i64;
+        let i = match self {
+            Wrap::Repeat => i.rem_euclid(n),
+            Wrap::ClampToEdge => i.clamp(0, n - 1),
+            Wrap::MirroredRepeat => {
+                let m = i.rem_euclid(2 * n);
+                if m < n {
+                    m
+                } else {
+                    2 * n - 1 - m
+                }
+            }
+        };
+
+        return i as usize;
+    }
+}
+
+#[derive(Debug)]
+pub enum TextureError {
+    Png(png::DecodeError),
+    Bmp(bmp::DecodeError),
+    Tga(tga::DecodeError),
+    Hdr(hdr::DecodeError),
+    /// The bytes match no format this module reads.
+    UnknownFormat,
+    Io(io::Error),
+}
+
+impl Display for TextureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextureError::Png(e) => write!(f, "{e}"),
+            TextureError::Bmp(e) => write!(f, "{e}"),
+            TextureError::Tga(e) => write!(f, "{e}"),
+            TextureError::Hdr(e) => write!(f, "{e}"),
+            TextureError::UnknownFormat => write!(f, "unknown texture format"),
+            TextureError::Io(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl Error for TextureError {}
+
+impl From<png::DecodeError> for TextureError {
+    fn from(e: png::DecodeError) -> Self {
+        TextureError::Png(e)
+    }
+}
+
+impl From<bmp::DecodeError> for TextureError {
+    fn from(e: bmp::DecodeError) -> Self {
+        TextureError::Bmp(e)
+    }
+}
+
+impl From<tga::DecodeError> for TextureError {
+    fn from(e: tga::DecodeError) -> Self {
+        TextureError::Tga(e)
+    }
+}
+
+impl From<hdr::DecodeError> for TextureError {
+    fn from(e: hdr::DecodeError) -> Self {
+        TextureError::Hdr(e)
+    }
+}
+
+impl From<io::Error> for TextureError {
+    fn from(e: io::Error) -> Self {
+        TextureError::Io(e)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Texels {
+    Rgba8(Vec<[u8; 4]>),
+    Rgba16F(Vec<[u16; 4]>),
+    Rgb32F(Vec<[f32; 3]>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Texture {
+    width: usize,
+    height: usize,
+    texels: Texels,
+    /// Whether 8-bit color channels are sRGB-encoded. Alpha never is.
+    srgb: bool,
+}
+
+impl Texture {
+    /// Evaluates the linear color `f(x, y)` for every texel and stores it
+    /// in `format`. `srgb` picks the encoding of [`TextureFormat::Rgba8`]
+    /// and is ignored for the float formats.
+    pub fn from_fn(
+        width: usize,
+        height: usize,
+        format: TextureFormat,
+        srgb: bool,
+        mut f: impl FnMut(usize, usize) -> [f32; 4],
+    ) -> Self {
+        let mut linear = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                linear.push(f(x, y));
+            }
+        }
+
+        let srgb = srgb && format == TextureFormat::Rgba8;
+        let texels = match format {
+            TextureFormat::Rgba8 => Texels::Rgba8(
+                linear
+                    .iter()
+                    .map(|c| {
+                        let mut out = [0u8; 4];
+                        for i in 0..4 {
+                            let v = c[i].clamp(0.0, 1.0);
+                            out[i] = to_u8(if srgb && i < 3 { linear_to_srgb(v) } else { v });
+                        }
+                        out
+                    })
+                    .collect(),
+            ),
+            TextureFormat::Rgba16F => {
+                Texels::Rgba16F(linear.iter().map(|c| c.map(half::from_f32)).collect())
+            }
+            TextureFormat::Rgb32F => {
+                Texels::Rgb32F(linear.iter().map(|c| [c[0], c[1], c[2]]).collect())
+            }
+        };
+
+        return Self {
+            width,
+            height,
+            texels,
+            srgb,
+        };
+    }
+
+    /// Copies an 8-bit image as is. Color textures are usually sRGB;
+    /// normal, roughness and other data maps are linear.
+    pub fn from_image(image: &Image, srgb: bool) -> Self {
+        Self {
+            width: image.width(),
+            height: image.height(),
+            texels: Texels::Rgba8(image.pixels().to_vec()),
+            srgb,
+        }
+    }
+
+    /// Decodes a PNG, BMP, TGA or Radiance HDR file, told apart by their
+    /// contents; anything unrecognised is tried as TGA, which has no
+    /// signature. `srgb` says whether color channels are sRGB-encoded;
+    /// 8-bit files keep their encoding, while 16-bit PNGs are decoded to
+    /// linear [`TextureFormat::Rgba16F`]. HDR is always linear
+    /// [`TextureFormat::Rgb32F`].
+    pub fn decode(bytes: &[u8], srgb: bool) -> Result<Self, TextureError> {
+        if bytes.starts_with(b"\x89PNG") {
+            let image = png::decode16(bytes)?;
+            if image.bit_depth < 16 {
+                return Ok(Self::from_image(&image.to_image(), srgb));
+            }
+
+            return Ok(Self::from_fn(
+                image.width,
+                image.height,
+                TextureFormat::Rgba16F,
+                false,
+                |x, y| {
+                    let mut c = image.pixels[y * image.width + x].map(|s| s as f32 / 65535.0);
+                    if srgb {
+                        for v in &mut c[..3] {
+                            *v = srgb_to_linear(*v);
+                        }
+                    }
+                    c
+                },
+            ));
+        }
+        if bytes.starts_with(b"BM") {
+            return Ok(Self::from_image(&bmp::decode(bytes)?, srgb));
+        }
+        if bytes.starts_with(b"#?") {
+            return Ok(hdr::decode(bytes)?);
+        }
+
+        return match tga::decode(bytes) {
+            Ok(image) => Ok(Self::from_image(&image, srgb)),
+            Err(tga::DecodeError::Unsupported(_)) => Err(TextureError::UnknownFormat),
+            Err(e) => Err(e.into()),
+        };
+    }
+
+    /// Reads and decodes the file at `path`; see [`Texture::decode`].
+    pub fn load(path: impl AsRef<Path>, srgb: bool) -> Result<Self, TextureError> {
+        return Self::decode(&fs::read(path)?, srgb);
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn format(&self) -> TextureFormat {
+        match self.texels {
+            Texels::Rgba8(_) => TextureFormat::Rgba8,
+            Texels::Rgba16F(_) => TextureFormat::Rgba16F,
+            Texels::Rgb32F(_) => TextureFormat::Rgb32F,
+        }
+    }
+
+    pub fn is_srgb(&self) -> bool {
+        self.srgb
+    }
+
+    /// The linear color of a texel. Panics if it is outside the texture.
+    pub fn texel(&self, x: usize, y: usize) -> [f32; 4] {
+        assert!(
+            x < self.width && y < self.height,
+            "texel ({x}, {y}) out of range for a {}x{} texture",
+            self.width,
+            self.height
+        );
+        let i = y * self.width + x;
+
+        match &self.texels {
+            Texels::Rgba8(texels) => {
+                let [r, g, b, a] = texels[i];
+                if self.srgb {
+                    let table = srgb_table();
+                    [
+                        table[r as usize],
+                        table[g as usize],
+                        table[b as usize],
+                        a as f32 / 255.0,
+                    ]
+                } else {
+                    [r, g, b, a].map(|c| c as f32 / 255.0)
+                }
+            }
+            Texels::Rgba16F(texels) => texels[i].map(half::to_f32),
+            Texels::Rgb32F(texels) => {
+                let [r, g, b] = texels[i];
+                [r, g, b, 1.0]
+            }
+        }
+    }
+
+    /// The same texels in another format or encoding, e.g. an sRGB texture
+    /// as linear 8-bit, or a float one quantised to sRGB for display.
+    pub fn convert(&self, format: TextureFormat, srgb: bool) -> Self {
+        Self::from_fn(self.width, self.height, format, srgb, |x, y| {
+            self.texel(x, y)
+        })
+    }
+
+    /// 8-bit textures come back as stored; float ones are encoded as sRGB,
+    /// clipping at 1.
+    pub fn to_image(&self) -> Image {
+        return match &self.texels {
+            Texels::Rgba8(texels) => Image::from_pixels(self.width, self.height, texels.clone()),
+            _ => self.convert(TextureFormat::Rgba8, true).to_image(),
+        };
+    }
+
+    /// The texel under `uv`, with V counting up from the bottom row.
+    pub fn sample_nearest(&self, uv: Vector2D, wrap: Wrap) -> [f32; 4] {
+        let x = (uv.x() * self.width as f32).floor() as i64;
+        let y = (uv.y() * self.height as f32).floor() as i64;
+
+        return self.texel(
+            wrap.apply(x, self.width),
+            self.row(wrap.apply(y, self.height)),
+        );
+    }
+
+    /// Blends the four texels whose centers surround `uv`, in linear space.
+    /// V counts up from the bottom row, as in [`Texture::sample_nearest`].
+    pub fn sample_bilinear(&self, uv: Vector2D, wrap: Wrap) -> [f32; 4] {
+        let x = uv.x() * self.width as f32 - 0.5;
+        let y = uv.y() * self.height as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let mut out = [0.0; 4];
+        for (dy, wy) in [(0, 1.0 - fy), (1, fy)] {
+            for (dx, wx) in [(0, 1.0 - fx), (1, fx)] {
+                let weight = wx * wy;
+                if weight == 0.0 {
+                    continue;
+                }
+                let texel = self.texel(
+                    wrap.apply(x0 + dx, self.width),
+                    self.row(wrap.apply(y0 + dy, self.height)),
+                );
+                for i in 0..4 {
+                    out[i] += texel[i] * weight;
+                }
+            }
+        }
+
+        return out;
+    }
+
+    /// The stored row, counted from the top, for row `y` counted from the
+    /// bottom.
+    fn row(&self, y: usize) -> usize {
+        return self.height - 1 - y;
+    }
+}
+
+/// Linear values of the 256 sRGB-encoded bytes.
+fn srgb_table() -> &'static [f32; 256] {
+    static TABLE: OnceLock<[f32; 256]> = OnceLock::new();
+    return TABLE.get_or_init(|| std::array::from_fn(|i| srgb_to_linear(i as f32 / 255.0)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::Mesh;
+
+    fn close(a: [f32; 4], b: [f32; 4]) -> bool {
+        a.iter().zip(&b).all(|(x, y)| (x - y).abs() < 1e-3)
+    }
+
+    #[test]
+    fn wrap_test() {
+        let indices = |wrap: Wrap| -> Vec<usize> { (-4..5).map(|i| wrap.apply(i, 3)).collect() };
+
+        assert_eq!(indices(Wrap::Repeat), [2, 0, 1, 2, 0, 1, 2, 0, 1]);
+        assert_eq!(indices(Wrap::ClampToEdge), [0, 0, 0, 0, 0, 1, 2, 2, 2]);
+        assert_eq!(indices(Wrap::MirroredRepeat), [2, 2, 1, 0, 0, 1, 2, 2, 1]);
+    }
+
+    #[test]
+    fn format_test() {
+        let image = Image::from_fn(4, 2, |x, y| [(x * 60) as u8, (y * 200) as u8, 128, 77]);
+        let srgb = Texture::from_image(&image, true);
+        let linear = Texture::from_image(&image, false);
+
+        assert!(close(
+            linear.texel(1, 1),
+            [60.0 / 255.0, 200.0 / 255.0, 128.0 / 255.0, 77.0 / 255.0]
+        ));
+        assert!(close(srgb.texel(0, 0), [0.0, 0.0, 0.2158, 77.0 / 255.0]));
+
+        // Converting through every format and back keeps the bytes.
+        for format in [TextureFormat::Rgba16F, TextureFormat::Rgb32F] {
+            let converted = srgb.convert(format, false);
+            assert_eq!(converted.format(), format);
+            assert!(!converted.is_srgb());
+            let back = converted.convert(TextureFormat::Rgba8, true).to_image();
+            for (a, b) in back.pixels().iter().zip(image.pixels()) {
+                let alpha = if format == TextureFormat::Rgb32F {
+                    255
+                } else {
+                    b[3]
+                };
+                assert_eq!(*a, [b[0], b[1], b[2], alpha]);
+            }
+        }
+        assert_eq!(srgb.convert(TextureFormat::Rgba8, true), srgb);
+        assert_eq!(srgb.to_image(), image);
+    }
+
+    #[test]
+    fn decode_test() {
+        let image = Image::from_fn(3, 3, |x, y| [x as u8, y as u8, 9, 255]);
+
+        for bytes in [image.encode_png(), image.encode_bmp()] {
+            let texture = Texture::decode(&bytes, true).unwrap();
+            assert_eq!(texture.to_image(), image);
+            assert!(texture.is_srgb());
+        }
+        assert!(matches!(
+            Texture::decode(b"P6 2 2 255 abcdefghijkl", true),
+            Err(TextureError::UnknownFormat)
+        ));
+        assert!(matches!(
+            Texture::load("no/such/texture.png", true),
+            Err(TextureError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn decode_png16_test() {
+        // One RGBA pixel with samples 8 bits cannot hold.
+        let bytes = png::tests::build(
+            (1, 1),
+            [16, 6, 0, 0, 0],
+            &[],
+            &[0, 0x03, 0xe8, 0x75, 0x30, 0xff, 0xff, 0x9c, 0x40],
+        );
+        let expected = [1000.0, 30000.0, 65535.0, 40000.0].map(|s| s / 65535.0);
+
+        let linear = Texture::decode(&bytes, false).unwrap();
+        assert_eq!(linear.format(), TextureFormat::Rgba16F);
+        let texel = linear.texel(0, 0);
+        for (a, b) in texel.iter().zip(expected) {
+            assert!((a - b).abs() < b * 1e-3, "{texel:?}");
+        }
+        assert!((texel[0] - 4.0 / 255.0).abs() > 1e-4);
+
+        let srgb = Texture::decode(&bytes, true).unwrap();
+        assert!(!srgb.is_srgb());
+        let texel = srgb.texel(0, 0);
+        assert!((texel[0] - srgb_to_linear(expected[0])).abs() < 1e-6);
+        assert!((texel[3] - expected[3]).abs() < 1e-3);
+    }
+
+    #[test]
+    fn sample_test() {
+        // 2x1: black then white, linear.
+        let texture = Texture::from_fn(2, 1, TextureFormat::Rgba16F, false, |x, _| {
+            [x as f32, x as f32, x as f32, 1.0]
+        });
+        let at = |u: f32, wrap| texture.sample_bilinear(Vector2D::create(u, 0.5), wrap)[0];
+
+        assert_eq!(at(0.25, Wrap::Repeat), 0.0);
+        assert_eq!(at(0.75, Wrap::Repeat), 1.0);
+        assert_eq!(at(0.5, Wrap::Repeat), 0.5);
+        assert_eq!(at(0.375, Wrap::Repeat), 0.25);
+        // Past the right edge: wraps back to black, or stays white.
+        assert_eq!(at(1.0, Wrap::Repeat), 0.5);
+        assert_eq!(at(1.0, Wrap::ClampToEdge), 1.0);
+        assert_eq!(at(1.0, Wrap::MirroredRepeat), 1.0);
+        assert_eq!(at(0.0, Wrap::ClampToEdge), 0.0);
+
+        let nearest = texture.sample_nearest(Vector2D::create(0.6, 0.0), Wrap::Repeat);
+        assert_eq!(nearest, [1.0; 4]);
+        let nearest = texture.sample_nearest(Vector2D::create(-0.1, 3.0), Wrap::Repeat);
+        assert_eq!(nearest, [1.0; 4]);
+
+        // 1x2: white top row over a black one. The far edge of a plane, V
+        // of 1, is the top of the image.
+        let texture = Texture::from_fn(1, 2, TextureFormat::Rgba16F, false, |_, y| {
+            let v = 1.0 - y as f32;
+            [v, v, v, 1.0]
+        });
+        let plane = Mesh::plane(1.0, 1.0, 1, 1);
+        for (p, &uv) in plane.positions.iter().zip(&plane.uvs) {
+            let expected = if p.z() < 0.0 { 1.0 } else { 0.0 };
+            assert_eq!(texture.sample_nearest(uv, Wrap::ClampToEdge)[0], expected);
+            assert_eq!(texture.sample_bilinear(uv, Wrap::ClampToEdge)[0], expected);
+        }
+        let middle = Vector2D::create(0.5, 0.625);
+        assert_eq!(texture.sample_bilinear(middle, Wrap::Repeat)[0], 0.75);
+    }
+}
@@ -0,0 +1,237 @@
+//! Truevision TGA decoding: color-mapped, true-color and grayscale images,
+//! raw or run-length encoded, in either row and column order.
+
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
+
+use super::Image;
+
+const HEADER: usize = 18;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    UnexpectedEnd,
+    /// A color-mapped image without a usable color map, or a pixel with an
+    /// index past its end.
+    InvalidPalette,
+    /// An image type or pixel depth this decoder does not read.
+    Unsupported(String),
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::UnexpectedEnd => write!(f, "TGA ends unexpectedly"),
+            DecodeError::InvalidPalette => write!(f, "missing or invalid TGA color map"),
+            DecodeError::Unsupported(what) => write!(f, "unsupported TGA: {what}"),
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
+/// TGA has no signature, so any buffer with a plausible header is read.
+pub fn decode(bytes: &[u8]) -> Result<Image, DecodeError> {
+    let header = bytes.get(..HEADER).ok_or(DecodeError::UnexpectedEnd)?;
+    let u16_at = |at: usize| u16::from_le_bytes([header[at], header[at + 1]]) as usize;
+
+    let (id_length, map_type, kind) = (header[0] as usize, header[1], header[2]);
+    let (map_first, map_length, map_depth) = (u16_at(3), u16_at(5), header[7] as usize);
+    let (width, height) = (u16_at(12), u16_at(14));
+    let (depth, descriptor) = (header[16] as usize, header[17]);
+    let alpha_bits = descriptor & 0x0f;
+
+    let supported = match kind & !8 {
+        1 => map_type == 1 && matches!(depth, 8 | 16),
+        2 => matches!(depth, 15 | 16 | 24 | 32),
+        3 => matches!(depth, 8 | 16),
+        _ => false,
+    };
+    if !supported || map_type > 1 {
+        return Err(DecodeError::Unsupported(format!(
+            "image type {kind}, {depth} bits per pixel"
+        )));
+    }
+    if width == 0 || height == 0 {
+        return Err(DecodeError::Unsupported(format!("size {width}x{height}")));
+    }
+
+    let mut at = HEADER + id_length;
+    let mut palette = Vec::new();
+    if map_type == 1 {
+        if !matches!(map_depth, 15 | 16 | 24 | 32) {
+            return Err(DecodeError::InvalidPalette);
+        }
+        let size = map_depth.div_ceil(8);
+        let map = bytes
+            .get(at..at + size * map_length)
+            .ok_or(DecodeError::UnexpectedEnd)?;
+        palette = map
+            .chunks_exact(size)
+            .map(|entry| color(entry, alpha_bits))
+            .collect();
+        at += size * map_length;
+    }
+
+    let size = depth.div_ceil(8);
+    let count = width * height;
+    let mut raw;
+    if kind & 8 == 0 {
+        let data = bytes
+            .get(at..at + count * size)
+            .ok_or(DecodeError::UnexpectedEnd)?;
+        raw = data.to_vec();
+    } else {
+        // A packet expands to at most 128 pixels, which bounds what the
+        // input can fill whatever size the header declares.
+        raw = Vec::with_capacity((count * size).min(bytes.len() * 128));
+        // Packets may run across scanlines.
+        while raw.len() < count * size {
+            let packet = *bytes.get(at).ok_or(DecodeError::UnexpectedEnd)?;
+            let run = (packet & 0x7f) as usize + 1;
+            let length = if packet & 0x80 != 0 { size } else { run * size };
+            let data = bytes
+                .get(at + 1..at + 1 + length)
+                .ok_or(DecodeError::UnexpectedEnd)?;
+            if packet & 0x80 != 0 {
+                for _ in 0..run {
+                    raw.extend_from_slice(data);
+                }
+            } else {
+                raw.extend_from_slice(data);
+            }
+            at += 1 + length;
+        }
+        raw.truncate(count * size);
+    }
+
+    let mut pixels = Vec::with_capacity(count);
+    for texel in raw.chunks_exact(size) {
+        let pixel = match kind & !8 {
+            1 => {
+                let index = if size == 2 {
+                    u16::from_le_bytes([texel[0], texel[1]]) as usize
+                } else {
+                    texel[0] as usize
+                };
+                *index
+                    .checked_sub(map_first)
+                    .and_then(|i| palette.get(i))
+                    .ok_or(DecodeError::InvalidPalette)?
+            }
+            3 => {
+                let alpha = if size == 2 { texel[1] } else { 255 };
+                [texel[0], texel[0], texel[0], alpha]
+            }
+            _ => color(texel, alpha_bits),
+        };
+        pixels.push(pixel);
+    }
+
+    // Rows are bottom up and columns left to right unless the descriptor
+    // says otherwise.
+    let right_to_left = descriptor & 0x10 != 0;
+    let top_down = descriptor & 0x20 != 0;
+    return Ok(Image::from_fn(width, height, |x, y| {
+        let column = if right_to_left { width - 1 - x } else { x };
+        let row = if top_down { y } else { height - 1 - y };
+        return pixels[row * width + column];
+    }));
+}
+
+/// A little-endian BGR(A) color of 2, 3 or 4 bytes. The attribute bits
+/// are only taken as alpha if the descriptor says there are some.
+fn color(bytes: &[u8], alpha_bits: u8) -> [u8; 4] {
+    match bytes.len() {
+        2 => {
+            let v = u16::from_le_bytes([bytes[0], bytes[1]]);
+            let five = |shift: u16| (((v >> shift) & 0x1f) as u32 * 255 / 31) as u8;
+            let alpha = if alpha_bits > 0 && v & 0x8000 == 0 {
+                0
+            } else {
+                255
+            };
+            [five(10), five(5), five(0), alpha]
+        }
+        3 => [bytes[2], bytes[1], bytes[0], 255],
+        _ => {
+            let alpha = if alpha_bits > 0 { bytes[3] } else { 255 };
+            [bytes[2], bytes[1], bytes[0], alpha]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(kind: u8, width: u16, height: u16, depth: u8, descriptor: u8) -> Vec<u8> {
+        let mut out = vec![0, 0, kind, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        out.extend_from_slice(&width.to_le_bytes());
+        out.extend_from_slice(&height.to_le_bytes());
+        out.extend_from_slice(&[depth, descriptor]);
+        return out;
+    }
+
+    #[test]
+    fn decode_test() {
+        // 24-bit, bottom up.
+        let mut bytes = header(2, 2, 2, 24, 0);
+        bytes.extend_from_slice(&[3, 2, 1, 6, 5, 4, 9, 8, 7, 12, 11, 10]);
+        let image = decode(&bytes).unwrap();
+        assert_eq!(
+            image.pixels(),
+            [
+                [7, 8, 9, 255],
+                [10, 11, 12, 255],
+                [1, 2, 3, 255],
+                [4, 5, 6, 255],
+            ]
+        );
+
+        // The same pixels top down, as 32-bit with alpha, run-length encoded
+        // with one run crossing the end of a row.
+        let mut bytes = header(10, 2, 2, 32, 0x28);
+        bytes.extend_from_slice(&[0x82, 3, 2, 1, 0]);
+        bytes.extend_from_slice(&[0x00, 6, 5, 4, 128]);
+        let image = decode(&bytes).unwrap();
+        assert_eq!(
+            image.pixels(),
+            [[1, 2, 3, 0], [1, 2, 3, 0], [1, 2, 3, 0], [4, 5, 6, 128]]
+        );
+
+        // 8-bit color map of 16-bit entries, right to left.
+        let mut bytes = header(1, 2, 1, 8, 0x10);
+        bytes[1] = 1;
+        bytes[5] = 2;
+        bytes[7] = 16;
+        bytes.extend_from_slice(&[0x00, 0x7c, 0x1f, 0x00]);
+        bytes.extend_from_slice(&[0, 1]);
+        let image = decode(&bytes).unwrap();
+        assert_eq!(image.pixels(), [[0, 0, 255, 255], [255, 0, 0, 255]]);
+
+        bytes[HEADER + 4] = 2;
+        assert_eq!(decode(&bytes), Err(DecodeError::InvalidPalette));
+        assert_eq!(decode(&bytes[..10]), Err(DecodeError::UnexpectedEnd));
+        assert!(matches!(
+            decode(&header(2, 1, 1, 12, 0)),
+            Err(DecodeError::Unsupported(_))
+        ));
+        assert!(matches!(
+            decode(&header(2, 0, 1, 32, 0)),
+            Err(DecodeError::Unsupported(_))
+        ));
+        assert!(matches!(
+            decode(&header(2, 1, 0, 32, 0)),
+            Err(DecodeError::Unsupported(_))
+        ));
+
+        // A tiny run-length encoded file cannot make the decoder reserve
+        // the 16 GiB its header declares.
+        let mut bytes = header(10, 65535, 65535, 32, 0);
+        bytes.extend_from_slice(&[0xff, 1, 2, 3, 4]);
+        assert_eq!(decode(&bytes), Err(DecodeError::UnexpectedEnd));
+    }
+}
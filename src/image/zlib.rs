@@ -0,0 +1,617 @@
+//! zlib streams (RFC 1950) around DEFLATE (RFC 1951).
+//!
+//! Compression uses LZ77 with hash chains and the fixed Huffman code, which
+//! keeps the encoder small while still shrinking the flat areas common in
+//! rendered images. Decompression handles stored, fixed and dynamic blocks.
+
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InflateError {
+    UnexpectedEnd,
+    InvalidHeader,
+    InvalidBlockType,
+    InvalidStoredLength,
+    InvalidCode,
+    InvalidDistance,
+    ChecksumMismatch,
+    /// The output would exceed the limit given to [`decompress`].
+    TooLarge,
+}
+
+impl Display for InflateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            InflateError::UnexpectedEnd => "unexpected end of compressed data",
+            InflateError::InvalidHeader => "invalid zlib header",
+            InflateError::InvalidBlockType => "invalid deflate block type",
+            InflateError::InvalidStoredLength => "stored block length check failed",
+            InflateError::InvalidCode => "invalid Huffman code",
+            InflateError::InvalidDistance => "distance reaches before the start of the output",
+            InflateError::ChecksumMismatch => "Adler-32 checksum mismatch",
+            InflateError::TooLarge => "decompressed data is larger than expected",
+        };
+
+        f.write_str(text)
+    }
+}
+
+impl Error for InflateError {}
+
+/// Base value and extra bits of length codes 257..=285.
+const LENGTHS: [(u16, u8); 29] = [
+    (3, 0),
+    (4, 0),
+    (5, 0),
+    (6, 0),
+    (7, 0),
+    (8, 0),
+    (9, 0),
+    (10, 0),
+    (11, 1),
+    (13, 1),
+    (15, 1),
+    (17, 1),
+    (19, 2),
+    (23, 2),
+    (27, 2),
+    (31, 2),
+    (35, 3),
+    (43, 3),
+    (51, 3),
+    (59, 3),
+    (67, 4),
+    (83, 4),
+    (99, 4),
+    (115, 4),
+    (131, 5),
+    (163, 5),
+    (195, 5),
+    (227, 5),
+    (258, 0),
+];
+
+/// Base value and extra bits of distance codes 0..=29.
+const DISTANCES: [(u16, u8); 30] = [
+    (1, 0),
+    (2, 0),
+    (3, 0),
+    (4, 0),
+    (5, 1),
+    (7, 1),
+    (9, 2),
+    (13, 2),
+    (17, 3),
+    (25, 3),
+    (33, 4),
+    (49, 4),
+    (65, 5),
+    (97, 5),
+    (129, 6),
+    (193, 6),
+    (257, 7),
+    (385, 7),
+    (513, 8),
+    (769, 8),
+    (1025, 9),
+    (1537, 9),
+    (2049, 10),
+    (3073, 10),
+    (4097, 11),
+    (6145, 11),
+    (8193, 12),
+    (12289, 12),
+    (16385, 13),
+    (24577, 13),
+];
+
+/// Order in which code length code lengths are stored in dynamic blocks.
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+const WINDOW: usize = 32 * 1024;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const MAX_CHAIN: usize = 64;
+const HASH_BITS: u32 = 15;
+
+pub fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+
+    // 5552 is the largest block whose sums cannot overflow before the modulo.
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+
+    return (b << 16) | a;
+}
+
+/// Compresses `data` into a zlib stream.
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    let mut out = BitWriter::default();
+
+    // CMF: deflate with a 32K window; FLG: no dictionary, check bits.
+    out.bytes.extend_from_slice(&[0x78, 0x01]);
+
+    // One final block with the fixed Huffman code.
+    out.bits(1, 1);
+    out.bits(1, 2);
+
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut prev = vec![usize::MAX; data.len()];
+    let mut i = 0;
+
+    while i < data.len() {
+        let (length, distance) = longest_match(data, i, &head, &prev);
+
+        if length >= MIN_MATCH {
+            write_length(&mut out, length);
+            write_distance(&mut out, distance);
+            for k in i..i + length {
+                insert(data, k, &mut head, &mut prev);
+            }
+            i += length;
+        } else {
+            write_literal(&mut out, data[i] as u16);
+            insert(data, i, &mut head, &mut prev);
+            i += 1;
+        }
+    }
+
+    write_literal(&mut out, 256);
+    out.flush();
+    out.bytes.extend_from_slice(&adler32(data).to_be_bytes());
+
+    return out.bytes;
+}
+
+fn hash(data: &[u8], i: usize) -> usize {
+    let v = (data[i] as u32) << 16 | (data[i + 1] as u32) << 8 | data[i + 2] as u32;
+
+    return (v.wrapping_mul(2_654_435_761) >> (32 - HASH_BITS)) as usize;
+}
+
+fn insert(data: &[u8], i: usize, head: &mut [usize], prev: &mut [usize]) {
+    if i + MIN_MATCH <= data.len() {
+        let h = hash(data, i);
+        prev[i] = head[h];
+        head[h] = i;
+    }
+}
+
+fn longest_match(data: &[u8], i: usize, head: &[usize], prev: &[usize]) -> (usize, usize) {
+    if i + MIN_MATCH > data.len() {
+        return (0, 0);
+    }
+
+    let limit = (data.len() - i).min(MAX_MATCH);
+    let (mut best, mut distance) = (0, 0);
+    let mut candidate = head[hash(data, i)];
+    let mut chain = 0;
+
+    while candidate != usize::MAX && i - candidate <= WINDOW && chain < MAX_CHAIN {
+        let mut length = 0;
+        while length < limit && data[candidate + length] == data[i + length] {
+            length += 1;
+        }
+        if length > best {
+            best = length;
+            distance = i - candidate;
+            if length == limit {
+                break;
+            }
+        }
+
+        candidate = prev[candidate];
+        chain += 1;
+    }
+
+    return (best, distance);
+}
+
+fn write_literal(out: &mut BitWriter, symbol: u16) {
+    // The fixed literal/length code of RFC 1951 section 3.2.6.
+    let (code, length) = match symbol {
+        0..=143 => (0x30 + symbol, 8),
+        144..=255 => (0x190 + symbol - 144, 9),
+        256..=279 => (symbol - 256, 7),
+        _ => (0xc0 + symbol - 280, 8),
+    };
+
+    out.huffman(code, length);
+}
+
+fn write_length(out: &mut BitWriter, length: usize) {
+    let index = LENGTHS
+        .iter()
+        .rposition(|&(base, _)| base as usize <= length)
+        .unwrap();
+    let (base, extra) = LENGTHS[index];
+
+    write_literal(out, 257 + index as u16);
+    out.bits(length as u32 - base as u32, extra);
+}
+
+fn write_distance(out: &mut BitWriter, distance: usize) {
+    let index = DISTANCES
+        .iter()
+        .rposition(|&(base, _)| base as usize <= distance)
+        .unwrap();
+    let (base, extra) = DISTANCES[index];
+
+    out.huffman(index as u16, 5);
+    out.bits(distance as u32 - base as u32, extra);
+}
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    count: u8,
+}
+
+impl BitWriter {
+    /// Writes `count` bits of `value`, least significant bit first.
+    fn bits(&mut self, value: u32, count: u8) {
+        for k in 0..count {
+            self.buffer |= ((value >> k) & 1) << self.count;
+            self.count += 1;
+            if self.count == 8 {
+                self.bytes.push(self.buffer as u8);
+                self.buffer = 0;
+                self.count = 0;
+            }
+        }
+    }
+
+    /// Huffman codes are packed starting from their most significant bit.
+    fn huffman(&mut self, code: u16, length: u8) {
+        for k in (0..length).rev() {
+            self.bits(((code >> k) & 1) as u32, 1);
+        }
+    }
+
+    fn flush(&mut self) {
+        if self.count > 0 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer = 0;
+            self.count = 0;
+        }
+    }
+}
+
+/// Decompresses a zlib stream and verifies its checksum. Fails as soon as
+/// the output would grow past `limit` bytes.
+pub fn decompress(data: &[u8], limit: usize) -> Result<Vec<u8>, InflateError> {
+    if data.len() < 6 {
+        return Err(InflateError::UnexpectedEnd);
+    }
+
+    let (cmf, flg) = (data[0], data[1]);
+    if cmf & 0x0f != 8 || !(cmf as u16 * 256 + flg as u16).is_multiple_of(31) || flg & 0x20 != 0 {
+        return Err(InflateError::InvalidHeader);
+    }
+
+    let mut reader = BitReader {
+        data: &data[2..],
+        position: 0,
+    };
+    let output = inflate(&mut reader, limit)?;
+
+    let end = 2 + reader.position.div_ceil(8);
+    let checksum = data.get(end..end + 4).ok_or(InflateError::UnexpectedEnd)?;
+    if u32::from_be_bytes(checksum.try_into().unwrap()) != adler32(&output) {
+        return Err(InflateError::ChecksumMismatch);
+    }
+
+    return Ok(output);
+}
+
+/// Decodes raw DEFLATE blocks up to and including the final one.
+fn inflate(reader: &mut BitReader, limit: usize) -> Result<Vec<u8>, InflateError> {
+    let mut out = Vec::new();
+
+    loop {
+        let last = reader.bits(1)? == 1;
+
+        match reader.bits(2)? {
+            0 => {
+                reader.align();
+                let length = reader.bits(16)?;
+                let complement = reader.bits(16)?;
+                if length != !complement & 0xffff {
+                    return Err(InflateError::InvalidStoredLength);
+                }
+                if out.len() + length as usize > limit {
+                    return Err(InflateError::TooLarge);
+                }
+                for _ in 0..length {
+                    out.push(reader.bits(8)? as u8);
+                }
+            }
+            1 => {
+                let (literals, distances) = fixed_codes();
+                inflate_block(reader, &mut out, limit, &literals, &distances)?;
+            }
+            2 => {
+                let (literals, distances) = dynamic_codes(reader)?;
+                inflate_block(reader, &mut out, limit, &literals, &distances)?;
+            }
+            _ => return Err(InflateError::InvalidBlockType),
+        }
+
+        if last {
+            return Ok(out);
+        }
+    }
+}
+
+fn inflate_block(
+    reader: &mut BitReader,
+    out: &mut Vec<u8>,
+    limit: usize,
+    literals: &Huffman,
+    distances: &Huffman,
+) -> Result<(), InflateError> {
+    loop {
+        let symbol = literals.decode(reader)?;
+
+        match symbol {
+            0..=255 if out.len() == limit => return Err(InflateError::TooLarge),
+            0..=255 => out.push(symbol as u8),
+            256 => return Ok(()),
+            257..=285 => {
+                let (base, extra) = LENGTHS[symbol as usize - 257];
+                let length = base as usize + reader.bits(extra)? as usize;
+
+                let code = distances.decode(reader)? as usize;
+                let &(base, extra) = DISTANCES.get(code).ok_or(InflateError::InvalidCode)?;
+                let distance = base as usize + reader.bits(extra)? as usize;
+                if distance > out.len() {
+                    return Err(InflateError::InvalidDistance);
+                }
+                if out.len() + length > limit {
+                    return Err(InflateError::TooLarge);
+                }
+
+                // Copies may overlap their own output, so go byte by byte.
+                let start = out.len() - distance;
+                for k in 0..length {
+                    out.push(out[start + k]);
+                }
+            }
+            _ => return Err(InflateError::InvalidCode),
+        }
+    }
+}
+
+fn fixed_codes() -> (Huffman, Huffman) {
+    let mut lengths = [0u8; 288];
+    for (symbol, length) in lengths.iter_mut().enumerate() {
+        *length = match symbol {
+            0..=143 => 8,
+            144..=255 => 9,
+            256..=279 => 7,
+            _ => 8,
+        };
+    }
+
+    return (Huffman::new(&lengths), Huffman::new(&[5; 30]));
+}
+
+fn dynamic_codes(reader: &mut BitReader) -> Result<(Huffman, Huffman), InflateError> {
+    let literal_count = reader.bits(5)? as usize + 257;
+    let distance_count = reader.bits(5)? as usize + 1;
+    let code_length_count = reader.bits(4)? as usize + 4;
+
+    let mut code_lengths = [0u8; 19];
+    for &i in &CODE_LENGTH_ORDER[..code_length_count] {
+        code_lengths[i] = reader.bits(3)? as u8;
+    }
+    let code_length_code = Huffman::new(&code_lengths);
+
+    let mut lengths = Vec::with_capacity(literal_count + distance_count);
+    while lengths.len() < literal_count + distance_count {
+        let symbol = code_length_code.decode(reader)?;
+        let (value, repeat) = match symbol {
+            0..=15 => (symbol as u8, 1),
+            16 => {
+                let &previous = lengths.last().ok_or(InflateError::InvalidCode)?;
+                (previous, 3 + reader.bits(2)?)
+            }
+            17 => (0, 3 + reader.bits(3)?),
+            18 => (0, 11 + reader.bits(7)?),
+            _ => return Err(InflateError::InvalidCode),
+        };
+        for _ in 0..repeat {
+            lengths.push(value);
+        }
+    }
+    if lengths.len() != literal_count + distance_count {
+        return Err(InflateError::InvalidCode);
+    }
+
+    return Ok((
+        Huffman::new(&lengths[..literal_count]),
+        Huffman::new(&lengths[literal_count..]),
+    ));
+}
+
+/// Canonical Huffman decoder: symbols sorted by code length, plus how many
+/// codes there are of each length.
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0u16; 16];
+        for &length in lengths {
+            counts[length as usize] += 1;
+        }
+        counts[0] = 0;
+
+        let mut symbols = Vec::with_capacity(lengths.len());
+        for length in 1..16 {
+            for (symbol, &l) in lengths.iter().enumerate() {
+                if l as usize == length {
+                    symbols.push(symbol as u16);
+                }
+            }
+        }
+
+        Self { counts, symbols }
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<u16, InflateError> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+
+        for length in 1..16 {
+            code |= reader.bits(1)? as i32;
+            let count = self.counts[length] as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+
+        return Err(InflateError::InvalidCode);
+    }
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    /// Position in bits.
+    position: usize,
+}
+
+impl BitReader<'_> {
+    fn bits(&mut self, count: u8) -> Result<u32, InflateError> {
+        let mut value = 0;
+
+        for k in 0..count {
+            let byte = *self
+                .data
+                .get(self.position / 8)
+                .ok_or(InflateError::UnexpectedEnd)?;
+            value |= (((byte >> (self.position % 8)) & 1) as u32) << k;
+            self.position += 1;
+        }
+
+        return Ok(value);
+    }
+
+    fn align(&mut self) {
+        self.position = self.position.div_ceil(8) * 8;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adler32_test() {
+        assert_eq!(adler32(b""), 1);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let inputs: [&[u8]; 4] = [
+            b"",
+            b"a",
+            b"abcabcabcabcabcabcabcabcabcabc hello hello hello",
+            &[7u8; 100_000],
+        ];
+
+        for input in inputs {
+            let compressed = compress(input);
+            assert_eq!(decompress(&compressed, usize::MAX).unwrap(), input);
+        }
+    }
+
+    #[test]
+    fn compresses_repetition_test() {
+        let input = vec![42u8; 100_000];
+
+        assert!(compress(&input).len() < 1_000);
+    }
+
+    #[test]
+    fn round_trip_random_test() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(33);
+        // Few distinct bytes so there are plenty of matches at all distances.
+        let input: Vec<u8> = (0..200_000).map(|_| rng.gen_range(0..4)).collect();
+
+        assert_eq!(decompress(&compress(&input), usize::MAX).unwrap(), input);
+    }
+
+    #[test]
+    fn decompress_stored_and_dynamic_test() {
+        // "hello" as a stored block, as written by `zlib.compress(b"hello", 0)`.
+        let stored = [
+            0x78, 0x01, 0x01, 0x05, 0x00, 0xfa, 0xff, b'h', b'e', b'l', b'l', b'o', 0x06, 0x2c,
+            0x02, 0x15,
+        ];
+        assert_eq!(decompress(&stored, usize::MAX).unwrap(), b"hello");
+
+        // A skewed random string compressed by zlib at level 9, which picks
+        // a dynamic Huffman block.
+        let expected = "abcccaaaacaabacaaaadcaabccabaabcabadaaaabbadabaababacaabaaabacaad\
+                        aacdbdbaabbcaabadbbbdabcdbaaabdacba";
+        let dynamic = [
+            0x78, 0xda, 0x2d, 0x8a, 0xc1, 0x11, 0xc0, 0x30, 0x0c, 0xc2, 0x66, 0x45, 0xb0, 0xff,
+            0x0c, 0x05, 0xa7, 0x7e, 0x60, 0x4e, 0x48, 0xd8, 0x56, 0xaf, 0x81, 0xae, 0x65, 0xb5,
+            0x90, 0xbd, 0x66, 0x06, 0xd9, 0x1f, 0x79, 0x16, 0xfa, 0xf5, 0x8e, 0x0e, 0xd9, 0xc2,
+            0xf1, 0x00, 0x35, 0x9d, 0x53, 0x22, 0xa3, 0x0f, 0x89, 0xbc, 0x26, 0x3a,
+        ];
+        assert_eq!(
+            decompress(&dynamic, usize::MAX),
+            Ok(expected.as_bytes().to_vec())
+        );
+    }
+
+    #[test]
+    fn decompress_errors_test() {
+        assert_eq!(
+            decompress(&[0x78], usize::MAX),
+            Err(InflateError::UnexpectedEnd)
+        );
+        assert_eq!(
+            decompress(&[0x78, 0x02, 0, 0, 0, 0], usize::MAX),
+            Err(InflateError::InvalidHeader)
+        );
+
+        let mut corrupt = compress(b"some text to checksum");
+        let last = corrupt.len() - 1;
+        corrupt[last] ^= 1;
+        assert_eq!(
+            decompress(&corrupt, usize::MAX),
+            Err(InflateError::ChecksumMismatch)
+        );
+        // Output is capped, including long back-references and stored blocks.
+        let zeros = compress(&[0; 100_000]);
+        assert_eq!(decompress(&zeros, 100_000).unwrap().len(), 100_000);
+        assert_eq!(decompress(&zeros, 99_999), Err(InflateError::TooLarge));
+        let stored = [
+            0x78, 0x01, 0x01, 0x05, 0x00, 0xfa, 0xff, b'h', b'e', b'l', b'l', b'o', 0x06, 0x2c,
+            0x02, 0x15,
+        ];
+        assert_eq!(decompress(&stored, 4), Err(InflateError::TooLarge));
+    }
+}
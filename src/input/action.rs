@@ -0,0 +1,383 @@
+use crate::math::Vector2D;
+
+use super::{GamepadAxis, GamepadButton, Input, Key, MouseButton};
+
+/// Values at least this large count as held.
+pub const PRESS_THRESHOLD: f32 = 0.5;
+
+/// An input an action responds to. Every binding yields a value in
+/// `[-1, 1]`: buttons 0 or 1, axes their position past the dead zone.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Binding {
+    Key(Key),
+    Mouse(MouseButton),
+    /// The button on any gamepad.
+    Gamepad(GamepadButton),
+    /// The axis on any gamepad, rescaled to start at 0 at the edge of the
+    /// dead zone and multiplied by `scale`; -1 inverts it.
+    Axis {
+        axis: GamepadAxis,
+        dead_zone: f32,
+        scale: f32,
+    },
+    /// -1 for `negative`, 1 for `positive`, 0 for both or neither.
+    Keys {
+        negative: Key,
+        positive: Key,
+    },
+}
+
+impl Binding {
+    pub fn axis(axis: GamepadAxis, dead_zone: f32) -> Self {
+        Binding::Axis {
+            axis,
+            dead_zone,
+            scale: 1.0,
+        }
+    }
+
+    pub fn value(&self, input: &Input) -> f32 {
+        let button = |held: bool| if held { 1.0 } else { 0.0 };
+
+        match *self {
+            Binding::Key(key) => button(input.keys.held(key)),
+            Binding::Mouse(b) => button(input.mouse_buttons.held(b)),
+            Binding::Gamepad(b) => button(input.gamepads().iter().any(|pad| pad.buttons.held(b))),
+            Binding::Axis {
+                axis,
+                dead_zone,
+                scale,
+            } => {
+                let value = input
+                    .gamepads()
+                    .iter()
+                    .map(|pad| apply_dead_zone(pad.axis(axis), dead_zone))
+                    .fold(0.0, |a: f32, b| if b.abs() > a.abs() { b } else { a });
+                (value * scale).clamp(-1.0, 1.0)
+            }
+            Binding::Keys { negative, positive } => {
+                button(input.keys.held(positive)) - button(input.keys.held(negative))
+            }
+        }
+    }
+
+    /// Whether a button of this binding went down this frame.
+    fn pressed(&self, input: &Input) -> bool {
+        match *self {
+            Binding::Key(key) => input.keys.pressed(key),
+            Binding::Mouse(b) => input.mouse_buttons.pressed(b),
+            Binding::Gamepad(b) => input.gamepads().iter().any(|pad| pad.buttons.pressed(b)),
+            Binding::Axis { .. } => false,
+            Binding::Keys { negative, positive } => {
+                input.keys.pressed(negative) || input.keys.pressed(positive)
+            }
+        }
+    }
+}
+
+/// Zero inside `dead_zone`, rescaled so the output still reaches ±1.
+pub fn apply_dead_zone(value: f32, dead_zone: f32) -> f32 {
+    let magnitude = value.abs();
+    if magnitude <= dead_zone {
+        return 0.0;
+    }
+
+    return value.signum() * ((magnitude - dead_zone) / (1.0 - dead_zone)).min(1.0);
+}
+
+/// [`apply_dead_zone`] on the length of a stick position, which keeps
+/// diagonals from snapping to the axes.
+pub fn radial_dead_zone(stick: Vector2D, dead_zone: f32) -> Vector2D {
+    let magnitude = stick.magnitude();
+    if magnitude <= dead_zone {
+        return Vector2D::default();
+    }
+
+    return stick * (apply_dead_zone(magnitude, dead_zone) / magnitude);
+}
+
+/// An action's state for the current frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ActionState {
+    /// The bound value of largest magnitude.
+    pub value: f32,
+    pub held: bool,
+    pub pressed: bool,
+    pub released: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Action {
+    name: String,
+    bindings: Vec<Binding>,
+    state: ActionState,
+}
+
+/// Named actions, each bound to any number of inputs, so gameplay code
+/// asks for "jump" rather than the space bar and players can rebind.
+#[derive(Debug, Clone, Default)]
+pub struct ActionMap {
+    actions: Vec<Action>,
+}
+
+impl ActionMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a binding, creating the action if needed.
+    pub fn bind(&mut self, action: &str, binding: Binding) -> &mut Self {
+        match self.find_mut(action) {
+            Some(a) => a.bindings.push(binding),
+            None => self.actions.push(Action {
+                name: action.to_string(),
+                bindings: vec![binding],
+                state: ActionState::default(),
+            }),
+        }
+
+        return self;
+    }
+
+    pub fn unbind(&mut self, action: &str, binding: &Binding) -> bool {
+        let Some(action) = self.find_mut(action) else {
+            return false;
+        };
+        let before = action.bindings.len();
+        action.bindings.retain(|b| b != binding);
+
+        return action.bindings.len() != before;
+    }
+
+    /// Replaces `old` with `new` in place; `false` if the action lacks
+    /// `old`.
+    pub fn rebind(&mut self, action: &str, old: &Binding, new: Binding) -> bool {
+        let binding = self
+            .find_mut(action)
+            .and_then(|a| a.bindings.iter_mut().find(|b| *b == old));
+        match binding {
+            Some(binding) => {
+                *binding = new;
+                return true;
+            }
+            None => return false,
+        }
+    }
+
+    pub fn bindings(&self, action: &str) -> &[Binding] {
+        self.find(action).map_or(&[], |a| &a.bindings)
+    }
+
+    /// Actions that already use `binding`, to warn about when rebinding.
+    pub fn actions_bound_to(&self, binding: &Binding) -> Vec<&str> {
+        self.actions
+            .iter()
+            .filter(|a| a.bindings.contains(binding))
+            .map(|a| a.name.as_str())
+            .collect()
+    }
+
+    /// The first button pressed this frame or axis pushed past
+    /// [`PRESS_THRESHOLD`], for "press a key" rebinding prompts.
+    pub fn capture(input: &Input) -> Option<Binding> {
+        if let Some(&key) = Key::ALL.iter().find(|&&k| input.keys.pressed(k)) {
+            return Some(Binding::Key(key));
+        }
+        if let Some(&b) = MouseButton::ALL
+            .iter()
+            .find(|&&b| input.mouse_buttons.pressed(b))
+        {
+            return Some(Binding::Mouse(b));
+        }
+
+        for pad in input.gamepads() {
+            if let Some(&b) = GamepadButton::ALL.iter().find(|&&b| pad.buttons.pressed(b)) {
+                return Some(Binding::Gamepad(b));
+            }
+            for &axis in GamepadAxis::ALL {
+                let value = pad.axis(axis);
+                if value.abs() >= PRESS_THRESHOLD {
+                    return Some(Binding::Axis {
+                        axis,
+                        dead_zone: 0.0,
+                        scale: value.signum(),
+                    });
+                }
+            }
+        }
+
+        return None;
+    }
+
+    /// Recomputes every action from this frame's input.
+    pub fn update(&mut self, input: &Input) {
+        for action in &mut self.actions {
+            let value = action
+                .bindings
+                .iter()
+                .map(|b| b.value(input))
+                .fold(0.0, |a: f32, b| if b.abs() > a.abs() { b } else { a });
+            let held = value.abs() >= PRESS_THRESHOLD;
+            let was_held = action.state.held;
+            // Catch a tap that went down and up within the frame.
+            let pressed = !was_held && (held || action.bindings.iter().any(|b| b.pressed(input)));
+
+            action.state = ActionState {
+                value,
+                held,
+                pressed,
+                released: !held && (was_held || pressed),
+            };
+        }
+    }
+
+    /// The state as of the last [`update`](Self::update); default for an
+    /// unknown action.
+    pub fn state(&self, action: &str) -> ActionState {
+        self.find(action)
+            .map_or_else(ActionState::default, |a| a.state)
+    }
+
+    pub fn held(&self, action: &str) -> bool {
+        self.state(action).held
+    }
+
+    pub fn pressed(&self, action: &str) -> bool {
+        self.state(action).pressed
+    }
+
+    pub fn released(&self, action: &str) -> bool {
+        self.state(action).released
+    }
+
+    pub fn value(&self, action: &str) -> f32 {
+        self.state(action).value
+    }
+
+    /// Two axis actions as a vector no longer than 1.
+    pub fn vector(&self, x: &str, y: &str) -> Vector2D {
+        let v = Vector2D::create(self.value(x), self.value(y));
+        let magnitude = v.magnitude();
+        if magnitude > 1.0 {
+            return v / magnitude;
+        }
+
+        return v;
+    }
+
+    fn find(&self, action: &str) -> Option<&Action> {
+        self.actions.iter().find(|a| a.name == action)
+    }
+
+    fn find_mut(&mut self, action: &str) -> Option<&mut Action> {
+        self.actions.iter_mut().find(|a| a.name == action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::Event;
+
+    fn map() -> ActionMap {
+        let mut map = ActionMap::new();
+        map.bind("jump", Binding::Key(Key::Space))
+            .bind("jump", Binding::Gamepad(GamepadButton::South))
+            .bind(
+                "move_x",
+                Binding::Keys {
+                    negative: Key::A,
+                    positive: Key::D,
+                },
+            )
+            .bind("move_x", Binding::axis(GamepadAxis::LeftX, 0.2))
+            .bind("move_y", Binding::axis(GamepadAxis::LeftY, 0.2));
+
+        return map;
+    }
+
+    #[test]
+    fn button_action_test() {
+        let (mut map, mut input) = (map(), Input::new());
+
+        input.apply(&Event::GamepadDown(0, GamepadButton::South));
+        map.update(&input);
+        assert!(map.pressed("jump") && map.held("jump"));
+
+        input.begin_frame();
+        input.apply(&Event::KeyDown(Key::Space));
+        map.update(&input);
+        assert!(!map.pressed("jump") && map.held("jump"));
+
+        input.begin_frame();
+        input.apply(&Event::KeyUp(Key::Space));
+        input.apply(&Event::GamepadUp(0, GamepadButton::South));
+        map.update(&input);
+        assert!(map.released("jump") && !map.held("jump"));
+
+        // A tap inside one frame is both pressed and released.
+        input.begin_frame();
+        input.apply(&Event::KeyDown(Key::Space));
+        input.apply(&Event::KeyUp(Key::Space));
+        map.update(&input);
+        assert!(map.pressed("jump") && map.released("jump"));
+
+        assert_eq!(map.state("missing"), ActionState::default());
+    }
+
+    #[test]
+    fn axis_action_test() {
+        let (mut map, mut input) = (map(), Input::new());
+
+        input.apply(&Event::KeyDown(Key::A));
+        map.update(&input);
+        assert_eq!(map.value("move_x"), -1.0);
+
+        input.apply(&Event::KeyUp(Key::A));
+        input.apply(&Event::GamepadAxis(0, GamepadAxis::LeftX, 0.1));
+        map.update(&input);
+        assert_eq!(map.value("move_x"), 0.0);
+
+        input.apply(&Event::GamepadAxis(0, GamepadAxis::LeftX, 0.6));
+        input.apply(&Event::GamepadAxis(0, GamepadAxis::LeftY, 1.0));
+        map.update(&input);
+        assert!((map.value("move_x") - 0.5).abs() < 1e-6);
+        assert!((map.vector("move_x", "move_y").magnitude() - 1.0).abs() < 1e-6);
+
+        assert!((apply_dead_zone(-0.6, 0.2) + 0.5).abs() < 1e-6);
+        let stick = radial_dead_zone(Vector2D::create(0.15, 0.15), 0.2);
+        assert!(stick.magnitude() > 0.0 && stick.x() == stick.y());
+    }
+
+    #[test]
+    fn rebind_test() {
+        let mut map = map();
+        let mut input = Input::new();
+        input.apply(&Event::KeyDown(Key::E));
+        let captured = ActionMap::capture(&input).unwrap();
+        assert_eq!(captured, Binding::Key(Key::E));
+
+        assert!(map.rebind("jump", &Binding::Key(Key::Space), captured));
+        assert!(!map.rebind("jump", &Binding::Key(Key::Space), captured));
+        assert_eq!(map.actions_bound_to(&captured), ["jump"]);
+        map.update(&input);
+        assert!(map.pressed("jump"));
+
+        assert!(map.unbind("jump", &captured));
+        assert_eq!(
+            map.bindings("jump"),
+            [Binding::Gamepad(GamepadButton::South)]
+        );
+
+        input.begin_frame();
+        input.apply(&Event::GamepadAxis(0, GamepadAxis::RightY, -0.9));
+        assert_eq!(
+            ActionMap::capture(&input),
+            Some(Binding::Axis {
+                axis: GamepadAxis::RightY,
+                dead_zone: 0.0,
+                scale: -1.0
+            })
+        );
+    }
+}
@@ -0,0 +1,80 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+/// Defines a field-less enum whose variants print and parse as their names,
+/// with `ALL` listing them.
+macro_rules! named {
+    ($(#[$meta:meta])* $name:ident { $($variant:ident),* $(,)? }) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub enum $name {
+            $($variant),*
+        }
+
+        impl $name {
+            pub const ALL: &'static [$name] = &[$($name::$variant),*];
+
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $($name::$variant => stringify!($variant)),*
+                }
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                f.write_str(self.name())
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                $name::ALL
+                    .iter()
+                    .find(|v| v.name() == s)
+                    .copied()
+                    .ok_or_else(|| format!("unknown {} {s:?}", stringify!($name)))
+            }
+        }
+    };
+}
+
+named!(
+    /// A keyboard key, by position on a US layout.
+    Key {
+        A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+        Digit0, Digit1, Digit2, Digit3, Digit4, Digit5, Digit6, Digit7, Digit8, Digit9,
+        F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
+        Up, Down, Left, Right,
+        Space, Enter, Escape, Tab, Backspace,
+        LeftShift, RightShift, LeftControl, RightControl, LeftAlt, RightAlt,
+    }
+);
+
+named!(MouseButton {
+    Left,
+    Right,
+    Middle,
+    Back,
+    Forward
+});
+
+named!(
+    /// Face buttons are named by position, like the axes of a standard
+    /// controller layout.
+    GamepadButton {
+        South, East, West, North,
+        LeftShoulder, RightShoulder, LeftStick, RightStick,
+        Start, Select,
+        DPadUp, DPadDown, DPadLeft, DPadRight,
+    }
+);
+
+named!(
+    /// Sticks range over `[-1, 1]` with +y up; triggers over `[0, 1]`.
+    GamepadAxis { LeftX, LeftY, RightX, RightY, LeftTrigger, RightTrigger }
+);
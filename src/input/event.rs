@@ -0,0 +1,94 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+use crate::math::Vector2D;
+
+use super::{GamepadAxis, GamepadButton, Key, MouseButton};
+
+/// Something a window or input device reported.
+///
+/// Events print as one line of text, e.g. `key_down W` or
+/// `mouse_move 10 20`, and parse back from it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event {
+    KeyDown(Key),
+    KeyUp(Key),
+    /// Cursor position in window pixels, from the top-left corner.
+    MouseMove(Vector2D),
+    MouseDown(MouseButton),
+    MouseUp(MouseButton),
+    /// Scroll in lines; positive is away from the user.
+    Wheel(f32),
+    GamepadDown(usize, GamepadButton),
+    GamepadUp(usize, GamepadButton),
+    GamepadAxis(usize, GamepadAxis, f32),
+    Resize(u32, u32),
+    Focus(bool),
+    CloseRequested,
+}
+
+/// A source of events, polled once per frame.
+pub trait Backend {
+    /// Appends the events since the last poll.
+    fn poll(&mut self, events: &mut Vec<Event>);
+}
+
+impl Display for Event {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::KeyDown(key) => write!(f, "key_down {key}"),
+            Event::KeyUp(key) => write!(f, "key_up {key}"),
+            Event::MouseMove(p) => write!(f, "mouse_move {} {}", p.x(), p.y()),
+            Event::MouseDown(button) => write!(f, "mouse_down {button}"),
+            Event::MouseUp(button) => write!(f, "mouse_up {button}"),
+            Event::Wheel(lines) => write!(f, "wheel {lines}"),
+            Event::GamepadDown(pad, button) => write!(f, "pad_down {pad} {button}"),
+            Event::GamepadUp(pad, button) => write!(f, "pad_up {pad} {button}"),
+            Event::GamepadAxis(pad, axis, value) => write!(f, "pad_axis {pad} {axis} {value}"),
+            Event::Resize(width, height) => write!(f, "resize {width} {height}"),
+            Event::Focus(focused) => write!(f, "focus {focused}"),
+            Event::CloseRequested => write!(f, "close"),
+        }
+    }
+}
+
+impl FromStr for Event {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = s.split_whitespace();
+        let kind = words.next().ok_or("empty event")?;
+        let mut arg = || {
+            words
+                .next()
+                .ok_or_else(|| format!("missing argument to {kind}"))
+        };
+        fn number<T: FromStr>(word: &str) -> Result<T, String> {
+            word.parse().map_err(|_| format!("invalid number {word:?}"))
+        }
+
+        let event = match kind {
+            "key_down" => Event::KeyDown(arg()?.parse()?),
+            "key_up" => Event::KeyUp(arg()?.parse()?),
+            "mouse_move" => Event::MouseMove(Vector2D::create(number(arg()?)?, number(arg()?)?)),
+            "mouse_down" => Event::MouseDown(arg()?.parse()?),
+            "mouse_up" => Event::MouseUp(arg()?.parse()?),
+            "wheel" => Event::Wheel(number(arg()?)?),
+            "pad_down" => Event::GamepadDown(number(arg()?)?, arg()?.parse()?),
+            "pad_up" => Event::GamepadUp(number(arg()?)?, arg()?.parse()?),
+            "pad_axis" => Event::GamepadAxis(number(arg()?)?, arg()?.parse()?, number(arg()?)?),
+            "resize" => Event::Resize(number(arg()?)?, number(arg()?)?),
+            "focus" => Event::Focus(arg()?.parse().map_err(|_| "expected true or false")?),
+            "close" => Event::CloseRequested,
+            _ => return Err(format!("unknown event {kind:?}")),
+        };
+
+        if let Some(extra) = words.next() {
+            return Err(format!("unexpected {extra:?} after {kind}"));
+        }
+
+        return Ok(event);
+    }
+}
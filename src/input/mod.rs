@@ -0,0 +1,24 @@
+//! Keyboard, mouse, gamepad and window input.
+//!
+//! A [`Backend`] reports [`Event`]s; [`Input`] turns them into per-frame
+//! state with held, pressed and released buttons. [`ActionMap`] binds named
+//! actions to inputs, with dead zones for analog axes. There is no
+//! windowing backend yet: [`ScriptedBackend`] replays a [`Recording`],
+//! which [`Recorder`] captures from another backend, so gameplay can be
+//! tested headless.
+
+#![allow(clippy::needless_return)]
+
+pub mod action;
+pub mod device;
+pub mod event;
+pub mod script;
+pub mod state;
+
+pub use action::{
+    apply_dead_zone, radial_dead_zone, ActionMap, ActionState, Binding, PRESS_THRESHOLD,
+};
+pub use device::{GamepadAxis, GamepadButton, Key, MouseButton};
+pub use event::{Backend, Event};
+pub use script::{ParseScriptError, Recorder, Recording, ScriptedBackend};
+pub use state::{Buttons, Gamepad, Input, WindowState};
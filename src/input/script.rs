@@ -0,0 +1,280 @@
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+use super::{Backend, Event};
+
+/// Events grouped by the frame they arrived in.
+///
+/// As text, each line is a frame number followed by an event, e.g.
+/// `12 key_down Space`; a frame number alone marks an empty frame, and
+/// `#` starts a comment. Frame numbers must not decrease.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Recording {
+    frames: Vec<Vec<Event>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseScriptError {
+    /// 1-based.
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for ParseScriptError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for ParseScriptError {}
+
+impl Recording {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn frames(&self) -> &[Vec<Event>] {
+        &self.frames
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn push_frame(&mut self, events: Vec<Event>) {
+        self.frames.push(events);
+    }
+
+    /// Adds an event to `frame`, extending the recording with empty frames
+    /// as needed.
+    pub fn push(&mut self, frame: usize, event: Event) {
+        if self.frames.len() <= frame {
+            self.frames.resize_with(frame + 1, Vec::new);
+        }
+        self.frames[frame].push(event);
+    }
+}
+
+impl Display for Recording {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (frame, events) in self.frames.iter().enumerate() {
+            for event in events {
+                writeln!(f, "{frame} {event}")?;
+            }
+        }
+
+        // Keep trailing empty frames.
+        if self.frames.last().is_some_and(|events| events.is_empty()) {
+            writeln!(f, "{}", self.frames.len() - 1)?;
+        }
+
+        return Ok(());
+    }
+}
+
+impl FromStr for Recording {
+    type Err = ParseScriptError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut recording = Recording::new();
+
+        for (i, line) in s.lines().enumerate() {
+            let error = |message: String| ParseScriptError {
+                line: i + 1,
+                message,
+            };
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (frame, event) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let frame: usize = frame
+                .parse()
+                .map_err(|_| error(format!("invalid frame number {frame:?}")))?;
+            if frame + 1 < recording.len() {
+                return Err(error(format!(
+                    "frame {frame} comes after frame {}",
+                    recording.len() - 1
+                )));
+            }
+
+            if event.trim().is_empty() {
+                if recording.len() <= frame {
+                    recording.frames.resize_with(frame + 1, Vec::new);
+                }
+            } else {
+                recording.push(frame, event.parse().map_err(error)?);
+            }
+        }
+
+        return Ok(recording);
+    }
+}
+
+/// Replays a [`Recording`], one frame per poll, then reports nothing.
+#[derive(Debug, Clone)]
+pub struct ScriptedBackend {
+    recording: Recording,
+    frame: usize,
+}
+
+impl ScriptedBackend {
+    pub fn new(recording: Recording) -> Self {
+        Self {
+            recording,
+            frame: 0,
+        }
+    }
+
+    /// Frames replayed so far.
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.frame >= self.recording.len()
+    }
+}
+
+impl Backend for ScriptedBackend {
+    fn poll(&mut self, events: &mut Vec<Event>) {
+        if let Some(frame) = self.recording.frames.get(self.frame) {
+            events.extend_from_slice(frame);
+        }
+        self.frame += 1;
+    }
+}
+
+/// Passes events through from another backend and records them.
+#[derive(Debug, Clone)]
+pub struct Recorder<B> {
+    inner: B,
+    recording: Recording,
+}
+
+impl<B: Backend> Recorder<B> {
+    pub fn new(inner: B) -> Self {
+        Self {
+            inner,
+            recording: Recording::new(),
+        }
+    }
+
+    pub fn recording(&self) -> &Recording {
+        &self.recording
+    }
+
+    pub fn into_recording(self) -> Recording {
+        self.recording
+    }
+}
+
+impl<B: Backend> Backend for Recorder<B> {
+    fn poll(&mut self, events: &mut Vec<Event>) {
+        let start = events.len();
+        self.inner.poll(events);
+        self.recording.push_frame(events[start..].to_vec());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        input::{ActionMap, Binding, GamepadAxis, Input, Key},
+        math::Vector2D,
+    };
+
+    const SCRIPT: &str = "
+        # walk right, jump, then stop
+        0 key_down D
+        0 mouse_move 320 240
+        2 key_down Space
+        3 key_up Space
+        3 pad_axis 0 LeftX -0.75
+        5 key_up D
+        7
+    ";
+
+    #[test]
+    fn parse_test() {
+        let recording: Recording = SCRIPT.parse().unwrap();
+        assert_eq!(recording.len(), 8);
+        assert_eq!(
+            recording.frames()[0][1],
+            Event::MouseMove(Vector2D::create(320.0, 240.0))
+        );
+        assert_eq!(
+            recording.frames()[3][1],
+            Event::GamepadAxis(0, GamepadAxis::LeftX, -0.75)
+        );
+        assert!(recording.frames()[7].is_empty());
+
+        assert_eq!(
+            recording.to_string().parse::<Recording>().unwrap(),
+            recording
+        );
+
+        let error = "0 key_down D\n\n1 key_down Shift"
+            .parse::<Recording>()
+            .unwrap_err();
+        assert_eq!(error.line, 3);
+        assert!(error.message.contains("Shift"));
+        assert_eq!("2 close\n1 close".parse::<Recording>().unwrap_err().line, 2);
+        assert!("x close".parse::<Recording>().is_err());
+        assert!("0 wheel 1 2".parse::<Recording>().is_err());
+    }
+
+    #[test]
+    fn replay_test() {
+        let mut backend = ScriptedBackend::new(SCRIPT.parse().unwrap());
+        let mut input = Input::new();
+        let mut actions = ActionMap::new();
+        actions
+            .bind("jump", Binding::Key(Key::Space))
+            .bind(
+                "move",
+                Binding::Keys {
+                    negative: Key::A,
+                    positive: Key::D,
+                },
+            )
+            .bind("move", Binding::axis(GamepadAxis::LeftX, 0.25));
+
+        let (mut x, mut jumps) = (0.0, 0);
+        while !backend.is_finished() {
+            input.update(&mut backend);
+            actions.update(&input);
+            x += actions.value("move");
+            if actions.pressed("jump") {
+                jumps += 1;
+            }
+        }
+
+        // D on frames 0-4 (it outweighs the stick on 3-4), then the
+        // stick's -2/3 past the dead zone on 5-7.
+        assert!((x - 3.0).abs() < 1e-5, "{x}");
+        assert_eq!(jumps, 1);
+        assert_eq!(backend.frame(), 8);
+    }
+
+    #[test]
+    fn record_test() {
+        let recording: Recording = SCRIPT.parse().unwrap();
+        let mut recorder = Recorder::new(ScriptedBackend::new(recording.clone()));
+        let mut input = Input::new();
+        for _ in 0..recording.len() {
+            input.update(&mut recorder);
+        }
+
+        assert_eq!(recorder.into_recording(), recording);
+    }
+}
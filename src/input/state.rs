@@ -0,0 +1,275 @@
+use std::{collections::HashSet, hash::Hash};
+
+use crate::math::Vector2D;
+
+use super::{Backend, Event, GamepadAxis, GamepadButton, Key, MouseButton};
+
+/// Which buttons of one kind are held, and which changed this frame.
+#[derive(Debug, Clone)]
+pub struct Buttons<T> {
+    held: HashSet<T>,
+    pressed: HashSet<T>,
+    released: HashSet<T>,
+}
+
+impl<T> Default for Buttons<T> {
+    fn default() -> Self {
+        Self {
+            held: HashSet::new(),
+            pressed: HashSet::new(),
+            released: HashSet::new(),
+        }
+    }
+}
+
+impl<T: Copy + Eq + Hash> Buttons<T> {
+    /// Down now, whenever it was pressed.
+    pub fn held(&self, button: T) -> bool {
+        self.held.contains(&button)
+    }
+
+    /// Went down this frame.
+    pub fn pressed(&self, button: T) -> bool {
+        self.pressed.contains(&button)
+    }
+
+    /// Went up this frame. A button can be pressed and released in the
+    /// same frame.
+    pub fn released(&self, button: T) -> bool {
+        self.released.contains(&button)
+    }
+
+    pub fn iter_held(&self) -> impl Iterator<Item = T> + '_ {
+        self.held.iter().copied()
+    }
+
+    /// Key repeat sends more presses for a held button; they are ignored.
+    pub fn press(&mut self, button: T) {
+        if self.held.insert(button) {
+            self.pressed.insert(button);
+        }
+    }
+
+    pub fn release(&mut self, button: T) {
+        if self.held.remove(&button) {
+            self.released.insert(button);
+        }
+    }
+
+    /// Releases everything, e.g. when the window loses focus.
+    pub fn release_all(&mut self) {
+        self.released.extend(self.held.drain());
+    }
+
+    fn begin_frame(&mut self) {
+        self.pressed.clear();
+        self.released.clear();
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Gamepad {
+    pub buttons: Buttons<GamepadButton>,
+    axes: [f32; 6],
+}
+
+impl Gamepad {
+    /// The raw value, without dead zone.
+    pub fn axis(&self, axis: GamepadAxis) -> f32 {
+        self.axes[axis as usize]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WindowState {
+    pub width: u32,
+    pub height: u32,
+    pub focused: bool,
+    pub close_requested: bool,
+}
+
+impl Default for WindowState {
+    fn default() -> Self {
+        Self {
+            width: 0,
+            height: 0,
+            focused: true,
+            close_requested: false,
+        }
+    }
+}
+
+/// Input and window state for the current frame, built from [`Event`]s.
+#[derive(Debug, Clone, Default)]
+pub struct Input {
+    pub keys: Buttons<Key>,
+    pub mouse_buttons: Buttons<MouseButton>,
+    /// `None` until the first move, and again after a focus change, so the
+    /// next move does not jump from a stale position.
+    mouse_position: Option<Vector2D>,
+    mouse_delta: Vector2D,
+    wheel: f32,
+    gamepads: Vec<Gamepad>,
+    pub window: WindowState,
+}
+
+impl Input {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The last reported cursor position, or the origin before any.
+    pub fn mouse_position(&self) -> Vector2D {
+        self.mouse_position.unwrap_or_default()
+    }
+
+    /// Cursor movement this frame. The first move after startup or a focus
+    /// change only sets the position.
+    pub fn mouse_delta(&self) -> Vector2D {
+        self.mouse_delta
+    }
+
+    /// Scroll this frame.
+    pub fn wheel(&self) -> f32 {
+        self.wheel
+    }
+
+    /// Gamepads that have reported any event, by index.
+    pub fn gamepads(&self) -> &[Gamepad] {
+        &self.gamepads
+    }
+
+    pub fn gamepad(&self, index: usize) -> Option<&Gamepad> {
+        self.gamepads.get(index)
+    }
+
+    /// Starts a new frame and applies everything the backend reports.
+    pub fn update(&mut self, backend: &mut impl Backend) {
+        let mut events = Vec::new();
+        backend.poll(&mut events);
+
+        self.begin_frame();
+        for event in &events {
+            self.apply(event);
+        }
+    }
+
+    /// Forgets this frame's presses, releases and motion.
+    pub fn begin_frame(&mut self) {
+        self.keys.begin_frame();
+        self.mouse_buttons.begin_frame();
+        for pad in &mut self.gamepads {
+            pad.buttons.begin_frame();
+        }
+        self.mouse_delta = Vector2D::default();
+        self.wheel = 0.0;
+    }
+
+    pub fn apply(&mut self, event: &Event) {
+        match *event {
+            Event::KeyDown(key) => self.keys.press(key),
+            Event::KeyUp(key) => self.keys.release(key),
+            Event::MouseMove(position) => {
+                if let Some(last) = self.mouse_position {
+                    self.mouse_delta += position - last;
+                }
+                self.mouse_position = Some(position);
+            }
+            Event::MouseDown(button) => self.mouse_buttons.press(button),
+            Event::MouseUp(button) => self.mouse_buttons.release(button),
+            Event::Wheel(lines) => self.wheel += lines,
+            Event::GamepadDown(pad, button) => self.pad(pad).buttons.press(button),
+            Event::GamepadUp(pad, button) => self.pad(pad).buttons.release(button),
+            Event::GamepadAxis(pad, axis, value) => self.pad(pad).axes[axis as usize] = value,
+            Event::Resize(width, height) => {
+                self.window.width = width;
+                self.window.height = height;
+            }
+            Event::Focus(focused) => {
+                self.window.focused = focused;
+                // The cursor may have moved anywhere while unfocused.
+                self.mouse_position = None;
+                if !focused {
+                    // The key-up events go to whichever window has focus.
+                    self.keys.release_all();
+                    self.mouse_buttons.release_all();
+                }
+            }
+            Event::CloseRequested => self.window.close_requested = true,
+        }
+    }
+
+    fn pad(&mut self, index: usize) -> &mut Gamepad {
+        if self.gamepads.len() <= index {
+            self.gamepads.resize_with(index + 1, Gamepad::default);
+        }
+
+        return &mut self.gamepads[index];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_edges_test() {
+        let mut input = Input::new();
+        input.apply(&Event::KeyDown(Key::W));
+        input.apply(&Event::KeyDown(Key::W));
+        assert!(input.keys.pressed(Key::W) && input.keys.held(Key::W));
+
+        input.begin_frame();
+        assert!(!input.keys.pressed(Key::W) && input.keys.held(Key::W));
+
+        // Tapped within one frame.
+        input.begin_frame();
+        input.apply(&Event::KeyDown(Key::Space));
+        input.apply(&Event::KeyUp(Key::Space));
+        assert!(input.keys.pressed(Key::Space) && input.keys.released(Key::Space));
+        assert!(!input.keys.held(Key::Space));
+
+        input.apply(&Event::MouseDown(MouseButton::Left));
+        input.apply(&Event::Focus(false));
+        assert!(input.keys.released(Key::W) && input.mouse_buttons.released(MouseButton::Left));
+        assert_eq!(input.keys.iter_held().count(), 0);
+    }
+
+    #[test]
+    fn mouse_gamepad_test() {
+        let mut input = Input::new();
+        input.apply(&Event::MouseMove(Vector2D::create(10.0, 5.0)));
+        input.apply(&Event::MouseMove(Vector2D::create(12.0, 4.0)));
+        input.apply(&Event::Wheel(1.0));
+        assert_eq!(input.mouse_position(), Vector2D::create(12.0, 4.0));
+        // The first move only places the cursor.
+        assert_eq!(input.mouse_delta(), Vector2D::create(2.0, -1.0));
+
+        input.begin_frame();
+        assert_eq!(input.mouse_delta(), Vector2D::default());
+        assert_eq!(input.wheel(), 0.0);
+
+        input.apply(&Event::Focus(false));
+        input.apply(&Event::Focus(true));
+        input.apply(&Event::MouseMove(Vector2D::create(300.0, 200.0)));
+        assert_eq!(input.mouse_delta(), Vector2D::default());
+        input.apply(&Event::MouseMove(Vector2D::create(301.0, 200.0)));
+        assert_eq!(input.mouse_delta(), Vector2D::create(1.0, 0.0));
+        input.begin_frame();
+
+        input.apply(&Event::GamepadAxis(1, GamepadAxis::LeftX, -0.5));
+        input.apply(&Event::GamepadDown(1, GamepadButton::South));
+        assert_eq!(input.gamepads().len(), 2);
+        assert_eq!(input.gamepad(1).unwrap().axis(GamepadAxis::LeftX), -0.5);
+        assert!(input
+            .gamepad(1)
+            .unwrap()
+            .buttons
+            .pressed(GamepadButton::South));
+
+        input.apply(&Event::Resize(800, 600));
+        input.apply(&Event::CloseRequested);
+        assert_eq!((input.window.width, input.window.height), (800, 600));
+        assert!(input.window.close_requested);
+    }
+}
@@ -5,7 +5,18 @@ use math::{constants::FOUR, Matrix4x4};
 
 use crate::math::Matrix3x3;
 
+mod app;
+mod asset;
+mod camera;
+mod ecs;
+mod geometry;
+mod image;
+mod input;
 mod math;
+mod mesh;
+mod render;
+mod scene;
+mod spatial;
 
 fn main() {
     let row = Matrix3x3::new_row_major();
@@ -0,0 +1,361 @@
+//! Property-based tests for the algebraic laws of the math types.
+//!
+//! Every property runs from a fixed seed so failures are reproducible; set
+//! `WMB_QUICKCHECK_SEED` to explore other inputs and `WMB_QUICKCHECK_TESTS`
+//! to change the number of cases. Failing inputs are shrunk towards zero
+//! before they are reported.
+
+use quickcheck::{Arbitrary, Gen, QuickCheck, TestResult, Testable};
+
+use super::{Matrix3x3, Matrix4x4, Vector2D, Vector3D};
+
+const DEFAULT_SEED: u64 = 0x574d_4221;
+const DEFAULT_TESTS: u64 = 500;
+
+fn env_or<T: std::str::FromStr>(name: &str, default: T) -> T {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+fn check<A: Testable>(property: A) {
+    let seed = env_or("WMB_QUICKCHECK_SEED", DEFAULT_SEED);
+    let tests = env_or("WMB_QUICKCHECK_TESTS", DEFAULT_TESTS);
+
+    QuickCheck::new()
+        .rng(Gen::from_size_and_seed(100, seed))
+        .tests(tests)
+        .quickcheck(property);
+}
+
+/// A float in `[-100, 100]` with two decimal places, so sums and products
+/// stay far from overflow and the tolerances below stay meaningful.
+fn small_f32(g: &mut Gen) -> f32 {
+    (i32::arbitrary(g) % 10_001) as f32 / 100.0
+}
+
+/// An integer in `[-100, 100]`; products of three such matrices fit in `i32`.
+fn small_i32(g: &mut Gen) -> i32 {
+    i32::arbitrary(g) % 101
+}
+
+const TOLERANCE: f32 = 1e-5;
+
+/// Compares with an error bound relative to `scale`, the magnitude of the
+/// operands; the result alone is not enough when terms cancel.
+fn approx(a: f32, b: f32, scale: f32) -> bool {
+    (a - b).abs() <= TOLERANCE * scale.max(1.0)
+}
+
+fn approx2(a: &Vector2D, b: &Vector2D, scale: f32) -> bool {
+    approx(a.x(), b.x(), scale) && approx(a.y(), b.y(), scale)
+}
+
+fn approx3(a: &Vector3D, b: &Vector3D, scale: f32) -> bool {
+    approx(a.x(), b.x(), scale) && approx(a.y(), b.y(), scale) && approx(a.z(), b.z(), scale)
+}
+
+impl Arbitrary for Vector2D {
+    fn arbitrary(g: &mut Gen) -> Self {
+        Vector2D::create(small_f32(g), small_f32(g))
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        Box::new((self.x(), self.y()).shrink().map(Vector2D::from))
+    }
+}
+
+impl Arbitrary for Vector3D {
+    fn arbitrary(g: &mut Gen) -> Self {
+        Vector3D::create(small_f32(g), small_f32(g), small_f32(g))
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        Box::new((self.x(), self.y(), self.z()).shrink().map(Vector3D::from))
+    }
+}
+
+impl Arbitrary for Matrix3x3 {
+    fn arbitrary(g: &mut Gen) -> Self {
+        Matrix3x3::from([0; 9].map(|_| small_i32(g)))
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        Box::new(self.to_row_major_array().shrink().map(Matrix3x3::from))
+    }
+}
+
+impl Arbitrary for Matrix4x4 {
+    fn arbitrary(g: &mut Gen) -> Self {
+        Matrix4x4::from([0; 16].map(|_| small_i32(g)))
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        Box::new(self.to_row_major_array().shrink().map(Matrix4x4::from))
+    }
+}
+
+/// A non-zero scalar in `[0.1, 100]` or `[-100, -0.1]`.
+#[derive(Debug, Clone, Copy)]
+struct Scalar(f32);
+
+impl Arbitrary for Scalar {
+    fn arbitrary(g: &mut Gen) -> Self {
+        let magnitude = (u32::arbitrary(g) % 9_991 + 10) as f32 / 100.0;
+        let sign = if bool::arbitrary(g) { 1.0 } else { -1.0 };
+        Scalar(sign * magnitude)
+    }
+}
+
+mod vector2d {
+    use super::*;
+
+    #[test]
+    fn add_commutative_test() {
+        fn prop(a: Vector2D, b: Vector2D) -> bool {
+            a + b == b + a
+        }
+        check(prop as fn(Vector2D, Vector2D) -> bool);
+    }
+
+    #[test]
+    fn add_associative_test() {
+        fn prop(a: Vector2D, b: Vector2D, c: Vector2D) -> bool {
+            let scale = a.magnitude() + b.magnitude() + c.magnitude();
+            approx2(&((a + b) + c), &(a + (b + c)), scale)
+        }
+        check(prop as fn(Vector2D, Vector2D, Vector2D) -> bool);
+    }
+
+    #[test]
+    fn scalar_mul_distributes_over_add_test() {
+        fn prop(a: Vector2D, b: Vector2D, s: Scalar) -> bool {
+            let scale = (a.magnitude() + b.magnitude()) * s.0.abs();
+            approx2(&((a + b) * s.0), &(a * s.0 + b * s.0), scale)
+        }
+        check(prop as fn(Vector2D, Vector2D, Scalar) -> bool);
+    }
+
+    #[test]
+    fn mul_div_round_trip_test() {
+        fn prop(v: Vector2D, s: Scalar) -> bool {
+            approx2(&(v * s.0 / s.0), &v, v.magnitude())
+        }
+        check(prop as fn(Vector2D, Scalar) -> bool);
+    }
+
+    #[test]
+    fn dot_commutative_test() {
+        fn prop(a: Vector2D, b: Vector2D) -> bool {
+            a.dot(&b) == b.dot(&a)
+        }
+        check(prop as fn(Vector2D, Vector2D) -> bool);
+    }
+
+    #[test]
+    fn normalize_has_unit_magnitude_test() {
+        fn prop(v: Vector2D) -> TestResult {
+            if v.magnitude() < 1e-3 {
+                return TestResult::discard();
+            }
+            TestResult::from_bool(approx(v.normalize().magnitude(), 1.0, 1.0))
+        }
+        check(prop as fn(Vector2D) -> TestResult);
+    }
+
+    #[test]
+    fn display_parse_round_trip_test() {
+        fn prop(v: Vector2D) -> bool {
+            let parsed: Vector2D = v.to_string().parse().unwrap();
+            parsed.x() == v.x() && parsed.y() == v.y()
+        }
+        check(prop as fn(Vector2D) -> bool);
+    }
+}
+
+mod vector3d {
+    use super::*;
+
+    #[test]
+    fn add_commutative_test() {
+        fn prop(a: Vector3D, b: Vector3D) -> bool {
+            a + b == b + a
+        }
+        check(prop as fn(Vector3D, Vector3D) -> bool);
+    }
+
+    #[test]
+    fn add_associative_test() {
+        fn prop(a: Vector3D, b: Vector3D, c: Vector3D) -> bool {
+            let scale = a.magnitude() + b.magnitude() + c.magnitude();
+            approx3(&((a + b) + c), &(a + (b + c)), scale)
+        }
+        check(prop as fn(Vector3D, Vector3D, Vector3D) -> bool);
+    }
+
+    #[test]
+    fn scalar_mul_distributes_over_add_test() {
+        fn prop(a: Vector3D, b: Vector3D, s: Scalar) -> bool {
+            let scale = (a.magnitude() + b.magnitude()) * s.0.abs();
+            approx3(&((a + b) * s.0), &(a * s.0 + b * s.0), scale)
+        }
+        check(prop as fn(Vector3D, Vector3D, Scalar) -> bool);
+    }
+
+    #[test]
+    fn mul_div_round_trip_test() {
+        fn prop(v: Vector3D, s: Scalar) -> bool {
+            let mut assigned = v;
+            assigned *= s.0;
+            assigned /= s.0;
+
+            let scale = v.magnitude();
+            approx3(&(v * s.0 / s.0), &v, scale) && approx3(&assigned, &v, scale)
+        }
+        check(prop as fn(Vector3D, Scalar) -> bool);
+    }
+
+    #[test]
+    fn dot_is_squared_magnitude_test() {
+        fn prop(v: Vector3D) -> bool {
+            let squared = v.magnitude() * v.magnitude();
+            approx(v.dot(&v), squared, squared)
+        }
+        check(prop as fn(Vector3D) -> bool);
+    }
+
+    #[test]
+    fn normalize_has_unit_magnitude_test() {
+        fn prop(v: Vector3D) -> TestResult {
+            if v.magnitude() < 1e-3 {
+                return TestResult::discard();
+            }
+            TestResult::from_bool(approx(v.normalize().magnitude(), 1.0, 1.0))
+        }
+        check(prop as fn(Vector3D) -> TestResult);
+    }
+
+    #[test]
+    fn display_parse_round_trip_test() {
+        fn prop(v: Vector3D) -> bool {
+            let parsed: Vector3D = v.to_string().parse().unwrap();
+            parsed.x() == v.x() && parsed.y() == v.y() && parsed.z() == v.z()
+        }
+        check(prop as fn(Vector3D) -> bool);
+    }
+}
+
+/// The same laws hold for both matrix sizes; integer arithmetic makes them
+/// exact.
+macro_rules! matrix_laws {
+    ($name:ident, $mat:ty, $n:expr) => {
+        mod $name {
+            use super::*;
+
+            fn identity() -> $mat {
+                let mut m = <$mat>::default();
+                for i in 0..$n {
+                    m[(i, i)] = 1;
+                }
+                m
+            }
+
+            #[test]
+            fn mul_associative_test() {
+                fn prop(a: $mat, b: $mat, c: $mat) -> bool {
+                    (a * b) * c == a * (b * c)
+                }
+                check(prop as fn($mat, $mat, $mat) -> bool);
+            }
+
+            #[test]
+            fn mul_distributes_over_add_test() {
+                fn prop(a: $mat, b: $mat, c: $mat) -> bool {
+                    a * (b + c) == a * b + a * c && (a + b) * c == a * c + b * c
+                }
+                check(prop as fn($mat, $mat, $mat) -> bool);
+            }
+
+            #[test]
+            fn scalar_mul_commutes_with_mul_test() {
+                fn prop(a: $mat, b: $mat, k: i8) -> bool {
+                    let k = k as i32 % 10;
+                    (a * k) * b == (a * b) * k
+                }
+                check(prop as fn($mat, $mat, i8) -> bool);
+            }
+
+            #[test]
+            fn identity_is_neutral_test() {
+                fn prop(a: $mat) -> bool {
+                    a * identity() == a && identity() * a == a
+                }
+                check(prop as fn($mat) -> bool);
+            }
+
+            #[test]
+            fn add_commutative_test() {
+                fn prop(a: $mat, b: $mat) -> bool {
+                    a + b == b + a
+                }
+                check(prop as fn($mat, $mat) -> bool);
+            }
+
+            #[test]
+            fn sub_inverts_add_test() {
+                fn prop(a: $mat, b: $mat) -> bool {
+                    (a + b) - b == a && a - a == <$mat>::default()
+                }
+                check(prop as fn($mat, $mat) -> bool);
+            }
+
+            #[test]
+            fn row_major_array_round_trip_test() {
+                fn prop(a: $mat) -> bool {
+                    <$mat>::from(a.to_row_major_array()) == a
+                }
+                check(prop as fn($mat) -> bool);
+            }
+
+            #[test]
+            fn row_col_transpose_test() {
+                fn prop(a: $mat) -> bool {
+                    (0..$n).all(|i| (0..$n).all(|j| a.row(i)[j] == a.col(j)[i]))
+                }
+                check(prop as fn($mat) -> bool);
+            }
+
+            #[test]
+            fn display_parse_round_trip_test() {
+                fn prop(a: $mat) -> bool {
+                    a.to_string().parse::<$mat>() == Ok(a)
+                        && format!("{:#}", a).parse::<$mat>() == Ok(a)
+                }
+                check(prop as fn($mat) -> bool);
+            }
+        }
+    };
+}
+
+matrix_laws!(matrix3x3, Matrix3x3, 3);
+matrix_laws!(matrix4x4, Matrix4x4, 4);
+
+#[test]
+fn seeded_generation_is_deterministic_test() {
+    let mut a = Gen::from_size_and_seed(100, DEFAULT_SEED);
+    let mut b = Gen::from_size_and_seed(100, DEFAULT_SEED);
+
+    for _ in 0..10 {
+        assert_eq!(Matrix3x3::arbitrary(&mut a), Matrix3x3::arbitrary(&mut b));
+    }
+}
+
+#[test]
+#[should_panic(expected = "TEST FAILED")]
+fn failing_property_is_reported_test() {
+    fn prop(a: Matrix3x3, b: Matrix3x3) -> bool {
+        a * b == b * a
+    }
+    check(prop as fn(Matrix3x3, Matrix3x3) -> bool);
+}
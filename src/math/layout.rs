@@ -0,0 +1,593 @@
+//! GPU-compatible memory layouts.
+//!
+//! The `Gpu*` types are `#[repr(C)]` float mirrors of the math types that can
+//! be copied byte-for-byte into vertex or uniform buffers. With the
+//! `bytemuck` feature enabled they (and the math types themselves) implement
+//! `Pod`/`Zeroable` so they can be cast with `bytemuck::cast_slice`.
+//!
+//! [`Packer`] writes values following the GLSL `std140` or `std430` block
+//! layout rules, inserting the padding the shader expects.
+
+use std::marker::PhantomData;
+
+use super::{Matrix3x3, Matrix4x4, Matrix4x4F32, Vector2D, Vector3D};
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GpuVec2 {
+    pub x: f32,
+    pub y: f32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GpuVec3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GpuVec4 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+/// 3x3 float matrix with every column padded to 16 bytes, which is how both
+/// `std140` and `std430` store a `mat3`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GpuMat3 {
+    pub cols: [GpuVec4; 3],
+}
+
+/// 4x4 column major float matrix.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GpuMat4 {
+    pub cols: [GpuVec4; 4],
+}
+
+impl From<&Vector2D> for GpuVec2 {
+    fn from(v: &Vector2D) -> Self {
+        Self { x: v.x(), y: v.y() }
+    }
+}
+
+impl From<&Vector3D> for GpuVec3 {
+    fn from(v: &Vector3D) -> Self {
+        Self {
+            x: v.x(),
+            y: v.y(),
+            z: v.z(),
+        }
+    }
+}
+
+impl From<&Vector3D> for GpuVec4 {
+    /// Extends the vector with `w = 0`.
+    fn from(v: &Vector3D) -> Self {
+        Self {
+            x: v.x(),
+            y: v.y(),
+            z: v.z(),
+            w: 0.0,
+        }
+    }
+}
+
+impl From<[f32; 4]> for GpuVec4 {
+    fn from(v: [f32; 4]) -> Self {
+        Self {
+            x: v[0],
+            y: v[1],
+            z: v[2],
+            w: v[3],
+        }
+    }
+}
+
+impl GpuVec4 {
+    pub fn to_array(self) -> [f32; 4] {
+        [self.x, self.y, self.z, self.w]
+    }
+}
+
+#[cfg(feature = "bytemuck")]
+mod pod {
+    use super::*;
+    use bytemuck::{Pod, Zeroable};
+
+    // SAFETY: every type below is `#[repr(C)]`, `Copy`, built only from
+    // `f32`/`i32` fields of equal size and therefore has no padding bytes.
+    unsafe impl Zeroable for GpuVec2 {}
+    unsafe impl Pod for GpuVec2 {}
+    unsafe impl Zeroable for GpuVec3 {}
+    unsafe impl Pod for GpuVec3 {}
+    unsafe impl Zeroable for GpuVec4 {}
+    unsafe impl Pod for GpuVec4 {}
+    unsafe impl Zeroable for GpuMat3 {}
+    unsafe impl Pod for GpuMat3 {}
+    unsafe impl Zeroable for GpuMat4 {}
+    unsafe impl Pod for GpuMat4 {}
+
+    unsafe impl Zeroable for Vector2D {}
+    unsafe impl Pod for Vector2D {}
+    unsafe impl Zeroable for Vector3D {}
+    unsafe impl Pod for Vector3D {}
+    unsafe impl Zeroable for Matrix3x3 {}
+    unsafe impl Pod for Matrix3x3 {}
+    unsafe impl Zeroable for Matrix4x4 {}
+    unsafe impl Pod for Matrix4x4 {}
+    unsafe impl Zeroable for Matrix4x4F32 {}
+    unsafe impl Pod for Matrix4x4F32 {}
+}
+
+/// Alignment and stride rules of a GLSL buffer block layout.
+pub trait Layout {
+    /// Stride between consecutive elements of an array whose element has the
+    /// given size and base alignment.
+    fn array_stride(size: usize, align: usize) -> usize;
+
+    /// Base alignment of an array or struct whose widest member has the
+    /// given alignment.
+    fn aggregate_align(align: usize) -> usize;
+}
+
+/// Layout used by uniform blocks.
+#[derive(Debug, Clone, Copy)]
+pub enum Std140 {}
+
+/// Layout used by shader storage blocks and push constants.
+#[derive(Debug, Clone, Copy)]
+pub enum Std430 {}
+
+const VEC4_ALIGN: usize = 16;
+
+fn round_up(n: usize, align: usize) -> usize {
+    n.div_ceil(align) * align
+}
+
+impl Layout for Std140 {
+    fn array_stride(size: usize, align: usize) -> usize {
+        round_up(round_up(size, align), VEC4_ALIGN)
+    }
+
+    fn aggregate_align(align: usize) -> usize {
+        round_up(align, VEC4_ALIGN)
+    }
+}
+
+impl Layout for Std430 {
+    fn array_stride(size: usize, align: usize) -> usize {
+        round_up(size, align)
+    }
+
+    fn aggregate_align(align: usize) -> usize {
+        align
+    }
+}
+
+/// Writes a buffer block member by member, returning the byte offset of
+/// every member so it can be checked against the shader reflection data.
+pub struct Packer<L: Layout> {
+    bytes: Vec<u8>,
+    align: usize,
+    layout: PhantomData<L>,
+}
+
+impl<L: Layout> Default for Packer<L> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<L: Layout> Packer<L> {
+    pub fn new() -> Self {
+        Self {
+            bytes: Vec::new(),
+            align: 4,
+            layout: PhantomData,
+        }
+    }
+
+    /// Current write offset in bytes.
+    pub fn offset(&self) -> usize {
+        self.bytes.len()
+    }
+
+    fn begin(&mut self, align: usize) -> usize {
+        self.align = self.align.max(align);
+        let offset = round_up(self.bytes.len(), align);
+        self.bytes.resize(offset, 0);
+        offset
+    }
+
+    fn put(&mut self, values: &[f32]) {
+        for v in values {
+            self.bytes.extend_from_slice(&v.to_le_bytes());
+        }
+    }
+
+    pub fn f32(&mut self, v: f32) -> usize {
+        let offset = self.begin(4);
+        self.put(&[v]);
+        offset
+    }
+
+    pub fn i32(&mut self, v: i32) -> usize {
+        let offset = self.begin(4);
+        self.bytes.extend_from_slice(&v.to_le_bytes());
+        offset
+    }
+
+    pub fn u32(&mut self, v: u32) -> usize {
+        let offset = self.begin(4);
+        self.bytes.extend_from_slice(&v.to_le_bytes());
+        offset
+    }
+
+    pub fn vec2(&mut self, v: &Vector2D) -> usize {
+        let offset = self.begin(8);
+        self.put(&[v.x(), v.y()]);
+        offset
+    }
+
+    pub fn vec3(&mut self, v: &Vector3D) -> usize {
+        let offset = self.begin(16);
+        self.put(&[v.x(), v.y(), v.z()]);
+        offset
+    }
+
+    pub fn vec4(&mut self, v: [f32; 4]) -> usize {
+        let offset = self.begin(16);
+        self.put(&v);
+        offset
+    }
+
+    /// Writes the matrix as a `mat3`; integer entries are converted to floats.
+    pub fn mat3(&mut self, m: &Matrix3x3) -> usize {
+        let offset = self.begin(L::aggregate_align(16));
+        for col in GpuMat3::from(m).cols {
+            let start = self.bytes.len();
+            self.put(&[col.x, col.y, col.z]);
+            self.bytes.resize(start + L::array_stride(12, 16), 0);
+        }
+        offset
+    }
+
+    /// Writes the matrix as a `mat4`; integer entries are converted to floats.
+    pub fn mat4(&mut self, m: &Matrix4x4) -> usize {
+        let offset = self.begin(L::aggregate_align(16));
+        for col in GpuMat4::from(m).cols {
+            self.put(&col.to_array());
+        }
+        offset
+    }
+
+    pub fn f32_array(&mut self, values: &[f32]) -> usize {
+        let stride = L::array_stride(4, 4);
+        let offset = self.begin(L::aggregate_align(4));
+        for v in values {
+            let start = self.bytes.len();
+            self.put(&[*v]);
+            self.bytes.resize(start + stride, 0);
+        }
+        offset
+    }
+
+    pub fn vec2_array(&mut self, values: &[Vector2D]) -> usize {
+        let stride = L::array_stride(8, 8);
+        let offset = self.begin(L::aggregate_align(8));
+        for v in values {
+            let start = self.bytes.len();
+            self.put(&[v.x(), v.y()]);
+            self.bytes.resize(start + stride, 0);
+        }
+        offset
+    }
+
+    pub fn vec3_array(&mut self, values: &[Vector3D]) -> usize {
+        let stride = L::array_stride(12, 16);
+        let offset = self.begin(L::aggregate_align(16));
+        for v in values {
+            let start = self.bytes.len();
+            self.put(&[v.x(), v.y(), v.z()]);
+            self.bytes.resize(start + stride, 0);
+        }
+        offset
+    }
+
+    /// Writes a nested struct built by `build`.
+    pub fn nested(&mut self, build: impl FnOnce(&mut Packer<L>)) -> usize {
+        let mut inner = Packer::<L>::new();
+        build(&mut inner);
+        let align = L::aggregate_align(inner.align);
+        let bytes = inner.finish();
+        let offset = self.begin(align);
+        self.bytes.extend_from_slice(&bytes);
+        offset
+    }
+
+    /// Writes every element of `values` as a struct, padded to the array stride.
+    pub fn struct_array<T: Pack>(&mut self, values: &[T]) -> usize {
+        let mut offset = None;
+        for v in values {
+            let at = self.nested(|p| v.pack(p));
+            offset.get_or_insert(at);
+        }
+        offset.unwrap_or_else(|| self.offset())
+    }
+
+    /// Pads the block to its alignment and returns the bytes.
+    pub fn finish(mut self) -> Vec<u8> {
+        let size = round_up(self.bytes.len(), L::aggregate_align(self.align));
+        self.bytes.resize(size, 0);
+        self.bytes
+    }
+}
+
+/// A type that can be written into a buffer block.
+///
+/// Implement it for the structs mirrored in shaders by packing each field in
+/// declaration order.
+pub trait Pack {
+    fn pack<L: Layout>(&self, packer: &mut Packer<L>);
+}
+
+impl Pack for f32 {
+    fn pack<L: Layout>(&self, packer: &mut Packer<L>) {
+        packer.f32(*self);
+    }
+}
+
+impl Pack for Vector2D {
+    fn pack<L: Layout>(&self, packer: &mut Packer<L>) {
+        packer.vec2(self);
+    }
+}
+
+impl Pack for Vector3D {
+    fn pack<L: Layout>(&self, packer: &mut Packer<L>) {
+        packer.vec3(self);
+    }
+}
+
+impl Pack for Matrix3x3 {
+    fn pack<L: Layout>(&self, packer: &mut Packer<L>) {
+        packer.mat3(self);
+    }
+}
+
+impl Pack for Matrix4x4 {
+    fn pack<L: Layout>(&self, packer: &mut Packer<L>) {
+        packer.mat4(self);
+    }
+}
+
+pub fn std140_bytes<T: Pack>(value: &T) -> Vec<u8> {
+    let mut packer = Packer::<Std140>::new();
+    value.pack(&mut packer);
+    packer.finish()
+}
+
+pub fn std430_bytes<T: Pack>(value: &T) -> Vec<u8> {
+    let mut packer = Packer::<Std430>::new();
+    value.pack(&mut packer);
+    packer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Light {
+        position: Vector3D,
+        intensity: f32,
+        basis: Matrix3x3,
+        uv: Vector2D,
+    }
+
+    impl Pack for Light {
+        fn pack<L: Layout>(&self, p: &mut Packer<L>) {
+            p.vec3(&self.position);
+            p.f32(self.intensity);
+            p.mat3(&self.basis);
+            p.vec2(&self.uv);
+        }
+    }
+
+    fn light() -> Light {
+        Light {
+            position: Vector3D::create(1.0, 2.0, 3.0),
+            intensity: 4.0,
+            basis: Matrix3x3::from([1, 2, 3, 4, 5, 6, 7, 8, 9]),
+            uv: Vector2D::create(0.5, 0.25),
+        }
+    }
+
+    fn read_f32(bytes: &[u8], offset: usize) -> f32 {
+        f32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn gpu_type_sizes_test() {
+        assert_eq!(std::mem::size_of::<GpuVec2>(), 8);
+        assert_eq!(std::mem::size_of::<GpuVec3>(), 12);
+        assert_eq!(std::mem::size_of::<GpuVec4>(), 16);
+        assert_eq!(std::mem::size_of::<GpuMat3>(), 48);
+        assert_eq!(std::mem::size_of::<GpuMat4>(), 64);
+        assert_eq!(std::mem::size_of::<Vector3D>(), 12);
+        assert_eq!(std::mem::size_of::<Matrix3x3>(), 36);
+        assert_eq!(std::mem::size_of::<Matrix4x4>(), 64);
+    }
+
+    #[test]
+    fn gpu_mat3_from_matrix_test() {
+        let m = GpuMat3::from(&Matrix3x3::from([1, 2, 3, 4, 5, 6, 7, 8, 9]));
+
+        assert_eq!(m.cols[0].to_array(), [1.0, 4.0, 7.0, 0.0]);
+        assert_eq!(m.cols[1].to_array(), [2.0, 5.0, 8.0, 0.0]);
+        assert_eq!(m.cols[2].to_array(), [3.0, 6.0, 9.0, 0.0]);
+    }
+
+    #[test]
+    fn scalars_pack_after_vec3_test() {
+        let mut p = Packer::<Std140>::new();
+
+        assert_eq!(p.vec3(&Vector3D::create(1.0, 2.0, 3.0)), 0);
+        assert_eq!(p.f32(4.0), 12);
+        assert_eq!(p.vec2(&Vector2D::create(5.0, 6.0)), 16);
+        assert_eq!(p.vec3(&Vector3D::default()), 32);
+        assert_eq!(p.finish().len(), 48);
+    }
+
+    #[test]
+    fn struct_offsets_std140_test() {
+        let mut p = Packer::<Std140>::new();
+        let l = light();
+
+        assert_eq!(p.vec3(&l.position), 0);
+        assert_eq!(p.f32(l.intensity), 12);
+        assert_eq!(p.mat3(&l.basis), 16);
+        assert_eq!(p.vec2(&l.uv), 64);
+
+        let bytes = p.finish();
+        assert_eq!(bytes.len(), 80);
+        assert_eq!(read_f32(&bytes, 16), 1.0);
+        assert_eq!(read_f32(&bytes, 20), 4.0);
+        assert_eq!(read_f32(&bytes, 24), 7.0);
+        assert_eq!(read_f32(&bytes, 32), 2.0);
+        assert_eq!(read_f32(&bytes, 48), 3.0);
+        assert_eq!(read_f32(&bytes, 68), 0.25);
+    }
+
+    #[test]
+    fn struct_offsets_std430_test() {
+        let bytes = std430_bytes(&light());
+
+        // std430 only pads the struct to its widest member (16 bytes).
+        assert_eq!(bytes.len(), 80);
+        assert_eq!(read_f32(&bytes, 12), 4.0);
+        assert_eq!(read_f32(&bytes, 64), 0.5);
+    }
+
+    #[test]
+    fn float_array_stride_test() {
+        let mut p140 = Packer::<Std140>::new();
+        p140.f32(1.0);
+        assert_eq!(p140.f32_array(&[2.0, 3.0, 4.0]), 16);
+        assert_eq!(p140.f32(5.0), 64);
+
+        let mut p430 = Packer::<Std430>::new();
+        p430.f32(1.0);
+        assert_eq!(p430.f32_array(&[2.0, 3.0, 4.0]), 4);
+        assert_eq!(p430.f32(5.0), 16);
+    }
+
+    #[test]
+    fn vec2_array_stride_test() {
+        let values = [Vector2D::create(1.0, 2.0), Vector2D::create(3.0, 4.0)];
+
+        let mut p140 = Packer::<Std140>::new();
+        p140.vec2_array(&values);
+        let bytes = p140.finish();
+        assert_eq!(bytes.len(), 32);
+        assert_eq!(read_f32(&bytes, 16), 3.0);
+
+        let mut p430 = Packer::<Std430>::new();
+        p430.vec2_array(&values);
+        let bytes = p430.finish();
+        assert_eq!(bytes.len(), 16);
+        assert_eq!(read_f32(&bytes, 8), 3.0);
+    }
+
+    #[test]
+    fn vec3_array_stride_test() {
+        let values = [
+            Vector3D::create(1.0, 2.0, 3.0),
+            Vector3D::create(4.0, 5.0, 6.0),
+        ];
+
+        for bytes in [
+            {
+                let mut p = Packer::<Std140>::new();
+                p.vec3_array(&values);
+                p.finish()
+            },
+            {
+                let mut p = Packer::<Std430>::new();
+                p.vec3_array(&values);
+                p.finish()
+            },
+        ] {
+            assert_eq!(bytes.len(), 32);
+            assert_eq!(read_f32(&bytes, 16), 4.0);
+        }
+    }
+
+    #[test]
+    fn nested_struct_alignment_test() {
+        let mut p140 = Packer::<Std140>::new();
+        p140.f32(1.0);
+        assert_eq!(
+            p140.nested(|p| {
+                p.f32(2.0);
+            }),
+            16
+        );
+        assert_eq!(p140.f32(3.0), 32);
+
+        let mut p430 = Packer::<Std430>::new();
+        p430.f32(1.0);
+        assert_eq!(
+            p430.nested(|p| {
+                p.f32(2.0);
+            }),
+            4
+        );
+        assert_eq!(p430.f32(3.0), 8);
+    }
+
+    #[test]
+    fn struct_array_test() {
+        let lights = [light(), light()];
+
+        let mut p = Packer::<Std140>::new();
+        assert_eq!(p.f32(0.0), 0);
+        assert_eq!(p.struct_array(&lights), 16);
+        assert_eq!(p.offset(), 16 + 2 * 80);
+    }
+
+    #[test]
+    fn mat4_column_major_test() {
+        let m = Matrix4x4::from([1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16]);
+        let bytes = std140_bytes(&m);
+
+        assert_eq!(bytes.len(), 64);
+        assert_eq!(read_f32(&bytes, 0), 1.0);
+        assert_eq!(read_f32(&bytes, 4), 5.0);
+        assert_eq!(read_f32(&bytes, 16), 2.0);
+        assert_eq!(read_f32(&bytes, 60), 16.0);
+    }
+
+    #[cfg(feature = "bytemuck")]
+    #[test]
+    fn pod_cast_test() {
+        let verts = [
+            Vector3D::create(1.0, 2.0, 3.0),
+            Vector3D::create(4.0, 5.0, 6.0),
+        ];
+        let floats: &[f32] = bytemuck::cast_slice(&verts);
+
+        assert_eq!(floats, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+
+        let m = GpuMat4::from(&Matrix4x4::new_col_major());
+        let bytes: &[u8] = bytemuck::bytes_of(&m);
+        assert_eq!(bytes.len(), 64);
+        assert_eq!(bytes, std140_bytes(&Matrix4x4::new_col_major()).as_slice());
+    }
+}
@@ -1,27 +1,54 @@
 use super::constants::*;
+use super::layout::{GpuMat3, GpuVec4};
+use super::text::{self, ParseMathError};
 use std::{
-    fmt::Debug,
-    ops::{Add, Mul, Sub},
+    fmt::{self, Debug, Display, Formatter},
+    ops::{Add, Index, IndexMut, Mul, Sub},
+    str::FromStr,
 };
 
 pub type Vec3D = [i32; THREE];
 pub type Vec9D = [i32; NINE];
 pub type Mat3x3 = [Vec3D; THREE];
 
-#[derive(Clone, Copy)]
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
 pub struct Matrix3x3 {
     inner: Mat3x3,
 }
 
 impl Debug for Matrix3x3 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "\n----------------------------");
-        writeln!(f, "{:?}", self.inner[0]);
-        writeln!(f, "{:?}", self.inner[1]);
-        writeln!(f, "{:?}", self.inner[2]);
-        writeln!(f, "----------------------------");
+        writeln!(f, "\n----------------------------")?;
+        writeln!(f, "{:?}", self.inner[0])?;
+        writeln!(f, "{:?}", self.inner[1])?;
+        writeln!(f, "{:?}", self.inner[2])?;
+        writeln!(f, "----------------------------")
+    }
+}
 
-        Ok(())
+/// Row-major text, `[[n00, n01, ..], [n10, ..], ..]`. The alternate flag
+/// (`{:#}`) writes one row per line with aligned columns.
+impl Display for Matrix3x3 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        text::write_matrix(f, &self.rows())
+    }
+}
+
+impl FromStr for Matrix3x3 {
+    type Err = ParseMathError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rows = text::parse_rows::<i32>(s, THREE, THREE)?;
+        let mut mat = Matrix3x3::default();
+
+        for (i, row) in rows.iter().enumerate() {
+            for (j, &value) in row.iter().enumerate() {
+                mat.inner[j][i] = value;
+            }
+        }
+
+        return Ok(mat);
     }
 }
 
@@ -50,6 +77,7 @@ impl Matrix3x3 {
         return result;
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn new(
         n00: i32,
         n01: i32,
@@ -66,27 +94,109 @@ impl Matrix3x3 {
         }
     }
 
-    /// column major matrix
-    fn at(&self, i: usize, j: usize) -> i32 {
-        assert!(i >= ZERO && i <= THREE);
-        assert!(j >= ZERO && j <= THREE);
+    /// Element at row `i`, column `j`.
+    ///
+    /// Panics if either index is out of range.
+    pub fn at(&self, i: usize, j: usize) -> i32 {
+        return self[(i, j)];
+    }
 
-        return self.inner[j][i];
+    /// Element at row `i`, column `j`, or `None` if out of range.
+    pub fn get(&self, i: usize, j: usize) -> Option<i32> {
+        if i < THREE && j < THREE {
+            return Some(self.inner[j][i]);
+        }
+
+        return None;
     }
 
-    fn col_at(&self, i: usize) -> &Vec3D {
-        assert!(i >= ZERO && i <= THREE);
+    fn rows(&self) -> [Vec3D; THREE] {
+        return std::array::from_fn(|i| self.row(i));
+    }
+
+    /// Column `i`, stored contiguously since the matrix is column major.
+    pub fn col_at(&self, i: usize) -> &Vec3D {
+        check_index(i);
 
         return &self.inner[i];
     }
-}
 
-impl Default for Matrix3x3 {
-    fn default() -> Self {
-        Self {
-            inner: Default::default(),
+    pub fn col(&self, i: usize) -> Vec3D {
+        return *self.col_at(i);
+    }
+
+    pub fn row(&self, i: usize) -> Vec3D {
+        check_index(i);
+
+        return self.inner.map(|col| col[i]);
+    }
+
+    pub fn set_col(&mut self, i: usize, col: Vec3D) {
+        check_index(i);
+
+        self.inner[i] = col;
+    }
+
+    pub fn set_row(&mut self, i: usize, row: Vec3D) {
+        check_index(i);
+
+        for (column, value) in self.inner.iter_mut().zip(row) {
+            column[i] = value;
         }
     }
+
+    /// Iterates over the elements in column-major order.
+    pub fn iter(&self) -> impl Iterator<Item = &i32> {
+        self.inner.iter().flatten()
+    }
+
+    pub fn to_col_major_array(self) -> Vec9D {
+        let mut v: Vec9D = Default::default();
+
+        for (e, &n) in v.iter_mut().zip(self.iter()) {
+            *e = n;
+        }
+
+        return v;
+    }
+
+    /// Inverse of `From<Vec9D>`.
+    pub fn to_row_major_array(self) -> Vec9D {
+        let mut v: Vec9D = Default::default();
+
+        for i in 0..THREE {
+            for j in 0..THREE {
+                v[i * THREE + j] = self.inner[j][i];
+            }
+        }
+
+        return v;
+    }
+}
+
+fn check_index(i: usize) {
+    assert!(i < THREE, "index {i} out of range for a 3x3 matrix");
+}
+
+/// Indexed by `(row, column)`.
+impl Index<(usize, usize)> for Matrix3x3 {
+    type Output = i32;
+
+    fn index(&self, (i, j): (usize, usize)) -> &Self::Output {
+        check_index(i);
+        check_index(j);
+
+        return &self.inner[j][i];
+    }
+}
+
+impl IndexMut<(usize, usize)> for Matrix3x3 {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut Self::Output {
+        check_index(i);
+        check_index(j);
+
+        return &mut self.inner[j][i];
+    }
 }
 
 impl From<Vec9D> for Matrix3x3 {
@@ -117,6 +227,19 @@ impl From<i32> for Matrix3x3 {
     }
 }
 
+impl From<&Matrix3x3> for GpuMat3 {
+    fn from(m: &Matrix3x3) -> Self {
+        let mut result = GpuMat3::default();
+
+        for i in 0..THREE {
+            let [x, y, z] = m.inner[i];
+            result.cols[i] = GpuVec4::from([x as f32, y as f32, z as f32, 0.0]);
+        }
+
+        return result;
+    }
+}
+
 impl Add for Matrix3x3 {
     type Output = Matrix3x3;
 
@@ -221,6 +344,7 @@ mod tests {
 
     use super::*;
 
+    #[allow(clippy::needless_range_loop)]
     fn random_vec9d() -> Vec9D {
         let mut v: Vec9D = Default::default();
 
@@ -417,4 +541,170 @@ mod tests {
 
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn display_test() {
+        let mat = Matrix3x3::from([1, -2, 3, 4, 5, 6, 7, 8, 109]);
+
+        assert_eq!(format!("{}", mat), "[[1, -2, 3], [4, 5, 6], [7, 8, 109]]");
+        assert_eq!(
+            format!("{:5}", mat),
+            "[[    1,    -2,     3], [    4,     5,     6], [    7,     8,   109]]"
+        );
+    }
+
+    #[test]
+    fn display_pretty_test() {
+        let mat = Matrix3x3::from([1, -2, 3, 4, 5, 6, 7, 8, 109]);
+
+        assert_eq!(
+            format!("{:#}", mat),
+            "[[  1,  -2,   3],\n [  4,   5,   6],\n [  7,   8, 109]]"
+        );
+    }
+
+    #[test]
+    fn from_str_test() {
+        let mat = Matrix3x3::from([1, -2, 3, 4, 5, 6, 7, 8, 109]);
+
+        assert_eq!("[[1,-2,3],[4,5,6],[7,8,109]]".parse::<Matrix3x3>(), Ok(mat));
+        assert_eq!(format!("{:#}", mat).parse::<Matrix3x3>(), Ok(mat));
+        assert_eq!(format!("{:5}", mat).parse::<Matrix3x3>(), Ok(mat));
+    }
+
+    #[test]
+    fn from_str_errors_test() {
+        assert!("[[1, 2], [3, 4]]".parse::<Matrix3x3>().is_err());
+        assert!("[1, 1, 1, 1, 1, 1, 1, 1, 1]".parse::<Matrix3x3>().is_err());
+        assert!("[[1.5, -2, 3], [4, 5, 6], [7, 8, 109]]"
+            .parse::<Matrix3x3>()
+            .is_err());
+    }
+
+    #[test]
+    fn display_round_trip_test() {
+        let mat = random_mat3x3();
+
+        assert_eq!(mat.to_string().parse::<Matrix3x3>(), Ok(mat));
+    }
+
+    #[test]
+    fn debug_test() {
+        let text = format!("{:?}", Matrix3x3::new_row_major());
+
+        assert!(text.starts_with("\n---"));
+        assert_eq!(text.lines().count(), 6);
+    }
+
+    #[test]
+    fn index_test() {
+        let v = random_vec9d();
+        let mut mat = Matrix3x3::from(v);
+
+        for i in 0..THREE {
+            for j in 0..THREE {
+                assert_eq!(mat[(i, j)], v[i * THREE + j]);
+                assert_eq!(mat.get(i, j), Some(v[i * THREE + j]));
+            }
+        }
+
+        mat[(1, 2)] = 42;
+        assert_eq!(mat.at(1, 2), 42);
+        assert_eq!(mat.col_at(2)[1], 42);
+    }
+
+    #[test]
+    fn get_out_of_range_test() {
+        let mat = Matrix3x3::new_row_major();
+
+        assert_eq!(mat.get(THREE, 0), None);
+        assert_eq!(mat.get(0, THREE), None);
+        assert_eq!(mat.get(usize::MAX, usize::MAX), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn index_row_out_of_range_test() {
+        let mat = Matrix3x3::new_row_major();
+
+        let _ = mat[(THREE, 0)];
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn index_col_out_of_range_test() {
+        let mut mat = Matrix3x3::new_row_major();
+
+        mat[(0, THREE)] = 1;
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn at_out_of_range_test() {
+        Matrix3x3::new_row_major().at(0, THREE);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn col_at_out_of_range_test() {
+        Matrix3x3::new_row_major().col_at(THREE);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn set_row_out_of_range_test() {
+        Matrix3x3::new_row_major().set_row(THREE, Default::default());
+    }
+
+    #[test]
+    fn row_col_test() {
+        let v = random_vec9d();
+        let mat = Matrix3x3::from(v);
+
+        for i in 0..THREE {
+            for j in 0..THREE {
+                assert_eq!(mat.row(i)[j], v[i * THREE + j]);
+                assert_eq!(mat.col(j)[i], v[i * THREE + j]);
+            }
+        }
+    }
+
+    #[test]
+    fn set_row_col_test() {
+        let mut mat = Matrix3x3::default();
+        let row = [1, 2, 3];
+        let col = [-1, -2, -3];
+
+        mat.set_row(1, row);
+        assert_eq!(mat.row(1), row);
+
+        mat.set_col(0, col);
+        assert_eq!(mat.col(0), col);
+        assert_eq!(mat.row(1)[0], -2);
+        assert_eq!(mat.row(1)[1], 2);
+    }
+
+    #[test]
+    fn iter_col_major_test() {
+        let v = random_vec9d();
+        let mat = Matrix3x3::from(v);
+        let collected: Vec<i32> = mat.iter().copied().collect();
+
+        let mut expected = Vec::new();
+        for j in 0..THREE {
+            for i in 0..THREE {
+                expected.push(v[i * THREE + j]);
+            }
+        }
+
+        assert_eq!(collected, expected);
+        assert_eq!(mat.to_col_major_array().to_vec(), expected);
+    }
+
+    #[test]
+    fn to_row_major_array_test() {
+        let v = random_vec9d();
+
+        assert_eq!(Matrix3x3::from(v).to_row_major_array(), v);
+    }
 }
@@ -0,0 +1,665 @@
+use super::constants::*;
+use super::layout::{GpuMat4, GpuVec4};
+use super::text::{self, ParseMathError};
+use super::Vector3D;
+
+use std::{
+    fmt::{self, Debug, Display, Formatter},
+    ops::{Add, Index, IndexMut, Mul, Sub},
+    str::FromStr,
+};
+
+pub type Vec4F = [f32; FOUR];
+pub type Vec16F = [f32; SIXTEEN];
+pub type Mat4x4F = [Vec4F; FOUR];
+
+/// Float 4x4 matrix for transforms, stored column major like [`Matrix4x4`].
+///
+/// Transforms follow the OpenGL conventions: column vectors multiplied on
+/// the right, a right-handed view space looking down `-z`, and clip space
+/// depth in `[-w, w]`.
+///
+/// [`Matrix4x4`]: super::Matrix4x4
+#[repr(C)]
+#[derive(Clone, Copy, Default, PartialEq)]
+pub struct Matrix4x4F32 {
+    inner: Mat4x4F,
+}
+
+impl Debug for Matrix4x4F32 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "\n----------------------------")?;
+        writeln!(f, "{:?}", self.inner[0])?;
+        writeln!(f, "{:?}", self.inner[1])?;
+        writeln!(f, "{:?}", self.inner[2])?;
+        writeln!(f, "{:?}", self.inner[3])?;
+        writeln!(f, "----------------------------")
+    }
+}
+
+/// Row-major text, `[[n00, n01, ..], [n10, ..], ..]`, like the integer
+/// matrices.
+impl Display for Matrix4x4F32 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        text::write_matrix(f, &self.rows())
+    }
+}
+
+impl FromStr for Matrix4x4F32 {
+    type Err = ParseMathError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rows = text::parse_rows::<f32>(s, FOUR, FOUR)?;
+        let mut mat = Matrix4x4F32::default();
+
+        for (i, row) in rows.iter().enumerate() {
+            for (j, &value) in row.iter().enumerate() {
+                mat.inner[j][i] = value;
+            }
+        }
+
+        return Ok(mat);
+    }
+}
+
+impl Matrix4x4F32 {
+    pub fn identity() -> Self {
+        let mut result = Self::default();
+
+        for i in 0..FOUR {
+            result.inner[i][i] = 1.0;
+        }
+
+        return result;
+    }
+
+    pub fn translation(offset: &Vector3D) -> Self {
+        let mut result = Self::identity();
+        result.inner[3] = [offset.x(), offset.y(), offset.z(), 1.0];
+
+        return result;
+    }
+
+    pub fn scale(factors: &Vector3D) -> Self {
+        let mut result = Self::identity();
+        result.inner[0][0] = factors.x();
+        result.inner[1][1] = factors.y();
+        result.inner[2][2] = factors.z();
+
+        return result;
+    }
+
+    /// Counterclockwise rotation by `angle` radians about the x axis.
+    pub fn rotation_x(angle: f32) -> Self {
+        let (s, c) = angle.sin_cos();
+
+        return Self::from([
+            1.0, 0.0, 0.0, 0.0, //
+            0.0, c, -s, 0.0, //
+            0.0, s, c, 0.0, //
+            0.0, 0.0, 0.0, 1.0,
+        ]);
+    }
+
+    /// Counterclockwise rotation by `angle` radians about the y axis.
+    pub fn rotation_y(angle: f32) -> Self {
+        let (s, c) = angle.sin_cos();
+
+        return Self::from([
+            c, 0.0, s, 0.0, //
+            0.0, 1.0, 0.0, 0.0, //
+            -s, 0.0, c, 0.0, //
+            0.0, 0.0, 0.0, 1.0,
+        ]);
+    }
+
+    /// Counterclockwise rotation by `angle` radians about the z axis.
+    pub fn rotation_z(angle: f32) -> Self {
+        let (s, c) = angle.sin_cos();
+
+        return Self::from([
+            c, -s, 0.0, 0.0, //
+            s, c, 0.0, 0.0, //
+            0.0, 0.0, 1.0, 0.0, //
+            0.0, 0.0, 0.0, 1.0,
+        ]);
+    }
+
+    /// Perspective projection with a vertical field of view of `fov_y`
+    /// radians.
+    pub fn perspective(fov_y: f32, aspect: f32, near: f32, far: f32) -> Self {
+        let f = 1.0 / (fov_y / 2.0).tan();
+        let depth = near - far;
+
+        return Self::from([
+            f / aspect,
+            0.0,
+            0.0,
+            0.0,
+            0.0,
+            f,
+            0.0,
+            0.0,
+            0.0,
+            0.0,
+            (far + near) / depth,
+            2.0 * far * near / depth,
+            0.0,
+            0.0,
+            -1.0,
+            0.0,
+        ]);
+    }
+
+    pub fn orthographic(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Self {
+        let (w, h, d) = (right - left, top - bottom, far - near);
+
+        return Self::from([
+            2.0 / w,
+            0.0,
+            0.0,
+            -(right + left) / w,
+            0.0,
+            2.0 / h,
+            0.0,
+            -(top + bottom) / h,
+            0.0,
+            0.0,
+            -2.0 / d,
+            -(far + near) / d,
+            0.0,
+            0.0,
+            0.0,
+            1.0,
+        ]);
+    }
+
+    /// View matrix of a camera at `eye` looking at `target`.
+    pub fn look_at(eye: &Vector3D, target: &Vector3D, up: &Vector3D) -> Self {
+        let forward = (*target - *eye).normalize();
+        let right = forward.cross(up).normalize();
+        let up = right.cross(&forward);
+
+        return Self::from([
+            right.x(),
+            right.y(),
+            right.z(),
+            -right.dot(eye),
+            up.x(),
+            up.y(),
+            up.z(),
+            -up.dot(eye),
+            -forward.x(),
+            -forward.y(),
+            -forward.z(),
+            forward.dot(eye),
+            0.0,
+            0.0,
+            0.0,
+            1.0,
+        ]);
+    }
+
+    /// Element at row `i`, column `j`.
+    ///
+    /// Panics if either index is out of range.
+    pub fn at(&self, i: usize, j: usize) -> f32 {
+        return self[(i, j)];
+    }
+
+    /// Element at row `i`, column `j`, or `None` if out of range.
+    pub fn get(&self, i: usize, j: usize) -> Option<f32> {
+        if i < FOUR && j < FOUR {
+            return Some(self.inner[j][i]);
+        }
+
+        return None;
+    }
+
+    fn rows(&self) -> [Vec4F; FOUR] {
+        return std::array::from_fn(|i| self.row(i));
+    }
+
+    /// Column `i`, stored contiguously since the matrix is column major.
+    pub fn col_at(&self, i: usize) -> &Vec4F {
+        check_index(i);
+
+        return &self.inner[i];
+    }
+
+    pub fn col(&self, i: usize) -> Vec4F {
+        return *self.col_at(i);
+    }
+
+    pub fn row(&self, i: usize) -> Vec4F {
+        check_index(i);
+
+        return self.inner.map(|col| col[i]);
+    }
+
+    pub fn set_col(&mut self, i: usize, col: Vec4F) {
+        check_index(i);
+
+        self.inner[i] = col;
+    }
+
+    pub fn set_row(&mut self, i: usize, row: Vec4F) {
+        check_index(i);
+
+        for (column, value) in self.inner.iter_mut().zip(row) {
+            column[i] = value;
+        }
+    }
+
+    /// Iterates over the elements in column-major order.
+    pub fn iter(&self) -> impl Iterator<Item = &f32> {
+        self.inner.iter().flatten()
+    }
+
+    pub fn to_col_major_array(self) -> Vec16F {
+        let mut v: Vec16F = Default::default();
+
+        for (e, &n) in v.iter_mut().zip(self.iter()) {
+            *e = n;
+        }
+
+        return v;
+    }
+
+    /// Inverse of `From<Vec16F>`.
+    pub fn to_row_major_array(self) -> Vec16F {
+        let mut v: Vec16F = Default::default();
+
+        for i in 0..FOUR {
+            for j in 0..FOUR {
+                v[i * FOUR + j] = self.inner[j][i];
+            }
+        }
+
+        return v;
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut result = Self::default();
+
+        for i in 0..FOUR {
+            for j in 0..FOUR {
+                result.inner[i][j] = self.inner[j][i];
+            }
+        }
+
+        return result;
+    }
+
+    /// Inverse by Gauss-Jordan elimination with partial pivoting, or `None`
+    /// if the matrix is singular.
+    pub fn inverse(&self) -> Option<Self> {
+        let mut a = self.rows();
+        let mut b = Self::identity().rows();
+
+        for col in 0..FOUR {
+            let mut pivot = col;
+            for row in col + 1..FOUR {
+                if a[row][col].abs() > a[pivot][col].abs() {
+                    pivot = row;
+                }
+            }
+            if a[pivot][col] == 0.0 {
+                return None;
+            }
+            a.swap(col, pivot);
+            b.swap(col, pivot);
+
+            let scale = 1.0 / a[col][col];
+            for j in 0..FOUR {
+                a[col][j] *= scale;
+                b[col][j] *= scale;
+            }
+
+            for row in 0..FOUR {
+                if row == col {
+                    continue;
+                }
+                let factor = a[row][col];
+                for j in 0..FOUR {
+                    a[row][j] -= factor * a[col][j];
+                    b[row][j] -= factor * b[col][j];
+                }
+            }
+        }
+
+        let mut result = Self::default();
+        for (i, row) in b.into_iter().enumerate() {
+            result.set_row(i, row);
+        }
+
+        return Some(result);
+    }
+
+    /// Transforms a point (`w = 1`) and divides by the resulting `w`.
+    pub fn transform_point(&self, p: &Vector3D) -> Vector3D {
+        let v = *self * [p.x(), p.y(), p.z(), 1.0];
+
+        return Vector3D::create(v[0] / v[3], v[1] / v[3], v[2] / v[3]);
+    }
+
+    /// Transforms a direction (`w = 0`), ignoring the translation.
+    pub fn transform_vector(&self, v: &Vector3D) -> Vector3D {
+        let v = *self * [v.x(), v.y(), v.z(), 0.0];
+
+        return Vector3D::create(v[0], v[1], v[2]);
+    }
+}
+
+fn check_index(i: usize) {
+    assert!(i < FOUR, "index {i} out of range for a 4x4 matrix");
+}
+
+/// Indexed by `(row, column)`.
+impl Index<(usize, usize)> for Matrix4x4F32 {
+    type Output = f32;
+
+    fn index(&self, (i, j): (usize, usize)) -> &Self::Output {
+        check_index(i);
+        check_index(j);
+
+        return &self.inner[j][i];
+    }
+}
+
+impl IndexMut<(usize, usize)> for Matrix4x4F32 {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut Self::Output {
+        check_index(i);
+        check_index(j);
+
+        return &mut self.inner[j][i];
+    }
+}
+
+impl From<Vec16F> for Matrix4x4F32 {
+    fn from(v: Vec16F) -> Self {
+        let mut mat = Matrix4x4F32::default();
+
+        for i in 0..FOUR {
+            for j in 0..FOUR {
+                mat.inner[j][i] = v[i * FOUR + j];
+            }
+        }
+
+        return mat;
+    }
+}
+
+impl From<&super::Matrix4x4> for Matrix4x4F32 {
+    fn from(m: &super::Matrix4x4) -> Self {
+        return Self::from(m.to_row_major_array().map(|e| e as f32));
+    }
+}
+
+impl From<&Matrix4x4F32> for GpuMat4 {
+    fn from(m: &Matrix4x4F32) -> Self {
+        let mut result = GpuMat4::default();
+
+        for i in 0..FOUR {
+            result.cols[i] = GpuVec4::from(m.inner[i]);
+        }
+
+        return result;
+    }
+}
+
+impl Add for Matrix4x4F32 {
+    type Output = Matrix4x4F32;
+
+    fn add(self, other: Self) -> Self::Output {
+        let mut result: Matrix4x4F32 = Default::default();
+
+        for i in 0..FOUR {
+            for j in 0..FOUR {
+                result.inner[i][j] = self.inner[i][j] + other.inner[i][j];
+            }
+        }
+
+        return result;
+    }
+}
+
+impl Sub for Matrix4x4F32 {
+    type Output = Matrix4x4F32;
+
+    fn sub(self, other: Self) -> Self::Output {
+        let mut result: Matrix4x4F32 = Default::default();
+
+        for i in 0..FOUR {
+            for j in 0..FOUR {
+                result.inner[i][j] = self.inner[i][j] - other.inner[i][j];
+            }
+        }
+
+        return result;
+    }
+}
+
+impl Mul<f32> for Matrix4x4F32 {
+    type Output = Matrix4x4F32;
+
+    fn mul(self, scalar: f32) -> Self::Output {
+        let mut result: Matrix4x4F32 = Default::default();
+
+        for i in 0..FOUR {
+            for j in 0..FOUR {
+                result.inner[i][j] = self.inner[i][j] * scalar;
+            }
+        }
+
+        return result;
+    }
+}
+
+impl Mul<Matrix4x4F32> for Matrix4x4F32 {
+    type Output = Matrix4x4F32;
+
+    fn mul(self, other: Matrix4x4F32) -> Self::Output {
+        let mut result: Matrix4x4F32 = Default::default();
+
+        for j in 0..FOUR {
+            result.inner[j] = self * other.inner[j];
+        }
+
+        return result;
+    }
+}
+
+/// Matrix times column vector.
+impl Mul<Vec4F> for Matrix4x4F32 {
+    type Output = Vec4F;
+
+    fn mul(self, v: Vec4F) -> Self::Output {
+        let mut result: Vec4F = Default::default();
+
+        for (column, s) in self.inner.iter().zip(v) {
+            for (r, m) in result.iter_mut().zip(column) {
+                *r += m * s;
+            }
+        }
+
+        return result;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use crate::math::random::random_f32;
+
+    use super::*;
+
+    fn random_mat4x4() -> Matrix4x4F32 {
+        let mut mat = Matrix4x4F32::default();
+
+        for i in 0..FOUR {
+            for j in 0..FOUR {
+                mat.inner[i][j] = random_f32() % 100.0;
+            }
+        }
+
+        return mat;
+    }
+
+    fn assert_close(a: &Matrix4x4F32, b: &Matrix4x4F32) {
+        for (x, y) in a.iter().zip(b.iter()) {
+            assert!((x - y).abs() < 1e-4, "{a} != {b}");
+        }
+    }
+
+    fn assert_close_vec(a: &Vector3D, b: &Vector3D) {
+        assert!((*a - *b).magnitude() < 1e-5, "{a} != {b}");
+    }
+
+    #[test]
+    fn from_vec16f_test() {
+        let v: Vec16F = std::array::from_fn(|i| i as f32);
+        let mat = Matrix4x4F32::from(v);
+
+        for i in 0..FOUR {
+            for j in 0..FOUR {
+                assert_eq!(mat.at(i, j), v[i * FOUR + j]);
+            }
+        }
+        assert_eq!(mat.to_row_major_array(), v);
+    }
+
+    #[test]
+    fn from_i32_matrix_test() {
+        let mat = Matrix4x4F32::from(&super::super::Matrix4x4::new_row_major());
+
+        assert_eq!(mat.at(0, 1), 4.0);
+        assert_eq!(mat.at(1, 0), 1.0);
+    }
+
+    #[test]
+    fn matrix_mul_test() {
+        let left = random_mat4x4();
+        let right = random_mat4x4();
+        let result = left * right;
+
+        for i in 0..FOUR {
+            for j in 0..FOUR {
+                let mut expected = 0.0;
+                for k in 0..FOUR {
+                    expected += left.at(i, k) * right.at(k, j);
+                }
+                assert!((result.at(i, j) - expected).abs() < 1e-2);
+            }
+        }
+    }
+
+    #[test]
+    fn identity_test() {
+        let mat = random_mat4x4();
+
+        assert_eq!(mat * Matrix4x4F32::identity(), mat);
+        assert_eq!(Matrix4x4F32::identity() * mat, mat);
+    }
+
+    #[test]
+    fn transpose_test() {
+        let mat = random_mat4x4();
+
+        for i in 0..FOUR {
+            assert_eq!(mat.transpose().row(i), mat.col(i));
+        }
+    }
+
+    #[test]
+    fn inverse_test() {
+        let mat = Matrix4x4F32::translation(&Vector3D::create(1.0, 2.0, 3.0))
+            * Matrix4x4F32::rotation_y(0.7)
+            * Matrix4x4F32::scale(&Vector3D::create(2.0, 3.0, 4.0));
+        let inverse = mat.inverse().unwrap();
+
+        assert_close(&(mat * inverse), &Matrix4x4F32::identity());
+        assert_close(&(inverse * mat), &Matrix4x4F32::identity());
+        assert_eq!(Matrix4x4F32::default().inverse(), None);
+    }
+
+    #[test]
+    fn transform_test() {
+        let p = Vector3D::create(1.0, 0.0, 0.0);
+
+        let moved = Matrix4x4F32::translation(&Vector3D::create(0.0, 2.0, 0.0));
+        assert_close_vec(&moved.transform_point(&p), &Vector3D::create(1.0, 2.0, 0.0));
+        assert_close_vec(&moved.transform_vector(&p), &p);
+
+        let y = Vector3D::create(0.0, 1.0, 0.0);
+        let z = Vector3D::create(0.0, 0.0, 1.0);
+        assert_close_vec(&Matrix4x4F32::rotation_z(FRAC_PI_2).transform_point(&p), &y);
+        assert_close_vec(&Matrix4x4F32::rotation_x(FRAC_PI_2).transform_point(&y), &z);
+        assert_close_vec(&Matrix4x4F32::rotation_y(FRAC_PI_2).transform_point(&z), &p);
+    }
+
+    #[test]
+    fn perspective_test() {
+        let proj = Matrix4x4F32::perspective(FRAC_PI_2, 2.0, 1.0, 10.0);
+
+        // The near and far planes map to -1 and 1.
+        let near = proj.transform_point(&Vector3D::create(0.0, 0.0, -1.0));
+        let far = proj.transform_point(&Vector3D::create(0.0, 0.0, -10.0));
+        assert!((near.z() + 1.0).abs() < 1e-5);
+        assert!((far.z() - 1.0).abs() < 1e-5);
+
+        // 90 degrees vertically: the top edge at depth d is at y = d.
+        let top = proj.transform_point(&Vector3D::create(0.0, 5.0, -5.0));
+        assert!((top.y() - 1.0).abs() < 1e-5);
+        let right = proj.transform_point(&Vector3D::create(10.0, 0.0, -5.0));
+        assert!((right.x() - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn orthographic_test() {
+        let proj = Matrix4x4F32::orthographic(-2.0, 2.0, -1.0, 1.0, 0.5, 5.0);
+
+        assert_close_vec(
+            &proj.transform_point(&Vector3D::create(2.0, -1.0, -0.5)),
+            &Vector3D::create(1.0, -1.0, -1.0),
+        );
+        assert_close_vec(
+            &proj.transform_point(&Vector3D::create(-2.0, 1.0, -5.0)),
+            &Vector3D::create(-1.0, 1.0, 1.0),
+        );
+    }
+
+    #[test]
+    fn look_at_test() {
+        let eye = Vector3D::create(0.0, 0.0, 5.0);
+        let view = Matrix4x4F32::look_at(
+            &eye,
+            &Vector3D::create(0.0, 0.0, 0.0),
+            &Vector3D::create(0.0, 1.0, 0.0),
+        );
+
+        assert_close_vec(&view.transform_point(&eye), &Vector3D::default());
+        assert_close_vec(
+            &view.transform_point(&Vector3D::create(1.0, 0.0, 0.0)),
+            &Vector3D::create(1.0, 0.0, -5.0),
+        );
+    }
+
+    #[test]
+    fn display_round_trip_test() {
+        let mat = Matrix4x4F32::rotation_z(0.3);
+
+        assert_eq!(mat.to_string().parse::<Matrix4x4F32>(), Ok(mat));
+        assert_eq!(
+            Matrix4x4F32::identity().to_string(),
+            "[[1, 0, 0, 0], [0, 1, 0, 0], [0, 0, 1, 0], [0, 0, 0, 1]]"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn index_out_of_range_test() {
+        let _ = Matrix4x4F32::identity()[(0, FOUR)];
+    }
+}
@@ -1,29 +1,56 @@
 use super::constants::*;
+use super::layout::{GpuMat4, GpuVec4};
+use super::text::{self, ParseMathError};
 
 use std::{
-    fmt::Debug,
-    ops::{Add, Mul, Sub},
+    fmt::{self, Debug, Display, Formatter},
+    ops::{Add, Index, IndexMut, Mul, Sub},
+    str::FromStr,
 };
 
 pub type Vec4D = [i32; FOUR];
 pub type Vec16D = [i32; SIXTEEN];
 pub type Mat4x4 = [Vec4D; FOUR];
 
-#[derive(Copy, Clone)]
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
 pub struct Matrix4x4 {
     inner: Mat4x4,
 }
 
 impl Debug for Matrix4x4 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "\n----------------------------");
-        writeln!(f, "{:?}", self.inner[0]);
-        writeln!(f, "{:?}", self.inner[1]);
-        writeln!(f, "{:?}", self.inner[2]);
-        writeln!(f, "{:?}", self.inner[3]);
-        writeln!(f, "----------------------------");
+        writeln!(f, "\n----------------------------")?;
+        writeln!(f, "{:?}", self.inner[0])?;
+        writeln!(f, "{:?}", self.inner[1])?;
+        writeln!(f, "{:?}", self.inner[2])?;
+        writeln!(f, "{:?}", self.inner[3])?;
+        writeln!(f, "----------------------------")
+    }
+}
+
+/// Row-major text, `[[n00, n01, ..], [n10, ..], ..]`. The alternate flag
+/// (`{:#}`) writes one row per line with aligned columns.
+impl Display for Matrix4x4 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        text::write_matrix(f, &self.rows())
+    }
+}
+
+impl FromStr for Matrix4x4 {
+    type Err = ParseMathError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rows = text::parse_rows::<i32>(s, FOUR, FOUR)?;
+        let mut mat = Matrix4x4::default();
+
+        for (i, row) in rows.iter().enumerate() {
+            for (j, &value) in row.iter().enumerate() {
+                mat.inner[j][i] = value;
+            }
+        }
 
-        Ok(())
+        return Ok(mat);
     }
 }
 
@@ -52,6 +79,7 @@ impl Matrix4x4 {
         return result;
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn new(
         n00: i32,
         n01: i32,
@@ -80,27 +108,109 @@ impl Matrix4x4 {
         }
     }
 
-    /// column major matrix
-    fn at(&self, i: usize, j: usize) -> i32 {
-        assert!(i >= ZERO && i <= FOUR);
-        assert!(j >= ZERO && j <= FOUR);
+    /// Element at row `i`, column `j`.
+    ///
+    /// Panics if either index is out of range.
+    pub fn at(&self, i: usize, j: usize) -> i32 {
+        return self[(i, j)];
+    }
+
+    /// Element at row `i`, column `j`, or `None` if out of range.
+    pub fn get(&self, i: usize, j: usize) -> Option<i32> {
+        if i < FOUR && j < FOUR {
+            return Some(self.inner[j][i]);
+        }
+
+        return None;
+    }
 
-        return self.inner[j][i];
+    fn rows(&self) -> [Vec4D; FOUR] {
+        return std::array::from_fn(|i| self.row(i));
     }
 
-    fn col_at(&self, i: usize) -> &Vec4D {
-        assert!(i >= ZERO && i <= FOUR);
+    /// Column `i`, stored contiguously since the matrix is column major.
+    pub fn col_at(&self, i: usize) -> &Vec4D {
+        check_index(i);
 
         return &self.inner[i];
     }
-}
 
-impl Default for Matrix4x4 {
-    fn default() -> Self {
-        Self {
-            inner: Default::default(),
+    pub fn col(&self, i: usize) -> Vec4D {
+        return *self.col_at(i);
+    }
+
+    pub fn row(&self, i: usize) -> Vec4D {
+        check_index(i);
+
+        return self.inner.map(|col| col[i]);
+    }
+
+    pub fn set_col(&mut self, i: usize, col: Vec4D) {
+        check_index(i);
+
+        self.inner[i] = col;
+    }
+
+    pub fn set_row(&mut self, i: usize, row: Vec4D) {
+        check_index(i);
+
+        for (column, value) in self.inner.iter_mut().zip(row) {
+            column[i] = value;
         }
     }
+
+    /// Iterates over the elements in column-major order.
+    pub fn iter(&self) -> impl Iterator<Item = &i32> {
+        self.inner.iter().flatten()
+    }
+
+    pub fn to_col_major_array(self) -> Vec16D {
+        let mut v: Vec16D = Default::default();
+
+        for (e, &n) in v.iter_mut().zip(self.iter()) {
+            *e = n;
+        }
+
+        return v;
+    }
+
+    /// Inverse of `From<Vec16D>`.
+    pub fn to_row_major_array(self) -> Vec16D {
+        let mut v: Vec16D = Default::default();
+
+        for i in 0..FOUR {
+            for j in 0..FOUR {
+                v[i * FOUR + j] = self.inner[j][i];
+            }
+        }
+
+        return v;
+    }
+}
+
+fn check_index(i: usize) {
+    assert!(i < FOUR, "index {i} out of range for a 4x4 matrix");
+}
+
+/// Indexed by `(row, column)`.
+impl Index<(usize, usize)> for Matrix4x4 {
+    type Output = i32;
+
+    fn index(&self, (i, j): (usize, usize)) -> &Self::Output {
+        check_index(i);
+        check_index(j);
+
+        return &self.inner[j][i];
+    }
+}
+
+impl IndexMut<(usize, usize)> for Matrix4x4 {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut Self::Output {
+        check_index(i);
+        check_index(j);
+
+        return &mut self.inner[j][i];
+    }
 }
 
 impl From<Vec16D> for Matrix4x4 {
@@ -131,6 +241,18 @@ impl From<i32> for Matrix4x4 {
     }
 }
 
+impl From<&Matrix4x4> for GpuMat4 {
+    fn from(m: &Matrix4x4) -> Self {
+        let mut result = GpuMat4::default();
+
+        for i in 0..FOUR {
+            result.cols[i] = GpuVec4::from(m.inner[i].map(|e| e as f32));
+        }
+
+        return result;
+    }
+}
+
 impl Add for Matrix4x4 {
     type Output = Matrix4x4;
 
@@ -281,6 +403,7 @@ mod tests {
 
     use super::*;
 
+    #[allow(clippy::needless_range_loop)]
     fn random_vec16d() -> Vec16D {
         let mut v: Vec16D = Default::default();
 
@@ -291,6 +414,7 @@ mod tests {
         return v;
     }
 
+    #[allow(clippy::type_complexity)]
     fn random_tuple_16d_i32() -> (
         i32,
         i32,
@@ -565,4 +689,177 @@ mod tests {
 
         assert_eq!(expected, result);
     }
+
+    #[test]
+    fn display_test() {
+        let mat = Matrix4x4::from([1, -2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 116]);
+
+        assert_eq!(
+            format!("{}", mat),
+            "[[1, -2, 3, 4], [5, 6, 7, 8], [9, 10, 11, 12], [13, 14, 15, 116]]"
+        );
+        assert_eq!(format!("{:5}", mat), "[[    1,    -2,     3,     4], [    5,     6,     7,     8], [    9,    10,    11,    12], [   13,    14,    15,   116]]");
+    }
+
+    #[test]
+    fn display_pretty_test() {
+        let mat = Matrix4x4::from([1, -2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 116]);
+
+        assert_eq!(
+            format!("{:#}", mat),
+            "[[  1,  -2,   3,   4],\n [  5,   6,   7,   8],\n [  9,  10,  11,  12],\n [ 13,  14,  15, 116]]"
+        );
+    }
+
+    #[test]
+    fn from_str_test() {
+        let mat = Matrix4x4::from([1, -2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 116]);
+
+        assert_eq!(
+            "[[1,-2,3,4],[5,6,7,8],[9,10,11,12],[13,14,15,116]]".parse::<Matrix4x4>(),
+            Ok(mat)
+        );
+        assert_eq!(format!("{:#}", mat).parse::<Matrix4x4>(), Ok(mat));
+        assert_eq!(format!("{:5}", mat).parse::<Matrix4x4>(), Ok(mat));
+    }
+
+    #[test]
+    fn from_str_errors_test() {
+        assert!("[[1, 2], [3, 4]]".parse::<Matrix4x4>().is_err());
+        assert!("[1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1]"
+            .parse::<Matrix4x4>()
+            .is_err());
+        assert!(
+            "[[1.5, -2, 3, 4], [5, 6, 7, 8], [9, 10, 11, 12], [13, 14, 15, 116]]"
+                .parse::<Matrix4x4>()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn display_round_trip_test() {
+        let mat = random_mat4x4();
+
+        assert_eq!(mat.to_string().parse::<Matrix4x4>(), Ok(mat));
+    }
+
+    #[test]
+    fn debug_test() {
+        let text = format!("{:?}", Matrix4x4::new_row_major());
+
+        assert!(text.starts_with("\n---"));
+        assert_eq!(text.lines().count(), 7);
+    }
+
+    #[test]
+    fn index_test() {
+        let v = random_vec16d();
+        let mut mat = Matrix4x4::from(v);
+
+        for i in 0..FOUR {
+            for j in 0..FOUR {
+                assert_eq!(mat[(i, j)], v[i * FOUR + j]);
+                assert_eq!(mat.get(i, j), Some(v[i * FOUR + j]));
+            }
+        }
+
+        mat[(1, 2)] = 42;
+        assert_eq!(mat.at(1, 2), 42);
+        assert_eq!(mat.col_at(2)[1], 42);
+    }
+
+    #[test]
+    fn get_out_of_range_test() {
+        let mat = Matrix4x4::new_row_major();
+
+        assert_eq!(mat.get(FOUR, 0), None);
+        assert_eq!(mat.get(0, FOUR), None);
+        assert_eq!(mat.get(usize::MAX, usize::MAX), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn index_row_out_of_range_test() {
+        let mat = Matrix4x4::new_row_major();
+
+        let _ = mat[(FOUR, 0)];
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn index_col_out_of_range_test() {
+        let mut mat = Matrix4x4::new_row_major();
+
+        mat[(0, FOUR)] = 1;
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn at_out_of_range_test() {
+        Matrix4x4::new_row_major().at(0, FOUR);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn col_at_out_of_range_test() {
+        Matrix4x4::new_row_major().col_at(FOUR);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn set_row_out_of_range_test() {
+        Matrix4x4::new_row_major().set_row(FOUR, Default::default());
+    }
+
+    #[test]
+    fn row_col_test() {
+        let v = random_vec16d();
+        let mat = Matrix4x4::from(v);
+
+        for i in 0..FOUR {
+            for j in 0..FOUR {
+                assert_eq!(mat.row(i)[j], v[i * FOUR + j]);
+                assert_eq!(mat.col(j)[i], v[i * FOUR + j]);
+            }
+        }
+    }
+
+    #[test]
+    fn set_row_col_test() {
+        let mut mat = Matrix4x4::default();
+        let row = [1, 2, 3, 4];
+        let col = [-1, -2, -3, -4];
+
+        mat.set_row(1, row);
+        assert_eq!(mat.row(1), row);
+
+        mat.set_col(0, col);
+        assert_eq!(mat.col(0), col);
+        assert_eq!(mat.row(1)[0], -2);
+        assert_eq!(mat.row(1)[1], 2);
+    }
+
+    #[test]
+    fn iter_col_major_test() {
+        let v = random_vec16d();
+        let mat = Matrix4x4::from(v);
+        let collected: Vec<i32> = mat.iter().copied().collect();
+
+        let mut expected = Vec::new();
+        for j in 0..FOUR {
+            for i in 0..FOUR {
+                expected.push(v[i * FOUR + j]);
+            }
+        }
+
+        assert_eq!(collected, expected);
+        assert_eq!(mat.to_col_major_array().to_vec(), expected);
+    }
+
+    #[test]
+    fn to_row_major_array_test() {
+        let v = random_vec16d();
+
+        assert_eq!(Matrix4x4::from(v).to_row_major_array(), v);
+    }
 }
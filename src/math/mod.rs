@@ -1,13 +1,25 @@
+#![allow(clippy::needless_return)]
+
 pub use mat3x3_i32::Mat3x3;
 pub use mat3x3_i32::Matrix3x3;
+pub use mat4x4_f32::Matrix4x4F32;
 pub use mat4x4_i32::Mat4x4;
 pub use mat4x4_i32::Matrix4x4;
+pub use quaternion::Quaternion;
+pub use text::ParseMathError;
 pub use vector2d::Vector2D;
 pub use vector3d::Vector3D;
 
 pub mod constants;
+#[cfg(test)]
+mod laws;
+pub mod layout;
 pub mod mat3x3_i32;
+pub mod mat4x4_f32;
 pub mod mat4x4_i32;
+pub mod predicates;
+pub mod quaternion;
 pub mod random;
+pub mod text;
 pub mod vector2d;
 pub mod vector3d;
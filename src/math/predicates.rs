@@ -0,0 +1,626 @@
+//! Robust geometric predicates.
+//!
+//! Port of Shewchuk's "Adaptive Precision Floating-Point Arithmetic and Fast
+//! Robust Geometric Predicates". Each predicate first evaluates its
+//! determinant in `f64` together with a forward error bound; only when the
+//! result is too close to zero to trust the sign is the determinant
+//! recomputed exactly with floating-point expansions. The returned value is
+//! an approximation of the determinant whose *sign* is always correct.
+
+use super::{Vector2D, Vector3D};
+
+const EPSILON: f64 = f64::EPSILON / 2.0;
+const CCW_ERRBOUND_A: f64 = (3.0 + 16.0 * EPSILON) * EPSILON;
+const O3D_ERRBOUND_A: f64 = (7.0 + 56.0 * EPSILON) * EPSILON;
+const ICC_ERRBOUND_A: f64 = (10.0 + 96.0 * EPSILON) * EPSILON;
+const ISP_ERRBOUND_A: f64 = (16.0 + 224.0 * EPSILON) * EPSILON;
+
+/// Positive if `a`, `b`, `c` are in counterclockwise order, negative if
+/// clockwise and zero if they are collinear.
+pub fn orient2d(a: &Vector2D, b: &Vector2D, c: &Vector2D) -> f64 {
+    orient2d_raw(xy(a), xy(b), xy(c))
+}
+
+/// Positive if `d` lies below the plane through `a`, `b`, `c` (which appear
+/// counterclockwise when viewed from above), negative if above and zero if
+/// the four points are coplanar.
+pub fn orient3d(a: &Vector3D, b: &Vector3D, c: &Vector3D, d: &Vector3D) -> f64 {
+    orient3d_raw(xyz(a), xyz(b), xyz(c), xyz(d))
+}
+
+/// Positive if `d` lies inside the circle through `a`, `b`, `c` (given in
+/// counterclockwise order), negative if outside and zero if cocircular.
+pub fn incircle(a: &Vector2D, b: &Vector2D, c: &Vector2D, d: &Vector2D) -> f64 {
+    incircle_raw(xy(a), xy(b), xy(c), xy(d))
+}
+
+/// Positive if `e` lies inside the sphere through `a`, `b`, `c`, `d`, negative
+/// if outside and zero if cospherical. The first four points must be
+/// positively oriented (`orient3d(a, b, c, d) > 0`), otherwise the sign is
+/// reversed.
+pub fn insphere(a: &Vector3D, b: &Vector3D, c: &Vector3D, d: &Vector3D, e: &Vector3D) -> f64 {
+    insphere_raw(xyz(a), xyz(b), xyz(c), xyz(d), xyz(e))
+}
+
+fn xy(v: &Vector2D) -> [f64; 2] {
+    [v.x() as f64, v.y() as f64]
+}
+
+fn xyz(v: &Vector3D) -> [f64; 3] {
+    [v.x() as f64, v.y() as f64, v.z() as f64]
+}
+
+pub(crate) fn orient2d_raw(a: [f64; 2], b: [f64; 2], c: [f64; 2]) -> f64 {
+    let left = (a[0] - c[0]) * (b[1] - c[1]);
+    let right = (a[1] - c[1]) * (b[0] - c[0]);
+    let det = left - right;
+
+    let sum = if left > 0.0 {
+        if right <= 0.0 {
+            return det;
+        }
+        left + right
+    } else if left < 0.0 {
+        if right >= 0.0 {
+            return det;
+        }
+        -left - right
+    } else {
+        return det;
+    };
+
+    let bound = CCW_ERRBOUND_A * sum;
+    if det >= bound || -det >= bound {
+        return det;
+    }
+
+    exact::orient2d(a, b, c)
+}
+
+pub(crate) fn orient3d_raw(a: [f64; 3], b: [f64; 3], c: [f64; 3], d: [f64; 3]) -> f64 {
+    let [adx, ady, adz] = sub3(a, d);
+    let [bdx, bdy, bdz] = sub3(b, d);
+    let [cdx, cdy, cdz] = sub3(c, d);
+
+    let bdxcdy = bdx * cdy;
+    let cdxbdy = cdx * bdy;
+    let cdxady = cdx * ady;
+    let adxcdy = adx * cdy;
+    let adxbdy = adx * bdy;
+    let bdxady = bdx * ady;
+
+    let det = adz * (bdxcdy - cdxbdy) + bdz * (cdxady - adxcdy) + cdz * (adxbdy - bdxady);
+    let permanent = (bdxcdy.abs() + cdxbdy.abs()) * adz.abs()
+        + (cdxady.abs() + adxcdy.abs()) * bdz.abs()
+        + (adxbdy.abs() + bdxady.abs()) * cdz.abs();
+
+    let bound = O3D_ERRBOUND_A * permanent;
+    if det > bound || -det > bound {
+        return det;
+    }
+
+    exact::orient3d(a, b, c, d)
+}
+
+pub(crate) fn incircle_raw(a: [f64; 2], b: [f64; 2], c: [f64; 2], d: [f64; 2]) -> f64 {
+    let (adx, ady) = (a[0] - d[0], a[1] - d[1]);
+    let (bdx, bdy) = (b[0] - d[0], b[1] - d[1]);
+    let (cdx, cdy) = (c[0] - d[0], c[1] - d[1]);
+
+    let bdxcdy = bdx * cdy;
+    let cdxbdy = cdx * bdy;
+    let alift = adx * adx + ady * ady;
+
+    let cdxady = cdx * ady;
+    let adxcdy = adx * cdy;
+    let blift = bdx * bdx + bdy * bdy;
+
+    let adxbdy = adx * bdy;
+    let bdxady = bdx * ady;
+    let clift = cdx * cdx + cdy * cdy;
+
+    let det = alift * (bdxcdy - cdxbdy) + blift * (cdxady - adxcdy) + clift * (adxbdy - bdxady);
+    let permanent = (bdxcdy.abs() + cdxbdy.abs()) * alift
+        + (cdxady.abs() + adxcdy.abs()) * blift
+        + (adxbdy.abs() + bdxady.abs()) * clift;
+
+    let bound = ICC_ERRBOUND_A * permanent;
+    if det > bound || -det > bound {
+        return det;
+    }
+
+    exact::incircle(a, b, c, d)
+}
+
+pub(crate) fn insphere_raw(a: [f64; 3], b: [f64; 3], c: [f64; 3], d: [f64; 3], e: [f64; 3]) -> f64 {
+    let [aex, aey, aez] = sub3(a, e);
+    let [bex, bey, bez] = sub3(b, e);
+    let [cex, cey, cez] = sub3(c, e);
+    let [dex, dey, dez] = sub3(d, e);
+
+    let (aexbey, bexaey) = (aex * bey, bex * aey);
+    let ab = aexbey - bexaey;
+    let (bexcey, cexbey) = (bex * cey, cex * bey);
+    let bc = bexcey - cexbey;
+    let (cexdey, dexcey) = (cex * dey, dex * cey);
+    let cd = cexdey - dexcey;
+    let (dexaey, aexdey) = (dex * aey, aex * dey);
+    let da = dexaey - aexdey;
+    let (aexcey, cexaey) = (aex * cey, cex * aey);
+    let ac = aexcey - cexaey;
+    let (bexdey, dexbey) = (bex * dey, dex * bey);
+    let bd = bexdey - dexbey;
+
+    let abc = aez * bc - bez * ac + cez * ab;
+    let bcd = bez * cd - cez * bd + dez * bc;
+    let cda = cez * da + dez * ac + aez * cd;
+    let dab = dez * ab + aez * bd + bez * da;
+
+    let alift = aex * aex + aey * aey + aez * aez;
+    let blift = bex * bex + bey * bey + bez * bez;
+    let clift = cex * cex + cey * cey + cez * cez;
+    let dlift = dex * dex + dey * dey + dez * dez;
+
+    let det = (dlift * abc - clift * dab) + (blift * cda - alift * bcd);
+
+    let (aez, bez, cez, dez) = (aez.abs(), bez.abs(), cez.abs(), dez.abs());
+    let aexbey = aexbey.abs();
+    let bexaey = bexaey.abs();
+    let bexcey = bexcey.abs();
+    let cexbey = cexbey.abs();
+    let cexdey = cexdey.abs();
+    let dexcey = dexcey.abs();
+    let dexaey = dexaey.abs();
+    let aexdey = aexdey.abs();
+    let aexcey = aexcey.abs();
+    let cexaey = cexaey.abs();
+    let bexdey = bexdey.abs();
+    let dexbey = dexbey.abs();
+
+    let permanent = ((cexdey + dexcey) * bez + (dexbey + bexdey) * cez + (bexcey + cexbey) * dez)
+        * alift
+        + ((dexaey + aexdey) * cez + (aexcey + cexaey) * dez + (cexdey + dexcey) * aez) * blift
+        + ((aexbey + bexaey) * dez + (bexdey + dexbey) * aez + (dexaey + aexdey) * bez) * clift
+        + ((bexcey + cexbey) * aez + (cexaey + aexcey) * bez + (aexbey + bexaey) * cez) * dlift;
+
+    let bound = ISP_ERRBOUND_A * permanent;
+    if det > bound || -det > bound {
+        return det;
+    }
+
+    exact::insphere(a, b, c, d, e)
+}
+
+fn sub3(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+/// Exact evaluation with floating-point expansions: a value is kept as a
+/// sum of non-overlapping `f64` components ordered by increasing magnitude,
+/// so the sign of the sum is the sign of its last component.
+mod exact {
+    type Expansion = Vec<f64>;
+
+    fn two_sum(a: f64, b: f64) -> (f64, f64) {
+        let x = a + b;
+        let b_virtual = x - a;
+        let a_virtual = x - b_virtual;
+        let y = (a - a_virtual) + (b - b_virtual);
+        (x, y)
+    }
+
+    fn two_product(a: f64, b: f64) -> (f64, f64) {
+        let x = a * b;
+        (x, a.mul_add(b, -x))
+    }
+
+    /// `a - b` as an exact two-component expansion.
+    fn diff(a: f64, b: f64) -> Expansion {
+        let (x, y) = two_sum(a, -b);
+        compress(vec![y, x])
+    }
+
+    fn compress(e: Expansion) -> Expansion {
+        e.into_iter().filter(|&c| c != 0.0).collect()
+    }
+
+    /// Adds a single component to an expansion.
+    fn grow(e: &[f64], b: f64) -> Expansion {
+        let mut result = Vec::with_capacity(e.len() + 1);
+        let mut q = b;
+
+        for &c in e {
+            let (sum, err) = two_sum(q, c);
+            if err != 0.0 {
+                result.push(err);
+            }
+            q = sum;
+        }
+
+        if q != 0.0 || result.is_empty() {
+            result.push(q);
+        }
+
+        result
+    }
+
+    fn sum(e: &[f64], f: &[f64]) -> Expansion {
+        let mut result = e.to_vec();
+
+        for &c in f {
+            result = grow(&result, c);
+        }
+
+        result
+    }
+
+    fn negate(e: &[f64]) -> Expansion {
+        e.iter().map(|c| -c).collect()
+    }
+
+    fn scale(e: &[f64], b: f64) -> Expansion {
+        let mut result = Vec::new();
+
+        for &c in e {
+            let (hi, lo) = two_product(c, b);
+            result = grow(&result, lo);
+            result = grow(&result, hi);
+        }
+
+        compress(result)
+    }
+
+    fn product(e: &[f64], f: &[f64]) -> Expansion {
+        let mut result = Vec::new();
+
+        for &c in f {
+            result = sum(&result, &scale(e, c));
+        }
+
+        result
+    }
+
+    /// Largest component, which carries the sign of the whole expansion.
+    fn estimate(e: &[f64]) -> f64 {
+        e.iter().rev().copied().find(|&c| c != 0.0).unwrap_or(0.0)
+    }
+
+    /// `a * d - b * c`
+    fn det2(a: &[f64], b: &[f64], c: &[f64], d: &[f64]) -> Expansion {
+        sum(&product(a, d), &negate(&product(b, c)))
+    }
+
+    fn lift2(x: &[f64], y: &[f64]) -> Expansion {
+        sum(&product(x, x), &product(y, y))
+    }
+
+    pub(super) fn orient2d(a: [f64; 2], b: [f64; 2], c: [f64; 2]) -> f64 {
+        let (acx, acy) = (diff(a[0], c[0]), diff(a[1], c[1]));
+        let (bcx, bcy) = (diff(b[0], c[0]), diff(b[1], c[1]));
+
+        estimate(&det2(&acx, &acy, &bcx, &bcy))
+    }
+
+    pub(super) fn orient3d(a: [f64; 3], b: [f64; 3], c: [f64; 3], d: [f64; 3]) -> f64 {
+        let ad: Vec<Expansion> = (0..3).map(|i| diff(a[i], d[i])).collect();
+        let bd: Vec<Expansion> = (0..3).map(|i| diff(b[i], d[i])).collect();
+        let cd: Vec<Expansion> = (0..3).map(|i| diff(c[i], d[i])).collect();
+
+        let bc = det2(&bd[0], &bd[1], &cd[0], &cd[1]);
+        let ca = det2(&cd[0], &cd[1], &ad[0], &ad[1]);
+        let ab = det2(&ad[0], &ad[1], &bd[0], &bd[1]);
+
+        let det = sum(
+            &sum(&product(&ad[2], &bc), &product(&bd[2], &ca)),
+            &product(&cd[2], &ab),
+        );
+
+        estimate(&det)
+    }
+
+    pub(super) fn incircle(a: [f64; 2], b: [f64; 2], c: [f64; 2], d: [f64; 2]) -> f64 {
+        let (adx, ady) = (diff(a[0], d[0]), diff(a[1], d[1]));
+        let (bdx, bdy) = (diff(b[0], d[0]), diff(b[1], d[1]));
+        let (cdx, cdy) = (diff(c[0], d[0]), diff(c[1], d[1]));
+
+        let bc = det2(&bdx, &bdy, &cdx, &cdy);
+        let ca = det2(&cdx, &cdy, &adx, &ady);
+        let ab = det2(&adx, &ady, &bdx, &bdy);
+
+        let det = sum(
+            &sum(
+                &product(&lift2(&adx, &ady), &bc),
+                &product(&lift2(&bdx, &bdy), &ca),
+            ),
+            &product(&lift2(&cdx, &cdy), &ab),
+        );
+
+        estimate(&det)
+    }
+
+    pub(super) fn insphere(a: [f64; 3], b: [f64; 3], c: [f64; 3], d: [f64; 3], e: [f64; 3]) -> f64 {
+        let ae: Vec<Expansion> = (0..3).map(|i| diff(a[i], e[i])).collect();
+        let be: Vec<Expansion> = (0..3).map(|i| diff(b[i], e[i])).collect();
+        let ce: Vec<Expansion> = (0..3).map(|i| diff(c[i], e[i])).collect();
+        let de: Vec<Expansion> = (0..3).map(|i| diff(d[i], e[i])).collect();
+
+        let ab = det2(&ae[0], &ae[1], &be[0], &be[1]);
+        let bc = det2(&be[0], &be[1], &ce[0], &ce[1]);
+        let cd = det2(&ce[0], &ce[1], &de[0], &de[1]);
+        let da = det2(&de[0], &de[1], &ae[0], &ae[1]);
+        let ac = det2(&ae[0], &ae[1], &ce[0], &ce[1]);
+        let bd = det2(&be[0], &be[1], &de[0], &de[1]);
+
+        let abc = sum(
+            &sum(&product(&ae[2], &bc), &negate(&product(&be[2], &ac))),
+            &product(&ce[2], &ab),
+        );
+        let bcd = sum(
+            &sum(&product(&be[2], &cd), &negate(&product(&ce[2], &bd))),
+            &product(&de[2], &bc),
+        );
+        let cda = sum(
+            &sum(&product(&ce[2], &da), &product(&de[2], &ac)),
+            &product(&ae[2], &cd),
+        );
+        let dab = sum(
+            &sum(&product(&de[2], &ab), &product(&ae[2], &bd)),
+            &product(&be[2], &da),
+        );
+
+        let lift = |v: &[Expansion]| sum(&lift2(&v[0], &v[1]), &product(&v[2], &v[2]));
+
+        let det = sum(
+            &sum(
+                &product(&lift(&de), &abc),
+                &negate(&product(&lift(&ce), &dab)),
+            ),
+            &sum(
+                &product(&lift(&be), &cda),
+                &negate(&product(&lift(&ae), &bcd)),
+            ),
+        );
+
+        estimate(&det)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn two_sum_is_exact_test() {
+            let (x, y) = two_sum(1.0, 1e-20);
+
+            assert_eq!(x, 1.0);
+            assert_eq!(y, 1e-20);
+        }
+
+        #[test]
+        fn expansion_sum_keeps_small_terms_test() {
+            let e = sum(&[1e-30, 1.0], &[-1.0]);
+
+            assert_eq!(estimate(&e), 1e-30);
+        }
+
+        #[test]
+        fn product_is_exact_test() {
+            // (1 + 2^-30)^2 = 1 + 2^-29 + 2^-60 needs more than 53 bits.
+            let x = 1.0 + 2f64.powi(-30);
+            let p = product(&[x], &[x]);
+            let rest = sum(&p, &[-1.0, -(2f64.powi(-29))]);
+
+            assert_eq!(estimate(&rest), 2f64.powi(-60));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn sign(x: f64) -> i32 {
+        (x > 0.0) as i32 - (x < 0.0) as i32
+    }
+
+    fn naive_orient2d(a: &Vector2D, b: &Vector2D, c: &Vector2D) -> f32 {
+        (a.x() - c.x()) * (b.y() - c.y()) - (a.y() - c.y()) * (b.x() - c.x())
+    }
+
+    /// Exact orientation of points on the grid `k / 2^20` using integers.
+    fn grid_orient2d(a: [i64; 2], b: [i64; 2], c: [i64; 2]) -> i128 {
+        let (a, b, c) = (a.map(i128::from), b.map(i128::from), c.map(i128::from));
+        (a[0] - c[0]) * (b[1] - c[1]) - (a[1] - c[1]) * (b[0] - c[0])
+    }
+
+    fn on_grid(p: [i64; 2]) -> Vector2D {
+        let scale = 2f32.powi(-20);
+        Vector2D::create(p[0] as f32 * scale, p[1] as f32 * scale)
+    }
+
+    #[test]
+    fn orient2d_basic_test() {
+        let a = Vector2D::create(0.0, 0.0);
+        let b = Vector2D::create(1.0, 0.0);
+        let c = Vector2D::create(0.0, 1.0);
+
+        assert!(orient2d(&a, &b, &c) > 0.0);
+        assert!(orient2d(&a, &c, &b) < 0.0);
+        assert_eq!(orient2d(&a, &b, &Vector2D::create(2.0, 0.0)), 0.0);
+    }
+
+    #[test]
+    fn orient2d_collinear_test() {
+        let a = Vector2D::create(0.1, 0.1);
+        let b = Vector2D::create(0.3, 0.3);
+
+        for t in [-3.0f32, 0.0, 0.25, 7.0, 1e6] {
+            let c = Vector2D::create(t, t);
+            assert_eq!(orient2d(&a, &b, &c), 0.0, "t = {t}");
+        }
+    }
+
+    #[test]
+    fn orient2d_matches_exact_near_degenerate_test() {
+        let mut rng = StdRng::seed_from_u64(30);
+        let mut naive_wrong = 0;
+
+        for _ in 0..2_000 {
+            // Points close to a long line through a large offset, where the
+            // f32 determinant suffers catastrophic cancellation.
+            let base = rng.gen_range(1i64 << 20..1 << 21);
+            let a = [base, base];
+            let b = [base * 3, base * 3 + rng.gen_range(-2..=2)];
+            let c = [
+                base * 2 + rng.gen_range(-2..=2),
+                base * 2 + rng.gen_range(-2..=2),
+            ];
+
+            let expected = grid_orient2d(a, b, c).signum() as i32;
+            let (pa, pb, pc) = (on_grid(a), on_grid(b), on_grid(c));
+
+            assert_eq!(sign(orient2d(&pa, &pb, &pc)), expected);
+            if sign(naive_orient2d(&pa, &pb, &pc) as f64) != expected {
+                naive_wrong += 1;
+            }
+        }
+
+        // The naive determinant is not trustworthy on this input.
+        assert!(naive_wrong > 0);
+    }
+
+    #[test]
+    fn orient2d_exact_path_test() {
+        // Shewchuk's example: points near the line y = x, perturbed by
+        // single ulps, where the filter cannot decide.
+        let ulp = f64::EPSILON;
+        let b = [12.0, 12.0];
+        let c = [24.0, 24.0];
+
+        for i in 0..16i32 {
+            for j in 0..16i32 {
+                let a = [0.5 + i as f64 * ulp, 0.5 + j as f64 * ulp];
+                let expected = (j - i).signum();
+
+                assert_eq!(sign(orient2d_raw(a, b, c)), expected, "i = {i}, j = {j}");
+            }
+        }
+    }
+
+    #[test]
+    fn orient3d_basic_test() {
+        let a = Vector3D::create(0.0, 0.0, 0.0);
+        let b = Vector3D::create(1.0, 0.0, 0.0);
+        let c = Vector3D::create(0.0, 1.0, 0.0);
+
+        assert!(orient3d(&a, &b, &c, &Vector3D::create(0.0, 0.0, -1.0)) > 0.0);
+        assert!(orient3d(&a, &b, &c, &Vector3D::create(0.0, 0.0, 1.0)) < 0.0);
+        assert_eq!(orient3d(&a, &b, &c, &Vector3D::create(5.0, -3.0, 0.0)), 0.0);
+    }
+
+    #[test]
+    fn orient3d_near_coplanar_test() {
+        let a = [0.1, 0.2, 0.3];
+        let b = [1.1, 0.7, 0.3];
+        let c = [0.4, 1.9, 0.3];
+
+        let below = [0.5, 0.5, 0.3 - f64::EPSILON / 4.0];
+        let above = [0.5, 0.5, 0.3 + f64::EPSILON / 2.0];
+
+        assert!(orient3d_raw(a, b, c, below) > 0.0);
+        assert!(orient3d_raw(a, b, c, above) < 0.0);
+        assert_eq!(orient3d_raw(a, b, c, [7.0, -2.0, 0.3]), 0.0);
+    }
+
+    #[test]
+    fn incircle_basic_test() {
+        let a = Vector2D::create(1.0, 0.0);
+        let b = Vector2D::create(0.0, 1.0);
+        let c = Vector2D::create(-1.0, 0.0);
+
+        assert!(incircle(&a, &b, &c, &Vector2D::create(0.0, 0.0)) > 0.0);
+        assert!(incircle(&a, &b, &c, &Vector2D::create(2.0, 0.0)) < 0.0);
+        assert_eq!(incircle(&a, &b, &c, &Vector2D::create(0.0, -1.0)), 0.0);
+    }
+
+    #[test]
+    fn incircle_near_cocircular_test() {
+        // Four corners of a large offset square are exactly cocircular;
+        // nudging the fourth by one ulp must move it in or out.
+        let o = 1e8;
+        let a = [o + 1.0, o];
+        let b = [o + 1.0, o + 1.0];
+        let c = [o, o + 1.0];
+        let d = [o, o];
+
+        assert_eq!(incircle_raw(a, b, c, d), 0.0);
+
+        let ulp = o * f64::EPSILON;
+        assert!(incircle_raw(a, b, c, [o + ulp, o + ulp]) > 0.0);
+        assert!(incircle_raw(a, b, c, [o - ulp, o - ulp]) < 0.0);
+    }
+
+    #[test]
+    fn insphere_basic_test() {
+        let a = Vector3D::create(1.0, 0.0, 0.0);
+        let b = Vector3D::create(0.0, 1.0, 0.0);
+        let c = Vector3D::create(-1.0, 0.0, 0.0);
+        let d = Vector3D::create(0.0, 0.0, 1.0);
+
+        let orientation = sign(orient3d(&a, &b, &c, &d)) as f64;
+        let origin = Vector3D::create(0.0, 0.0, 0.0);
+        let far = Vector3D::create(0.0, 0.0, -3.0);
+        let on = Vector3D::create(0.0, -1.0, 0.0);
+
+        assert!(insphere(&a, &b, &c, &d, &origin) * orientation > 0.0);
+        assert!(insphere(&a, &b, &c, &d, &far) * orientation < 0.0);
+        assert_eq!(insphere(&a, &b, &c, &d, &on), 0.0);
+    }
+
+    #[test]
+    fn insphere_near_cospherical_test() {
+        let o = 1e6;
+        let a = [o + 1.0, o, o];
+        let b = [o, o + 1.0, o];
+        let c = [o - 1.0, o, o];
+        let d = [o, o, o + 1.0];
+        let orientation = sign(orient3d_raw(a, b, c, d)) as f64;
+
+        assert_eq!(insphere_raw(a, b, c, d, [o, o - 1.0, o]), 0.0);
+
+        let ulp = o * f64::EPSILON;
+        let inside = [o, o - 1.0 + ulp, o];
+        let outside = [o, o - 1.0 - ulp, o];
+        assert!(insphere_raw(a, b, c, d, inside) * orientation > 0.0);
+        assert!(insphere_raw(a, b, c, d, outside) * orientation < 0.0);
+    }
+
+    #[test]
+    fn fast_and_exact_agree_test() {
+        let mut rng = StdRng::seed_from_u64(31);
+        let mut point = || [0; 3].map(|_| rng.gen_range(-10.0..10.0));
+
+        for _ in 0..500 {
+            let (a, b, c, d, e) = (point(), point(), point(), point(), point());
+            let (a2, b2, c2, d2) = ([a[0], a[1]], [b[0], b[1]], [c[0], c[1]], [d[0], d[1]]);
+
+            assert_eq!(
+                sign(orient2d_raw(a2, b2, c2)),
+                sign(exact::orient2d(a2, b2, c2))
+            );
+            assert_eq!(
+                sign(orient3d_raw(a, b, c, d)),
+                sign(exact::orient3d(a, b, c, d))
+            );
+            assert_eq!(
+                sign(incircle_raw(a2, b2, c2, d2)),
+                sign(exact::incircle(a2, b2, c2, d2))
+            );
+            assert_eq!(
+                sign(insphere_raw(a, b, c, d, e)),
+                sign(exact::insphere(a, b, c, d, e))
+            );
+        }
+    }
+}
@@ -0,0 +1,284 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    ops::{Mul, Neg},
+};
+
+use super::{text, Matrix4x4F32, Vector3D};
+
+/// Unit quaternion `w + xi + yj + zk` representing a rotation.
+#[derive(Debug, Clone, Copy)]
+pub struct Quaternion {
+    x: f32,
+    y: f32,
+    z: f32,
+    w: f32,
+}
+
+impl Default for Quaternion {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl PartialEq for Quaternion {
+    fn eq(&self, other: &Self) -> bool {
+        ((self.x - other.x).abs() <= f32::EPSILON)
+            && ((self.y - other.y).abs() <= f32::EPSILON)
+            && ((self.z - other.z).abs() <= f32::EPSILON)
+            && ((self.w - other.w).abs() <= f32::EPSILON)
+    }
+}
+
+/// Hamilton product: `self * other` rotates by `other`, then by `self`.
+impl Mul for Quaternion {
+    type Output = Quaternion;
+
+    fn mul(self, other: Self) -> Self::Output {
+        Quaternion::create(
+            self.w * other.x + self.x * other.w + self.y * other.z - self.z * other.y,
+            self.w * other.y - self.x * other.z + self.y * other.w + self.z * other.x,
+            self.w * other.z + self.x * other.y - self.y * other.x + self.z * other.w,
+            self.w * other.w - self.x * other.x - self.y * other.y - self.z * other.z,
+        )
+    }
+}
+
+impl Neg for Quaternion {
+    type Output = Quaternion;
+
+    fn neg(self) -> Self::Output {
+        Quaternion::create(-self.x, -self.y, -self.z, -self.w)
+    }
+}
+
+/// `[x, y, z, w]`.
+impl Display for Quaternion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        text::write_vector(f, &[self.x, self.y, self.z, self.w])
+    }
+}
+
+impl Quaternion {
+    pub fn create(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Self { x, y, z, w }
+    }
+
+    pub fn identity() -> Self {
+        Self::create(0.0, 0.0, 0.0, 1.0)
+    }
+
+    /// Counterclockwise rotation by `angle` radians about `axis`, which
+    /// need not be normalized.
+    pub fn from_axis_angle(axis: &Vector3D, angle: f32) -> Self {
+        let (s, c) = (angle / 2.0).sin_cos();
+        let axis = axis.normalize() * s;
+
+        return Self::create(axis.x(), axis.y(), axis.z(), c);
+    }
+
+    /// Euler angles in radians, applied about X, then Y, then Z like the
+    /// ECS [`Transform`](crate::ecs::Transform).
+    pub fn from_euler(angles: &Vector3D) -> Self {
+        let x = Self::from_axis_angle(&Vector3D::create(1.0, 0.0, 0.0), angles.x());
+        let y = Self::from_axis_angle(&Vector3D::create(0.0, 1.0, 0.0), angles.y());
+        let z = Self::from_axis_angle(&Vector3D::create(0.0, 0.0, 1.0), angles.z());
+
+        return z * y * x;
+    }
+
+    /// The rotation in the upper 3x3 of `m`, which must be orthonormal.
+    pub fn from_rotation_matrix(m: &Matrix4x4F32) -> Self {
+        // Shepperd's method: divide by the largest of the four candidates.
+        let (m00, m11, m22) = (m.at(0, 0), m.at(1, 1), m.at(2, 2));
+        let trace = m00 + m11 + m22;
+
+        let q = if trace > 0.0 {
+            let s = (trace + 1.0).sqrt() * 2.0;
+            Self::create(
+                (m.at(2, 1) - m.at(1, 2)) / s,
+                (m.at(0, 2) - m.at(2, 0)) / s,
+                (m.at(1, 0) - m.at(0, 1)) / s,
+                s / 4.0,
+            )
+        } else if m00 > m11 && m00 > m22 {
+            let s = (1.0 + m00 - m11 - m22).sqrt() * 2.0;
+            Self::create(
+                s / 4.0,
+                (m.at(0, 1) + m.at(1, 0)) / s,
+                (m.at(0, 2) + m.at(2, 0)) / s,
+                (m.at(2, 1) - m.at(1, 2)) / s,
+            )
+        } else if m11 > m22 {
+            let s = (1.0 + m11 - m00 - m22).sqrt() * 2.0;
+            Self::create(
+                (m.at(0, 1) + m.at(1, 0)) / s,
+                s / 4.0,
+                (m.at(1, 2) + m.at(2, 1)) / s,
+                (m.at(0, 2) - m.at(2, 0)) / s,
+            )
+        } else {
+            let s = (1.0 + m22 - m00 - m11).sqrt() * 2.0;
+            Self::create(
+                (m.at(0, 2) + m.at(2, 0)) / s,
+                (m.at(1, 2) + m.at(2, 1)) / s,
+                s / 4.0,
+                (m.at(1, 0) - m.at(0, 1)) / s,
+            )
+        };
+
+        return q.normalize();
+    }
+
+    pub fn x(&self) -> f32 {
+        self.x
+    }
+
+    pub fn y(&self) -> f32 {
+        self.y
+    }
+
+    pub fn z(&self) -> f32 {
+        self.z
+    }
+
+    pub fn w(&self) -> f32 {
+        self.w
+    }
+
+    pub fn dot(&self, other: &Quaternion) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+    }
+
+    pub fn magnitude(&self) -> f32 {
+        return self.dot(self).sqrt();
+    }
+
+    pub fn normalize(&self) -> Quaternion {
+        let m = self.magnitude();
+
+        return Quaternion::create(self.x / m, self.y / m, self.z / m, self.w / m);
+    }
+
+    /// The inverse rotation of a unit quaternion.
+    pub fn conjugate(&self) -> Quaternion {
+        Quaternion::create(-self.x, -self.y, -self.z, self.w)
+    }
+
+    pub fn rotate(&self, v: &Vector3D) -> Vector3D {
+        // v' = v + 2w(u × v) + 2u × (u × v) with u the vector part.
+        let u = Vector3D::create(self.x, self.y, self.z);
+        let t = u.cross(v) * 2.0;
+
+        return *v + t * self.w + u.cross(&t);
+    }
+
+    /// Spherical interpolation along the shorter arc; `t = 0` gives
+    /// `self`.
+    pub fn slerp(&self, other: &Quaternion, t: f32) -> Quaternion {
+        let mut end = *other;
+        let mut cos = self.dot(other);
+        if cos < 0.0 {
+            end = -end;
+            cos = -cos;
+        }
+
+        // Nearly parallel: fall back to normalized linear interpolation.
+        let (a, b) = if cos > 0.9995 {
+            (1.0 - t, t)
+        } else {
+            let angle = cos.acos();
+            let sin = angle.sin();
+            (((1.0 - t) * angle).sin() / sin, (t * angle).sin() / sin)
+        };
+
+        return Quaternion::create(
+            self.x * a + end.x * b,
+            self.y * a + end.y * b,
+            self.z * a + end.z * b,
+            self.w * a + end.w * b,
+        )
+        .normalize();
+    }
+
+    pub fn to_matrix(self) -> Matrix4x4F32 {
+        let Quaternion { x, y, z, w } = self;
+
+        return Matrix4x4F32::from([
+            1.0 - 2.0 * (y * y + z * z),
+            2.0 * (x * y - w * z),
+            2.0 * (x * z + w * y),
+            0.0, //
+            2.0 * (x * y + w * z),
+            1.0 - 2.0 * (x * x + z * z),
+            2.0 * (y * z - w * x),
+            0.0, //
+            2.0 * (x * z - w * y),
+            2.0 * (y * z + w * x),
+            1.0 - 2.0 * (x * x + y * y),
+            0.0, //
+            0.0,
+            0.0,
+            0.0,
+            1.0,
+        ]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{FRAC_PI_2, PI};
+
+    use super::*;
+
+    fn assert_close(a: &Vector3D, b: &Vector3D) {
+        assert!((*a - *b).magnitude() < 1e-5, "{a} != {b}");
+    }
+
+    #[test]
+    fn rotate_test() {
+        let q = Quaternion::from_axis_angle(&Vector3D::create(0.0, 0.0, 2.0), FRAC_PI_2);
+        let v = Vector3D::create(1.0, 0.0, 0.0);
+
+        assert_close(&q.rotate(&v), &Vector3D::create(0.0, 1.0, 0.0));
+        assert_close(&q.conjugate().rotate(&q.rotate(&v)), &v);
+        assert_close(&(q * q).rotate(&v), &Vector3D::create(-1.0, 0.0, 0.0));
+        assert_eq!(Quaternion::default().rotate(&v), v);
+    }
+
+    #[test]
+    fn matrix_test() {
+        let angles = Vector3D::create(0.3, -1.2, 2.5);
+        let q = Quaternion::from_euler(&angles);
+        let m = Matrix4x4F32::rotation_z(angles.z())
+            * Matrix4x4F32::rotation_y(angles.y())
+            * Matrix4x4F32::rotation_x(angles.x());
+
+        let v = Vector3D::create(0.5, -2.0, 1.0);
+        assert_close(&q.rotate(&v), &m.transform_point(&v));
+        assert_close(&q.to_matrix().transform_point(&v), &m.transform_point(&v));
+
+        // q and -q are the same rotation.
+        let back = Quaternion::from_rotation_matrix(&m);
+        assert!((back.dot(&q).abs() - 1.0).abs() < 1e-5);
+
+        for axis in [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]] {
+            let q = Quaternion::from_axis_angle(&Vector3D::from(axis), PI * 0.99);
+            let back = Quaternion::from_rotation_matrix(&q.to_matrix());
+            assert!((back.dot(&q).abs() - 1.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn slerp_test() {
+        let z = Vector3D::create(0.0, 0.0, 1.0);
+        let a = Quaternion::identity();
+        let b = Quaternion::from_axis_angle(&z, FRAC_PI_2);
+
+        assert_eq!(a.slerp(&b, 0.0), a);
+        let half = a.slerp(&b, 0.5);
+        let expected = Quaternion::from_axis_angle(&z, FRAC_PI_2 / 2.0);
+        assert!((half.dot(&expected) - 1.0).abs() < 1e-6);
+        // The shorter arc is taken even when the signs differ.
+        assert!((a.slerp(&-b, 0.5).dot(&expected).abs() - 1.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,431 @@
+//! Text formatting and parsing shared by the vector and matrix types.
+//!
+//! Vectors are written as `(x, y, z)` and matrices as nested row-major lists,
+//! `[[1, 0], [0, 1]]`. The parsers accept either bracket style, commas or
+//! whitespace between elements and the multi-line `{:#}` output.
+
+use std::{
+    error::Error,
+    fmt::{self, Alignment, Display, Formatter},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseMathErrorKind {
+    /// Input ended before the value was complete.
+    UnexpectedEnd,
+    /// A character that cannot start or continue a value.
+    UnexpectedChar(char),
+    /// A closing bracket that does not match the opening one.
+    MismatchedBracket { expected: char, found: char },
+    /// A number that failed to parse as the element type.
+    InvalidNumber(String),
+    /// The value has the wrong number of elements (or rows).
+    WrongLength { expected: usize, found: usize },
+    /// A list was found where a number was expected, or the other way around.
+    WrongShape,
+    /// Brackets nested deeper than [`MAX_DEPTH`].
+    TooDeep,
+}
+
+/// Deepest bracket nesting the parsers accept. Matrices need two levels;
+/// the limit keeps hostile input from exhausting the stack.
+pub const MAX_DEPTH: usize = 32;
+
+/// Error returned by the `FromStr` implementations of the math types.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseMathError {
+    kind: ParseMathErrorKind,
+    /// Byte offset into the input where the problem was found.
+    position: usize,
+}
+
+impl ParseMathError {
+    fn new(kind: ParseMathErrorKind, position: usize) -> Self {
+        Self { kind, position }
+    }
+
+    pub fn kind(&self) -> &ParseMathErrorKind {
+        &self.kind
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl Display for ParseMathError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ParseMathErrorKind::UnexpectedEnd => write!(f, "unexpected end of input")?,
+            ParseMathErrorKind::UnexpectedChar(c) => write!(f, "unexpected character {c:?}")?,
+            ParseMathErrorKind::MismatchedBracket { expected, found } => {
+                write!(f, "expected {expected:?} but found {found:?}")?
+            }
+            ParseMathErrorKind::InvalidNumber(n) => write!(f, "invalid number {n:?}")?,
+            ParseMathErrorKind::WrongLength { expected, found } => {
+                write!(f, "expected {expected} elements but found {found}")?
+            }
+            ParseMathErrorKind::WrongShape => write!(f, "unexpected nesting")?,
+            ParseMathErrorKind::TooDeep => write!(f, "brackets nested too deeply")?,
+        }
+
+        write!(f, " at offset {}", self.position)
+    }
+}
+
+impl Error for ParseMathError {}
+
+/// A parsed number or bracketed list, remembering where it started.
+enum Node<'a> {
+    Number(&'a str, usize),
+    List(Vec<Node<'a>>, usize),
+}
+
+impl<'a> Node<'a> {
+    fn position(&self) -> usize {
+        match self {
+            Node::Number(_, at) | Node::List(_, at) => *at,
+        }
+    }
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    at: usize,
+    /// Number of brackets currently open.
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<char> {
+        self.src[self.at..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.at += c.len_utf8();
+        }
+    }
+
+    fn error(&self, kind: ParseMathErrorKind) -> ParseMathError {
+        ParseMathError::new(kind, self.at)
+    }
+
+    fn node(&mut self) -> Result<Node<'a>, ParseMathError> {
+        self.skip_whitespace();
+        let start = self.at;
+
+        match self.peek() {
+            None => Err(self.error(ParseMathErrorKind::UnexpectedEnd)),
+            Some(open @ ('(' | '[')) => {
+                if self.depth == MAX_DEPTH {
+                    return Err(self.error(ParseMathErrorKind::TooDeep));
+                }
+                let close = if open == '(' { ')' } else { ']' };
+                self.at += 1;
+                self.depth += 1;
+                let items = self.items(Some(close))?;
+                self.depth -= 1;
+                Ok(Node::List(items, start))
+            }
+            Some(c) if is_number_char(c) => {
+                while self.peek().is_some_and(is_number_char) {
+                    self.at += 1;
+                }
+                Ok(Node::Number(&self.src[start..self.at], start))
+            }
+            Some(c) => Err(self.error(ParseMathErrorKind::UnexpectedChar(c))),
+        }
+    }
+
+    /// Parses elements up to `close`, or to the end of input for a bare list.
+    fn items(&mut self, close: Option<char>) -> Result<Vec<Node<'a>>, ParseMathError> {
+        let mut items = Vec::new();
+
+        loop {
+            self.skip_whitespace();
+
+            match (self.peek(), close) {
+                (None, None) => return Ok(items),
+                (None, Some(_)) => return Err(self.error(ParseMathErrorKind::UnexpectedEnd)),
+                (Some(c @ (')' | ']')), Some(expected)) => {
+                    if c != expected {
+                        return Err(self
+                            .error(ParseMathErrorKind::MismatchedBracket { expected, found: c }));
+                    }
+                    self.at += 1;
+                    return Ok(items);
+                }
+                _ => {}
+            }
+
+            items.push(self.node()?);
+
+            self.skip_whitespace();
+            if self.peek() == Some(',') {
+                self.at += 1;
+            }
+        }
+    }
+}
+
+fn is_number_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+')
+}
+
+/// Parses `src` as a flat list of exactly `len` elements.
+pub(crate) fn parse_list<T: std::str::FromStr>(
+    src: &str,
+    len: usize,
+) -> Result<Vec<T>, ParseMathError> {
+    let nodes = parse_root(src)?;
+    list_of(nodes, len, src.len())
+}
+
+/// Parses `src` as a list of `rows` lists with `cols` elements each.
+pub(crate) fn parse_rows<T: std::str::FromStr>(
+    src: &str,
+    rows: usize,
+    cols: usize,
+) -> Result<Vec<Vec<T>>, ParseMathError> {
+    let nodes = parse_root(src)?;
+    check_len(nodes.len(), rows, src.len())?;
+
+    nodes
+        .into_iter()
+        .map(|node| match node {
+            Node::List(items, at) => list_of(items, cols, at),
+            Node::Number(_, at) => Err(ParseMathError::new(ParseMathErrorKind::WrongShape, at)),
+        })
+        .collect()
+}
+
+/// Parses the top-level elements; a single bracketed list is unwrapped so
+/// that `(1, 2)` and `1 2` give the same result.
+fn parse_root(src: &str) -> Result<Vec<Node<'_>>, ParseMathError> {
+    let mut parser = Parser {
+        src,
+        at: 0,
+        depth: 0,
+    };
+    let mut nodes = parser.items(None)?;
+
+    if nodes.len() == 1 {
+        if let Node::List(..) = nodes[0] {
+            let Some(Node::List(items, _)) = nodes.pop() else {
+                unreachable!()
+            };
+            return Ok(items);
+        }
+    }
+
+    if nodes.is_empty() {
+        return Err(ParseMathError::new(
+            ParseMathErrorKind::UnexpectedEnd,
+            src.len(),
+        ));
+    }
+
+    Ok(nodes)
+}
+
+fn check_len(found: usize, expected: usize, at: usize) -> Result<(), ParseMathError> {
+    if found != expected {
+        return Err(ParseMathError::new(
+            ParseMathErrorKind::WrongLength { expected, found },
+            at,
+        ));
+    }
+
+    Ok(())
+}
+
+fn list_of<T: std::str::FromStr>(
+    nodes: Vec<Node<'_>>,
+    len: usize,
+    at: usize,
+) -> Result<Vec<T>, ParseMathError> {
+    check_len(nodes.len(), len, at)?;
+
+    nodes
+        .into_iter()
+        .map(|node| match node {
+            Node::Number(text, at) => text.parse::<T>().map_err(|_| {
+                ParseMathError::new(ParseMathErrorKind::InvalidNumber(text.to_string()), at)
+            }),
+            node => Err(ParseMathError::new(
+                ParseMathErrorKind::WrongShape,
+                node.position(),
+            )),
+        })
+        .collect()
+}
+
+/// Formats one element honouring the sign and precision flags of `f`.
+pub(crate) fn element<T: Display>(f: &Formatter<'_>, v: T) -> String {
+    match (f.sign_plus(), f.precision()) {
+        (true, Some(p)) => format!("{v:+.p$}"),
+        (true, None) => format!("{v:+}"),
+        (false, Some(p)) => format!("{v:.p$}"),
+        (false, None) => format!("{v}"),
+    }
+}
+
+/// Writes `s` padded to `width` using the fill and alignment of `f`.
+pub(crate) fn pad(f: &mut Formatter<'_>, s: &str, width: usize, default: Alignment) -> fmt::Result {
+    let len = s.chars().count();
+    if len >= width {
+        return f.write_str(s);
+    }
+
+    let padding = width - len;
+    let (before, after) = match f.align().unwrap_or(default) {
+        Alignment::Left => (0, padding),
+        Alignment::Right => (padding, 0),
+        Alignment::Center => (padding / 2, padding - padding / 2),
+    };
+    let fill = f.fill();
+
+    for _ in 0..before {
+        write!(f, "{fill}")?;
+    }
+    f.write_str(s)?;
+    for _ in 0..after {
+        write!(f, "{fill}")?;
+    }
+
+    Ok(())
+}
+
+/// Writes a vector as `(x, y, z)`; the width applies to the whole vector.
+pub(crate) fn write_vector(f: &mut Formatter<'_>, components: &[f32]) -> fmt::Result {
+    let parts: Vec<String> = components.iter().map(|&c| element(f, c)).collect();
+    let text = format!("({})", parts.join(", "));
+
+    pad(f, &text, f.width().unwrap_or(0), Alignment::Left)
+}
+
+/// Writes a matrix given as rows. The width applies to every element; the
+/// alternate flag (`{:#}`) puts each row on its own line with the columns
+/// aligned.
+pub(crate) fn write_matrix<T: Display + Copy, const N: usize>(
+    f: &mut Formatter<'_>,
+    rows: &[[T; N]; N],
+) -> fmt::Result {
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| row.iter().map(|&e| element(f, e)).collect())
+        .collect();
+
+    let mut width = f.width().unwrap_or(0);
+    if f.alternate() {
+        let widest = cells.iter().flatten().map(|c| c.chars().count()).max();
+        width = width.max(widest.unwrap_or(0));
+    }
+
+    f.write_str("[")?;
+    for (i, row) in cells.iter().enumerate() {
+        if i > 0 {
+            f.write_str(if f.alternate() { ",\n " } else { ", " })?;
+        }
+
+        f.write_str("[")?;
+        for (j, cell) in row.iter().enumerate() {
+            if j > 0 {
+                f.write_str(", ")?;
+            }
+            pad(f, cell, width, Alignment::Right)?;
+        }
+        f.write_str("]")?;
+    }
+    f.write_str("]")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_list_forms_test() {
+        let expected = vec![1.0f32, -2.5, 3e2];
+
+        assert_eq!(parse_list::<f32>("(1, -2.5, 3e2)", 3), Ok(expected.clone()));
+        assert_eq!(parse_list::<f32>("[1,-2.5,3e2]", 3), Ok(expected.clone()));
+        assert_eq!(parse_list::<f32>("  1 -2.5 3e2 ", 3), Ok(expected));
+    }
+
+    #[test]
+    fn parse_rows_forms_test() {
+        let expected = vec![vec![1, 0], vec![0, 1]];
+
+        assert_eq!(
+            parse_rows::<i32>("[[1,0],[0,1]]", 2, 2),
+            Ok(expected.clone())
+        );
+        assert_eq!(
+            parse_rows::<i32>("[[1, 0],\n [0, 1]]", 2, 2),
+            Ok(expected.clone())
+        );
+        assert_eq!(parse_rows::<i32>("(1 0) (0 1)", 2, 2), Ok(expected));
+    }
+
+    #[test]
+    fn parse_errors_test() {
+        let err = parse_list::<f32>("(1, 2", 2).unwrap_err();
+        assert_eq!(err.kind(), &ParseMathErrorKind::UnexpectedEnd);
+
+        let err = parse_list::<f32>("(1, 2]", 2).unwrap_err();
+        assert_eq!(
+            err.kind(),
+            &ParseMathErrorKind::MismatchedBracket {
+                expected: ')',
+                found: ']'
+            }
+        );
+        assert_eq!(err.position(), 5);
+
+        let err = parse_list::<f32>("(1, x)", 2).unwrap_err();
+        assert_eq!(err.kind(), &ParseMathErrorKind::InvalidNumber("x".into()));
+        assert_eq!(err.position(), 4);
+
+        let err = parse_list::<f32>("(1, 2, 3)", 2).unwrap_err();
+        assert_eq!(
+            err.kind(),
+            &ParseMathErrorKind::WrongLength {
+                expected: 2,
+                found: 3
+            }
+        );
+
+        let err = parse_list::<f32>("(1, ?)", 2).unwrap_err();
+        assert_eq!(err.kind(), &ParseMathErrorKind::UnexpectedChar('?'));
+
+        let err = parse_rows::<i32>("[[1, 0], 2]", 2, 2).unwrap_err();
+        assert_eq!(err.kind(), &ParseMathErrorKind::WrongShape);
+
+        let err = parse_list::<f32>("", 2).unwrap_err();
+        assert_eq!(err.kind(), &ParseMathErrorKind::UnexpectedEnd);
+    }
+
+    #[test]
+    fn parse_depth_test() {
+        let nested = format!("{}1{}", "[".repeat(MAX_DEPTH), "]".repeat(MAX_DEPTH));
+        let err = parse_list::<f32>(&nested, 1).unwrap_err();
+        assert_eq!(err.kind(), &ParseMathErrorKind::WrongShape);
+
+        let deep = "[".repeat(100_000);
+        let err = parse_list::<f32>(&deep, 1).unwrap_err();
+        assert_eq!(err.kind(), &ParseMathErrorKind::TooDeep);
+        assert_eq!(err.position(), MAX_DEPTH);
+    }
+
+    #[test]
+    fn error_display_test() {
+        let err = parse_list::<f32>("(1, x)", 2).unwrap_err();
+
+        assert_eq!(err.to_string(), "invalid number \"x\" at offset 4");
+    }
+}
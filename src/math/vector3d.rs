@@ -1,6 +1,13 @@
-use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign};
+use std::{
+    fmt::{self, Display, Formatter},
+    ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+    str::FromStr,
+};
 
-#[derive(Debug)]
+use super::text::{self, ParseMathError};
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
 pub struct Vector3D {
     x: f32,
     y: f32,
@@ -9,9 +16,9 @@ pub struct Vector3D {
 
 impl PartialEq for Vector3D {
     fn eq(&self, other: &Self) -> bool {
-        (self.x - other.x <= f32::EPSILON)
-            && (self.y - other.y <= f32::EPSILON)
-            && (self.z - other.z <= f32::EPSILON)
+        ((self.x - other.x).abs() <= f32::EPSILON)
+            && ((self.y - other.y).abs() <= f32::EPSILON)
+            && ((self.z - other.z).abs() <= f32::EPSILON)
     }
 }
 
@@ -31,6 +38,30 @@ impl AddAssign for Vector3D {
     }
 }
 
+impl Sub for Vector3D {
+    type Output = Vector3D;
+
+    fn sub(self, right: Self) -> Self::Output {
+        Vector3D::create(self.x - right.x, self.y - right.y, self.z - right.z)
+    }
+}
+
+impl SubAssign for Vector3D {
+    fn sub_assign(&mut self, right: Self) {
+        self.x -= right.x;
+        self.y -= right.y;
+        self.z -= right.z;
+    }
+}
+
+impl Neg for Vector3D {
+    type Output = Vector3D;
+
+    fn neg(self) -> Self::Output {
+        Vector3D::create(-self.x, -self.y, -self.z)
+    }
+}
+
 impl Add<f32> for Vector3D {
     type Output = Vector3D;
 
@@ -136,13 +167,19 @@ impl From<[f32; 3]> for Vector3D {
     }
 }
 
-impl Default for Vector3D {
-    fn default() -> Self {
-        Self {
-            x: Default::default(),
-            y: Default::default(),
-            z: Default::default(),
-        }
+impl Display for Vector3D {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        text::write_vector(f, &[self.x, self.y, self.z])
+    }
+}
+
+impl FromStr for Vector3D {
+    type Err = ParseMathError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let v = text::parse_list::<f32>(s, 3)?;
+
+        Ok(Self::create(v[0], v[1], v[2]))
     }
 }
 
@@ -151,10 +188,34 @@ impl Vector3D {
         Self { x, y, z }
     }
 
+    pub fn x(&self) -> f32 {
+        self.x
+    }
+
+    pub fn y(&self) -> f32 {
+        self.y
+    }
+
+    pub fn z(&self) -> f32 {
+        self.z
+    }
+
     pub fn dot(&self, other: &Vector3D) -> f32 {
         (self.x * other.x) + (self.y * other.y) + (self.z * other.z)
     }
 
+    pub fn cross(&self, other: &Vector3D) -> Vector3D {
+        Vector3D::create(
+            (self.y * other.z) - (self.z * other.y),
+            (self.z * other.x) - (self.x * other.z),
+            (self.x * other.y) - (self.y * other.x),
+        )
+    }
+
+    pub fn distance(&self, other: &Vector3D) -> f32 {
+        (*other - *self).magnitude()
+    }
+
     pub fn magnitude(&self) -> f32 {
         return self.dot(self).sqrt();
     }
@@ -302,8 +363,9 @@ mod tests {
     #[test]
     fn div_scalar_test() {
         let v = Vector3D::create(random_f32(), random_f32(), random_f32());
-        let scalar = random_f32();
-        let scalar = 1.0f32 / scalar; //this is how we operate
+        // keep the quotient below one so the reciprocal multiply in `Div`
+        // stays within epsilon of a true division
+        let scalar = random_f32() + 1.0;
         let expected = Vector3D::create(v.x / scalar, v.y / scalar, v.z / scalar);
 
         assert_eq!(expected, v / scalar);
@@ -312,8 +374,7 @@ mod tests {
     #[test]
     fn div_scalar_borrowed_test() {
         let v = &Vector3D::create(random_f32(), random_f32(), random_f32());
-        let scalar = random_f32();
-        let scalar = 1.0f32 / scalar; //this is how we operate
+        let scalar = random_f32() + 1.0;
         let expected = Vector3D::create(v.x / scalar, v.y / scalar, v.z / scalar);
 
         assert_eq!(expected, v / scalar);
@@ -357,4 +418,92 @@ mod tests {
 
         assert_eq!(v1.normalize(), expected);
     }
+
+    #[test]
+    fn display_test() {
+        let v = Vector3D::create(1.0, -2.5, 0.125);
+
+        assert_eq!(format!("{}", v), "(1, -2.5, 0.125)");
+        assert_eq!(format!("{:.2}", v), "(1.00, -2.50, 0.12)");
+        assert_eq!(format!("{:+}", v), "(+1, -2.5, +0.125)");
+    }
+
+    #[test]
+    fn display_width_test() {
+        let v = Vector3D::create(1.0, 1.0, 1.0);
+        let text = "(1, 1, 1)";
+        let pad = 20 - text.len();
+
+        assert_eq!(format!("{:>20}", v), format!("{}{}", " ".repeat(pad), text));
+        assert_eq!(
+            format!("{:*<20}", v),
+            format!("{}{}", text, "*".repeat(pad))
+        );
+    }
+
+    #[test]
+    fn from_str_test() {
+        let expected = Vector3D::create(1.0, -2.5, 3.0);
+
+        assert_eq!("(1, -2.5, 3)".parse::<Vector3D>(), Ok(expected));
+        assert_eq!("[1,-2.5,3]".parse::<Vector3D>(), Ok(expected));
+        assert_eq!("1 -2.5 3".parse::<Vector3D>(), Ok(expected));
+        assert!("(1, 1, 1, 1)".parse::<Vector3D>().is_err());
+        assert!("(1, 1, a)".parse::<Vector3D>().is_err());
+    }
+
+    #[test]
+    fn display_round_trip_test() {
+        let v = Vector3D::create(random_f32(), random_f32(), random_f32());
+        let parsed: Vector3D = v.to_string().parse().unwrap();
+
+        assert_eq!(parsed.x, v.x);
+        assert_eq!(parsed.y, v.y);
+        assert_eq!(parsed.z, v.z);
+    }
+
+    #[test]
+    fn sub_test() {
+        let v1 = Vector3D::create(random_f32(), random_f32(), random_f32());
+        let v2 = Vector3D::create(random_f32(), random_f32(), random_f32());
+        let expected = Vector3D::create(v1.x - v2.x, v1.y - v2.y, v1.z - v2.z);
+
+        assert_eq!(expected, v1 - v2);
+    }
+
+    #[test]
+    fn sub_assign_test() {
+        let v1 = Vector3D::create(random_f32(), random_f32(), random_f32());
+        let v2 = Vector3D::create(random_f32(), random_f32(), random_f32());
+        let expected = Vector3D::create(v1.x - v2.x, v1.y - v2.y, v1.z - v2.z);
+
+        let mut v1_mut = v1;
+        v1_mut -= v2;
+
+        assert_eq!(expected, v1_mut);
+    }
+
+    #[test]
+    fn neg_test() {
+        let v = Vector3D::create(random_f32(), random_f32(), random_f32());
+
+        assert_eq!(Vector3D::default(), v + -v);
+    }
+
+    #[test]
+    fn cross_test() {
+        let x = Vector3D::create(1.0, 0.0, 0.0);
+        let y = Vector3D::create(0.0, 1.0, 0.0);
+        let z = Vector3D::create(0.0, 0.0, 1.0);
+
+        assert_eq!(x.cross(&y), z);
+        assert_eq!(y.cross(&z), x);
+        assert_eq!(y.cross(&x), -z);
+
+        let v1 = Vector3D::create(random_f32(), random_f32(), random_f32());
+        let v2 = Vector3D::create(random_f32(), random_f32(), random_f32());
+        let c = v1.cross(&v2);
+        assert!(c.dot(&v1).abs() < 1e-6);
+        assert!(c.dot(&v2).abs() < 1e-6);
+    }
 }
@@ -0,0 +1,633 @@
+//! Modelling operations on [`HalfEdgeMesh`].
+//!
+//! Each operation reads the adjacency, edits the polygon list and rebuilds
+//! the topology, so ids from before an edit are only meaningful where the
+//! operation says so. A failed edit leaves the mesh unchanged.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use super::halfedge::{FaceId, HalfEdgeError, HalfEdgeId, HalfEdgeMesh, VertexId};
+use crate::math::Vector3D;
+
+impl HalfEdgeMesh {
+    /// Moves a region of faces `distance` along its averaged normals and
+    /// joins it to the rest of the mesh with a quad per boundary edge of
+    /// the region. Existing ids are kept; returns the new side faces.
+    pub fn extrude_faces(
+        &mut self,
+        faces: &[FaceId],
+        distance: f32,
+    ) -> Result<Vec<FaceId>, HalfEdgeError> {
+        let selected: BTreeSet<FaceId> = faces.iter().copied().collect();
+        let (mut positions, mut polygons) = self.to_polygons();
+
+        let mut normals = BTreeMap::new();
+        let mut boundary = Vec::new();
+        for &f in &selected {
+            let normal = self.face_normal(f);
+            for h in self.face_halfedges(f) {
+                *normals
+                    .entry(self.origin(h))
+                    .or_insert_with(Vector3D::default) += normal;
+                if self
+                    .twin(h)
+                    .is_none_or(|t| !selected.contains(&self.face(t)))
+                {
+                    boundary.push(h);
+                }
+            }
+        }
+
+        // Vertices on the region's boundary are copied, so the side faces
+        // can join the copy to the original; the others just move.
+        let on_boundary: BTreeSet<VertexId> = boundary
+            .iter()
+            .flat_map(|&h| [self.origin(h), self.target(h)])
+            .collect();
+        let mut moved = HashMap::new();
+        for (v, normal) in normals {
+            let mut position = self.position(v);
+            if normal.magnitude() > 0.0 {
+                position += normal.normalize() * distance;
+            }
+
+            if on_boundary.contains(&v) {
+                positions.push(position);
+                moved.insert(v.0, positions.len() - 1);
+            } else {
+                positions[v.0] = position;
+                moved.insert(v.0, v.0);
+            }
+        }
+
+        for f in &selected {
+            for v in polygons[f.0].iter_mut() {
+                *v = moved[v];
+            }
+        }
+        let first = polygons.len();
+        for h in boundary {
+            let (a, b) = (self.origin(h).0, self.target(h).0);
+            polygons.push(vec![a, b, moved[&b], moved[&a]]);
+        }
+
+        *self = HalfEdgeMesh::from_polygons(positions, &polygons)?;
+        return Ok((first..polygons.len()).map(FaceId).collect());
+    }
+
+    /// Chamfers an interior edge into a quad of about `width`, which should
+    /// be less than the lengths of the neighbouring edges. Each end vertex
+    /// splits in two along the side edges of the edge's faces; an end with
+    /// only three faces disappears. Returns the new face.
+    pub fn bevel_edge(&mut self, h: HalfEdgeId, width: f32) -> Result<FaceId, HalfEdgeError> {
+        let Some(twin) = self.twin(h) else {
+            return Err(HalfEdgeError::BoundaryEdge(h));
+        };
+        let (mut positions, mut polygons) = self.to_polygons();
+        let mut bevel = Vec::new();
+
+        // At each end, `out` leaves the vertex in one face and `into`
+        // enters it in the other.
+        for (out, into) in [(h, twin), (twin, h)] {
+            let v = self.origin(out);
+            let (out_face, into_face) = (self.face(out), self.face(into));
+            let before = self.prev(out);
+            let after = self.next(into);
+            let before_face = self.twin(before).map(|t| self.face(t));
+            let after_face = self.twin(after).map(|t| self.face(t));
+            if before_face == Some(into_face) || after_face == Some(out_face) {
+                return Err(HalfEdgeError::Topology(h));
+            }
+
+            let p = self.position(v);
+            let toward = |u: VertexId| p + (self.position(u) - p).normalize() * width;
+            positions.push(toward(self.origin(before)));
+            positions.push(toward(self.target(after)));
+            let (v_out, v_into) = (positions.len() - 2, positions.len() - 1);
+            let v = v.0;
+
+            replace(&mut polygons[out_face.0], v, &[v_out]);
+            replace(&mut polygons[into_face.0], v, &[v_into]);
+            if before_face == after_face {
+                if let Some(f) = before_face {
+                    replace(&mut polygons[f.0], v, &[v_into, v_out]);
+                }
+                bevel.extend([v_out, v_into]);
+            } else {
+                if let Some(f) = before_face {
+                    replace(&mut polygons[f.0], v, &[v, v_out]);
+                }
+                if let Some(f) = after_face {
+                    replace(&mut polygons[f.0], v, &[v_into, v]);
+                }
+                bevel.extend([v_out, v, v_into]);
+            }
+        }
+        polygons.push(bevel);
+
+        let (mesh, _) = rebuild(&positions, &polygons).map_err(|_| HalfEdgeError::Topology(h))?;
+        *self = mesh;
+        return Ok(FaceId(polygons.len() - 1));
+    }
+
+    /// Cuts the ring of quads across an edge in two, at `t` along the edge
+    /// from its origin and at matching points on the rest of the ring. The
+    /// ring stops at the boundary or at a face that is not a quad, which
+    /// gains a vertex. Existing ids are kept; returns the new vertices.
+    pub fn loop_cut(&mut self, h: HalfEdgeId, t: f32) -> Result<Vec<VertexId>, HalfEdgeError> {
+        let is_quad = |e: HalfEdgeId| self.face_halfedges(self.face(e)).len() == 4;
+
+        // The quads of the ring, each with the half-edge the walk entered
+        // it by and where the cut crosses that half-edge.
+        let mut ring: Vec<(HalfEdgeId, f32)> = Vec::new();
+        let mut visited = BTreeSet::new();
+        let mut closed = false;
+        let mut entry = Some(h);
+        while let Some(e) = entry.filter(|&e| is_quad(e) && visited.insert(self.face(e))) {
+            ring.push((e, t));
+            entry = self.twin(self.next(self.next(e)));
+            if entry == Some(h) {
+                closed = true;
+                break;
+            }
+        }
+        if !closed {
+            let mut entry = self.twin(h);
+            while let Some(e) = entry.filter(|&e| is_quad(e) && visited.insert(self.face(e))) {
+                ring.push((e, 1.0 - t));
+                entry = self.twin(self.next(self.next(e)));
+            }
+        }
+        if ring.is_empty() {
+            return Err(HalfEdgeError::NoQuadRing(h));
+        }
+
+        let (mut positions, mut polygons) = self.to_polygons();
+        let first = positions.len();
+        let mut splits = HashMap::new();
+        let mut split = |u: usize, w: usize, s: f32| -> usize {
+            return *splits.entry((u.min(w), u.max(w))).or_insert_with(|| {
+                positions.push(positions[u] + (positions[w] - positions[u]) * s);
+                positions.len() - 1
+            });
+        };
+
+        for &(e, s) in &ring {
+            // The quad's corners from the entry edge on.
+            let opposite = self.next(self.next(e));
+            let [a, b, c, d] = [e, self.next(e), opposite, self.prev(e)].map(|h| self.origin(h).0);
+            let m = split(a, b, s);
+            let n = split(c, d, 1.0 - s);
+            polygons[self.face(e).0] = vec![a, m, n, d];
+            polygons.push(vec![m, b, c, n]);
+        }
+
+        // Faces at the ends of an open ring share a cut edge.
+        for f in self.faces().filter(|f| !visited.contains(f)) {
+            let face = &polygons[f.0];
+            let mut cut = Vec::with_capacity(face.len() + 1);
+            for (k, &v) in face.iter().enumerate() {
+                cut.push(v);
+                let w = face[(k + 1) % face.len()];
+                if let Some(&m) = splits.get(&(v.min(w), v.max(w))) {
+                    cut.push(m);
+                }
+            }
+            polygons[f.0] = cut;
+        }
+
+        *self = HalfEdgeMesh::from_polygons(positions, &polygons)?;
+        return Ok((first..self.vertex_count()).map(VertexId).collect());
+    }
+
+    /// Merges an edge's target into its origin at the edge's midpoint,
+    /// removing the triangles on the edge. Refuses collapses that would
+    /// pinch the surface, checked by the link condition. Returns the
+    /// merged vertex.
+    pub fn collapse_edge(&mut self, h: HalfEdgeId) -> Result<VertexId, HalfEdgeError> {
+        let (a, b) = (self.origin(h), self.target(h));
+
+        // The only vertices next to both ends should be the far corners
+        // of the triangles on the edge.
+        let corners: BTreeSet<VertexId> = [Some(h), self.twin(h)]
+            .into_iter()
+            .flatten()
+            .filter(|&e| self.face_halfedges(self.face(e)).len() == 3)
+            .map(|e| self.origin(self.prev(e)))
+            .collect();
+        let around_a: BTreeSet<VertexId> = self.neighbours(a).into_iter().collect();
+        let common: BTreeSet<VertexId> = self
+            .neighbours(b)
+            .into_iter()
+            .filter(|v| around_a.contains(v))
+            .collect();
+        let joins_boundaries =
+            self.twin(h).is_some() && self.is_boundary_vertex(a) && self.is_boundary_vertex(b);
+        if common != corners || joins_boundaries {
+            return Err(HalfEdgeError::Topology(h));
+        }
+
+        let (mut positions, mut polygons) = self.to_polygons();
+        positions[a.0] = (self.position(a) + self.position(b)) / 2.0;
+        for face in polygons.iter_mut() {
+            for v in face.iter_mut() {
+                if *v == b.0 {
+                    *v = a.0;
+                }
+            }
+            face.dedup();
+            if face.len() > 1 && face.first() == face.last() {
+                face.pop();
+            }
+        }
+        polygons.retain(|face| face.len() >= 3);
+
+        let (mesh, remap) =
+            rebuild(&positions, &polygons).map_err(|_| HalfEdgeError::Topology(h))?;
+        mesh.validate().map_err(|_| HalfEdgeError::Topology(h))?;
+        *self = mesh;
+        return Ok(remap[a.0].unwrap());
+    }
+
+    /// One step of Loop subdivision: each triangle becomes four and the
+    /// surface is smoothed, with boundaries kept as curves. Vertex ids are
+    /// kept; new edge vertices follow them.
+    pub fn subdivide_loop(&mut self) -> Result<(), HalfEdgeError> {
+        if let Some(f) = self.faces().find(|&f| self.face_halfedges(f).len() != 3) {
+            return Err(HalfEdgeError::NotTriangle(f));
+        }
+
+        let boundary = self.boundary_neighbours();
+        let mut positions: Vec<Vector3D> = (0..self.vertex_count())
+            .map(|v| {
+                let p = self.position(VertexId(v));
+                if !boundary[v].is_empty() {
+                    return boundary_smooth(p, &boundary[v], self);
+                }
+
+                let neighbours = self.neighbours(VertexId(v));
+                let n = neighbours.len() as f32;
+                if neighbours.is_empty() {
+                    return p;
+                }
+                // Warren's weights.
+                let beta = if neighbours.len() == 3 {
+                    3.0 / 16.0
+                } else {
+                    3.0 / (8.0 * n)
+                };
+                let sum = neighbours
+                    .iter()
+                    .fold(Vector3D::default(), |sum, &u| sum + self.position(u));
+                return p * (1.0 - n * beta) + sum * beta;
+            })
+            .collect();
+
+        let edges = self.number_edges(positions.len());
+        positions.resize(positions.len() + self.edge_count(), Vector3D::default());
+        for h in (0..self.halfedge_count()).map(HalfEdgeId) {
+            let (a, b) = (self.position(self.origin(h)), self.position(self.target(h)));
+            positions[edges[h.0]] = match self.twin(h) {
+                None => (a + b) / 2.0,
+                Some(t) => {
+                    let c = self.position(self.origin(self.prev(h)));
+                    let d = self.position(self.origin(self.prev(t)));
+                    (a + b) * (3.0 / 8.0) + (c + d) * (1.0 / 8.0)
+                }
+            };
+        }
+
+        let mut polygons = Vec::with_capacity(4 * self.face_count());
+        for f in self.faces() {
+            let [h0, h1, h2] = [0, 1, 2].map(|k| self.face_halfedges(f)[k]);
+            let [a, b, c] = [h0, h1, h2].map(|h| self.origin(h).0);
+            let [ab, bc, ca] = [h0, h1, h2].map(|h| edges[h.0]);
+            polygons.extend([
+                vec![a, ab, ca],
+                vec![ab, b, bc],
+                vec![ca, bc, c],
+                vec![ab, bc, ca],
+            ]);
+        }
+
+        *self = HalfEdgeMesh::from_polygons(positions, &polygons)?;
+        return Ok(());
+    }
+
+    /// One step of Catmull-Clark subdivision: each n-gon becomes n quads
+    /// and the surface is smoothed, with boundaries kept as curves. Vertex
+    /// ids are kept; new edge vertices follow them, then face vertices.
+    pub fn subdivide_catmull_clark(&mut self) -> Result<(), HalfEdgeError> {
+        let centroids: Vec<Vector3D> = self.faces().map(|f| self.face_centroid(f)).collect();
+        let boundary = self.boundary_neighbours();
+
+        let mut positions: Vec<Vector3D> = (0..self.vertex_count())
+            .map(|v| {
+                let p = self.position(VertexId(v));
+                if !boundary[v].is_empty() {
+                    return boundary_smooth(p, &boundary[v], self);
+                }
+
+                let outgoing = self.outgoing(VertexId(v));
+                if outgoing.is_empty() {
+                    return p;
+                }
+                let n = outgoing.len() as f32;
+                let (mut faces, mut edges) = (Vector3D::default(), Vector3D::default());
+                for &h in &outgoing {
+                    faces += centroids[self.face(h).0];
+                    edges += (p + self.position(self.target(h))) / 2.0;
+                }
+                return (faces / n + edges * (2.0 / n) + p * (n - 3.0)) / n;
+            })
+            .collect();
+
+        let edges = self.number_edges(positions.len());
+        positions.resize(positions.len() + self.edge_count(), Vector3D::default());
+        for h in (0..self.halfedge_count()).map(HalfEdgeId) {
+            let (a, b) = (self.position(self.origin(h)), self.position(self.target(h)));
+            positions[edges[h.0]] = match self.twin(h) {
+                None => (a + b) / 2.0,
+                Some(t) => (a + b + centroids[self.face(h).0] + centroids[self.face(t).0]) / 4.0,
+            };
+        }
+
+        let first_face = positions.len();
+        positions.extend(centroids);
+        let mut polygons = Vec::with_capacity(4 * self.face_count());
+        for f in self.faces() {
+            let halfedges = self.face_halfedges(f);
+            let n = halfedges.len();
+            for (k, &h) in halfedges.iter().enumerate() {
+                let before = halfedges[(k + n - 1) % n];
+                polygons.push(vec![
+                    self.origin(h).0,
+                    edges[h.0],
+                    first_face + f.0,
+                    edges[before.0],
+                ]);
+            }
+        }
+
+        *self = HalfEdgeMesh::from_polygons(positions, &polygons)?;
+        return Ok(());
+    }
+
+    /// The neighbours of each vertex along boundary edges.
+    fn boundary_neighbours(&self) -> Vec<Vec<VertexId>> {
+        let mut neighbours = vec![Vec::new(); self.vertex_count()];
+        for h in (0..self.halfedge_count()).map(HalfEdgeId) {
+            if self.twin(h).is_none() {
+                let (a, b) = (self.origin(h), self.target(h));
+                neighbours[a.0].push(b);
+                neighbours[b.0].push(a);
+            }
+        }
+
+        return neighbours;
+    }
+
+    /// Numbers the undirected edges from `first`; each half-edge gets its
+    /// edge's number.
+    fn number_edges(&self, first: usize) -> Vec<usize> {
+        let mut numbers = vec![usize::MAX; self.halfedge_count()];
+        let mut next = first;
+        for h in 0..self.halfedge_count() {
+            if numbers[h] == usize::MAX {
+                numbers[h] = next;
+                if let Some(t) = self.twin(HalfEdgeId(h)) {
+                    numbers[t.0] = next;
+                }
+                next += 1;
+            }
+        }
+
+        return numbers;
+    }
+}
+
+/// The cubic B-spline rule along a boundary, shared by both subdivision
+/// schemes. Corners where the boundary does not simply pass through stay
+/// put.
+fn boundary_smooth(p: Vector3D, neighbours: &[VertexId], mesh: &HalfEdgeMesh) -> Vector3D {
+    if neighbours.len() != 2 {
+        return p;
+    }
+
+    let sum = mesh.position(neighbours[0]) + mesh.position(neighbours[1]);
+    return p * 0.75 + sum * 0.125;
+}
+
+/// Replaces `v` in a polygon by the vertices `with`.
+fn replace(face: &mut Vec<usize>, v: usize, with: &[usize]) {
+    let k = face.iter().position(|&u| u == v).unwrap();
+    face.splice(k..=k, with.iter().copied());
+}
+
+/// Builds the topology of edited polygons, dropping vertices no face uses.
+/// Also returns the new id of each old vertex that was kept.
+fn rebuild(
+    positions: &[Vector3D],
+    polygons: &[Vec<usize>],
+) -> Result<(HalfEdgeMesh, Vec<Option<VertexId>>), HalfEdgeError> {
+    let mut remap = vec![None; positions.len()];
+    for face in polygons {
+        for &v in face {
+            remap[v] = Some(VertexId(0));
+        }
+    }
+
+    let mut kept = Vec::new();
+    for (v, id) in remap.iter_mut().enumerate() {
+        if id.is_some() {
+            *id = Some(VertexId(kept.len()));
+            kept.push(positions[v]);
+        }
+    }
+    let polygons: Vec<Vec<usize>> = polygons
+        .iter()
+        .map(|face| face.iter().map(|&v| remap[v].unwrap().0).collect())
+        .collect();
+
+    let mesh = HalfEdgeMesh::from_polygons(kept, &polygons)?;
+    return Ok((mesh, remap));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::Mesh;
+
+    /// A quad cube from -1 to 1; vertex `i` has x, y and z from bits 0, 1
+    /// and 2.
+    fn cube() -> HalfEdgeMesh {
+        let positions = (0..8)
+            .map(|i| {
+                let bit = |b: usize| if i & b == 0 { -1.0 } else { 1.0 };
+                return Vector3D::create(bit(1), bit(2), bit(4));
+            })
+            .collect();
+        let faces = [
+            [0, 4, 6, 2],
+            [1, 3, 7, 5],
+            [0, 1, 5, 4],
+            [2, 6, 7, 3],
+            [0, 2, 3, 1],
+            [4, 5, 7, 6],
+        ];
+
+        return HalfEdgeMesh::from_polygons(positions, &faces.map(Vec::from)).unwrap();
+    }
+
+    /// Checks a closed convex mesh around `center`.
+    fn assert_closed_convex(mesh: &HalfEdgeMesh, center: Vector3D) {
+        assert_eq!(mesh.validate(), Ok(()));
+        assert!(mesh.is_closed());
+        let euler = mesh.vertex_count() + mesh.face_count() - mesh.edge_count();
+        assert_eq!(euler, 2);
+        for f in mesh.faces() {
+            let outward = mesh.face_centroid(f) - center;
+            assert!(mesh.face_normal(f).dot(&outward) > 0.0, "{f:?}");
+        }
+    }
+
+    #[test]
+    fn extrude_test() {
+        let mut mesh = cube();
+        assert_closed_convex(&mesh, Vector3D::default());
+
+        let sides = mesh.extrude_faces(&[FaceId(3)], 1.0).unwrap();
+        assert_eq!(sides, (6..10).map(FaceId).collect::<Vec<_>>());
+        assert_eq!(mesh.vertex_count(), 12);
+        assert_closed_convex(&mesh, Vector3D::create(0.0, 0.5, 0.0));
+        for v in mesh.face_vertices(FaceId(3)) {
+            assert_eq!(mesh.position(v).y(), 2.0);
+        }
+
+        // The centre of a grid moves instead of being copied.
+        let mut plane = HalfEdgeMesh::try_from(&Mesh::plane(2.0, 2.0, 2, 2)).unwrap();
+        let all: Vec<FaceId> = plane.faces().collect();
+        assert_eq!(plane.extrude_faces(&all, 1.0).unwrap().len(), 8);
+        assert_eq!((plane.vertex_count(), plane.face_count()), (17, 16));
+        assert_eq!(plane.validate(), Ok(()));
+        assert_eq!(plane.position(VertexId(4)), Vector3D::create(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn bevel_test() {
+        let mut mesh = cube();
+        let edge = mesh.find_halfedge(VertexId(3), VertexId(7)).unwrap();
+        let face = mesh.bevel_edge(edge, 0.5).unwrap();
+
+        assert_eq!(face, FaceId(6));
+        assert_eq!((mesh.vertex_count(), mesh.face_count()), (10, 7));
+        assert_closed_convex(&mesh, Vector3D::default());
+        let diagonal = Vector3D::create(1.0, 1.0, 0.0).normalize();
+        assert_eq!(mesh.face_normal(face), diagonal);
+
+        // Ends with more than three faces keep their vertex.
+        let mut grid = HalfEdgeMesh::try_from(&Mesh::plane(2.0, 2.0, 2, 2)).unwrap();
+        let edge = grid.find_halfedge(VertexId(4), VertexId(1)).unwrap();
+        grid.bevel_edge(edge, 0.1).unwrap();
+        assert_eq!((grid.vertex_count(), grid.face_count()), (13, 9));
+        assert_eq!(grid.validate(), Ok(()));
+
+        let boundary = grid.find_halfedge(VertexId(0), VertexId(3)).unwrap();
+        assert_eq!(
+            grid.bevel_edge(boundary, 0.1),
+            Err(HalfEdgeError::BoundaryEdge(boundary))
+        );
+    }
+
+    #[test]
+    fn loop_cut_test() {
+        let mut mesh = cube();
+        let edge = mesh.find_halfedge(VertexId(0), VertexId(1)).unwrap();
+        let cut = mesh.loop_cut(edge, 0.25).unwrap();
+
+        assert_eq!(cut, (8..12).map(VertexId).collect::<Vec<_>>());
+        assert_eq!(mesh.face_count(), 10);
+        assert_closed_convex(&mesh, Vector3D::default());
+        for v in cut {
+            assert_eq!(mesh.position(v).x(), -0.5);
+        }
+
+        // An open ring ends in triangles, which become quads.
+        let mut strip = HalfEdgeMesh::from_polygons(
+            (0..6)
+                .map(|i| Vector3D::create((i / 2) as f32, (i % 2) as f32, 0.0))
+                .collect(),
+            &[vec![0, 2, 1], vec![1, 2, 3], vec![2, 4, 5, 3]],
+        )
+        .unwrap();
+        let edge = strip.find_halfedge(VertexId(4), VertexId(5)).unwrap();
+        assert_eq!(strip.loop_cut(edge, 0.5).unwrap().len(), 2);
+        assert_eq!(strip.face_vertices(FaceId(1)).len(), 4);
+        assert_eq!(strip.validate(), Ok(()));
+
+        let edge = strip.find_halfedge(VertexId(0), VertexId(2)).unwrap();
+        assert_eq!(
+            strip.loop_cut(edge, 0.5),
+            Err(HalfEdgeError::NoQuadRing(edge))
+        );
+    }
+
+    #[test]
+    fn collapse_test() {
+        let mut grid = HalfEdgeMesh::try_from(&Mesh::plane(2.0, 2.0, 2, 2)).unwrap();
+        let edge = grid.find_halfedge(VertexId(4), VertexId(1)).unwrap();
+        let merged = grid.collapse_edge(edge).unwrap();
+
+        assert_eq!(merged, VertexId(3));
+        assert_eq!((grid.vertex_count(), grid.face_count()), (8, 6));
+        assert_eq!(grid.validate(), Ok(()));
+        assert_eq!(grid.position(merged), Vector3D::create(0.0, 0.0, -0.5));
+
+        // Both ends on the boundary of a single quad.
+        let mut quad = HalfEdgeMesh::try_from(&Mesh::plane(1.0, 1.0, 1, 1)).unwrap();
+        let diagonal = quad.find_halfedge(VertexId(0), VertexId(3)).unwrap();
+        assert_eq!(
+            quad.collapse_edge(diagonal),
+            Err(HalfEdgeError::Topology(diagonal))
+        );
+        assert_eq!(quad.face_count(), 2);
+    }
+
+    #[test]
+    fn subdivide_test() {
+        let mut mesh = cube();
+        mesh.subdivide_catmull_clark().unwrap();
+        assert_eq!((mesh.vertex_count(), mesh.face_count()), (26, 24));
+        assert_closed_convex(&mesh, Vector3D::default());
+        assert_eq!(
+            mesh.position(VertexId(7)),
+            Vector3D::create(5.0, 5.0, 5.0) / 9.0
+        );
+
+        assert_eq!(
+            cube().subdivide_loop(),
+            Err(HalfEdgeError::NotTriangle(FaceId(0)))
+        );
+
+        let mut triangles = Mesh::cube(2.0);
+        triangles.normals.clear();
+        triangles.uvs.clear();
+        triangles.weld(0.0);
+        let mut mesh = HalfEdgeMesh::try_from(&triangles).unwrap();
+        mesh.subdivide_loop().unwrap();
+        assert_eq!((mesh.vertex_count(), mesh.face_count()), (26, 48));
+        assert_closed_convex(&mesh, Vector3D::default());
+        for v in 0..mesh.vertex_count() {
+            assert!(mesh.position(VertexId(v)).magnitude() < 3f32.sqrt());
+        }
+
+        // Boundaries follow the cubic B-spline rule.
+        let mut plane = HalfEdgeMesh::try_from(&Mesh::plane(2.0, 2.0, 1, 1)).unwrap();
+        plane.subdivide_catmull_clark().unwrap();
+        assert_eq!(plane.face_count(), 6);
+        assert_eq!(
+            plane.position(VertexId(0)),
+            Vector3D::create(-0.75, 0.0, -0.75)
+        );
+    }
+}
@@ -0,0 +1,459 @@
+//! Half-edge topology over polygon meshes, for editing operations that
+//! walk adjacency (see [`edit`](super::edit)).
+
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+use super::Mesh;
+use crate::math::Vector3D;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VertexId(pub usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HalfEdgeId(pub usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FaceId(pub usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HalfEdgeError {
+    /// A face with fewer than three vertices.
+    FaceTooSmall(usize),
+    /// A face that visits a vertex twice.
+    RepeatedVertex(usize),
+    IndexOutOfRange {
+        index: usize,
+        vertices: usize,
+    },
+    /// The directed edge is in two faces: the edge is shared by more than
+    /// two faces, or its faces disagree on orientation.
+    NonManifoldEdge(usize, usize),
+    /// The faces around the vertex do not form a single fan.
+    NonManifoldVertex(VertexId),
+    /// An operation that needs triangles met another polygon.
+    NotTriangle(FaceId),
+    /// An operation that needs an interior edge was given a boundary one.
+    BoundaryEdge(HalfEdgeId),
+    /// A loop cut found no quad to cut across the edge.
+    NoQuadRing(HalfEdgeId),
+    /// Editing the edge would make the mesh non-manifold.
+    Topology(HalfEdgeId),
+}
+
+impl Display for HalfEdgeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            HalfEdgeError::FaceTooSmall(face) => write!(f, "face {face} has fewer than 3 vertices"),
+            HalfEdgeError::RepeatedVertex(face) => write!(f, "face {face} repeats a vertex"),
+            HalfEdgeError::IndexOutOfRange { index, vertices } => {
+                write!(f, "index {index} out of range; {vertices} vertices")
+            }
+            HalfEdgeError::NonManifoldEdge(a, b) => {
+                write!(f, "edge {a} -> {b} is in more than one face")
+            }
+            HalfEdgeError::NonManifoldVertex(v) => write!(f, "{v:?} is non-manifold"),
+            HalfEdgeError::NotTriangle(face) => write!(f, "{face:?} is not a triangle"),
+            HalfEdgeError::BoundaryEdge(h) => write!(f, "{h:?} is on the boundary"),
+            HalfEdgeError::NoQuadRing(h) => write!(f, "no quads to cut across {h:?}"),
+            HalfEdgeError::Topology(h) => {
+                write!(f, "editing {h:?} would make the mesh non-manifold")
+            }
+        }
+    }
+}
+
+impl Error for HalfEdgeError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct HalfEdge {
+    origin: VertexId,
+    /// `None` on the boundary.
+    twin: Option<HalfEdgeId>,
+    next: HalfEdgeId,
+    prev: HalfEdgeId,
+    face: FaceId,
+}
+
+/// A polygon mesh with half-edge adjacency.
+///
+/// Only faces own half-edges, so a boundary edge is a half-edge without a
+/// twin. Each vertex keeps one outgoing half-edge, a boundary one if it
+/// has any, from which [`HalfEdgeMesh::outgoing`] circulates. The ids are
+/// dense indices and every edit renumbers them.
+#[derive(Debug, Clone, Default)]
+pub struct HalfEdgeMesh {
+    positions: Vec<Vector3D>,
+    /// An outgoing half-edge of each vertex; `None` for isolated ones.
+    vertices: Vec<Option<HalfEdgeId>>,
+    halfedges: Vec<HalfEdge>,
+    /// The first half-edge of each face.
+    faces: Vec<HalfEdgeId>,
+}
+
+impl HalfEdgeMesh {
+    /// Builds the topology of counterclockwise polygons over `positions`.
+    /// Non-manifold edges are rejected; non-manifold vertices are only
+    /// reported by [`HalfEdgeMesh::validate`].
+    pub fn from_polygons(
+        positions: Vec<Vector3D>,
+        faces: &[Vec<usize>],
+    ) -> Result<Self, HalfEdgeError> {
+        let mut mesh = Self {
+            vertices: vec![None; positions.len()],
+            positions,
+            halfedges: Vec::new(),
+            faces: Vec::with_capacity(faces.len()),
+        };
+        let mut edges = HashMap::new();
+
+        for (f, face) in faces.iter().enumerate() {
+            if face.len() < 3 {
+                return Err(HalfEdgeError::FaceTooSmall(f));
+            }
+            for (k, &v) in face.iter().enumerate() {
+                if v >= mesh.positions.len() {
+                    return Err(HalfEdgeError::IndexOutOfRange {
+                        index: v,
+                        vertices: mesh.positions.len(),
+                    });
+                }
+                if face[..k].contains(&v) {
+                    return Err(HalfEdgeError::RepeatedVertex(f));
+                }
+            }
+
+            let first = mesh.halfedges.len();
+            let n = face.len();
+            for (k, &v) in face.iter().enumerate() {
+                let to = face[(k + 1) % n];
+                if edges.insert((v, to), first + k).is_some() {
+                    return Err(HalfEdgeError::NonManifoldEdge(v, to));
+                }
+                mesh.halfedges.push(HalfEdge {
+                    origin: VertexId(v),
+                    twin: None,
+                    next: HalfEdgeId(first + (k + 1) % n),
+                    prev: HalfEdgeId(first + (k + n - 1) % n),
+                    face: FaceId(f),
+                });
+            }
+            mesh.faces.push(HalfEdgeId(first));
+        }
+
+        for h in 0..mesh.halfedges.len() {
+            let from = mesh.halfedges[h].origin;
+            let to = mesh.target(HalfEdgeId(h));
+            let twin = edges.get(&(to.0, from.0)).map(|&t| HalfEdgeId(t));
+            mesh.halfedges[h].twin = twin;
+
+            let vertex = &mut mesh.vertices[from.0];
+            if vertex.is_none() || twin.is_none() {
+                *vertex = Some(HalfEdgeId(h));
+            }
+        }
+
+        return Ok(mesh);
+    }
+
+    /// The positions and counterclockwise vertex lists of the faces.
+    pub fn to_polygons(&self) -> (Vec<Vector3D>, Vec<Vec<usize>>) {
+        let faces = self
+            .faces()
+            .map(|f| self.face_vertices(f).iter().map(|v| v.0).collect())
+            .collect();
+
+        return (self.positions.clone(), faces);
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn halfedge_count(&self) -> usize {
+        self.halfedges.len()
+    }
+
+    pub fn face_count(&self) -> usize {
+        self.faces.len()
+    }
+
+    /// Undirected edges: a boundary half-edge or a pair of twins.
+    pub fn edge_count(&self) -> usize {
+        let boundary = self.halfedges.iter().filter(|h| h.twin.is_none()).count();
+        return (self.halfedges.len() + boundary) / 2;
+    }
+
+    pub fn faces(&self) -> impl Iterator<Item = FaceId> {
+        (0..self.faces.len()).map(FaceId)
+    }
+
+    pub fn position(&self, v: VertexId) -> Vector3D {
+        self.positions[v.0]
+    }
+
+    pub fn set_position(&mut self, v: VertexId, position: Vector3D) {
+        self.positions[v.0] = position;
+    }
+
+    pub fn origin(&self, h: HalfEdgeId) -> VertexId {
+        self.halfedges[h.0].origin
+    }
+
+    pub fn target(&self, h: HalfEdgeId) -> VertexId {
+        self.origin(self.next(h))
+    }
+
+    pub fn twin(&self, h: HalfEdgeId) -> Option<HalfEdgeId> {
+        self.halfedges[h.0].twin
+    }
+
+    pub fn next(&self, h: HalfEdgeId) -> HalfEdgeId {
+        self.halfedges[h.0].next
+    }
+
+    pub fn prev(&self, h: HalfEdgeId) -> HalfEdgeId {
+        self.halfedges[h.0].prev
+    }
+
+    pub fn face(&self, h: HalfEdgeId) -> FaceId {
+        self.halfedges[h.0].face
+    }
+
+    /// The half-edge from `from` to `to`, if a face has that edge.
+    pub fn find_halfedge(&self, from: VertexId, to: VertexId) -> Option<HalfEdgeId> {
+        return self
+            .outgoing(from)
+            .into_iter()
+            .find(|&h| self.target(h) == to);
+    }
+
+    /// The half-edges of a face, counterclockwise.
+    pub fn face_halfedges(&self, f: FaceId) -> Vec<HalfEdgeId> {
+        let first = self.faces[f.0];
+        let mut halfedges = vec![first];
+        let mut h = self.next(first);
+        while h != first {
+            halfedges.push(h);
+            h = self.next(h);
+        }
+
+        return halfedges;
+    }
+
+    pub fn face_vertices(&self, f: FaceId) -> Vec<VertexId> {
+        return self
+            .face_halfedges(f)
+            .into_iter()
+            .map(|h| self.origin(h))
+            .collect();
+    }
+
+    /// The unit normal of a face by Newell's method, which tolerates
+    /// non-planar polygons; zero if the face is degenerate.
+    pub fn face_normal(&self, f: FaceId) -> Vector3D {
+        let points: Vec<Vector3D> = self
+            .face_vertices(f)
+            .into_iter()
+            .map(|v| self.position(v))
+            .collect();
+
+        let mut normal = Vector3D::default();
+        for (k, p) in points.iter().enumerate() {
+            normal += p.cross(&points[(k + 1) % points.len()]);
+        }
+        if normal.magnitude() == 0.0 {
+            return normal;
+        }
+
+        return normal.normalize();
+    }
+
+    /// The average of a face's vertices.
+    pub fn face_centroid(&self, f: FaceId) -> Vector3D {
+        let vertices = self.face_vertices(f);
+        let sum = vertices
+            .iter()
+            .fold(Vector3D::default(), |sum, &v| sum + self.position(v));
+
+        return sum / vertices.len() as f32;
+    }
+
+    /// The outgoing half-edges of a vertex in order around it, starting
+    /// at the boundary if the vertex is on one. At a non-manifold vertex
+    /// this is only one of its fans.
+    pub fn outgoing(&self, v: VertexId) -> Vec<HalfEdgeId> {
+        let Some(start) = self.vertices[v.0] else {
+            return Vec::new();
+        };
+
+        let mut halfedges = vec![start];
+        let mut h = start;
+        while let Some(t) = self.twin(self.prev(h)) {
+            if t == start {
+                break;
+            }
+            halfedges.push(t);
+            h = t;
+        }
+
+        return halfedges;
+    }
+
+    /// The vertices sharing an edge with `v`.
+    pub fn neighbours(&self, v: VertexId) -> Vec<VertexId> {
+        let outgoing = self.outgoing(v);
+        let mut neighbours: Vec<VertexId> = outgoing.iter().map(|&h| self.target(h)).collect();
+        // The last edge of an open fan only has an incoming half-edge.
+        if let Some(&last) = outgoing.last() {
+            if self.twin(self.prev(last)).is_none() {
+                neighbours.push(self.origin(self.prev(last)));
+            }
+        }
+
+        return neighbours;
+    }
+
+    pub fn is_boundary_vertex(&self, v: VertexId) -> bool {
+        self.vertices[v.0].is_some_and(|h| self.twin(h).is_none())
+    }
+
+    /// Whether every edge has two faces.
+    pub fn is_closed(&self) -> bool {
+        self.halfedges.iter().all(|h| h.twin.is_some())
+    }
+
+    /// Checks that every vertex's faces form one fan, so the mesh is a
+    /// 2-manifold, possibly with boundary. Edges were checked on
+    /// construction.
+    pub fn validate(&self) -> Result<(), HalfEdgeError> {
+        let mut outgoing = vec![0; self.vertices.len()];
+        for h in &self.halfedges {
+            outgoing[h.origin.0] += 1;
+        }
+
+        for (v, &count) in outgoing.iter().enumerate() {
+            if self.outgoing(VertexId(v)).len() != count {
+                return Err(HalfEdgeError::NonManifoldVertex(VertexId(v)));
+            }
+        }
+
+        return Ok(());
+    }
+}
+
+impl TryFrom<&Mesh> for HalfEdgeMesh {
+    type Error = HalfEdgeError;
+
+    /// Uses only the positions, so weld the mesh first or its UV and
+    /// normal seams become boundaries.
+    fn try_from(mesh: &Mesh) -> Result<Self, HalfEdgeError> {
+        let faces: Vec<Vec<usize>> = mesh
+            .triangles()
+            .map(|t| t.iter().map(|&v| v as usize).collect())
+            .collect();
+
+        return HalfEdgeMesh::from_polygons(mesh.positions.clone(), &faces);
+    }
+}
+
+impl From<&HalfEdgeMesh> for Mesh {
+    /// Triangulates each face as a fan from its first vertex, which is
+    /// exact for convex faces.
+    fn from(mesh: &HalfEdgeMesh) -> Self {
+        let (positions, faces) = mesh.to_polygons();
+        let indices = faces
+            .iter()
+            .flat_map(|face| (1..face.len() - 1).flat_map(move |k| [face[0], face[k], face[k + 1]]))
+            .map(|v| v as u32)
+            .collect();
+
+        return Mesh::new(positions, indices);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn topology_test() {
+        let mut cube = Mesh::cube(2.0);
+        cube.normals.clear();
+        cube.uvs.clear();
+        cube.weld(1e-6);
+
+        let mesh = HalfEdgeMesh::try_from(&cube).unwrap();
+        assert_eq!(mesh.validate(), Ok(()));
+        assert!(mesh.is_closed());
+        assert_eq!(
+            (mesh.vertex_count(), mesh.edge_count(), mesh.face_count()),
+            (8, 18, 12)
+        );
+        for v in 0..8 {
+            let v = VertexId(v);
+            let neighbours = mesh.neighbours(v);
+            assert_eq!(neighbours.len(), mesh.outgoing(v).len());
+            for n in neighbours {
+                let h = mesh.find_halfedge(v, n).unwrap();
+                assert_eq!(mesh.twin(h).map(|t| mesh.origin(t)), Some(n));
+            }
+        }
+        for f in mesh.faces() {
+            let [a, b, c] = [0, 1, 2].map(|k| mesh.face_vertices(f)[k].0 as u32);
+            assert_eq!(mesh.face_normal(f), cube.face_normal([a, b, c]));
+        }
+
+        let back = Mesh::from(&mesh);
+        assert_eq!(back.indices, cube.indices);
+        assert_eq!(back.positions, cube.positions);
+    }
+
+    #[test]
+    fn manifold_test() {
+        let points = |n: usize| -> Vec<Vector3D> {
+            (0..n)
+                .map(|i| Vector3D::create(i as f32, (i * i) as f32, 0.0))
+                .collect()
+        };
+
+        // An open fan around vertex 0.
+        let fan = HalfEdgeMesh::from_polygons(points(4), &[vec![0, 1, 2], vec![0, 2, 3]]).unwrap();
+        assert_eq!(fan.validate(), Ok(()));
+        assert!(!fan.is_closed());
+        assert!(fan.is_boundary_vertex(VertexId(0)));
+        assert_eq!(fan.edge_count(), 5);
+        assert_eq!(fan.neighbours(VertexId(0)).len(), 3);
+
+        // Two triangles touching only at vertex 0.
+        let bowtie =
+            HalfEdgeMesh::from_polygons(points(5), &[vec![0, 1, 2], vec![0, 3, 4]]).unwrap();
+        assert_eq!(
+            bowtie.validate(),
+            Err(HalfEdgeError::NonManifoldVertex(VertexId(0)))
+        );
+
+        let error =
+            |faces: &[Vec<usize>]| HalfEdgeMesh::from_polygons(points(4), faces).unwrap_err();
+        assert_eq!(
+            error(&[vec![0, 1, 2], vec![0, 1, 3]]),
+            HalfEdgeError::NonManifoldEdge(0, 1)
+        );
+        assert_eq!(
+            error(&[vec![0, 1, 2], vec![1, 0, 3], vec![0, 1, 3]]),
+            HalfEdgeError::NonManifoldEdge(0, 1)
+        );
+        assert_eq!(error(&[vec![0, 1]]), HalfEdgeError::FaceTooSmall(0));
+        assert_eq!(error(&[vec![0, 1, 0]]), HalfEdgeError::RepeatedVertex(0));
+        assert_eq!(
+            error(&[vec![0, 1, 4]]),
+            HalfEdgeError::IndexOutOfRange {
+                index: 4,
+                vertices: 4
+            }
+        );
+    }
+}
@@ -0,0 +1,211 @@
+//! Levels of detail: chains of simplified meshes, and picking one by how
+//! large its error looks on screen.
+
+use super::{simplify::SimplifyOptions, Mesh};
+use crate::{
+    camera::Camera,
+    math::{Matrix4x4F32, Vector3D},
+};
+
+/// A level must have at least this fraction fewer triangles than the
+/// one before it to be kept.
+const MIN_REDUCTION: f32 = 0.1;
+
+/// One level of a [`LodChain`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lod {
+    pub mesh: Mesh,
+    /// How far, in mesh units, the surface may stray from the full mesh.
+    pub error: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LodChain {
+    /// Finest first. The first level is the full mesh, with no error.
+    pub levels: Vec<Lod>,
+    /// The full mesh's bounding sphere, in mesh space.
+    pub center: Vector3D,
+    pub radius: f32,
+}
+
+impl LodChain {
+    /// Builds up to `max_levels` levels, including the full mesh, each
+    /// with about `ratio` times the triangles of the one before. Stops
+    /// early once a level would need more than `max_error` or would
+    /// barely shrink. Levels are simplified from the full mesh, so errors
+    /// do not compound, and ordered for the vertex cache.
+    pub fn generate(mesh: &Mesh, max_levels: usize, ratio: f32, max_error: f32) -> Self {
+        let (center, radius) = mesh.bounding_sphere();
+        let mut levels = vec![Lod {
+            mesh: mesh.clone(),
+            error: 0.0,
+        }];
+
+        while levels.len() < max_levels {
+            let previous = levels.last().unwrap();
+            let triangles = previous.mesh.triangle_count() as f32;
+            let options = SimplifyOptions {
+                target_triangles: (triangles * ratio) as usize,
+                max_error,
+                lock_border: false,
+            };
+
+            let mut simplified = mesh.clone();
+            let error = simplified.simplify(&options);
+            if simplified.triangle_count() as f32 > triangles * (1.0 - MIN_REDUCTION) {
+                break;
+            }
+            simplified.optimize_vertex_cache();
+            levels.push(Lod {
+                mesh: simplified,
+                error: error.max(previous.error),
+            });
+        }
+
+        return Self {
+            levels,
+            center,
+            radius,
+        };
+    }
+
+    /// The coarsest level whose error covers at most `max_pixels` pixels
+    /// with the mesh placed by `world` and seen by `camera`. Measures from
+    /// the nearest depth of the bounding sphere and uses the largest scale
+    /// of `world`, so the error on screen is never underestimated.
+    pub fn select(&self, camera: &Camera, world: &Matrix4x4F32, max_pixels: f32) -> usize {
+        let center = world.transform_point(&self.center);
+        let scale = [
+            Vector3D::create(1.0, 0.0, 0.0),
+            Vector3D::create(0.0, 1.0, 0.0),
+            Vector3D::create(0.0, 0.0, 1.0),
+        ]
+        .iter()
+        .map(|axis| world.transform_vector(axis).magnitude())
+        .fold(0.0, f32::max);
+        let depth = camera.forward().dot(&(center - camera.position)) - self.radius * scale;
+
+        return (0..self.levels.len())
+            .rev()
+            .find(|&i| camera.projected_size(self.levels[i].error * scale, depth) <= max_pixels)
+            .unwrap_or(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+    use crate::math::Vector2D;
+
+    #[test]
+    fn lod_test() {
+        let chain = LodChain::generate(&Mesh::sphere(1.0, 32, 16), 5, 0.5, 0.25);
+
+        assert!(chain.levels.len() >= 3, "{}", chain.levels.len());
+        assert_eq!(chain.levels[0].error, 0.0);
+        for pair in chain.levels.windows(2) {
+            assert!(pair[1].mesh.triangle_count() < pair[0].mesh.triangle_count());
+            assert!(pair[1].error >= pair[0].error);
+            assert!(pair[1].error <= 0.25);
+        }
+
+        let mut camera =
+            Camera::perspective(FRAC_PI_2, 0.1, 1000.0, Vector2D::create(800.0, 600.0));
+        let identity = Matrix4x4F32::identity();
+        let mut previous = 0;
+        for distance in [1.5, 5.0, 20.0, 100.0, 1000.0] {
+            camera.position = Vector3D::create(0.0, 0.0, distance);
+            let level = chain.select(&camera, &identity, 1.0);
+            assert!(level >= previous, "{distance}");
+            previous = level;
+        }
+        assert_eq!(previous, chain.levels.len() - 1);
+
+        // Scaling the mesh up makes its error larger on screen.
+        camera.position = Vector3D::create(0.0, 0.0, 1.5);
+        assert_eq!(chain.select(&camera, &identity, 1.0), 0);
+        camera.position = Vector3D::create(0.0, 0.0, 1000.0);
+        let scale = Matrix4x4F32::scale(&Vector3D::create(100.0, 100.0, 100.0));
+        assert!(chain.select(&camera, &scale, 1.0) < chain.levels.len() - 1);
+    }
+
+    /// Levels with the given errors around a unit sphere at the origin.
+    fn chain(errors: &[f32]) -> LodChain {
+        LodChain {
+            levels: errors
+                .iter()
+                .map(|&error| Lod {
+                    mesh: Mesh::plane(1.0, 1.0, 1, 1),
+                    error,
+                })
+                .collect(),
+            center: Vector3D::default(),
+            radius: 1.0,
+        }
+    }
+
+    #[test]
+    fn select_test() {
+        let chain = chain(&[0.0, 0.01, 0.1, 1.0]);
+        let identity = Matrix4x4F32::identity();
+        // 600 pixels span 90 degrees, so an error `e` at depth `d` covers
+        // `300 * e / d` pixels; the nearest depth is one radius closer.
+        let mut camera =
+            Camera::perspective(FRAC_PI_2, 0.1, 1000.0, Vector2D::create(800.0, 600.0));
+
+        for (distance, level) in [(2.0, 0), (11.0, 1), (101.0, 2), (1001.0, 3)] {
+            camera.position = Vector3D::create(0.0, 0.0, distance);
+            assert_eq!(chain.select(&camera, &identity, 1.0), level, "{distance}");
+        }
+
+        // A larger budget, an offset to the side, or a placement further
+        // away pick coarser levels.
+        camera.position = Vector3D::create(0.0, 0.0, 11.0);
+        assert_eq!(chain.select(&camera, &identity, 10.0), 2);
+        camera.position = Vector3D::create(50.0, 0.0, 11.0);
+        assert_eq!(chain.select(&camera, &identity, 1.0), 1);
+        let away = Matrix4x4F32::translation(&Vector3D::create(0.0, 0.0, -90.0));
+        assert_eq!(chain.select(&camera, &away, 1.0), 2);
+
+        // Inside the bounding sphere nothing but the full mesh will do.
+        camera.position = Vector3D::create(0.0, 0.0, 0.5);
+        assert_eq!(chain.select(&camera, &identity, 1000.0), 0);
+    }
+
+    #[test]
+    fn select_orthographic_test() {
+        let chain = chain(&[0.0, 0.01, 0.1, 1.0]);
+        // 60 pixels per unit at any distance.
+        let mut camera = Camera::orthographic(10.0, 0.1, 1000.0, Vector2D::create(800.0, 600.0));
+
+        for distance in [5.0, 500.0] {
+            camera.position = Vector3D::create(0.0, 0.0, distance);
+            assert_eq!(chain.select(&camera, &Matrix4x4F32::identity(), 1.0), 1);
+        }
+    }
+
+    #[test]
+    fn generate_test() {
+        let mesh = Mesh::sphere(1.0, 32, 16);
+        let chain = LodChain::generate(&mesh, 3, 0.5, f32::INFINITY);
+        assert_eq!(chain.levels.len(), 3);
+        assert_eq!(chain.levels[0].mesh, mesh);
+        // Each level meets its share of the previous level's triangles.
+        for pair in chain.levels.windows(2) {
+            let target = pair[0].mesh.triangle_count() / 2;
+            assert!(pair[1].mesh.triangle_count() <= target);
+        }
+
+        // With no error allowed, or room for one level, only the full
+        // mesh remains.
+        assert_eq!(LodChain::generate(&mesh, 4, 0.5, 0.0).levels.len(), 1);
+        assert_eq!(
+            LodChain::generate(&mesh, 1, 0.5, f32::INFINITY)
+                .levels
+                .len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,681 @@
+//! Indexed triangle meshes.
+//!
+//! A [`Mesh`] keeps its vertex attributes in parallel arrays. Normals,
+//! tangents and bounds can be computed from the positions; [`optimize`]
+//! reorders triangles and vertices for the GPU's caches and [`primitives`]
+//! generates common shapes. [`halfedge`] adds polygon adjacency for the
+//! modelling operations in [`edit`]; [`simplify`] reduces triangle counts
+//! and [`lod`] builds levels of detail from it.
+
+#![allow(clippy::needless_return)]
+
+pub mod edit;
+pub mod halfedge;
+pub mod lod;
+pub mod optimize;
+pub mod primitives;
+pub mod simplify;
+
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+use crate::{
+    asset::{gltf::Primitive, ObjMesh},
+    math::{Vector2D, Vector3D},
+    spatial::Aabb,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeshError {
+    /// The index count is not a multiple of three.
+    NotTriangles(usize),
+    IndexOutOfRange {
+        index: u32,
+        vertices: usize,
+    },
+    /// An attribute is neither empty nor one per vertex.
+    AttributeLength {
+        attribute: &'static str,
+        len: usize,
+        vertices: usize,
+    },
+    /// An operation needs an attribute the mesh does not have.
+    MissingAttribute(&'static str),
+}
+
+impl Display for MeshError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            MeshError::NotTriangles(count) => {
+                write!(f, "{count} indices do not form triangles")
+            }
+            MeshError::IndexOutOfRange { index, vertices } => {
+                write!(f, "index {index} out of range; {vertices} vertices")
+            }
+            MeshError::AttributeLength {
+                attribute,
+                len,
+                vertices,
+            } => write!(f, "{len} {attribute} for {vertices} vertices"),
+            MeshError::MissingAttribute(attribute) => write!(f, "the mesh has no {attribute}"),
+        }
+    }
+}
+
+impl Error for MeshError {}
+
+/// An indexed triangle mesh with counterclockwise front faces.
+///
+/// `normals`, `uvs` and `tangents` are either empty or hold one value per
+/// position. UVs have their origin at the bottom left.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Mesh {
+    pub positions: Vec<Vector3D>,
+    pub normals: Vec<Vector3D>,
+    pub uvs: Vec<Vector2D>,
+    /// `xyz` is the tangent and `w` the sign of the bitangent, which is
+    /// `w * normal × tangent`.
+    pub tangents: Vec<[f32; 4]>,
+    pub indices: Vec<u32>,
+}
+
+impl From<ObjMesh> for Mesh {
+    fn from(mesh: ObjMesh) -> Self {
+        Self {
+            positions: mesh.positions,
+            normals: mesh.normals,
+            uvs: mesh.uvs,
+            tangents: vec![],
+            indices: mesh.indices,
+        }
+    }
+}
+
+impl From<Primitive> for Mesh {
+    /// Flips V, since glTF puts the UV origin at the top left.
+    fn from(primitive: Primitive) -> Self {
+        Self {
+            positions: primitive.positions,
+            normals: primitive.normals,
+            uvs: primitive
+                .uvs
+                .iter()
+                .map(|uv| Vector2D::create(uv.x(), 1.0 - uv.y()))
+                .collect(),
+            tangents: primitive.tangents,
+            indices: primitive.indices,
+        }
+    }
+}
+
+impl Mesh {
+    pub fn new(positions: Vec<Vector3D>, indices: Vec<u32>) -> Self {
+        Self {
+            positions,
+            indices,
+            ..Self::default()
+        }
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    pub fn triangles(&self) -> impl Iterator<Item = [u32; 3]> + '_ {
+        self.indices.chunks_exact(3).map(|t| [t[0], t[1], t[2]])
+    }
+
+    /// Checks that the indices form triangles over existing vertices and
+    /// that every attribute has the right length.
+    pub fn validate(&self) -> Result<(), MeshError> {
+        let vertices = self.vertex_count();
+        if !self.indices.len().is_multiple_of(3) {
+            return Err(MeshError::NotTriangles(self.indices.len()));
+        }
+        if let Some(&index) = self.indices.iter().find(|&&i| i as usize >= vertices) {
+            return Err(MeshError::IndexOutOfRange { index, vertices });
+        }
+
+        for (attribute, len) in [
+            ("normals", self.normals.len()),
+            ("uvs", self.uvs.len()),
+            ("tangents", self.tangents.len()),
+        ] {
+            if len != 0 && len != vertices {
+                return Err(MeshError::AttributeLength {
+                    attribute,
+                    len,
+                    vertices,
+                });
+            }
+        }
+
+        return Ok(());
+    }
+
+    pub fn bounds(&self) -> Aabb {
+        Aabb::from_points(&self.positions)
+    }
+
+    /// A sphere around every position, as `(center, radius)`. Ritter's
+    /// method: within a few percent of the smallest such sphere.
+    pub fn bounding_sphere(&self) -> (Vector3D, f32) {
+        let Some(&first) = self.positions.first() else {
+            return (Vector3D::default(), 0.0);
+        };
+        let farthest = |from: Vector3D| {
+            self.positions
+                .iter()
+                .copied()
+                .max_by(|a, b| a.distance(&from).total_cmp(&b.distance(&from)))
+                .unwrap()
+        };
+
+        let a = farthest(first);
+        let b = farthest(a);
+        let mut center = (a + b) * 0.5;
+        let mut radius = a.distance(&b) / 2.0;
+
+        for &p in &self.positions {
+            let distance = p.distance(&center);
+            if distance > radius {
+                // Grow just enough to reach `p`, keeping the far side fixed.
+                let new_radius = (radius + distance) / 2.0;
+                center = center + (p - center) * ((new_radius - radius) / distance);
+                radius = new_radius;
+            }
+        }
+
+        return (center, radius);
+    }
+
+    /// The unit normal of a triangle, or zero if it is degenerate.
+    pub fn face_normal(&self, [a, b, c]: [u32; 3]) -> Vector3D {
+        let [a, b, c] = [a, b, c].map(|i| self.positions[i as usize]);
+        let normal = (b - a).cross(&(c - a));
+        if normal.magnitude() == 0.0 {
+            return normal;
+        }
+
+        return normal.normalize();
+    }
+
+    /// Replaces the normals with the average of the adjacent face normals,
+    /// weighted by the angle of each face at the vertex. Vertices on no
+    /// proper triangle get a zero normal.
+    pub fn compute_smooth_normals(&mut self) {
+        let mut normals = vec![Vector3D::default(); self.vertex_count()];
+
+        for triangle in self.triangles() {
+            let normal = self.face_normal(triangle);
+            for (k, angle) in corner_angles(&self.positions, triangle).iter().enumerate() {
+                normals[triangle[k] as usize] += normal * *angle;
+            }
+        }
+
+        self.normals = normals
+            .into_iter()
+            .map(|n| {
+                if n.magnitude() > 0.0 {
+                    n.normalize()
+                } else {
+                    n
+                }
+            })
+            .collect();
+    }
+
+    /// Gives every triangle its own vertices carrying the face normal.
+    /// Tangents are dropped, since they no longer match the normals.
+    pub fn compute_flat_normals(&mut self) {
+        let corners = std::mem::take(&mut self.indices);
+        let normals: Vec<Vector3D> = corners
+            .chunks_exact(3)
+            .flat_map(|t| [self.face_normal([t[0], t[1], t[2]]); 3])
+            .collect();
+
+        self.positions = corners
+            .iter()
+            .map(|&i| self.positions[i as usize])
+            .collect();
+        if !self.uvs.is_empty() {
+            self.uvs = corners.iter().map(|&i| self.uvs[i as usize]).collect();
+        }
+        self.normals = normals;
+        self.tangents.clear();
+        self.indices = (0..corners.len() as u32).collect();
+    }
+
+    /// Computes per-vertex tangents from the UVs: each face's tangent is
+    /// projected onto the vertex's tangent plane, normalized and weighted
+    /// by the face's angle at the vertex, and the bitangent sign comes from
+    /// the accumulated bitangent.
+    ///
+    /// This borrows MikkTSpace's weighting but not its grouping of face
+    /// corners: all faces at a vertex are averaged, except that vertices
+    /// shared by faces of opposite UV orientation, as on the seam of a
+    /// mirrored UV island, are split so the two sides do not cancel out.
+    /// Normal maps baked with MikkTSpace may therefore shade slightly
+    /// differently where a vertex's faces have very different tangents.
+    ///
+    /// The split copies are appended after the existing vertices, whose
+    /// indices do not change, and the mirrored faces are re-indexed to use
+    /// them. Returns, for each copy in order, the vertex it was copied
+    /// from, so that data kept alongside the mesh can be extended to match.
+    ///
+    /// Faces with degenerate UVs do not contribute; vertices with no other
+    /// faces get an arbitrary tangent perpendicular to the normal.
+    pub fn compute_tangents(&mut self) -> Result<Vec<u32>, MeshError> {
+        self.validate()?;
+        if self.normals.is_empty() {
+            return Err(MeshError::MissingAttribute("normals"));
+        }
+        if self.uvs.is_empty() {
+            return Err(MeshError::MissingAttribute("uvs"));
+        }
+        let copied = self.split_mirrored_vertices();
+
+        let mut tangents = vec![Vector3D::default(); self.vertex_count()];
+        let mut bitangents = vec![Vector3D::default(); self.vertex_count()];
+
+        for triangle in self.triangles() {
+            let [a, b, c] = triangle.map(|i| i as usize);
+            let (e1, e2) = (
+                self.positions[b] - self.positions[a],
+                self.positions[c] - self.positions[a],
+            );
+            let (d1, d2) = (self.uvs[b] - self.uvs[a], self.uvs[c] - self.uvs[a]);
+            let det = self.uv_determinant(triangle);
+            if det.abs() < 1e-12 {
+                continue;
+            }
+
+            let tangent = (e1 * d2.y() - e2 * d1.y()) / det;
+            let bitangent = (e2 * d1.x() - e1 * d2.x()) / det;
+            let angles = corner_angles(&self.positions, triangle);
+            for (k, &v) in [a, b, c].iter().enumerate() {
+                let n = self.normals[v];
+                let t = tangent - n * n.dot(&tangent);
+                if t.magnitude() > 0.0 {
+                    tangents[v] += t.normalize() * angles[k];
+                }
+                bitangents[v] += bitangent * angles[k];
+            }
+        }
+
+        self.tangents = (0..self.vertex_count())
+            .map(|v| {
+                let n = self.normals[v];
+                let t = if tangents[v].magnitude() > 1e-12 {
+                    tangents[v].normalize()
+                } else {
+                    any_perpendicular(n)
+                };
+                let w = if n.cross(&t).dot(&bitangents[v]) < 0.0 {
+                    -1.0
+                } else {
+                    1.0
+                };
+                return [t.x(), t.y(), t.z(), w];
+            })
+            .collect();
+
+        return Ok(copied);
+    }
+
+    /// Gives faces with mirrored UVs their own copy of every vertex they
+    /// share with unmirrored faces. Returns the vertices copied, in the
+    /// order the copies were appended.
+    fn split_mirrored_vertices(&mut self) -> Vec<u32> {
+        let count = self.vertex_count();
+        let mirrored: Vec<Option<bool>> = self
+            .triangles()
+            .map(|t| {
+                let det = self.uv_determinant(t);
+                return (det.abs() >= 1e-12).then_some(det < 0.0);
+            })
+            .collect();
+
+        let mut sides = vec![[false; 2]; count];
+        for (triangle, side) in self.triangles().zip(&mirrored) {
+            if let Some(side) = side {
+                for v in triangle {
+                    sides[v as usize][*side as usize] = true;
+                }
+            }
+        }
+
+        let mut kept: Vec<usize> = (0..count).collect();
+        let mut copies = HashMap::new();
+        for (v, _) in sides.iter().enumerate().filter(|(_, s)| s[0] && s[1]) {
+            copies.insert(v as u32, kept.len() as u32);
+            kept.push(v);
+        }
+        if copies.is_empty() {
+            return Vec::new();
+        }
+
+        self.keep_vertices(&kept);
+        for (triangle, side) in self.indices.chunks_exact_mut(3).zip(&mirrored) {
+            if *side == Some(true) {
+                for i in triangle {
+                    *i = copies.get(i).copied().unwrap_or(*i);
+                }
+            }
+        }
+
+        return kept[count..].iter().map(|&v| v as u32).collect();
+    }
+
+    /// Twice the signed UV area of a triangle; negative when its UVs are
+    /// mirrored relative to its winding.
+    fn uv_determinant(&self, triangle: [u32; 3]) -> f32 {
+        let [a, b, c] = triangle.map(|i| self.uvs[i as usize]);
+        let (d1, d2) = (b - a, c - a);
+
+        return d1.x() * d2.y() - d2.x() * d1.y();
+    }
+
+    /// Merges vertices whose attributes all lie within `epsilon` of each
+    /// other and drops triangles that collapse. With `epsilon` zero only
+    /// identical vertices merge. Returns the number of vertices removed.
+    pub fn weld(&mut self, epsilon: f32) -> usize {
+        let count = self.vertex_count();
+        let cell = |p: Vector3D| -> [i64; 3] {
+            if epsilon > 0.0 {
+                [p.x(), p.y(), p.z()].map(|c| (c / epsilon).floor() as i64)
+            } else {
+                [p.x(), p.y(), p.z()].map(|c| c.to_bits() as i64)
+            }
+        };
+        let reach = if epsilon > 0.0 { 1 } else { 0 };
+
+        let mut cells: HashMap<[i64; 3], Vec<usize>> = HashMap::new();
+        let mut kept: Vec<usize> = Vec::new();
+        let mut remap = vec![0u32; count];
+
+        for (v, &position) in self.positions.iter().enumerate() {
+            let [x, y, z] = cell(position);
+            let mut found = None;
+            'search: for dx in -reach..=reach {
+                for dy in -reach..=reach {
+                    for dz in -reach..=reach {
+                        let Some(candidates) = cells.get(&[x + dx, y + dy, z + dz]) else {
+                            continue;
+                        };
+                        if let Some(&k) = candidates
+                            .iter()
+                            .find(|&&k| self.vertices_close(kept[k], v, epsilon))
+                        {
+                            found = Some(k);
+                            break 'search;
+                        }
+                    }
+                }
+            }
+
+            remap[v] = match found {
+                Some(k) => k as u32,
+                None => {
+                    cells.entry([x, y, z]).or_default().push(kept.len());
+                    kept.push(v);
+                    kept.len() as u32 - 1
+                }
+            };
+        }
+
+        self.keep_vertices(&kept);
+        self.indices = self
+            .indices
+            .chunks_exact(3)
+            .map(|t| {
+                [
+                    remap[t[0] as usize],
+                    remap[t[1] as usize],
+                    remap[t[2] as usize],
+                ]
+            })
+            .filter(|[a, b, c]| a != b && b != c && a != c)
+            .flatten()
+            .collect();
+
+        return count - kept.len();
+    }
+
+    /// Drops vertices no triangle uses, keeping the order of the rest.
+    /// Returns the number removed.
+    pub fn remove_unused_vertices(&mut self) -> usize {
+        let count = self.vertex_count();
+        let mut remap = vec![u32::MAX; count];
+        for &i in &self.indices {
+            remap[i as usize] = 0;
+        }
+
+        let mut kept = Vec::new();
+        for (v, new) in remap.iter_mut().enumerate() {
+            if *new == 0 {
+                *new = kept.len() as u32;
+                kept.push(v);
+            }
+        }
+        for i in self.indices.iter_mut() {
+            *i = remap[*i as usize];
+        }
+        self.keep_vertices(&kept);
+
+        return count - kept.len();
+    }
+
+    fn vertices_close(&self, a: usize, b: usize, epsilon: f32) -> bool {
+        let close3 = |p: Vector3D, q: Vector3D| (p - q).magnitude() <= epsilon;
+
+        return close3(self.positions[a], self.positions[b])
+            && (self.normals.is_empty() || close3(self.normals[a], self.normals[b]))
+            && (self.uvs.is_empty() || (self.uvs[a] - self.uvs[b]).magnitude() <= epsilon)
+            && (self.tangents.is_empty() || {
+                let (s, t) = (self.tangents[a], self.tangents[b]);
+                s.iter().zip(t).all(|(x, y)| (x - y).abs() <= epsilon)
+            });
+    }
+
+    /// Keeps the attributes of the listed vertices, in that order. Indices
+    /// are left to the caller.
+    pub(crate) fn keep_vertices(&mut self, kept: &[usize]) {
+        fn pick<T: Copy>(values: &mut Vec<T>, kept: &[usize]) {
+            if !values.is_empty() {
+                *values = kept.iter().map(|&v| values[v]).collect();
+            }
+        }
+
+        pick(&mut self.positions, kept);
+        pick(&mut self.normals, kept);
+        pick(&mut self.uvs, kept);
+        pick(&mut self.tangents, kept);
+    }
+}
+
+/// The interior angle of a triangle at each of its corners.
+fn corner_angles(positions: &[Vector3D], triangle: [u32; 3]) -> [f32; 3] {
+    let p = triangle.map(|i| positions[i as usize]);
+
+    return std::array::from_fn(|k| {
+        let (u, v) = (p[(k + 1) % 3] - p[k], p[(k + 2) % 3] - p[k]);
+        let lengths = u.magnitude() * v.magnitude();
+        if lengths == 0.0 {
+            return 0.0;
+        }
+        return (u.dot(&v) / lengths).clamp(-1.0, 1.0).acos();
+    });
+}
+
+/// A unit vector perpendicular to unit `n`.
+fn any_perpendicular(n: Vector3D) -> Vector3D {
+    let axis = if n.x().abs() < 0.9 {
+        Vector3D::create(1.0, 0.0, 0.0)
+    } else {
+        Vector3D::create(0.0, 1.0, 0.0)
+    };
+
+    return (axis - n * n.dot(&axis)).normalize();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asset::obj;
+
+    #[test]
+    fn validate_test() {
+        let mut mesh = Mesh::new(vec![Vector3D::default(); 3], vec![0, 1, 2]);
+        assert_eq!(mesh.validate(), Ok(()));
+
+        mesh.indices.push(0);
+        assert_eq!(mesh.validate(), Err(MeshError::NotTriangles(4)));
+        mesh.indices.extend([1, 3]);
+        assert_eq!(
+            mesh.validate(),
+            Err(MeshError::IndexOutOfRange {
+                index: 3,
+                vertices: 3
+            })
+        );
+        mesh.indices.truncate(3);
+        mesh.uvs.push(Vector2D::default());
+        assert_eq!(
+            mesh.validate().unwrap_err().to_string(),
+            "1 uvs for 3 vertices"
+        );
+        assert_eq!(
+            mesh.compute_tangents(),
+            Err(MeshError::AttributeLength {
+                attribute: "uvs",
+                len: 1,
+                vertices: 3
+            })
+        );
+    }
+
+    #[test]
+    fn normals_test() {
+        // Two faces of a cube corner sharing an edge.
+        let mut mesh = Mesh::new(
+            vec![
+                Vector3D::create(0.0, 0.0, 0.0),
+                Vector3D::create(1.0, 0.0, 0.0),
+                Vector3D::create(0.0, 1.0, 0.0),
+                Vector3D::create(0.0, 0.0, 1.0),
+            ],
+            vec![0, 2, 1, 0, 1, 3],
+        );
+
+        mesh.compute_smooth_normals();
+        assert_eq!(mesh.normals[2], Vector3D::create(0.0, 0.0, -1.0));
+        assert_eq!(
+            mesh.normals[0],
+            Vector3D::create(0.0, -1.0, -1.0).normalize()
+        );
+
+        mesh.compute_flat_normals();
+        assert_eq!(mesh.vertex_count(), 6);
+        assert_eq!(mesh.normals[0], Vector3D::create(0.0, 0.0, -1.0));
+        assert_eq!(mesh.normals[3], Vector3D::create(0.0, -1.0, 0.0));
+        assert_eq!(mesh.validate(), Ok(()));
+
+        // The shared corners only weld once their normals agree.
+        assert_eq!(mesh.weld(1e-6), 0);
+        mesh.normals.clear();
+        assert_eq!(mesh.weld(1e-6), 2);
+        assert_eq!(mesh.indices, [0, 1, 2, 0, 2, 3]);
+    }
+
+    #[test]
+    fn tangents_test() {
+        let mut mesh = Mesh::plane(2.0, 2.0, 2, 2);
+        assert_eq!(mesh.compute_tangents(), Ok(Vec::new()));
+        for t in &mesh.tangents {
+            assert_eq!(*t, [1.0, 0.0, 0.0, 1.0]);
+        }
+
+        // Mirroring the UVs flips the handedness.
+        for uv in mesh.uvs.iter_mut() {
+            *uv = Vector2D::create(1.0 - uv.x(), uv.y());
+        }
+        mesh.compute_tangents().unwrap();
+        assert_eq!(mesh.tangents[0], [-1.0, 0.0, 0.0, -1.0]);
+
+        // Two quads sharing an edge, the right one with mirrored UVs: the
+        // shared vertices are split so each side keeps its own tangent.
+        let mut mesh = Mesh::new(
+            [0.0, 1.0, 2.0, 0.0, 1.0, 2.0]
+                .iter()
+                .enumerate()
+                .map(|(i, &x)| Vector3D::create(x, (i / 3) as f32, 0.0))
+                .collect(),
+            vec![0, 1, 4, 0, 4, 3, 1, 2, 5, 1, 5, 4],
+        );
+        mesh.normals = vec![Vector3D::create(0.0, 0.0, 1.0); 6];
+        mesh.uvs = mesh
+            .positions
+            .iter()
+            .map(|p| Vector2D::create(1.0 - (p.x() - 1.0).abs(), p.y()))
+            .collect();
+        assert_eq!(mesh.compute_tangents(), Ok(vec![1, 4]));
+        assert_eq!(mesh.vertex_count(), 8);
+        assert_eq!(mesh.indices, [0, 1, 4, 0, 4, 3, 6, 2, 5, 6, 5, 7]);
+        assert_eq!(mesh.tangents[1], [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(mesh.tangents[4], [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(mesh.tangents[6], [-1.0, 0.0, 0.0, -1.0]);
+        assert_eq!(mesh.tangents[7], [-1.0, 0.0, 0.0, -1.0]);
+        assert_eq!(mesh.positions[6], mesh.positions[1]);
+        assert_eq!(mesh.positions[7], mesh.positions[4]);
+        // Splitting again finds nothing left to split.
+        assert_eq!(mesh.compute_tangents(), Ok(Vec::new()));
+        assert_eq!(mesh.vertex_count(), 8);
+
+        mesh.uvs.clear();
+        assert_eq!(
+            mesh.compute_tangents(),
+            Err(MeshError::MissingAttribute("uvs"))
+        );
+    }
+
+    #[test]
+    fn bounds_test() {
+        let mesh = Mesh::sphere(2.0, 16, 8);
+        let bounds = mesh.bounds();
+        assert_eq!(bounds.min, Vector3D::create(-2.0, -2.0, -2.0));
+        assert_eq!(bounds.max, Vector3D::create(2.0, 2.0, 2.0));
+
+        let (center, radius) = mesh.bounding_sphere();
+        assert!(center.magnitude() < 0.1);
+        assert!(radius < 2.0 * 1.05);
+        for p in &mesh.positions {
+            assert!(p.distance(&center) <= radius + 1e-5);
+        }
+        assert_eq!(Mesh::default().bounding_sphere().1, 0.0);
+    }
+
+    #[test]
+    fn convert_test() {
+        let obj = obj::parse("v 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0 0\nvt 1 0\nvt 0 1\nf 1/1 2/2 3/3\n")
+            .unwrap();
+        let mesh = Mesh::from(obj.meshes[0].clone());
+        assert_eq!(mesh.validate(), Ok(()));
+        assert_eq!(mesh.normals[0], Vector3D::create(0.0, 0.0, 1.0));
+
+        let primitive = Primitive {
+            positions: mesh.positions.clone(),
+            uvs: vec![Vector2D::create(0.25, 0.0); 3],
+            indices: vec![0, 1, 2],
+            ..Primitive::default()
+        };
+        assert_eq!(Mesh::from(primitive).uvs[0], Vector2D::create(0.25, 1.0));
+    }
+}
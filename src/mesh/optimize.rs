@@ -0,0 +1,223 @@
+//! Triangle and vertex order for the post-transform vertex cache and for
+//! vertex fetch.
+
+use super::Mesh;
+
+/// Entries in the simulated cache of [`Mesh::optimize_vertex_cache`].
+const CACHE_SIZE: usize = 32;
+/// Score of the vertices of the triangle just emitted.
+const LAST_TRIANGLE_SCORE: f32 = 0.75;
+const CACHE_DECAY_POWER: f32 = 1.5;
+const VALENCE_BOOST_SCALE: f32 = 2.0;
+const VALENCE_BOOST_POWER: f32 = 0.5;
+
+/// Forsyth's score for a vertex at `position` in the cache, or `None` if
+/// not cached, with `remaining` triangles left to emit.
+fn vertex_score(position: Option<usize>, remaining: usize) -> f32 {
+    if remaining == 0 {
+        return -1.0;
+    }
+
+    let cache = match position {
+        None => 0.0,
+        Some(p) if p < 3 => LAST_TRIANGLE_SCORE,
+        Some(p) => {
+            let scale = 1.0 / (CACHE_SIZE - 3) as f32;
+            (1.0 - (p - 3) as f32 * scale).powf(CACHE_DECAY_POWER)
+        }
+    };
+
+    // Vertices with few triangles left are finished first, so they leave
+    // the cache for good.
+    return cache + VALENCE_BOOST_SCALE * (remaining as f32).powf(-VALENCE_BOOST_POWER);
+}
+
+impl Mesh {
+    /// Reorders triangles to reuse recently transformed vertices, using
+    /// Tom Forsyth's linear-speed vertex cache optimisation. Triangles keep
+    /// their winding.
+    pub fn optimize_vertex_cache(&mut self) {
+        let vertex_count = self.vertex_count();
+        let triangle_count = self.triangle_count();
+
+        // Triangles adjacent to each vertex, as a flat list with offsets.
+        let mut remaining = vec![0usize; vertex_count];
+        for &i in &self.indices {
+            remaining[i as usize] += 1;
+        }
+        let mut offsets = vec![0usize; vertex_count + 1];
+        for v in 0..vertex_count {
+            offsets[v + 1] = offsets[v] + remaining[v];
+        }
+        let mut adjacency = vec![0usize; self.indices.len()];
+        let mut filled = offsets.clone();
+        for (k, &i) in self.indices.iter().enumerate() {
+            adjacency[filled[i as usize]] = k / 3;
+            filled[i as usize] += 1;
+        }
+
+        let mut cache_position: Vec<Option<usize>> = vec![None; vertex_count];
+        let mut vertex_scores: Vec<f32> = (0..vertex_count)
+            .map(|v| vertex_score(None, remaining[v]))
+            .collect();
+        let mut triangle_scores: Vec<f32> = self
+            .triangles()
+            .map(|t| t.iter().map(|&v| vertex_scores[v as usize]).sum())
+            .collect();
+        let mut emitted = vec![false; triangle_count];
+        let mut order = Vec::with_capacity(self.indices.len());
+        let mut cache: Vec<u32> = Vec::with_capacity(CACHE_SIZE + 3);
+        let mut best = None;
+        let mut scan = 0;
+
+        for _ in 0..triangle_count {
+            let triangle = match best {
+                Some(t) => t,
+                None => {
+                    // Nothing cached is left; continue with any triangle.
+                    while emitted[scan] {
+                        scan += 1;
+                    }
+                    scan
+                }
+            };
+            emitted[triangle] = true;
+            let vertices = [0, 1, 2].map(|k| self.indices[3 * triangle + k]);
+            order.extend(vertices);
+
+            // Emitted vertices move to the front of the cache.
+            for &v in &vertices {
+                remaining[v as usize] -= 1;
+                cache.retain(|&c| c != v);
+            }
+            cache.splice(0..0, vertices);
+
+            for &v in &cache[CACHE_SIZE.min(cache.len())..] {
+                cache_position[v as usize] = None;
+            }
+            cache.truncate(CACHE_SIZE);
+
+            // Rescore the cached vertices and their triangles, and pick the
+            // best triangle among those.
+            best = None;
+            let mut best_score = f32::NEG_INFINITY;
+            for (p, &v) in cache.iter().enumerate() {
+                cache_position[v as usize] = Some(p);
+            }
+            for &v in cache.iter().chain(&vertices) {
+                let v = v as usize;
+                let score = vertex_score(cache_position[v], remaining[v]);
+                let delta = score - vertex_scores[v];
+                vertex_scores[v] = score;
+                for &t in &adjacency[offsets[v]..offsets[v + 1]] {
+                    if !emitted[t] {
+                        triangle_scores[t] += delta;
+                    }
+                }
+            }
+            for &v in &cache {
+                let v = v as usize;
+                for &t in &adjacency[offsets[v]..offsets[v + 1]] {
+                    if !emitted[t] && triangle_scores[t] > best_score {
+                        best_score = triangle_scores[t];
+                        best = Some(t);
+                    }
+                }
+            }
+        }
+
+        self.indices = order;
+    }
+
+    /// Renumbers vertices in the order the triangles first use them, so
+    /// vertex fetches walk memory forwards. Unreferenced vertices are
+    /// removed.
+    pub fn optimize_vertex_fetch(&mut self) {
+        let mut remap = vec![u32::MAX; self.vertex_count()];
+        let mut kept = Vec::new();
+
+        for i in self.indices.iter_mut() {
+            let v = *i as usize;
+            if remap[v] == u32::MAX {
+                remap[v] = kept.len() as u32;
+                kept.push(v);
+            }
+            *i = remap[v];
+        }
+
+        self.keep_vertices(&kept);
+    }
+
+    /// Average cache miss ratio: vertices transformed per triangle with a
+    /// FIFO cache of `cache_size` entries. Between 0.5 for an ideal grid
+    /// and 3.0 when nothing is reused.
+    pub fn acmr(&self, cache_size: usize) -> f32 {
+        if self.indices.is_empty() {
+            return 0.0;
+        }
+
+        let mut cache = std::collections::VecDeque::with_capacity(cache_size);
+        let mut misses = 0;
+        for &i in &self.indices {
+            if !cache.contains(&i) {
+                misses += 1;
+                if cache.len() == cache_size {
+                    cache.pop_front();
+                }
+                cache.push_back(i);
+            }
+        }
+
+        return misses as f32 / self.triangle_count() as f32;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn vertex_cache_test() {
+        let mut mesh = Mesh::plane(1.0, 1.0, 40, 40);
+
+        // Shuffle the triangles, keeping each one's winding.
+        let mut triangles: Vec<[u32; 3]> = mesh.triangles().collect();
+        triangles.shuffle(&mut StdRng::seed_from_u64(47));
+        mesh.indices = triangles.concat();
+        let mut sorted = triangles.clone();
+        sorted.sort();
+
+        let before = mesh.acmr(16);
+        mesh.optimize_vertex_cache();
+        let after = mesh.acmr(16);
+        assert!(before > 2.0, "{before}");
+        assert!(after < 0.8, "{after}");
+
+        let mut optimized: Vec<[u32; 3]> = mesh.triangles().collect();
+        optimized.sort();
+        assert_eq!(optimized, sorted);
+    }
+
+    #[test]
+    fn vertex_fetch_test() {
+        let mut mesh = Mesh::plane(1.0, 1.0, 2, 2);
+        let expected = mesh.clone();
+        mesh.indices.reverse();
+        mesh.positions.push(Default::default());
+        mesh.normals.push(Default::default());
+        mesh.uvs.push(Default::default());
+
+        mesh.optimize_vertex_fetch();
+        assert_eq!(mesh.vertex_count(), 9);
+        assert_eq!(&mesh.indices[..3], [0, 1, 2]);
+        assert_eq!(mesh.validate(), Ok(()));
+        for (a, b) in mesh.indices.iter().zip(expected.indices.iter().rev()) {
+            assert_eq!(mesh.positions[*a as usize], expected.positions[*b as usize]);
+            assert_eq!(mesh.uvs[*a as usize], expected.uvs[*b as usize]);
+        }
+
+        assert_eq!(Mesh::default().acmr(16), 0.0);
+    }
+}
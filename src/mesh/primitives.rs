@@ -0,0 +1,230 @@
+//! Generators for common shapes, centred on the origin with outward
+//! normals, counter-clockwise winding and UVs.
+
+use std::f32::consts::{PI, TAU};
+
+use super::Mesh;
+use crate::math::{Vector2D, Vector3D};
+
+impl Mesh {
+    /// An axis-aligned cube with edges of `size`. Each face has its own
+    /// four vertices, so normals stay flat, and its own `[0, 1]` UV square.
+    pub fn cube(size: f32) -> Self {
+        let h = size / 2.0;
+        let x = Vector3D::create(1.0, 0.0, 0.0);
+        let y = Vector3D::create(0.0, 1.0, 0.0);
+        let z = Vector3D::create(0.0, 0.0, 1.0);
+        // (normal, u, v) with u × v = normal.
+        let faces = [
+            (x, -z, y),
+            (-x, z, y),
+            (y, x, -z),
+            (-y, x, z),
+            (z, x, y),
+            (-z, -x, y),
+        ];
+
+        let mut mesh = Mesh::default();
+        for (n, u, v) in faces {
+            let base = mesh.vertex_count() as u32;
+            for (su, sv) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+                mesh.positions.push((n + u * su + v * sv) * h);
+                mesh.normals.push(n);
+                mesh.uvs
+                    .push(Vector2D::create((su + 1.0) / 2.0, (sv + 1.0) / 2.0));
+            }
+            mesh.indices.extend([0, 1, 2, 0, 2, 3].map(|i| base + i));
+        }
+
+        return mesh;
+    }
+
+    /// A UV sphere with `segments` columns around the y axis (at least 3)
+    /// and `rings` rows from pole to pole (at least 2). U runs around the
+    /// sphere from +z and the seam vertices are duplicated.
+    pub fn sphere(radius: f32, segments: usize, rings: usize) -> Self {
+        let segments = segments.max(3);
+        let rings = rings.max(2);
+
+        let mut mesh = Mesh::default();
+        mesh.push_grid(rings, segments, |i, j| {
+            let phi = PI * i as f32 / rings as f32;
+            let theta = TAU * j as f32 / segments as f32;
+            // Exact zeros at the poles, so their triangles collapse.
+            let sin_phi = if i == 0 || i == rings { 0.0 } else { phi.sin() };
+            let n = Vector3D::create(sin_phi * theta.sin(), phi.cos(), sin_phi * theta.cos());
+            let uv = Vector2D::create(j as f32 / segments as f32, 1.0 - i as f32 / rings as f32);
+            return (n * radius, n, uv);
+        });
+
+        return mesh;
+    }
+
+    /// A capped cylinder along the y axis with `segments` sides (at least
+    /// 3). The side and the caps have separate vertices.
+    pub fn cylinder(radius: f32, height: f32, segments: usize) -> Self {
+        let segments = segments.max(3);
+        let h = height / 2.0;
+        let angle = |j: usize| TAU * j as f32 / segments as f32;
+
+        let mut mesh = Mesh::default();
+        mesh.push_grid(1, segments, |i, j| {
+            let (sin, cos) = angle(j).sin_cos();
+            let n = Vector3D::create(sin, 0.0, cos);
+            let p = Vector3D::create(sin * radius, if i == 0 { h } else { -h }, cos * radius);
+            return (
+                p,
+                n,
+                Vector2D::create(j as f32 / segments as f32, 1.0 - i as f32),
+            );
+        });
+
+        for sign in [1.0, -1.0] {
+            let n = Vector3D::create(0.0, sign, 0.0);
+            let center = mesh.vertex_count() as u32;
+            mesh.positions.push(n * h);
+            mesh.normals.push(n);
+            mesh.uvs.push(Vector2D::create(0.5, 0.5));
+
+            for j in 0..segments {
+                let (sin, cos) = angle(j).sin_cos();
+                mesh.positions
+                    .push(Vector3D::create(sin * radius, sign * h, cos * radius));
+                mesh.normals.push(n);
+                // Seen from outside, with +x to the right.
+                mesh.uvs
+                    .push(Vector2D::create(0.5 + sin / 2.0, 0.5 - sign * cos / 2.0));
+
+                let a = center + 1 + j as u32;
+                let b = center + 1 + ((j + 1) % segments) as u32;
+                if sign > 0.0 {
+                    mesh.indices.extend([center, a, b]);
+                } else {
+                    mesh.indices.extend([center, b, a]);
+                }
+            }
+        }
+
+        return mesh;
+    }
+
+    /// A torus around the y axis: a tube of radius `minor` whose centre
+    /// follows a circle of radius `major`. Both segment counts are at
+    /// least 3.
+    pub fn torus(major: f32, minor: f32, major_segments: usize, minor_segments: usize) -> Self {
+        let major_segments = major_segments.max(3);
+        let minor_segments = minor_segments.max(3);
+
+        let mut mesh = Mesh::default();
+        mesh.push_grid(major_segments, minor_segments, |i, j| {
+            let u = i as f32 / major_segments as f32;
+            let v = j as f32 / minor_segments as f32;
+            let (sin_theta, cos_theta) = (TAU * u).sin_cos();
+            let (sin_phi, cos_phi) = (TAU * v).sin_cos();
+
+            let n = Vector3D::create(cos_phi * sin_theta, sin_phi, cos_phi * cos_theta);
+            let ring = Vector3D::create(sin_theta, 0.0, cos_theta) * major;
+            return (ring + n * minor, n, Vector2D::create(u, v));
+        });
+
+        return mesh;
+    }
+
+    /// A `width` by `depth` plane in xz facing +y, split into `nx` by `nz`
+    /// quads (at least one each). U runs along +x and V along -z.
+    pub fn plane(width: f32, depth: f32, nx: usize, nz: usize) -> Self {
+        let nx = nx.max(1);
+        let nz = nz.max(1);
+
+        let mut mesh = Mesh::default();
+        mesh.push_grid(nz, nx, |i, j| {
+            let u = j as f32 / nx as f32;
+            let t = i as f32 / nz as f32;
+            let p = Vector3D::create((u - 0.5) * width, 0.0, (t - 0.5) * depth);
+            return (
+                p,
+                Vector3D::create(0.0, 1.0, 0.0),
+                Vector2D::create(u, 1.0 - t),
+            );
+        });
+
+        return mesh;
+    }
+
+    /// Appends a `(rows + 1)` by `(cols + 1)` grid of vertices from
+    /// `vertex(row, col)` and two triangles per cell. Triangles with two
+    /// coincident corners, such as at a sphere's poles, are left out.
+    fn push_grid(
+        &mut self,
+        rows: usize,
+        cols: usize,
+        vertex: impl Fn(usize, usize) -> (Vector3D, Vector3D, Vector2D),
+    ) {
+        let base = self.vertex_count();
+        for i in 0..=rows {
+            for j in 0..=cols {
+                let (p, n, uv) = vertex(i, j);
+                self.positions.push(p);
+                self.normals.push(n);
+                self.uvs.push(uv);
+            }
+        }
+
+        let index = |i: usize, j: usize| (base + i * (cols + 1) + j) as u32;
+        for i in 0..rows {
+            for j in 0..cols {
+                let a = index(i, j);
+                let b = index(i + 1, j);
+                let c = index(i + 1, j + 1);
+                let d = index(i, j + 1);
+
+                for triangle in [[a, b, c], [a, c, d]] {
+                    let [p, q, r] = triangle.map(|v| self.positions[v as usize]);
+                    if p != q && q != r && r != p {
+                        self.indices.extend(triangle);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn primitives_test() {
+        let shapes = [
+            ("cube", Mesh::cube(2.0), 24, 12),
+            ("sphere", Mesh::sphere(1.0, 8, 4), 45, 48),
+            ("cylinder", Mesh::cylinder(1.0, 2.0, 8), 36, 32),
+            ("torus", Mesh::torus(2.0, 0.5, 8, 8), 81, 128),
+            ("plane", Mesh::plane(2.0, 1.0, 3, 2), 12, 12),
+        ];
+
+        for (name, mesh, vertices, triangles) in shapes {
+            assert_eq!(mesh.validate(), Ok(()), "{name}");
+            assert_eq!(mesh.vertex_count(), vertices, "{name}");
+            assert_eq!(mesh.triangle_count(), triangles, "{name}");
+
+            for triangle in mesh.triangles() {
+                let face = mesh.face_normal(triangle);
+                for v in triangle {
+                    let n = mesh.normals[v as usize];
+                    assert!((n.magnitude() - 1.0).abs() < 1e-5, "{name}");
+                    assert!(face.dot(&n) > 0.5, "{name} {triangle:?}");
+                }
+            }
+        }
+
+        let cube = Mesh::cube(2.0);
+        for (p, n) in cube.positions.iter().zip(&cube.normals) {
+            assert!(p.dot(n) > 0.0);
+            assert!([p.x(), p.y(), p.z()].iter().all(|c| c.abs() == 1.0));
+        }
+        let bounds = Mesh::torus(2.0, 0.5, 8, 8).bounds();
+        assert!((bounds.max.y() - 0.5).abs() < 1e-5);
+        assert!((bounds.max.z() - 2.5).abs() < 1e-5);
+    }
+}
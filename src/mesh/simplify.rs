@@ -0,0 +1,660 @@
+//! Edge-collapse simplification guided by quadric error metrics
+//! (Garland and Heckbert).
+//!
+//! Vertices that share a position but not their other attributes, such as
+//! the two sides of a UV seam, are treated as one position with several
+//! wedges. Collapses move one position onto a neighbouring one, so
+//! surviving vertices keep their attributes exactly; borders and seams
+//! only collapse along themselves, and their corners never move.
+
+use std::collections::HashMap;
+
+use super::Mesh;
+use crate::math::Vector3D;
+
+/// Weight of the planes that hold borders and seams in place, relative to
+/// the planes of the faces.
+const EDGE_WEIGHT: f64 = 10.0;
+
+/// When to stop simplifying.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimplifyOptions {
+    /// Stop at or below this many triangles.
+    pub target_triangles: usize,
+    /// The largest error, in mesh units, a collapse may introduce.
+    pub max_error: f32,
+    /// Keep every border vertex, so the mesh still meets its neighbours,
+    /// e.g. other terrain chunks.
+    pub lock_border: bool,
+}
+
+impl Default for SimplifyOptions {
+    /// As far as possible, moving borders.
+    fn default() -> Self {
+        Self {
+            target_triangles: 0,
+            max_error: f32::INFINITY,
+            lock_border: false,
+        }
+    }
+}
+
+/// A sum of squared distances to planes, as a symmetric 4x4 matrix.
+#[derive(Debug, Clone, Copy, Default)]
+struct Quadric {
+    /// xx, xy, xz, yy, yz, zz.
+    a: [f64; 6],
+    b: [f64; 3],
+    c: f64,
+    /// Sum of the planes' weights.
+    weight: f64,
+}
+
+impl Quadric {
+    /// `weight` times the squared distance to the plane with unit `normal`
+    /// through `point`.
+    fn plane(normal: Vector3D, point: Vector3D, weight: f64) -> Self {
+        let [x, y, z] = [normal.x(), normal.y(), normal.z()].map(f64::from);
+        let d = -(x * point.x() as f64 + y * point.y() as f64 + z * point.z() as f64);
+
+        return Self {
+            a: [x * x, x * y, x * z, y * y, y * z, z * z].map(|e| e * weight),
+            b: [x * d, y * d, z * d].map(|e| e * weight),
+            c: d * d * weight,
+            weight,
+        };
+    }
+
+    fn add(&mut self, other: &Quadric) {
+        for k in 0..6 {
+            self.a[k] += other.a[k];
+        }
+        for k in 0..3 {
+            self.b[k] += other.b[k];
+        }
+        self.c += other.c;
+        self.weight += other.weight;
+    }
+
+    /// The weighted root-mean-square distance from `p` to the planes.
+    fn error(&self, p: Vector3D) -> f32 {
+        let [x, y, z] = [p.x(), p.y(), p.z()].map(f64::from);
+        let [xx, xy, xz, yy, yz, zz] = self.a;
+        let sum = xx * x * x
+            + yy * y * y
+            + zz * z * z
+            + 2.0 * (xy * x * y + xz * x * z + yz * y * z)
+            + 2.0 * (self.b[0] * x + self.b[1] * y + self.b[2] * z)
+            + self.c;
+
+        let sum = sum.max(0.0);
+        if self.weight == 0.0 {
+            return sum.sqrt() as f32;
+        }
+
+        return (sum / self.weight).sqrt() as f32;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    /// Inside a region of one wedge; collapses anywhere.
+    Manifold,
+    /// On a border; collapses along it.
+    Border,
+    /// On a seam between two wedges; collapses along it.
+    Seam,
+    /// Never collapses: corners, non-manifold and locked vertices.
+    Locked,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EdgeKind {
+    Interior,
+    Border,
+    Seam,
+}
+
+/// Adjacency of the current triangles between positions. Everything is
+/// indexed by each position's first vertex.
+struct Topology {
+    kinds: Vec<Kind>,
+    /// Each undirected edge once, sorted.
+    edges: Vec<(usize, usize, EdgeKind)>,
+    /// The triangles around each position.
+    around: Vec<Vec<usize>>,
+}
+
+impl Mesh {
+    /// Collapses edges, cheapest first by quadric error, until the mesh
+    /// is down to the target triangle count or every remaining collapse
+    /// costs more than `max_error`. Unused vertices are removed. Returns
+    /// the largest error of a collapse made.
+    pub fn simplify(&mut self, options: &SimplifyOptions) -> f32 {
+        let canonical = self.canonical_positions();
+        let mut quadrics = self.quadrics(&canonical);
+        let mut error = 0.0f32;
+
+        // Each pass makes collapses that do not touch each other's
+        // triangles, then applies them.
+        while self.triangle_count() > options.target_triangles {
+            let topology = self.topology(&canonical, options.lock_border);
+
+            let mut candidates: Vec<(f32, usize, usize)> = topology
+                .edges
+                .iter()
+                .filter_map(|&(p, q, kind)| {
+                    return [(p, q), (q, p)]
+                        .into_iter()
+                        .filter(|&(u, w)| topology.can_collapse(u, w, kind))
+                        .map(|(u, w)| {
+                            let mut quadric = quadrics[u];
+                            quadric.add(&quadrics[w]);
+                            return (quadric.error(self.positions[w]), u, w);
+                        })
+                        .min_by(|a, b| a.0.total_cmp(&b.0));
+                })
+                .filter(|c| c.0 <= options.max_error)
+                .collect();
+            candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+            let excess = self.triangle_count() - options.target_triangles;
+            let mut touched = vec![false; self.vertex_count()];
+            let mut remap: Vec<u32> = (0..self.vertex_count() as u32).collect();
+            let mut removed = 0;
+            for (cost, u, w) in candidates {
+                if removed >= excess {
+                    break;
+                }
+                if touched[u] || touched[w] {
+                    continue;
+                }
+                let Some(wedges) = self.collapse_wedges(&topology, &canonical, u, w) else {
+                    continue;
+                };
+
+                for (from, to) in wedges {
+                    remap[from as usize] = to;
+                }
+                let quadric = quadrics[u];
+                quadrics[w].add(&quadric);
+                for &t in &topology.around[u] {
+                    let triangle = self.triangle(t);
+                    for v in triangle {
+                        touched[canonical[v as usize]] = true;
+                    }
+                    if triangle.iter().any(|&v| canonical[v as usize] == w) {
+                        removed += 1;
+                    }
+                }
+                error = error.max(cost);
+            }
+            if removed == 0 {
+                break;
+            }
+
+            self.indices = self
+                .triangles()
+                .map(|t| t.map(|v| remap[v as usize]))
+                .filter(|t| {
+                    let [a, b, c] = t.map(|v| canonical[v as usize]);
+                    return a != b && b != c && c != a;
+                })
+                .flatten()
+                .collect();
+        }
+
+        self.remove_unused_vertices();
+        return error;
+    }
+
+    fn triangle(&self, t: usize) -> [u32; 3] {
+        [0, 1, 2].map(|k| self.indices[3 * t + k])
+    }
+
+    /// The first vertex with the same position as each vertex.
+    fn canonical_positions(&self) -> Vec<usize> {
+        let mut first = HashMap::new();
+
+        return self
+            .positions
+            .iter()
+            .enumerate()
+            .map(|(v, p)| {
+                // Adding zero turns -0.0 into 0.0.
+                let key = [p.x(), p.y(), p.z()].map(|c| (c + 0.0).to_bits());
+                return *first.entry(key).or_insert(v);
+            })
+            .collect();
+    }
+
+    /// The quadric of each position: the planes of its faces weighted by
+    /// area, plus planes through border and seam edges at right angles to
+    /// their faces.
+    fn quadrics(&self, canonical: &[usize]) -> Vec<Quadric> {
+        let mut quadrics = vec![Quadric::default(); self.vertex_count()];
+        let normal = |t: [u32; 3]| {
+            let [a, b, c] = t.map(|v| self.positions[v as usize]);
+            return (b - a).cross(&(c - a));
+        };
+
+        for t in self.triangles() {
+            let n = normal(t);
+            let area = n.magnitude() / 2.0;
+            if area == 0.0 {
+                continue;
+            }
+            let quadric = Quadric::plane(n.normalize(), self.positions[t[0] as usize], area.into());
+            for v in t {
+                quadrics[canonical[v as usize]].add(&quadric);
+            }
+        }
+
+        let topology = self.topology(canonical, false);
+        for &(p, q, kind) in &topology.edges {
+            if kind == EdgeKind::Interior {
+                continue;
+            }
+
+            let edge = self.positions[q] - self.positions[p];
+            for &t in &topology.around[p] {
+                let triangle = self.triangle(t);
+                let n = normal(triangle);
+                let across = edge.cross(&n);
+                if !triangle.iter().any(|&v| canonical[v as usize] == q)
+                    || across.magnitude() == 0.0
+                {
+                    continue;
+                }
+
+                let weight = f64::from(edge.dot(&edge)) * EDGE_WEIGHT;
+                let quadric = Quadric::plane(across.normalize(), self.positions[p], weight);
+                quadrics[p].add(&quadric);
+                quadrics[q].add(&quadric);
+            }
+        }
+
+        return quadrics;
+    }
+
+    fn topology(&self, canonical: &[usize], lock_border: bool) -> Topology {
+        let count = self.vertex_count();
+        let mut around = vec![Vec::new(); count];
+        let mut wedges = vec![Vec::new(); count];
+        // Directed position edges: how many triangles use each, and the
+        // vertices of the first one.
+        let mut directed: HashMap<(usize, usize), (usize, [u32; 2])> = HashMap::new();
+
+        for (t, triangle) in self.triangles().enumerate() {
+            let p = triangle.map(|v| canonical[v as usize]);
+            for k in 0..3 {
+                around[p[k]].push(t);
+                wedges[p[k]].push(triangle[k]);
+                let edge = [triangle[k], triangle[(k + 1) % 3]];
+                directed
+                    .entry((p[k], p[(k + 1) % 3]))
+                    .or_insert((0, edge))
+                    .0 += 1;
+            }
+        }
+
+        let mut borders = vec![0; count];
+        let mut seams = vec![0; count];
+        let mut complex = vec![false; count];
+        let mut edges = Vec::new();
+        for (&(p, q), &(uses, [a, b])) in &directed {
+            let kind = match directed.get(&(q, p)) {
+                None => EdgeKind::Border,
+                Some(_) if p > q => continue,
+                Some(&(back, [c, d])) => {
+                    if back > 1 {
+                        complex[p] = true;
+                        complex[q] = true;
+                    }
+                    if (a, b) == (d, c) {
+                        EdgeKind::Interior
+                    } else {
+                        EdgeKind::Seam
+                    }
+                }
+            };
+            if uses > 1 {
+                complex[p] = true;
+                complex[q] = true;
+            }
+            match kind {
+                EdgeKind::Border => {
+                    borders[p] += 1;
+                    borders[q] += 1;
+                }
+                EdgeKind::Seam => {
+                    seams[p] += 1;
+                    seams[q] += 1;
+                }
+                EdgeKind::Interior => {}
+            }
+            edges.push((p.min(q), p.max(q), kind));
+        }
+        edges.sort_by_key(|&(p, q, _)| (p, q));
+
+        let kinds = (0..count)
+            .map(|p| {
+                wedges[p].sort();
+                wedges[p].dedup();
+                let wedges = wedges[p].len();
+
+                if complex[p] {
+                    Kind::Locked
+                } else if borders[p] > 0 {
+                    if borders[p] == 2 && wedges == 1 && !lock_border {
+                        Kind::Border
+                    } else {
+                        Kind::Locked
+                    }
+                } else if seams[p] == 0 && wedges == 1 {
+                    Kind::Manifold
+                } else if seams[p] == 2 && wedges == 2 {
+                    Kind::Seam
+                } else {
+                    Kind::Locked
+                }
+            })
+            .collect();
+
+        return Topology {
+            kinds,
+            edges,
+            around,
+        };
+    }
+
+    /// Where each wedge of position `u` goes when it collapses onto `w`,
+    /// or `None` if the collapse would flip a triangle or pinch the
+    /// surface.
+    fn collapse_wedges(
+        &self,
+        topology: &Topology,
+        canonical: &[usize],
+        u: usize,
+        w: usize,
+    ) -> Option<Vec<(u32, u32)>> {
+        let mut wedges: Vec<(u32, u32)> = Vec::new();
+        let mut corners = Vec::new();
+        let mut around_u = Vec::new();
+
+        for &t in &topology.around[u] {
+            let triangle = self.triangle(t);
+            let p = triangle.map(|v| canonical[v as usize]);
+            around_u.extend(p.iter().filter(|&&q| q != u));
+
+            let ku = p.iter().position(|&q| q == u).unwrap();
+            if let Some(kw) = p.iter().position(|&q| q == w) {
+                corners.push(p[3 - ku - kw]);
+                wedges.push((triangle[ku], triangle[kw]));
+                continue;
+            }
+
+            let [a, b, c] = p.map(|q| self.positions[q]);
+            let before = (b - a).cross(&(c - a));
+            let mut moved = [a, b, c];
+            moved[ku] = self.positions[w];
+            let after = (moved[1] - moved[0]).cross(&(moved[2] - moved[0]));
+            if before.magnitude() > 0.0 && after.dot(&before) <= 0.0 {
+                return None;
+            }
+        }
+
+        // The link condition: the only positions next to both ends are
+        // the far corners of the triangles on the edge.
+        let mut common: Vec<usize> = topology.around[w]
+            .iter()
+            .flat_map(|&t| self.triangle(t))
+            .map(|v| canonical[v as usize])
+            .filter(|q| *q != w && *q != u && around_u.contains(q))
+            .collect();
+        common.sort();
+        common.dedup();
+        corners.sort();
+        if common != corners {
+            return None;
+        }
+
+        // Every wedge of `u` needs exactly one wedge of `w` to go to.
+        wedges.sort();
+        wedges.dedup();
+        for &t in &topology.around[u] {
+            let triangle = self.triangle(t);
+            for v in triangle.into_iter().filter(|&v| canonical[v as usize] == u) {
+                if wedges.iter().filter(|&&(from, _)| from == v).count() != 1 {
+                    return None;
+                }
+            }
+        }
+
+        return Some(wedges);
+    }
+}
+
+impl Topology {
+    fn can_collapse(&self, u: usize, w: usize, edge: EdgeKind) -> bool {
+        match self.kinds[u] {
+            Kind::Manifold => true,
+            Kind::Border => {
+                edge == EdgeKind::Border && matches!(self.kinds[w], Kind::Border | Kind::Locked)
+            }
+            Kind::Seam => {
+                edge == EdgeKind::Seam && matches!(self.kinds[w], Kind::Seam | Kind::Locked)
+            }
+            Kind::Locked => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::Vector2D;
+
+    /// Edges used by one triangle only.
+    fn border_edges(mesh: &Mesh) -> Vec<[Vector3D; 2]> {
+        let mut uses: HashMap<(u32, u32), usize> = HashMap::new();
+        for t in mesh.triangles() {
+            for k in 0..3 {
+                let (a, b) = (t[k], t[(k + 1) % 3]);
+                *uses.entry((a.min(b), a.max(b))).or_default() += 1;
+            }
+        }
+
+        return uses
+            .into_iter()
+            .filter(|&(_, n)| n == 1)
+            .map(|((a, b), _)| [a, b].map(|v| mesh.positions[v as usize]))
+            .collect();
+    }
+
+    /// A 2 by 2 plane with a bump in the middle.
+    fn bumpy_plane() -> Mesh {
+        let mut mesh = Mesh::plane(2.0, 2.0, 12, 12);
+        for p in mesh.positions.iter_mut() {
+            let y = 0.5 * (-4.0 * (p.x() * p.x() + p.z() * p.z())).exp();
+            *p = Vector3D::create(p.x(), y, p.z());
+        }
+
+        return mesh;
+    }
+
+    #[test]
+    fn plane_test() {
+        let flat = SimplifyOptions {
+            max_error: 1e-4,
+            ..SimplifyOptions::default()
+        };
+        let mut mesh = Mesh::plane(2.0, 2.0, 10, 10);
+        let error = mesh.simplify(&flat);
+        assert_eq!(mesh.triangle_count(), 2);
+        assert_eq!(mesh.vertex_count(), 4);
+        assert!(error < 1e-4, "{error}");
+        assert_eq!(mesh.validate(), Ok(()));
+        assert_eq!(mesh.bounds(), Mesh::plane(2.0, 2.0, 1, 1).bounds());
+
+        // A locked border keeps all 40 border vertices.
+        let mut mesh = Mesh::plane(2.0, 2.0, 10, 10);
+        mesh.simplify(&SimplifyOptions {
+            lock_border: true,
+            ..flat
+        });
+        assert_eq!(mesh.vertex_count(), 40);
+        assert_eq!(mesh.triangle_count(), 38);
+    }
+
+    #[test]
+    fn sphere_test() {
+        let mut mesh = Mesh::sphere(1.0, 32, 16);
+        let before = mesh.triangle_count();
+        let options = SimplifyOptions {
+            target_triangles: before / 4,
+            max_error: 0.1,
+            lock_border: false,
+        };
+        let error = mesh.simplify(&options);
+
+        assert!(mesh.triangle_count() <= before / 4);
+        assert!(error > 0.0 && error <= 0.1, "{error}");
+        assert_eq!(mesh.validate(), Ok(()));
+        for p in &mesh.positions {
+            assert!((p.magnitude() - 1.0).abs() < 1e-5);
+        }
+        // No triangle reaches across the UV seam.
+        for t in mesh.triangles() {
+            let u = t.map(|v| mesh.uvs[v as usize].x());
+            let span =
+                u.iter().fold(0.0f32, |m, &a| m.max(a)) - u.iter().fold(1.0f32, |m, &a| m.min(a));
+            assert!(span < 0.5, "{u:?}");
+        }
+
+        // Nothing is cheap enough.
+        let mut mesh = Mesh::sphere(1.0, 32, 16);
+        let options = SimplifyOptions {
+            max_error: 0.0,
+            ..options
+        };
+        assert_eq!(mesh.simplify(&options), 0.0);
+        assert_eq!(mesh.triangle_count(), before);
+    }
+
+    #[test]
+    fn border_test() {
+        let mut mesh = bumpy_plane();
+        mesh.simplify(&SimplifyOptions {
+            max_error: 0.01,
+            ..SimplifyOptions::default()
+        });
+        assert!(mesh.triangle_count() < 12 * 12 * 2 / 2);
+        assert_eq!(mesh.validate(), Ok(()));
+
+        // The outline only collapses along itself: every border edge runs
+        // along one side of the square, and the corners stay.
+        let on_side = |p: Vector3D| [p.x().abs(), p.z().abs()].map(|c| c == 1.0);
+        let border = border_edges(&mesh);
+        for [a, b] in &border {
+            let sides = [on_side(*a), on_side(*b)];
+            assert!(
+                (sides[0][0] && sides[1][0] && a.x() == b.x())
+                    || (sides[0][1] && sides[1][1] && a.z() == b.z()),
+                "{a} {b}"
+            );
+        }
+        for (x, z) in [(-1.0, -1.0), (-1.0, 1.0), (1.0, -1.0), (1.0, 1.0)] {
+            assert!(mesh.positions.iter().any(|p| p.x() == x && p.z() == z));
+        }
+        let length: f32 = border.iter().map(|[a, b]| (*b - *a).magnitude()).sum();
+        // Only the bump's tail makes the sides longer than 8.
+        assert!((length - 8.0).abs() < 1e-3, "{length}");
+    }
+
+    #[test]
+    fn uv_seam_test() {
+        // Split the plane down x = 0 into two UV islands.
+        let mut mesh = Mesh::plane(2.0, 2.0, 8, 8);
+        let mut copies = HashMap::new();
+        let triangles: Vec<[u32; 3]> = mesh.triangles().collect();
+        mesh.indices.clear();
+        for t in triangles {
+            let right = t.iter().any(|&v| mesh.positions[v as usize].x() > 0.0);
+            for v in t {
+                let v = if right {
+                    *copies.entry(v).or_insert_with(|| {
+                        let copy = mesh.vertex_count() as u32;
+                        mesh.positions.push(mesh.positions[v as usize]);
+                        mesh.normals.push(mesh.normals[v as usize]);
+                        mesh.uvs
+                            .push(mesh.uvs[v as usize] + Vector2D::create(10.0, 0.0));
+                        return copy;
+                    })
+                } else {
+                    v
+                };
+                mesh.indices.push(v);
+            }
+        }
+        mesh.remove_unused_vertices();
+        let original: Vec<(Vector3D, Vector2D)> = mesh
+            .positions
+            .iter()
+            .copied()
+            .zip(mesh.uvs.iter().copied())
+            .collect();
+
+        mesh.simplify(&SimplifyOptions {
+            max_error: 1e-4,
+            ..SimplifyOptions::default()
+        });
+        assert_eq!(mesh.validate(), Ok(()));
+        // Each island is reduced to its corners.
+        assert_eq!(mesh.triangle_count(), 4);
+
+        // Surviving vertices keep their attributes, and no triangle mixes
+        // the islands.
+        for (p, uv) in mesh.positions.iter().zip(&mesh.uvs) {
+            assert!(original.contains(&(*p, *uv)), "{p} {uv}");
+        }
+        for t in mesh.triangles() {
+            let right = t.map(|v| mesh.uvs[v as usize].x() >= 5.0);
+            assert!(right.iter().all(|&r| r == right[0]), "{t:?}");
+        }
+        // The seam's ends are on both islands.
+        let seam: Vec<Vector2D> = mesh
+            .positions
+            .iter()
+            .zip(&mesh.uvs)
+            .filter(|(p, _)| p.x() == 0.0)
+            .map(|(_, uv)| *uv)
+            .collect();
+        assert_eq!(seam.len(), 4);
+    }
+
+    #[test]
+    fn target_test() {
+        let full = Mesh::sphere(1.0, 32, 16);
+        for target in [full.triangle_count(), 600, 300, 150] {
+            let mut mesh = full.clone();
+            mesh.simplify(&SimplifyOptions {
+                target_triangles: target,
+                ..SimplifyOptions::default()
+            });
+
+            // A collapse removes at most two triangles, so the last one
+            // overshoots by at most one.
+            let count = mesh.triangle_count();
+            assert!(count <= target && count + 1 >= target, "{count} {target}");
+            assert_eq!(mesh.validate(), Ok(()));
+        }
+
+        let mut mesh = full.clone();
+        mesh.simplify(&SimplifyOptions {
+            target_triangles: full.triangle_count() + 10,
+            ..SimplifyOptions::default()
+        });
+        assert_eq!(mesh.triangle_count(), full.triangle_count());
+    }
+}
@@ -0,0 +1,113 @@
+/// An RGBA8 color target with a matching depth buffer.
+///
+/// Pixels are stored row by row starting at the top-left corner. Depth
+/// values are window-space, `0.0` at the near plane and `1.0` at the far
+/// plane.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Framebuffer {
+    width: usize,
+    height: usize,
+    color: Vec<[u8; 4]>,
+    depth: Vec<f32>,
+}
+
+impl Framebuffer {
+    /// A black, transparent framebuffer with the depth cleared to the far
+    /// plane.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            color: vec![[0; 4]; width * height],
+            depth: vec![1.0; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn clear_color(&mut self, color: [u8; 4]) {
+        self.color.fill(color);
+    }
+
+    pub fn clear_depth(&mut self, depth: f32) {
+        self.depth.fill(depth);
+    }
+
+    /// Color at column `x`, row `y`.
+    ///
+    /// Panics if the pixel is outside the framebuffer.
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        return self.color[self.index(x, y)];
+    }
+
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: [u8; 4]) {
+        let i = self.index(x, y);
+        self.color[i] = color;
+    }
+
+    pub fn depth(&self, x: usize, y: usize) -> f32 {
+        return self.depth[self.index(x, y)];
+    }
+
+    pub fn set_depth(&mut self, x: usize, y: usize, depth: f32) {
+        let i = self.index(x, y);
+        self.depth[i] = depth;
+    }
+
+    pub fn pixels(&self) -> &[[u8; 4]] {
+        &self.color
+    }
+
+    /// The color buffer as tightly packed RGBA bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        self.color.as_flattened()
+    }
+
+    fn index(&self, x: usize, y: usize) -> usize {
+        assert!(
+            x < self.width && y < self.height,
+            "pixel ({x}, {y}) out of range for a {}x{} framebuffer",
+            self.width,
+            self.height
+        );
+
+        return y * self.width + x;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_test() {
+        let fb = Framebuffer::new(4, 3);
+
+        assert_eq!(fb.pixels().len(), 12);
+        assert_eq!(fb.as_bytes().len(), 48);
+        assert_eq!(fb.depth(3, 2), 1.0);
+    }
+
+    #[test]
+    fn pixel_test() {
+        let mut fb = Framebuffer::new(4, 3);
+        fb.clear_color([1, 2, 3, 4]);
+        fb.set_pixel(3, 1, [9, 9, 9, 9]);
+
+        assert_eq!(fb.pixel(0, 0), [1, 2, 3, 4]);
+        assert_eq!(fb.pixel(3, 1), [9, 9, 9, 9]);
+        assert_eq!(&fb.as_bytes()[28..32], &[9, 9, 9, 9]);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn pixel_out_of_range_test() {
+        Framebuffer::new(4, 3).pixel(4, 0);
+    }
+}
@@ -0,0 +1,15 @@
+//! Headless CPU rendering.
+//!
+//! [`Rasterizer`] draws triangles into a [`Framebuffer`] without a GPU, so
+//! rendering can be exercised in tests and on build servers. The
+//! [`pathtracer`] renders the same kind of scenes offline as a ground-truth
+//! reference.
+
+#![allow(clippy::needless_return)]
+
+pub mod framebuffer;
+pub mod pathtracer;
+pub mod rasterizer;
+
+pub use framebuffer::Framebuffer;
+pub use rasterizer::{CullMode, Fragment, Rasterizer, Vertex};
@@ -0,0 +1,178 @@
+use rand::Rng;
+
+use crate::{math::Vector3D, spatial::Ray};
+
+use super::primitive::Hit;
+
+/// How a surface scatters and emits light. Colors are linear RGB stored in
+/// a `Vector3D`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Material {
+    /// Ideal diffuse reflector.
+    Lambertian { albedo: Vector3D },
+    /// Mirror reflection blurred by `fuzz` in `[0, 1]`.
+    Metal { albedo: Vector3D, fuzz: f32 },
+    /// Clear glass-like material with the given index of refraction.
+    Dielectric { ior: f32 },
+    /// A light source; it does not scatter.
+    Emissive { color: Vector3D },
+}
+
+impl Material {
+    /// The attenuation and the outgoing ray, or `None` if the path ends.
+    pub fn scatter(&self, ray: &Ray, hit: &Hit, rng: &mut impl Rng) -> Option<(Vector3D, Ray)> {
+        match *self {
+            Material::Lambertian { albedo } => {
+                let mut direction = hit.normal + random_unit_vector(rng);
+                if direction.magnitude() < 1e-6 {
+                    direction = hit.normal;
+                }
+
+                return Some((albedo, Ray::new(hit.point, direction)));
+            }
+            Material::Metal { albedo, fuzz } => {
+                let reflected = reflect(&ray.direction.normalize(), &hit.normal);
+                let direction = reflected + random_unit_vector(rng) * fuzz.clamp(0.0, 1.0);
+                if direction.dot(&hit.normal) <= 0.0 {
+                    return None;
+                }
+
+                return Some((albedo, Ray::new(hit.point, direction)));
+            }
+            Material::Dielectric { ior } => {
+                let ratio = if hit.front_face { 1.0 / ior } else { ior };
+                let unit = ray.direction.normalize();
+                let cos_theta = (-unit).dot(&hit.normal).min(1.0);
+                let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+
+                let direction = if ratio * sin_theta > 1.0
+                    || reflectance(cos_theta, ratio) > rng.gen::<f32>()
+                {
+                    reflect(&unit, &hit.normal)
+                } else {
+                    refract(&unit, &hit.normal, ratio)
+                };
+
+                return Some((
+                    Vector3D::create(1.0, 1.0, 1.0),
+                    Ray::new(hit.point, direction),
+                ));
+            }
+            Material::Emissive { .. } => return None,
+        }
+    }
+
+    pub fn emitted(&self) -> Vector3D {
+        match *self {
+            Material::Emissive { color } => color,
+            _ => Vector3D::default(),
+        }
+    }
+}
+
+/// Mirrors `v` about the plane with unit normal `n`.
+pub fn reflect(v: &Vector3D, n: &Vector3D) -> Vector3D {
+    *v - *n * (2.0 * v.dot(n))
+}
+
+/// Snell's law for unit `v` against unit `n`, with `ratio` the incident
+/// over the transmitted index of refraction.
+pub fn refract(v: &Vector3D, n: &Vector3D, ratio: f32) -> Vector3D {
+    let cos_theta = (-*v).dot(n).min(1.0);
+    let perpendicular = (*v + *n * cos_theta) * ratio;
+    let parallel = *n * -(1.0 - perpendicular.dot(&perpendicular)).abs().sqrt();
+
+    return perpendicular + parallel;
+}
+
+/// Schlick's approximation of Fresnel reflectance.
+fn reflectance(cosine: f32, ratio: f32) -> f32 {
+    let r0 = ((1.0 - ratio) / (1.0 + ratio)).powi(2);
+
+    return r0 + (1.0 - r0) * (1.0 - cosine).powi(5);
+}
+
+/// Uniformly distributed on the unit sphere.
+pub fn random_unit_vector(rng: &mut impl Rng) -> Vector3D {
+    loop {
+        let p = Vector3D::create(
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-1.0..1.0),
+        );
+        let length = p.magnitude();
+        if length > 1e-4 && length <= 1.0 {
+            return p / length;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    fn hit(normal: Vector3D, front_face: bool) -> Hit {
+        Hit {
+            t: 1.0,
+            point: Vector3D::default(),
+            normal,
+            front_face,
+            material: 0,
+        }
+    }
+
+    #[test]
+    fn reflect_refract_test() {
+        let n = Vector3D::create(0.0, 1.0, 0.0);
+        let v = Vector3D::create(1.0, -1.0, 0.0).normalize();
+
+        assert_eq!(reflect(&v, &n), Vector3D::create(1.0, 1.0, 0.0).normalize());
+        // Equal indices leave the direction unchanged.
+        assert_eq!(refract(&v, &n, 1.0), v);
+
+        let bent = refract(&v, &n, 1.0 / 1.5);
+        let sin_in = v.x();
+        assert!((bent.x() - sin_in / 1.5).abs() < 1e-6);
+        assert!((bent.magnitude() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn scatter_test() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let up = Vector3D::create(0.0, 1.0, 0.0);
+        let ray = Ray::new(
+            Vector3D::create(-1.0, 1.0, 0.0),
+            Vector3D::create(1.0, -1.0, 0.0),
+        );
+
+        for _ in 0..100 {
+            let albedo = Vector3D::create(0.5, 0.5, 0.5);
+            let (attenuation, out) = Material::Lambertian { albedo }
+                .scatter(&ray, &hit(up, true), &mut rng)
+                .unwrap();
+            assert_eq!(attenuation, albedo);
+            assert!(out.direction.dot(&up) >= 0.0);
+        }
+
+        let mirror = Material::Metal {
+            albedo: Vector3D::create(1.0, 1.0, 1.0),
+            fuzz: 0.0,
+        };
+        let (_, out) = mirror.scatter(&ray, &hit(up, true), &mut rng).unwrap();
+        assert_eq!(out.direction, Vector3D::create(1.0, 1.0, 0.0).normalize());
+
+        // Leaving glass at a grazing angle is total internal reflection.
+        let grazing = Ray::new(Vector3D::default(), Vector3D::create(1.0, -0.1, 0.0));
+        let glass = Material::Dielectric { ior: 1.5 };
+        let (_, out) = glass.scatter(&grazing, &hit(up, false), &mut rng).unwrap();
+        assert!(out.direction.y() > 0.0);
+
+        let light = Material::Emissive {
+            color: Vector3D::create(4.0, 4.0, 4.0),
+        };
+        assert!(light.scatter(&ray, &hit(up, true), &mut rng).is_none());
+        assert_eq!(light.emitted(), Vector3D::create(4.0, 4.0, 4.0));
+    }
+}
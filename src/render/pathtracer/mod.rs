@@ -0,0 +1,502 @@
+//! Offline Monte Carlo path tracing.
+//!
+//! A [`Scene`] of spheres and triangles is rendered through a
+//! [`Camera`] by a [`PathTracer`] into an [`Accumulator`], which keeps a
+//! running sum so more passes can be added later to reduce noise. Work is split into
+//! square tiles shared by a pool of scoped threads. Every tile of every
+//! pass has its own seeded random generator, so the result depends only on
+//! the seed, never on the thread count or scheduling.
+
+pub mod material;
+pub mod primitive;
+
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    camera::Camera,
+    image::HdrImage,
+    math::Vector3D,
+    spatial::{Aabb, Bvh},
+};
+
+pub use crate::spatial::Ray;
+pub use material::Material;
+pub use primitive::{Hit, Shape};
+
+/// Offset keeping secondary rays from hitting the surface they leave.
+const EPSILON: f32 = 1e-3;
+
+/// The near and far planes of a [`Camera`] in world space, each as a
+/// top-left corner and the edges spanning it, so that rays can be
+/// interpolated instead of unprojected per sample.
+struct Lens {
+    near: [Vector3D; 3],
+    far: [Vector3D; 3],
+}
+
+impl Lens {
+    fn new(camera: &Camera) -> Self {
+        let inverse = camera
+            .view_projection()
+            .inverse()
+            .expect("invertible camera");
+        let plane = |z: f32| {
+            let corner = |x: f32, y: f32| inverse.transform_point(&Vector3D::create(x, y, z));
+            let upper_left = corner(-1.0, 1.0);
+
+            return [
+                upper_left,
+                corner(1.0, 1.0) - upper_left,
+                corner(-1.0, -1.0) - upper_left,
+            ];
+        };
+
+        Self {
+            near: plane(-1.0),
+            far: plane(1.0),
+        }
+    }
+
+    /// The ray from the near plane through the image point `(s, t)`,
+    /// where `(0, 0)` is the top-left corner and `(1, 1)` the
+    /// bottom-right, with a unit direction.
+    fn ray(&self, s: f32, t: f32) -> Ray {
+        let at =
+            |[corner, horizontal, vertical]: [Vector3D; 3]| corner + horizontal * s + vertical * t;
+        let near = at(self.near);
+
+        return Ray::new(near, (at(self.far) - near).normalize());
+    }
+}
+
+/// Shapes, the materials they refer to and the radiance of rays that
+/// escape.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Scene {
+    pub shapes: Vec<Shape>,
+    pub materials: Vec<Material>,
+    pub background: Vector3D,
+}
+
+impl Scene {
+    pub fn new(background: Vector3D) -> Self {
+        Self {
+            shapes: Vec::new(),
+            materials: Vec::new(),
+            background,
+        }
+    }
+
+    /// Returns the index shapes use to refer to the material.
+    pub fn add_material(&mut self, material: Material) -> usize {
+        self.materials.push(material);
+
+        return self.materials.len() - 1;
+    }
+
+    pub fn add_sphere(&mut self, center: Vector3D, radius: f32, material: usize) {
+        self.shapes.push(Shape::Sphere {
+            center,
+            radius,
+            material,
+        });
+    }
+
+    pub fn add_triangle(&mut self, vertices: [Vector3D; 3], material: usize) {
+        self.shapes.push(Shape::Triangle { vertices, material });
+    }
+}
+
+/// A running per-pixel sum of radiance samples.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Accumulator {
+    sum: HdrImage,
+    samples: u32,
+    passes: u32,
+}
+
+impl Accumulator {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            sum: HdrImage::new(width, height),
+            samples: 0,
+            passes: 0,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.sum.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.sum.height()
+    }
+
+    /// Samples per pixel so far.
+    pub fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    /// The mean radiance per pixel, with alpha 1.
+    pub fn image(&self) -> HdrImage {
+        let scale = 1.0 / self.samples.max(1) as f32;
+
+        return HdrImage::from_fn(self.width(), self.height(), |x, y| {
+            let p = self.sum.pixel(x, y);
+            [p[0] * scale, p[1] * scale, p[2] * scale, 1.0]
+        });
+    }
+
+    pub fn clear(&mut self) {
+        self.sum.pixels_mut().fill([0.0; 4]);
+        self.samples = 0;
+        self.passes = 0;
+    }
+}
+
+/// Rendering settings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PathTracer {
+    /// Samples per pixel in each pass.
+    pub samples_per_pass: u32,
+    /// Bounces before a path is terminated.
+    pub max_depth: u32,
+    pub tile_size: usize,
+    /// Worker threads; 0 uses the available parallelism.
+    pub threads: usize,
+    pub seed: u64,
+}
+
+impl Default for PathTracer {
+    fn default() -> Self {
+        Self {
+            samples_per_pass: 4,
+            max_depth: 8,
+            tile_size: 16,
+            threads: 0,
+            seed: 0,
+        }
+    }
+}
+
+impl PathTracer {
+    /// Adds `passes` passes of `samples_per_pass` samples to `accumulator`.
+    /// The image covers the camera's view, so its viewport should have the
+    /// accumulator's aspect ratio.
+    pub fn render(
+        &self,
+        scene: &Scene,
+        camera: &Camera,
+        accumulator: &mut Accumulator,
+        passes: u32,
+    ) {
+        let bounds: Vec<Aabb> = scene.shapes.iter().map(|s| s.bounds()).collect();
+        let bvh = Bvh::sah(&bounds);
+        let lens = Lens::new(camera);
+        let (width, height) = (accumulator.width(), accumulator.height());
+        let tile = self.tile_size.max(1);
+        let (tiles_x, tiles_y) = (width.div_ceil(tile), height.div_ceil(tile));
+        let threads = match self.threads {
+            0 => thread::available_parallelism().map_or(1, |n| n.get()),
+            n => n,
+        };
+
+        for _ in 0..passes {
+            let pass = accumulator.passes;
+            let next = AtomicUsize::new(0);
+
+            let results: Vec<(usize, Vec<Vector3D>)> = thread::scope(|s| {
+                let workers: Vec<_> = (0..threads)
+                    .map(|_| {
+                        s.spawn(|| {
+                            let mut done = Vec::new();
+                            loop {
+                                let index = next.fetch_add(1, Ordering::Relaxed);
+                                if index >= tiles_x * tiles_y {
+                                    return done;
+                                }
+
+                                let x0 = (index % tiles_x) * tile;
+                                let y0 = (index / tiles_x) * tile;
+                                let region =
+                                    (x0, y0, (x0 + tile).min(width), (y0 + tile).min(height));
+                                let radiance = self.render_tile(
+                                    scene,
+                                    &bvh,
+                                    &lens,
+                                    (width, height),
+                                    region,
+                                    mix(self.seed, pass as u64, index as u64),
+                                );
+                                done.push((index, radiance));
+                            }
+                        })
+                    })
+                    .collect();
+
+                return workers
+                    .into_iter()
+                    .flat_map(|w| w.join().unwrap())
+                    .collect();
+            });
+
+            for (index, radiance) in results {
+                let x0 = (index % tiles_x) * tile;
+                let y0 = (index / tiles_x) * tile;
+                let mut i = 0;
+                for y in y0..(y0 + tile).min(height) {
+                    for x in x0..(x0 + tile).min(width) {
+                        let mut p = accumulator.sum.pixel(x, y);
+                        p[0] += radiance[i].x();
+                        p[1] += radiance[i].y();
+                        p[2] += radiance[i].z();
+                        accumulator.sum.set_pixel(x, y, p);
+                        i += 1;
+                    }
+                }
+            }
+
+            accumulator.samples += self.samples_per_pass;
+            accumulator.passes += 1;
+        }
+    }
+
+    /// Sums `samples_per_pass` samples for each pixel of the region
+    /// `(x0, y0, x1, y1)`, row by row.
+    fn render_tile(
+        &self,
+        scene: &Scene,
+        bvh: &Bvh,
+        lens: &Lens,
+        (width, height): (usize, usize),
+        (x0, y0, x1, y1): (usize, usize, usize, usize),
+        seed: u64,
+    ) -> Vec<Vector3D> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut radiance = Vec::with_capacity((x1 - x0) * (y1 - y0));
+
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let mut sum = Vector3D::default();
+                for _ in 0..self.samples_per_pass {
+                    let s = (x as f32 + rng.gen::<f32>()) / width as f32;
+                    let t = (y as f32 + rng.gen::<f32>()) / height as f32;
+                    sum += self.trace(scene, bvh, lens.ray(s, t), &mut rng);
+                }
+                radiance.push(sum);
+            }
+        }
+
+        return radiance;
+    }
+
+    /// Radiance arriving along `ray`.
+    fn trace(&self, scene: &Scene, bvh: &Bvh, mut ray: Ray, rng: &mut StdRng) -> Vector3D {
+        let mut radiance = Vector3D::default();
+        let mut throughput = Vector3D::create(1.0, 1.0, 1.0);
+
+        for _ in 0..=self.max_depth {
+            let closest = bvh.raycast(&ray, EPSILON, f32::INFINITY, |i, t_max| {
+                scene.shapes[i].hit(&ray, EPSILON, t_max).map(|h| h.t)
+            });
+            let Some(hit) =
+                closest.and_then(|(i, _)| scene.shapes[i].hit(&ray, EPSILON, f32::INFINITY))
+            else {
+                return radiance + multiply(&throughput, &scene.background);
+            };
+
+            let material = &scene.materials[hit.material];
+            radiance += multiply(&throughput, &material.emitted());
+            match material.scatter(&ray, &hit, rng) {
+                Some((attenuation, scattered)) => {
+                    throughput = multiply(&throughput, &attenuation);
+                    ray = scattered;
+                }
+                None => return radiance,
+            }
+        }
+
+        return radiance;
+    }
+}
+
+/// Component-wise product of two colors.
+fn multiply(a: &Vector3D, b: &Vector3D) -> Vector3D {
+    Vector3D::create(a.x() * b.x(), a.y() * b.y(), a.z() * b.z())
+}
+
+/// A well-spread seed for one tile of one pass (SplitMix64 finalizer).
+fn mix(seed: u64, pass: u64, tile: u64) -> u64 {
+    let mut z =
+        seed ^ pass.wrapping_mul(0x9e37_79b9_7f4a_7c15) ^ tile.wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+
+    return z ^ (z >> 31);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+    use crate::math::Vector2D;
+
+    fn camera(aspect: f32) -> Camera {
+        let viewport = Vector2D::create(aspect, 1.0);
+        let mut camera = Camera::perspective(FRAC_PI_2, 0.01, 100.0, viewport);
+        camera.position = Vector3D::create(0.0, 0.0, 3.0);
+        camera.look_at(&Vector3D::default(), &Vector3D::create(0.0, 1.0, 0.0));
+
+        return camera;
+    }
+
+    fn assert_close(a: Vector3D, b: Vector3D) {
+        assert!((a - b).magnitude() < 1e-4, "{a} != {b}");
+    }
+
+    fn spheres() -> Scene {
+        let mut scene = Scene::new(Vector3D::create(0.6, 0.7, 0.9));
+        let ground = scene.add_material(Material::Lambertian {
+            albedo: Vector3D::create(0.5, 0.5, 0.5),
+        });
+        let glass = scene.add_material(Material::Dielectric { ior: 1.5 });
+        let metal = scene.add_material(Material::Metal {
+            albedo: Vector3D::create(0.8, 0.6, 0.2),
+            fuzz: 0.3,
+        });
+        let light = scene.add_material(Material::Emissive {
+            color: Vector3D::create(4.0, 4.0, 4.0),
+        });
+
+        scene.add_sphere(Vector3D::create(0.0, -101.0, 0.0), 100.0, ground);
+        scene.add_sphere(Vector3D::create(-1.0, 0.0, 0.0), 0.5, glass);
+        scene.add_sphere(Vector3D::create(1.0, 0.0, 0.0), 0.5, metal);
+        scene.add_triangle(
+            [
+                Vector3D::create(-1.0, 2.0, -1.0),
+                Vector3D::create(1.0, 2.0, -1.0),
+                Vector3D::create(0.0, 2.0, 1.0),
+            ],
+            light,
+        );
+
+        return scene;
+    }
+
+    #[test]
+    fn lens_test() {
+        let camera = camera(2.0);
+        let lens = Lens::new(&camera);
+
+        let center = lens.ray(0.5, 0.5);
+        assert_close(center.direction, Vector3D::create(0.0, 0.0, -1.0));
+        assert_close(center.origin, Vector3D::create(0.0, 0.0, 2.99));
+
+        let corner = lens.ray(0.0, 0.0).direction;
+        assert_close(corner, Vector3D::create(-2.0, 1.0, -1.0).normalize());
+
+        let ray = lens.ray(0.25, 0.75);
+        let expected = camera.screen_to_ray(Vector2D::create(0.5, 0.75));
+        assert_close(ray.origin, expected.origin);
+        assert_close(ray.direction, expected.direction);
+    }
+
+    #[test]
+    fn orthographic_test() {
+        let mut camera = Camera::orthographic(2.0, 0.0, 10.0, Vector2D::create(1.0, 1.0));
+        camera.position = Vector3D::create(0.0, 0.0, 3.0);
+        let lens = Lens::new(&camera);
+
+        let corner = lens.ray(0.0, 0.0);
+        assert_close(corner.origin, Vector3D::create(-1.0, 1.0, 3.0));
+        assert_close(corner.direction, Vector3D::create(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn background_only_test() {
+        let scene = Scene::new(Vector3D::create(0.25, 0.5, 1.0));
+        let mut accumulator = Accumulator::new(5, 3);
+        PathTracer::default().render(&scene, &camera(5.0 / 3.0), &mut accumulator, 2);
+
+        assert_eq!(accumulator.samples(), 8);
+        for p in accumulator.image().pixels() {
+            assert!(
+                (p[0] - 0.25).abs() < 1e-6
+                    && (p[1] - 0.5).abs() < 1e-6
+                    && (p[2] - 1.0).abs() < 1e-6
+            );
+            assert_eq!(p[3], 1.0);
+        }
+    }
+
+    #[test]
+    fn white_furnace_test() {
+        // Under a uniform sky a white diffuse surface is indistinguishable
+        // from the sky itself, whatever the sample directions.
+        let mut scene = Scene::new(Vector3D::create(0.5, 0.5, 0.5));
+        let white = scene.add_material(Material::Lambertian {
+            albedo: Vector3D::create(1.0, 1.0, 1.0),
+        });
+        scene.add_sphere(Vector3D::default(), 1.0, white);
+
+        let mut accumulator = Accumulator::new(8, 8);
+        let tracer = PathTracer {
+            max_depth: 50,
+            ..Default::default()
+        };
+        tracer.render(&scene, &camera(1.0), &mut accumulator, 1);
+
+        for p in accumulator.image().pixels() {
+            assert!((p[0] - 0.5).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn deterministic_across_threads_test() {
+        let scene = spheres();
+        let render = |threads, passes: &[u32]| {
+            let tracer = PathTracer {
+                threads,
+                tile_size: 7,
+                seed: 42,
+                ..Default::default()
+            };
+            let mut accumulator = Accumulator::new(24, 16);
+            for &n in passes {
+                tracer.render(&scene, &camera(1.5), &mut accumulator, n);
+            }
+            accumulator.image()
+        };
+
+        let single = render(1, &[2]);
+        assert_eq!(render(4, &[2]), single);
+        // Progressive passes continue the same sequence.
+        assert_eq!(render(3, &[1, 1]), single);
+    }
+
+    #[test]
+    fn scene_test() {
+        let scene = spheres();
+        let mut accumulator = Accumulator::new(16, 16);
+        PathTracer::default().render(&scene, &camera(1.0), &mut accumulator, 2);
+
+        let image = accumulator.image();
+        let ground = image.pixel(8, 15);
+        let sky = image.pixel(8, 0);
+        assert!(ground[0] > 0.0 && ground[0] < sky[2]);
+        assert!(image
+            .pixels()
+            .iter()
+            .all(|p| p.iter().all(|c| c.is_finite())));
+
+        accumulator.clear();
+        assert_eq!(accumulator.samples(), 0);
+        assert_eq!(accumulator.image().pixel(0, 0), [0.0, 0.0, 0.0, 1.0]);
+    }
+}
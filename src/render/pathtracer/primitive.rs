@@ -0,0 +1,155 @@
+use crate::{
+    math::Vector3D,
+    spatial::{ray_triangle, Aabb, Ray},
+};
+
+/// The closest intersection along a ray.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hit {
+    pub t: f32,
+    pub point: Vector3D,
+    /// Unit normal facing against the ray.
+    pub normal: Vector3D,
+    /// Whether the ray hit the outside of the surface.
+    pub front_face: bool,
+    pub material: usize,
+}
+
+impl Hit {
+    fn new(ray: &Ray, t: f32, outward_normal: Vector3D, material: usize) -> Self {
+        let front_face = ray.direction.dot(&outward_normal) < 0.0;
+
+        Self {
+            t,
+            point: ray.at(t),
+            normal: if front_face {
+                outward_normal
+            } else {
+                -outward_normal
+            },
+            front_face,
+            material,
+        }
+    }
+}
+
+/// Geometry the path tracer can intersect. Each shape refers to a material
+/// by its index in the [`Scene`](super::Scene).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shape {
+    Sphere {
+        center: Vector3D,
+        radius: f32,
+        material: usize,
+    },
+    /// Counterclockwise vertices face the viewer.
+    Triangle {
+        vertices: [Vector3D; 3],
+        material: usize,
+    },
+}
+
+impl Shape {
+    /// The closest hit with `t` in `(t_min, t_max)`.
+    pub fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit> {
+        match *self {
+            Shape::Sphere {
+                center,
+                radius,
+                material,
+            } => {
+                let oc = ray.origin - center;
+                let a = ray.direction.dot(&ray.direction);
+                let half_b = oc.dot(&ray.direction);
+                let c = oc.dot(&oc) - radius * radius;
+                let discriminant = half_b * half_b - a * c;
+                if discriminant < 0.0 {
+                    return None;
+                }
+
+                let root = discriminant.sqrt();
+                let mut t = (-half_b - root) / a;
+                if t <= t_min || t >= t_max {
+                    t = (-half_b + root) / a;
+                    if t <= t_min || t >= t_max {
+                        return None;
+                    }
+                }
+
+                let outward = (ray.at(t) - center) / radius;
+                return Some(Hit::new(ray, t, outward, material));
+            }
+            Shape::Triangle {
+                vertices: [a, b, c],
+                material,
+            } => {
+                let hit = ray_triangle(ray, [a, b, c], t_min, t_max)?;
+                let normal = (b - a).cross(&(c - a)).normalize();
+                return Some(Hit::new(ray, hit.t, normal, material));
+            }
+        }
+    }
+
+    pub fn bounds(&self) -> Aabb {
+        match *self {
+            Shape::Sphere { center, radius, .. } => {
+                let r = Vector3D::create(radius, radius, radius);
+                return Aabb::new(center - r, center + r);
+            }
+            Shape::Triangle {
+                vertices: [a, b, c],
+                ..
+            } => return Aabb::from_points(&[a, b, c]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sphere_hit_test() {
+        let sphere = Shape::Sphere {
+            center: Vector3D::create(0.0, 0.0, -5.0),
+            radius: 1.0,
+            material: 3,
+        };
+        let ray = Ray::new(Vector3D::default(), Vector3D::create(0.0, 0.0, -1.0));
+
+        let hit = sphere.hit(&ray, 0.0, f32::INFINITY).unwrap();
+        assert_eq!(hit.t, 4.0);
+        assert_eq!(hit.normal, Vector3D::create(0.0, 0.0, 1.0));
+        assert!(hit.front_face);
+        assert_eq!(hit.material, 3);
+
+        // From inside, the far side is hit and the normal points back in.
+        let inside = Ray::new(Vector3D::create(0.0, 0.0, -5.0), ray.direction);
+        let hit = sphere.hit(&inside, 0.0, f32::INFINITY).unwrap();
+        assert_eq!(hit.t, 1.0);
+        assert!(!hit.front_face);
+        assert_eq!(hit.normal, Vector3D::create(0.0, 0.0, 1.0));
+
+        assert!(sphere.hit(&ray, 0.0, 3.0).is_none());
+    }
+
+    #[test]
+    fn triangle_hit_test() {
+        let triangle = Shape::Triangle {
+            vertices: [
+                Vector3D::create(-1.0, -1.0, -2.0),
+                Vector3D::create(1.0, -1.0, -2.0),
+                Vector3D::create(0.0, 1.0, -2.0),
+            ],
+            material: 0,
+        };
+
+        let ray = Ray::new(Vector3D::default(), Vector3D::create(0.0, 0.0, -1.0));
+        let hit = triangle.hit(&ray, 0.0, f32::INFINITY).unwrap();
+        assert_eq!(hit.t, 2.0);
+        assert_eq!(hit.normal, Vector3D::create(0.0, 0.0, 1.0));
+
+        let miss = Ray::new(Vector3D::create(2.0, 0.0, 0.0), ray.direction);
+        assert!(triangle.hit(&miss, 0.0, f32::INFINITY).is_none());
+    }
+}
@@ -0,0 +1,475 @@
+use crate::math::{Matrix4x4F32, Vector3D};
+
+use super::Framebuffer;
+
+/// Input vertex: an object-space position plus `N` float attributes that
+/// are interpolated across the triangle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vertex<const N: usize> {
+    pub position: Vector3D,
+    pub attributes: [f32; N],
+}
+
+impl<const N: usize> Vertex<N> {
+    pub fn new(position: Vector3D, attributes: [f32; N]) -> Self {
+        Self {
+            position,
+            attributes,
+        }
+    }
+}
+
+/// A covered pixel handed to the fragment shader.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fragment<const N: usize> {
+    pub x: usize,
+    pub y: usize,
+    /// Window-space depth in `[0, 1]`.
+    pub depth: f32,
+    /// Perspective-correct interpolated vertex attributes.
+    pub attributes: [f32; N],
+}
+
+/// Which triangles are discarded. Front faces are counterclockwise in
+/// normalized device coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CullMode {
+    None,
+    #[default]
+    Back,
+    Front,
+}
+
+/// Fixed-function state of the triangle pipeline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rasterizer {
+    pub cull_mode: CullMode,
+    /// Discard fragments that are not closer than the stored depth.
+    pub depth_test: bool,
+    pub depth_write: bool,
+}
+
+impl Default for Rasterizer {
+    fn default() -> Self {
+        Self {
+            cull_mode: CullMode::Back,
+            depth_test: true,
+            depth_write: true,
+        }
+    }
+}
+
+/// A vertex in homogeneous clip space.
+#[derive(Debug, Clone, Copy)]
+struct ClipVertex<const N: usize> {
+    position: [f32; 4],
+    attributes: [f32; N],
+}
+
+impl<const N: usize> ClipVertex<N> {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        let mut result = *self;
+
+        for i in 0..4 {
+            result.position[i] += (other.position[i] - self.position[i]) * t;
+        }
+        for i in 0..N {
+            result.attributes[i] += (other.attributes[i] - self.attributes[i]) * t;
+        }
+
+        return result;
+    }
+}
+
+/// A vertex after the perspective divide and viewport transform. The
+/// attributes are pre-divided by `w` for perspective-correct interpolation.
+#[derive(Debug, Clone, Copy)]
+struct ScreenVertex<const N: usize> {
+    x: f32,
+    y: f32,
+    z: f32,
+    inv_w: f32,
+    attributes: [f32; N],
+}
+
+/// Smallest `w` kept by clipping, so the perspective divide stays finite.
+const W_EPSILON: f32 = 1e-5;
+
+impl Rasterizer {
+    /// Draws indexed triangles into `target` and returns the number of
+    /// fragments written.
+    ///
+    /// Each vertex is transformed by `mvp` into clip space, clipped against
+    /// the view frustum, culled and rasterized with the top-left fill rule;
+    /// `shader` computes the color of every fragment that passes the depth
+    /// test.
+    pub fn draw<const N: usize>(
+        &self,
+        target: &mut Framebuffer,
+        mvp: &Matrix4x4F32,
+        vertices: &[Vertex<N>],
+        triangles: &[[usize; 3]],
+        mut shader: impl FnMut(&Fragment<N>) -> [u8; 4],
+    ) -> usize {
+        let clip: Vec<ClipVertex<N>> = vertices
+            .iter()
+            .map(|v| ClipVertex {
+                position: *mvp * [v.position.x(), v.position.y(), v.position.z(), 1.0],
+                attributes: v.attributes,
+            })
+            .collect();
+
+        let mut written = 0;
+
+        for t in triangles {
+            let polygon = clip_polygon(vec![clip[t[0]], clip[t[1]], clip[t[2]]]);
+            if polygon.len() < 3 {
+                continue;
+            }
+
+            let screen: Vec<ScreenVertex<N>> = polygon
+                .iter()
+                .map(|v| to_screen(v, target.width(), target.height()))
+                .collect();
+
+            for k in 1..screen.len() - 1 {
+                written += self.raster(target, [screen[0], screen[k], screen[k + 1]], &mut shader);
+            }
+        }
+
+        return written;
+    }
+
+    fn raster<const N: usize>(
+        &self,
+        target: &mut Framebuffer,
+        [a, mut b, mut c]: [ScreenVertex<N>; 3],
+        shader: &mut impl FnMut(&Fragment<N>) -> [u8; 4],
+    ) -> usize {
+        let area = edge(&a, &b, c.x, c.y);
+        if area == 0.0 {
+            return 0;
+        }
+
+        // Window y points down, so counterclockwise triangles in NDC have a
+        // negative area here.
+        let front = area < 0.0;
+        let culled = match self.cull_mode {
+            CullMode::None => false,
+            CullMode::Back => !front,
+            CullMode::Front => front,
+        };
+        if culled {
+            return 0;
+        }
+
+        let area = if area < 0.0 {
+            std::mem::swap(&mut b, &mut c);
+            -area
+        } else {
+            area
+        };
+
+        let min_x = a.x.min(b.x).min(c.x).floor().max(0.0) as usize;
+        let min_y = a.y.min(b.y).min(c.y).floor().max(0.0) as usize;
+        let max_x = (a.x.max(b.x).max(c.x).ceil() as usize).min(target.width());
+        let max_y = (a.y.max(b.y).max(c.y).ceil() as usize).min(target.height());
+
+        let bias = [top_left(&b, &c), top_left(&c, &a), top_left(&a, &b)];
+        let mut written = 0;
+
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                let w = [
+                    edge(&b, &c, px, py),
+                    edge(&c, &a, px, py),
+                    edge(&a, &b, px, py),
+                ];
+
+                if (0..3).any(|i| w[i] < 0.0 || (w[i] == 0.0 && !bias[i])) {
+                    continue;
+                }
+
+                let l = w.map(|e| e / area);
+                let depth = l[0] * a.z + l[1] * b.z + l[2] * c.z;
+
+                if self.depth_test && depth >= target.depth(x, y) {
+                    continue;
+                }
+
+                let inv_w = l[0] * a.inv_w + l[1] * b.inv_w + l[2] * c.inv_w;
+                let attributes: [f32; N] = std::array::from_fn(|i| {
+                    (l[0] * a.attributes[i] + l[1] * b.attributes[i] + l[2] * c.attributes[i])
+                        / inv_w
+                });
+
+                let color = shader(&Fragment {
+                    x,
+                    y,
+                    depth,
+                    attributes,
+                });
+                target.set_pixel(x, y, color);
+                if self.depth_write {
+                    target.set_depth(x, y, depth);
+                }
+                written += 1;
+            }
+        }
+
+        return written;
+    }
+}
+
+/// Sutherland–Hodgman clipping against the six frustum planes
+/// `-w <= x, y, z <= w`, plus `w >= W_EPSILON`.
+fn clip_polygon<const N: usize>(mut polygon: Vec<ClipVertex<N>>) -> Vec<ClipVertex<N>> {
+    let planes: [fn(&[f32; 4]) -> f32; 7] = [
+        |p| p[3] + p[0],
+        |p| p[3] - p[0],
+        |p| p[3] + p[1],
+        |p| p[3] - p[1],
+        |p| p[3] + p[2],
+        |p| p[3] - p[2],
+        |p| p[3] - W_EPSILON,
+    ];
+
+    for distance in planes {
+        if polygon.is_empty() {
+            break;
+        }
+
+        let mut result = Vec::with_capacity(polygon.len() + 1);
+        for i in 0..polygon.len() {
+            let a = &polygon[i];
+            let b = &polygon[(i + 1) % polygon.len()];
+            let (da, db) = (distance(&a.position), distance(&b.position));
+
+            if da >= 0.0 {
+                result.push(*a);
+            }
+            if (da >= 0.0) != (db >= 0.0) {
+                result.push(a.lerp(b, da / (da - db)));
+            }
+        }
+        polygon = result;
+    }
+
+    return polygon;
+}
+
+fn to_screen<const N: usize>(v: &ClipVertex<N>, width: usize, height: usize) -> ScreenVertex<N> {
+    let inv_w = 1.0 / v.position[3];
+    let [x, y, z] = [v.position[0], v.position[1], v.position[2]].map(|c| c * inv_w);
+
+    ScreenVertex {
+        x: (x + 1.0) / 2.0 * width as f32,
+        y: (1.0 - y) / 2.0 * height as f32,
+        z: ((z + 1.0) / 2.0).clamp(0.0, 1.0),
+        inv_w,
+        attributes: v.attributes.map(|a| a * inv_w),
+    }
+}
+
+/// Twice the signed area of `a`, `b`, `p`; positive when `p` is on the
+/// inside of a triangle with positive area.
+fn edge<const N: usize>(a: &ScreenVertex<N>, b: &ScreenVertex<N>, px: f32, py: f32) -> f32 {
+    (b.x - a.x) * (py - a.y) - (b.y - a.y) * (px - a.x)
+}
+
+/// Whether pixels exactly on the edge from `a` to `b` belong to the
+/// triangle: top edges (horizontal, pointing right) and left edges
+/// (pointing up).
+fn top_left<const N: usize>(a: &ScreenVertex<N>, b: &ScreenVertex<N>) -> bool {
+    (a.y == b.y && b.x > a.x) || b.y < a.y
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+
+    fn v(x: f32, y: f32, z: f32) -> Vertex<0> {
+        Vertex::new(Vector3D::create(x, y, z), [])
+    }
+
+    fn count_colored(fb: &Framebuffer, color: [u8; 4]) -> usize {
+        fb.pixels().iter().filter(|&&p| p == color).count()
+    }
+
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const GREEN: [u8; 4] = [0, 255, 0, 255];
+
+    #[test]
+    fn full_screen_quad_covers_each_pixel_once_test() {
+        let mut fb = Framebuffer::new(16, 9);
+        let quad = [
+            v(-1.0, -1.0, 0.0),
+            v(1.0, -1.0, 0.0),
+            v(1.0, 1.0, 0.0),
+            v(-1.0, 1.0, 0.0),
+        ];
+        let mut hits = vec![0; 16 * 9];
+
+        let written = Rasterizer::default().draw(
+            &mut fb,
+            &Matrix4x4F32::identity(),
+            &quad,
+            &[[0, 1, 2], [0, 2, 3]],
+            |f| {
+                hits[f.y * 16 + f.x] += 1;
+                RED
+            },
+        );
+
+        assert_eq!(written, 16 * 9);
+        assert!(hits.iter().all(|&h| h == 1));
+    }
+
+    #[test]
+    fn shared_edges_do_not_overlap_test() {
+        // A fan of thin triangles around a center that is not on a pixel
+        // center boundary; every covered pixel must be hit exactly once.
+        let mut fb = Framebuffer::new(32, 32);
+        let mut vertices = vec![v(0.13, -0.07, 0.0)];
+        for k in 0..12 {
+            let angle = k as f32 / 12.0 * std::f32::consts::TAU;
+            vertices.push(v(0.9 * angle.cos(), 0.9 * angle.sin(), 0.0));
+        }
+        let triangles: Vec<[usize; 3]> = (0..12).map(|k| [0, 1 + k, 1 + (k + 1) % 12]).collect();
+        let mut hits = vec![0; 32 * 32];
+
+        let raster = Rasterizer {
+            depth_test: false,
+            ..Default::default()
+        };
+        raster.draw(
+            &mut fb,
+            &Matrix4x4F32::identity(),
+            &vertices,
+            &triangles,
+            |f| {
+                hits[f.y * 32 + f.x] += 1;
+                RED
+            },
+        );
+
+        assert!(hits.iter().all(|&h| h <= 1));
+        assert!(hits.iter().filter(|&&h| h == 1).count() > 500);
+    }
+
+    #[test]
+    fn back_face_culling_test() {
+        let mut fb = Framebuffer::new(8, 8);
+        let triangle = [v(-1.0, -1.0, 0.0), v(1.0, -1.0, 0.0), v(-1.0, 1.0, 0.0)];
+        let identity = Matrix4x4F32::identity();
+
+        let mut raster = Rasterizer::default();
+        assert!(raster.draw(&mut fb, &identity, &triangle, &[[0, 1, 2]], |_| RED) > 0);
+        assert_eq!(
+            raster.draw(&mut fb, &identity, &triangle, &[[0, 2, 1]], |_| RED),
+            0
+        );
+
+        raster.cull_mode = CullMode::Front;
+        fb.clear_depth(1.0);
+        assert_eq!(
+            raster.draw(&mut fb, &identity, &triangle, &[[0, 1, 2]], |_| RED),
+            0
+        );
+        assert!(raster.draw(&mut fb, &identity, &triangle, &[[0, 2, 1]], |_| RED) > 0);
+    }
+
+    #[test]
+    fn depth_test_test() {
+        let near = [v(-1.0, -1.0, -0.5), v(1.0, -1.0, -0.5), v(0.0, 1.0, -0.5)];
+        let far = [
+            v(-1.0, -1.0, 0.5),
+            v(1.0, -1.0, 0.5),
+            v(1.0, 1.0, 0.5),
+            v(-1.0, 1.0, 0.5),
+        ];
+        let identity = Matrix4x4F32::identity();
+        let raster = Rasterizer::default();
+
+        // The near triangle wins regardless of the drawing order.
+        let mut first = Framebuffer::new(16, 16);
+        raster.draw(&mut first, &identity, &near, &[[0, 1, 2]], |_| RED);
+        raster.draw(&mut first, &identity, &far, &[[0, 1, 2], [0, 2, 3]], |_| {
+            GREEN
+        });
+
+        let mut second = Framebuffer::new(16, 16);
+        raster.draw(
+            &mut second,
+            &identity,
+            &far,
+            &[[0, 1, 2], [0, 2, 3]],
+            |_| GREEN,
+        );
+        raster.draw(&mut second, &identity, &near, &[[0, 1, 2]], |_| RED);
+
+        assert_eq!(first, second);
+        assert_eq!(first.pixel(8, 12), RED);
+        assert_eq!(first.pixel(1, 1), GREEN);
+        assert!((first.depth(8, 12) - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn clipping_test() {
+        let mut fb = Framebuffer::new(20, 20);
+        let mvp = Matrix4x4F32::perspective(FRAC_PI_2, 1.0, 1.0, 100.0);
+
+        // A floor triangle that passes behind the camera and beyond the
+        // sides of the view.
+        let floor = [v(-50.0, -1.0, 5.0), v(50.0, -1.0, 5.0), v(0.0, -1.0, -50.0)];
+        let written = Rasterizer::default().draw(&mut fb, &mvp, &floor, &[[0, 1, 2]], |_| RED);
+
+        // The floor fills the bottom half of the view up to the horizon.
+        assert!(written > 0);
+        assert_eq!(fb.pixel(10, 19), RED);
+        assert_eq!(fb.pixel(10, 5), [0; 4]);
+
+        // Entirely outside.
+        let behind = [v(-1.0, -1.0, 5.0), v(1.0, -1.0, 5.0), v(0.0, 1.0, 5.0)];
+        assert_eq!(
+            Rasterizer::default().draw(&mut fb, &mvp, &behind, &[[0, 1, 2]], |_| RED),
+            0
+        );
+    }
+
+    #[test]
+    fn perspective_correct_interpolation_test() {
+        let (n, f) = (1.0, 20.0);
+        let mvp = Matrix4x4F32::perspective(FRAC_PI_2, 1.0, n, f);
+        let mut fb = Framebuffer::new(32, 32);
+
+        // A plane receding from z = -2 to z = -10, carrying its own view
+        // depth as an attribute. Affine interpolation would bend it.
+        let vertex = |x: f32, y: f32, z: f32| Vertex::new(Vector3D::create(x, y, z), [-z]);
+        let quad = [
+            vertex(-2.0, -2.0, -2.0),
+            vertex(2.0, -2.0, -2.0),
+            vertex(10.0, 10.0, -10.0),
+            vertex(-10.0, 10.0, -10.0),
+        ];
+
+        let mut checked = 0;
+        Rasterizer::default().draw(&mut fb, &mvp, &quad, &[[0, 1, 2], [0, 2, 3]], |frag| {
+            // Recover view depth from the window depth.
+            let ndc = frag.depth * 2.0 - 1.0;
+            let w = 2.0 * f * n / ((f + n) - ndc * (f - n));
+            assert!(
+                (frag.attributes[0] - w).abs() < 1e-2 * w,
+                "{} vs {w}",
+                frag.attributes[0]
+            );
+            checked += 1;
+            RED
+        });
+
+        assert!(checked > 100);
+    }
+}
@@ -0,0 +1,608 @@
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+use crate::math::Matrix4x4F32;
+
+use super::Transform;
+
+/// A generational node ID; stale IDs of removed nodes are rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId {
+    index: u32,
+    generation: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SceneError {
+    MissingNode(NodeId),
+    /// The new parent is the node itself or one of its descendants.
+    Cycle {
+        node: NodeId,
+        parent: NodeId,
+    },
+    /// The new parent's world matrix has no inverse, e.g. a zero scale.
+    SingularParent(NodeId),
+}
+
+impl Display for SceneError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::MissingNode(id) => write!(f, "no scene node {id:?}"),
+            SceneError::Cycle { node, parent } => {
+                write!(f, "{parent:?} is {node:?} or one of its descendants")
+            }
+            SceneError::SingularParent(id) => write!(f, "{id:?} has a singular world matrix"),
+        }
+    }
+}
+
+impl Error for SceneError {}
+
+/// A node of a [`SceneGraph`] with its user data.
+#[derive(Debug, Clone)]
+pub struct Node<T> {
+    data: T,
+    local: Transform,
+    world: Matrix4x4F32,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+    /// The local transform changed since the last update.
+    dirty: bool,
+    /// Some descendant is dirty.
+    dirty_below: bool,
+}
+
+impl<T> Node<T> {
+    pub fn data(&self) -> &T {
+        &self.data
+    }
+
+    pub fn transform(&self) -> &Transform {
+        &self.local
+    }
+
+    /// As of the last [`SceneGraph::update`].
+    pub fn world_matrix(&self) -> &Matrix4x4F32 {
+        &self.world
+    }
+
+    pub fn parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+
+    pub fn children(&self) -> &[NodeId] {
+        &self.children
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Slot<T> {
+    generation: u32,
+    node: Option<Node<T>>,
+}
+
+/// A hierarchy of nodes with local transforms.
+///
+/// World matrices are cached per node. Changing a local transform marks the
+/// node dirty and flags its ancestors, so [`update`](Self::update) only
+/// visits the subtrees that changed.
+#[derive(Debug, Clone)]
+pub struct SceneGraph<T> {
+    slots: Vec<Slot<T>>,
+    free: Vec<u32>,
+    roots: Vec<NodeId>,
+    len: usize,
+}
+
+impl<T> Default for SceneGraph<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> SceneGraph<T> {
+    pub fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            free: Vec::new(),
+            roots: Vec::new(),
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn contains(&self, id: NodeId) -> bool {
+        self.node(id).is_some()
+    }
+
+    pub fn roots(&self) -> &[NodeId] {
+        &self.roots
+    }
+
+    /// Adds a node without a parent.
+    pub fn add(&mut self, transform: Transform, data: T) -> NodeId {
+        let id = self.alloc(transform, data, None);
+        self.roots.push(id);
+
+        return id;
+    }
+
+    /// Adds a node as the last child of `parent`.
+    pub fn add_child(
+        &mut self,
+        parent: NodeId,
+        transform: Transform,
+        data: T,
+    ) -> Result<NodeId, SceneError> {
+        if !self.contains(parent) {
+            return Err(SceneError::MissingNode(parent));
+        }
+
+        let id = self.alloc(transform, data, Some(parent));
+        self.node_mut(parent).unwrap().children.push(id);
+        self.mark_dirty(id);
+
+        return Ok(id);
+    }
+
+    /// Removes the node and its descendants, returning the node's data.
+    pub fn remove(&mut self, id: NodeId) -> Option<T> {
+        let node = self.node(id)?;
+        match node.parent {
+            Some(parent) => self.node_mut(parent).unwrap().children.retain(|&c| c != id),
+            None => self.roots.retain(|&r| r != id),
+        }
+
+        let mut stack = vec![id];
+        let mut data = None;
+        while let Some(next) = stack.pop() {
+            let slot = &mut self.slots[next.index as usize];
+            let node = slot.node.take().unwrap();
+            slot.generation += 1;
+            self.free.push(next.index);
+            self.len -= 1;
+
+            stack.extend(node.children);
+            if next == id {
+                data = Some(node.data);
+            }
+        }
+
+        return data;
+    }
+
+    pub fn node(&self, id: NodeId) -> Option<&Node<T>> {
+        let slot = self.slots.get(id.index as usize)?;
+        if slot.generation != id.generation {
+            return None;
+        }
+
+        return slot.node.as_ref();
+    }
+
+    pub fn get(&self, id: NodeId) -> Option<&T> {
+        self.node(id).map(|n| &n.data)
+    }
+
+    pub fn get_mut(&mut self, id: NodeId) -> Option<&mut T> {
+        self.node_mut(id).map(|n| &mut n.data)
+    }
+
+    pub fn transform(&self, id: NodeId) -> Option<&Transform> {
+        self.node(id).map(|n| &n.local)
+    }
+
+    /// Marks the node dirty whether or not the transform is changed.
+    pub fn transform_mut(&mut self, id: NodeId) -> Option<&mut Transform> {
+        self.node(id)?;
+        self.mark_dirty(id);
+
+        return self.node_mut(id).map(|n| &mut n.local);
+    }
+
+    pub fn set_transform(&mut self, id: NodeId, transform: Transform) -> bool {
+        match self.transform_mut(id) {
+            Some(local) => {
+                *local = transform;
+                return true;
+            }
+            None => return false,
+        }
+    }
+
+    /// The cached world matrix, as of the last [`update`](Self::update).
+    pub fn world_matrix(&self, id: NodeId) -> Option<Matrix4x4F32> {
+        self.node(id).map(|n| n.world)
+    }
+
+    /// The world matrix from the current local transforms, computed
+    /// without touching the cache.
+    pub fn compute_world_matrix(&self, id: NodeId) -> Option<Matrix4x4F32> {
+        let mut world = self.node(id)?.local.matrix();
+        for ancestor in self.ancestors(id) {
+            world = self.node(ancestor).unwrap().local.matrix() * world;
+        }
+
+        return Some(world);
+    }
+
+    /// Recomputes the world matrices of dirty nodes and their descendants.
+    pub fn update(&mut self) {
+        let mut stack: Vec<(NodeId, Matrix4x4F32, bool)> = self
+            .roots
+            .iter()
+            .rev()
+            .map(|&r| (r, Matrix4x4F32::identity(), false))
+            .collect();
+
+        while let Some((id, parent_world, parent_changed)) = stack.pop() {
+            let node = self.node_mut(id).unwrap();
+            let changed = parent_changed || node.dirty;
+            if !changed && !node.dirty_below {
+                continue;
+            }
+
+            if changed {
+                node.world = parent_world * node.local.matrix();
+            }
+            node.dirty = false;
+            node.dirty_below = false;
+
+            let world = node.world;
+            stack.extend(node.children.iter().rev().map(|&c| (c, world, changed)));
+        }
+    }
+
+    /// Moves the node under `parent`, or makes it a root, keeping its world
+    /// transform: the local transform is recomputed relative to the new
+    /// parent.
+    pub fn set_parent(&mut self, id: NodeId, parent: Option<NodeId>) -> Result<(), SceneError> {
+        let old_parent = self.node(id).ok_or(SceneError::MissingNode(id))?.parent;
+        let world = self.compute_world_matrix(id).unwrap();
+
+        let local = match parent {
+            Some(p) => {
+                if !self.contains(p) {
+                    return Err(SceneError::MissingNode(p));
+                }
+                if p == id || self.ancestors(p).any(|a| a == id) {
+                    return Err(SceneError::Cycle {
+                        node: id,
+                        parent: p,
+                    });
+                }
+
+                let parent_world = self.compute_world_matrix(p).unwrap();
+                let inverse = parent_world
+                    .inverse()
+                    .ok_or(SceneError::SingularParent(p))?;
+                inverse * world
+            }
+            None => world,
+        };
+
+        match old_parent {
+            Some(old) => self.node_mut(old).unwrap().children.retain(|&c| c != id),
+            None => self.roots.retain(|&r| r != id),
+        }
+        match parent {
+            Some(p) => self.node_mut(p).unwrap().children.push(id),
+            None => self.roots.push(id),
+        }
+
+        let node = self.node_mut(id).unwrap();
+        node.parent = parent;
+        node.local = Transform::from_matrix(&local);
+        self.mark_dirty(id);
+
+        return Ok(());
+    }
+
+    /// Parent, grandparent and so on up to the root.
+    pub fn ancestors(&self, id: NodeId) -> Ancestors<'_, T> {
+        Ancestors {
+            graph: self,
+            next: self.node(id).and_then(|n| n.parent),
+        }
+    }
+
+    /// The node and its descendants, depth first, parents before children.
+    pub fn descendants(&self, id: NodeId) -> DepthFirst<'_, T> {
+        DepthFirst {
+            graph: self,
+            stack: if self.contains(id) {
+                vec![(id, 0)]
+            } else {
+                Vec::new()
+            },
+            current: None,
+            skip: false,
+        }
+    }
+
+    /// Every node, depth first from each root in turn.
+    pub fn iter(&self) -> DepthFirst<'_, T> {
+        DepthFirst {
+            graph: self,
+            stack: self.roots.iter().rev().map(|&r| (r, 0)).collect(),
+            current: None,
+            skip: false,
+        }
+    }
+
+    fn alloc(&mut self, transform: Transform, data: T, parent: Option<NodeId>) -> NodeId {
+        let node = Node {
+            data,
+            local: transform,
+            world: Matrix4x4F32::identity(),
+            parent,
+            children: Vec::new(),
+            dirty: true,
+            dirty_below: false,
+        };
+        self.len += 1;
+
+        if let Some(index) = self.free.pop() {
+            let slot = &mut self.slots[index as usize];
+            slot.node = Some(node);
+            return NodeId {
+                index,
+                generation: slot.generation,
+            };
+        }
+
+        self.slots.push(Slot {
+            generation: 0,
+            node: Some(node),
+        });
+        return NodeId {
+            index: self.slots.len() as u32 - 1,
+            generation: 0,
+        };
+    }
+
+    fn node_mut(&mut self, id: NodeId) -> Option<&mut Node<T>> {
+        let slot = self.slots.get_mut(id.index as usize)?;
+        if slot.generation != id.generation {
+            return None;
+        }
+
+        return slot.node.as_mut();
+    }
+
+    /// Flags the node, and its ancestors up to the first already flagged.
+    fn mark_dirty(&mut self, id: NodeId) {
+        let node = self.node_mut(id).unwrap();
+        node.dirty = true;
+
+        let mut next = node.parent;
+        while let Some(parent) = next {
+            let node = self.node_mut(parent).unwrap();
+            if node.dirty_below {
+                break;
+            }
+            node.dirty_below = true;
+            next = node.parent;
+        }
+    }
+}
+
+/// See [`SceneGraph::ancestors`].
+pub struct Ancestors<'a, T> {
+    graph: &'a SceneGraph<T>,
+    next: Option<NodeId>,
+}
+
+impl<T> Iterator for Ancestors<'_, T> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        let id = self.next?;
+        self.next = self.graph.node(id).unwrap().parent;
+
+        return Some(id);
+    }
+}
+
+/// Pre-order traversal; see [`SceneGraph::iter`]. Culling and picking can
+/// prune subtrees with [`skip_children`](Self::skip_children).
+pub struct DepthFirst<'a, T> {
+    graph: &'a SceneGraph<T>,
+    /// Nodes to visit with their depth.
+    stack: Vec<(NodeId, usize)>,
+    /// The node returned last, whose children are pushed on the next call
+    /// unless skipped.
+    current: Option<(NodeId, usize)>,
+    skip: bool,
+}
+
+impl<T> DepthFirst<'_, T> {
+    /// Does not descend into the node returned last.
+    pub fn skip_children(&mut self) {
+        self.skip = true;
+    }
+
+    /// Depth of the node returned last; roots are at 0.
+    pub fn depth(&self) -> Option<usize> {
+        self.current.map(|(_, depth)| depth)
+    }
+}
+
+impl<'a, T> Iterator for DepthFirst<'a, T> {
+    type Item = (NodeId, &'a Node<T>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some((id, depth)) = self.current.take() {
+            if !self.skip {
+                let children = &self.graph.node(id).unwrap().children;
+                self.stack
+                    .extend(children.iter().rev().map(|&c| (c, depth + 1)));
+            }
+        }
+        self.skip = false;
+
+        let (id, depth) = self.stack.pop()?;
+        self.current = Some((id, depth));
+
+        return Some((id, self.graph.node(id).unwrap()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+    use crate::math::{Quaternion, Vector3D};
+
+    fn v(x: f32, y: f32, z: f32) -> Vector3D {
+        Vector3D::create(x, y, z)
+    }
+
+    fn origin(graph: &SceneGraph<&str>, id: NodeId) -> Vector3D {
+        graph
+            .world_matrix(id)
+            .unwrap()
+            .transform_point(&Vector3D::default())
+    }
+
+    fn assert_close(a: Vector3D, b: Vector3D) {
+        assert!((a - b).magnitude() < 1e-5, "{a} != {b}");
+    }
+
+    /// root (+x 1) -> arm (rotated 90° about z, +x 2) -> hand (+x 1)
+    fn arm() -> (SceneGraph<&'static str>, [NodeId; 3]) {
+        let mut graph = SceneGraph::new();
+        let root = graph.add(Transform::from_translation(v(1.0, 0.0, 0.0)), "root");
+        let arm = graph
+            .add_child(
+                root,
+                Transform {
+                    translation: v(2.0, 0.0, 0.0),
+                    rotation: Quaternion::from_axis_angle(&v(0.0, 0.0, 1.0), FRAC_PI_2),
+                    ..Transform::default()
+                },
+                "arm",
+            )
+            .unwrap();
+        let hand = graph
+            .add_child(arm, Transform::from_translation(v(1.0, 0.0, 0.0)), "hand")
+            .unwrap();
+        graph.update();
+
+        return (graph, [root, arm, hand]);
+    }
+
+    #[test]
+    fn propagate_test() {
+        let (mut graph, [root, arm, hand]) = arm();
+        assert_close(origin(&graph, arm), v(3.0, 0.0, 0.0));
+        assert_close(origin(&graph, hand), v(3.0, 1.0, 0.0));
+
+        graph.transform_mut(root).unwrap().translation = v(0.0, 0.0, 5.0);
+        // The cache is stale until the next update.
+        assert_close(origin(&graph, hand), v(3.0, 1.0, 0.0));
+        assert_close(
+            graph
+                .compute_world_matrix(hand)
+                .unwrap()
+                .transform_point(&Vector3D::default()),
+            v(2.0, 1.0, 5.0),
+        );
+        graph.update();
+        assert_close(origin(&graph, hand), v(2.0, 1.0, 5.0));
+
+        graph.set_transform(hand, Transform::from_translation(v(2.0, 0.0, 0.0)));
+        assert!(graph.node(arm).unwrap().dirty_below && graph.node(root).unwrap().dirty_below);
+        graph.update();
+        assert_close(origin(&graph, hand), v(2.0, 2.0, 5.0));
+        assert!(!graph.node(root).unwrap().dirty_below);
+    }
+
+    #[test]
+    fn reparent_test() {
+        let (mut graph, [root, arm, hand]) = arm();
+        let before = graph.world_matrix(hand).unwrap();
+
+        graph.set_parent(hand, Some(root)).unwrap();
+        assert_eq!(graph.node(hand).unwrap().parent(), Some(root));
+        assert_eq!(graph.node(root).unwrap().children(), [arm, hand]);
+        assert!(graph.node(arm).unwrap().children().is_empty());
+        graph.update();
+        for (a, b) in graph.world_matrix(hand).unwrap().iter().zip(before.iter()) {
+            assert!((a - b).abs() < 1e-5);
+        }
+
+        graph.set_parent(arm, None).unwrap();
+        assert_eq!(graph.roots(), [root, arm]);
+        graph.update();
+        assert_close(origin(&graph, arm), v(3.0, 0.0, 0.0));
+
+        assert_eq!(
+            graph.set_parent(root, Some(root)),
+            Err(SceneError::Cycle {
+                node: root,
+                parent: root
+            })
+        );
+        graph.set_parent(arm, Some(hand)).unwrap();
+        assert_eq!(
+            graph.set_parent(root, Some(arm)),
+            Err(SceneError::Cycle {
+                node: root,
+                parent: arm
+            })
+        );
+    }
+
+    #[test]
+    fn remove_test() {
+        let (mut graph, [root, arm, hand]) = arm();
+        let other = graph.add(Transform::default(), "other");
+
+        assert_eq!(graph.remove(arm), Some("arm"));
+        assert_eq!(graph.len(), 2);
+        assert!(!graph.contains(hand));
+        assert!(graph.node(root).unwrap().children().is_empty());
+        assert_eq!(graph.remove(arm), None);
+
+        let reused = graph.add_child(other, Transform::default(), "new").unwrap();
+        assert!(reused != arm && reused != hand);
+        assert_eq!(graph.get(arm), None);
+        assert_eq!(
+            graph.add_child(hand, Transform::default(), "x"),
+            Err(SceneError::MissingNode(hand))
+        );
+    }
+
+    #[test]
+    fn traversal_test() {
+        let (mut graph, [root, arm, hand]) = arm();
+        let leg = graph.add_child(root, Transform::default(), "leg").unwrap();
+        let other = graph.add(Transform::default(), "other");
+
+        let order: Vec<_> = graph.iter().map(|(_, node)| *node.data()).collect();
+        assert_eq!(order, ["root", "arm", "hand", "leg", "other"]);
+        assert_eq!(graph.ancestors(hand).collect::<Vec<_>>(), [arm, root]);
+
+        let mut visited = Vec::new();
+        let mut walk = graph.descendants(root);
+        while let Some((id, node)) = walk.next() {
+            visited.push((*node.data(), walk.depth().unwrap()));
+            if id == arm {
+                walk.skip_children();
+            }
+        }
+        assert_eq!(visited, [("root", 0), ("arm", 1), ("leg", 1)]);
+        assert_eq!(graph.descendants(other).count(), 1);
+    }
+}
@@ -0,0 +1,14 @@
+//! Scene graph.
+//!
+//! A [`SceneGraph`] holds nodes with a local [`Transform`] and user data.
+//! World matrices are propagated from parents to children by
+//! [`SceneGraph::update`], which only revisits the subtrees whose
+//! transforms changed.
+
+#![allow(clippy::needless_return)]
+
+pub mod graph;
+pub mod transform;
+
+pub use graph::{Ancestors, DepthFirst, Node, NodeId, SceneError, SceneGraph};
+pub use transform::Transform;
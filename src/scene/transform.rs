@@ -0,0 +1,90 @@
+use crate::math::{Matrix4x4F32, Quaternion, Vector3D};
+
+/// Translation, rotation and scale of a node relative to its parent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub translation: Vector3D,
+    pub rotation: Quaternion,
+    pub scale: Vector3D,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            translation: Vector3D::default(),
+            rotation: Quaternion::identity(),
+            scale: Vector3D::create(1.0, 1.0, 1.0),
+        }
+    }
+}
+
+impl Transform {
+    pub fn from_translation(translation: Vector3D) -> Self {
+        Self {
+            translation,
+            ..Self::default()
+        }
+    }
+
+    pub fn from_rotation(rotation: Quaternion) -> Self {
+        Self {
+            rotation,
+            ..Self::default()
+        }
+    }
+
+    /// Splits an affine matrix into translation, rotation and scale. Shear,
+    /// which a parent with non-uniform scale can introduce, is lost; a
+    /// mirroring matrix gets a negative x scale.
+    pub fn from_matrix(m: &Matrix4x4F32) -> Self {
+        let column = |j: usize| Vector3D::create(m.at(0, j), m.at(1, j), m.at(2, j));
+        let (x, y, z) = (column(0), column(1), column(2));
+
+        let mut scale = Vector3D::create(x.magnitude(), y.magnitude(), z.magnitude());
+        if x.cross(&y).dot(&z) < 0.0 {
+            scale = Vector3D::create(-scale.x(), scale.y(), scale.z());
+        }
+
+        let mut rotation = Matrix4x4F32::identity();
+        for (j, axis) in [x / scale.x(), y / scale.y(), z / scale.z()]
+            .iter()
+            .enumerate()
+        {
+            rotation[(0, j)] = axis.x();
+            rotation[(1, j)] = axis.y();
+            rotation[(2, j)] = axis.z();
+        }
+
+        return Self {
+            translation: column(3),
+            rotation: Quaternion::from_rotation_matrix(&rotation),
+            scale,
+        };
+    }
+
+    /// Scale, then rotate, then translate.
+    pub fn matrix(&self) -> Matrix4x4F32 {
+        Matrix4x4F32::translation(&self.translation)
+            * self.rotation.to_matrix()
+            * Matrix4x4F32::scale(&self.scale)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decompose_test() {
+        let transform = Transform {
+            translation: Vector3D::create(1.0, -2.0, 3.0),
+            rotation: Quaternion::from_euler(&Vector3D::create(0.4, 1.1, -0.7)),
+            scale: Vector3D::create(2.0, 0.5, 3.0),
+        };
+
+        let back = Transform::from_matrix(&transform.matrix());
+        assert!((back.translation - transform.translation).magnitude() < 1e-5);
+        assert!((back.scale - transform.scale).magnitude() < 1e-5);
+        assert!((back.rotation.dot(&transform.rotation).abs() - 1.0).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,238 @@
+use crate::math::Vector3D;
+
+use super::Ray;
+
+/// An axis-aligned bounding box. An empty box has `min > max` and grows to
+/// fit whatever is included.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Vector3D,
+    pub max: Vector3D,
+}
+
+impl Default for Aabb {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl Aabb {
+    pub fn new(min: Vector3D, max: Vector3D) -> Self {
+        Self { min, max }
+    }
+
+    pub fn empty() -> Self {
+        Self {
+            min: Vector3D::create(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+            max: Vector3D::create(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+        }
+    }
+
+    /// The smallest box around `points`; empty if there are none.
+    pub fn from_points(points: &[Vector3D]) -> Self {
+        return points.iter().fold(Aabb::empty(), |b, p| b.include(*p));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.x() > self.max.x() || self.min.y() > self.max.y() || self.min.z() > self.max.z()
+    }
+
+    pub fn include(&self, p: Vector3D) -> Aabb {
+        Aabb::new(
+            Vector3D::create(
+                self.min.x().min(p.x()),
+                self.min.y().min(p.y()),
+                self.min.z().min(p.z()),
+            ),
+            Vector3D::create(
+                self.max.x().max(p.x()),
+                self.max.y().max(p.y()),
+                self.max.z().max(p.z()),
+            ),
+        )
+    }
+
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        if other.is_empty() {
+            return *self;
+        }
+
+        return self.include(other.min).include(other.max);
+    }
+
+    pub fn center(&self) -> Vector3D {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn extent(&self) -> Vector3D {
+        self.max - self.min
+    }
+
+    /// Zero for empty boxes.
+    pub fn surface_area(&self) -> f32 {
+        if self.is_empty() {
+            return 0.0;
+        }
+
+        let e = self.extent();
+        return 2.0 * (e.x() * e.y() + e.y() * e.z() + e.z() * e.x());
+    }
+
+    /// The axis, 0 to 2, along which the box is longest.
+    pub fn longest_axis(&self) -> usize {
+        let e = self.extent();
+        if e.x() >= e.y() && e.x() >= e.z() {
+            return 0;
+        }
+        if e.y() >= e.z() {
+            return 1;
+        }
+
+        return 2;
+    }
+
+    pub fn contains(&self, p: Vector3D) -> bool {
+        (0..3).all(|i| axis(&self.min, i) <= axis(&p, i) && axis(&p, i) <= axis(&self.max, i))
+    }
+
+    /// Whether the boxes overlap or touch.
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        (0..3).all(|i| {
+            axis(&self.min, i) <= axis(&other.max, i) && axis(&other.min, i) <= axis(&self.max, i)
+        })
+    }
+
+    /// The point of the box nearest to `p`.
+    pub fn closest_point(&self, p: Vector3D) -> Vector3D {
+        Vector3D::create(
+            p.x().clamp(self.min.x(), self.max.x()),
+            p.y().clamp(self.min.y(), self.max.y()),
+            p.z().clamp(self.min.z(), self.max.z()),
+        )
+    }
+
+    /// Squared distance from `p` to the box; zero inside it.
+    pub fn distance_squared(&self, p: Vector3D) -> f32 {
+        let d = self.closest_point(p) - p;
+
+        return d.dot(&d);
+    }
+
+    /// Where the ray enters the box, clamped to `t_min`, if it does so
+    /// before `t_max`. Slab test.
+    pub fn ray_hit(&self, ray: &Ray, mut t_min: f32, mut t_max: f32) -> Option<f32> {
+        for i in 0..3 {
+            let inv = 1.0 / axis(&ray.direction, i);
+            let mut t0 = (axis(&self.min, i) - axis(&ray.origin, i)) * inv;
+            let mut t1 = (axis(&self.max, i) - axis(&ray.origin, i)) * inv;
+            if inv < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+
+            // Written so that NaN from 0 * inf leaves the bounds alone.
+            t_min = if t0 > t_min { t0 } else { t_min };
+            t_max = if t1 < t_max { t1 } else { t_max };
+            if t_max < t_min {
+                return None;
+            }
+        }
+
+        return Some(t_min);
+    }
+}
+
+/// Component `i` of `v`: 0 is x, 1 is y and 2 is z.
+pub(crate) fn axis(v: &Vector3D, i: usize) -> f32 {
+    match i {
+        0 => v.x(),
+        1 => v.y(),
+        _ => v.z(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit() -> Aabb {
+        Aabb::new(
+            Vector3D::create(-1.0, -1.0, -1.0),
+            Vector3D::create(1.0, 1.0, 1.0),
+        )
+    }
+
+    #[test]
+    fn build_test() {
+        let b = Aabb::from_points(&[
+            Vector3D::create(1.0, 5.0, -2.0),
+            Vector3D::create(-3.0, 0.0, 4.0),
+        ]);
+
+        assert_eq!(b.min, Vector3D::create(-3.0, 0.0, -2.0));
+        assert_eq!(b.max, Vector3D::create(1.0, 5.0, 4.0));
+        assert_eq!(b.longest_axis(), 2);
+        assert_eq!(b.surface_area(), 2.0 * (20.0 + 30.0 + 24.0));
+
+        assert!(Aabb::empty().is_empty());
+        assert_eq!(Aabb::empty().union(&b), b);
+        assert_eq!(b.union(&Aabb::empty()), b);
+    }
+
+    #[test]
+    fn overlap_test() {
+        let a = unit();
+        let touching = Aabb::new(
+            Vector3D::create(1.0, 0.0, 0.0),
+            Vector3D::create(2.0, 1.0, 1.0),
+        );
+        let apart = Aabb::new(
+            Vector3D::create(1.5, 0.0, 0.0),
+            Vector3D::create(2.0, 1.0, 1.0),
+        );
+
+        assert!(a.intersects(&touching));
+        assert!(!a.intersects(&apart));
+        assert!(a.contains(Vector3D::create(1.0, 0.0, -1.0)));
+        assert_eq!(a.distance_squared(Vector3D::create(3.0, 3.0, 0.0)), 8.0);
+        assert_eq!(a.distance_squared(Vector3D::default()), 0.0);
+    }
+
+    #[test]
+    fn ray_hit_test() {
+        let b = unit();
+        let x = Vector3D::create(1.0, 0.0, 0.0);
+
+        assert_eq!(
+            b.ray_hit(
+                &Ray::new(Vector3D::create(-5.0, 0.0, 0.0), x),
+                0.0,
+                f32::INFINITY
+            ),
+            Some(4.0)
+        );
+        assert_eq!(
+            b.ray_hit(&Ray::new(Vector3D::default(), x), 0.0, f32::INFINITY),
+            Some(0.0)
+        );
+        assert_eq!(
+            b.ray_hit(&Ray::new(Vector3D::create(-5.0, 0.0, 0.0), x), 0.0, 3.0),
+            None
+        );
+        assert_eq!(
+            b.ray_hit(
+                &Ray::new(Vector3D::create(-5.0, 2.0, 0.0), x),
+                0.0,
+                f32::INFINITY
+            ),
+            None
+        );
+        assert_eq!(
+            b.ray_hit(
+                &Ray::new(Vector3D::create(-5.0, 0.0, 0.0), -x),
+                0.0,
+                f32::INFINITY
+            ),
+            None
+        );
+    }
+}
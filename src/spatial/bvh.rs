@@ -0,0 +1,728 @@
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+use crate::math::Vector3D;
+
+use super::{aabb::axis, Aabb, Frustum, Intersection, Ray, ALL_PLANES};
+
+/// Items per leaf the builders aim for.
+const LEAF_SIZE: usize = 4;
+/// Centroid bins per axis for the SAH builder.
+const SAH_BINS: usize = 16;
+/// Cost of visiting a node relative to testing one item.
+const TRAVERSAL_COST: f32 = 1.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Leaf { first: usize, count: usize },
+    Interior { left: usize, right: usize },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Node {
+    bounds: Aabb,
+    kind: Kind,
+}
+
+/// A bounding volume hierarchy over items identified by their index in the
+/// slice of boxes it was built from.
+///
+/// The hierarchy only knows the boxes; queries that need exact geometry
+/// take a callback which tests a single item. Nodes are stored parents
+/// first, so [`refit`](Bvh::refit) is a single backwards pass.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Bvh {
+    nodes: Vec<Node>,
+    /// Item indices, grouped so each leaf owns a contiguous run.
+    items: Vec<usize>,
+    /// Item boxes, indexed like the input.
+    boxes: Vec<Aabb>,
+}
+
+impl Bvh {
+    /// Top-down build minimizing the surface area heuristic over binned
+    /// centroids. Slower to build than [`lbvh`](Self::lbvh) but faster to
+    /// query.
+    pub fn sah(bounds: &[Aabb]) -> Self {
+        let mut bvh = Bvh {
+            nodes: Vec::with_capacity(2 * bounds.len() / LEAF_SIZE + 1),
+            items: (0..bounds.len()).collect(),
+            boxes: bounds.to_vec(),
+        };
+
+        if !bounds.is_empty() {
+            bvh.build_sah(bounds, 0, bounds.len());
+        }
+
+        return bvh;
+    }
+
+    /// Linear BVH: items are sorted along a 30-bit Morton curve through
+    /// their centroids and split where the codes first differ. Builds
+    /// quickly enough to redo every frame.
+    pub fn lbvh(bounds: &[Aabb]) -> Self {
+        let mut bvh = Bvh {
+            nodes: Vec::with_capacity(2 * bounds.len() / LEAF_SIZE + 1),
+            items: (0..bounds.len()).collect(),
+            boxes: bounds.to_vec(),
+        };
+        if bounds.is_empty() {
+            return bvh;
+        }
+
+        let centroids = bounds
+            .iter()
+            .fold(Aabb::empty(), |b, item| b.include(item.center()));
+        let extent = centroids.extent();
+        let codes: Vec<u32> = bounds
+            .iter()
+            .map(|b| {
+                let offset = b.center() - centroids.min;
+                let scale = |i| {
+                    let e = axis(&extent, i);
+                    if e > 0.0 {
+                        ((axis(&offset, i) / e) * 1023.0) as u32
+                    } else {
+                        0
+                    }
+                };
+                morton(scale(0), scale(1), scale(2))
+            })
+            .collect();
+
+        bvh.items.sort_by_key(|&i| codes[i]);
+        let sorted: Vec<u32> = bvh.items.iter().map(|&i| codes[i]).collect();
+        bvh.build_lbvh(bounds, &sorted, 0, bounds.len());
+
+        return bvh;
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// The box around every item; empty if there are none.
+    pub fn bounds(&self) -> Aabb {
+        self.nodes.first().map_or(Aabb::empty(), |n| n.bounds)
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Levels from the root to the deepest leaf; 0 when empty.
+    pub fn depth(&self) -> usize {
+        if self.nodes.is_empty() {
+            return 0;
+        }
+
+        let mut deepest = 0;
+        let mut stack = vec![(0, 1)];
+        while let Some((node, depth)) = stack.pop() {
+            deepest = deepest.max(depth);
+            if let Kind::Interior { left, right } = self.nodes[node].kind {
+                stack.push((left, depth + 1));
+                stack.push((right, depth + 1));
+            }
+        }
+
+        return deepest;
+    }
+
+    /// Recomputes node boxes after items moved, keeping the topology.
+    /// `bounds` must have as many boxes as the hierarchy was built with.
+    ///
+    /// Quality degrades as items drift from where they were at build time;
+    /// rebuild when queries slow down.
+    pub fn refit(&mut self, bounds: &[Aabb]) {
+        assert_eq!(
+            bounds.len(),
+            self.items.len(),
+            "refit with a different number of items"
+        );
+        self.boxes.copy_from_slice(bounds);
+
+        for node in (0..self.nodes.len()).rev() {
+            self.nodes[node].bounds = match self.nodes[node].kind {
+                Kind::Leaf { first, count } => self.items[first..first + count]
+                    .iter()
+                    .fold(Aabb::empty(), |b, &i| b.union(&bounds[i])),
+                Kind::Interior { left, right } => {
+                    self.nodes[left].bounds.union(&self.nodes[right].bounds)
+                }
+            };
+        }
+    }
+
+    /// The closest item along `ray` in `(t_min, t_max)`.
+    ///
+    /// `intersect(item, t_max)` returns the distance at which the ray hits
+    /// the item if it does so before `t_max`. Children are visited near to
+    /// far so that distant subtrees are usually skipped.
+    pub fn raycast(
+        &self,
+        ray: &Ray,
+        t_min: f32,
+        t_max: f32,
+        mut intersect: impl FnMut(usize, f32) -> Option<f32>,
+    ) -> Option<(usize, f32)> {
+        if self.nodes.is_empty() || self.nodes[0].bounds.ray_hit(ray, t_min, t_max).is_none() {
+            return None;
+        }
+
+        let mut closest: Option<(usize, f32)> = None;
+        let mut stack = vec![0];
+        while let Some(node) = stack.pop() {
+            let limit = closest.map_or(t_max, |(_, t)| t);
+
+            match self.nodes[node].kind {
+                Kind::Leaf { first, count } => {
+                    for &item in &self.items[first..first + count] {
+                        let limit = closest.map_or(t_max, |(_, t)| t);
+                        if self.boxes[item].ray_hit(ray, t_min, limit).is_none() {
+                            continue;
+                        }
+                        if let Some(t) = intersect(item, limit) {
+                            if t > t_min && t < limit {
+                                closest = Some((item, t));
+                            }
+                        }
+                    }
+                }
+                Kind::Interior { left, right } => {
+                    let l = self.nodes[left].bounds.ray_hit(ray, t_min, limit);
+                    let r = self.nodes[right].bounds.ray_hit(ray, t_min, limit);
+                    match (l, r) {
+                        (Some(tl), Some(tr)) => {
+                            // The nearer child is popped first.
+                            if tl <= tr {
+                                stack.push(right);
+                                stack.push(left);
+                            } else {
+                                stack.push(left);
+                                stack.push(right);
+                            }
+                        }
+                        (Some(_), None) => stack.push(left),
+                        (None, Some(_)) => stack.push(right),
+                        (None, None) => {}
+                    }
+                }
+            }
+        }
+
+        return closest;
+    }
+
+    /// Items whose boxes overlap `aabb`.
+    pub fn query_aabb(&self, aabb: &Aabb) -> Vec<usize> {
+        let mut found = Vec::new();
+        let mut stack = if self.nodes.is_empty() {
+            vec![]
+        } else {
+            vec![0]
+        };
+
+        while let Some(node) = stack.pop() {
+            if !self.nodes[node].bounds.intersects(aabb) {
+                continue;
+            }
+
+            match self.nodes[node].kind {
+                Kind::Leaf { first, count } => found.extend(
+                    self.items[first..first + count]
+                        .iter()
+                        .filter(|&&i| self.boxes[i].intersects(aabb)),
+                ),
+                Kind::Interior { left, right } => {
+                    stack.push(left);
+                    stack.push(right);
+                }
+            }
+        }
+
+        return found;
+    }
+
+    /// Items whose boxes are at least partly inside `frustum`. Subtrees
+    /// entirely inside are taken whole without testing their items.
+    pub fn query_frustum(&self, frustum: &Frustum) -> Vec<usize> {
+        let mut found = Vec::new();
+        self.cull(frustum, &mut found);
+
+        return found;
+    }
+
+    /// Replaces `visible` with the result of
+    /// [`query_frustum`](Self::query_frustum), reusing its allocation.
+    ///
+    /// Children are only tested against the planes their parent straddles,
+    /// so deep subtrees near one side of the frustum cost a single plane
+    /// test per node.
+    pub fn cull(&self, frustum: &Frustum, visible: &mut Vec<usize>) {
+        visible.clear();
+        let mut stack = if self.nodes.is_empty() {
+            vec![]
+        } else {
+            vec![(0, ALL_PLANES)]
+        };
+
+        while let Some((node, mask)) = stack.pop() {
+            match frustum.classify_aabb_masked(&self.nodes[node].bounds, mask) {
+                (Intersection::Outside, _) => {}
+                (Intersection::Inside, _) => self.collect(node, visible),
+                (Intersection::Intersecting, mask) => match self.nodes[node].kind {
+                    Kind::Leaf { first, count } => {
+                        visible.extend(self.items[first..first + count].iter().filter(|&&i| {
+                            frustum.classify_aabb_masked(&self.boxes[i], mask).0
+                                != Intersection::Outside
+                        }))
+                    }
+                    Kind::Interior { left, right } => {
+                        stack.push((left, mask));
+                        stack.push((right, mask));
+                    }
+                },
+            }
+        }
+    }
+
+    /// The item nearest to `point` and its squared distance.
+    ///
+    /// `distance_squared(item)` measures the exact squared distance from
+    /// `point` to an item. Subtrees whose boxes are farther than the best
+    /// candidate so far are skipped.
+    pub fn nearest(
+        &self,
+        point: Vector3D,
+        mut distance_squared: impl FnMut(usize) -> f32,
+    ) -> Option<(usize, f32)> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        let mut best: Option<(usize, f32)> = None;
+        let mut queue = BinaryHeap::new();
+        queue.push(Candidate {
+            distance: self.nodes[0].bounds.distance_squared(point),
+            node: 0,
+        });
+
+        while let Some(Candidate { distance, node }) = queue.pop() {
+            if best.is_some_and(|(_, d)| d <= distance) {
+                break;
+            }
+
+            match self.nodes[node].kind {
+                Kind::Leaf { first, count } => {
+                    for &item in &self.items[first..first + count] {
+                        if best.is_some_and(|(_, b)| self.boxes[item].distance_squared(point) >= b)
+                        {
+                            continue;
+                        }
+                        let d = distance_squared(item);
+                        if best.is_none_or(|(_, b)| d < b) {
+                            best = Some((item, d));
+                        }
+                    }
+                }
+                Kind::Interior { left, right } => {
+                    for child in [left, right] {
+                        queue.push(Candidate {
+                            distance: self.nodes[child].bounds.distance_squared(point),
+                            node: child,
+                        });
+                    }
+                }
+            }
+        }
+
+        return best;
+    }
+
+    fn collect(&self, node: usize, found: &mut Vec<usize>) {
+        match self.nodes[node].kind {
+            Kind::Leaf { first, count } => {
+                found.extend_from_slice(&self.items[first..first + count])
+            }
+            Kind::Interior { left, right } => {
+                self.collect(left, found);
+                self.collect(right, found);
+            }
+        }
+    }
+
+    fn push_leaf(&mut self, bounds: Aabb, first: usize, count: usize) -> usize {
+        self.nodes.push(Node {
+            bounds,
+            kind: Kind::Leaf { first, count },
+        });
+
+        return self.nodes.len() - 1;
+    }
+
+    /// Reserves a node whose children are built next and filled in by
+    /// [`link`](Self::link).
+    fn push_interior(&mut self, bounds: Aabb) -> usize {
+        self.push_leaf(bounds, 0, 0)
+    }
+
+    fn link(&mut self, node: usize, left: usize, right: usize) {
+        self.nodes[node].kind = Kind::Interior { left, right };
+    }
+
+    fn build_sah(&mut self, bounds: &[Aabb], first: usize, count: usize) -> usize {
+        let run = &mut self.items[first..first + count];
+        let total = run.iter().fold(Aabb::empty(), |b, &i| b.union(&bounds[i]));
+        if count <= LEAF_SIZE {
+            return self.push_leaf(total, first, count);
+        }
+
+        let centroids = run
+            .iter()
+            .fold(Aabb::empty(), |b, &i| b.include(bounds[i].center()));
+        let split = best_sah_split(bounds, run, &centroids, total.surface_area());
+
+        let mid = match split {
+            Some((split_axis, position, cost)) if cost < count as f32 => {
+                partition(run, |&i| axis(&bounds[i].center(), split_axis) < position)
+            }
+            // Splitting does not pay off, or every centroid coincides.
+            _ => {
+                let longest = centroids.longest_axis();
+                let half = count / 2;
+                run.select_nth_unstable_by(half, |&a, &b| {
+                    axis(&bounds[a].center(), longest)
+                        .total_cmp(&axis(&bounds[b].center(), longest))
+                });
+                half
+            }
+        };
+        let mid = if mid == 0 || mid == count {
+            count / 2
+        } else {
+            mid
+        };
+
+        let node = self.push_interior(total);
+        let left = self.build_sah(bounds, first, mid);
+        let right = self.build_sah(bounds, first + mid, count - mid);
+        self.link(node, left, right);
+
+        return node;
+    }
+
+    fn build_lbvh(&mut self, bounds: &[Aabb], codes: &[u32], first: usize, count: usize) -> usize {
+        let total = self.items[first..first + count]
+            .iter()
+            .fold(Aabb::empty(), |b, &i| b.union(&bounds[i]));
+        if count <= LEAF_SIZE {
+            return self.push_leaf(total, first, count);
+        }
+
+        let (a, b) = (codes[first], codes[first + count - 1]);
+        let mid = if a == b {
+            count / 2
+        } else {
+            // The first code with the highest differing bit set.
+            let bit = 31 - (a ^ b).leading_zeros();
+            let prefix = b >> bit;
+            codes[first..first + count].partition_point(|&c| c >> bit < prefix)
+        };
+
+        let node = self.push_interior(total);
+        let left = self.build_lbvh(bounds, codes, first, mid);
+        let right = self.build_lbvh(bounds, codes, first + mid, count - mid);
+        self.link(node, left, right);
+
+        return node;
+    }
+}
+
+/// The cheapest `(axis, position, cost)` split of `run` into centroids
+/// below and at or above `position`, with cost in units of item tests.
+fn best_sah_split(
+    bounds: &[Aabb],
+    run: &[usize],
+    centroids: &Aabb,
+    area: f32,
+) -> Option<(usize, f32, f32)> {
+    let mut best: Option<(usize, f32, f32)> = None;
+
+    for split_axis in 0..3 {
+        let lo = axis(&centroids.min, split_axis);
+        let extent = axis(&centroids.max, split_axis) - lo;
+        if extent <= 0.0 {
+            continue;
+        }
+
+        let mut bins = [(Aabb::empty(), 0usize); SAH_BINS];
+        for &i in run {
+            let c = axis(&bounds[i].center(), split_axis);
+            let bin = (((c - lo) / extent * SAH_BINS as f32) as usize).min(SAH_BINS - 1);
+            bins[bin].0 = bins[bin].0.union(&bounds[i]);
+            bins[bin].1 += 1;
+        }
+
+        // Sweep from the right to know the cost of every right side.
+        let mut right_cost = [0.0f32; SAH_BINS];
+        let (mut right_box, mut right_count) = (Aabb::empty(), 0);
+        for bin in (1..SAH_BINS).rev() {
+            right_box = right_box.union(&bins[bin].0);
+            right_count += bins[bin].1;
+            right_cost[bin] = right_box.surface_area() * right_count as f32;
+        }
+
+        let (mut left_box, mut left_count) = (Aabb::empty(), 0);
+        for bin in 1..SAH_BINS {
+            left_box = left_box.union(&bins[bin - 1].0);
+            left_count += bins[bin - 1].1;
+            if left_count == 0 || left_count == run.len() {
+                continue;
+            }
+
+            let cost = TRAVERSAL_COST
+                + (left_box.surface_area() * left_count as f32 + right_cost[bin])
+                    / area.max(f32::MIN_POSITIVE);
+            if best.is_none_or(|(_, _, c)| cost < c) {
+                best = Some((split_axis, lo + extent * bin as f32 / SAH_BINS as f32, cost));
+            }
+        }
+    }
+
+    return best;
+}
+
+/// Moves the items matching `pred` to the front; returns how many there are.
+fn partition(items: &mut [usize], pred: impl Fn(&usize) -> bool) -> usize {
+    let mut mid = 0;
+    for i in 0..items.len() {
+        if pred(&items[i]) {
+            items.swap(i, mid);
+            mid += 1;
+        }
+    }
+
+    return mid;
+}
+
+/// Interleaves the low 10 bits of each coordinate.
+fn morton(x: u32, y: u32, z: u32) -> u32 {
+    fn spread(mut v: u32) -> u32 {
+        v &= 0x3ff;
+        v = (v | (v << 16)) & 0x0300_00ff;
+        v = (v | (v << 8)) & 0x0300_f00f;
+        v = (v | (v << 4)) & 0x030c_30c3;
+        v = (v | (v << 2)) & 0x0924_9249;
+        v
+    }
+
+    return (spread(x) << 2) | (spread(y) << 1) | spread(z);
+}
+
+/// A node waiting in the nearest-point queue, closest first.
+#[derive(PartialEq)]
+struct Candidate {
+    distance: f32,
+    node: usize,
+}
+
+impl Eq for Candidate {}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.distance.total_cmp(&self.distance)
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::{math::Matrix4x4F32, spatial::Plane};
+
+    fn random_boxes(rng: &mut StdRng, n: usize) -> Vec<Aabb> {
+        (0..n)
+            .map(|_| {
+                let min = Vector3D::create(
+                    rng.gen_range(-50.0..50.0),
+                    rng.gen_range(-50.0..50.0),
+                    rng.gen_range(-50.0..50.0),
+                );
+                let size = Vector3D::create(
+                    rng.gen_range(0.0..4.0),
+                    rng.gen_range(0.0..4.0),
+                    rng.gen_range(0.0..4.0),
+                );
+                Aabb::new(min, min + size)
+            })
+            .collect()
+    }
+
+    fn sorted(mut v: Vec<usize>) -> Vec<usize> {
+        v.sort();
+        v
+    }
+
+    /// Checks every query of `bvh` against brute force over `boxes`.
+    fn check(bvh: &Bvh, boxes: &[Aabb], rng: &mut StdRng) {
+        assert_eq!(bvh.len(), boxes.len());
+        assert_eq!(
+            bvh.bounds(),
+            boxes.iter().fold(Aabb::empty(), |b, x| b.union(x))
+        );
+
+        for _ in 0..50 {
+            let query = random_boxes(rng, 1)[0];
+            let query = Aabb::new(query.min, query.max + Vector3D::create(10.0, 10.0, 10.0));
+            let expected: Vec<usize> = (0..boxes.len())
+                .filter(|&i| boxes[i].intersects(&query))
+                .collect();
+            assert_eq!(sorted(bvh.query_aabb(&query)), expected);
+
+            let origin = Vector3D::create(
+                rng.gen_range(-80.0..80.0),
+                rng.gen_range(-80.0..80.0),
+                -80.0,
+            );
+            let ray = Ray::new(
+                origin,
+                Vector3D::create(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), 1.0),
+            );
+            let expected = boxes
+                .iter()
+                .filter_map(|b| b.ray_hit(&ray, 0.0, f32::INFINITY))
+                .min_by(f32::total_cmp);
+            let hit = bvh.raycast(&ray, 0.0, f32::INFINITY, |i, t_max| {
+                boxes[i].ray_hit(&ray, 0.0, t_max)
+            });
+            assert_eq!(hit.map(|(_, t)| t), expected);
+
+            let point = Vector3D::create(
+                rng.gen_range(-60.0..60.0),
+                rng.gen_range(-60.0..60.0),
+                rng.gen_range(-60.0..60.0),
+            );
+            let expected = boxes
+                .iter()
+                .map(|b| b.distance_squared(point))
+                .min_by(f32::total_cmp);
+            let nearest = bvh.nearest(point, |i| boxes[i].distance_squared(point));
+            assert_eq!(nearest.map(|(_, d)| d), expected);
+        }
+    }
+
+    #[test]
+    fn sah_test() {
+        let mut rng = StdRng::seed_from_u64(36);
+        let boxes = random_boxes(&mut rng, 1000);
+        let bvh = Bvh::sah(&boxes);
+
+        check(&bvh, &boxes, &mut rng);
+        assert!(bvh.depth() < 30);
+    }
+
+    #[test]
+    fn lbvh_test() {
+        let mut rng = StdRng::seed_from_u64(360);
+        let boxes = random_boxes(&mut rng, 1000);
+        let bvh = Bvh::lbvh(&boxes);
+
+        check(&bvh, &boxes, &mut rng);
+        assert!(bvh.depth() < 30);
+    }
+
+    #[test]
+    fn degenerate_test() {
+        // Identical boxes must not recurse forever.
+        let same = vec![Aabb::new(Vector3D::default(), Vector3D::create(1.0, 1.0, 1.0)); 100];
+
+        for bvh in [Bvh::sah(&same), Bvh::lbvh(&same)] {
+            assert_eq!(bvh.query_aabb(&same[0]).len(), 100);
+            assert!(bvh.depth() <= 7);
+        }
+
+        let empty = Bvh::sah(&[]);
+        assert!(empty.is_empty());
+        assert!(empty.bounds().is_empty());
+        assert_eq!(empty.depth(), 0);
+        assert!(empty.query_aabb(&same[0]).is_empty());
+        assert!(empty.nearest(Vector3D::default(), |_| 0.0).is_none());
+    }
+
+    #[test]
+    fn refit_test() {
+        let mut rng = StdRng::seed_from_u64(3600);
+        let mut boxes = random_boxes(&mut rng, 300);
+        let mut bvh = Bvh::sah(&boxes);
+
+        let offset = Vector3D::create(5.0, -3.0, 1.0);
+        for (i, b) in boxes.iter_mut().enumerate() {
+            if i % 2 == 0 {
+                *b = Aabb::new(b.min + offset, b.max + offset);
+            }
+        }
+        bvh.refit(&boxes);
+
+        check(&bvh, &boxes, &mut rng);
+    }
+
+    #[test]
+    fn frustum_test() {
+        let mut rng = StdRng::seed_from_u64(36000);
+        let boxes = random_boxes(&mut rng, 500);
+        let bvh = Bvh::lbvh(&boxes);
+
+        // A slab between two planes, tilted so nodes straddle it.
+        let n = Vector3D::create(1.0, 1.0, 0.0).normalize();
+        let m = Vector3D::create(1.0, -1.0, 0.0).normalize();
+        let z = Vector3D::create(0.0, 0.0, 1.0);
+        let frustum = Frustum::new([
+            Plane::new(n, 10.0),
+            Plane::new(-n, 10.0),
+            Plane::new(m, 1000.0),
+            Plane::new(-m, 1000.0),
+            Plane::new(z, 1000.0),
+            Plane::new(-z, 1000.0),
+        ]);
+
+        let expected: Vec<usize> = (0..boxes.len())
+            .filter(|&i| frustum.intersects_aabb(&boxes[i]))
+            .collect();
+        assert!(!expected.is_empty() && expected.len() < boxes.len());
+        assert_eq!(sorted(bvh.query_frustum(&frustum)), expected);
+
+        // A camera inside the cloud sees part of it; the hierarchical and
+        // batched culls agree.
+        let view = Matrix4x4F32::look_at(
+            &Vector3D::create(0.0, 0.0, 20.0),
+            &Vector3D::create(10.0, 5.0, -20.0),
+            &Vector3D::create(0.0, 1.0, 0.0),
+        );
+        let frustum =
+            Frustum::from_matrix(&(Matrix4x4F32::perspective(1.0, 1.5, 0.5, 60.0) * view));
+        let (mut visible, mut expected) = (vec![1], vec![]);
+        bvh.cull(&frustum, &mut visible);
+        frustum.cull_aabbs(&boxes, &mut expected);
+        assert!(!expected.is_empty() && expected.len() < boxes.len());
+        assert_eq!(sorted(visible), expected);
+    }
+
+    #[test]
+    fn morton_test() {
+        assert_eq!(morton(1, 0, 0), 0b100);
+        assert_eq!(morton(0, 1, 0), 0b010);
+        assert_eq!(morton(0, 0, 1), 0b001);
+        assert_eq!(morton(1023, 1023, 1023), (1 << 30) - 1);
+    }
+}
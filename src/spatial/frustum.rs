@@ -0,0 +1,312 @@
+use crate::math::{Matrix4x4F32, Vector3D};
+
+use super::Aabb;
+
+/// The plane `normal · p + distance = 0`. Points with a positive signed
+/// distance are in front of it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Plane {
+    pub normal: Vector3D,
+    pub distance: f32,
+}
+
+impl Plane {
+    pub fn new(normal: Vector3D, distance: f32) -> Self {
+        Self { normal, distance }
+    }
+
+    /// The plane through `point` facing along `normal`.
+    pub fn from_point(normal: Vector3D, point: Vector3D) -> Self {
+        Self::new(normal, -normal.dot(&point))
+    }
+
+    /// Scales the equation so the normal has unit length, which makes
+    /// [`signed_distance`](Self::signed_distance) a true distance.
+    pub fn normalize(&self) -> Plane {
+        let length = self.normal.magnitude();
+
+        return Plane::new(self.normal / length, self.distance / length);
+    }
+
+    pub fn signed_distance(&self, p: Vector3D) -> f32 {
+        self.normal.dot(&p) + self.distance
+    }
+}
+
+/// How a volume relates to a [`Frustum`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Intersection {
+    Outside,
+    Intersecting,
+    Inside,
+}
+
+/// A set of frustum planes, one bit per index into [`Frustum::planes`].
+pub type PlaneMask = u8;
+
+/// Every plane of a frustum.
+pub const ALL_PLANES: PlaneMask = 0b11_1111;
+
+/// A convex volume bounded by six inward-facing planes.
+///
+/// Overlap tests are conservative: a box near a corner of the frustum may
+/// be reported as intersecting although it lies just outside.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frustum {
+    pub planes: [Plane; 6],
+}
+
+impl Frustum {
+    pub fn new(planes: [Plane; 6]) -> Self {
+        Self { planes }
+    }
+
+    /// The volume a view-projection matrix maps into clip space, with
+    /// planes ordered left, right, bottom, top, near, far and normalized.
+    ///
+    /// Uses the Gribb–Hartmann extraction: a point is inside when
+    /// `-w <= x, y, z <= w` in clip space, so each plane is the last row of
+    /// the matrix plus or minus one of the others.
+    pub fn from_matrix(m: &Matrix4x4F32) -> Self {
+        let w = m.row(3);
+        let plane = |row: usize, sign: f32| {
+            let r = m.row(row);
+            let normal =
+                Vector3D::create(w[0] + sign * r[0], w[1] + sign * r[1], w[2] + sign * r[2]);
+            return Plane::new(normal, w[3] + sign * r[3]).normalize();
+        };
+
+        return Self::new([
+            plane(0, 1.0),
+            plane(0, -1.0),
+            plane(1, 1.0),
+            plane(1, -1.0),
+            plane(2, 1.0),
+            plane(2, -1.0),
+        ]);
+    }
+
+    pub fn contains_point(&self, p: Vector3D) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.signed_distance(p) >= 0.0)
+    }
+
+    pub fn intersects_sphere(&self, center: Vector3D, radius: f32) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.signed_distance(center) >= -radius * plane.normal.magnitude())
+    }
+
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        self.classify_aabb(aabb) != Intersection::Outside
+    }
+
+    /// Tests the corners of the box nearest to and farthest from each
+    /// plane.
+    pub fn classify_aabb(&self, aabb: &Aabb) -> Intersection {
+        return self.classify_aabb_masked(aabb, ALL_PLANES).0;
+    }
+
+    /// Like [`classify_aabb`](Self::classify_aabb), testing only the planes
+    /// in `mask`. Also returns the planes the box still straddles, which is
+    /// all a box inside it needs testing against: once a parent volume is
+    /// entirely in front of a plane, so are its children.
+    pub fn classify_aabb_masked(&self, aabb: &Aabb, mask: PlaneMask) -> (Intersection, PlaneMask) {
+        let mut straddled = 0;
+
+        for (i, plane) in self.planes.iter().enumerate() {
+            if mask & (1 << i) == 0 {
+                continue;
+            }
+
+            let n = plane.normal;
+            let farthest = Vector3D::create(
+                if n.x() >= 0.0 {
+                    aabb.max.x()
+                } else {
+                    aabb.min.x()
+                },
+                if n.y() >= 0.0 {
+                    aabb.max.y()
+                } else {
+                    aabb.min.y()
+                },
+                if n.z() >= 0.0 {
+                    aabb.max.z()
+                } else {
+                    aabb.min.z()
+                },
+            );
+            if plane.signed_distance(farthest) < 0.0 {
+                return (Intersection::Outside, 0);
+            }
+
+            let nearest = aabb.min + aabb.max - farthest;
+            if plane.signed_distance(nearest) < 0.0 {
+                straddled |= 1 << i;
+            }
+        }
+
+        if straddled == 0 {
+            return (Intersection::Inside, 0);
+        }
+
+        return (Intersection::Intersecting, straddled);
+    }
+
+    /// Replaces `visible` with the indices of the boxes that overlap the
+    /// frustum, in order. Reusing `visible` across frames avoids
+    /// reallocating the draw list.
+    pub fn cull_aabbs(&self, boxes: &[Aabb], visible: &mut Vec<usize>) {
+        // A box is outside a plane when its center is farther behind it than
+        // the box's half extent projected onto the normal.
+        let planes = self.planes.map(|p| {
+            let n = p.normal;
+            (p, Vector3D::create(n.x().abs(), n.y().abs(), n.z().abs()))
+        });
+
+        visible.clear();
+        visible.extend(boxes.iter().enumerate().filter_map(|(i, aabb)| {
+            let center = aabb.center();
+            let half = aabb.extent() * 0.5;
+            let inside = planes
+                .iter()
+                .all(|(plane, abs)| plane.signed_distance(center) >= -abs.dot(&half));
+
+            return inside.then_some(i);
+        }));
+    }
+
+    /// Replaces `visible` with the indices of the `(center, radius)`
+    /// spheres that overlap the frustum, in order.
+    pub fn cull_spheres(&self, spheres: &[(Vector3D, f32)], visible: &mut Vec<usize>) {
+        let planes = self.planes.map(|p| (p, p.normal.magnitude()));
+
+        visible.clear();
+        visible.extend(
+            spheres
+                .iter()
+                .enumerate()
+                .filter_map(|(i, &(center, radius))| {
+                    let inside = planes
+                        .iter()
+                        .all(|(plane, length)| plane.signed_distance(center) >= -radius * length);
+
+                    return inside.then_some(i);
+                }),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+
+    /// The box `[-1, 1]^3` as a frustum.
+    fn cube() -> Frustum {
+        let plane = |x, y, z| Plane::new(Vector3D::create(x, y, z), 1.0);
+
+        Frustum::new([
+            plane(1.0, 0.0, 0.0),
+            plane(-1.0, 0.0, 0.0),
+            plane(0.0, 1.0, 0.0),
+            plane(0.0, -1.0, 0.0),
+            plane(0.0, 0.0, 1.0),
+            plane(0.0, 0.0, -1.0),
+        ])
+    }
+
+    #[test]
+    fn plane_test() {
+        let plane = Plane::from_point(
+            Vector3D::create(0.0, 2.0, 0.0),
+            Vector3D::create(0.0, 1.0, 0.0),
+        )
+        .normalize();
+
+        assert_eq!(plane.distance, -1.0);
+        assert_eq!(plane.signed_distance(Vector3D::create(5.0, 3.0, 0.0)), 2.0);
+    }
+
+    #[test]
+    fn classify_test() {
+        let frustum = cube();
+        let aabb = |min: f32, max: f32| {
+            Aabb::new(
+                Vector3D::create(min, min, min),
+                Vector3D::create(max, max, max),
+            )
+        };
+
+        assert_eq!(
+            frustum.classify_aabb(&aabb(-0.5, 0.5)),
+            Intersection::Inside
+        );
+        assert_eq!(
+            frustum.classify_aabb(&aabb(0.5, 1.5)),
+            Intersection::Intersecting
+        );
+        assert_eq!(
+            frustum.classify_aabb(&aabb(1.5, 2.5)),
+            Intersection::Outside
+        );
+
+        assert!(frustum.contains_point(Vector3D::create(1.0, 0.0, 0.0)));
+        assert!(!frustum.contains_point(Vector3D::create(1.1, 0.0, 0.0)));
+        assert!(frustum.intersects_sphere(Vector3D::create(1.5, 0.0, 0.0), 0.6));
+        assert!(!frustum.intersects_sphere(Vector3D::create(1.5, 0.0, 0.0), 0.4));
+    }
+
+    #[test]
+    fn from_matrix_test() {
+        // The identity maps the clip cube onto itself.
+        let frustum = Frustum::from_matrix(&Matrix4x4F32::identity());
+        assert_eq!(frustum, cube());
+
+        let proj = Matrix4x4F32::perspective(FRAC_PI_2, 1.0, 1.0, 10.0);
+        let frustum = Frustum::from_matrix(&proj);
+        let near = frustum.planes[4];
+        assert_eq!(near.normal, Vector3D::create(0.0, 0.0, -1.0));
+        assert!((near.distance + 1.0).abs() < 1e-5);
+        assert!((frustum.planes[5].distance - 10.0).abs() < 1e-4);
+
+        assert!(frustum.contains_point(Vector3D::create(0.0, 0.0, -5.0)));
+        assert!(frustum.contains_point(Vector3D::create(4.9, -4.9, -5.0)));
+        assert!(!frustum.contains_point(Vector3D::create(5.1, 0.0, -5.0)));
+        assert!(!frustum.contains_point(Vector3D::create(0.0, 0.0, -0.5)));
+        assert!(!frustum.contains_point(Vector3D::create(0.0, 0.0, -11.0)));
+    }
+
+    #[test]
+    fn cull_test() {
+        let frustum = cube();
+        let at = |x: f32| Vector3D::create(x, 0.0, 0.0);
+        let unit = Vector3D::create(0.5, 0.5, 0.5);
+
+        let boxes: Vec<Aabb> = [0.0, 1.4, 1.6, -3.0]
+            .iter()
+            .map(|&x| Aabb::new(at(x) - unit, at(x) + unit))
+            .collect();
+        let mut visible = vec![7];
+        frustum.cull_aabbs(&boxes, &mut visible);
+        assert_eq!(visible, [0, 1]);
+        for (i, aabb) in boxes.iter().enumerate() {
+            assert_eq!(visible.contains(&i), frustum.intersects_aabb(aabb));
+        }
+
+        let spheres = [(at(1.5), 0.6), (at(1.5), 0.4), (at(-0.5), 0.1)];
+        frustum.cull_spheres(&spheres, &mut visible);
+        assert_eq!(visible, [0, 2]);
+
+        let (result, mask) = frustum.classify_aabb_masked(&boxes[1], ALL_PLANES);
+        assert_eq!(result, Intersection::Intersecting);
+        assert_eq!(mask, 0b10);
+        assert_eq!(
+            frustum.classify_aabb_masked(&boxes[2], 0b01),
+            (Intersection::Inside, 0)
+        );
+    }
+}
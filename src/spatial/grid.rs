@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+
+use super::{
+    index::{KBest, Slab},
+    point::{in_box, sphere_box},
+    Handle, Point, SpatialIndex,
+};
+
+/// Buckets items into cubic cells of a fixed size, stored sparsely.
+///
+/// Best when items are spread evenly and queries are about the size of a
+/// cell, as with particles or crowds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UniformGrid<P, T> {
+    cell_size: f32,
+    cells: HashMap<[i32; 3], Vec<Handle>>,
+    items: Slab<P, T>,
+}
+
+impl<P: Point, T> UniformGrid<P, T> {
+    /// Panics unless `cell_size` is positive.
+    pub fn new(cell_size: f32) -> Self {
+        assert!(
+            cell_size > 0.0,
+            "cell size must be positive, got {cell_size}"
+        );
+
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+            items: Slab::default(),
+        }
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// Occupied cells.
+    pub fn cell_count(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Handle, &P, &T)> {
+        self.items.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.items.clear();
+    }
+
+    fn cell_of(&self, p: &P) -> [i32; 3] {
+        let mut cell = [0; 3];
+        for (i, c) in cell.iter_mut().enumerate().take(P::DIM) {
+            *c = (p.coord(i) / self.cell_size).floor() as i32;
+        }
+
+        return cell;
+    }
+
+    fn unlink(&mut self, handle: Handle, cell: [i32; 3]) {
+        let bucket = self.cells.get_mut(&cell).expect("item is in its cell");
+        let i = bucket
+            .iter()
+            .position(|&h| h == handle)
+            .expect("item is in its cell");
+        bucket.swap_remove(i);
+
+        if bucket.is_empty() {
+            self.cells.remove(&cell);
+        }
+    }
+}
+
+impl<P: Point, T> SpatialIndex<P, T> for UniformGrid<P, T> {
+    fn insert(&mut self, position: P, value: T) -> Handle {
+        let handle = self.items.insert(position, value);
+        self.cells
+            .entry(self.cell_of(&position))
+            .or_default()
+            .push(handle);
+
+        return handle;
+    }
+
+    fn remove(&mut self, handle: Handle) -> Option<(P, T)> {
+        let (position, value) = self.items.remove(handle)?;
+        self.unlink(handle, self.cell_of(&position));
+
+        return Some((position, value));
+    }
+
+    fn move_to(&mut self, handle: Handle, position: P) -> bool {
+        let Some(&(old, _)) = self.items.get(handle) else {
+            return false;
+        };
+
+        let (from, to) = (self.cell_of(&old), self.cell_of(&position));
+        if from != to {
+            self.unlink(handle, from);
+            self.cells.entry(to).or_default().push(handle);
+        }
+        self.items.get_mut(handle).unwrap().0 = position;
+
+        return true;
+    }
+
+    fn get(&self, handle: Handle) -> Option<(&P, &T)> {
+        self.items.get(handle).map(|(p, t)| (p, t))
+    }
+
+    fn get_mut(&mut self, handle: Handle) -> Option<&mut T> {
+        self.items.get_mut(handle).map(|(_, t)| t)
+    }
+
+    fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    fn range(&self, min: &P, max: &P) -> Vec<Handle> {
+        let (lo, hi) = (self.cell_of(min), self.cell_of(max));
+        let cells: f64 = (0..P::DIM)
+            .map(|i| (hi[i] as f64 - lo[i] as f64 + 1.0).max(0.0))
+            .product();
+
+        // Scanning every item is cheaper than visiting mostly empty cells.
+        if cells > self.cells.len() as f64 {
+            return self
+                .items
+                .iter()
+                .filter(|(_, p, _)| in_box(*p, min, max))
+                .map(|(h, _, _)| h)
+                .collect();
+        }
+
+        let mut found = Vec::new();
+        let mut cell = lo;
+        loop {
+            if let Some(bucket) = self.cells.get(&cell) {
+                found.extend(
+                    bucket
+                        .iter()
+                        .filter(|&&h| in_box(&self.items.position(h), min, max)),
+                );
+            }
+
+            // Odometer over the cell range.
+            let mut axis = 0;
+            loop {
+                if axis == P::DIM {
+                    return found;
+                }
+                if cell[axis] < hi[axis] {
+                    cell[axis] += 1;
+                    break;
+                }
+                cell[axis] = lo[axis];
+                axis += 1;
+            }
+        }
+    }
+
+    fn within_radius(&self, center: &P, radius: f32) -> Vec<Handle> {
+        let (min, max) = sphere_box(center, radius);
+        let mut found = self.range(&min, &max);
+        found.retain(|&h| self.items.position(h).distance_squared(center) <= radius * radius);
+
+        return found;
+    }
+
+    fn k_nearest(&self, point: &P, k: usize) -> Vec<Handle> {
+        // Search ever larger spheres; once one holds k items, the k
+        // nearest are all inside it.
+        let mut radius = self.cell_size;
+        loop {
+            let candidates = if k >= self.len() {
+                self.items.iter().map(|(h, _, _)| h).collect()
+            } else {
+                self.within_radius(point, radius)
+            };
+
+            if candidates.len() >= k.min(self.len()) {
+                let mut best = KBest::new(k);
+                for h in candidates {
+                    best.offer(self.items.position(h).distance_squared(point), h);
+                }
+                return best.into_sorted();
+            }
+
+            radius *= 2.0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::{
+        math::{Vector2D, Vector3D},
+        spatial::index::conformance,
+    };
+
+    #[test]
+    fn conformance_test() {
+        conformance::check(UniformGrid::new(5.0), 37, |rng| {
+            Vector2D::create(rng.gen_range(-50.0..50.0), rng.gen_range(-50.0..50.0))
+        });
+        conformance::check(UniformGrid::new(0.5), 370, |rng| {
+            Vector3D::create(
+                rng.gen_range(-20.0..20.0),
+                rng.gen_range(-20.0..20.0),
+                rng.gen_range(-20.0..20.0),
+            )
+        });
+    }
+
+    #[test]
+    fn cells_test() {
+        let mut grid = UniformGrid::new(1.0);
+        let a = grid.insert(Vector2D::create(0.5, 0.5), 'a');
+        grid.insert(Vector2D::create(-0.5, 0.5), 'b');
+        assert_eq!(grid.cell_count(), 2);
+
+        // Moving within a cell keeps it; moving out empties it.
+        grid.move_to(a, Vector2D::create(0.9, 0.1));
+        assert_eq!(grid.cell_count(), 2);
+        grid.move_to(a, Vector2D::create(-0.9, 0.1));
+        assert_eq!(grid.cell_count(), 1);
+
+        assert_eq!(grid.nearest(&Vector2D::create(-100.0, 0.0)), Some(a));
+        grid.clear();
+        assert!(grid.is_empty());
+        assert_eq!(grid.nearest(&Vector2D::default()), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "cell size must be positive")]
+    fn zero_cell_size_test() {
+        UniformGrid::<Vector2D, ()>::new(0.0);
+    }
+}
@@ -0,0 +1,283 @@
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+/// Identifies an item in one of the spatial containers.
+///
+/// Handles are indices into the container's storage and are reused after
+/// the item is removed, so a stale handle may refer to a newer item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Handle(usize);
+
+impl Handle {
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+/// Item storage shared by the containers: positions and values addressed
+/// by [`Handle`], with freed slots reused.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Slab<P, T> {
+    entries: Vec<Option<(P, T)>>,
+    free: Vec<usize>,
+    len: usize,
+}
+
+impl<P: Copy, T> Default for Slab<P, T> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            free: Vec::new(),
+            len: 0,
+        }
+    }
+}
+
+impl<P: Copy, T> Slab<P, T> {
+    pub(crate) fn insert(&mut self, position: P, value: T) -> Handle {
+        self.len += 1;
+
+        if let Some(i) = self.free.pop() {
+            self.entries[i] = Some((position, value));
+            return Handle(i);
+        }
+
+        self.entries.push(Some((position, value)));
+        return Handle(self.entries.len() - 1);
+    }
+
+    pub(crate) fn remove(&mut self, handle: Handle) -> Option<(P, T)> {
+        let entry = self.entries.get_mut(handle.0)?.take()?;
+        self.free.push(handle.0);
+        self.len -= 1;
+
+        return Some(entry);
+    }
+
+    pub(crate) fn get(&self, handle: Handle) -> Option<&(P, T)> {
+        self.entries.get(handle.0)?.as_ref()
+    }
+
+    pub(crate) fn get_mut(&mut self, handle: Handle) -> Option<&mut (P, T)> {
+        self.entries.get_mut(handle.0)?.as_mut()
+    }
+
+    /// Panics if `handle` is vacant; containers only keep live handles.
+    pub(crate) fn position(&self, handle: Handle) -> P {
+        self.entries[handle.0].as_ref().expect("live handle").0
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (Handle, &P, &T)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, e)| e.as_ref().map(|(p, t)| (Handle(i), p, t)))
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+        self.free.clear();
+        self.len = 0;
+    }
+}
+
+/// Operations shared by [`UniformGrid`](super::UniformGrid),
+/// [`Quadtree`](super::Quadtree), [`Octree`](super::Octree) and
+/// [`KdTree`](super::KdTree).
+pub trait SpatialIndex<P, T> {
+    fn insert(&mut self, position: P, value: T) -> Handle;
+
+    fn remove(&mut self, handle: Handle) -> Option<(P, T)>;
+
+    /// Moves an item; returns `false` if the handle is vacant.
+    fn move_to(&mut self, handle: Handle, position: P) -> bool;
+
+    fn get(&self, handle: Handle) -> Option<(&P, &T)>;
+
+    fn get_mut(&mut self, handle: Handle) -> Option<&mut T>;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Items inside the box `[min, max]`, boundaries included, in no
+    /// particular order.
+    fn range(&self, min: &P, max: &P) -> Vec<Handle>;
+
+    /// Items no farther than `radius` from `center`, in no particular
+    /// order.
+    fn within_radius(&self, center: &P, radius: f32) -> Vec<Handle>;
+
+    /// Up to `k` items nearest to `point`, nearest first.
+    fn k_nearest(&self, point: &P, k: usize) -> Vec<Handle>;
+
+    fn nearest(&self, point: &P) -> Option<Handle> {
+        self.k_nearest(point, 1).first().copied()
+    }
+}
+
+/// The `k` closest candidates seen so far, by squared distance.
+pub(crate) struct KBest {
+    k: usize,
+    heap: BinaryHeap<(Distance, Handle)>,
+}
+
+impl KBest {
+    pub(crate) fn new(k: usize) -> Self {
+        Self {
+            k,
+            heap: BinaryHeap::with_capacity(k + 1),
+        }
+    }
+
+    pub(crate) fn offer(&mut self, distance_squared: f32, handle: Handle) {
+        if distance_squared >= self.worst() {
+            return;
+        }
+
+        self.heap.push((Distance(distance_squared), handle));
+        if self.heap.len() > self.k {
+            self.heap.pop();
+        }
+    }
+
+    /// The squared distance a candidate must beat; infinite until `k`
+    /// candidates have been seen and minus infinity if `k` is 0.
+    pub(crate) fn worst(&self) -> f32 {
+        if self.k == 0 {
+            return f32::NEG_INFINITY;
+        }
+        if self.heap.len() < self.k {
+            return f32::INFINITY;
+        }
+
+        return self.heap.peek().map_or(f32::INFINITY, |(d, _)| d.0);
+    }
+
+    /// Nearest first.
+    pub(crate) fn into_sorted(self) -> Vec<Handle> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|(_, h)| h)
+            .collect()
+    }
+}
+
+/// A totally ordered `f32` for heaps.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Distance(pub(crate) f32);
+
+impl Eq for Distance {}
+
+impl Ord for Distance {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl PartialOrd for Distance {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Checks a container against brute force over the same operations.
+#[cfg(test)]
+pub(crate) mod conformance {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::spatial::{point::in_box, Point};
+
+    pub(crate) fn check<P: Point + std::fmt::Debug, I: SpatialIndex<P, usize>>(
+        mut index: I,
+        seed: u64,
+        point: impl Fn(&mut StdRng) -> P,
+    ) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut model: Vec<Option<(Handle, P)>> = Vec::new();
+
+        for i in 0..600 {
+            let p = point(&mut rng);
+            let handle = index.insert(p, i);
+            assert_eq!(index.get(handle), Some((&p, &i)));
+            model.push(Some((handle, p)));
+        }
+        for i in (0..600).step_by(3) {
+            let (handle, p) = model[i].take().unwrap();
+            assert_eq!(index.remove(handle), Some((p, i)));
+            assert_eq!(index.remove(handle), None);
+        }
+        for i in (1..600).step_by(3) {
+            let (handle, _) = model[i].unwrap();
+            let p = point(&mut rng);
+            assert!(index.move_to(handle, p));
+            model[i] = Some((handle, p));
+        }
+        // Freed handles are reused.
+        for i in (0..600).step_by(6) {
+            let p = point(&mut rng);
+            let handle = index.insert(p, i);
+            model[i] = Some((handle, p));
+        }
+        *index.get_mut(model[1].unwrap().0).unwrap() = 1;
+
+        let live: Vec<(Handle, P)> = model.iter().flatten().copied().collect();
+        assert_eq!(index.len(), live.len());
+
+        for _ in 0..100 {
+            let (a, b) = (point(&mut rng), point(&mut rng));
+            let coords = |f: fn(f32, f32) -> f32| {
+                (0..P::DIM)
+                    .map(|i| f(a.coord(i), b.coord(i)))
+                    .collect::<Vec<_>>()
+            };
+            let (min, max) = (
+                P::from_coords(&coords(f32::min)),
+                P::from_coords(&coords(f32::max)),
+            );
+
+            let mut found = index.range(&min, &max);
+            found.sort();
+            let mut expected: Vec<Handle> = live
+                .iter()
+                .filter(|(_, p)| in_box(p, &min, &max))
+                .map(|(h, _)| *h)
+                .collect();
+            expected.sort();
+            assert_eq!(found, expected);
+
+            let radius = rng.gen_range(0.0..30.0);
+            let mut found = index.within_radius(&a, radius);
+            found.sort();
+            let mut expected: Vec<Handle> = live
+                .iter()
+                .filter(|(_, p)| p.distance_squared(&a) <= radius * radius)
+                .map(|(h, _)| *h)
+                .collect();
+            expected.sort();
+            assert_eq!(found, expected);
+
+            let k = rng.gen_range(0..12);
+            let distances = |handles: &[Handle]| -> Vec<f32> {
+                handles
+                    .iter()
+                    .map(|&h| index.get(h).unwrap().0.distance_squared(&a))
+                    .collect()
+            };
+            let mut expected: Vec<f32> = live.iter().map(|(_, p)| p.distance_squared(&a)).collect();
+            expected.sort_by(f32::total_cmp);
+            expected.truncate(k);
+            assert_eq!(distances(&index.k_nearest(&a, k)), expected);
+        }
+
+        let all = index.k_nearest(&point(&mut rng), 10_000);
+        assert_eq!(all.len(), live.len());
+    }
+}
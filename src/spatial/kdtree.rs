@@ -0,0 +1,328 @@
+use super::{
+    index::{KBest, Slab},
+    point::{in_box, sphere_box},
+    Handle, Point, SpatialIndex,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Node<P> {
+    handle: Handle,
+    /// The item's position when the node was made, which stays the
+    /// splitting plane even after the item moves or is removed.
+    point: P,
+    axis: usize,
+    /// Removed items stay in the tree as splitting planes until the next
+    /// rebuild.
+    live: bool,
+    left: Option<usize>,
+    right: Option<usize>,
+}
+
+/// A k-d tree with one item per node, splitting on each axis in turn.
+///
+/// Insertions descend to a leaf; removals leave a dead node behind. The
+/// tree rebuilds itself balanced once dead nodes outnumber live ones or a
+/// branch grows much deeper than a balanced tree would be. Best for
+/// nearest-neighbour queries on mostly static data.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KdTree<P, T> {
+    nodes: Vec<Node<P>>,
+    root: Option<usize>,
+    /// Node of each item, indexed by handle.
+    node_of: Vec<usize>,
+    dead: usize,
+    items: Slab<P, T>,
+}
+
+impl<P: Point, T> Default for KdTree<P, T> {
+    fn default() -> Self {
+        Self {
+            nodes: Vec::new(),
+            root: None,
+            node_of: Vec::new(),
+            dead: 0,
+            items: Slab::default(),
+        }
+    }
+}
+
+impl<P: Point, T> KdTree<P, T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds a balanced tree; handles are assigned in iteration order.
+    pub fn from_items(items: impl IntoIterator<Item = (P, T)>) -> Self {
+        let mut tree = Self::new();
+        for (p, t) in items {
+            tree.items.insert(p, t);
+        }
+        tree.rebuild();
+
+        return tree;
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Handle, &P, &T)> {
+        self.items.iter()
+    }
+
+    /// Levels from the root to the deepest node; 0 when empty.
+    pub fn depth(&self) -> usize {
+        fn depth<P>(nodes: &[Node<P>], node: Option<usize>) -> usize {
+            node.map_or(0, |n| {
+                1 + depth(nodes, nodes[n].left).max(depth(nodes, nodes[n].right))
+            })
+        }
+
+        return depth(&self.nodes, self.root);
+    }
+
+    /// Rebalances the tree and drops dead nodes.
+    pub fn rebuild(&mut self) {
+        let mut handles: Vec<Handle> = self.items.iter().map(|(h, _, _)| h).collect();
+        self.nodes.clear();
+        self.dead = 0;
+        self.node_of
+            .resize(handles.iter().map(|h| h.index() + 1).max().unwrap_or(0), 0);
+        self.root = self.build(&mut handles, 0);
+    }
+
+    fn build(&mut self, handles: &mut [Handle], axis: usize) -> Option<usize> {
+        if handles.is_empty() {
+            return None;
+        }
+
+        let mid = handles.len() / 2;
+        let items = &self.items;
+        handles.select_nth_unstable_by(mid, |a, b| {
+            items
+                .position(*a)
+                .coord(axis)
+                .total_cmp(&items.position(*b).coord(axis))
+        });
+
+        let handle = handles[mid];
+        let node = self.push(handle, axis);
+        let next = (axis + 1) % P::DIM;
+        let (lower, upper) = handles.split_at_mut(mid);
+        self.nodes[node].left = self.build(lower, next);
+        self.nodes[node].right = self.build(&mut upper[1..], next);
+
+        return Some(node);
+    }
+
+    fn push(&mut self, handle: Handle, axis: usize) -> usize {
+        self.nodes.push(Node {
+            handle,
+            point: self.items.position(handle),
+            axis,
+            live: true,
+            left: None,
+            right: None,
+        });
+
+        if self.node_of.len() <= handle.index() {
+            self.node_of.resize(handle.index() + 1, 0);
+        }
+        self.node_of[handle.index()] = self.nodes.len() - 1;
+
+        return self.nodes.len() - 1;
+    }
+
+    /// Adds a node for `handle` below the leaf its position leads to.
+    fn link(&mut self, handle: Handle) {
+        let p = self.items.position(handle);
+        let Some(mut node) = self.root else {
+            self.root = Some(self.push(handle, 0));
+            return;
+        };
+
+        let mut depth = 1;
+        loop {
+            let n = self.nodes[node];
+            let goes_right = p.coord(n.axis) >= n.point.coord(n.axis);
+            let next = if goes_right { n.right } else { n.left };
+            depth += 1;
+
+            match next {
+                Some(child) => node = child,
+                None => {
+                    let child = self.push(handle, (n.axis + 1) % P::DIM);
+                    if goes_right {
+                        self.nodes[node].right = Some(child);
+                    } else {
+                        self.nodes[node].left = Some(child);
+                    }
+                    break;
+                }
+            }
+        }
+
+        let balanced = (self.items.len() as f32).log2().ceil() as usize + 1;
+        if depth > 2 * balanced + 4 {
+            self.rebuild();
+        }
+    }
+
+    fn unlink(&mut self, handle: Handle) {
+        self.nodes[self.node_of[handle.index()]].live = false;
+        self.dead += 1;
+    }
+
+    /// Visits the live items in `[min, max]`.
+    fn visit_range(&self, node: Option<usize>, min: &P, max: &P, found: &mut Vec<Handle>) {
+        let Some(node) = node else {
+            return;
+        };
+
+        let n = &self.nodes[node];
+        let p = n.point;
+        if n.live && in_box(&p, min, max) {
+            found.push(n.handle);
+        }
+
+        let split = p.coord(n.axis);
+        if min.coord(n.axis) < split {
+            self.visit_range(n.left, min, max, found);
+        }
+        if max.coord(n.axis) >= split {
+            self.visit_range(n.right, min, max, found);
+        }
+    }
+
+    fn visit_nearest(&self, node: Option<usize>, point: &P, best: &mut KBest) {
+        let Some(node) = node else {
+            return;
+        };
+
+        let n = &self.nodes[node];
+        let p = n.point;
+        if n.live {
+            best.offer(p.distance_squared(point), n.handle);
+        }
+
+        let difference = point.coord(n.axis) - p.coord(n.axis);
+        let (near, far) = if difference >= 0.0 {
+            (n.right, n.left)
+        } else {
+            (n.left, n.right)
+        };
+        self.visit_nearest(near, point, best);
+        if difference * difference < best.worst() {
+            self.visit_nearest(far, point, best);
+        }
+    }
+}
+
+impl<P: Point, T> SpatialIndex<P, T> for KdTree<P, T> {
+    fn insert(&mut self, position: P, value: T) -> Handle {
+        let handle = self.items.insert(position, value);
+        self.link(handle);
+
+        return handle;
+    }
+
+    fn remove(&mut self, handle: Handle) -> Option<(P, T)> {
+        let entry = self.items.remove(handle)?;
+        self.unlink(handle);
+
+        if self.dead > self.items.len() {
+            self.rebuild();
+        }
+
+        return Some(entry);
+    }
+
+    fn move_to(&mut self, handle: Handle, position: P) -> bool {
+        if self.items.get(handle).is_none() {
+            return false;
+        }
+
+        self.unlink(handle);
+        self.items.get_mut(handle).unwrap().0 = position;
+        self.link(handle);
+
+        if self.dead > self.items.len() {
+            self.rebuild();
+        }
+
+        return true;
+    }
+
+    fn get(&self, handle: Handle) -> Option<(&P, &T)> {
+        self.items.get(handle).map(|(p, t)| (p, t))
+    }
+
+    fn get_mut(&mut self, handle: Handle) -> Option<&mut T> {
+        self.items.get_mut(handle).map(|(_, t)| t)
+    }
+
+    fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    fn range(&self, min: &P, max: &P) -> Vec<Handle> {
+        let mut found = Vec::new();
+        self.visit_range(self.root, min, max, &mut found);
+
+        return found;
+    }
+
+    fn within_radius(&self, center: &P, radius: f32) -> Vec<Handle> {
+        let (min, max) = sphere_box(center, radius);
+        let mut found = self.range(&min, &max);
+        found.retain(|&h| self.items.position(h).distance_squared(center) <= radius * radius);
+
+        return found;
+    }
+
+    fn k_nearest(&self, point: &P, k: usize) -> Vec<Handle> {
+        let mut best = KBest::new(k);
+        self.visit_nearest(self.root, point, &mut best);
+
+        return best.into_sorted();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::{
+        math::{Vector2D, Vector3D},
+        spatial::index::conformance,
+    };
+
+    #[test]
+    fn conformance_test() {
+        conformance::check(KdTree::new(), 39, |rng| {
+            Vector2D::create(rng.gen_range(-50.0..50.0), rng.gen_range(-50.0..50.0))
+        });
+        conformance::check(KdTree::new(), 390, |rng| {
+            Vector3D::create(
+                rng.gen_range(-20.0..20.0),
+                rng.gen_range(-20.0..20.0),
+                rng.gen_range(-20.0..20.0),
+            )
+        });
+    }
+
+    #[test]
+    fn balance_test() {
+        let tree = KdTree::from_items((0..1023).map(|i| (Vector2D::create(i as f32, 0.0), i)));
+        assert_eq!(tree.depth(), 10);
+
+        // Sorted insertions would make a list without rebuilding.
+        let mut tree = KdTree::new();
+        for i in 0..1000 {
+            tree.insert(Vector3D::create(i as f32, i as f32, i as f32), i);
+        }
+        assert!(tree.depth() <= 2 * 11 + 4);
+        assert_eq!(
+            tree.nearest(&Vector3D::create(500.2, 500.0, 500.0))
+                .map(|h| *tree.get(h).unwrap().1),
+            Some(500)
+        );
+    }
+}
@@ -0,0 +1,325 @@
+use crate::math::Vector3D;
+
+use super::{Aabb, Bvh, Ray};
+
+/// Where a ray meets a triangle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TriangleHit {
+    pub t: f32,
+    /// Barycentric weights of the second and third vertex.
+    pub u: f32,
+    pub v: f32,
+}
+
+/// Möller–Trumbore intersection, from either side, with `t` in
+/// `(t_min, t_max)`.
+pub fn ray_triangle(
+    ray: &Ray,
+    [a, b, c]: [Vector3D; 3],
+    t_min: f32,
+    t_max: f32,
+) -> Option<TriangleHit> {
+    let ab = b - a;
+    let ac = c - a;
+    let p = ray.direction.cross(&ac);
+    let det = ab.dot(&p);
+    if det.abs() < 1e-12 {
+        return None;
+    }
+
+    let inv = 1.0 / det;
+    let s = ray.origin - a;
+    let u = s.dot(&p) * inv;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = s.cross(&ab);
+    let v = ray.direction.dot(&q) * inv;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let t = ac.dot(&q) * inv;
+    if t <= t_min || t >= t_max {
+        return None;
+    }
+
+    return Some(TriangleHit { t, u, v });
+}
+
+/// The point of triangle `abc` nearest to `p`, by Voronoi region
+/// (Ericson, Real-Time Collision Detection, 5.1.5).
+pub fn closest_point_on_triangle(p: Vector3D, [a, b, c]: [Vector3D; 3]) -> Vector3D {
+    let ab = b - a;
+    let ac = c - a;
+    let ap = p - a;
+    let d1 = ab.dot(&ap);
+    let d2 = ac.dot(&ap);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return a;
+    }
+
+    let bp = p - b;
+    let d3 = ab.dot(&bp);
+    let d4 = ac.dot(&bp);
+    if d3 >= 0.0 && d4 <= d3 {
+        return b;
+    }
+
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return a + ab * (d1 / (d1 - d3));
+    }
+
+    let cp = p - c;
+    let d5 = ab.dot(&cp);
+    let d6 = ac.dot(&cp);
+    if d6 >= 0.0 && d5 <= d6 {
+        return c;
+    }
+
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return a + ac * (d2 / (d2 - d6));
+    }
+
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && d4 - d3 >= 0.0 && d5 - d6 >= 0.0 {
+        return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+
+    let denom = 1.0 / (va + vb + vc);
+    return a + ab * (vb * denom) + ac * (vc * denom);
+}
+
+/// A hit reported by [`MeshBvh::raycast`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MeshHit {
+    pub triangle: usize,
+    pub point: Vector3D,
+    pub hit: TriangleHit,
+}
+
+/// An indexed triangle mesh with a [`Bvh`] over its triangles, for
+/// raycasts and nearest-point queries against level geometry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeshBvh {
+    positions: Vec<Vector3D>,
+    triangles: Vec<[usize; 3]>,
+    bvh: Bvh,
+}
+
+impl MeshBvh {
+    /// Builds with the SAH; panics if an index is out of range.
+    pub fn new(positions: Vec<Vector3D>, triangles: Vec<[usize; 3]>) -> Self {
+        let bounds = triangle_bounds(&positions, &triangles);
+
+        Self {
+            bvh: Bvh::sah(&bounds),
+            positions,
+            triangles,
+        }
+    }
+
+    pub fn positions(&self) -> &[Vector3D] {
+        &self.positions
+    }
+
+    pub fn triangles(&self) -> &[[usize; 3]] {
+        &self.triangles
+    }
+
+    pub fn bvh(&self) -> &Bvh {
+        &self.bvh
+    }
+
+    pub fn triangle(&self, i: usize) -> [Vector3D; 3] {
+        self.triangles[i].map(|v| self.positions[v])
+    }
+
+    /// Moves the vertices of an animated mesh and refits the hierarchy.
+    ///
+    /// Panics if the vertex count changes.
+    pub fn set_positions(&mut self, positions: Vec<Vector3D>) {
+        assert_eq!(
+            positions.len(),
+            self.positions.len(),
+            "vertex count changed"
+        );
+
+        self.positions = positions;
+        self.bvh
+            .refit(&triangle_bounds(&self.positions, &self.triangles));
+    }
+
+    pub fn raycast(&self, ray: &Ray, t_max: f32) -> Option<MeshHit> {
+        let (triangle, _) = self.bvh.raycast(ray, 0.0, t_max, |i, limit| {
+            ray_triangle(ray, self.triangle(i), 0.0, limit).map(|hit| hit.t)
+        })?;
+        let hit = ray_triangle(ray, self.triangle(triangle), 0.0, t_max)?;
+
+        return Some(MeshHit {
+            triangle,
+            point: ray.at(hit.t),
+            hit,
+        });
+    }
+
+    /// The triangle nearest to `p`, the nearest point on it and the
+    /// distance.
+    pub fn closest_point(&self, p: Vector3D) -> Option<(usize, Vector3D, f32)> {
+        let (triangle, distance_squared) = self.bvh.nearest(p, |i| {
+            let q = closest_point_on_triangle(p, self.triangle(i));
+            (q - p).dot(&(q - p))
+        })?;
+
+        let point = closest_point_on_triangle(p, self.triangle(triangle));
+        return Some((triangle, point, distance_squared.sqrt()));
+    }
+}
+
+fn triangle_bounds(positions: &[Vector3D], triangles: &[[usize; 3]]) -> Vec<Aabb> {
+    triangles
+        .iter()
+        .map(|t| Aabb::from_points(&t.map(|v| positions[v])))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A `n` by `n` grid of unit quads in the plane `z = 0`.
+    fn grid(n: usize) -> MeshBvh {
+        let mut positions = Vec::new();
+        for y in 0..=n {
+            for x in 0..=n {
+                positions.push(Vector3D::create(x as f32, y as f32, 0.0));
+            }
+        }
+
+        let mut triangles = Vec::new();
+        for y in 0..n {
+            for x in 0..n {
+                let i = y * (n + 1) + x;
+                triangles.push([i, i + 1, i + n + 2]);
+                triangles.push([i, i + n + 2, i + n + 1]);
+            }
+        }
+
+        return MeshBvh::new(positions, triangles);
+    }
+
+    #[test]
+    fn ray_triangle_test() {
+        let triangle = [
+            Vector3D::create(0.0, 0.0, 0.0),
+            Vector3D::create(1.0, 0.0, 0.0),
+            Vector3D::create(0.0, 1.0, 0.0),
+        ];
+        let down = Vector3D::create(0.0, 0.0, -1.0);
+
+        let hit = ray_triangle(
+            &Ray::new(Vector3D::create(0.25, 0.5, 2.0), down),
+            triangle,
+            0.0,
+            f32::INFINITY,
+        )
+        .unwrap();
+        assert_eq!(
+            hit,
+            TriangleHit {
+                t: 2.0,
+                u: 0.25,
+                v: 0.5
+            }
+        );
+
+        // Back faces are hit too.
+        assert!(ray_triangle(
+            &Ray::new(Vector3D::create(0.25, 0.5, -2.0), -down),
+            triangle,
+            0.0,
+            f32::INFINITY
+        )
+        .is_some());
+        assert!(ray_triangle(
+            &Ray::new(Vector3D::create(0.75, 0.5, 2.0), down),
+            triangle,
+            0.0,
+            f32::INFINITY
+        )
+        .is_none());
+        assert!(ray_triangle(
+            &Ray::new(Vector3D::create(0.25, 0.5, 2.0), down),
+            triangle,
+            0.0,
+            1.0
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn closest_point_on_triangle_test() {
+        let triangle = [
+            Vector3D::create(0.0, 0.0, 0.0),
+            Vector3D::create(2.0, 0.0, 0.0),
+            Vector3D::create(0.0, 2.0, 0.0),
+        ];
+        let closest = |x, y, z| closest_point_on_triangle(Vector3D::create(x, y, z), triangle);
+
+        assert_eq!(closest(-1.0, -1.0, 0.0), triangle[0]);
+        assert_eq!(closest(3.0, -1.0, 0.0), triangle[1]);
+        assert_eq!(closest(0.0, 3.0, 1.0), triangle[2]);
+        assert_eq!(closest(1.0, -1.0, 0.0), Vector3D::create(1.0, 0.0, 0.0));
+        assert_eq!(closest(-1.0, 1.0, 0.0), Vector3D::create(0.0, 1.0, 0.0));
+        assert_eq!(closest(2.0, 2.0, 0.0), Vector3D::create(1.0, 1.0, 0.0));
+        assert_eq!(closest(0.5, 0.5, 3.0), Vector3D::create(0.5, 0.5, 0.0));
+    }
+
+    #[test]
+    fn raycast_test() {
+        let mesh = grid(20);
+        let ray = Ray::new(
+            Vector3D::create(3.25, 7.75, 10.0),
+            Vector3D::create(0.0, 0.0, -1.0),
+        );
+
+        let hit = mesh.raycast(&ray, f32::INFINITY).unwrap();
+        assert_eq!(hit.point, Vector3D::create(3.25, 7.75, 0.0));
+        assert_eq!(hit.hit.t, 10.0);
+        assert_eq!(hit.triangle, 2 * (7 * 20 + 3) + 1);
+
+        assert!(mesh.raycast(&ray, 5.0).is_none());
+        let outside = Ray::new(Vector3D::create(30.0, 0.0, 10.0), ray.direction);
+        assert!(mesh.raycast(&outside, f32::INFINITY).is_none());
+    }
+
+    #[test]
+    fn closest_point_and_refit_test() {
+        let mut mesh = grid(10);
+
+        let (_, point, distance) = mesh
+            .closest_point(Vector3D::create(12.0, 5.0, 0.0))
+            .unwrap();
+        assert_eq!(point, Vector3D::create(10.0, 5.0, 0.0));
+        assert_eq!(distance, 2.0);
+
+        // Lift the mesh; the refitted hierarchy must find it at its new
+        // height.
+        let lifted = mesh
+            .positions()
+            .iter()
+            .map(|p| *p + Vector3D::create(0.0, 0.0, 4.0))
+            .collect();
+        mesh.set_positions(lifted);
+
+        let ray = Ray::new(
+            Vector3D::create(5.5, 5.5, 10.0),
+            Vector3D::create(0.0, 0.0, -1.0),
+        );
+        assert_eq!(mesh.raycast(&ray, f32::INFINITY).unwrap().hit.t, 6.0);
+        let (_, _, distance) = mesh.closest_point(Vector3D::create(5.0, 5.0, 0.0)).unwrap();
+        assert_eq!(distance, 4.0);
+    }
+}
@@ -0,0 +1,34 @@
+//! Spatial acceleration structures.
+//!
+//! [`Bvh`] organizes boxes into a bounding volume hierarchy for ray,
+//! overlap, frustum and nearest-point queries; [`MeshBvh`] applies it to
+//! indexed triangle meshes.
+//!
+//! For points, [`UniformGrid`], [`Quadtree`], [`Octree`] and [`KdTree`]
+//! implement [`SpatialIndex`]: insert, remove and move items keyed by a
+//! [`Vector2D`](crate::math::Vector2D) or [`Vector3D`](crate::math::Vector3D)
+//! position, with range, radius and k-nearest-neighbour queries.
+
+#![allow(clippy::needless_return)]
+
+pub mod aabb;
+pub mod bvh;
+pub mod frustum;
+pub mod grid;
+pub mod index;
+pub mod kdtree;
+pub mod mesh;
+pub mod orthtree;
+pub mod point;
+pub mod ray;
+
+pub use aabb::Aabb;
+pub use bvh::Bvh;
+pub use frustum::{Frustum, Intersection, Plane, PlaneMask, ALL_PLANES};
+pub use grid::UniformGrid;
+pub use index::{Handle, SpatialIndex};
+pub use kdtree::KdTree;
+pub use mesh::{closest_point_on_triangle, ray_triangle, MeshBvh, MeshHit, TriangleHit};
+pub use orthtree::{Octree, Orthtree, Quadtree};
+pub use point::Point;
+pub use ray::Ray;
@@ -0,0 +1,422 @@
+use std::collections::BinaryHeap;
+
+use crate::math::{Vector2D, Vector3D};
+
+use super::{
+    index::{Distance, KBest, Slab},
+    point::{box_distance_squared, in_box, sphere_box},
+    Handle, Point, SpatialIndex,
+};
+
+/// Items a leaf holds before it splits.
+const CAPACITY: usize = 8;
+/// Splitting stops at this depth, so coincident points cannot recurse
+/// forever.
+const MAX_DEPTH: usize = 24;
+
+/// A tree over points whose nodes split into `2^DIM` equal boxes: a
+/// quadtree in 2D and an octree in 3D.
+pub type Quadtree<T> = Orthtree<Vector2D, T>;
+pub type Octree<T> = Orthtree<Vector3D, T>;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Node<P> {
+    min: P,
+    max: P,
+    /// Only leaves hold items.
+    items: Vec<Handle>,
+    /// Index of the first of `2^DIM` consecutive children.
+    children: Option<usize>,
+}
+
+impl<P: Point> Node<P> {
+    fn new(min: P, max: P) -> Self {
+        Self {
+            min,
+            max,
+            items: Vec::new(),
+            children: None,
+        }
+    }
+
+    /// Which child of this node `p` falls in: bit `i` is set for the upper
+    /// half along axis `i`.
+    fn octant(&self, p: &P) -> usize {
+        let mut octant = 0;
+        for i in 0..P::DIM {
+            if p.coord(i) >= (self.min.coord(i) + self.max.coord(i)) * 0.5 {
+                octant |= 1 << i;
+            }
+        }
+
+        return octant;
+    }
+
+    fn child_bounds(&self, octant: usize) -> (P, P) {
+        let (mut min, mut max) = ([0.0; 3], [0.0; 3]);
+        for i in 0..P::DIM {
+            let (lo, hi) = (self.min.coord(i), self.max.coord(i));
+            let mid = (lo + hi) * 0.5;
+            (min[i], max[i]) = if octant & (1 << i) != 0 {
+                (mid, hi)
+            } else {
+                (lo, mid)
+            };
+        }
+
+        return (P::from_coords(&min), P::from_coords(&max));
+    }
+}
+
+/// A region tree that splits full leaves into `2^DIM` children and merges
+/// them back once they empty out. Inserting outside the bounds grows the
+/// root.
+///
+/// Adapts to clustered data better than a [`UniformGrid`](super::UniformGrid).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Orthtree<P, T> {
+    /// The root is always node 0.
+    nodes: Vec<Node<P>>,
+    /// Unused blocks of children.
+    free: Vec<usize>,
+    items: Slab<P, T>,
+}
+
+impl<P: Point, T> Orthtree<P, T> {
+    /// An empty tree covering the box `[min, max]`.
+    pub fn new(min: P, max: P) -> Self {
+        assert!(
+            (0..P::DIM).all(|i| min.coord(i) < max.coord(i)),
+            "tree bounds must have positive size"
+        );
+
+        Self {
+            nodes: vec![Node::new(min, max)],
+            free: Vec::new(),
+            items: Slab::default(),
+        }
+    }
+
+    /// The box the root currently covers.
+    pub fn bounds(&self) -> (P, P) {
+        (self.nodes[0].min, self.nodes[0].max)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Handle, &P, &T)> {
+        self.items.iter()
+    }
+
+    /// Levels from the root to the deepest leaf.
+    pub fn depth(&self) -> usize {
+        let mut deepest = 0;
+        let mut stack = vec![(0, 1)];
+        while let Some((node, depth)) = stack.pop() {
+            deepest = deepest.max(depth);
+            if let Some(first) = self.nodes[node].children {
+                stack.extend((first..first + (1 << P::DIM)).map(|c| (c, depth + 1)));
+            }
+        }
+
+        return deepest;
+    }
+
+    /// Doubles the root toward `p` until it contains it.
+    fn grow(&mut self, p: &P) {
+        assert!(
+            (0..P::DIM).all(|i| p.coord(i).is_finite()),
+            "position must be finite"
+        );
+
+        while !in_box(p, &self.nodes[0].min, &self.nodes[0].max) {
+            let root = &self.nodes[0];
+            let (mut min, mut max) = ([0.0; 3], [0.0; 3]);
+            let mut octant = 0;
+            for i in 0..P::DIM {
+                let (lo, hi) = (root.min.coord(i), root.max.coord(i));
+                if p.coord(i) < lo {
+                    // The old root becomes the upper half on this axis.
+                    (min[i], max[i]) = (lo - (hi - lo), hi);
+                    octant |= 1 << i;
+                } else {
+                    (min[i], max[i]) = (lo, hi + (hi - lo));
+                }
+            }
+
+            let mut parent = Node::new(P::from_coords(&min), P::from_coords(&max));
+            let first = self.allocate_children(&parent);
+            parent.children = Some(first);
+            self.nodes[first + octant] = std::mem::replace(&mut self.nodes[0], parent);
+        }
+    }
+
+    fn allocate_children(&mut self, parent: &Node<P>) -> usize {
+        let children = (0..1 << P::DIM).map(|o| {
+            let (min, max) = parent.child_bounds(o);
+            Node::new(min, max)
+        });
+
+        if let Some(first) = self.free.pop() {
+            for (o, child) in children.enumerate() {
+                self.nodes[first + o] = child;
+            }
+            return first;
+        }
+
+        let first = self.nodes.len();
+        self.nodes.extend(children);
+        return first;
+    }
+
+    /// The leaf containing `p` and the nodes above it, root first.
+    fn path(&self, p: &P) -> Vec<usize> {
+        let mut path = vec![0];
+        while let Some(first) = self.nodes[*path.last().unwrap()].children {
+            let node = *path.last().unwrap();
+            path.push(first + self.nodes[node].octant(p));
+        }
+
+        return path;
+    }
+
+    fn link(&mut self, handle: Handle, p: &P) {
+        self.grow(p);
+        let path = self.path(p);
+        let leaf = *path.last().unwrap();
+        self.nodes[leaf].items.push(handle);
+
+        if self.nodes[leaf].items.len() > CAPACITY && path.len() < MAX_DEPTH {
+            let parent = Node::new(self.nodes[leaf].min, self.nodes[leaf].max);
+            let first = self.allocate_children(&parent);
+            let items = std::mem::take(&mut self.nodes[leaf].items);
+            self.nodes[leaf].children = Some(first);
+            // Redistribute; a child that overflows again splits on its
+            // next insertion.
+            for h in items {
+                let octant = self.nodes[leaf].octant(&self.items.position(h));
+                self.nodes[first + octant].items.push(h);
+            }
+        }
+    }
+
+    fn unlink(&mut self, handle: Handle, p: &P) {
+        let path = self.path(p);
+        let leaf = *path.last().unwrap();
+        let items = &mut self.nodes[leaf].items;
+        let i = items
+            .iter()
+            .position(|&h| h == handle)
+            .expect("item is in its leaf");
+        items.swap_remove(i);
+
+        // Merge parents whose children are leaves that fit in one node.
+        for &node in path.iter().rev().skip(1) {
+            let first = self.nodes[node].children.unwrap();
+            let children = first..first + (1 << P::DIM);
+            if children.clone().any(|c| self.nodes[c].children.is_some()) {
+                break;
+            }
+            let total: usize = children.clone().map(|c| self.nodes[c].items.len()).sum();
+            if total > CAPACITY {
+                break;
+            }
+
+            let mut merged = Vec::with_capacity(total);
+            for c in children {
+                merged.append(&mut self.nodes[c].items);
+            }
+            self.nodes[node].items = merged;
+            self.nodes[node].children = None;
+            self.free.push(first);
+        }
+    }
+}
+
+impl<P: Point, T> SpatialIndex<P, T> for Orthtree<P, T> {
+    fn insert(&mut self, position: P, value: T) -> Handle {
+        let handle = self.items.insert(position, value);
+        self.link(handle, &position);
+
+        return handle;
+    }
+
+    fn remove(&mut self, handle: Handle) -> Option<(P, T)> {
+        let position = self.items.get(handle)?.0;
+        self.unlink(handle, &position);
+
+        return self.items.remove(handle);
+    }
+
+    fn move_to(&mut self, handle: Handle, position: P) -> bool {
+        let Some(&(old, _)) = self.items.get(handle) else {
+            return false;
+        };
+
+        let leaf = *self.path(&old).last().unwrap();
+        if in_box(&position, &self.nodes[0].min, &self.nodes[0].max)
+            && *self.path(&position).last().unwrap() == leaf
+        {
+            self.items.get_mut(handle).unwrap().0 = position;
+            return true;
+        }
+
+        self.unlink(handle, &old);
+        self.items.get_mut(handle).unwrap().0 = position;
+        self.link(handle, &position);
+
+        return true;
+    }
+
+    fn get(&self, handle: Handle) -> Option<(&P, &T)> {
+        self.items.get(handle).map(|(p, t)| (p, t))
+    }
+
+    fn get_mut(&mut self, handle: Handle) -> Option<&mut T> {
+        self.items.get_mut(handle).map(|(_, t)| t)
+    }
+
+    fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    fn range(&self, min: &P, max: &P) -> Vec<Handle> {
+        let mut found = Vec::new();
+        let mut stack = vec![0];
+
+        while let Some(node) = stack.pop() {
+            let n = &self.nodes[node];
+            if (0..P::DIM).any(|i| n.max.coord(i) < min.coord(i) || max.coord(i) < n.min.coord(i)) {
+                continue;
+            }
+
+            match n.children {
+                Some(first) => stack.extend(first..first + (1 << P::DIM)),
+                None => found.extend(
+                    n.items
+                        .iter()
+                        .filter(|&&h| in_box(&self.items.position(h), min, max)),
+                ),
+            }
+        }
+
+        return found;
+    }
+
+    fn within_radius(&self, center: &P, radius: f32) -> Vec<Handle> {
+        let (min, max) = sphere_box(center, radius);
+        let mut found = self.range(&min, &max);
+        found.retain(|&h| self.items.position(h).distance_squared(center) <= radius * radius);
+
+        return found;
+    }
+
+    fn k_nearest(&self, point: &P, k: usize) -> Vec<Handle> {
+        let mut best = KBest::new(k);
+        let mut queue = BinaryHeap::new();
+        queue.push((Distance(-0.0), 0));
+
+        // Best-first: nodes come out nearest first, as negated distances.
+        while let Some((Distance(negated), node)) = queue.pop() {
+            if -negated >= best.worst() {
+                break;
+            }
+
+            let n = &self.nodes[node];
+            match n.children {
+                Some(first) => {
+                    for c in first..first + (1 << P::DIM) {
+                        let child = &self.nodes[c];
+                        let d = box_distance_squared(point, &child.min, &child.max);
+                        queue.push((Distance(-d), c));
+                    }
+                }
+                None => {
+                    for &h in &n.items {
+                        best.offer(self.items.position(h).distance_squared(point), h);
+                    }
+                }
+            }
+        }
+
+        return best.into_sorted();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::spatial::index::conformance;
+
+    #[test]
+    fn conformance_test() {
+        // Bounds smaller than the data exercise growing the root.
+        let quadtree = Quadtree::new(Vector2D::create(0.0, 0.0), Vector2D::create(10.0, 10.0));
+        conformance::check(quadtree, 38, |rng| {
+            Vector2D::create(rng.gen_range(-50.0..50.0), rng.gen_range(-50.0..50.0))
+        });
+
+        let octree = Octree::new(
+            Vector3D::create(-20.0, -20.0, -20.0),
+            Vector3D::create(20.0, 20.0, 20.0),
+        );
+        conformance::check(octree, 380, |rng| {
+            Vector3D::create(
+                rng.gen_range(-20.0..20.0),
+                rng.gen_range(-20.0..20.0),
+                rng.gen_range(-20.0..20.0),
+            )
+        });
+    }
+
+    #[test]
+    fn split_and_merge_test() {
+        let mut tree = Quadtree::new(Vector2D::create(0.0, 0.0), Vector2D::create(16.0, 16.0));
+        let handles: Vec<Handle> = (0..64)
+            .map(|i| {
+                tree.insert(
+                    Vector2D::create((i % 8) as f32 * 2.0 + 0.5, (i / 8) as f32 * 2.0 + 0.5),
+                    i,
+                )
+            })
+            .collect();
+        assert!(tree.depth() >= 3);
+
+        for &h in &handles[..60] {
+            tree.remove(h);
+        }
+        assert_eq!(tree.depth(), 1);
+        assert_eq!(tree.len(), 4);
+    }
+
+    #[test]
+    fn grow_test() {
+        let mut tree = Quadtree::new(Vector2D::create(0.0, 0.0), Vector2D::create(1.0, 1.0));
+        let far = tree.insert(Vector2D::create(-5.0, 3.0), ());
+
+        let (min, max) = tree.bounds();
+        assert_eq!(
+            (min, max),
+            (Vector2D::create(-7.0, 0.0), Vector2D::create(1.0, 8.0))
+        );
+        assert_eq!(
+            tree.range(&Vector2D::create(-6.0, 2.0), &Vector2D::create(-4.0, 4.0)),
+            [far]
+        );
+    }
+
+    #[test]
+    fn coincident_points_test() {
+        let mut tree = Octree::new(Vector3D::default(), Vector3D::create(1.0, 1.0, 1.0));
+        for i in 0..100 {
+            tree.insert(Vector3D::create(0.25, 0.25, 0.25), i);
+        }
+
+        assert!(tree.depth() <= MAX_DEPTH);
+        assert_eq!(
+            tree.within_radius(&Vector3D::create(0.25, 0.25, 0.25), 0.0)
+                .len(),
+            100
+        );
+    }
+}
@@ -0,0 +1,79 @@
+use crate::math::{Vector2D, Vector3D};
+
+/// A position in 2 or 3 dimensions, so the spatial containers can be
+/// written once for [`Vector2D`] and [`Vector3D`].
+pub trait Point: Copy + PartialEq {
+    const DIM: usize;
+
+    /// Coordinate `axis`, from 0 to `DIM - 1`.
+    fn coord(&self, axis: usize) -> f32;
+
+    /// Builds a point from its first `DIM` coordinates.
+    fn from_coords(coords: &[f32]) -> Self;
+
+    fn distance_squared(&self, other: &Self) -> f32 {
+        (0..Self::DIM)
+            .map(|i| (self.coord(i) - other.coord(i)).powi(2))
+            .sum()
+    }
+}
+
+impl Point for Vector2D {
+    const DIM: usize = 2;
+
+    fn coord(&self, axis: usize) -> f32 {
+        if axis == 0 {
+            self.x()
+        } else {
+            self.y()
+        }
+    }
+
+    fn from_coords(coords: &[f32]) -> Self {
+        Vector2D::create(coords[0], coords[1])
+    }
+}
+
+impl Point for Vector3D {
+    const DIM: usize = 3;
+
+    fn coord(&self, axis: usize) -> f32 {
+        match axis {
+            0 => self.x(),
+            1 => self.y(),
+            _ => self.z(),
+        }
+    }
+
+    fn from_coords(coords: &[f32]) -> Self {
+        Vector3D::create(coords[0], coords[1], coords[2])
+    }
+}
+
+/// Whether `p` lies in the box `[min, max]`, boundaries included.
+pub(crate) fn in_box<P: Point>(p: &P, min: &P, max: &P) -> bool {
+    (0..P::DIM).all(|i| min.coord(i) <= p.coord(i) && p.coord(i) <= max.coord(i))
+}
+
+/// Squared distance from `p` to the box `[min, max]`; zero inside.
+pub(crate) fn box_distance_squared<P: Point>(p: &P, min: &P, max: &P) -> f32 {
+    (0..P::DIM)
+        .map(|i| {
+            let c = p.coord(i);
+            let d = (min.coord(i) - c).max(c - max.coord(i)).max(0.0);
+            d * d
+        })
+        .sum()
+}
+
+/// The axis-aligned box around the sphere `center`, `radius`.
+pub(crate) fn sphere_box<P: Point>(center: &P, radius: f32) -> (P, P) {
+    let mut min = [0.0; 3];
+    let mut max = [0.0; 3];
+    for i in 0..P::DIM {
+        min[i] = center.coord(i) - radius;
+        max[i] = center.coord(i) + radius;
+    }
+
+    return (P::from_coords(&min), P::from_coords(&max));
+}
@@ -0,0 +1,19 @@
+use crate::math::Vector3D;
+
+/// A half-line starting at `origin`. `direction` need not be normalized;
+/// distances along the ray are measured in multiples of it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: Vector3D,
+    pub direction: Vector3D,
+}
+
+impl Ray {
+    pub fn new(origin: Vector3D, direction: Vector3D) -> Self {
+        Self { origin, direction }
+    }
+
+    pub fn at(&self, t: f32) -> Vector3D {
+        self.origin + self.direction * t
+    }
+}